@@ -0,0 +1,265 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Recording of the writes made in dry-run mode into a [`WriteBatch`], which can be exported
+//! and published later on, possibly from another machine, with [`Safe::publish_write_batch`].
+
+use crate::{Error, Result, Safe};
+
+use sn_client::{sign_register_create, sign_register_policy_edit, sign_register_write, WriteBatch};
+use sn_interface::types::{
+    register::{Entry, EntryHash, Policy, PolicyEdit, Register, User},
+    Chunk, Keypair, RegisterAddress,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+use tracing::debug;
+use xor_name::XorName;
+
+// The batch being recorded, along with the key its Register operations are signed with,
+// and the local replicas of the Registers written to, which the operations are applied to.
+pub(crate) struct BatchRecorder {
+    keypair: Keypair,
+    batch: WriteBatch,
+    registers: BTreeMap<RegisterAddress, Register>,
+}
+
+pub(crate) type SharedBatchRecorder = Arc<Mutex<BatchRecorder>>;
+
+impl Safe {
+    /// Start recording the writes made by this instance into a [`WriteBatch`], signing the
+    /// Register operations with the given keypair, which will then own the Registers created.
+    ///
+    /// This sets the instance in dry-run mode, thus nothing is sent to the network until the
+    /// batch returned by [`Safe::take_write_batch`] is published. Registers which are not
+    /// created within the batch are retrieved from the network when written to, if connected.
+    pub fn start_write_batch(&mut self, keypair: Keypair) {
+        debug!(
+            "Recording writes into a batch, signed by {}",
+            keypair.public_key()
+        );
+        self.dry_run_mode = true;
+        self.write_batch = Some(Arc::new(Mutex::new(BatchRecorder {
+            keypair,
+            batch: WriteBatch::default(),
+            registers: BTreeMap::new(),
+        })));
+    }
+
+    /// Stop recording writes, returning the batch recorded since [`Safe::start_write_batch`].
+    pub fn take_write_batch(&mut self) -> Option<WriteBatch> {
+        let recorder = self.write_batch.take()?;
+        let batch = lock(&recorder).batch.clone();
+        Some(batch)
+    }
+
+    /// Publish a batch of writes, e.g. one exported from a dry run, to the network.
+    pub async fn publish_write_batch(&self, batch: WriteBatch) -> Result<()> {
+        let client = self.get_safe_client()?;
+        client.publish_batch(batch).await?;
+        Ok(())
+    }
+
+    // Returns true if the writes are being recorded into a batch
+    pub(crate) fn is_recording_batch(&self) -> bool {
+        self.write_batch.is_some()
+    }
+
+    // Record the chunks in the batch being recorded, if any
+    pub(crate) fn record_chunks(&self, chunks: Vec<Chunk>) {
+        if let Some(recorder) = &self.write_batch {
+            lock(recorder).batch.chunks.extend(chunks);
+        }
+    }
+
+    // Record the creation of a Register, owned by the key of the batch,
+    // with the policy built out of the owner by the given function
+    pub(crate) fn record_register_create(
+        &self,
+        name: XorName,
+        tag: u64,
+        policy: impl FnOnce(User) -> Policy,
+    ) -> Result<()> {
+        let recorder = match &self.write_batch {
+            Some(recorder) => recorder,
+            None => return Ok(()),
+        };
+
+        let mut recorder = lock(recorder);
+        let owner = User::Key(recorder.keypair.public_key());
+        let policy = policy(owner);
+        let cmd = sign_register_create(&recorder.keypair, name, tag, policy.clone())?;
+
+        let register = Register::new(owner, name, tag, policy);
+        let _ = recorder.registers.insert(*register.address(), register);
+        recorder.batch.register_ops.push(cmd);
+
+        Ok(())
+    }
+
+    // Record a write to a Register, returning the hash of the entry written
+    pub(crate) async fn record_register_write(
+        &self,
+        address: RegisterAddress,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<EntryHash> {
+        let recorder = self.recorded_or_fetched_register(address).await?;
+
+        let mut recorder = lock(recorder);
+        let BatchRecorder {
+            keypair,
+            batch,
+            registers,
+        } = &mut *recorder;
+        let register = registers
+            .get_mut(&address)
+            .ok_or_else(|| Error::ContentNotFound(format!("No Register found at {address:?}")))?;
+        let (hash, cmd) = sign_register_write(keypair, register, entry, children)?;
+        batch.register_ops.push(cmd);
+
+        Ok(hash)
+    }
+
+    // Record an edit to the policy of a Register
+    pub(crate) async fn record_register_policy_edit(
+        &self,
+        address: RegisterAddress,
+        edit: PolicyEdit,
+    ) -> Result<()> {
+        let recorder = self.recorded_or_fetched_register(address).await?;
+
+        let mut recorder = lock(recorder);
+        let BatchRecorder {
+            keypair,
+            batch,
+            registers,
+        } = &mut *recorder;
+        let register = registers
+            .get_mut(&address)
+            .ok_or_else(|| Error::ContentNotFound(format!("No Register found at {address:?}")))?;
+        let cmd = sign_register_policy_edit(keypair, register, edit)?;
+        batch.register_ops.push(cmd);
+
+        Ok(())
+    }
+
+    // Returns the batch being recorded, having the local replica of the Register at the address.
+    // Registers which were not created within the batch are retrieved from the network.
+    async fn recorded_or_fetched_register(
+        &self,
+        address: RegisterAddress,
+    ) -> Result<&SharedBatchRecorder> {
+        let recorder = self.write_batch.as_ref().ok_or_else(|| {
+            Error::InvalidInput("Writes are not being recorded into a batch".to_string())
+        })?;
+
+        if !lock(recorder).registers.contains_key(&address) {
+            let client = self.get_safe_client()?;
+            let register = client.get_register(address).await?;
+            let _ = lock(recorder).registers.entry(address).or_insert(register);
+        }
+
+        Ok(recorder)
+    }
+
+    // Returns the local replica of a Register written to within the batch being recorded, if any
+    pub(crate) fn recorded_register(&self, address: &RegisterAddress) -> Option<Register> {
+        let recorder = self.write_batch.as_ref()?;
+        let register = lock(recorder).registers.get(address).cloned();
+        register
+    }
+}
+
+// The lock is only ever held while mutating the recorder in memory, thus it can
+// only be poisoned by a panic while doing so, in which case the batch is still usable.
+fn lock(recorder: &SharedBatchRecorder) -> std::sync::MutexGuard<'_, BatchRecorder> {
+    recorder
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentType, Safe, SafeUrl};
+
+    use anyhow::Result;
+    use bytes::Bytes;
+    use sn_client::WriteBatch;
+    use sn_interface::{
+        messaging::data::{DataCmd, RegisterCmd},
+        types::{
+            register::{Permissions, User},
+            Keypair,
+        },
+    };
+    use std::collections::BTreeSet;
+
+    #[tokio::test]
+    async fn dry_run_writes_are_recorded_into_a_batch() -> Result<()> {
+        let mut safe = Safe::dry_runner(None);
+        safe.start_write_batch(Keypair::new_ed25519());
+
+        let data = Bytes::from_static(b"some data to store");
+        let _ = safe.store_bytes(data, None).await?;
+
+        let reg_url = safe.register_create(None, 15_000, ContentType::Raw).await?;
+        let hash = safe
+            .register_write(&reg_url, b"first entry".to_vec(), BTreeSet::new())
+            .await?;
+
+        // the entry written can be read back from the local replica
+        let entries = safe.register_read(&reg_url).await?;
+        assert_eq!(entries, BTreeSet::from([(hash, b"first entry".to_vec())]));
+
+        let batch = safe.take_write_batch().expect("a batch was being recorded");
+        assert!(!safe.is_recording_batch());
+        assert_eq!(batch.chunks.len(), 1);
+        assert_eq!(batch.register_ops.len(), 2);
+
+        let address = safe.get_register_address(&SafeUrl::from_url(&reg_url)?)?;
+        assert!(safe.recorded_register(&address).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dry_run_policy_edits_are_recorded_into_a_batch() -> Result<()> {
+        let mut safe = Safe::dry_runner(None);
+        safe.start_write_batch(Keypair::new_ed25519());
+
+        let reg_url = safe.register_create(None, 15_000, ContentType::Raw).await?;
+        let user = User::Key(Keypair::new_ed25519().public_key());
+        safe.register_grant(&reg_url, user, Permissions::new(true))
+            .await?;
+
+        // the edit is applied to the local replica
+        let address = safe.get_register_address(&SafeUrl::from_url(&reg_url)?)?;
+        let register = safe
+            .recorded_register(&address)
+            .expect("the Register was created within the batch");
+        assert_eq!(register.permissions(user)?, Permissions::new(true));
+
+        // and the signed edit is kept when the batch is exported and loaded back
+        let batch = safe.take_write_batch().expect("a batch was being recorded");
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let path = tmp_dir.path().join("batch");
+        batch.save(&path)?;
+        let loaded = WriteBatch::load(&path)?;
+        assert_eq!(loaded, batch);
+        assert_eq!(loaded.register_ops.len(), 2);
+        assert!(matches!(
+            &loaded.register_ops[1],
+            DataCmd::Register(RegisterCmd::EditPolicy(edit)) if edit.op.address == address
+        ));
+
+        Ok(())
+    }
+}
@@ -12,6 +12,8 @@ use crate::safeurl::XorUrlBase;
 pub const DEFAULT_XORURL_BASE: XorUrlBase = XorUrlBase::Base32z;
 
 pub const PREDICATE_LINK: &str = "link";
+// The XOR-URL a private file's content had before being encrypted
+pub const PREDICATE_CONTENT_LINK: &str = "content_link";
 pub const PREDICATE_TYPE: &str = "type";
 pub const PREDICATE_SIZE: &str = "size";
 pub const PREDICATE_MODIFIED: &str = "modified";
@@ -8,7 +8,7 @@
 
 use super::{metadata::get_metadata, FilesMapChange, ProcessedFiles};
 
-use crate::{Error, Result, Safe, XorUrl};
+use crate::{ContentType, Error, Result, Safe, SafeUrl, XorUrl};
 
 use sn_client::Error as ClientError;
 
@@ -17,26 +17,26 @@ use std::{
     fs,
     path::{Path, PathBuf},
 };
+use tokio::task;
 use tracing::info;
 use walkdir::{DirEntry, WalkDir};
 
 const MAX_RECURSIVE_DEPTH: usize = 10_000;
 
 // Upload a file to the Network
+// The file is streamed from disk, thus it's never read into memory as a whole.
 pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
-        Error::InvalidInput(format!("Failed to read file from local location: {err}"))
-    })?;
-    let data = Bytes::from(data);
-
     let mut mime_type_for_xorurl = mime_guess::from_path(path).first_raw();
-    let result = match safe.store_bytes(data.clone(), mime_type_for_xorurl).await {
+    let result = match safe.store_from_path(path, mime_type_for_xorurl).await {
         Ok(xorurl) => Ok(xorurl),
         Err(Error::InvalidMediaType(_)) => {
             // Let's then upload it and set media-type to be simply raw content
             mime_type_for_xorurl = None;
-            safe.store_bytes(data.clone(), mime_type_for_xorurl).await
+            safe.store_from_path(path, mime_type_for_xorurl).await
         }
+        Err(Error::ClientError(ClientError::IoError(err))) => Err(Error::InvalidInput(format!(
+            "Failed to read file from local location: {err}"
+        ))),
         other_err => other_err,
     };
 
@@ -46,7 +46,9 @@ pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUr
         // Let's obtain the xorurl with using dry-run mode.
         // Use a dry runner only for this next operation
         let dry_runner = Safe::dry_runner(Some(safe.xorurl_base));
-        let xorurl = dry_runner.store_bytes(data, mime_type_for_xorurl).await?;
+        let xorurl = dry_runner
+            .store_from_path(path, mime_type_for_xorurl)
+            .await?;
 
         Err(Error::ContentUploadVerificationFailed(xorurl))
     } else {
@@ -54,6 +56,35 @@ pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUr
     }
 }
 
+// Upload a file to the Network encrypted to the readers of private data, returning its XOR-URL
+// along with the one its content has when not encrypted, to later tell if the local file changed.
+// Unlike public files, the file is read into memory as a whole, to encrypt it at once.
+pub(crate) async fn upload_private_file_to_net(
+    safe: &Safe,
+    path: &Path,
+) -> Result<(XorUrl, XorUrl)> {
+    let dry_runner = Safe::dry_runner(Some(safe.xorurl_base));
+    let content_xorurl = upload_file_to_net(&dry_runner, path).await?;
+    let media_type = SafeUrl::from_url(&content_xorurl)?.content_type();
+
+    let file_path = path.to_path_buf();
+    let data = task::spawn_blocking(move || fs::read(file_path))
+        .await
+        .map_err(|err| Error::InvalidInput(format!("Failed to read file: {err}")))?
+        .map_err(|err| {
+            Error::InvalidInput(format!("Failed to read file from local location: {err}"))
+        })?;
+    let media_type = match &media_type {
+        ContentType::MediaType(media_type) => Some(media_type.as_str()),
+        _ => None,
+    };
+    let xorurl = safe
+        .store_private_bytes(Bytes::from(data), media_type)
+        .await?;
+
+    Ok((xorurl, content_xorurl))
+}
+
 // Simply change Windows style path separator into `/`
 pub(crate) fn normalise_path_separator(from: &str) -> String {
     str::replace(from, "\\", "/")
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    file_system::{normalise_path_separator, upload_file_to_net},
+    file_system::{normalise_path_separator, upload_file_to_net, upload_private_file_to_net},
     metadata::FileMeta,
     ProcessedFiles, RealPath,
 };
@@ -99,9 +99,10 @@ pub(crate) async fn add_or_update_file_item(
     name_exists: bool,
     files_map: &mut FilesMap,
     processed_files: &mut ProcessedFiles,
+    private: bool,
 ) -> bool {
     // We need to add a new FileInfo, let's generate the FileInfo first
-    match gen_new_file_item(safe, file_path, file_meta, file_link).await {
+    match gen_new_file_item(safe, file_path, file_meta, file_link, private).await {
         Ok(new_file_item) => {
             // note: files have link property, dirs and symlinks do not
             let xorurl = new_file_item
@@ -135,16 +136,23 @@ pub(crate) async fn add_or_update_file_item(
     }
 }
 
-// Generate a FileInfo for a file which can then be added to a FilesMap
+// Generate a FileInfo for a file which can then be added to a FilesMap,
+// uploading the file encrypted to the readers of private data if it's private
 async fn gen_new_file_item(
     safe: &Safe,
     file_path: &Path,
     file_meta: &FileMeta,
     link: Option<&str>, // must be symlink target or None if FileMeta::is_symlink() is true.
+    private: bool,
 ) -> Result<FileInfo> {
     let mut file_item = file_meta.to_file_item();
     if file_meta.is_file() {
         let xorurl = match link {
+            None if private => {
+                let (xorurl, content_xorurl) = upload_private_file_to_net(safe, file_path).await?;
+                file_item.insert(PREDICATE_CONTENT_LINK.to_string(), content_xorurl);
+                xorurl
+            }
             None => upload_file_to_net(safe, file_path).await?,
             Some(link) => link.to_string(),
         };
@@ -0,0 +1,285 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{FileInfo, FilesMap};
+
+use crate::{app::nrs::VersionHash, Error, Result, Safe};
+
+use serde::{Deserialize, Serialize};
+use sn_interface::types::register::EntryHash;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str,
+};
+use tracing::debug;
+
+/// A version of a `FilesContainer`, as found when walking back its history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilesContainerVersion {
+    /// The version, i.e. the hash of the Register entry it was written as.
+    pub version: VersionHash,
+    /// The versions this one succeeds. There is more than one if it merged concurrent versions,
+    /// and none if it's the first version of the `FilesContainer`.
+    pub previous: BTreeSet<VersionHash>,
+    /// The `FilesMap` of this version.
+    pub files_map: FilesMap,
+}
+
+/// The changes between two `FilesMap`s, by path.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilesMapDiff {
+    /// Items only found in the newer `FilesMap`.
+    pub added: FilesMap,
+    /// Items found in both `FilesMap`s but with a different `FileInfo`, older one first.
+    pub updated: BTreeMap<String, (FileInfo, FileInfo)>,
+    /// Items only found in the older `FilesMap`.
+    pub removed: FilesMap,
+}
+
+impl FilesMapDiff {
+    /// Compare an older `FilesMap` with a newer one.
+    pub fn new(from: &FilesMap, to: &FilesMap) -> Self {
+        let mut diff = Self::default();
+        for (path, to_info) in to {
+            match from.get(path) {
+                None => {
+                    let _ = diff.added.insert(path.clone(), to_info.clone());
+                }
+                Some(from_info) if from_info != to_info => {
+                    let _ = diff
+                        .updated
+                        .insert(path.clone(), (from_info.clone(), to_info.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for (path, from_info) in from {
+            if !to.contains_key(path) {
+                let _ = diff.removed.insert(path.clone(), from_info.clone());
+            }
+        }
+        diff
+    }
+
+    /// Returns true if both `FilesMap`s have the same items.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Safe {
+    /// # List all the versions of a `FilesContainer`.
+    ///
+    /// The history of the `FilesContainer` is walked back from its latest versions, thus each
+    /// version is listed before those it succeeds.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   let safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let versions = safe.files_container_versions(&xorurl).await.unwrap();
+    ///     for version in versions {
+    ///         println!("Version {} has {} files", version.version, version.files_map.len());
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_versions(&self, url: &str) -> Result<Vec<FilesContainerVersion>> {
+        debug!("Listing versions of FilesContainer at: {:?}", url);
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+        safe_url.set_content_version(None);
+        let register = self.fetch_register(&safe_url).await?;
+
+        // Collect the whole history, counting the successors of each entry
+        let heads: Vec<EntryHash> = register.read().into_iter().map(|(hash, _)| hash).collect();
+        let mut children_of = BTreeMap::new();
+        let mut successors_count = BTreeMap::<EntryHash, usize>::new();
+        let mut to_visit = heads.clone();
+        while let Some(hash) = to_visit.pop() {
+            if children_of.contains_key(&hash) {
+                continue;
+            }
+            let children = register.children(hash)?;
+            for child in &children {
+                *successors_count.entry(*child).or_default() += 1;
+                to_visit.push(*child);
+            }
+            let _ = children_of.insert(hash, children);
+        }
+
+        // List each entry once all of its successors were listed
+        let mut versions = vec![];
+        let mut ready = heads;
+        while let Some(hash) = ready.pop() {
+            let children = children_of.remove(&hash).unwrap_or_default();
+            for child in &children {
+                if let Some(count) = successors_count.get_mut(child) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(*child);
+                    }
+                }
+            }
+
+            let mut entry = register.get(hash)?.clone();
+            if safe_url.is_private() {
+                entry = self.open_private_data(&entry)?;
+            }
+            let files_map_xorurl = str::from_utf8(&entry).map_err(|err| {
+                Error::ContentError(format!(
+                    "Invalid FilesMap link found in FilesContainer version {}: {err}",
+                    VersionHash::from(&hash)
+                ))
+            })?;
+            let files_map = self
+                .fetch_files_map(files_map_xorurl, safe_url.is_private())
+                .await?;
+
+            versions.push(FilesContainerVersion {
+                version: VersionHash::from(&hash),
+                previous: children.iter().map(VersionHash::from).collect(),
+                files_map,
+            });
+        }
+
+        Ok(versions)
+    }
+
+    /// # Compare two versions of `FilesContainer`s.
+    ///
+    /// Each URL can target a specific version of a `FilesContainer`, otherwise its latest version
+    /// is compared. The changes are those to be made to the first to end up with the second.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   let safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let diff = safe.files_container_diff("safe://mysite?v=hyfktce8xrz3y8mm1dkd5ebfndgxg96fp7q56hy3kje8ii9hgd9ytcu4dqe", "safe://mysite").await.unwrap();
+    ///     for path in diff.added.keys() {
+    ///         println!("Added since that version: {}", path);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_diff(&self, url_a: &str, url_b: &str) -> Result<FilesMapDiff> {
+        debug!("Comparing FilesContainers at {:?} and {:?}", url_a, url_b);
+        let files_map_a = self
+            .files_container_get(url_a)
+            .await?
+            .map(|(_, files_map)| files_map)
+            .unwrap_or_default();
+        let files_map_b = self
+            .files_container_get(url_b)
+            .await?
+            .map(|(_, files_map)| files_map)
+            .unwrap_or_default();
+
+        Ok(FilesMapDiff::new(&files_map_a, &files_map_b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilesMapDiff;
+    use crate::{
+        app::{consts::*, test_helpers::new_safe_instance},
+        files::{FileInfo, FilesMap},
+        SafeUrl,
+    };
+
+    use anyhow::{anyhow, Result};
+    use std::collections::BTreeSet;
+
+    fn file_info(link: &str, size: &str) -> FileInfo {
+        FileInfo::from([
+            (PREDICATE_LINK.to_string(), link.to_string()),
+            (PREDICATE_SIZE.to_string(), size.to_string()),
+        ])
+    }
+
+    #[test]
+    fn files_maps_diff_lists_added_updated_and_removed_items() {
+        let from = FilesMap::from([
+            ("/kept".to_string(), file_info("safe://kept", "1")),
+            ("/updated".to_string(), file_info("safe://old", "2")),
+            ("/removed".to_string(), file_info("safe://removed", "3")),
+        ]);
+        let to = FilesMap::from([
+            ("/kept".to_string(), file_info("safe://kept", "1")),
+            ("/updated".to_string(), file_info("safe://new", "4")),
+            ("/added".to_string(), file_info("safe://added", "5")),
+        ]);
+
+        let diff = FilesMapDiff::new(&from, &to);
+        assert_eq!(
+            diff.added.keys().collect::<Vec<_>>(),
+            vec![&"/added".to_string()]
+        );
+        assert_eq!(
+            diff.updated.get("/updated"),
+            Some(&(file_info("safe://old", "2"), file_info("safe://new", "4")))
+        );
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(
+            diff.removed.keys().collect::<Vec<_>>(),
+            vec![&"/removed".to_string()]
+        );
+
+        assert!(FilesMapDiff::new(&to, &to).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_files_container_versions_and_diff() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create_from("./testdata/test.md", None, false, false)
+            .await?;
+
+        let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
+        url_with_path.set_content_version(None);
+        url_with_path.set_path("/another.md");
+        let (content, _) = safe
+            .files_container_add(
+                "./testdata/another.md",
+                &url_with_path.to_string(),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let (version1, _) =
+            content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        let versions = safe.files_container_versions(&xorurl).await?;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, version1);
+        assert_eq!(versions[0].previous, BTreeSet::from([versions[1].version]));
+        assert!(versions[1].previous.is_empty());
+        assert_eq!(versions[1].files_map.len(), 1);
+
+        // the first version is compared with the latest one
+        let mut latest_url = SafeUrl::from_xorurl(&xorurl)?;
+        latest_url.set_content_version(None);
+        let diff = safe
+            .files_container_diff(&xorurl, &latest_url.to_string())
+            .await?;
+        assert_eq!(
+            diff.added.keys().collect::<Vec<_>>(),
+            vec![&"/another.md".to_string()]
+        );
+        assert!(diff.updated.is_empty());
+        assert!(diff.removed.is_empty());
+
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@
 
 mod file_system;
 mod files_map;
+mod history;
 mod metadata;
 mod realpath;
 
@@ -16,6 +17,8 @@ pub(crate) use metadata::FileMeta;
 pub(crate) use realpath::RealPath;
 
 pub use files_map::{FileInfo, FilesMap, FilesMapChange, GetAttr};
+pub use history::{FilesContainerVersion, FilesMapDiff};
+pub use sn_client::FileProgress;
 
 // List of files uploaded with details if they were added, updated or removed from FilesContainer
 pub type ProcessedFiles = BTreeMap<PathBuf, FilesMapChange>;
@@ -29,7 +32,7 @@ use crate::{
     Safe, SafeUrl, XorUrl,
 };
 
-use sn_client::{Client, QueriedDataReplicas};
+use sn_client::{Client, QueriedDataReplicas, UploadJournal};
 
 use bytes::{Buf, Bytes};
 use file_system::{
@@ -43,6 +46,10 @@ use std::{
     path::{Path, PathBuf},
     str,
 };
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::broadcast,
+};
 use tracing::{debug, info, warn};
 use xor_name::XorName;
 
@@ -69,6 +76,35 @@ impl Safe {
         Ok(xorurl)
     }
 
+    /// # Create an empty private `FilesContainer`.
+    ///
+    /// The FilesMaps of a private `FilesContainer`, as well as the content of the files added to it,
+    /// are encrypted to the key of the client, and to the keys set in `private_data_readers`,
+    /// before being stored, and transparently decrypted when fetched with the key of the client.
+    /// The XOR-URLs of such files are flagged as private. Unlike public files, they are held in
+    /// memory as a whole when uploaded and fetched, since they are encrypted and decrypted at once.
+    ///
+    /// Files linked to it with an existing `safe://` URL are kept as they are though.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{Safe, SafeUrl};
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     let mut safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let xorurl = safe.files_container_create_private().await.unwrap();
+    ///     assert!(SafeUrl::from_url(&xorurl).unwrap().is_private())
+    /// # });
+    /// ```
+    pub async fn files_container_create_private(&self) -> Result<XorUrl> {
+        let xorurl = self
+            .register_create_private(None, FILES_CONTAINER_TYPE_TAG, ContentType::FilesContainer)
+            .await?;
+
+        Ok(xorurl)
+    }
+
     /// # Create a `FilesContainer` containing files uploaded from a local folder.
     ///
     /// ## Example
@@ -107,23 +143,29 @@ impl Safe {
         // Create a Register
         let xorurl = self.files_container_create().await?;
 
-        if self.dry_run_mode {
+        if self.dry_run_mode && !self.is_recording_batch() {
             Ok((xorurl.to_string(), processed_files, files_map))
         } else {
             // Store files map on network
-            let files_map_xorurl = self.store_files_map(&files_map).await?;
+            let files_map_xorurl = self.store_files_map(&files_map, false).await?;
 
             let mut reg_url = SafeUrl::from_xorurl(&xorurl)?;
 
             // Write pointer to files_map onto our register
             let reg_address = self.get_register_address(&reg_url)?;
             let entry = files_map_xorurl.as_bytes().to_vec();
-            let client = self.get_safe_client()?;
-            let (entry_hash, reg_op) = client
-                .write_to_local_register(reg_address, entry, Default::default())
-                .await?;
+            let entry_hash = if self.dry_run_mode {
+                self.record_register_write(reg_address, entry, Default::default())
+                    .await?
+            } else {
+                let client = self.get_safe_client()?;
+                let (entry_hash, reg_op) = client
+                    .write_to_local_register(reg_address, entry, Default::default())
+                    .await?;
 
-            client.publish_register_ops(reg_op).await?;
+                client.publish_register_ops(reg_op).await?;
+                entry_hash
+            };
 
             // We return versioned xorurl
             reg_url.set_content_version(Some(VersionHash::from(&entry_hash)));
@@ -181,6 +223,7 @@ impl Safe {
                     },
                     safe_url
                 )),
+                err @ Error::PrivateDataError(_) => err,
                 err => Error::NetDataError(format!("Failed to get current version: {err}")),
             })?;
 
@@ -201,17 +244,27 @@ impl Safe {
             return Ok(None);
         };
 
-        // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
+        let files_map = self
+            .fetch_files_map(files_map_xorurl, safe_url.is_private())
+            .await?;
+        debug!("Files map retrieved.... {:?}", &version);
+
+        Ok(Some((version, files_map)))
+    }
+
+    // Private helper to fetch the FilesMap a FilesContainer entry links to, and deserialise it,
+    // decrypting it first if the FilesContainer is private
+    async fn fetch_files_map(&self, files_map_xorurl: &str, private: bool) -> Result<FilesMap> {
         let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
-        let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
-        let files_map = serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
+        let mut serialised_files_map = self.fetch_data(&files_map_url, None).await?;
+        if private {
+            serialised_files_map = Bytes::from(self.open_private_data(&serialised_files_map)?);
+        }
+        serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
             Error::ContentError(format!(
                 "Couldn't deserialise the FilesMap stored in the FilesContainer: {err:?}"
             ))
-        })?;
-        debug!("Files map retrieved.... {:?}", &version);
-
-        Ok(Some((version, files_map)))
+        })
     }
 
     /// # Sync up local folder with the content on a `FilesContainer`.
@@ -287,6 +340,7 @@ impl Safe {
             false,
             true,
             follow_links,
+            safe_url.is_private(),
         )
         .await?;
 
@@ -358,6 +412,7 @@ impl Safe {
                 force,
                 false,
                 follow_links,
+                safe_url.is_private(),
             )
             .await?
         };
@@ -404,7 +459,11 @@ impl Safe {
         let (safe_url, current_version, current_files_map) =
             validate_files_add_params(self, "", url, update_nrs).await?;
 
-        let new_file_xorurl = self.store_bytes(data, None).await?;
+        let new_file_xorurl = if safe_url.is_private() {
+            self.store_private_bytes(data, None).await?
+        } else {
+            self.store_bytes(data, None).await?
+        };
 
         let dst_path = Path::new(safe_url.path());
         let (processed_files, new_files_map, success_count) =
@@ -554,8 +613,9 @@ impl Safe {
     ) -> Result<VersionHash> {
         // The FilesContainer is updated by adding an entry containing the link to
         // the file with the serialised new version of the FilesMap.
-        let files_map_xorurl = if !self.dry_run_mode {
-            self.store_files_map(new_files_map).await?
+        let files_map_xorurl = if !self.dry_run_mode || self.is_recording_batch() {
+            self.store_files_map(new_files_map, safe_url.is_private())
+                .await?
         } else {
             "".to_string()
         };
@@ -600,20 +660,14 @@ impl Safe {
     /// # });
     /// ```
     pub async fn store_bytes(&self, bytes: Bytes, media_type: Option<&str>) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(ContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(ContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{media_type_str}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                    )))
-                }
-            },
-        )?;
-
-        let address = if self.dry_run_mode {
+        let content_type = content_type_for(media_type)?;
+
+        let address = if self.is_recording_batch() {
+            debug!("Recording {} bytes of data into the batch", bytes.len());
+            let (address, chunks) = Client::chunk_bytes(bytes)?;
+            self.record_chunks(chunks);
+            address
+        } else if self.dry_run_mode {
             debug!(
                 "Calculating network address for {} bytes of data",
                 bytes.len()
@@ -629,6 +683,80 @@ impl Safe {
         Ok(xorurl)
     }
 
+    // Store the data encrypted to the readers of private data, see `files_container_create_private`,
+    // returning a XOR-URL flagged as private
+    pub(crate) async fn store_private_bytes(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+    ) -> Result<XorUrl> {
+        let bytes = if self.dry_run_mode {
+            // the encrypted data is random, so is its address
+            bytes
+        } else {
+            Bytes::from(self.seal_private_data(&bytes)?)
+        };
+        let mut url = SafeUrl::from_url(&self.store_bytes(bytes, media_type).await?)?;
+        url.set_private(true);
+
+        Ok(url.encode(self.xorurl_base))
+    }
+
+    /// # Store a file from a local path
+    ///
+    /// Store the file at the given local path onto the network, without ever reading the whole file into memory.
+    /// The file is read and self-encrypted in segments, each of them being stored before reading the next,
+    /// while all of its chunks are paid for before any is stored.
+    /// A file fitting in a single segment is stored at the same address [`Safe::store_bytes`] stores its content,
+    /// while a larger one is stored at the address of a data map of its segments.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # use std::path::Path;
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   let safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let xorurl = safe.store_from_path(Path::new("./big-dataset.tar"), None).await.unwrap();
+    ///     let mut file = tokio::fs::File::create("./big-dataset-copy.tar").await.unwrap();
+    ///     let _bytes_written = safe.files_get_to_writer(&xorurl, &mut file).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn store_from_path(&self, path: &Path, media_type: Option<&str>) -> Result<XorUrl> {
+        let content_type = content_type_for(media_type)?;
+
+        let address = if self.is_recording_batch() {
+            debug!("Recording file at {} into the batch", path.display());
+            let (address, chunks) = Client::chunk_path(path).await?;
+            self.record_chunks(chunks);
+            address
+        } else if self.dry_run_mode {
+            debug!("Calculating network address for file at {}", path.display());
+            Client::calculate_address_from_path(path).await?
+        } else if let Some(journal_dir) = &self.uploads_journal_dir {
+            let journal_path = upload_journal_path(journal_dir, path)?;
+            if !self.resume_uploads {
+                UploadJournal::open(&journal_path, path)?.remove()?;
+            }
+            debug!(
+                "Storing file at {}, journaling it at {}",
+                path.display(),
+                journal_path.display()
+            );
+            let client = self.get_safe_client()?;
+            client
+                .upload_from_path_resumable(path, &journal_path, true)
+                .await?
+        } else {
+            debug!("Storing file at {}", path.display());
+            let client = self.get_safe_client()?;
+            client.upload_from_path(path, true).await?
+        };
+        let xorurl = SafeUrl::from_bytes(address, content_type)?.encode(self.xorurl_base);
+
+        Ok(xorurl)
+    }
+
     /// # Get a file
     /// Get file from the network.
     ///
@@ -651,9 +779,75 @@ impl Safe {
         self.fetch_data(&safe_url, range).await
     }
 
+    /// # Subscribe to the progress of files uploads and downloads
+    /// Returns a receiver of the events reporting the progress of the files uploaded and
+    /// downloaded from now on, e.g. by any of the `files_container_*` APIs.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::{files::FileProgress, Safe};
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   let safe = Safe::connected(None, None, None, None).await.unwrap();
+    ///     let mut progress = safe.files_progress().unwrap();
+    ///     tokio::spawn(async move {
+    ///         while let Ok(event) = progress.recv().await {
+    ///             if let FileProgress::ChunkVerified(name) = event {
+    ///                 println!("Chunk stored at {name:?}");
+    ///             }
+    ///         }
+    ///     });
+    ///     let _ = safe.files_container_create_from("./testdata", None, true, false).await.unwrap();
+    /// # });
+    /// ```
+    pub fn files_progress(&self) -> Result<broadcast::Receiver<FileProgress>> {
+        Ok(self.get_safe_client()?.file_progress())
+    }
+
+    /// # Get a file into a writer
+    /// Get file from the network, writing its content to the given writer as it's fetched,
+    /// thus without ever holding the whole file in memory.
+    /// Returns the number of bytes written.
+    pub async fn files_get_to_writer<W: AsyncWrite + Unpin + Send>(
+        &self,
+        url: &str,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let safe_url = self.parse_and_resolve_url(url).await?;
+        match safe_url.data_type() {
+            DataType::File if safe_url.is_private() => {
+                let data = self.fetch_data(&safe_url, None).await?;
+                writer.write_all(&data).await?;
+                writer.flush().await?;
+                Ok(data.len() as u64)
+            }
+            DataType::File => {
+                let address = safe_url.xorname();
+                debug!("Attempting to fetch data from {address:?}");
+                let client = self.get_safe_client()?;
+                let written = client
+                    .download_to_writer(address, writer)
+                    .await
+                    .map_err(|err| Error::NetDataError(format!("Failed to GET file: {err:?}")))?;
+                debug!("{written} bytes of data successfully retrieved from: {address:?}");
+                Ok(written)
+            }
+            other => Err(Error::ContentError(format!("{other}"))),
+        }
+    }
+
     /// Fetch a file from a `SafeUrl` without performing any type of URL resolution
     pub(crate) async fn fetch_data(&self, safe_url: &SafeUrl, range: Range) -> Result<Bytes> {
         match safe_url.data_type() {
+            DataType::File if safe_url.is_private() => {
+                // the whole file is needed to decrypt any range of it
+                let sealed = self.get_bytes(safe_url.xorname(), None).await?;
+                let data = Bytes::from(self.open_private_data(&sealed)?);
+                let (start, end) = range.unwrap_or_default();
+                let end = end.map_or(data.len(), |end| usize::min(end as usize, data.len()));
+                let start = start.map_or(0, |start| usize::min(start as usize, end));
+                Ok(data.slice(start..end))
+            }
             DataType::File => self.get_bytes(safe_url.xorname(), range).await,
             other => Err(Error::ContentError(format!("{other}"))),
         }
@@ -707,8 +901,9 @@ impl Safe {
         }
     }
 
-    // Private helper to serialise a FilesMap and store it in a file
-    async fn store_files_map(&self, files_map: &FilesMap) -> Result<String> {
+    // Private helper to serialise a FilesMap and store it in a file,
+    // encrypted to the readers of the FilesContainer if it's private
+    async fn store_files_map(&self, files_map: &FilesMap, private: bool) -> Result<String> {
         // The FilesMapContainer is a Register where each NRS Map version is
         // an entry containing the XOR-URL of the file that contains the serialised NrsMap.
         let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
@@ -717,9 +912,13 @@ impl Safe {
             ))
         })?;
 
-        let files_map_xorurl = self
-            .store_bytes(Bytes::from(serialised_files_map), None)
-            .await?;
+        let serialised_files_map = if private {
+            Bytes::from(self.seal_private_data(serialised_files_map.as_bytes())?)
+        } else {
+            Bytes::from(serialised_files_map)
+        };
+
+        let files_map_xorurl = self.store_bytes(serialised_files_map, None).await?;
 
         Ok(files_map_xorurl)
     }
@@ -830,6 +1029,7 @@ async fn files_map_sync(
     force: bool,
     compare_file_content: bool,
     follow_links: bool,
+    private: bool,
 ) -> Result<(ProcessedFiles, FilesMap, u64)> {
     let (location_base_path, dst_base_path) = get_base_paths(location, dst_path);
     let mut updated_files_map = FilesMap::new();
@@ -870,6 +1070,7 @@ async fn files_map_sync(
                     false,
                     &mut updated_files_map,
                     &mut processed_files,
+                    private,
                 )
                 .await
                 {
@@ -909,6 +1110,7 @@ async fn files_map_sync(
                         true,
                         &mut updated_files_map,
                         &mut processed_files,
+                        private,
                     )
                     .await
                     {
@@ -988,8 +1190,13 @@ async fn is_file_item_modified(safe: &Safe, local_filename: &Path, file_item: &F
         // Use a dry runner only for this next operation
         let dry_runner = Safe::dry_runner(Some(safe.xorurl_base));
 
+        // the content of private files is encrypted at random, so their XOR-URL is compared
+        // with the one their content had when uploaded instead
+        let link = file_item
+            .get(PREDICATE_CONTENT_LINK)
+            .unwrap_or(&file_item[PREDICATE_LINK]);
         match upload_file_to_net(&dry_runner, local_filename).await {
-            Ok(local_xorurl) => file_item[PREDICATE_LINK] != local_xorurl,
+            Ok(local_xorurl) => *link != local_xorurl,
             Err(_) => false,
         }
     } else {
@@ -1059,6 +1266,7 @@ async fn files_map_add_link(
                         true,
                         &mut files_map,
                         &mut processed_files,
+                        false,
                     )
                     .await
                     {
@@ -1096,6 +1304,7 @@ async fn files_map_add_link(
                 false,
                 &mut files_map,
                 &mut processed_files,
+                false,
             )
             .await
             {
@@ -1214,6 +1423,7 @@ async fn files_map_create(
             false,
             &mut files_map,
             content,
+            false,
         )
         .await;
     }
@@ -1221,6 +1431,34 @@ async fn files_map_create(
     Ok(files_map)
 }
 
+// Path of the journal of the upload of the file at the given path, named after its absolute path.
+fn upload_journal_path(journal_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let absolute_path = path.canonicalize().map_err(|err| {
+        Error::InvalidInput(format!(
+            "Failed to resolve path of file \"{}\": {err}",
+            path.display()
+        ))
+    })?;
+    let name = XorName::from_content(absolute_path.to_string_lossy().as_bytes());
+    Ok(journal_dir.join(format!("{name:x}")))
+}
+
+// Parses the media type of some content, if any, into its `ContentType`.
+fn content_type_for(media_type: Option<&str>) -> Result<ContentType> {
+    media_type.map_or_else(
+        || Ok(ContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(ContentType::MediaType(media_type_str.to_string()))
+            } else {
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{media_type_str}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                )))
+            }
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1328,6 +1566,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_create_private() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.files_container_create_private().await?;
+        assert!(SafeUrl::from_url(&xorurl)?.is_private());
+
+        let (content, _) = safe
+            .files_container_add("./testdata/test.md", &xorurl, false, false, false)
+            .await?;
+        let (version, files_map) =
+            content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        // the FilesMap is decrypted when fetched by its owner...
+        let (fetched_version, fetched_files_map) = safe
+            .files_container_get(&xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(fetched_version, version);
+        assert_eq!(fetched_files_map, files_map);
+
+        // ...and so is the content of its files
+        let file_xorurl = files_map["/test.md"][PREDICATE_LINK].clone();
+        assert!(SafeUrl::from_url(&file_xorurl)?.is_private());
+        let content = safe.files_get(&file_xorurl, None).await?;
+        assert_eq!(content, Bytes::from(std::fs::read("./testdata/test.md")?));
+
+        // ...but not by anyone else
+        let stranger_safe = new_safe_instance().await?;
+        match stranger_safe.files_container_get(&xorurl).await {
+            Err(Error::PrivateDataError(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(_) => bail!("Private FilesContainer was unexpectedly read by a stranger"),
+        }
+        match stranger_safe.files_get(&file_xorurl, None).await {
+            Err(Error::PrivateDataError(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(_) => bail!("Private file was unexpectedly read by a stranger"),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_store_bytes() -> Result<()> {
         let safe = new_safe_instance().await?;
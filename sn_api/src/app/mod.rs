@@ -19,15 +19,17 @@ pub mod wallet;
 
 pub use crate::safeurl::*;
 pub use consts::DEFAULT_XORURL_BASE;
-pub use sn_client::DEFAULT_NETWORK_CONTACTS_FILE_NAME;
+pub use sn_client::{WriteBatch, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
 pub use sn_interface::network_knowledge::SectionTree;
 pub use xor_name::XorName;
 
 // --------------------------------------------------------------------
 
 mod auth;
+mod batch;
 mod consts;
 mod helpers;
+mod private;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_helpers;
@@ -35,10 +37,10 @@ pub mod test_helpers;
 use super::{common, constants, Error, Result};
 
 use sn_client::Client;
-use sn_dbc::Owner;
+use sn_dbc::{Dbc, Owner};
 use sn_interface::types::Keypair;
 
-use std::time::Duration;
+use std::{collections::BTreeSet, path::PathBuf, time::Duration};
 use tracing::debug;
 
 const APP_NOT_CONNECTED: &str = "Application is not connected to the network";
@@ -48,6 +50,20 @@ pub struct Safe {
     client: Option<Client>,
     pub xorurl_base: XorUrlBase,
     pub dry_run_mode: bool,
+    /// Directory where the journals of files being uploaded are kept, so that interrupted
+    /// uploads can be resumed. Uploads are not journaled if this is not set.
+    pub uploads_journal_dir: Option<PathBuf>,
+    /// Whether to resume the uploads of those files which have a journal left by a previous,
+    /// interrupted, upload. Otherwise any such journal is discarded and the upload starts over.
+    pub resume_uploads: bool,
+    /// Keys, besides the client's own DBC owner key, which the private data written by this
+    /// instance is encrypted to, i.e. the keys of those who can read it besides the client.
+    pub private_data_readers: BTreeSet<bls::PublicKey>,
+    /// Directory where the client connected by this instance keeps an on-disk cache of the
+    /// chunks and Registers it retrieves. No such cache is kept if this is not set.
+    pub client_cache_dir: Option<PathBuf>,
+    // Batch the writes are being recorded into instead of being sent to the network, if any
+    write_batch: Option<batch::SharedBatchRecorder>,
 }
 
 impl Safe {
@@ -57,6 +73,11 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: true,
+            uploads_journal_dir: None,
+            resume_uploads: false,
+            private_data_readers: BTreeSet::new(),
+            client_cache_dir: None,
+            write_batch: None,
         }
     }
 
@@ -71,6 +92,11 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: false,
+            uploads_journal_dir: None,
+            resume_uploads: false,
+            private_data_readers: BTreeSet::new(),
+            client_cache_dir: None,
+            write_batch: None,
         };
 
         safe.connect(keypair, timeout, dbc_owner).await?;
@@ -91,7 +117,8 @@ impl Safe {
         let mut b = Client::builder()
             .from_env() // Load parameters from environment variables.
             .keypair(keypair)
-            .dbc_owner(dbc_owner);
+            .dbc_owner(dbc_owner)
+            .cache_dir(self.client_cache_dir.clone());
 
         // Override timeout
         if let Some(timeout) = timeout {
@@ -122,6 +149,18 @@ impl Safe {
         Ok(section_tree)
     }
 
+    /// Add DBCs to those used for paying for storing data on the network.
+    pub async fn add_payment_dbcs(&self, dbcs: Vec<Dbc>) -> Result<()> {
+        self.get_safe_client()?.add_payment_dbcs(dbcs).await;
+        Ok(())
+    }
+
+    /// DBCs currently held for paying for storing data on the network,
+    /// which includes the change of any payment made so far.
+    pub async fn payment_dbcs(&self) -> Result<Vec<Dbc>> {
+        Ok(self.get_safe_client()?.payment_dbcs().await)
+    }
+
     // Private helper to obtain the Client instance
     pub(crate) fn get_safe_client(&self) -> Result<&Client> {
         match &self.client {
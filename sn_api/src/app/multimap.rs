@@ -95,6 +95,9 @@ impl Safe {
         };
 
         if self.dry_run_mode {
+            if self.is_recording_batch() {
+                return self.record_register_write(address, data, replace).await;
+            }
             return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
         }
 
@@ -131,6 +134,11 @@ impl Safe {
         };
 
         if self.dry_run_mode {
+            if self.is_recording_batch() {
+                return self
+                    .record_register_write(address, MULTIMAP_REMOVED_MARK.to_vec(), to_remove)
+                    .await;
+            }
             return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
         }
 
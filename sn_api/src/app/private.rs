@@ -0,0 +1,127 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Client-side encryption of private data, i.e. the entries of private Registers and
+//! the FilesMaps of private FilesContainers, which only their readers can decrypt.
+
+use crate::{Error, Result, Safe};
+
+use bincode::{deserialize, serialize};
+use bls::{Ciphertext, PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Private data as stored on the network. The data is encrypted with a random key,
+// the secret key of which is in turn encrypted to each of the readers' keys, so that
+// the data is only stored once regardless of the number of readers.
+#[derive(Serialize, Deserialize)]
+struct SealedData {
+    readers: Vec<(PublicKey, Ciphertext)>,
+    content: Ciphertext,
+}
+
+/// Encrypts the data so that only the holders of the secret keys of the readers can decrypt it.
+pub(crate) fn seal(data: &[u8], readers: &BTreeSet<PublicKey>) -> Result<Vec<u8>> {
+    if readers.is_empty() {
+        return Err(Error::PrivateDataError(
+            "Private data must have at least one reader".to_string(),
+        ));
+    }
+
+    let content_key = SecretKey::random();
+    let content = content_key.public_key().encrypt(data);
+    let content_key_bytes = content_key.to_bytes();
+    let readers = readers
+        .iter()
+        .map(|reader| (*reader, reader.encrypt(content_key_bytes)))
+        .collect();
+
+    serialize(&SealedData { readers, content }).map_err(|err| {
+        Error::Serialisation(format!("Couldn't serialise the private data: {err:?}"))
+    })
+}
+
+/// Decrypts data sealed with [`seal`], as long as the secret key is one of its readers'.
+pub(crate) fn open(sealed: &[u8], secret_key: &SecretKey) -> Result<Vec<u8>> {
+    let sealed: SealedData = deserialize(sealed).map_err(|err| {
+        Error::PrivateDataError(format!("Couldn't deserialise the private data: {err:?}"))
+    })?;
+
+    let reader = secret_key.public_key();
+    let (_, encrypted_key) = sealed
+        .readers
+        .iter()
+        .find(|(public_key, _)| *public_key == reader)
+        .ok_or_else(|| {
+            Error::PrivateDataError(format!(
+                "The key {} is not a reader of the private data",
+                reader.to_hex()
+            ))
+        })?;
+
+    let content_key = secret_key
+        .decrypt(encrypted_key)
+        .and_then(|bytes| <[u8; bls::SK_SIZE]>::try_from(bytes).ok())
+        .and_then(|bytes| SecretKey::from_bytes(bytes).ok())
+        .ok_or_else(|| {
+            Error::PrivateDataError("Couldn't decrypt the key of the private data".to_string())
+        })?;
+
+    content_key
+        .decrypt(&sealed.content)
+        .ok_or_else(|| Error::PrivateDataError("Couldn't decrypt the private data".to_string()))
+}
+
+impl Safe {
+    // Encrypt private data to the key of the client, and to the readers set on this instance
+    pub(crate) fn seal_private_data(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let client = self.get_safe_client()?;
+        let mut readers = self.private_data_readers.clone();
+        let _ = readers.insert(client.dbc_owner().public_key());
+        seal(data, &readers)
+    }
+
+    // Decrypt private data with the key of the client
+    pub(crate) fn open_private_data(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        let client = self.get_safe_client()?;
+        let secret_key = client.dbc_owner().secret_key()?;
+        open(sealed, &secret_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, seal};
+    use crate::Error;
+
+    use anyhow::{bail, Result};
+    use bls::SecretKey;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn sealed_data_can_only_be_opened_by_its_readers() -> Result<()> {
+        let owner = SecretKey::random();
+        let reader = SecretKey::random();
+        let stranger = SecretKey::random();
+        let readers = BTreeSet::from([owner.public_key(), reader.public_key()]);
+
+        let data = b"some private data".to_vec();
+        let sealed = seal(&data, &readers)?;
+        assert_ne!(sealed, data);
+
+        assert_eq!(open(&sealed, &owner)?, data);
+        assert_eq!(open(&sealed, &reader)?, data);
+        match open(&sealed, &stranger) {
+            Err(Error::PrivateDataError(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(_) => bail!("Private data was unexpectedly opened by a stranger"),
+        }
+
+        Ok(())
+    }
+}
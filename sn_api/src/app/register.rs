@@ -6,7 +6,9 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-pub use sn_interface::types::register::{Entry, EntryHash};
+pub use sn_interface::types::register::{
+    Action, Entry, EntryHash, Permissions, Policy, PolicyEdit, User,
+};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
@@ -14,10 +16,7 @@ use crate::{Error, Result, Safe};
 use sn_client::Error as ClientError;
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
-    types::{
-        register::{Permissions, Policy, User},
-        DataAddress, Error as SafeNdError, RegisterAddress,
-    },
+    types::{register::Register, DataAddress, Error as SafeNdError, RegisterAddress},
 };
 
 use rand::Rng;
@@ -33,19 +32,45 @@ impl Safe {
         name: Option<XorName>,
         tag: u64,
         content_type: ContentType,
+    ) -> Result<XorUrl> {
+        self.create_register(name, tag, content_type, false).await
+    }
+
+    /// Create a private Register on the network, the entries of which are encrypted to the key
+    /// of the client, and to the keys set in `private_data_readers`, before being written to it.
+    /// Its entries are then transparently decrypted when read with the key of the client.
+    pub async fn register_create_private(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        content_type: ContentType,
+    ) -> Result<XorUrl> {
+        self.create_register(name, tag, content_type, true).await
+    }
+
+    // Create a Register on the network, which is flagged as private on its XOR-URL if so
+    async fn create_register(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        content_type: ContentType,
+        private: bool,
     ) -> Result<XorUrl> {
         debug!(
-            "Storing Register data with tag type: {}, xorname: {:?}, dry_run: {}",
-            tag, name, self.dry_run_mode
+            "Storing Register data with tag type: {}, xorname: {:?}, private: {}, dry_run: {}",
+            tag, name, private, self.dry_run_mode
         );
 
         let xorname = name.unwrap_or_else(xor_name::rand::random);
         info!("Xorname for new Register storage: {:?}", &xorname);
 
-        let xorurl = SafeUrl::from_register(xorname, tag, content_type)?.encode(self.xorurl_base);
+        let mut url = SafeUrl::from_register(xorname, tag, content_type)?;
+        url.set_private(private);
+        let xorurl = url.encode(self.xorurl_base);
 
-        // return early if dry_run_mode
+        // return early if dry_run_mode, recording the creation if writes are being batched
         if self.dry_run_mode {
+            self.record_register_create(xorname, tag, policy)?;
             return Ok(xorurl);
         }
 
@@ -82,6 +107,28 @@ impl Safe {
         self.register_fetch_entry(&safeurl, hash).await
     }
 
+    /// Read the policy of a Register on the network, i.e. its owner and users' permissions
+    pub async fn register_policy(&self, url: &str) -> Result<Policy> {
+        debug!("Getting Register policy from: {:?}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&safeurl)?;
+        let client = self.get_safe_client()?;
+        client
+            .get_register_policy(address)
+            .await
+            .map_err(|err| match err {
+                ClientError::ErrorMsg {
+                    source: ErrorMsg::AccessDenied(_),
+                    ..
+                } => Error::AccessDenied(format!(
+                    "Couldn't read policy of Register found at \"{url}\"",
+                )),
+                err => {
+                    Error::NetDataError(format!("Failed to read policy of Register data: {err:?}",))
+                }
+            })
+    }
+
     /// Fetch a Register from a `SafeUrl` without performing any type of URL resolution
     /// Supports version hashes:
     /// e.g. safe://mysafeurl?v=ce56a3504c8f27bfeb13bdf9051c2e91409230ea
@@ -101,9 +148,17 @@ impl Safe {
             None => {
                 debug!("No version so take latest entry from Register at: {}", url);
                 let address = self.get_register_address(url)?;
-                let client = self.get_safe_client()?;
-                match client.read_register(address).await {
-                    Ok(entry) => Ok(entry),
+                // only the Registers created in the batch being recorded, if any, are read locally
+                let entries = match self.recorded_register(&address) {
+                    Some(register) => Ok(register.read()),
+                    None => self.get_safe_client()?.read_register(address).await,
+                };
+                match entries {
+                    Ok(entries) if url.is_private() => entries
+                        .into_iter()
+                        .map(|(hash, entry)| Ok((hash, self.open_private_data(&entry)?)))
+                        .collect(),
+                    Ok(entries) => Ok(entries),
                     Err(ClientError::NetworkDataError(SafeNdError::NoSuchEntry(_))) => Err(
                         Error::EmptyContent(format!("Empty Register found at \"{url}\"")),
                     ),
@@ -144,24 +199,59 @@ impl Safe {
         // TODO: allow to specify the hash with the SafeUrl as well: safeurl.content_hash(),
         // e.g. safe://mysafeurl#ce56a3504c8f27bfeb13bdf9051c2e91409230ea
         let address = self.get_register_address(url)?;
+        let entry = if let Some(register) = self.recorded_register(&address) {
+            register
+                .get(hash)
+                .cloned()
+                .map_err(|_| Error::HashNotFound(hash))?
+        } else {
+            let client = self.get_safe_client()?;
+            client
+                .get_register_entry(address, hash)
+                .await
+                .map_err(|err| {
+                    if let ClientError::ErrorMsg {
+                        source: sn_interface::messaging::data::Error::NoSuchEntry(_),
+                        ..
+                    } = err
+                    {
+                        Error::HashNotFound(hash)
+                    } else {
+                        Error::NetDataError(format!(
+                            "Failed to retrieve entry with hash '{}' from Register data: {err:?}",
+                            hex::encode(hash.0),
+                        ))
+                    }
+                })?
+        };
+
+        if url.is_private() {
+            self.open_private_data(&entry)
+        } else {
+            Ok(entry)
+        }
+    }
+
+    /// Fetch a whole Register replica from a `SafeUrl` without performing any type of URL
+    /// resolution, e.g. to walk its history. Its entries are not decrypted if it's private.
+    pub(crate) async fn fetch_register(&self, url: &SafeUrl) -> Result<Register> {
+        let address = self.get_register_address(url)?;
+        if let Some(register) = self.recorded_register(&address) {
+            return Ok(register);
+        }
+
         let client = self.get_safe_client()?;
-        client
-            .get_register_entry(address, hash)
-            .await
-            .map_err(|err| {
-                if let ClientError::ErrorMsg {
-                    source: sn_interface::messaging::data::Error::NoSuchEntry(_),
-                    ..
-                } = err
-                {
-                    Error::HashNotFound(hash)
-                } else {
-                    Error::NetDataError(format!(
-                        "Failed to retrieve entry with hash '{}' from Register data: {err:?}",
-                        hex::encode(hash.0),
-                    ))
-                }
-            })
+        client.get_register(address).await.map_err(|err| match err {
+            ClientError::ErrorMsg {
+                source: ErrorMsg::AccessDenied(_),
+                ..
+            } => Error::AccessDenied(format!("Couldn't read Register found at \"{url}\"")),
+            ClientError::ErrorMsg {
+                source: ErrorMsg::DataNotFound(_),
+                ..
+            } => Error::ContentNotFound(format!("No Register found at \"{url}\"")),
+            err => Error::NetDataError(format!("Failed to read Register data: {err:?}")),
+        })
     }
 
     /// Write value to a Register on the network
@@ -173,10 +263,20 @@ impl Safe {
     ) -> Result<EntryHash> {
         let reg_url = self.parse_and_resolve_url(url).await?;
         let address = self.get_register_address(&reg_url)?;
-        if self.dry_run_mode {
+        if self.dry_run_mode && !self.is_recording_batch() {
             return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
         }
 
+        let entry = if reg_url.is_private() {
+            self.seal_private_data(&entry)?
+        } else {
+            entry
+        };
+
+        if self.dry_run_mode {
+            return self.record_register_write(address, entry, parents).await;
+        }
+
         let client = self.get_safe_client()?;
         let (entry_hash, op_batch) = match client
             .write_to_local_register(address, entry, parents)
@@ -206,6 +306,70 @@ impl Safe {
         Ok(entry_hash)
     }
 
+    /// Grant permissions to a user on a Register on the network, replacing any it had.
+    /// Only the owner of the Register can do so.
+    pub async fn register_grant(
+        &self,
+        url: &str,
+        user: User,
+        permissions: Permissions,
+    ) -> Result<()> {
+        self.register_edit_policy(url, PolicyEdit::Grant { user, permissions })
+            .await
+    }
+
+    /// Revoke all the permissions of a user on a Register on the network.
+    /// Only the owner of the Register can do so.
+    pub async fn register_revoke(&self, url: &str, user: User) -> Result<()> {
+        self.register_edit_policy(url, PolicyEdit::Revoke(user))
+            .await
+    }
+
+    /// Hand over the ownership of a Register on the network to another user.
+    /// Only the owner of the Register can do so.
+    pub async fn register_transfer_ownership(&self, url: &str, new_owner: User) -> Result<()> {
+        self.register_edit_policy(url, PolicyEdit::TransferOwnership(new_owner))
+            .await
+    }
+
+    // Apply an edit to the policy of a Register on the network
+    async fn register_edit_policy(&self, url: &str, edit: PolicyEdit) -> Result<()> {
+        debug!("Editing policy of Register at {:?}: {:?}", url, edit);
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&reg_url)?;
+        if self.dry_run_mode {
+            if self.is_recording_batch() {
+                return self.record_register_policy_edit(address, edit).await;
+            }
+            return Ok(());
+        }
+
+        let client = self.get_safe_client()?;
+        let op_batch = match client.edit_register_policy(address, edit).await {
+            Ok(op_batch) => op_batch,
+            Err(
+                ClientError::NetworkDataError(SafeNdError::AccessDenied(_))
+                | ClientError::ErrorMsg {
+                    source: ErrorMsg::AccessDenied(_),
+                    ..
+                },
+            ) => {
+                return Err(Error::AccessDenied(format!(
+                    "Couldn't edit policy of Register found at \"{url}\"",
+                )));
+            }
+            Err(err) => {
+                return Err(Error::NetDataError(format!(
+                    "Failed to edit policy of Register: {err:?}"
+                )));
+            }
+        };
+
+        client.publish_register_ops(op_batch).await?;
+
+        Ok(())
+    }
+
     pub(crate) fn get_register_address(&self, url: &SafeUrl) -> Result<RegisterAddress> {
         let address = match url.address() {
             DataAddress::Register(reg_address) => reg_address,
@@ -228,7 +392,8 @@ fn policy(owner: User) -> Policy {
 
 #[cfg(test)]
 mod tests {
-    use crate::{app::test_helpers::new_safe_instance, ContentType, Error};
+    use super::{Permissions, User};
+    use crate::{app::test_helpers::new_safe_instance, ContentType, Error, SafeUrl};
     use anyhow::{bail, Result};
 
     #[tokio::test]
@@ -253,6 +418,100 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_create_private() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let reader_safe = new_safe_instance().await?;
+        let _ = safe
+            .private_data_readers
+            .insert(reader_safe.get_safe_client()?.dbc_owner().public_key());
+
+        let xorurl = safe
+            .register_create_private(None, 25_000, ContentType::Raw)
+            .await?;
+        assert!(SafeUrl::from_url(&xorurl)?.is_private());
+
+        let data = b"private data".to_vec();
+        let hash = safe
+            .register_write(&xorurl, data.clone(), Default::default())
+            .await?;
+
+        // the entry is stored encrypted, while readers get it decrypted
+        let address = safe.get_register_address(&SafeUrl::from_url(&xorurl)?)?;
+        let stored = safe
+            .get_safe_client()?
+            .get_register_entry(address, hash)
+            .await?;
+        assert_ne!(stored, data);
+
+        assert_eq!(safe.register_read_entry(&xorurl, hash).await?, data);
+        assert_eq!(
+            reader_safe.register_read(&xorurl).await?,
+            vec![(hash, data)].into_iter().collect()
+        );
+
+        let stranger_safe = new_safe_instance().await?;
+        match stranger_safe.register_read_entry(&xorurl, hash).await {
+            Err(Error::PrivateDataError(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(_) => bail!("Private entry was unexpectedly read by a stranger"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_policy() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+
+        let policy = safe.register_policy(&xorurl).await?;
+        let owner = User::Key(safe.get_safe_client()?.public_key());
+
+        assert_eq!(policy.owner, owner);
+        assert_eq!(
+            policy.permissions.get(&owner).copied(),
+            Some(Permissions::new(true))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_policy_edits() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+
+        let other_safe = new_safe_instance().await?;
+        let other_user = User::Key(other_safe.get_safe_client()?.public_key());
+
+        // the other user can write once granted permissions
+        safe.register_grant(&xorurl, other_user, Permissions::new(true))
+            .await?;
+        let hash = other_safe
+            .register_write(&xorurl, b"other-data".to_vec(), Default::default())
+            .await?;
+        assert_eq!(
+            safe.register_read_entry(&xorurl, hash).await?,
+            b"other-data".to_vec()
+        );
+
+        // and it can edit the policy once it's the owner
+        safe.register_transfer_ownership(&xorurl, other_user)
+            .await?;
+        let policy = safe.register_policy(&xorurl).await?;
+        assert_eq!(policy.owner, other_user);
+
+        match safe.register_revoke(&xorurl, other_user).await {
+            Err(Error::AccessDenied(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(()) => bail!("Policy edit by a non-owner succeeded unexpectedly"),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_owner_permissions() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -174,6 +174,7 @@ pub async fn new_safe_instance() -> Result<Safe> {
         Err(_) => Keypair::new_ed25519(),
     };
     let safe = Safe::connected(Some(credentials), None, None, None).await?;
+    safe.add_payment_dbcs(vec![GENESIS_DBC.clone()]).await?;
     Ok(safe)
 }
 
@@ -190,6 +191,7 @@ pub async fn new_safe_instance_with_dbc_owner(secret_key: &str) -> Result<(Safe,
         .with_context(|| "Failed to deserialize secret key for DBC owner")?;
     let dbc_owner = Owner::from(sk);
     let safe = Safe::connected(Some(credentials), None, None, Some(dbc_owner.clone())).await?;
+    safe.add_payment_dbcs(vec![GENESIS_DBC.clone()]).await?;
 
     Ok((safe, dbc_owner))
 }
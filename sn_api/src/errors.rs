@@ -137,6 +137,9 @@ pub enum Error {
     /// DbcDepositError
     #[error("The secret key does not match the public key for this owned DBC")]
     DbcDepositInvalidSecretKey,
+    /// Private data couldn't be encrypted, or decrypted with the key of the client
+    #[error("PrivateDataError: {0}")]
+    PrivateDataError(String),
     /// NotImplementedError
     #[error("NotImplementedError: {0}")]
     NotImplementedError(String),
@@ -35,6 +35,7 @@ const XOR_URL_VERSION_1: u64 = 0x1; // TODO: consider using 16 bits
 const XOR_URL_STR_MAX_LENGTH: usize = 44;
 const XOR_NAME_BYTES_OFFSET: usize = 4; // offset where to find the XoR name bytes
 const URL_VERSION_QUERY_NAME: &str = "v";
+const PRIVATE_DATA_FLAG: u8 = 0x80; // set on the data type byte when the content is private
 
 /// The XOR-URL type
 pub type XorUrl = String;
@@ -234,6 +235,8 @@ pub struct SafeUrl {
     fragment: String,                     // fragment, no separator
     content_version: Option<VersionHash>, // convenience for ?v=<version
     url_type: UrlType,                    // nrsurl or xorurl
+    #[serde(default)]
+    private: bool,   // content is encrypted to its readers' keys
 }
 
 /// This implementation performs semi-rigorous validation,
@@ -343,6 +346,7 @@ impl SafeUrl {
             fragment: fragment.unwrap_or("").to_string(),
             content_version: None, // set below.
             url_type,
+            private: false,
         };
 
         // now we can call ::name_to_base(), to generate the top_name.
@@ -484,7 +488,8 @@ impl SafeUrl {
         type_tag_bytes[8 - type_tag_bytes_len..].copy_from_slice(&xorurl_bytes[type_tag_offset..]);
         let type_tag: u64 = u64::from_be_bytes(type_tag_bytes);
 
-        let address = match xorurl_bytes[3] {
+        let private = xorurl_bytes[3] & PRIVATE_DATA_FLAG != 0;
+        let address = match xorurl_bytes[3] & !PRIVATE_DATA_FLAG {
             0 => DataAddress::SafeKey(xor_name),
             1 => DataAddress::Bytes(ChunkAddress(xor_name)),
             2 => DataAddress::Register(RegisterAddress::new(xor_name, type_tag)),
@@ -495,7 +500,7 @@ impl SafeUrl {
             }
         };
 
-        Self::new(
+        let mut url = Self::new(
             address,
             None, // no nrs_name for an xorurl
             type_tag,
@@ -505,7 +510,10 @@ impl SafeUrl {
             Some(&parts.query_string),
             Some(&parts.fragment),
             None,
-        )
+        )?;
+        url.set_private(private);
+
+        Ok(url)
     }
 
     pub fn from_safekey(xor_name: XorName) -> Result<Self> {
@@ -586,6 +594,21 @@ impl SafeUrl {
         Ok(())
     }
 
+    /// returns true if the content is private, i.e. encrypted to the keys of its readers
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    /// sets whether the content is private, i.e. encrypted to the keys of its readers
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+        if self.url_type == UrlType::XorUrl {
+            self.top_name = self.name_to_base(DEFAULT_XORURL_BASE, false);
+            let sep = if self.sub_names.is_empty() { "" } else { "." };
+            self.public_name = format!("{}{}{}", self.sub_names(), sep, self.top_name);
+        }
+    }
+
     /// returns `XorName`
     pub fn xorname(&self) -> XorName {
         *self.address().name()
@@ -868,7 +891,7 @@ impl SafeUrl {
     // XOR-URL encoding format (var length from 37 to 45 bytes):
     // 1 byte for encoding version
     // 2 bytes for content type (enough to start including some MIME types also)
-    // 1 byte for data type
+    // 1 byte for data type, its highest bit set if the content is private
     // 32 bytes for XoR Name
     // and up to 8 bytes for type_tag
     // query param "v=" is treated as the content version
@@ -907,8 +930,12 @@ impl SafeUrl {
 
         cid_vec.extend_from_slice(&self.content_type_u16.to_be_bytes());
 
-        // push the data type byte
-        cid_vec.push(self.data_type() as u8);
+        // push the data type byte, flagging it if the content is private
+        let mut data_type = self.data_type() as u8;
+        if self.private {
+            data_type |= PRIVATE_DATA_FLAG;
+        }
+        cid_vec.push(data_type);
 
         // add the xor_name 32 bytes
         cid_vec.extend_from_slice(&self.address().name().0);
@@ -1406,6 +1433,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn encode_register_should_set_private_flag() -> Result<()> {
+        let xor_name = XorName(*b"12345678901234567890123456789012");
+        let mut url = SafeUrl::from_register(xor_name, 25_000, ContentType::FilesContainer)?;
+        assert!(!url.is_private());
+        let public_xorurl = url.encode(XorUrlBase::Base32z);
+
+        url.set_private(true);
+        let xorurl = url.encode(XorUrlBase::Base32z);
+        assert_ne!(xorurl, public_xorurl);
+
+        let url = SafeUrl::from_url(&xorurl)?;
+        assert!(url.is_private());
+        assert_eq!(url.data_type(), DataType::Register);
+        assert_eq!(url.content_type(), ContentType::FilesContainer);
+        assert_eq!(url.to_string(), xorurl);
+        assert!(!SafeUrl::from_url(&public_xorurl)?.is_private());
+        Ok(())
+    }
+
     #[test]
     fn test_url_too_long() -> Result<()> {
         let xorurl =
@@ -11,13 +11,16 @@ use crate::subcommands::node::node_commander;
 #[cfg(feature = "data-network")]
 use crate::subcommands::{dog::dog_commander, files::files_commander, nrs::nrs_commander};
 use crate::{
-    operations::auth_and_connect::connect,
+    operations::auth_and_connect::{connect, read_credentials},
     operations::config::Config,
     subcommands::{
         cat::cat_commander,
         config::config_commander,
         keys::key_commander,
+        multimap::multimap_commander,
         networks::networks_commander,
+        publish::{export_batch, publish_commander},
+        register::register_commander,
         setup::setup_commander,
         update::update_commander,
         wallet::wallet_commander,
@@ -26,7 +29,7 @@ use crate::{
     },
 };
 use clap::{AppSettings::ColoredHelp, Parser};
-use color_eyre::{eyre::eyre, Result};
+use color_eyre::{eyre::eyre, Help, Result};
 use sn_api::{Safe, XorUrlBase};
 use std::path::PathBuf;
 use tracing::{debug, warn};
@@ -46,6 +49,11 @@ pub struct CmdArgs {
     /// Perform a dry run of the command. No data will be written.
     #[clap(short = 'n', long = "dry-run", global(true))]
     dry: bool,
+    /// Export the data written by a dry run to a batch file at the given path, instead of
+    /// discarding it, so it can be published later on, possibly from another machine,
+    /// with 'safe publish'. The writes are signed with the credentials set up for safe.
+    #[clap(long = "export-batch", global(true), requires = "dry")]
+    export_batch: Option<PathBuf>,
     /// Output data serialisation: [json, jsoncompact, yaml]
     #[clap(short = 'o', long = "output", global(true))]
     output_fmt: Option<OutputFmt>,
@@ -70,6 +78,21 @@ pub async fn run() -> Result<()> {
 
     let result = process_commands(&mut safe, args, &mut config).await;
 
+    // Keep the payment DBCs left, including the change of the payments made, for later commands
+    if safe.is_connected() {
+        match safe.payment_dbcs().await {
+            Ok(payment_dbcs) => {
+                if let Err(err) = config.write_payment_dbcs(&payment_dbcs).await {
+                    warn!(
+                        "Failed to keep the payment DBCs at '{}': {err:?}",
+                        config.payment_dbcs_path().display()
+                    );
+                }
+            }
+            Err(err) => warn!("Failed to read the payment DBCs left: {err:?}"),
+        }
+    }
+
     // If we were connected to a network, cache the up to date network contacts
     // to disk before exiting
     if safe.is_connected() {
@@ -154,30 +177,53 @@ async fn process_commands(mut safe: &mut Safe, args: CmdArgs, config: &mut Confi
         other => {
             // Set dry run mode in Safe instance as per arg provide
             safe.dry_run_mode = args.dry;
+            safe.uploads_journal_dir = Some(config.uploads_journal_dir());
+            safe.client_cache_dir = Some(config.client_cache_dir());
             // We treat these commands separatelly since we use the credentials if they are
             // available to connect to the network with them (unless dry-run was set),
             // otherwise the connection created will be with read-only access and some
             // of these commands will fail if they require write access.
             if !safe.dry_run_mode {
                 connect(safe, config).await?;
+                safe.add_payment_dbcs(config.read_payment_dbcs().await?)
+                    .await?;
+            }
+            if args.export_batch.is_some() {
+                let keypair = read_credentials(config)?.1.ok_or_else(|| {
+                    eyre!("Exporting a batch requires credentials to sign the writes with")
+                        .suggestion("Use 'safe keys create --for-cli' to set them up.")
+                })?;
+                safe.start_write_batch(keypair);
             }
 
             #[cfg(not(feature = "data-network"))]
-            return match other {
+            let result = match other {
                 SubCommands::Cat(cmd) => cat_commander(cmd, output_fmt, safe).await,
+                SubCommands::Register(cmd) => register_commander(cmd, output_fmt, safe).await,
+                SubCommands::Multimap(cmd) => multimap_commander(cmd, output_fmt, safe).await,
                 SubCommands::Wallet(cmd) => wallet_commander(cmd, output_fmt, safe, config).await,
+                SubCommands::Publish(cmd) => publish_commander(cmd, output_fmt, safe).await,
                 _ => Err(eyre!("Unknown safe subcommand")),
             };
 
             #[cfg(feature = "data-network")]
-            return match other {
+            let result = match other {
                 SubCommands::Cat(cmd) => cat_commander(cmd, output_fmt, safe).await,
                 SubCommands::Dog(cmd) => dog_commander(cmd, output_fmt, safe).await,
                 SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe).await,
                 SubCommands::Nrs(cmd) => nrs_commander(cmd, output_fmt, safe).await,
+                SubCommands::Register(cmd) => register_commander(cmd, output_fmt, safe).await,
+                SubCommands::Multimap(cmd) => multimap_commander(cmd, output_fmt, safe).await,
                 SubCommands::Wallet(cmd) => wallet_commander(cmd, output_fmt, safe, config).await,
+                SubCommands::Publish(cmd) => publish_commander(cmd, output_fmt, safe).await,
                 _ => Err(eyre!("Unknown safe subcommand")),
             };
+
+            result?;
+            match (args.export_batch, safe.take_write_batch()) {
+                (Some(path), Some(batch)) => export_batch(batch, &path, output_fmt),
+                _ => Ok(()),
+            }
         }
     }
 }
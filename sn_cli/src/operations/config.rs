@@ -14,7 +14,7 @@ use comfy_table::Table;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use sn_api::{Safe, SectionTree, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
-use sn_dbc::Owner;
+use sn_dbc::{Dbc, Owner};
 use std::{
     collections::BTreeMap,
     default::Default,
@@ -28,6 +28,7 @@ use tracing::debug;
 use url::Url;
 
 const REMOTE_RETRY_COUNT: usize = 3;
+const PAYMENT_DBCS_FILENAME: &str = "payment_dbcs";
 
 #[derive(Deserialize, Debug, Serialize, Clone)]
 pub enum NetworkInfo {
@@ -160,6 +161,91 @@ impl Config {
         Ok(config)
     }
 
+    /// Directory where the journals of the files being uploaded are kept,
+    /// so that `files put --resume` can resume their upload if it gets interrupted.
+    pub fn uploads_journal_dir(&self) -> PathBuf {
+        let mut pb = self.cli_config_path.clone();
+        pb.pop();
+        pb.join("uploads")
+    }
+
+    /// Directory where the chunks and Registers retrieved from the network are cached,
+    /// so that they don't need to be retrieved again by later commands.
+    pub fn client_cache_dir(&self) -> PathBuf {
+        let mut pb = self.cli_config_path.clone();
+        pb.pop();
+        pb.join("cache")
+    }
+
+    /// File where the bearer DBCs the CLI pays for storing data with are kept, one hex-encoded
+    /// DBC per line, along with the change of the payments made with them so far.
+    pub fn payment_dbcs_path(&self) -> PathBuf {
+        let mut pb = self.cli_config_path.clone();
+        pb.pop();
+        pb.join(PAYMENT_DBCS_FILENAME)
+    }
+
+    /// Read the DBCs the CLI pays for storing data with, if any.
+    pub async fn read_payment_dbcs(&self) -> Result<Vec<Dbc>> {
+        let path = self.payment_dbcs_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let content = fs::read_to_string(&path)
+            .await
+            .wrap_err_with(|| format!("Unable to read payment DBCs from '{}'", path.display()))?;
+
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                Dbc::from_hex(line.trim()).wrap_err_with(|| {
+                    format!("Invalid hex-encoded DBC found in '{}'", path.display())
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the DBCs the CLI pays for storing data with.
+    pub async fn write_payment_dbcs(&self, dbcs: &[Dbc]) -> Result<()> {
+        let path = self.payment_dbcs_path();
+        let mut content = String::new();
+        for dbc in dbcs {
+            content.push_str(&dbc.to_hex()?);
+            content.push('\n');
+        }
+
+        let mut temp_file = NamedTempFile::new_in(path.parent().unwrap_or(&path))
+            .wrap_err("Error creating temp file")?;
+        temp_file
+            .write_all(content.as_bytes())
+            .wrap_err_with(|| format!("Unable to write payment DBCs to '{}'", path.display()))?;
+        fs::rename(temp_file.path(), &path)
+            .await
+            .wrap_err_with(|| format!("Unable to write payment DBCs to '{}'", path.display()))?;
+
+        debug!("{} payment DBCs written to {}", dbcs.len(), path.display());
+        Ok(())
+    }
+
+    /// Add a bearer DBC to those the CLI pays for storing data with.
+    pub async fn add_payment_dbc(&self, dbc: Dbc) -> Result<()> {
+        if !dbc.is_bearer() {
+            return Err(
+                eyre!("Only bearer DBCs can be used to pay for storing data")
+                    .suggestion("Use 'safe wallet reissue' to reissue a bearer DBC from a wallet."),
+            );
+        }
+        let mut dbcs = self.read_payment_dbcs().await?;
+        if dbcs
+            .iter()
+            .all(|held| held.public_key() != dbc.public_key())
+        {
+            dbcs.push(dbc);
+        }
+        self.write_payment_dbcs(&dbcs).await
+    }
+
     /// Sync settings and the network_contacts_dir
     pub async fn sync(&mut self) -> Result<()> {
         let mut dir_files_checklist: BTreeMap<String, bool> = BTreeMap::new();
@@ -20,8 +20,9 @@ use xor_name::{XorName, XOR_NAME_LEN};
 
 use safenode::safe_node_client::SafeNodeClient;
 use safenode::{
-    NodeEventsRequest, NodeInfoRequest, RestartRequest, SectionMembersRequest, StopRequest,
-    UpdateRequest,
+    DkgSessionsRequest, DrainRequest, FaultScoresRequest, NetworkKnowledgeRequest,
+    NodeEventsRequest, NodeInfoRequest, RestartRequest, SectionAuthority, SectionMember,
+    SectionMembersRequest, StopRequest, StoredDataRequest, UpdateRequest,
 };
 
 // this would include code generated from .proto file
@@ -96,6 +97,222 @@ pub async fn section_members(addr: SocketAddr, output_fmt: OutputFmt) -> Result<
     Ok(())
 }
 
+pub async fn stored_data(
+    addr: SocketAddr,
+    names: Vec<XorName>,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
+    // the data is fetched a page at a time, as sized by the node
+    let mut items = vec![];
+    loop {
+        let response = client
+            .stored_data(Request::new(StoredDataRequest {
+                names: names.iter().map(|name| name.0.to_vec()).collect(),
+                offset: items.len() as u64,
+                limit: 0,
+            }))
+            .await?;
+        let page = response.into_inner();
+        let done = page.stored_data.is_empty()
+            || items.len() + page.stored_data.len() >= page.total as usize;
+        items.extend(page.stored_data);
+        if done {
+            break;
+        }
+    }
+    let stored_data = items.iter().map(|data| {
+        (
+            xorname_from_bytes(&data.name),
+            data.data_type.clone(),
+            data.size,
+        )
+    });
+
+    if OutputFmt::Pretty == output_fmt {
+        let stored_data_len = stored_data.len();
+        let mut table = Table::new();
+        table.add_row(&vec!["Name", "Type", "Size (bytes)"]);
+        for (name, data_type, size) in stored_data {
+            table.add_row(&vec![format!("{name:?}"), data_type, size.to_string()]);
+        }
+
+        println!("The node holds {stored_data_len} data items:");
+        println!("{table}");
+    } else {
+        let stored_data_vec: Vec<_> = stored_data
+            .map(|(name, data_type, size)| (xorname_to_hex(&name), data_type, size))
+            .collect();
+        println!("{}", serialise_output(&stored_data_vec, output_fmt));
+    }
+
+    Ok(())
+}
+
+pub async fn network_knowledge(addr: SocketAddr, output_fmt: OutputFmt) -> Result<()> {
+    let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
+    let response = client
+        .network_knowledge(Request::new(NetworkKnowledgeRequest {}))
+        .await?;
+    let knowledge = response.get_ref();
+    let section_auth = knowledge
+        .section_auth
+        .as_ref()
+        .map(section_authority_output);
+    let section_tree: Vec<_> = knowledge
+        .section_tree
+        .iter()
+        .map(section_authority_output)
+        .collect();
+    let sections_dag: Vec<_> = knowledge
+        .sections_dag
+        .iter()
+        .map(|key| {
+            (
+                hex::encode(&key.key),
+                key.parent_key.as_ref().map(hex::encode),
+            )
+        })
+        .collect();
+
+    if OutputFmt::Pretty == output_fmt {
+        if let Some((prefix, key, elders)) = section_auth {
+            println!("Our section: prefix '{prefix}', key {key}");
+            println!("Our section Elders:");
+            for (name, addr) in elders {
+                println!("  {name} ({addr})");
+            }
+        }
+        match knowledge.membership_generation {
+            Some(generation) => println!("Membership generation: {generation}"),
+            None => println!("Membership generation: unknown, the node is not an Elder"),
+        }
+
+        let mut table = Table::new();
+        table.add_row(&vec!["Prefix", "Section key", "Elders"]);
+        for (prefix, key, elders) in section_tree {
+            table.add_row(&vec![prefix, key, elders.len().to_string()]);
+        }
+        println!("Sections in the section tree:");
+        println!("{table}");
+
+        let mut table = Table::new();
+        table.add_row(&vec!["Section key", "Parent key"]);
+        for (key, parent_key) in sections_dag {
+            table.add_row(&vec![key, parent_key.unwrap_or_else(|| "-".to_string())]);
+        }
+        println!("Keys in the sections DAG:");
+        println!("{table}");
+    } else {
+        println!(
+            "{}",
+            serialise_output(
+                &(
+                    section_auth,
+                    knowledge.membership_generation,
+                    section_tree,
+                    sections_dag
+                ),
+                output_fmt
+            )
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn dkg_sessions(addr: SocketAddr, output_fmt: OutputFmt) -> Result<()> {
+    let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
+    let response = client
+        .dkg_sessions(Request::new(DkgSessionsRequest {}))
+        .await?;
+    let sessions = response.get_ref().dkg_sessions.iter().map(|session| {
+        (
+            session.prefix.clone(),
+            session.section_chain_len,
+            session.membership_generation,
+            members_output(&session.elders),
+        )
+    });
+
+    if OutputFmt::Pretty == output_fmt {
+        let sessions_len = sessions.len();
+        let mut table = Table::new();
+        table.add_row(&vec![
+            "Prefix",
+            "Section chain length",
+            "Membership generation",
+            "Elder candidates",
+        ]);
+        for (prefix, chain_len, generation, elders) in sessions {
+            let elders: Vec<_> = elders.into_iter().map(|(name, _)| name).collect();
+            table.add_row(&vec![
+                prefix,
+                chain_len.to_string(),
+                generation.to_string(),
+                elders.join("\n"),
+            ]);
+        }
+
+        println!("The node is taking part in {sessions_len} pending DKG sessions:");
+        println!("{table}");
+    } else {
+        let sessions_vec: Vec<_> = sessions.collect();
+        println!("{}", serialise_output(&sessions_vec, output_fmt));
+    }
+
+    Ok(())
+}
+
+pub async fn fault_scores(addr: SocketAddr, output_fmt: OutputFmt) -> Result<()> {
+    let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
+    let response = client
+        .fault_scores(Request::new(FaultScoresRequest {}))
+        .await?;
+    let scores = response.get_ref().fault_scores.iter().map(|scores| {
+        (
+            xorname_from_bytes(&scores.node_name),
+            [
+                scores.communication,
+                scores.dkg,
+                scores.elder_voting,
+                scores.knowledge,
+                scores.op,
+                scores.probe,
+            ],
+        )
+    });
+
+    if OutputFmt::Pretty == output_fmt {
+        let scores_len = scores.len();
+        let mut table = Table::new();
+        table.add_row(&vec![
+            "Node name",
+            "Communication",
+            "DKG",
+            "Elder voting",
+            "Knowledge",
+            "Op",
+            "Probe",
+        ]);
+        for (name, node_scores) in scores {
+            let mut row = vec![format!("{name:?}")];
+            row.extend(node_scores.iter().map(|score| format!("{score:.2}")));
+            table.add_row(&row);
+        }
+
+        println!("The node is tracking the fault scores of {scores_len} nodes:");
+        println!("{table}");
+    } else {
+        let scores_vec: Vec<_> = scores
+            .map(|(name, node_scores)| (xorname_to_hex(&name), node_scores))
+            .collect();
+        println!("{}", serialise_output(&scores_vec, output_fmt));
+    }
+
+    Ok(())
+}
+
 pub async fn node_events(addr: SocketAddr, output_fmt: OutputFmt) -> Result<()> {
     let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
     let response = client
@@ -139,6 +356,18 @@ pub async fn node_stop(addr: SocketAddr, delay_millis: u64) -> Result<()> {
     Ok(())
 }
 
+pub async fn node_drain(addr: SocketAddr, timeout_millis: u64) -> Result<()> {
+    let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
+    let _response = client
+        .drain(Request::new(DrainRequest { timeout_millis }))
+        .await?;
+    println!(
+        "Node successfully received the request to drain, it will stop once its data is handed off, or in {:?} at the latest",
+        Duration::from_millis(timeout_millis)
+    );
+    Ok(())
+}
+
 pub async fn node_update(addr: SocketAddr, delay_millis: u64) -> Result<()> {
     let mut client = SafeNodeClient::connect(format!("http://{addr}")).await?;
     let _response = client
@@ -151,6 +380,28 @@ pub async fn node_update(addr: SocketAddr, delay_millis: u64) -> Result<()> {
     Ok(())
 }
 
+// Prefix, hex encoded section key, and elders of a section
+fn section_authority_output(sap: &SectionAuthority) -> (String, String, Vec<(String, String)>) {
+    (
+        sap.prefix.clone(),
+        hex::encode(&sap.section_key),
+        members_output(&sap.elders),
+    )
+}
+
+// Hex encoded name and address of each member
+fn members_output(members: &[SectionMember]) -> Vec<(String, String)> {
+    members
+        .iter()
+        .map(|member| {
+            (
+                xorname_to_hex(&xorname_from_bytes(&member.node_name)),
+                member.addr.clone(),
+            )
+        })
+        .collect()
+}
+
 fn xorname_from_bytes(bytes: &[u8]) -> XorName {
     let mut xorname = [0u8; XOR_NAME_LEN];
     bytes.iter().enumerate().for_each(|(i, b)| xorname[i] = *b);
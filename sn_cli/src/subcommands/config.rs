@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::OutputFmt;
+use super::{helpers::read_dbc_from_arg_or_stdin, OutputFmt};
 use crate::operations::config::{Config, NetworkInfo};
 
 use clap::Subcommand;
@@ -37,6 +37,14 @@ pub enum SettingAddCmd {
         /// Local path or a remote URL to fetch the network map from
         contacts_file_location: String,
     },
+    #[clap(name = "payment-dbc")]
+    /// Add a bearer DBC to those paying for storing data, e.g. one reissued from a wallet.
+    /// The change of the payments made with it is kept for later payments.
+    PaymentDbc {
+        /// A path to a file containing hex encoded DBC data, or you can supply the data directly.
+        /// If not provided, the data is read from stdin.
+        dbc: Option<String>,
+    },
     // #[clap(name = "contact")]
     // Contact {
     //    /// Contact friendly name
@@ -80,6 +88,14 @@ pub async fn config_commander(cmd: Option<ConfigSubCommands>, config: &mut Confi
                     .await?;
             }
         }
+        Some(ConfigSubCommands::Add(SettingAddCmd::PaymentDbc { dbc })) => {
+            let dbc = read_dbc_from_arg_or_stdin(dbc)?;
+            config.add_payment_dbc(dbc).await?;
+            println!(
+                "DBC added to those paying for storing data, kept at '{}'",
+                config.payment_dbcs_path().display()
+            );
+        }
         // Some(ConfigSubCommands::Add(SettingAddCmd::Contact { name, safeid })) => {}
         Some(ConfigSubCommands::Remove(SettingRemoveCmd::Network { network_name })) => {
             config.remove_network(&network_name).await?
@@ -12,7 +12,7 @@ use super::{
     files_get::{process_get_command, FileExistsAction, ProgressIndicator},
     helpers::{
         gen_processed_files_table, get_from_arg_or_stdin, get_from_stdin, get_target_url, if_tty,
-        notice_dry_run, parse_stdin_arg, pluralize, serialise_output,
+        notice_dry_run, parse_stdin_arg, pluralize, serialise_output, FilesProgressBar,
     },
     OutputFmt,
 };
@@ -23,7 +23,7 @@ use color_eyre::{eyre::bail, eyre::eyre, Result};
 use comfy_table::Table;
 use serde::Serialize;
 use sn_api::{
-    files::{FilesMap, ProcessedFiles},
+    files::{FilesMap, FilesMapDiff, ProcessedFiles},
     nrs::VersionHash,
     resolver::SafeData,
     Safe, SafeUrl, XorUrl,
@@ -107,6 +107,10 @@ pub enum FilesSubCommands {
         /// Follow symlinks
         #[clap(short = 'l', long = "follow-links")]
         follow_links: bool,
+        /// Resume the upload of those files whose previous upload was interrupted,
+        /// sending only the chunks which were not stored yet
+        #[clap(long = "resume")]
+        resume: bool,
     },
     /// Get a file or folder from the SAFE Network
     Get {
@@ -118,7 +122,7 @@ pub enum FilesSubCommands {
         #[clap(short = 'e', long = "exists", possible_values = &["ask", "preserve", "overwrite"], default_value="ask")]
         exists: FileExistsAction,
         /// How to display progress.
-        #[clap(short = 'i', long = "progress", possible_values = &["text", "none"], default_value="text")]
+        #[clap(short = 'i', long = "progress", possible_values = &["bars", "text", "none"], default_value="text")]
         progress: ProgressIndicator,
         /// Preserves modification times, access times, and modes from the original file
         #[clap(short = 'p', long = "preserve")]
@@ -185,6 +189,21 @@ pub enum FilesSubCommands {
         /// The target FilesContainer to list files from, optionally including a path (default is '/')
         target: Option<String>,
     },
+    #[clap(name = "log")]
+    /// List the versions of a FilesContainer, from the latest to the first one, along with the
+    /// files added, updated and removed by each of them
+    Log {
+        /// The target FilesContainer to list the versions of
+        target: Option<String>,
+    },
+    #[clap(name = "diff")]
+    /// Show the files added, updated and removed between two versions of FilesContainers
+    Diff {
+        /// The FilesContainer to compare from, which may target a specific version
+        from: String,
+        /// The FilesContainer to compare to, which may target a specific version
+        to: String,
+    },
     #[clap(name = "tree")]
     /// Recursively list files found in an existing FilesContainer on the network
     Tree {
@@ -207,14 +226,22 @@ pub async fn files_commander(
             dst,
             recursive,
             follow_links,
+            resume,
         } => {
             // create FilesContainer from a given path to local files/folders
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
-            let (files_container_xorurl, processed_files, _) = safe
+            let mut safe = safe.clone();
+            safe.resume_uploads = resume;
+            let progress_bar = upload_progress_bar(&safe, output_fmt);
+            let result = safe
                 .files_container_create_from(&location, dst.as_deref(), recursive, follow_links)
-                .await?;
+                .await;
+            if let Some(progress_bar) = progress_bar {
+                progress_bar.finish();
+            }
+            let (files_container_xorurl, processed_files, _) = result?;
 
             // Now let's just print out a list of the files uploaded/processed
             if OutputFmt::Pretty == output_fmt {
@@ -246,7 +273,8 @@ pub async fn files_commander(
                 notice_dry_run();
             }
             // Update the FilesContainer on the Network
-            let (content, processed_files) = safe
+            let progress_bar = upload_progress_bar(safe, output_fmt);
+            let result = safe
                 .files_container_sync(
                     &location,
                     &target_url.to_string(),
@@ -255,7 +283,11 @@ pub async fn files_commander(
                     delete,
                     update_nrs,
                 )
-                .await?;
+                .await;
+            if let Some(progress_bar) = progress_bar {
+                progress_bar.finish();
+            }
+            let (content, processed_files) = result?;
             let version = content.map(|(version, _)| version);
 
             // Now let's just print out a list of the files synced/processed
@@ -393,6 +425,77 @@ pub async fn files_commander(
 
             Ok(())
         }
+        FilesSubCommands::Log { target } => {
+            let target_url =
+                get_from_arg_or_stdin(target, Some("...awaiting target URl from STDIN"))?;
+
+            debug!("Getting versions of container {:?}", target_url);
+            let versions = safe.files_container_versions(&target_url).await?;
+            let files_maps: HashMap<_, _> = versions
+                .iter()
+                .map(|version| (version.version, &version.files_map))
+                .collect();
+
+            // Each version is compared with the first of those it succeeds
+            let log: Vec<(VersionHash, Vec<VersionHash>, FilesMapDiff)> = versions
+                .iter()
+                .map(|version| {
+                    let previous: Vec<VersionHash> = version.previous.iter().copied().collect();
+                    let empty = FilesMap::default();
+                    let previous_files_map = previous
+                        .first()
+                        .and_then(|v| files_maps.get(v).copied())
+                        .unwrap_or(&empty);
+                    let diff = FilesMapDiff::new(previous_files_map, &version.files_map);
+                    (version.version, previous, diff)
+                })
+                .collect();
+
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "FilesContainer at \"{target_url}\" has {} {}",
+                    log.len(),
+                    pluralize("version", "versions", log.len() as u64)
+                );
+                for (version, previous, diff) in log {
+                    let previous_str = match previous.as_slice() {
+                        [] => "first version".to_string(),
+                        [single] => format!("after {single}"),
+                        [first, others @ ..] => format!(
+                            "merging {}, compared with {first}",
+                            others
+                                .iter()
+                                .map(|v| v.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    };
+                    println!("\nVersion {version} ({previous_str})");
+                    println!("{}", gen_files_map_diff_table(&diff));
+                }
+            } else {
+                println!("{}", serialise_output(&(target_url, log), output_fmt));
+            }
+
+            Ok(())
+        }
+        FilesSubCommands::Diff { from, to } => {
+            debug!("Comparing containers {:?} and {:?}", from, to);
+            let diff = safe.files_container_diff(&from, &to).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                if diff.is_empty() {
+                    println!("No differences found between \"{from}\" and \"{to}\"");
+                } else {
+                    println!("Changes from \"{from}\" to \"{to}\":");
+                    println!("{}", gen_files_map_diff_table(&diff));
+                }
+            } else {
+                println!("{}", serialise_output(&(from, to, diff), output_fmt));
+            }
+
+            Ok(())
+        }
         FilesSubCommands::Tree { target, details } => {
             process_tree_command(safe, target, details, output_fmt).await
         }
@@ -406,6 +509,41 @@ pub async fn files_commander(
     }
 }
 
+// Progress bar of the files uploads, only rendered for the human readable output format.
+fn upload_progress_bar(safe: &Safe, output_fmt: OutputFmt) -> Option<FilesProgressBar> {
+    if OutputFmt::Pretty == output_fmt {
+        FilesProgressBar::uploads(safe)
+    } else {
+        None
+    }
+}
+
+// Generates a table with the items added (+), updated (*) and removed (-) in a FilesMapDiff,
+// along with their size and last modification time, those of the newer item when updated.
+fn gen_files_map_diff_table(diff: &FilesMapDiff) -> Table {
+    let mut table = Table::new();
+    table.add_row(&vec!["", "SIZE", "MODIFIED", "NAME"]);
+    let changes = diff
+        .added
+        .iter()
+        .map(|(name, info)| ("+", name, info))
+        .chain(
+            diff.updated
+                .iter()
+                .map(|(name, (_, info))| ("*", name, info)),
+        )
+        .chain(diff.removed.iter().map(|(name, info)| ("-", name, info)));
+    for (change_sign, name, info) in changes {
+        table.add_row(&vec![
+            change_sign,
+            info.get("size").map_or("", String::as_str),
+            info.get("modified").map_or("", String::as_str),
+            name,
+        ]);
+    }
+    table
+}
+
 // processes the `safe files tree` command.
 async fn process_tree_command(
     safe: &Safe,
@@ -9,24 +9,18 @@
 #![cfg(feature = "data-network")]
 
 use super::{
-    helpers::{div_or, pluralize, processed_files_err_report, prompt_user},
+    helpers::{div_or, pluralize, processed_files_err_report, prompt_user, FilesProgressBar},
     OutputFmt,
 };
-use bytes::Buf;
 use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
 use console::Term;
 use sn_api::{
     files::{FilesMap, GetAttr},
-    resolver::Range,
     resolver::SafeData,
-    DataType, Result as ApiResult, Safe, SafeUrl, XorUrl,
-};
-use std::{
-    collections::BTreeMap,
-    fs,
-    io::{BufWriter, Write},
-    path::Path,
+    Result as ApiResult, Safe, SafeUrl, XorUrl,
 };
+use std::{collections::BTreeMap, fs, path::Path};
+use tokio::io::{AsyncWriteExt, BufWriter};
 use tracing::{debug, info, trace, warn};
 
 /// # Retrieval/write status for current file and overall transfer.
@@ -76,6 +70,7 @@ impl std::str::FromStr for FileExistsAction {
 // What type of Progress Indicator to display.
 #[derive(Debug, Default)]
 pub enum ProgressIndicator {
+    Bars,
     #[default]
     Text,
     None,
@@ -86,6 +81,7 @@ impl std::str::FromStr for ProgressIndicator {
     type Err = String;
     fn from_str(str: &str) -> Result<Self, String> {
         match str {
+            "bars" => Ok(Self::Bars),
             "text" => Ok(Self::Text),
             "none" => Ok(Self::None),
             other => Err(format!(
@@ -127,76 +123,89 @@ pub async fn process_get_command(
     let mut overwrites: u64 = 0;
     let mut preserves: u64 = 0;
 
-    let (_version, processed_files) =
-        files_container_get_files(safe, &source, &str_path, |status| {
-            let mut overwrite = true;
-            let mut mystatus = status.clone();
-
-            if status.file_bytes_written == 0 {
-                // It is an error/warning if the dst path attempts to use
-                // an existing file as a directory. But other files should
-                // still be written.  eg:
-                // $ mkdir -p /tmp/a/b/c && touch /tmp/a/file.txt
-                // $ mkdir /tmp/target && touch /tmp/target/b   (b is a file)
-                // $ cp -r /tmp/a/* /tmp/target
-                //    cp: cannot overwrite non-directory '/tmp/target/b' with directory '/tmp/a/b'
-                // $ ls -l /tmp/target/
-                //      total 0
-                //      -rw-rw-r-- 1 user user 0 Mar 31 14:38 b         (b still a file)
-                //      -rw-rw-r-- 1 user user 0 Mar 31 14:38 file.txt  (other file written)
-                //
-                // TBD: Should FileExistsAction apply to this case?
-                //      unix cp does not provide any flag/option/prompt to permit this
-                //      and it always emits a warning.  So I am satisfied with this
-                //      working the same way, at least for now.
-                let dirpath = if status.file_type == "inode/directory" {
-                    Some(status.path_local)
-                } else {
-                    status.path_local.parent()
-                };
-                if let Some(parent) = dirpath {
-                    if let Some(filepath) = path_contains_file(parent) {
-                        let msg = format!(
-                            "cannot overwrite non-directory '{}' with directory in '{}'",
-                            filepath.display(),
-                            status.path_local.display()
-                        );
-
-                        warn!("Skipping file \"{}\". {}", status.path_local.display(), msg);
-                        if atty::is(atty::Stream::Stderr) {
-                            eprintln!("Warning: {msg}");
-                        }
-                        overwrite = false;
+    let progress_bar = match progress {
+        ProgressIndicator::Bars => FilesProgressBar::downloads(safe),
+        ProgressIndicator::Text | ProgressIndicator::None => None,
+    };
+
+    let result = files_container_get_files(safe, &source, &str_path, |status| {
+        let mut overwrite = true;
+        let mut mystatus = status.clone();
+
+        if status.file_bytes_written == 0 {
+            // It is an error/warning if the dst path attempts to use
+            // an existing file as a directory. But other files should
+            // still be written.  eg:
+            // $ mkdir -p /tmp/a/b/c && touch /tmp/a/file.txt
+            // $ mkdir /tmp/target && touch /tmp/target/b   (b is a file)
+            // $ cp -r /tmp/a/* /tmp/target
+            //    cp: cannot overwrite non-directory '/tmp/target/b' with directory '/tmp/a/b'
+            // $ ls -l /tmp/target/
+            //      total 0
+            //      -rw-rw-r-- 1 user user 0 Mar 31 14:38 b         (b still a file)
+            //      -rw-rw-r-- 1 user user 0 Mar 31 14:38 file.txt  (other file written)
+            //
+            // TBD: Should FileExistsAction apply to this case?
+            //      unix cp does not provide any flag/option/prompt to permit this
+            //      and it always emits a warning.  So I am satisfied with this
+            //      working the same way, at least for now.
+            let dirpath = if status.file_type == "inode/directory" {
+                Some(status.path_local)
+            } else {
+                status.path_local.parent()
+            };
+            if let Some(parent) = dirpath {
+                if let Some(filepath) = path_contains_file(parent) {
+                    let msg = format!(
+                        "cannot overwrite non-directory '{}' with directory in '{}'",
+                        filepath.display(),
+                        status.path_local.display()
+                    );
+
+                    warn!("Skipping file \"{}\". {}", status.path_local.display(), msg);
+                    if atty::is(atty::Stream::Stderr) {
+                        eprintln!("Warning: {msg}");
                     }
+                    overwrite = false;
                 }
-                if status.path_local.exists() && overwrite {
-                    overwrite = match exists {
-                        FileExistsAction::Overwrite => true,
-                        FileExistsAction::Preserve => false,
-                        FileExistsAction::Ask => {
-                            let prompt = format!("overwrite '{}'? ", status.path_local.display());
-                            prompt_yes_no(&prompt, "Y")
-                        }
-                    };
-                    if overwrite {
-                        overwrites += 1;
-                    } else {
-                        preserves += 1;
-                        mystatus.total_transfer_bytes -= mystatus.file_size;
+            }
+            if status.path_local.exists() && overwrite {
+                overwrite = match exists {
+                    FileExistsAction::Overwrite => true,
+                    FileExistsAction::Preserve => false,
+                    FileExistsAction::Ask => {
+                        let prompt = format!("overwrite '{}'? ", status.path_local.display());
+                        prompt_yes_no(&prompt, "Y")
                     }
+                };
+                if overwrite {
+                    overwrites += 1;
+                } else {
+                    preserves += 1;
+                    mystatus.total_transfer_bytes -= mystatus.file_size;
                 }
             }
-            if overwrite {
-                match progress {
-                    ProgressIndicator::Text => {
-                        print_status(status);
+        }
+        if overwrite {
+            match progress {
+                ProgressIndicator::Bars => {
+                    if let Some(progress_bar) = &progress_bar {
+                        progress_bar.set_total_bytes(mystatus.total_transfer_bytes);
                     }
-                    ProgressIndicator::None => {}
                 }
+                ProgressIndicator::Text => {
+                    print_status(status);
+                }
+                ProgressIndicator::None => {}
             }
-            overwrite
-        })
-        .await?;
+        }
+        overwrite
+    })
+    .await;
+    if let Some(progress_bar) = progress_bar {
+        progress_bar.finish();
+    }
+    let (_version, processed_files) = result?;
 
     if processed_files.is_empty() && preserves == 0 {
         bail!("Path '{}' not found", path.display());
@@ -611,51 +620,40 @@ fn denormalize_slashes(p: &str) -> String {
 async fn download_file_from_net(safe: &Safe, xorurl: &str, path: &Path, size: u64) -> Result<u64> {
     debug!("downloading file {} to {}", xorurl, path.display());
 
-    // TODO: download the file by concurrently (spawning tasks/threads) pulling chunks.
-    // The chunk_size can be based on https://stackoverflow.com/questions/8803515/optimal-buffer-size-for-write2
-    // which can be 4096 to match common disk block size, but that seems a bit small for the
-    // network, so I multiplied by 16. Perhaps should make it a param so caller can decide.
-    let mut rcvd: u64 = 0;
-    let mut bytes_written: u64 = 0;
-
-    let fh = file_create(path)?;
+    let fh = tokio::fs::File::from_std(file_create(path)?);
     let mut stream = BufWriter::new(fh);
 
-    // gets public or private, based on xorurl type
-    let filedata = files_get(safe, xorurl, None).await?;
-    bytes_written += stream_write(&mut stream, &filedata, path)? as u64;
-    rcvd += filedata.len() as u64;
-    trace!("received {} bytes of {}", rcvd, size,);
+    // the file content is written to the stream as it's fetched, batch by batch of chunks,
+    // so the file is never held in memory as a whole.
+    let bytes_written = safe.files_get_to_writer(xorurl, &mut stream).await?;
+    trace!("received {} bytes of {}", bytes_written, size,);
 
     // Close may generate an error, so we do a flush/sync first to detect such.
     // see https://github.com/rust-lang/rust/pull/63410#issuecomment-519965351
-    let fh = bufwriter_into_inner(stream, path)?;
-    file_sync_all(&fh, path)?;
+    let fh = bufwriter_into_inner(stream, path).await?;
+    file_sync_all(&fh, path).await?;
 
     Ok(bytes_written)
 }
 
 // syncs file to filesystem.
-fn file_sync_all(f: &fs::File, path: &Path) -> Result<()> {
+async fn file_sync_all(f: &tokio::fs::File, path: &Path) -> Result<()> {
     f.sync_all()
+        .await
         .with_context(|| format!("Error syncing file: \"{}\"", path.display(),))
 }
 
 // causes BufWriter to flush() file.
-fn bufwriter_into_inner<W: Write>(w: BufWriter<W>, path: &Path) -> Result<W> {
-    match w.into_inner() {
-        Ok(inner) => Ok(inner),
+async fn bufwriter_into_inner(
+    mut w: BufWriter<tokio::fs::File>,
+    path: &Path,
+) -> Result<tokio::fs::File> {
+    match w.flush().await {
+        Ok(()) => Ok(w.into_inner()),
         Err(err) => Err(eyre!("Error flushing file \"{}\": {}", path.display(), err)),
     }
 }
 
-// Writes data to a file/stream.
-fn stream_write(writer: &mut dyn Write, data: &[u8], path: &Path) -> Result<usize> {
-    writer
-        .write(data)
-        .with_context(|| format!("Error writing to file: \"{}\"", path.display(),))
-}
-
 // Creates a file, ready for writing.
 fn file_create(path: &Path) -> Result<fs::File> {
     fs::File::create(path).with_context(|| format!("Couldn't create file: \"{}\"", path.display(),))
@@ -672,15 +670,3 @@ fn create_dir_all(dir_path: &Path) -> Result<()> {
     fs::create_dir_all(dir_path)
         .with_context(|| format!("Couldn't create path: \"{}\"", dir_path.display(),))
 }
-
-/// # Get Public or Private file
-/// Get immutable files from the network.
-pub async fn files_get(safe: &Safe, url: &str, range: Range) -> Result<Vec<u8>> {
-    match SafeUrl::from_url(url)?.data_type() {
-        DataType::File => {
-            let bytes = safe.files_get(url, range).await?;
-            Ok(bytes.chunk().to_vec())
-        }
-        _ => Err(eyre!("URL target is not immutable data")),
-    }
-}
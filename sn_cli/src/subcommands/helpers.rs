@@ -9,26 +9,33 @@
 use super::OutputFmt;
 
 #[cfg(feature = "data-network")]
-use sn_api::nrs::NrsMap;
+use sn_api::{files::FileProgress, nrs::NrsMap};
 use sn_api::{
     files::{FilesMapChange, ProcessedFiles},
     multimap::Multimap,
+    register::EntryHash,
     wallet::Dbc,
     Safe, SafeUrl,
 };
 
 #[cfg(feature = "data-network")]
 use ansi_term::Style;
-use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
+use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Help, Result};
 use comfy_table::{Cell, CellAlignment, Table};
 #[cfg(feature = "data-network")]
+use indicatif::{ProgressBar, ProgressStyle};
+#[cfg(feature = "data-network")]
 use num_traits::Float;
 use serde::ser::Serialize;
-use std::io::{stdin, stdout, Read, Write};
+use std::{
+    io::{stdin, stdout, Read, Write},
+    path::Path,
+};
+#[cfg(feature = "data-network")]
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
 use tracing::{debug, warn};
 use xor_name::XorName;
 
-#[cfg(feature = "data-network")]
 // Warn the user about a dry-run being performed
 pub fn notice_dry_run() {
     println!("NOTE the operation is being performed in dry-run mode, therefore no changes are committed to the network.");
@@ -39,6 +46,29 @@ pub fn xorname_to_hex(xorname: &XorName) -> String {
     xorname.0.iter().map(|b| format!("{b:02x}")).collect()
 }
 
+// Parses a hex encoded XOR name
+pub fn parse_xorname(hex_str: &str) -> Result<XorName> {
+    let bytes: [u8; 32] = hex::decode(hex_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| eyre!("Invalid XOR name '{hex_str}', it must be 64 hex characters"))?;
+    Ok(XorName(bytes))
+}
+
+// Converts a register entry hash into a hex encoded string
+pub fn entry_hash_to_hex(hash: &EntryHash) -> String {
+    hex::encode(hash.0)
+}
+
+// Parses a hex encoded register entry hash
+pub fn parse_entry_hash(hex_str: &str) -> Result<EntryHash> {
+    let bytes: [u8; 32] = hex::decode(hex_str)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| eyre!("Invalid entry hash '{hex_str}', it must be 64 hex characters"))?;
+    Ok(EntryHash(bytes))
+}
+
 // Read the argument string from the STDIN if is not an arg provided
 pub fn get_from_arg_or_stdin(arg: Option<String>, message: Option<&str>) -> Result<String> {
     match arg {
@@ -64,6 +94,33 @@ pub fn get_from_arg_or_stdin(arg: Option<String>, message: Option<&str>) -> Resu
     }
 }
 
+// Read a DBC from the argument, either a path to a file containing hex encoded DBC data
+// or the data itself, or from the STDIN if is not an arg provided
+pub fn read_dbc_from_arg_or_stdin(arg: Option<String>) -> Result<Dbc> {
+    let dbc = if let Some(dbc) = arg {
+        let path = Path::new(&dbc);
+        if path.exists() {
+            if path.is_dir() {
+                return Err(eyre!("The path supplied refers to a directory.")
+                    .suggestion("A file path must be specified for the DBC data."));
+            }
+            let dbc_data = std::fs::read_to_string(path)?;
+            Dbc::from_hex(dbc_data.trim()).map_err(|e| {
+                eyre!(e.to_string()).suggestion(
+                    "This file does not appear to have DBC data. \
+                    Please select another file with valid hex-encoded DBC data.",
+                )
+            })?
+        } else {
+            Dbc::from_hex(&dbc)?
+        }
+    } else {
+        let dbc_hex = get_from_arg_or_stdin(arg, None)?;
+        Dbc::from_hex(dbc_hex.trim())?
+    };
+    Ok(dbc)
+}
+
 pub fn read_stdin_response() -> Result<String> {
     let mut user_input = String::new();
     stdin()
@@ -245,7 +302,6 @@ pub fn print_nrs_map(nrs_map: &NrsMap) {
     }
 }
 
-#[cfg(feature = "data-network")]
 // returns singular or plural version of string, based on count.
 pub fn pluralize<'a>(singular: &'a str, plural: &'a str, count: u64) -> &'a str {
     if count == 1 {
@@ -275,6 +331,83 @@ pub fn div_or<X: Float>(num: X, den: X, default: X) -> X {
     }
 }
 
+#[cfg(feature = "data-network")]
+// Progress bar rendered on stderr, out of the progress events of files uploads or downloads.
+pub struct FilesProgressBar {
+    bar: ProgressBar,
+    task: JoinHandle<()>,
+}
+
+#[cfg(feature = "data-network")]
+impl FilesProgressBar {
+    // Renders the progress of the chunks being stored, by the given Safe instance, until finished.
+    // No progress is rendered in dry-run mode, since nothing is uploaded then.
+    pub fn uploads(safe: &Safe) -> Option<Self> {
+        if safe.dry_run_mode {
+            return None;
+        }
+        let mut events = safe.files_progress().ok()?;
+        let bar = ProgressBar::new(0).with_style(
+            ProgressStyle::with_template("{spinner} [{bar:40}] {pos}/{len} chunks stored {msg}")
+                .ok()?
+                .progress_chars("=> "),
+        );
+
+        let progress = bar.clone();
+        let task = tokio::spawn(async move {
+            let (mut sent, mut acked) = (0, 0);
+            loop {
+                match events.recv().await {
+                    Ok(FileProgress::ChunksEncrypted(count)) => progress.inc_length(count as u64),
+                    Ok(FileProgress::ChunkSent(_)) => sent += 1,
+                    Ok(FileProgress::ChunkAcked(_)) => acked += 1,
+                    Ok(FileProgress::ChunkVerified(_)) => progress.inc(1),
+                    Ok(FileProgress::BytesFetched(_)) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+                progress.set_message(format!("(sent: {sent}, acked: {acked})"));
+            }
+        });
+
+        Some(Self { bar, task })
+    }
+
+    // Renders the bytes being fetched by the given Safe instance, until finished.
+    // The total number of bytes expected is to be set with `set_total_bytes`.
+    pub fn downloads(safe: &Safe) -> Option<Self> {
+        let mut events = safe.files_progress().ok()?;
+        let bar = ProgressBar::new(0).with_style(
+            ProgressStyle::with_template(
+                "{spinner} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .ok()?
+            .progress_chars("=> "),
+        );
+
+        let progress = bar.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(FileProgress::BytesFetched(bytes)) => progress.inc(bytes as u64),
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Some(Self { bar, task })
+    }
+
+    pub fn set_total_bytes(&self, total: u64) {
+        self.bar.set_length(total);
+    }
+
+    pub fn finish(self) {
+        self.task.abort();
+        self.bar.finish_and_clear();
+    }
+}
+
 /// Get the target URL from the link as a string.
 ///
 /// If the user hasn't prefixed the link with `safe://`, we'll do that for them here.
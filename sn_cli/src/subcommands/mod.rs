@@ -13,10 +13,13 @@ pub mod config;
 pub mod dog;
 pub mod files;
 pub mod keys;
+pub mod multimap;
 pub mod networks;
 #[cfg(feature = "node-ctrl")]
 pub mod node;
 pub mod nrs;
+pub mod publish;
+pub mod register;
 pub mod safe_id;
 pub mod setup;
 pub mod update;
@@ -100,9 +103,18 @@ pub enum SubCommands {
     #[clap(name = "keys", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage keys on the SAFE Network
     Keys(keys::KeysSubCommands),
+    #[clap(name = "register", subcommand, global_settings(&[AppSettings::DisableVersion]))]
+    /// Manage Registers on the SAFE Network
+    Register(register::RegisterSubCommands),
+    #[clap(name = "multimap", subcommand, global_settings(&[AppSettings::DisableVersion]))]
+    /// Manage Multimaps on the SAFE Network
+    Multimap(multimap::MultimapSubCommands),
     #[clap(name = "wallet", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage wallets on the SAFE Network
     Wallet(wallet::WalletSubCommands),
+    #[clap(name = "publish", global_settings(&[AppSettings::DisableVersion]))]
+    /// Publish to the SAFE Network a batch of writes exported from a dry run
+    Publish(publish::PublishCommands),
     /// Obtain the XOR-URL of data without uploading it to the network, or decode XOR-URLs
     Xorurl {
         /// subcommands
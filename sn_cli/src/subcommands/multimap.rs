@@ -0,0 +1,208 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{
+        entry_hash_to_hex, get_from_arg_or_stdin, notice_dry_run, parse_entry_hash, parse_xorname,
+        serialise_output,
+    },
+    register::DEFAULT_REGISTER_TYPE_TAG,
+    OutputFmt,
+};
+use clap::Subcommand;
+use color_eyre::{eyre::eyre, Result};
+use comfy_table::Table;
+use serde::Serialize;
+use sn_api::{
+    multimap::{Multimap, MultimapKeyValue},
+    register::EntryHash,
+    Error as ApiError, Safe,
+};
+use std::collections::BTreeSet;
+
+#[derive(Subcommand, Debug)]
+pub enum MultimapSubCommands {
+    #[clap(name = "create")]
+    /// Create a new Multimap, owned by the key configured for use with safe
+    Create {
+        /// The hex encoded XOR name to create the Multimap at. A random one is used if not provided
+        #[clap(long = "name")]
+        name: Option<String>,
+        /// The type tag of the Multimap
+        #[clap(long = "type-tag", default_value_t = DEFAULT_REGISTER_TYPE_TAG)]
+        type_tag: u64,
+    },
+    #[clap(name = "insert")]
+    /// Insert a key-value pair into a Multimap
+    Insert {
+        /// The URL of the Multimap
+        url: String,
+        /// The key to insert
+        key: String,
+        /// The value to insert. If not provided, it's read from STDIN
+        value: Option<String>,
+        /// The hex encoded hash of an entry the new one replaces. This can be provided multiple times
+        #[clap(long = "replace")]
+        replace: Vec<String>,
+    },
+    #[clap(name = "get")]
+    /// Get the values of a key in a Multimap, or an entry by its hash
+    Get {
+        /// The URL of the Multimap
+        url: String,
+        /// The key to get the values of
+        #[clap(required_unless_present = "hash")]
+        key: Option<String>,
+        /// The hex encoded hash of the entry to get, instead of the values of a key
+        #[clap(long = "hash", conflicts_with = "key")]
+        hash: Option<String>,
+    },
+    #[clap(name = "remove")]
+    /// Remove entries from a Multimap. They are still kept in its history
+    Remove {
+        /// The URL of the Multimap
+        url: String,
+        /// The hex encoded hashes of the entries to remove
+        #[clap(required = true)]
+        hashes: Vec<String>,
+    },
+}
+
+// Entry of a Multimap as output with a serialisation format
+#[derive(Serialize)]
+struct EntryOutput {
+    hash: String,
+    key: String,
+    value: String,
+}
+
+impl EntryOutput {
+    fn new(hash: &EntryHash, (key, value): &MultimapKeyValue) -> Self {
+        Self {
+            hash: entry_hash_to_hex(hash),
+            key: String::from_utf8_lossy(key).into_owned(),
+            value: String::from_utf8_lossy(value).into_owned(),
+        }
+    }
+}
+
+pub async fn multimap_commander(
+    cmd: MultimapSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    match cmd {
+        MultimapSubCommands::Create { name, type_tag } => {
+            let name = name.as_deref().map(parse_xorname).transpose()?;
+            let xorurl = safe.multimap_create(name, type_tag).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Multimap created at: \"{xorurl}\"");
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Insert {
+            url,
+            key,
+            value,
+            replace,
+        } => {
+            let value = get_from_arg_or_stdin(value, Some("...awaiting value from STDIN"))?;
+            let replace = parse_entry_hashes(&replace)?;
+            let hash = safe
+                .multimap_insert(&url, (key.into_bytes(), value.into_bytes()), replace)
+                .await?;
+            let hash = entry_hash_to_hex(&hash);
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Entry inserted into Multimap at \"{url}\" with hash: {hash}");
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+            } else {
+                println!("{}", serialise_output(&(url, hash), output_fmt));
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Get { url, key, hash } => {
+            let entries: Multimap = match (key, hash) {
+                (_, Some(hash)) => {
+                    let hash = parse_entry_hash(&hash)?;
+                    let key_value = safe.multimap_get_by_hash(&url, hash).await?;
+                    vec![(hash, key_value)].into_iter().collect()
+                }
+                (Some(key), None) => match safe.multimap_get_by_key(&url, key.as_bytes()).await {
+                    Ok(entries) => entries,
+                    Err(ApiError::EmptyContent(_)) => Multimap::new(),
+                    Err(err) => return Err(eyre!(err)),
+                },
+                (None, None) => {
+                    return Err(eyre!("Either a key or an entry hash must be provided"))
+                }
+            };
+
+            if OutputFmt::Pretty == output_fmt {
+                print_entries(&url, &entries);
+            } else {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|(hash, key_value)| EntryOutput::new(hash, key_value))
+                    .collect();
+                println!("{}", serialise_output(&(url, entries), output_fmt));
+            }
+
+            Ok(())
+        }
+        MultimapSubCommands::Remove { url, hashes } => {
+            let to_remove = parse_entry_hashes(&hashes)?;
+            let hash = safe.multimap_remove(&url, to_remove).await?;
+            let hash = entry_hash_to_hex(&hash);
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Entries removed from Multimap at \"{url}\" with tombstone hash: {hash}");
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+            } else {
+                println!("{}", serialise_output(&(url, hash), output_fmt));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn parse_entry_hashes(hashes: &[String]) -> Result<BTreeSet<EntryHash>> {
+    hashes.iter().map(|hash| parse_entry_hash(hash)).collect()
+}
+
+fn print_entries(url: &str, entries: &Multimap) {
+    if entries.is_empty() {
+        println!("No entries found in Multimap at \"{url}\"");
+        return;
+    }
+
+    println!("Entries found in Multimap at \"{url}\":");
+    let mut table = Table::new();
+    table.add_row(&vec!["Hash", "Key", "Value"]);
+    for (hash, (key, value)) in entries {
+        table.add_row(&vec![
+            entry_hash_to_hex(hash),
+            String::from_utf8_lossy(key).into_owned(),
+            String::from_utf8_lossy(value).into_owned(),
+        ]);
+    }
+    println!("{table}");
+}
@@ -7,10 +7,11 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::operations::node::{
-    node_events, node_info, node_restart, node_stop, node_update, section_members,
+    dkg_sessions, fault_scores, network_knowledge, node_drain, node_events, node_info,
+    node_restart, node_stop, node_update, section_members, stored_data,
 };
 
-use super::OutputFmt;
+use super::{helpers::parse_xorname, OutputFmt};
 
 use clap::Subcommand;
 use color_eyre::Result;
@@ -25,6 +26,21 @@ pub enum NodeSubCommands {
     /// Retrieve a list of section members the node is aware of
     #[clap(name = "section")]
     SectionMembers,
+    /// Retrieve the addresses and sizes of the data stored by the node
+    #[clap(name = "data")]
+    StoredData {
+        /// Hex encoded names of the data to look up. All the data stored is listed if none is given
+        names: Vec<String>,
+    },
+    /// Retrieve the node's network knowledge, i.e. its section tree and sections DAG
+    #[clap(name = "knowledge")]
+    NetworkKnowledge,
+    /// Retrieve the DKG sessions the node is taking part in, which haven't completed yet
+    #[clap(name = "dkg")]
+    DkgSessions,
+    /// Retrieve the fault detection scores of the nodes tracked by the node
+    #[clap(name = "faults")]
+    FaultScores,
     /// Start listening for node events.
     /// Note this blocks the CLI and it will print events as they are broadcasted by the node
     #[clap(name = "events")]
@@ -43,6 +59,13 @@ pub enum NodeSubCommands {
         #[clap(default_value = "0")]
         delay_millis: u64,
     },
+    /// Hand off the node's data to other nodes, and then stop the node
+    #[clap(name = "drain")]
+    Drain {
+        /// Max time in milliseconds to wait for the data to be handed off before stopping the node
+        #[clap(default_value = "60000")]
+        timeout_millis: u64,
+    },
     #[clap(name = "update")]
     /// Update to latest `safenode` released version, and restart it
     Update {
@@ -60,9 +83,20 @@ pub async fn node_commander(
     match cmd {
         NodeSubCommands::Info => node_info(addr, output_fmt).await,
         NodeSubCommands::SectionMembers => section_members(addr, output_fmt).await,
+        NodeSubCommands::StoredData { names } => {
+            let names = names
+                .iter()
+                .map(|name| parse_xorname(name))
+                .collect::<Result<Vec<_>>>()?;
+            stored_data(addr, names, output_fmt).await
+        }
+        NodeSubCommands::NetworkKnowledge => network_knowledge(addr, output_fmt).await,
+        NodeSubCommands::DkgSessions => dkg_sessions(addr, output_fmt).await,
+        NodeSubCommands::FaultScores => fault_scores(addr, output_fmt).await,
         NodeSubCommands::Events => node_events(addr, output_fmt).await,
         NodeSubCommands::Restart { delay_millis } => node_restart(addr, delay_millis).await,
         NodeSubCommands::Stop { delay_millis } => node_stop(addr, delay_millis).await,
+        NodeSubCommands::Drain { timeout_millis } => node_drain(addr, timeout_millis).await,
         NodeSubCommands::Update { delay_millis } => node_update(addr, delay_millis).await,
     }
 }
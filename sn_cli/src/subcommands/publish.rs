@@ -0,0 +1,73 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{pluralize, serialise_output},
+    OutputFmt,
+};
+use clap::Args;
+use color_eyre::{eyre::WrapErr, Result};
+use sn_api::{Safe, WriteBatch};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+#[derive(Args, Debug)]
+pub struct PublishCommands {
+    /// The batch file to publish, as exported by a dry run with '--export-batch'
+    batch_file: PathBuf,
+}
+
+pub async fn publish_commander(
+    cmd: PublishCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    debug!("Publishing batch from: {}", cmd.batch_file.display());
+    let batch = WriteBatch::load(&cmd.batch_file)
+        .wrap_err_with(|| format!("Failed to read batch file at {}", cmd.batch_file.display()))?;
+    let chunks = batch.chunks.len();
+    let register_ops = batch.register_ops.len();
+
+    safe.publish_write_batch(batch).await?;
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Published {chunks} {} and {register_ops} Register {} from batch file {}",
+            pluralize("chunk", "chunks", chunks as u64),
+            pluralize("operation", "operations", register_ops as u64),
+            cmd.batch_file.display()
+        );
+    } else {
+        println!(
+            "{}",
+            serialise_output(&(cmd.batch_file, chunks, register_ops), output_fmt)
+        );
+    }
+
+    Ok(())
+}
+
+// Export the writes recorded by a dry run to a batch file
+pub fn export_batch(batch: WriteBatch, path: &Path, output_fmt: OutputFmt) -> Result<()> {
+    batch
+        .save(path)
+        .wrap_err_with(|| format!("Failed to write batch file at {}", path.display()))?;
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Exported {} {} and {} Register {} to batch file {}, which can be published with 'safe publish'",
+            batch.chunks.len(),
+            pluralize("chunk", "chunks", batch.chunks.len() as u64),
+            batch.register_ops.len(),
+            pluralize("operation", "operations", batch.register_ops.len() as u64),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
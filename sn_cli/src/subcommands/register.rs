@@ -0,0 +1,287 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{
+        entry_hash_to_hex, get_from_arg_or_stdin, notice_dry_run, parse_entry_hash, parse_xorname,
+        serialise_output,
+    },
+    OutputFmt,
+};
+use clap::Subcommand;
+use color_eyre::{eyre::eyre, Help, Result};
+use comfy_table::Table;
+use serde::Serialize;
+use sn_api::{
+    register::{Action, Entry, EntryHash, Policy, User},
+    ContentType, Error as ApiError, Safe,
+};
+use std::collections::BTreeSet;
+
+/// Type tag used for registers created with the CLI if none is provided.
+pub const DEFAULT_REGISTER_TYPE_TAG: u64 = 25_000;
+
+#[derive(Subcommand, Debug)]
+pub enum RegisterSubCommands {
+    #[clap(name = "create")]
+    /// Create a new Register, owned by the key configured for use with safe
+    Create {
+        /// The hex encoded XOR name to create the Register at. A random one is used if not provided
+        #[clap(long = "name")]
+        name: Option<String>,
+        /// The type tag of the Register
+        #[clap(long = "type-tag", default_value_t = DEFAULT_REGISTER_TYPE_TAG)]
+        type_tag: u64,
+        /// Create a private Register, the entries of which are encrypted so that only
+        /// the owner of the key configured for use with safe can read them
+        #[clap(long = "private")]
+        private: bool,
+    },
+    #[clap(name = "read")]
+    /// Read the latest entries of a Register. More than one entry is listed when the Register
+    /// has concurrent branches, i.e. writes which were not merged yet
+    Read {
+        /// The URL of the Register
+        url: Option<String>,
+    },
+    #[clap(name = "write")]
+    /// Write a new entry to a Register
+    Write {
+        /// The URL of the Register
+        url: String,
+        /// The entry to write. If not provided, it's read from STDIN
+        entry: Option<String>,
+        /// The hex encoded hash of an entry the new one succeeds. This can be provided multiple
+        /// times. If not provided, all the latest entries of the Register are succeeded,
+        /// merging any concurrent branches it has
+        #[clap(long = "parent")]
+        parents: Vec<String>,
+    },
+    #[clap(name = "entry")]
+    /// Read an entry of a Register by its hash
+    Entry {
+        /// The URL of the Register
+        url: String,
+        /// The hex encoded hash of the entry
+        hash: String,
+    },
+    #[clap(name = "policy")]
+    /// Show the owner of a Register and the permissions of its users
+    Policy {
+        /// The URL of the Register
+        url: Option<String>,
+    },
+}
+
+// Entry of a Register as output with a serialisation format
+#[derive(Serialize)]
+struct EntryOutput {
+    hash: String,
+    entry: String,
+}
+
+impl EntryOutput {
+    fn new(hash: &EntryHash, entry: &Entry) -> Self {
+        Self {
+            hash: entry_hash_to_hex(hash),
+            entry: String::from_utf8_lossy(entry).into_owned(),
+        }
+    }
+}
+
+// Policy of a Register as output with a serialisation format
+#[derive(Serialize)]
+struct PolicyOutput {
+    owner: String,
+    permissions: Vec<(String, Option<bool>)>,
+}
+
+impl From<&Policy> for PolicyOutput {
+    fn from(policy: &Policy) -> Self {
+        Self {
+            owner: user_to_string(&policy.owner),
+            permissions: policy
+                .permissions
+                .iter()
+                .map(|(user, permissions)| {
+                    (user_to_string(user), permissions.is_allowed(Action::Write))
+                })
+                .collect(),
+        }
+    }
+}
+
+pub async fn register_commander(
+    cmd: RegisterSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    match cmd {
+        RegisterSubCommands::Create {
+            name,
+            type_tag,
+            private,
+        } => {
+            let name = name.as_deref().map(parse_xorname).transpose()?;
+            let xorurl = if private {
+                safe.register_create_private(name, type_tag, ContentType::Raw)
+                    .await?
+            } else {
+                safe.register_create(name, type_tag, ContentType::Raw)
+                    .await?
+            };
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Register created at: \"{xorurl}\"");
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Read { url } => {
+            let url = get_from_arg_or_stdin(url, Some("...awaiting Register URL from STDIN"))?;
+            let entries = safe.register_read(&url).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                print_entries(&url, &entries);
+            } else {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|(hash, entry)| EntryOutput::new(hash, entry))
+                    .collect();
+                println!("{}", serialise_output(&(url, entries), output_fmt));
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Write {
+            url,
+            entry,
+            parents,
+        } => {
+            let entry = get_from_arg_or_stdin(entry, Some("...awaiting entry from STDIN"))?;
+            let parents = if parents.is_empty() {
+                safe.register_read(&url)
+                    .await?
+                    .into_iter()
+                    .map(|(hash, _)| hash)
+                    .collect()
+            } else {
+                parents
+                    .iter()
+                    .map(|hash| parse_entry_hash(hash))
+                    .collect::<Result<BTreeSet<_>>>()?
+            };
+
+            let hash = safe
+                .register_write(&url, entry.into_bytes(), parents)
+                .await?;
+            let hash = entry_hash_to_hex(&hash);
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Entry written to Register at \"{url}\" with hash: {hash}");
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+            } else {
+                println!("{}", serialise_output(&(url, hash), output_fmt));
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Entry { url, hash } => {
+            let entry_hash = parse_entry_hash(&hash)?;
+            let entry =
+                safe.register_read_entry(&url, entry_hash)
+                    .await
+                    .map_err(|err| match err {
+                        ApiError::HashNotFound(_) => eyre!(err).suggestion(
+                            "Use the 'register read' command to list the latest entries.",
+                        ),
+                        err => eyre!(err),
+                    })?;
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("{}", String::from_utf8_lossy(&entry));
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&EntryOutput::new(&entry_hash, &entry), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+        RegisterSubCommands::Policy { url } => {
+            let url = get_from_arg_or_stdin(url, Some("...awaiting Register URL from STDIN"))?;
+            let policy = safe.register_policy(&url).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                print_policy(&url, &policy);
+            } else {
+                println!(
+                    "{}",
+                    serialise_output(&(url, PolicyOutput::from(&policy)), output_fmt)
+                );
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn print_entries(url: &str, entries: &BTreeSet<(EntryHash, Entry)>) {
+    match entries.len() {
+        0 => {
+            println!("Register at \"{url}\" is empty");
+            return;
+        }
+        1 => println!("Latest entry of Register at \"{url}\":"),
+        branches => println!(
+            "Register at \"{url}\" has {branches} concurrent branches, their latest entries are:"
+        ),
+    }
+
+    let mut table = Table::new();
+    table.add_row(&vec!["Hash", "Entry"]);
+    for (hash, entry) in entries {
+        table.add_row(&vec![
+            entry_hash_to_hex(hash),
+            String::from_utf8_lossy(entry).into_owned(),
+        ]);
+    }
+    println!("{table}");
+}
+
+fn print_policy(url: &str, policy: &Policy) {
+    println!("Policy of Register at \"{url}\":");
+    println!("Owner: {}", user_to_string(&policy.owner));
+
+    let mut table = Table::new();
+    table.add_row(&vec!["User", "Write"]);
+    for (user, permissions) in &policy.permissions {
+        let write = match permissions.is_allowed(Action::Write) {
+            Some(true) => "allowed",
+            Some(false) => "denied",
+            None => "default",
+        };
+        table.add_row(&vec![user_to_string(user), write.to_string()]);
+    }
+    println!("{table}");
+}
+
+fn user_to_string(user: &User) -> String {
+    match user {
+        User::Anyone => "anyone".to_string(),
+        User::Key(public_key) => format!("{public_key:x}"),
+    }
+}
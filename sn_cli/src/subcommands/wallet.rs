@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    helpers::{get_from_arg_or_stdin, serialise_output},
+    helpers::{get_from_arg_or_stdin, read_dbc_from_arg_or_stdin, serialise_output},
     OutputFmt,
 };
 use crate::operations::config::Config;
@@ -15,8 +15,8 @@ use bls::{PublicKey, SecretKey};
 use clap::Subcommand;
 use color_eyre::{eyre::eyre, eyre::Error, Help, Result};
 use sn_api::{wallet::DbcReason, Error as ApiError, Safe};
-use sn_dbc::{Dbc, Error as DbcError};
-use std::path::{Path, PathBuf};
+use sn_dbc::Error as DbcError;
+use std::path::PathBuf;
 use tokio::fs;
 
 #[derive(Subcommand, Debug)]
@@ -122,27 +122,7 @@ pub async fn wallet_commander(
             secret_key_hex,
             force,
         } => {
-            let dbc = if let Some(dbc) = dbc {
-                let path = Path::new(&dbc);
-                if path.exists() {
-                    if path.is_dir() {
-                        return Err(eyre!("The path supplied refers to a directory.")
-                            .suggestion("A file path must be specified for the DBC data."));
-                    }
-                    let dbc_data = std::fs::read_to_string(path)?;
-                    Dbc::from_hex(dbc_data.trim()).map_err(|e| {
-                        eyre!(e.to_string()).suggestion(
-                            "This file does not appear to have DBC data. \
-                            Please select another file with valid hex-encoded DBC data.",
-                        )
-                    })?
-                } else {
-                    Dbc::from_hex(&dbc)?
-                }
-            } else {
-                let dbc_hex = get_from_arg_or_stdin(dbc, None)?;
-                Dbc::from_hex(dbc_hex.trim())?
-            };
+            let dbc = read_dbc_from_arg_or_stdin(dbc)?;
 
             let (sk, public_key) = if dbc.is_bearer() {
                 (None, dbc.public_key())
@@ -1222,3 +1222,45 @@ fn files_tree_should_display_entire_directory_tree_of_container_with_symlinks()
 
     Ok(())
 }
+
+#[test]
+fn files_log_and_diff_should_show_changes_of_each_version() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let output = safe_cmd_stdout(&config_dir, ["files", "put", TEST_FILE], Some(0))?;
+    let mut lines = output.lines();
+    let files_container_xor_line = lines
+        .next()
+        .ok_or_else(|| eyre!("Could not fetch next line".to_string()))?;
+    let versioned_xorurl =
+        &files_container_xor_line[PRETTY_FILES_CREATION_RESPONSE.len()..].replace('"', "");
+
+    let mut url = SafeUrl::from_url(versioned_xorurl)?;
+    url.set_content_version(None);
+    url.set_path("/new.md");
+    safe_cmd(
+        &config_dir,
+        ["files", "add", TEST_FILE, &url.to_string()],
+        Some(0),
+    )?;
+    url.set_path("");
+
+    safe_cmd(&config_dir, ["files", "log", &url.to_string()], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("has 2 versions"))
+        .stdout(predicate::str::contains("(first version)"))
+        .stdout(predicate::str::contains("/new.md"))
+        .success();
+
+    safe_cmd(
+        &config_dir,
+        ["files", "diff", versioned_xorurl, &url.to_string()],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains("+"))
+    .stdout(predicate::str::contains("/new.md"))
+    .stdout(predicate::str::contains("/test.md").not())
+    .success();
+
+    Ok(())
+}
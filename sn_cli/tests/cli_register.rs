@@ -0,0 +1,238 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use assert_cmd::prelude::*;
+use color_eyre::{eyre::eyre, Result};
+use predicates::prelude::*;
+use serde_json::Value;
+use sn_cmd_test_utilities::util::{safe_cmd, safe_cmd_stdout, use_isolated_safe_config_dir};
+
+fn parse_json(output: &str) -> Result<Value> {
+    serde_json::from_str(output).map_err(|_| {
+        eyre!("Failed to parse output (Perhaps RUST_LOG is polluting output?): {output}")
+    })
+}
+
+// Returns the hashes and values of the entries listed in the output of `register read` or `multimap get`
+fn parse_entries(output: &str, value_field: &str) -> Result<Vec<(String, String)>> {
+    let json = parse_json(output)?;
+    let entries = json[1]
+        .as_array()
+        .ok_or_else(|| eyre!("No entries found in output: {output}"))?;
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            (
+                entry["hash"].as_str().unwrap_or_default().to_string(),
+                entry[value_field].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}
+
+// Returns the hash from the output of `register write`, `multimap insert` or `multimap remove`
+fn parse_hash(output: &str) -> Result<String> {
+    parse_json(output)?[1]
+        .as_str()
+        .map(|hash| hash.to_string())
+        .ok_or_else(|| eyre!("No hash found in output: {output}"))
+}
+
+#[test]
+fn register_read_should_show_concurrent_branches() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let url: String = serde_json::from_str(&output)?;
+
+    let output = safe_cmd_stdout(&config_dir, ["register", "read", &url, "--json"], Some(0))?;
+    assert!(parse_entries(&output, "entry")?.is_empty());
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &url, "first", "--json"],
+        Some(0),
+    )?;
+    let first = parse_hash(&output)?;
+
+    // two entries succeeding the same one create two branches
+    let output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "register", "write", &url, "left", "--parent", &first, "--json",
+        ],
+        Some(0),
+    )?;
+    let left = parse_hash(&output)?;
+    let output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "register", "write", &url, "right", "--parent", &first, "--json",
+        ],
+        Some(0),
+    )?;
+    let right = parse_hash(&output)?;
+
+    let output = safe_cmd_stdout(&config_dir, ["register", "read", &url, "--json"], Some(0))?;
+    let mut heads = parse_entries(&output, "entry")?;
+    heads.sort();
+    let mut expected = vec![(left, "left".to_string()), (right, "right".to_string())];
+    expected.sort();
+    assert_eq!(heads, expected);
+
+    safe_cmd(&config_dir, ["register", "read", &url], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("has 2 concurrent branches"))
+        .success();
+
+    // writing without parents merges the branches
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &url, "merged", "--json"],
+        Some(0),
+    )?;
+    let merged = parse_hash(&output)?;
+    let output = safe_cmd_stdout(&config_dir, ["register", "read", &url, "--json"], Some(0))?;
+    assert_eq!(
+        parse_entries(&output, "entry")?,
+        vec![(merged, "merged".to_string())]
+    );
+
+    safe_cmd(&config_dir, ["register", "entry", &url, &first], Some(0))?
+        .assert()
+        .stdout("first\n")
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn private_register_entries_should_be_read_decrypted() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "create", "--private", "--json"],
+        Some(0),
+    )?;
+    let url: String = serde_json::from_str(&output)?;
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["register", "write", &url, "secret", "--json"],
+        Some(0),
+    )?;
+    let hash = parse_hash(&output)?;
+
+    let output = safe_cmd_stdout(&config_dir, ["register", "read", &url, "--json"], Some(0))?;
+    assert_eq!(
+        parse_entries(&output, "entry")?,
+        vec![(hash, "secret".to_string())]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn register_policy_should_show_the_owner() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let output = safe_cmd_stdout(&config_dir, ["register", "create", "--json"], Some(0))?;
+    let url: String = serde_json::from_str(&output)?;
+
+    let output = safe_cmd_stdout(&config_dir, ["register", "policy", &url, "--json"], Some(0))?;
+    let policy = &parse_json(&output)?[1];
+    let owner = policy["owner"]
+        .as_str()
+        .ok_or_else(|| eyre!("No owner found in output: {output}"))?;
+    assert_eq!(policy["permissions"][0][0].as_str(), Some(owner));
+    assert_eq!(policy["permissions"][0][1].as_bool(), Some(true));
+
+    Ok(())
+}
+
+#[test]
+fn multimap_insert_get_and_remove() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let output = safe_cmd_stdout(&config_dir, ["multimap", "create", "--json"], Some(0))?;
+    let url: String = serde_json::from_str(&output)?;
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "insert", &url, "key", "value", "--json"],
+        Some(0),
+    )?;
+    let hash = parse_hash(&output)?;
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "get", &url, "key", "--json"],
+        Some(0),
+    )?;
+    assert_eq!(
+        parse_entries(&output, "value")?,
+        vec![(hash.clone(), "value".to_string())]
+    );
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "get", &url, "--hash", &hash, "--json"],
+        Some(0),
+    )?;
+    assert_eq!(
+        parse_entries(&output, "key")?,
+        vec![(hash.clone(), "key".to_string())]
+    );
+
+    safe_cmd(&config_dir, ["multimap", "remove", &url, &hash], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Entries removed from Multimap"))
+        .success();
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        ["multimap", "get", &url, "key", "--json"],
+        Some(0),
+    )?;
+    assert!(parse_entries(&output, "value")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn register_written_in_dry_run_should_be_published_from_exported_batch() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let batch_file = config_dir.path().join("register.batch");
+    let batch_path = batch_file.display().to_string();
+
+    let output = safe_cmd_stdout(
+        &config_dir,
+        [
+            "register",
+            "create",
+            "--dry-run",
+            "--export-batch",
+            &batch_path,
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let url: String = serde_json::from_str(&output)?;
+
+    // nothing was written to the network yet
+    safe_cmd(&config_dir, ["register", "read", &url, "--json"], Some(1))?;
+
+    safe_cmd(&config_dir, ["publish", &batch_path], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(
+            "Published 0 chunks and 1 Register operation",
+        ))
+        .success();
+
+    let output = safe_cmd_stdout(&config_dir, ["register", "read", &url, "--json"], Some(0))?;
+    assert!(parse_entries(&output, "entry")?.is_empty());
+
+    Ok(())
+}
@@ -1114,3 +1114,53 @@ async fn wallet_deposit_dbc_reissued_to_file() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn config_add_payment_dbc_should_keep_a_bearer_dbc() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let (dbc_file_path, _, _) = get_bearer_dbc_on_file(&tmp_data_dir).await?;
+
+    safe_cmd(
+        &config_dir,
+        [
+            "config",
+            "add",
+            "payment-dbc",
+            &dbc_file_path.path().display().to_string(),
+        ],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains(
+        "DBC added to those paying for storing data",
+    ))
+    .success();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn config_add_payment_dbc_should_fail_with_an_owned_dbc() -> Result<()> {
+    let config_dir = use_isolated_safe_config_dir()?;
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let (dbc_file_path, _, _) = get_owned_dbc_on_file(&tmp_data_dir).await?;
+
+    safe_cmd(
+        &config_dir,
+        [
+            "config",
+            "add",
+            "payment-dbc",
+            &dbc_file_path.path().display().to_string(),
+        ],
+        Some(1),
+    )?
+    .assert()
+    .stderr(predicate::str::contains(
+        "Only bearer DBCs can be used to pay for storing data",
+    ))
+    .failure();
+
+    Ok(())
+}
@@ -13,12 +13,21 @@ use eyre::Result;
 
 use sn_client::{Client, Error};
 use sn_interface::{
-    messaging::{self, data::ClientMsg, ClientAuth, Dst, MsgId, WireMsg},
-    types::register::{Policy, User},
+    messaging::{
+        self,
+        data::{ClientMsg, EditRegister, RegisterCmd, SignedRegisterEdit},
+        serialisation::{COMPRESSION_PROTO_VERSION, MESSAGING_PROTO_VERSION},
+        system::{NodeDataCmd, NodeMsg},
+        ClientAuth, Dst, MsgId, ProtocolVersions, WireMsg,
+    },
+    types::{
+        register::{Policy, Register, User},
+        Keypair, ReplicatedData, ReplicatedRegisterLog,
+    },
 };
 use tokio::runtime::Runtime;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 fn public_policy(owner: User) -> Policy {
     let permissions = BTreeMap::new();
@@ -43,6 +52,114 @@ async fn create_client() -> Result<Client, Error> {
     Ok(client)
 }
 
+/// Generates a batch of register logs to replicate, with `entries` entries each.
+fn register_logs_batch(registers: usize, entries: usize) -> NodeMsg {
+    let keypair = Keypair::new_ed25519();
+    let owner = User::Key(keypair.public_key());
+    let batch = (0..registers)
+        .map(|r| {
+            let mut register =
+                Register::new(owner, xor_name::rand::random(), 15000, public_policy(owner));
+            let mut children = BTreeSet::new();
+            let mut op_log = vec![];
+            for i in 0..entries {
+                let entry = format!("{{\"path\":\"photos/{r}/{i}.jpg\",\"size\":{}}}", i * 1024);
+                let (hash, edit) = match register.write(entry.into_bytes(), children) {
+                    Ok(written) => written,
+                    Err(error) => panic!("failed to write register entry: {error:?}"),
+                };
+                children = [hash].into();
+                let op = EditRegister {
+                    address: *register.address(),
+                    edit,
+                    policy_version: register.policy_version(),
+                };
+                let signature = match bincode::serialize(&op) {
+                    Ok(bytes) => keypair.sign(&bytes),
+                    Err(error) => panic!("failed to serialise register edit: {error:?}"),
+                };
+                op_log.push(RegisterCmd::Edit(SignedRegisterEdit {
+                    op,
+                    auth: ClientAuth {
+                        public_key: keypair.public_key(),
+                        signature,
+                    },
+                }));
+            }
+            ReplicatedData::RegisterLog(ReplicatedRegisterLog {
+                address: *register.address(),
+                op_log,
+                snapshots: vec![],
+            })
+        })
+        .collect();
+
+    NodeMsg::NodeDataCmd(NodeDataCmd::ReplicateDataBatch(batch))
+}
+
+fn compression_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+
+    let payload = match WireMsg::serialize_msg_payload(&register_logs_batch(10, 100)) {
+        Ok(payload) => payload,
+        Err(error) => panic!("failed to serialise msg payload: {error:?}"),
+    };
+    let wire_msg = WireMsg::new_msg(
+        MsgId::new(),
+        payload,
+        messaging::MsgKind::Node {
+            name: xor_name::rand::random(),
+            is_join: false,
+            is_data: true,
+        },
+        Dst {
+            name: xor_name::rand::random(),
+            section_key: bls::SecretKey::random().public_key(),
+        },
+    );
+    let bytes = match wire_msg.serialize() {
+        Ok(bytes) => bytes,
+        Err(error) => panic!("Could not form initial WireMsg: {error:?}"),
+    };
+    let encode = |version| match WireMsg::encode_for_version(
+        bytes.clone(),
+        version,
+        ProtocolVersions::SUPPORTED,
+    ) {
+        Ok(bytes) => bytes,
+        Err(error) => panic!("failed to serialise for version {version}: {error:?}"),
+    };
+
+    let uncompressed = encode(COMPRESSION_PROTO_VERSION - 1);
+    let compressed = encode(MESSAGING_PROTO_VERSION);
+    let size = |(header, dst, payload): &(bytes::Bytes, bytes::Bytes, bytes::Bytes)| {
+        header.len() + dst.len() + payload.len()
+    };
+    println!(
+        "Register logs batch of {} bytes compressed down to {} bytes",
+        size(&uncompressed),
+        size(&compressed)
+    );
+
+    group.throughput(Throughput::Bytes(size(&uncompressed) as u64));
+    for version in [COMPRESSION_PROTO_VERSION - 1, MESSAGING_PROTO_VERSION] {
+        group.bench_function(
+            format!("serialize register logs for sending with version {version}"),
+            |b| b.iter(|| encode(version)),
+        );
+    }
+    for (name, bytes) in [("uncompressed", uncompressed), ("compressed", compressed)] {
+        group.bench_function(format!("deserialize {name} register logs"), |b| {
+            b.iter(|| match WireMsg::from(bytes.clone()) {
+                Ok(wire_msg) => wire_msg,
+                Err(error) => panic!("failed to deserialise msg: {error:?}"),
+            })
+        });
+    }
+
+    group.finish()
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("serialize");
     let runtime = Runtime::new().unwrap();
@@ -102,6 +219,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             auth,
             is_spend: false,
             query_index: None,
+            is_register: false,
         },
         dst,
     );
@@ -135,5 +253,5 @@ fn criterion_benchmark(c: &mut Criterion) {
     group.finish()
 }
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group!(benches, compression_benchmark, criterion_benchmark);
 criterion_main!(benches);
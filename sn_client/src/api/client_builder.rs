@@ -14,9 +14,10 @@
 //! # Ok(())
 //! # }
 //! ```
+use super::{disk_cache::DiskCache, progress::FILE_PROGRESS_CHANNEL_SIZE};
 use crate::{sessions::Session, Client, Error, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
 
-use sn_dbc::Owner;
+use sn_dbc::{Dbc, Owner};
 use sn_interface::{network_knowledge::SectionTree, types::Keypair};
 use std::{
     net::{Ipv4Addr, SocketAddr},
@@ -25,7 +26,7 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
 /// Environment variable used to convert into [`ClientBuilder::query_timeout`] (seconds)
 pub const ENV_QUERY_TIMEOUT: &str = "SN_QUERY_TIMEOUT";
@@ -38,6 +39,8 @@ pub const ENV_CMD_TIMEOUT: &str = "SN_CMD_TIMEOUT";
 pub const DEFAULT_LOCAL_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::UNSPECIFIED, 0);
 /// Max amount of time for an operation backoff (time between attempts). In Seconds.
 pub const DEFAULT_MAX_QUERY_CMD_BACKOFF_INTERVAL: Duration = Duration::from_secs(3);
+/// Max total size of the chunks and Registers kept in the on-disk cache, if any. In bytes.
+pub const DEFAULT_CACHE_MAX_SIZE: u64 = 512 * 1024 * 1024;
 
 /// Build a [`crate::Client`]
 #[derive(Debug, Default)]
@@ -49,6 +52,9 @@ pub struct ClientBuilder {
     max_backoff_interval: Option<Duration>,
     cmd_timeout: Option<Duration>,
     network_contacts: Option<SectionTree>,
+    payment_dbcs: Vec<Dbc>,
+    cache_dir: Option<PathBuf>,
+    cache_max_size: Option<u64>,
 }
 
 impl ClientBuilder {
@@ -102,6 +108,25 @@ impl ClientBuilder {
         self
     }
 
+    /// DBCs the client pays for storing data with
+    pub fn payment_dbcs(mut self, dbcs: impl IntoIterator<Item = Dbc>) -> Self {
+        self.payment_dbcs = dbcs.into_iter().collect();
+        self
+    }
+
+    /// Directory to keep an on-disk cache of the chunks and Registers retrieved from the network,
+    /// so that they are kept across instances of the client. No such cache is kept if not set.
+    pub fn cache_dir(mut self, dir: impl Into<Option<PathBuf>>) -> Self {
+        self.cache_dir = dir.into();
+        self
+    }
+
+    /// Max total size, in bytes, of the chunks and Registers kept in the on-disk cache
+    pub fn cache_max_size(mut self, size: impl Into<Option<u64>>) -> Self {
+        self.cache_max_size = size.into();
+        self
+    }
+
     /// Read options from environment variables:
     /// - [`Self::query_timeout()`] from [`ENV_QUERY_TIMEOUT`]
     /// - [`Self::max_backoff_interval()`] from [`ENV_MAX_BACKOFF_INTERVAL`]
@@ -125,6 +150,7 @@ impl ClientBuilder {
     /// In case parameters have not been passed to this builder, defaults will be used:
     /// - `[Self::keypair]` and `[Self::dbc_owner]` are randomly generated
     /// - `[Self::max_backoff_interval`] defaults to [`DEFAULT_MAX_QUERY_CMD_BACKOFF_INTERVAL`]
+    /// - `[Self::cache_max_size`] defaults to [`DEFAULT_CACHE_MAX_SIZE`]
     /// - Network contacts file will be read from a standard location
     pub async fn build(self) -> Result<Client, Error> {
         let max_backoff_interval = self
@@ -149,6 +175,14 @@ impl ClientBuilder {
             network_contacts,
         )?;
 
+        let disk_cache = match self.cache_dir {
+            Some(dir) => {
+                let max_size = self.cache_max_size.unwrap_or(DEFAULT_CACHE_MAX_SIZE);
+                Some(Arc::new(DiskCache::open(&dir, max_size).await?))
+            }
+            None => None,
+        };
+
         let keypair = self.keypair.unwrap_or_else(Keypair::new_ed25519);
         let dbc_owner = self
             .dbc_owner
@@ -162,6 +196,9 @@ impl ClientBuilder {
             max_backoff_interval,
             cmd_timeout,
             chunks_cache: Arc::new(RwLock::new(Default::default())),
+            payment_dbcs: Arc::new(RwLock::new(self.payment_dbcs)),
+            file_progress: broadcast::channel(FILE_PROGRESS_CHANNEL_SIZE).0,
+            disk_cache,
         };
         client.connect().await?;
 
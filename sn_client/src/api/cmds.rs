@@ -14,7 +14,7 @@ use sn_interface::{
         data::{ClientMsg, DataCmd},
         ClientAuth, MsgId, WireMsg,
     },
-    types::{PublicKey, Signature},
+    types::{fees::PaymentProof, PublicKey, Signature},
 };
 
 use bytes::Bytes;
@@ -29,6 +29,7 @@ impl Client {
     /// Send a signed `DataCmd` to the network.
     /// This is part of the public API, for the user to
     /// provide the serialised and already signed cmd.
+    /// `is_register_cmd` is to be set for a register cmd, for its edits to be notified to watchers.
     pub async fn send_signed_cmd(
         &self,
         dst_address: XorName,
@@ -36,6 +37,32 @@ impl Client {
         serialised_cmd: Bytes,
         signature: Signature,
         is_spend_cmd: bool,
+        is_register_cmd: bool,
+    ) -> Result<()> {
+        self.send_signed_cmd_payload(
+            dst_address,
+            client_pk,
+            serialised_cmd,
+            signature,
+            is_spend_cmd,
+            is_register_cmd,
+            true,
+        )
+        .await
+    }
+
+    // Sends a signed cmd, whose payload is compressed if `compressible`
+    // and large enough for it to be worth it.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_signed_cmd_payload(
+        &self,
+        dst_address: XorName,
+        client_pk: PublicKey,
+        serialised_cmd: Bytes,
+        signature: Signature,
+        is_spend_cmd: bool,
+        is_register_cmd: bool,
+        compressible: bool,
     ) -> Result<()> {
         let auth = ClientAuth {
             public_key: client_pk,
@@ -46,7 +73,15 @@ impl Client {
         if let Some(cmd_timeout) = self.cmd_timeout {
             tokio::time::timeout(cmd_timeout, async {
                 self.session
-                    .send_cmd(dst_address, auth, serialised_cmd, is_spend_cmd, msg_id)
+                    .send_cmd(
+                        dst_address,
+                        auth,
+                        serialised_cmd,
+                        is_spend_cmd,
+                        is_register_cmd,
+                        compressible,
+                        msg_id,
+                    )
                     .await
             })
             .await
@@ -57,7 +92,15 @@ impl Client {
             })?
         } else {
             self.session
-                .send_cmd(dst_address, auth, serialised_cmd, is_spend_cmd, msg_id)
+                .send_cmd(
+                    dst_address,
+                    auth,
+                    serialised_cmd,
+                    is_spend_cmd,
+                    is_register_cmd,
+                    compressible,
+                    msg_id,
+                )
                 .await
         }
     }
@@ -74,6 +117,7 @@ impl Client {
         debug!("Attempting {debug_cmd}");
 
         let is_spend_cmd = matches!(cmd, DataCmd::Spentbook(_));
+        let is_register_cmd = matches!(cmd, DataCmd::Register(_));
         let serialised_cmd = {
             let msg = ClientMsg::Cmd(cmd);
             WireMsg::serialize_msg_payload(&msg)?
@@ -81,7 +125,14 @@ impl Client {
         let signature = self.sign(&serialised_cmd);
 
         let res = self
-            .send_signed_cmd(dst_name, client_pk, serialised_cmd, signature, is_spend_cmd)
+            .send_signed_cmd(
+                dst_name,
+                client_pk,
+                serialised_cmd,
+                signature,
+                is_spend_cmd,
+                is_register_cmd,
+            )
             .await;
 
         if res.is_ok() {
@@ -92,4 +143,42 @@ impl Client {
 
         res
     }
+
+    /// Public API to send a `DataCmd` storing new data to the network, along with
+    /// the proof of it having been paid for. See [`DataCmd::requires_payment`].
+    /// The cmd is sent to the Elders of the section where the data is to be stored,
+    /// who verify the payment before forwarding the data to the data holders.
+    #[instrument(skip_all, level = "debug", name = "client-api send paid cmd")]
+    pub async fn send_paid_cmd(&self, cmd: DataCmd, payment: PaymentProof) -> Result<()> {
+        let client_pk = self.public_key();
+        let dst_name = cmd.dst_name();
+
+        let debug_cmd = format!("{:?}", cmd);
+        debug!("Attempting paid {debug_cmd}");
+
+        let msg = ClientMsg::PaidCmd { cmd, payment };
+        let serialised_cmd = WireMsg::serialize_msg_payload(&msg)?;
+        let signature = self.sign(&serialised_cmd);
+
+        // Paid cmds are handled by the Elders, same as spends.
+        let res = self
+            .send_signed_cmd_payload(
+                dst_name,
+                client_pk,
+                serialised_cmd,
+                signature,
+                true,
+                false,
+                !msg.carries_chunks(),
+            )
+            .await;
+
+        if res.is_ok() {
+            debug!("Paid {debug_cmd} sent okay: {res:?}");
+        } else {
+            trace!("Failed response on paid {debug_cmd}, response: {:?}", res);
+        }
+
+        res
+    }
 }
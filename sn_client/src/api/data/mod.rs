@@ -7,8 +7,12 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod pac_man;
+mod segments;
 
-pub(crate) use pac_man::{encrypt_large, to_chunk, DataMapLevel};
+pub(crate) use pac_man::{encrypt_large, pack_segments, to_chunk, DataMapLevel};
+pub(crate) use segments::SegmentReader;
+#[cfg(all(test, not(feature = "limit-client-upload-size")))]
+pub(crate) use segments::SEGMENT_SIZE;
 
 use crate::{Error, Result};
 
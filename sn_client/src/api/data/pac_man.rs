@@ -14,7 +14,6 @@ use bytes::Bytes;
 use rayon::prelude::*;
 use self_encryption::{DataMap, EncryptedChunk, MAX_CHUNK_SIZE};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
 use xor_name::XorName;
 
 #[derive(Serialize, Deserialize)]
@@ -25,12 +24,10 @@ pub(crate) enum DataMapLevel {
     // resulting from chunking up a previous level data map.
     // This happens when that previous level data map was too big to fit in a chunk itself.
     Additional(DataMap),
-}
-
-#[allow(unused)]
-pub(crate) fn encrypt_from_path(path: &Path) -> Result<(XorName, Vec<Chunk>)> {
-    let (data_map, encrypted_chunks) = encrypt_file(path)?;
-    pack(data_map, encrypted_chunks)
+    // Holds the head addresses, and sizes, of the consecutive segments the source data was split into,
+    // each of which was self-encrypted on its own.
+    // This happens when the source data was streamed, and was too big to fit in a single segment.
+    Segments(Vec<(XorName, usize)>),
 }
 
 pub(crate) fn encrypt_large(data: Bytes) -> Result<(XorName, Vec<Chunk>)> {
@@ -38,6 +35,18 @@ pub(crate) fn encrypt_large(data: Bytes) -> Result<(XorName, Vec<Chunk>)> {
     pack(data_map, encrypted_chunks)
 }
 
+/// Returns the top-most chunk address through which all the given segments can be accessed,
+/// and the chunks needed for it, if any.
+/// A single segment is returned as is, thus data fitting in one segment
+/// has the same address as if it had not been streamed.
+pub(crate) fn pack_segments(segments: Vec<(XorName, usize)>) -> Result<(XorName, Vec<Chunk>)> {
+    match segments.as_slice() {
+        [] => Err(Error::EmptyFileProvided),
+        [(head_address, _)] => Ok((*head_address, vec![])),
+        _ => pack_level(DataMapLevel::Segments(segments), vec![]),
+    }
+}
+
 /// Returns the top-most chunk address through which the entire
 /// data tree can be accessed, and all the other encrypted chunks.
 /// If encryption is provided, the additional secret key level chunks are encrypted with it.
@@ -45,6 +54,13 @@ pub(crate) fn encrypt_large(data: Bytes) -> Result<(XorName, Vec<Chunk>)> {
 pub(crate) fn pack(
     data_map: DataMap,
     encrypted_chunks: Vec<EncryptedChunk>,
+) -> Result<(XorName, Vec<Chunk>)> {
+    pack_level(DataMapLevel::First(data_map), encrypted_chunks)
+}
+
+fn pack_level(
+    level: DataMapLevel,
+    encrypted_chunks: Vec<EncryptedChunk>,
 ) -> Result<(XorName, Vec<Chunk>)> {
     // Produces a chunk out of the first secret key, which is validated for its size.
     // If the chunk is too big, it is self-encrypted and the resulting (additional level) secret key is put into a chunk.
@@ -53,7 +69,7 @@ pub(crate) fn pack(
     // self encrypted into additional chunks, and now we have a new secret key
     // which points to all of those additional chunks.. and so on.
     let mut chunks = vec![];
-    let mut chunk_content = pack_data_map(DataMapLevel::First(data_map))?;
+    let mut chunk_content = pack_data_map(level)?;
 
     let (address, additional_chunks) = loop {
         let chunk = to_chunk(chunk_content);
@@ -102,12 +118,6 @@ fn pack_data_map(data_map: DataMapLevel) -> Result<Bytes> {
     Ok(Bytes::from(serialize(&data_map)?))
 }
 
-fn encrypt_file(file: &Path) -> Result<(DataMap, Vec<EncryptedChunk>)> {
-    let bytes = Bytes::from(std::fs::read(file)?);
-    let encrypted_chunk = self_encryption::encrypt(bytes)?;
-    Ok(encrypted_chunk)
-}
-
 fn encrypt_data(bytes: Bytes) -> Result<(DataMap, Vec<EncryptedChunk>)> {
     let encrypted_chunk = self_encryption::encrypt(bytes)?;
     Ok(encrypted_chunk)
@@ -0,0 +1,151 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+#[cfg(feature = "limit-client-upload-size")]
+use super::LargeFile;
+use crate::Result;
+
+use bytes::Bytes;
+use self_encryption::{MAX_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES};
+use std::io::{Read, Seek};
+
+/// Max size of the segments streamed data is split into, each of which is self-encrypted on its own.
+/// This is what bounds the memory used when uploading data of any size.
+pub(crate) const SEGMENT_SIZE: usize = 20 * MAX_CHUNK_SIZE;
+
+/// Splits the data read from a reader into consecutive segments of [`SEGMENT_SIZE`] bytes.
+///
+/// The last segment is never smaller than [`MIN_ENCRYPTABLE_BYTES`] (unless it's the only one),
+/// as such a tail is appended to the previous segment instead, so that all segments can be self-encrypted.
+pub(crate) struct SegmentReader<R> {
+    reader: R,
+    // Bytes read ahead of the segment last returned.
+    read_ahead: Vec<u8>,
+    eof: bool,
+    #[cfg(feature = "limit-client-upload-size")]
+    total_read: usize,
+}
+
+impl<R: Read> SegmentReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            read_ahead: vec![],
+            eof: false,
+            #[cfg(feature = "limit-client-upload-size")]
+            total_read: 0,
+        }
+    }
+
+    /// Returns the next segment, or `None` if all the data has already been read.
+    pub(crate) fn next_segment(&mut self) -> Result<Option<Bytes>> {
+        let mut segment = std::mem::take(&mut self.read_ahead);
+        self.fill(&mut segment, SEGMENT_SIZE)?;
+        if segment.is_empty() {
+            return Ok(None);
+        }
+
+        if !self.eof {
+            let mut read_ahead = vec![];
+            self.fill(&mut read_ahead, MIN_ENCRYPTABLE_BYTES)?;
+            if self.eof && MIN_ENCRYPTABLE_BYTES > read_ahead.len() {
+                segment.append(&mut read_ahead);
+            }
+            self.read_ahead = read_ahead;
+        }
+
+        #[cfg(feature = "limit-client-upload-size")]
+        {
+            self.total_read += segment.len();
+            if self.total_read > LargeFile::CLIENT_UPLOAD_SIZE_LIMIT {
+                return Err(crate::Error::UploadSizeLimitExceeded {
+                    size: self.total_read,
+                    limit: LargeFile::CLIENT_UPLOAD_SIZE_LIMIT,
+                });
+            }
+        }
+
+        Ok(Some(Bytes::from(segment)))
+    }
+
+    /// Rewinds the reader, so that its data can be split into segments again from its start.
+    pub(crate) fn rewind(self) -> Result<Self>
+    where
+        R: Seek,
+    {
+        let mut reader = self.reader;
+        reader.rewind()?;
+        Ok(Self::new(reader))
+    }
+
+    // Reads into the buffer until it holds `size` bytes, or the reader is exhausted.
+    fn fill(&mut self, buffer: &mut Vec<u8>, size: usize) -> Result<()> {
+        if self.eof || buffer.len() >= size {
+            return Ok(());
+        }
+        let wanted = size - buffer.len();
+        let read = (&mut self.reader).take(wanted as u64).read_to_end(buffer)?;
+        self.eof = wanted > read;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SegmentReader, SEGMENT_SIZE};
+    #[cfg(not(feature = "limit-client-upload-size"))]
+    use self_encryption::MIN_ENCRYPTABLE_BYTES;
+    use sn_interface::types::utils::random_bytes;
+
+    use eyre::Result;
+
+    fn segment_sizes(size: usize) -> Result<Vec<usize>> {
+        let bytes = random_bytes(size);
+        let mut reader = SegmentReader::new(bytes.as_ref());
+        let mut sizes = vec![];
+        let mut read = vec![];
+        while let Some(segment) = reader.next_segment()? {
+            sizes.push(segment.len());
+            read.extend(segment);
+        }
+        assert_eq!(bytes.as_ref(), read.as_slice());
+        Ok(sizes)
+    }
+
+    #[test]
+    fn data_fitting_a_segment_is_read_as_one() -> Result<()> {
+        assert_eq!(segment_sizes(0)?, Vec::<usize>::new());
+        assert_eq!(segment_sizes(1)?, vec![1]);
+        assert_eq!(segment_sizes(SEGMENT_SIZE)?, vec![SEGMENT_SIZE]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "limit-client-upload-size"))]
+    fn small_tail_is_appended_to_the_last_segment() -> Result<()> {
+        let size = 2 * SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES - 1;
+        assert_eq!(
+            segment_sizes(size)?,
+            vec![SEGMENT_SIZE, SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES - 1]
+        );
+        let size = 2 * SEGMENT_SIZE + 1;
+        assert_eq!(segment_sizes(size)?, vec![SEGMENT_SIZE, SEGMENT_SIZE + 1]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "limit-client-upload-size"))]
+    fn encryptable_tail_is_its_own_segment() -> Result<()> {
+        let size = SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES;
+        assert_eq!(
+            segment_sizes(size)?,
+            vec![SEGMENT_SIZE, MIN_ENCRYPTABLE_BYTES]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::Result;
+
+use bytes::Bytes;
+use sn_interface::types::{register::Register, Chunk, Error as DtError, RegisterAddress};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::{fs, sync::Mutex};
+use xor_name::XorName;
+
+const CHUNKS_DIR: &str = "chunks";
+const REGISTERS_DIR: &str = "registers";
+
+/// A cache, persisted on disk, of the chunks and Register replicas a client retrieves,
+/// so that they are kept across the instances of the client.
+///
+/// Chunks are immutable, hence a cached chunk is always valid. Registers are only cached as
+/// the last replica of each retrieved from the network, to be merged with the next one retrieved,
+/// since the network's may not have all the entries yet. The total size of the chunks and
+/// Registers is kept within a maximum, evicting those used the least recently once it's exceeded.
+///
+/// The cached files are tracked in memory, by how recently they were read or written, so that
+/// they don't have to be listed each time some are evicted. When the cache is opened they are
+/// ordered by the time they were last written, how recently they were read not being persisted.
+#[derive(Debug)]
+pub(crate) struct DiskCache {
+    chunks_dir: PathBuf,
+    registers_dir: PathBuf,
+    max_size: u64,
+    index: Mutex<CacheIndex>,
+}
+
+impl DiskCache {
+    /// Opens the cache kept at the given directory, creating it if it doesn't exist.
+    pub(crate) async fn open(dir: &Path, max_size: u64) -> Result<Self> {
+        let chunks_dir = dir.join(CHUNKS_DIR);
+        let registers_dir = dir.join(REGISTERS_DIR);
+        fs::create_dir_all(&chunks_dir).await?;
+        fs::create_dir_all(&registers_dir).await?;
+
+        let mut cached = cached_files(&chunks_dir).await?;
+        cached.extend(cached_files(&registers_dir).await?);
+        cached.sort_by_key(|(_, _, modified)| *modified);
+        let mut index = CacheIndex::default();
+        for (path, size, _) in cached {
+            index.insert(path, size);
+        }
+        debug!(
+            "Opened client cache at {}, with {} bytes of chunks and Registers",
+            dir.display(),
+            index.size
+        );
+
+        Ok(Self {
+            chunks_dir,
+            registers_dir,
+            max_size,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// Returns the chunk with the given name if it's cached.
+    pub(crate) async fn get_chunk(&self, name: &XorName) -> Option<Chunk> {
+        let path = self.chunks_dir.join(hex::encode(name));
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return None,
+            Err(error) => {
+                warn!("Failed to read cached chunk {name:?}: {error}");
+                return None;
+            }
+        };
+
+        let chunk = Chunk::new(Bytes::from(bytes));
+        if chunk.name() != name {
+            warn!("Removing corrupted cached chunk {name:?}");
+            let _ = self.remove_file(&path).await;
+            return None;
+        }
+
+        let _ = self.index.lock().await.touch(&path);
+        Some(chunk)
+    }
+
+    /// Caches the chunk, evicting what was used the least recently if it's then over its maximum size.
+    pub(crate) async fn put_chunk(&self, chunk: &Chunk) -> Result<()> {
+        let path = self.chunks_dir.join(hex::encode(chunk.name()));
+        if self.index.lock().await.touch(&path) {
+            return Ok(());
+        }
+        self.put_file(&path, chunk.value()).await
+    }
+
+    /// Returns the last known replica of the Register at the given address, if it's cached.
+    pub(crate) async fn get_register(&self, address: &RegisterAddress) -> Option<Register> {
+        let path = self.register_path(address);
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return None,
+            Err(error) => {
+                warn!("Failed to read cached Register {address:?}: {error}");
+                return None;
+            }
+        };
+
+        // Registers are serialised as msgs are, rather than with bincode, as they skip
+        // serialising the policy edits they have none of
+        match rmp_serde::from_slice::<Register>(&bytes) {
+            Ok(register) if register.address() == address => {
+                let _ = self.index.lock().await.touch(&path);
+                Some(register)
+            }
+            _ => {
+                warn!("Removing corrupted cached Register {address:?}");
+                let _ = self.remove_file(&path).await;
+                None
+            }
+        }
+    }
+
+    /// Caches the Register replica as the last known one, evicting what was used the least recently
+    /// if it's then over its maximum size.
+    pub(crate) async fn put_register(&self, register: &Register) -> Result<()> {
+        let path = self.register_path(register.address());
+        let bytes =
+            rmp_serde::to_vec(register).map_err(|err| DtError::Serialisation(err.to_string()))?;
+        self.put_file(&path, &bytes).await
+    }
+
+    fn register_path(&self, address: &RegisterAddress) -> PathBuf {
+        self.registers_dir
+            .join(format!("{}-{}", hex::encode(address.name), address.tag))
+    }
+
+    // Writes the file, replacing the one at the path if any, then evicts the files used
+    // the least recently, other than this one, until the cache is within its maximum size.
+    async fn put_file(&self, path: &Path, bytes: &[u8]) -> Result<()> {
+        let len = bytes.len() as u64;
+        if len > self.max_size {
+            return Ok(());
+        }
+
+        let mut index = self.index.lock().await;
+        write_atomically(path, bytes).await?;
+        index.insert(path.to_path_buf(), len);
+
+        while index.size > self.max_size {
+            let Some(evicted) = index.least_recently_used(path) else {
+                break;
+            };
+            match fs::remove_file(&evicted).await {
+                Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+                _ => index.remove(&evicted),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: &Path) -> Result<()> {
+        let mut index = self.index.lock().await;
+        fs::remove_file(path).await?;
+        index.remove(path);
+        Ok(())
+    }
+}
+
+// The files cached, with their total size, ordered by how recently they were used.
+#[derive(Debug, Default)]
+struct CacheIndex {
+    size: u64,
+    // Size of each file, and the use it was last used at.
+    files: HashMap<PathBuf, (u64, u64)>,
+    // The files by the use they were last used at, the least recently used first.
+    by_use: BTreeMap<u64, PathBuf>,
+    uses: u64,
+}
+
+impl CacheIndex {
+    // Records the file as used the most recently, with the given size.
+    fn insert(&mut self, path: PathBuf, size: u64) {
+        self.remove(&path);
+        self.uses += 1;
+        self.size += size;
+        let _ = self.by_use.insert(self.uses, path.clone());
+        let _ = self.files.insert(path, (size, self.uses));
+    }
+
+    // Records the file as used the most recently, returning whether it's cached.
+    fn touch(&mut self, path: &Path) -> bool {
+        let Some((_, last_use)) = self.files.get_mut(path) else {
+            return false;
+        };
+        let _ = self.by_use.remove(last_use);
+        self.uses += 1;
+        *last_use = self.uses;
+        let _ = self.by_use.insert(self.uses, path.to_path_buf());
+        true
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some((size, last_use)) = self.files.remove(path) {
+            let _ = self.by_use.remove(&last_use);
+            self.size = self.size.saturating_sub(size);
+        }
+    }
+
+    // The file used the least recently, other than the one given.
+    fn least_recently_used(&self, other_than: &Path) -> Option<PathBuf> {
+        self.by_use
+            .values()
+            .find(|path| path.as_path() != other_than)
+            .cloned()
+    }
+}
+
+impl Client {
+    // Returns the chunk from the on-disk cache, if there's one and the chunk is cached.
+    pub(crate) async fn get_chunk_from_disk_cache(&self, name: &XorName) -> Option<Chunk> {
+        self.disk_cache.as_ref()?.get_chunk(name).await
+    }
+
+    // Caches the chunk in the on-disk cache, if there's one.
+    pub(crate) async fn put_chunk_in_disk_cache(&self, chunk: &Chunk) {
+        if let Some(cache) = &self.disk_cache {
+            if let Err(error) = cache.put_chunk(chunk).await {
+                warn!("Failed to cache chunk {:?}: {error}", chunk.name());
+            }
+        }
+    }
+
+    // Merges the Register retrieved from the network with the last replica of it retrieved before,
+    // if there's an on-disk cache, which is then updated with the one just retrieved as is.
+    pub(crate) async fn merge_with_cached_register(&self, register: Register) -> Register {
+        let cache = match &self.disk_cache {
+            Some(cache) => cache,
+            None => return register,
+        };
+
+        let cached = cache.get_register(register.address()).await;
+        if let Err(error) = cache.put_register(&register).await {
+            warn!("Failed to cache Register {:?}: {error}", register.address());
+        }
+
+        let mut merged = register.clone();
+        if let Some(cached) = cached {
+            if let Err(error) = merged.merge(cached) {
+                warn!(
+                    "Discarding cached Register {:?} which can't be merged: {error}",
+                    register.address()
+                );
+                return register;
+            }
+        }
+
+        merged
+    }
+}
+
+// Write to a temporary file first, so that an interruption
+// while writing it doesn't leave a corrupted file behind.
+async fn write_atomically(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bytes).await?;
+    fs::rename(tmp_path, path).await?;
+    Ok(())
+}
+
+// Path, size and last modification time of the files in the directory.
+async fn cached_files(dir: &Path) -> Result<Vec<(PathBuf, u64, Option<SystemTime>)>> {
+    let mut files = vec![];
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if metadata.is_file() {
+            files.push((entry.path(), metadata.len(), metadata.modified().ok()));
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskCache;
+
+    use bytes::Bytes;
+    use eyre::Result;
+    use sn_interface::types::{
+        register::{Register, User},
+        Chunk, Keypair,
+    };
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn chunks_cache_is_kept_within_its_max_size() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = DiskCache::open(dir.path(), 10).await?;
+
+        let first = Chunk::new(Bytes::from_static(b"first"));
+        let second = Chunk::new(Bytes::from_static(b"second"));
+        cache.put_chunk(&first).await?;
+        assert_eq!(cache.get_chunk(first.name()).await, Some(first.clone()));
+
+        // the first chunk is evicted to make room for the second one
+        cache.put_chunk(&second).await?;
+        assert_eq!(cache.get_chunk(first.name()).await, None);
+        assert_eq!(cache.get_chunk(second.name()).await, Some(second.clone()));
+
+        // and the cache is persisted across instances
+        let cache = DiskCache::open(dir.path(), 10).await?;
+        assert_eq!(cache.get_chunk(second.name()).await, Some(second));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_chunk_is_evicted() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = DiskCache::open(dir.path(), 11).await?;
+
+        let first = Chunk::new(Bytes::from_static(b"first"));
+        let second = Chunk::new(Bytes::from_static(b"second"));
+        let third = Chunk::new(Bytes::from_static(b"third"));
+        cache.put_chunk(&first).await?;
+        cache.put_chunk(&second).await?;
+
+        // reading the first chunk makes the second one the least recently used
+        assert_eq!(cache.get_chunk(first.name()).await, Some(first.clone()));
+        cache.put_chunk(&third).await?;
+        assert_eq!(cache.get_chunk(second.name()).await, None);
+        assert_eq!(cache.get_chunk(first.name()).await, Some(first));
+        assert_eq!(cache.get_chunk(third.name()).await, Some(third));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn last_known_register_replica_is_cached() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = DiskCache::open(dir.path(), 1024).await?;
+
+        let owner = User::Key(Keypair::new_ed25519().public_key());
+        let mut register = Register::new_owned(owner, xor_name::rand::random(), 15_000);
+        assert_eq!(cache.get_register(register.address()).await, None);
+
+        let _ = register.write(b"entry".to_vec(), Default::default())?;
+        cache.put_register(&register).await?;
+        assert_eq!(cache.get_register(register.address()).await, Some(register));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn registers_are_kept_within_the_cache_max_size() -> Result<()> {
+        let dir = tempdir()?;
+        let owner = User::Key(Keypair::new_ed25519().public_key());
+        let register = Register::new_owned(owner, xor_name::rand::random(), 15_000);
+        let register_size = rmp_serde::to_vec(&register)?.len() as u64;
+        let cache = DiskCache::open(dir.path(), register_size + 4).await?;
+
+        let chunk = Chunk::new(Bytes::from_static(b"chunk"));
+        cache.put_chunk(&chunk).await?;
+        assert_eq!(cache.get_chunk(chunk.name()).await, Some(chunk.clone()));
+
+        // the chunk is evicted to make room for the Register
+        cache.put_register(&register).await?;
+        assert_eq!(cache.get_chunk(chunk.name()).await, None);
+        assert_eq!(
+            cache.get_register(register.address()).await,
+            Some(register.clone())
+        );
+
+        // and the Registers are accounted for across instances
+        let cache = DiskCache::open(dir.path(), register_size + 4).await?;
+        cache.put_chunk(&chunk).await?;
+        assert_eq!(cache.get_register(register.address()).await, None);
+
+        Ok(())
+    }
+}
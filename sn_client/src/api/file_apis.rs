@@ -7,29 +7,45 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    data::{encrypt_large, to_chunk, LargeFile, SmallFile},
-    Client,
+    data::{encrypt_large, pack_segments, to_chunk, LargeFile, SegmentReader, SmallFile},
+    ChunkStatus, Client, FileProgress, StoragePayments, UploadJournal,
 };
 use crate::{api::data::DataMapLevel, Error, Result};
 
 use sn_interface::{
     messaging::data::{DataCmd, DataQuery, QueryResponse},
-    types::{Chunk, ChunkAddress},
+    types::{fees::MAX_PAID_ITEMS, Chunk, ChunkAddress},
 };
 
 use bincode::deserialize;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures::future::join_all;
 use itertools::Itertools;
 use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk};
-use std::collections::BTreeMap;
-use tokio::task;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::{Path, PathBuf},
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    task,
+};
 use tracing::trace;
 use xor_name::XorName;
 
 // Maximum number of concurrent chunks to be uploaded/retrieved for a file
 const CHUNKS_BATCH_MAX_SIZE: usize = 5;
 
+// What the data map found at the head address of some data unpacks to.
+enum UnpackedData {
+    // The data map of the whole data.
+    DataMap(DataMap),
+    // The head addresses, and sizes, of the segments the data was split into when streamed.
+    Segments(Vec<(XorName, usize)>),
+}
+
 /// List of results obtained when querying a chunk to several replicas.
 // TODO: expand this definition to support other types of data like Registers.
 #[derive(Debug)]
@@ -47,14 +63,65 @@ impl Client {
         let chunk = self.get_chunk(&address).await?;
 
         // first try to deserialize a LargeFile, if it works, we go and seek it
-        if let Ok(data_map) = self.unpack_chunk(chunk.clone()).await {
-            self.read_all(data_map).await
-        } else {
+        match self.unpack_chunk(chunk.clone()).await {
+            Ok(UnpackedData::DataMap(data_map)) => {
+                let bytes = self.read_all(data_map).await?;
+                self.report_progress(FileProgress::BytesFetched(bytes.len()));
+                Ok(bytes)
+            }
+            Ok(UnpackedData::Segments(segments)) => {
+                let mut bytes = BytesMut::new();
+                for (head_address, _) in segments {
+                    let data_map = self.get_segment_data_map(head_address).await?;
+                    let segment = self.read_all(data_map).await?;
+                    self.report_progress(FileProgress::BytesFetched(segment.len()));
+                    bytes.extend(segment);
+                }
+                Ok(bytes.freeze())
+            }
             // if an error occurs, we assume it's a SmallFile
-            Ok(chunk.value().clone())
+            Err(_) => {
+                self.report_progress(FileProgress::BytesFetched(chunk.value().len()));
+                Ok(chunk.value().clone())
+            }
         }
     }
 
+    /// Reads the data stored at the given address from the network, and writes it to the given writer,
+    /// returning the number of bytes written.
+    ///
+    /// Unlike [`Client::read_bytes`], the data is not held in memory as a whole,
+    /// as it is fetched, decrypted and written in batches of chunks.
+    #[instrument(skip(self, writer), level = "debug")]
+    pub async fn download_to_writer<W: AsyncWrite + Unpin + Send>(
+        &self,
+        address: XorName,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let chunk = self.get_chunk(&address).await?;
+
+        let written = match self.unpack_chunk(chunk.clone()).await {
+            Ok(UnpackedData::DataMap(data_map)) => self.write_all(data_map, writer).await?,
+            Ok(UnpackedData::Segments(segments)) => {
+                let mut written = 0;
+                for (head_address, _) in segments {
+                    let data_map = self.get_segment_data_map(head_address).await?;
+                    written += self.write_all(data_map, writer).await?;
+                }
+                written
+            }
+            // if an error occurs, we assume it's a SmallFile
+            Err(_) => {
+                writer.write_all(chunk.value()).await?;
+                self.report_progress(FileProgress::BytesFetched(chunk.value().len()));
+                chunk.value().len() as u64
+            }
+        };
+        writer.flush().await?;
+
+        Ok(written)
+    }
+
     #[instrument(skip(self), level = "debug")]
     /// Reads [`Bytes`] from the network, querying each of the data
     /// replicas which match any of the indexes provided.
@@ -69,11 +136,26 @@ impl Client {
         if let Some(chunk) = found_chunk {
             // first try to deserialize a LargeFile, if it works, retrieve all unpacked chunks.
             // if an error occurs, we assume it's a SmallFile
-            if let Ok(data_map) = self.unpack_chunk(chunk).await {
-                chunks_replicas.extend(
-                    self.get_chunks_from_replicas(data_map.infos(), replicas)
-                        .await?,
-                );
+            match self.unpack_chunk(chunk).await {
+                Ok(UnpackedData::DataMap(data_map)) => {
+                    chunks_replicas.extend(
+                        self.get_chunks_from_replicas(data_map.infos(), replicas)
+                            .await?,
+                    );
+                }
+                Ok(UnpackedData::Segments(segments)) => {
+                    for (head_address, _) in segments {
+                        let (head_replicas, _) =
+                            self.get_chunk_from_replicas(head_address, replicas).await?;
+                        chunks_replicas.push(head_replicas);
+                        let data_map = self.get_segment_data_map(head_address).await?;
+                        chunks_replicas.extend(
+                            self.get_chunks_from_replicas(data_map.infos(), replicas)
+                                .await?,
+                        );
+                    }
+                }
+                Err(_) => {}
             }
         }
 
@@ -98,8 +180,14 @@ impl Client {
 
         // First try to deserialize a LargeFile, if it works, we go and seek it.
         // If an error occurs, we consider it to be a SmallFile.
-        if let Ok(data_map) = self.unpack_chunk(chunk.clone()).await {
-            return self.seek(data_map, position, length).await;
+        match self.unpack_chunk(chunk.clone()).await {
+            Ok(UnpackedData::DataMap(data_map)) => {
+                return self.seek(data_map, position, length).await;
+            }
+            Ok(UnpackedData::Segments(segments)) => {
+                return self.seek_segments(segments, position, length).await;
+            }
+            Err(_) => {}
         }
 
         // The error above is ignored to avoid leaking the storage format detail of SmallFiles and LargeFiles.
@@ -109,6 +197,7 @@ impl Client {
 
         let _ = bytes.split_to(position);
         bytes.truncate(length);
+        self.report_progress(FileProgress::BytesFetched(bytes.len()));
 
         Ok(bytes)
     }
@@ -126,6 +215,12 @@ impl Client {
             return Ok(chunk.clone());
         }
 
+        if let Some(chunk) = self.get_chunk_from_disk_cache(name).await {
+            trace!("Chunk retrieved from disk cache: {name:?}");
+            let _ = self.chunks_cache.write().await.insert(chunk.clone());
+            return Ok(chunk);
+        }
+
         let query = DataQuery::GetChunk(ChunkAddress(*name));
         let response = self.send_query(query.clone()).await?;
 
@@ -137,6 +232,7 @@ impl Client {
         }?;
 
         let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        self.put_chunk_in_disk_cache(&chunk).await;
 
         Ok(chunk)
     }
@@ -144,6 +240,34 @@ impl Client {
     /// Tries to chunk the bytes, returning an address and chunks, without storing anything to network.
     #[instrument(skip_all, level = "trace")]
     pub fn chunk_bytes(bytes: Bytes) -> Result<(XorName, Vec<Chunk>)> {
+        Self::chunk_segment(bytes)
+    }
+
+    /// Tries to chunk the data read from the given reader, returning the address it would be stored at
+    /// along with all the chunks to store, without storing anything to network.
+    /// Unlike [`Client::calculate_address_from_reader`], all the chunks are held in memory.
+    ///
+    /// The data is split into segments as when it's streamed, see [`Client::upload_from_reader`].
+    #[instrument(skip_all, level = "trace")]
+    pub fn chunk_reader(reader: impl Read) -> Result<(XorName, Vec<Chunk>)> {
+        let mut segments = SegmentReader::new(reader);
+        let mut heads = vec![];
+        let mut all_chunks = vec![];
+        while let Some(segment) = segments.next_segment()? {
+            let size = segment.len();
+            let (head_address, chunks) = Self::chunk_segment(segment)?;
+            heads.push((head_address, size));
+            all_chunks.extend(chunks);
+        }
+
+        let (address, chunks) = pack_segments(heads)?;
+        all_chunks.extend(chunks);
+
+        Ok((address, all_chunks))
+    }
+
+    // Chunks the data in a single pass, without splitting it into segments.
+    fn chunk_segment(bytes: Bytes) -> Result<(XorName, Vec<Chunk>)> {
         match LargeFile::new(bytes.clone()) {
             Ok(file) => Self::encrypt_large(file),
             Err(Error::TooSmallForSelfEncryption { .. }) => {
@@ -155,6 +279,44 @@ impl Client {
         }
     }
 
+    /// Tries to chunk the data read from the given reader, returning the address it would be stored at,
+    /// without storing anything to network.
+    /// The data is read and self-encrypted in segments, thus it's never held in memory as a whole.
+    #[instrument(skip_all, level = "trace")]
+    pub fn calculate_address_from_reader(reader: impl Read) -> Result<XorName> {
+        let mut segments = SegmentReader::new(reader);
+        let mut heads = vec![];
+        while let Some(segment) = segments.next_segment()? {
+            let size = segment.len();
+            let (head_address, _) = Self::chunk_segment(segment)?;
+            heads.push((head_address, size));
+        }
+        pack_segments(heads).map(|(address, _)| address)
+    }
+
+    /// Tries to chunk the file at the given path, returning the address it would be stored at
+    /// along with all the chunks to store, without storing anything to network.
+    /// The file is read, and its data self-encrypted, off the async runtime.
+    /// See [`Client::chunk_reader`].
+    #[instrument(skip_all, level = "trace")]
+    pub async fn chunk_path(path: &Path) -> Result<(XorName, Vec<Chunk>)> {
+        let path = path.to_path_buf();
+        task::spawn_blocking(move || Self::chunk_reader(BufReader::new(File::open(path)?))).await?
+    }
+
+    /// Tries to chunk the file at the given path, returning the address it would be stored at,
+    /// without storing anything to network.
+    /// The file is read, and its data self-encrypted, off the async runtime.
+    /// See [`Client::calculate_address_from_reader`].
+    #[instrument(skip_all, level = "trace")]
+    pub async fn calculate_address_from_path(path: &Path) -> Result<XorName> {
+        let path = path.to_path_buf();
+        task::spawn_blocking(move || {
+            Self::calculate_address_from_reader(BufReader::new(File::open(path)?))
+        })
+        .await?
+    }
+
     /// Encrypts a [`LargeFile`] and returns the resulting address and all chunks.
     /// Does not store anything to the network.
     #[instrument(skip(file), level = "trace")]
@@ -188,6 +350,57 @@ impl Client {
         self.upload_bytes(bytes, true).await
     }
 
+    /// Writes the data read from the given reader to the network in the form of immutable chunks.
+    ///
+    /// Unlike [`Client::upload`], the data is never held in memory as a whole. It's read twice,
+    /// in segments of a bounded size: first to learn the names of all the chunks, so they're all
+    /// paid for before any is stored, then to self-encrypt and store each segment before reading the next.
+    /// Data fitting in a single segment is stored at the same address [`Client::upload`] would store it,
+    /// while larger data is stored at the address of a data map of its segments.
+    /// The reader is read, and its data self-encrypted, off the async runtime.
+    /// If `verify` is set, it's also verified that each chunk was stored, as with [`Client::upload_and_verify`].
+    #[instrument(skip(self, reader), level = "debug")]
+    pub async fn upload_from_reader<R: Read + Seek + Send + 'static>(
+        &self,
+        reader: R,
+        verify: bool,
+    ) -> Result<XorName> {
+        self.upload_segments(reader, verify, None).await
+    }
+
+    /// Writes the file at the given path to the network, as [`Client::upload_from_path`] does,
+    /// while recording the progress of the upload in the [`UploadJournal`] persisted at `journal_path`.
+    ///
+    /// If the journal found there was left by a previous upload of the same file which was interrupted,
+    /// the upload is resumed, with only the chunks which were not stored yet being sent, and only those
+    /// which were not paid for yet being paid for, the proofs of the payments made being recorded in it.
+    /// The journal is removed once the upload is complete.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn upload_from_path_resumable(
+        &self,
+        path: &Path,
+        journal_path: &Path,
+        verify: bool,
+    ) -> Result<XorName> {
+        let mut journal = UploadJournal::open(journal_path, path)?;
+        let reader = Self::open_file(path.to_path_buf()).await?;
+        let address = self
+            .upload_segments(reader, verify, Some(&mut journal))
+            .await?;
+
+        journal.remove()?;
+
+        Ok(address)
+    }
+
+    /// Writes the file at the given path to the network in the form of immutable chunks.
+    /// See [`Client::upload_from_reader`].
+    #[instrument(skip(self), level = "debug")]
+    pub async fn upload_from_path(&self, path: &Path, verify: bool) -> Result<XorName> {
+        let reader = Self::open_file(path.to_path_buf()).await?;
+        self.upload_from_reader(reader, verify).await
+    }
+
     /// Calculates a LargeFile's/SmallFile's address from self encrypted chunks,
     /// without storing them onto the network.
     #[instrument(skip(bytes), level = "debug")]
@@ -199,23 +412,161 @@ impl Client {
     // ---------- Private helpers -----------------
     // --------------------------------------------
 
+    /// Directly writes [`Bytes`] to the network in the form of immutable chunks,
+    /// all of them being paid for before any is stored.
     #[instrument(skip(self, bytes), level = "trace")]
     async fn upload_bytes(&self, bytes: Bytes, verify: bool) -> Result<XorName> {
-        match LargeFile::new(bytes.clone()) {
-            Ok(file) => self.upload_large(file, verify).await,
-            Err(Error::TooSmallForSelfEncryption { .. }) => {
-                let file = SmallFile::new(bytes)?;
-                self.upload_small(file, verify).await
+        let (address, all_chunks) = Self::chunk_bytes(bytes)?;
+        self.store_chunks(all_chunks, verify, None).await?;
+        Ok(address)
+    }
+
+    // Opens the file at the given path off the async runtime.
+    async fn open_file(path: PathBuf) -> Result<BufReader<File>> {
+        let file = task::spawn_blocking(move || File::open(path)).await??;
+        Ok(BufReader::new(file))
+    }
+
+    /// Streams the data of the reader to the network, segment by segment, paying for all the chunks
+    /// first. See [`Client::upload_from_reader`].
+    /// If a journal is provided, those chunks it records as stored already are neither paid for nor stored,
+    /// and those it records a payment for are not paid for again.
+    #[instrument(skip_all, level = "trace")]
+    async fn upload_segments<R: Read + Seek + Send + 'static>(
+        &self,
+        reader: R,
+        verify: bool,
+        mut journal: Option<&mut UploadJournal>,
+    ) -> Result<XorName> {
+        // First pass: learn the name of every chunk without holding them all in memory.
+        let (mut segments, heads, mut names) = task::spawn_blocking(move || {
+            let mut segments = SegmentReader::new(reader);
+            let mut heads = vec![];
+            let mut names = BTreeSet::new();
+            while let Some(segment) = segments.next_segment()? {
+                let size = segment.len();
+                let (head_address, chunks) = Self::chunk_segment(segment)?;
+                heads.push((head_address, size));
+                names.extend(chunks.iter().map(|chunk| *chunk.name()));
             }
-            Err(error) => Err(error),
+            Ok::<_, Error>((segments.rewind()?, heads, names))
+        })
+        .await??;
+
+        let (address, head_chunks) = pack_segments(heads.clone())?;
+        names.extend(head_chunks.iter().map(|chunk| *chunk.name()));
+        if let Some(journal) = journal.as_deref_mut() {
+            journal.head_address = Some(address);
+            names.retain(|name| !journal.is_stored(name, verify));
         }
+        if names.is_empty() {
+            return Ok(address);
+        }
+        let payments = self.pay_for_chunks(names, journal.as_deref_mut()).await?;
+
+        // Second pass: self-encrypt and store each segment before reading the next.
+        for (index, (expected_head, _)) in heads.into_iter().enumerate() {
+            let (read_back, next) = task::spawn_blocking(move || {
+                let next = segments
+                    .next_segment()?
+                    .map(Self::chunk_segment)
+                    .transpose()?;
+                Ok::<_, Error>((segments, next))
+            })
+            .await??;
+            segments = read_back;
+
+            match next {
+                Some((head_address, chunks)) if head_address == expected_head => {
+                    self.store_paid_chunks(chunks, &payments, verify, journal.as_deref_mut())
+                        .await?;
+                }
+                _ => return Err(Error::StreamedDataChanged { segment: index }),
+            }
+        }
+        self.store_paid_chunks(head_chunks, &payments, verify, journal)
+            .await?;
+
+        Ok(address)
     }
 
-    /// Directly writes a [`LargeFile`] to the network in the
-    /// form of immutable self encrypted chunks, without any batching.
+    /// Stores the chunks to the network in batches, paying for all of them first.
+    /// If a journal is provided, those chunks it records as stored already are skipped,
+    /// those it records a payment for are not paid for again, and the status of the others
+    /// is recorded in it as they are stored.
     #[instrument(skip_all, level = "trace")]
-    async fn upload_large(&self, large: LargeFile, verify: bool) -> Result<XorName> {
-        let (head_address, all_chunks) = Self::encrypt_large(large)?;
+    pub(crate) async fn store_chunks(
+        &self,
+        mut all_chunks: Vec<Chunk>,
+        verify: bool,
+        mut journal: Option<&mut UploadJournal>,
+    ) -> Result<()> {
+        if let Some(journal) = journal.as_deref() {
+            all_chunks.retain(|chunk| !journal.is_stored(chunk.name(), verify));
+        }
+        if all_chunks.is_empty() {
+            return Ok(());
+        }
+
+        let payments = self
+            .pay_for_chunks(
+                all_chunks.iter().map(|chunk| *chunk.name()).collect(),
+                journal.as_deref_mut(),
+            )
+            .await?;
+
+        self.store_paid_chunks(all_chunks, &payments, verify, journal)
+            .await
+    }
+
+    /// Pays for storing the chunks with the given names.
+    /// If a journal is provided, the chunks it records a payment for are not paid for again, and
+    /// each payment is recorded in it as soon as it's made, for it to be reused by a resumed upload.
+    async fn pay_for_chunks(
+        &self,
+        mut names: BTreeSet<XorName>,
+        journal: Option<&mut UploadJournal>,
+    ) -> Result<StoragePayments> {
+        let Some(journal) = journal else {
+            return self.pay_for_storage(names).await;
+        };
+
+        names.retain(|name| !journal.is_paid(name));
+        let names = names.into_iter().collect_vec();
+        for batch in names.chunks(MAX_PAID_ITEMS) {
+            let payments = self
+                .pay_for_storage(batch.iter().copied().collect())
+                .await?;
+            journal.add_payments(payments);
+            journal.save()?;
+        }
+
+        Ok(journal.payments.clone())
+    }
+
+    /// Stores the chunks to the network in batches, with a payment made for them already.
+    /// If a journal is provided, it's handled as by [`Client::store_chunks`].
+    #[instrument(skip_all, level = "trace")]
+    async fn store_paid_chunks(
+        &self,
+        mut all_chunks: Vec<Chunk>,
+        payments: &StoragePayments,
+        verify: bool,
+        mut journal: Option<&mut UploadJournal>,
+    ) -> Result<()> {
+        if let Some(journal) = journal.as_deref_mut() {
+            all_chunks.retain(|chunk| !journal.is_stored(chunk.name(), verify));
+            for chunk in &all_chunks {
+                journal.add_pending(*chunk.name());
+            }
+            journal.save()?;
+        }
+
+        if all_chunks.is_empty() {
+            return Ok(());
+        }
+        self.report_progress(FileProgress::ChunksEncrypted(all_chunks.len()));
+
         for next_batch in all_chunks.chunks(CHUNKS_BATCH_MAX_SIZE) {
             // Connect to all relevant elders before we fire off all msgs...
             self.session
@@ -224,13 +575,26 @@ impl Client {
 
             let tasks = next_batch.iter().cloned().map(|chunk| {
                 let client_clone = self.clone();
+                let payment = payments.proof_for(chunk.name()).cloned();
                 task::spawn(async move {
                     let chunk_addr = *chunk.address().name();
-                    client_clone.send_cmd(DataCmd::StoreChunk(chunk)).await?;
-                    if verify {
-                        client_clone.verify_chunk_is_stored(chunk_addr).await?;
+                    let result = async {
+                        let payment = payment.ok_or(Error::MissingStorePayment(chunk_addr))?;
+                        client_clone.report_progress(FileProgress::ChunkSent(chunk_addr));
+                        client_clone
+                            .send_paid_cmd(DataCmd::StoreChunk(chunk), payment)
+                            .await?;
+                        client_clone.report_progress(FileProgress::ChunkAcked(chunk_addr));
+                        if verify {
+                            client_clone.verify_chunk_is_stored(chunk_addr).await?;
+                            client_clone.report_progress(FileProgress::ChunkVerified(chunk_addr));
+                            Ok::<_, Error>(ChunkStatus::Verified)
+                        } else {
+                            Ok(ChunkStatus::Acked)
+                        }
                     }
-                    Ok::<(), Error>(())
+                    .await;
+                    (chunk_addr, result)
                 })
             });
 
@@ -240,28 +604,22 @@ impl Client {
                 .flatten() // swallows errors
                 .collect_vec();
 
-            for res in respones {
-                // fail with any issue here
-                res?;
+            if let Some(journal) = journal.as_deref_mut() {
+                for (name, res) in &respones {
+                    if let Ok(status) = res {
+                        journal.set_status(*name, *status);
+                    }
+                }
+                journal.save()?;
             }
-        }
 
-        Ok(head_address)
-    }
-
-    /// Directly writes a [`SmallFile`] to the network in the
-    /// form of a single chunk, without any batching.
-    #[instrument(skip_all, level = "trace")]
-    async fn upload_small(&self, small: SmallFile, verify: bool) -> Result<XorName> {
-        let chunk = Self::package_small(small)?;
-        let address = *chunk.name();
-        self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
-
-        if verify {
-            self.verify_chunk_is_stored(address).await?;
+            for (_, res) in respones {
+                // fail with any issue here
+                let _status = res?;
+            }
         }
 
-        Ok(address)
+        Ok(())
     }
 
     // Verify a chunk is stored at provided address
@@ -288,6 +646,58 @@ impl Client {
         Ok(bytes)
     }
 
+    // Gets and decrypts chunks from the network in batches using nothing else but the data map,
+    // writing the raw data of each batch to the writer before fetching the next one.
+    async fn write_all<W: AsyncWrite + Unpin + Send>(
+        &self,
+        data_map: DataMap,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let mut written = 0;
+        for next_batch in data_map.infos().chunks(CHUNKS_BATCH_MAX_SIZE) {
+            let encrypted_chunks = self.try_get_chunks(next_batch.to_vec()).await?;
+            let bytes =
+                self_encryption::decrypt_range(&data_map, &encrypted_chunks, 0, usize::MAX)?;
+            writer.write_all(&bytes).await?;
+            self.report_progress(FileProgress::BytesFetched(bytes.len()));
+            written += bytes.len() as u64;
+        }
+        Ok(written)
+    }
+
+    // Reads `len` bytes of the data starting at given `pos` of the original data,
+    // from those segments it was split into which are covered by that range.
+    async fn seek_segments(
+        &self,
+        segments: Vec<(XorName, usize)>,
+        pos: usize,
+        len: usize,
+    ) -> Result<Bytes> {
+        let end = pos.saturating_add(len);
+        let mut bytes = BytesMut::new();
+        let mut segment_start = 0;
+        for (head_address, size) in segments {
+            let segment_end = segment_start + size;
+            if segment_end > pos && end > segment_start {
+                let relative_pos = pos.saturating_sub(segment_start);
+                let relative_len = usize::min(end, segment_end) - segment_start - relative_pos;
+                let data_map = self.get_segment_data_map(head_address).await?;
+                bytes.extend(self.seek(data_map, relative_pos, relative_len).await?);
+            }
+            segment_start = segment_end;
+        }
+        Ok(bytes.freeze())
+    }
+
+    // Gets the data map of a segment the data was split into when streamed.
+    async fn get_segment_data_map(&self, head_address: XorName) -> Result<DataMap> {
+        let chunk = self.get_chunk(&head_address).await?;
+        match self.unpack_chunk(chunk).await {
+            Ok(UnpackedData::DataMap(data_map)) => Ok(data_map),
+            _ => Err(Error::InvalidSegment(head_address)),
+        }
+    }
+
     // Gets a subset of chunks from the network, decrypts and
     // reads `len` bytes of the data starting at given `pos` of original file.
     #[instrument(skip_all, level = "trace")]
@@ -307,6 +717,7 @@ impl Client {
 
         let bytes =
             self_encryption::decrypt_range(&data_map, &encrypted_chunks, info.relative_pos, len)?;
+        self.report_progress(FileProgress::BytesFetched(bytes.len()));
 
         Ok(bytes)
     }
@@ -416,13 +827,17 @@ impl Client {
 
     /// Extracts a file DataMapLevel from a chunk.
     /// If the DataMapLevel is not the first level mapping directly to the user's contents,
-    /// the process repeats itself until it obtains the first level DataMapLevel.
+    /// nor the level listing the segments of the contents,
+    /// the process repeats itself until it obtains one of those.
     #[instrument(skip_all, level = "trace")]
-    async fn unpack_chunk(&self, mut chunk: Chunk) -> Result<DataMap> {
+    async fn unpack_chunk(&self, mut chunk: Chunk) -> Result<UnpackedData> {
         loop {
             match deserialize(chunk.value())? {
                 DataMapLevel::First(data_map) => {
-                    return Ok(data_map);
+                    return Ok(UnpackedData::DataMap(data_map));
+                }
+                DataMapLevel::Segments(segments) => {
+                    return Ok(UnpackedData::Segments(segments));
                 }
                 DataMapLevel::Additional(data_map) => {
                     let serialized_chunk = self.read_all(data_map).await?;
@@ -437,6 +852,7 @@ impl Client {
 mod tests {
     use super::LargeFile;
     use crate::{
+        api::FileProgress,
         utils::test_utils::{create_test_client, init_logger, try_create_test_client},
         Client,
     };
@@ -484,6 +900,111 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn streamed_address_matches_stored_bytes_address_within_a_segment() -> Result<()> {
+        #[cfg(not(feature = "limit-client-upload-size"))]
+        use crate::api::data::SEGMENT_SIZE;
+        init_logger();
+        #[allow(unused_mut)]
+        let mut sizes = vec![
+            MIN_ENCRYPTABLE_BYTES / 3,
+            MIN_ENCRYPTABLE_BYTES,
+            1024 * 1024,
+        ];
+        #[cfg(not(feature = "limit-client-upload-size"))]
+        sizes.push(SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES - 1);
+        for size in sizes {
+            let bytes = random_bytes(size);
+            assert_eq!(
+                Client::calculate_address(bytes.clone())?,
+                Client::calculate_address_from_reader(bytes.as_ref())?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "limit-client-upload-size"))]
+    fn stored_bytes_larger_than_a_segment_are_chunked_in_a_single_pass() -> Result<()> {
+        use crate::api::data::{encrypt_large, SEGMENT_SIZE};
+        init_logger();
+        let bytes = random_bytes(2 * SEGMENT_SIZE + MIN_ENCRYPTABLE_BYTES);
+
+        let (address, mut chunks) = Client::chunk_bytes(bytes.clone())?;
+        let (expected_address, mut expected_chunks) = encrypt_large(bytes.clone())?;
+        assert_eq!(address, expected_address);
+        chunks.sort();
+        expected_chunks.sort();
+        assert_eq!(chunks, expected_chunks);
+
+        // the data map of the segments streamed data is split into is stored elsewhere
+        assert_ne!(
+            address,
+            Client::calculate_address_from_reader(bytes.as_ref())?
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[cfg(not(feature = "limit-client-upload-size"))]
+    async fn store_and_read_streamed_25mb() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("store_and_read_streamed_25mb").entered();
+        let client = create_test_client().await?;
+
+        let bytes = random_bytes(25 * 1024 * 1024);
+        let expected_address = Client::calculate_address_from_reader(bytes.as_ref())?;
+        let address = client
+            .upload_from_reader(std::io::Cursor::new(bytes.clone()), true)
+            .await?;
+        assert_eq!(address, expected_address);
+
+        let mut downloaded = vec![];
+        let written = client.download_to_writer(address, &mut downloaded).await?;
+        assert_eq!(written as usize, bytes.len());
+        compare(bytes.clone(), Bytes::from(downloaded));
+
+        compare(bytes.clone(), client.read_bytes(address).await?);
+
+        // read across the boundary of the first two segments
+        let pos = 10 * 1024 * 1024 - 512;
+        let len = 1024;
+        let read_data = client.read_from(address, pos, len).await?;
+        compare(bytes.slice(pos..pos + len), read_data);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_reports_progress() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("store_and_read_reports_progress").entered();
+        let client = create_test_client().await?;
+        let mut progress = client.file_progress();
+
+        let bytes = random_bytes(2 * MIN_ENCRYPTABLE_BYTES);
+        let address = client.upload_and_verify(bytes.clone()).await?;
+        let _ = client.read_bytes(address).await?;
+
+        let (mut encrypted, mut sent, mut acked, mut verified, mut fetched) = (0, 0, 0, 0, 0);
+        while let Ok(event) = progress.try_recv() {
+            match event {
+                FileProgress::ChunksEncrypted(count) => encrypted += count,
+                FileProgress::ChunkSent(_) => sent += 1,
+                FileProgress::ChunkAcked(_) => acked += 1,
+                FileProgress::ChunkVerified(_) => verified += 1,
+                FileProgress::BytesFetched(bytes) => fetched += bytes,
+            }
+        }
+        assert!(encrypted > 0);
+        assert_eq!(encrypted, sent);
+        assert_eq!(encrypted, acked);
+        assert_eq!(encrypted, verified);
+        assert_eq!(fetched, bytes.len());
+
+        Ok(())
+    }
+
     // Test storing and reading min sized LargeFile.
     #[tokio::test(flavor = "multi_thread")]
     async fn store_and_read_3kb() -> Result<()> {
@@ -502,7 +1023,7 @@ mod tests {
         // Test storing file with the same value.
         // Should not conflict and should return same address
         let reupload_address = client
-            .upload_large(file.clone(), false)
+            .upload(file.bytes())
             .instrument(tracing::info_span!(
                 "checking no conflict on same private upload"
             ))
@@ -768,7 +1289,7 @@ mod tests {
             .map(|(i, client)| {
                 tokio::spawn(async move {
                     let file = LargeFile::new(random_bytes(MIN_ENCRYPTABLE_BYTES))?;
-                    let _ = client.upload_large(file, false).await?;
+                    let _ = client.upload(file.bytes()).await?;
                     println!("Iter: {}", i);
                     let res: Result<()> = Ok(());
                     res
@@ -801,7 +1322,7 @@ mod tests {
         for i in 0..1000_usize {
             let file = LargeFile::new(random_bytes(MIN_ENCRYPTABLE_BYTES))?;
             let now = Instant::now();
-            let _ = client.upload_large(file, false).await?;
+            let _ = client.upload(file.bytes()).await?;
             let elapsed = now.elapsed();
             println!("Iter: {}, in {} millis", i, elapsed.as_millis());
         }
@@ -10,24 +10,38 @@
 pub mod client_builder;
 mod cmds;
 mod data;
+mod disk_cache;
 mod file_apis;
+mod progress;
 mod queries;
 mod register_apis;
 mod spend_queries;
 mod spentbook_apis;
+mod storage_payments;
 mod transfers;
+mod upload_journal;
+mod write_batch;
 
 pub use client_builder::ClientBuilder;
 pub use file_apis::QueriedDataReplicas;
-pub use register_apis::RegisterWriteAheadLog;
-pub use transfers::{select_inputs as select_dbc_inputs, send_tokens, Error as TransferError};
+pub use progress::FileProgress;
+pub use register_apis::{
+    sign_register_create, sign_register_policy_edit, sign_register_write, RegisterWriteAheadLog,
+};
+pub use storage_payments::StoragePayments;
+pub use transfers::{
+    pay_for_storage, select_inputs as select_dbc_inputs, send_tokens, Error as TransferError,
+};
+pub use upload_journal::{ChunkStatus, UploadJournal};
+pub use write_batch::WriteBatch;
 
 use crate::{
     errors::{Error, Result},
     sessions::Session,
 };
 
-use sn_dbc::Owner;
+use disk_cache::DiskCache;
+use sn_dbc::{Dbc, Owner};
 use sn_interface::{
     messaging::data::{DataQuery, RegisterQuery},
     network_knowledge::SectionTree,
@@ -35,7 +49,10 @@ use sn_interface::{
 };
 
 use std::sync::Arc;
-use tokio::{sync::RwLock, time::Duration};
+use tokio::{
+    sync::{broadcast, RwLock},
+    time::Duration,
+};
 use tracing::debug;
 use uluru::LRUCache;
 
@@ -61,6 +78,9 @@ pub struct Client {
     pub(crate) max_backoff_interval: Duration,
     pub(crate) cmd_timeout: Option<Duration>,
     chunks_cache: Arc<RwLock<ChunksCache>>,
+    payment_dbcs: Arc<RwLock<Vec<Dbc>>>,
+    file_progress: broadcast::Sender<FileProgress>,
+    disk_cache: Option<Arc<DiskCache>>,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
@@ -0,0 +1,47 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+
+use tokio::sync::broadcast;
+use xor_name::XorName;
+
+// Number of events kept for each subscriber before the oldest ones are dropped.
+pub(crate) const FILE_PROGRESS_CHANNEL_SIZE: usize = 1024;
+
+/// Progress of the file uploads and downloads made by a [`Client`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileProgress {
+    /// Data being uploaded was self-encrypted into the given number of chunks, yet to be stored.
+    ChunksEncrypted(usize),
+    /// The cmd storing the chunk was sent to the network.
+    ChunkSent(XorName),
+    /// The cmd storing the chunk was acknowledged by the network.
+    ChunkAcked(XorName),
+    /// The chunk was read back from the network after it was stored.
+    ChunkVerified(XorName),
+    /// The given number of bytes of the data being downloaded were fetched and decrypted.
+    BytesFetched(usize),
+}
+
+impl Client {
+    /// Subscribe to the progress of the file uploads and downloads made by this client,
+    /// from now on, including those made by any of its clones.
+    ///
+    /// If the events are not received fast enough, the oldest ones are dropped,
+    /// with the receiver being notified of it with `RecvError::Lagged`.
+    pub fn file_progress(&self) -> broadcast::Receiver<FileProgress> {
+        self.file_progress.subscribe()
+    }
+
+    // Notify the subscribers, if any, of the progress of a file upload or download.
+    pub(crate) fn report_progress(&self, event: FileProgress) {
+        // an error only means there are no subscribers currently
+        let _ = self.file_progress.send(event);
+    }
+}
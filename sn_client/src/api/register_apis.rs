@@ -6,24 +6,37 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
+use super::{Client, StoragePayments};
 
 use crate::{Error, Result};
 
 use sn_interface::{
-    messaging::data::{
-        CreateRegister, DataCmd, DataQuery, EditRegister, QueryResponse, RegisterCmd,
-        RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+    messaging::{
+        data::{
+            ClientMsg, CreateRegister, DataCmd, DataQuery, EditRegister, EditRegisterPolicy,
+            QueryResponse, RegisterCmd, RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+            SignedRegisterPolicyEdit, REGISTER_SUBSCRIPTION_TTL,
+        },
+        ClientAuth, WireMsg,
     },
     types::{
-        register::{Action, Entry, EntryHash, Permissions, Policy, Register, User},
-        RegisterAddress as Address,
+        register::{
+            Action, Entry, EntryHash, Permissions, Policy, PolicyEdit, PolicyOp, Register, User,
+        },
+        Keypair, RegisterAddress as Address,
     },
 };
 
+use futures::{stream, Stream};
 use std::collections::BTreeSet;
+use tokio::{sync::mpsc, time::sleep};
+use uluru::LRUCache;
 use xor_name::XorName;
 
+// Number of edits notified to a register watcher which are kept for deduplication,
+// as each edit is notified by every Elder polled
+const WATCHED_EDITS_CACHE_SIZE: usize = 1_000;
+
 /// Register Write Ahead Log
 ///
 /// Batches up register write operation before publishing them up to the network, in order.
@@ -40,10 +53,30 @@ impl Client {
     /// Publish all register mutation operations in a WAL to the network
     /// Incrementing the WAL index as successful writes are sent out. Stops at the first error.
     /// Starts publishing from the index when called again with the same WAL.
+    /// The creation of any register in the WAL is paid for before publishing.
     #[instrument(skip(self), level = "debug")]
     pub async fn publish_register_ops(&self, wal: RegisterWriteAheadLog) -> Result<()> {
+        let to_be_paid: BTreeSet<_> = wal
+            .iter()
+            .filter(|cmd| cmd.requires_payment())
+            .map(|cmd| cmd.dst_name())
+            .collect();
+        let payments = if to_be_paid.is_empty() {
+            StoragePayments::default()
+        } else {
+            self.pay_for_storage(to_be_paid).await?
+        };
+
         for cmd in &wal {
-            self.send_cmd(cmd.clone()).await?;
+            if cmd.requires_payment() {
+                let name = cmd.dst_name();
+                let payment = payments
+                    .proof_for(&name)
+                    .ok_or(Error::MissingStorePayment(name))?;
+                self.send_paid_cmd(cmd.clone(), payment.clone()).await?
+            } else {
+                self.send_cmd(cmd.clone()).await?
+            }
         }
         Ok(())
     }
@@ -63,20 +96,7 @@ impl Client {
         policy: Policy,
     ) -> Result<(Address, RegisterWriteAheadLog)> {
         let address = Address { name, tag };
-
-        let op = CreateRegister { name, tag, policy };
-        let signature = self.keypair.sign(&bincode::serialize(&op)?);
-
-        let cmd = DataCmd::Register(RegisterCmd::Create {
-            cmd: SignedRegisterCreate {
-                op,
-                auth: sn_interface::messaging::ClientAuth {
-                    public_key: self.keypair.public_key(),
-                    signature,
-                },
-            },
-            section_sig: section_sig(), // obtained after presenting a valid payment to the network
-        });
+        let cmd = sign_register_create(&self.keypair, name, tag, policy)?;
 
         debug!("Creating Register: {:?}", cmd);
 
@@ -98,37 +118,33 @@ impl Client {
         // either from local CRDT replica or from the network if not found
         debug!("Writing to register at {:?}", address);
         let mut register = self.get_register(address).await?;
+        let (hash, cmd) = sign_register_write(&self.keypair, &mut register, entry, children)?;
+        Ok((hash, vec![cmd]))
+    }
 
-        // Let's check the policy/permissions to make sure this operation is allowed,
-        // otherwise it will fail when the operation is applied on the network replica.
-        let public_key = self.keypair.public_key();
-        register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
-
-        // We can now write the entry to the Register
-        let (hash, op) = register.write(entry, children)?;
-        let op = EditRegister { address, edit: op };
-
-        let signature = self.keypair.sign(&bincode::serialize(&op)?);
-
-        let edit = SignedRegisterEdit {
-            op,
-            auth: sn_interface::messaging::ClientAuth {
-                public_key,
-                signature,
-            },
-        };
-
-        // Finally we package the mutation for the network's replicas (it's now ready to be sent)
-        let cmd = DataCmd::Register(RegisterCmd::Edit(edit));
-        let batch = vec![cmd];
-        Ok((hash, batch))
+    /// Edit the policy of a Register, e.g. to grant or revoke permissions to users,
+    /// or to hand over its ownership. Only the current owner of the Register can do so.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn edit_register_policy(
+        &self,
+        address: Address,
+        edit: PolicyEdit,
+    ) -> Result<RegisterWriteAheadLog> {
+        debug!("Editing policy of register at {:?}", address);
+        let mut register = self.get_register(address).await?;
+        let cmd = sign_register_policy_edit(&self.keypair, &mut register, edit)?;
+        Ok(vec![cmd])
     }
 
     //----------------------
     // Get Register
     //---------------------
 
-    /// Get the entire Register from the Network
+    /// Get the entire Register from the Network, merged with the last known replica of it
+    /// if the client keeps an on-disk cache, since the network may not have all its entries yet.
     #[instrument(skip(self), level = "debug")]
     pub async fn get_register(&self, address: Address) -> Result<Register> {
         // Let's fetch the Register from the network
@@ -137,7 +153,10 @@ impl Client {
 
         debug!("get_register response is; {response:?}");
         match response {
-            QueryResponse::GetRegister(res) => res.map_err(|err| Error::ErrorMsg { source: err }),
+            QueryResponse::GetRegister(Ok(register)) => {
+                Ok(self.merge_with_cached_register(register).await)
+            }
+            QueryResponse::GetRegister(Err(err)) => Err(Error::ErrorMsg { source: err }),
             other => Err(Error::UnexpectedQueryResponse {
                 query,
                 response: other,
@@ -175,6 +194,97 @@ impl Client {
         }
     }
 
+    //----------------------
+    // Watch Register
+    //---------------------
+
+    /// Watch the edits of a Register as they are sent to the Network.
+    ///
+    /// A few of the Elders holding the Register are polled for the edits they forward
+    /// to its holders, renewing our subscriptions with them before they expire.
+    /// Each edit is yielded once, after the holders of the Register applied it.
+    /// Watching stops once the returned stream is dropped.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn watch_register(
+        &self,
+        address: Address,
+    ) -> Result<impl Stream<Item = SignedRegisterEdit> + Send + Unpin> {
+        let query = DataQuery::Register(RegisterQuery::Subscribe(address));
+        let payload = WireMsg::serialize_msg_payload(&ClientMsg::Query(query.clone()))?;
+        let auth = ClientAuth {
+            public_key: self.public_key(),
+            signature: self.keypair.sign(&payload),
+        };
+
+        // make sure we know the Elders to poll before watching, they're as many as we query
+        let (_, elders) = self.session.get_data_query_elders(query.dst_name()).await?;
+
+        let (sender, receiver) = mpsc::channel(WATCHED_EDITS_CACHE_SIZE);
+        for elder_index in 0..elders.len() {
+            let session = self.session.clone();
+            let query = query.clone();
+            let auth = auth.clone();
+            let payload = payload.clone();
+            let sender = sender.clone();
+            let retry_interval = self.max_backoff_interval;
+
+            let _handle = tokio::spawn(async move {
+                loop {
+                    let poll = session.poll_register_edits(
+                        query.clone(),
+                        elder_index,
+                        auth.clone(),
+                        payload.clone(),
+                        REGISTER_SUBSCRIPTION_TTL / 2,
+                    );
+                    let result = tokio::select! {
+                        result = poll => result,
+                        _ = sender.closed() => break,
+                    };
+
+                    match result {
+                        Ok(edits) => {
+                            for edit in edits {
+                                if sender.send(edit).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(error) => {
+                            let delay = error.rate_limited_retry_after().unwrap_or(retry_interval);
+                            warn!(
+                                "Failed to poll Elder #{elder_index} for edits of register \
+                                {address:?}, polling again in {delay:?}: {error:?}"
+                            );
+                            sleep(delay).await;
+                        }
+                    }
+                }
+                debug!("Stopped polling Elder #{elder_index} for edits of register {address:?}");
+            });
+        }
+
+        let seen_edits = LRUCache::<XorName, WATCHED_EDITS_CACHE_SIZE>::default();
+        let edits = stream::unfold(
+            (receiver, seen_edits),
+            |(mut receiver, mut seen_edits)| async move {
+                loop {
+                    let edit = receiver.recv().await?;
+                    let Ok(serialised_edit) = bincode::serialize(&edit) else {
+                        continue;
+                    };
+                    let id = XorName::from_content(&serialised_edit);
+                    if seen_edits.find(|seen| *seen == id).is_none() {
+                        let _ = seen_edits.insert(id);
+                        break Some((edit, (receiver, seen_edits)));
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(edits))
+    }
+
     //----------------------
     // Ownership
     //---------------------
@@ -236,6 +346,99 @@ impl Client {
     }
 }
 
+/// Signs the creation of a Register with the given keypair, without any network access,
+/// returning the cmd to send to the network, e.g. to publish it later on.
+pub fn sign_register_create(
+    keypair: &Keypair,
+    name: XorName,
+    tag: u64,
+    policy: Policy,
+) -> Result<DataCmd> {
+    let op = CreateRegister { name, tag, policy };
+    let signature = keypair.sign(&bincode::serialize(&op)?);
+
+    Ok(DataCmd::Register(RegisterCmd::Create {
+        cmd: SignedRegisterCreate {
+            op,
+            auth: ClientAuth {
+                public_key: keypair.public_key(),
+                signature,
+            },
+        },
+        section_sig: section_sig(), // the cmd is instead paid for when publishing it
+    }))
+}
+
+/// Writes an entry to the given replica of a Register, signing the operation with the given
+/// keypair, without any network access. Returns the hash of the entry, and the cmd to send
+/// to the network, e.g. to publish it later on.
+pub fn sign_register_write(
+    keypair: &Keypair,
+    register: &mut Register,
+    entry: Entry,
+    children: BTreeSet<EntryHash>,
+) -> Result<(EntryHash, DataCmd)> {
+    // Let's check the policy/permissions to make sure this operation is allowed,
+    // otherwise it will fail when the operation is applied on the network replica.
+    let public_key = keypair.public_key();
+    register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
+
+    // We can now write the entry to the Register
+    let (hash, op) = register.write(entry, children)?;
+    let op = EditRegister {
+        address: *register.address(),
+        edit: op,
+        policy_version: register.policy_version(),
+    };
+
+    let signature = keypair.sign(&bincode::serialize(&op)?);
+
+    let edit = SignedRegisterEdit {
+        op,
+        auth: ClientAuth {
+            public_key,
+            signature,
+        },
+    };
+
+    // Finally we package the mutation for the network's replicas (it's now ready to be sent)
+    Ok((hash, DataCmd::Register(RegisterCmd::Edit(edit))))
+}
+
+/// Edits the policy of the given replica of a Register, signing the operation with the given
+/// keypair, without any network access. Returns the cmd to send to the network, e.g. to publish
+/// it later on.
+pub fn sign_register_policy_edit(
+    keypair: &Keypair,
+    register: &mut Register,
+    edit: PolicyEdit,
+) -> Result<DataCmd> {
+    // The edit is applied on top of the latest policy we know of, which also checks
+    // we are the owner, otherwise it will fail when the operation is applied on the network replica.
+    let public_key = keypair.public_key();
+    let op = PolicyOp {
+        version: register.policy_version() + 1,
+        edit,
+    };
+    register.apply_policy_op(op.clone(), User::Key(public_key))?;
+
+    let op = EditRegisterPolicy {
+        address: *register.address(),
+        op,
+    };
+    let signature = keypair.sign(&bincode::serialize(&op)?);
+
+    let edit = SignedRegisterPolicyEdit {
+        op,
+        auth: ClientAuth {
+            public_key,
+            signature,
+        },
+    };
+
+    Ok(DataCmd::Register(RegisterCmd::EditPolicy(edit)))
+}
+
 // temp dummy
 fn section_sig() -> sn_interface::messaging::SectionSig {
     use sn_interface::messaging::system::SectionSig;
@@ -261,8 +464,8 @@ mod tests {
         messaging::data::Error as ErrorMsg,
         types::{
             log_markers::LogMarker,
-            register::{Action, EntryHash, Permissions, Policy, User},
-            Keypair,
+            register::{Action, EntryHash, Permissions, Policy, PolicyEdit, User},
+            Error as SnError, Keypair,
         },
     };
 
@@ -632,6 +835,57 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_policy_edits() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("test__register_policy_edits").entered();
+
+        let client = create_test_client().await?;
+
+        let name = xor_name::rand::random();
+        let tag = 10;
+        let owner = User::Key(client.public_key());
+        let other_user = User::Key(Keypair::new_ed25519().public_key());
+
+        let (address, batch) = client.create_register(name, tag, policy(owner)).await?;
+        client.publish_register_ops(batch).await?;
+
+        let grant = PolicyEdit::Grant {
+            user: other_user,
+            permissions: Permissions::new(true),
+        };
+        let batch = client.edit_register_policy(address, grant).await?;
+        client.publish_register_ops(batch).await?;
+
+        let permissions = client
+            .get_register_permissions_for_user(address, other_user)
+            .await?;
+        assert_eq!(Some(true), permissions.is_allowed(Action::Write));
+
+        let transfer = PolicyEdit::TransferOwnership(other_user);
+        let batch = client.edit_register_policy(address, transfer).await?;
+        client.publish_register_ops(batch).await?;
+
+        let register = client.get_register(address).await?;
+        assert_eq!(register.owner(), other_user);
+        assert_eq!(register.policy_version(), 2);
+
+        // we are no longer the owner, so we cannot edit the policy anymore
+        match client
+            .edit_register_policy(address, PolicyEdit::Revoke(other_user))
+            .await
+        {
+            Err(Error::NetworkDataError(SnError::AccessDenied(user))) => {
+                assert_eq!(*user, owner);
+                Ok(())
+            }
+            other => Err(eyre!(
+                "Unexpected result when editing the policy of a Register not owned: {:?}",
+                other
+            )),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn ae_checks_register_test() -> Result<()> {
         init_logger();
@@ -23,6 +23,7 @@ use futures::future::join_all;
 use std::collections::BTreeMap;
 use tokio::time::sleep;
 use tracing::{debug, info_span};
+use xor_name::XorName;
 
 impl Client {
     /// Return the set of Elder reward keys and the individual fee they ask for processing a spend.
@@ -31,8 +32,29 @@ impl Client {
         &self,
         dbc_id: PublicKey,
     ) -> Result<BTreeMap<NodeId, RequiredFee>> {
-        let fee_query = DataQuery::Spentbook(SpendQuery::GetFees(dbc_id));
+        self.get_elder_fees(DataQuery::Spentbook(SpendQuery::GetFees(dbc_id)))
+            .await
+    }
 
+    /// Return the set of Elder reward keys and the individual fee they ask for storing
+    /// a data item with the given name, in the section the data belongs to.
+    /// The fee amounts are encrypted to the given dbc id, i.e. the dbc to pay with.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_store_cost(
+        &self,
+        dbc_id: PublicKey,
+        data_name: XorName,
+    ) -> Result<BTreeMap<NodeId, RequiredFee>> {
+        self.get_elder_fees(DataQuery::Spentbook(SpendQuery::GetStoreCost {
+            dbc_id,
+            data_name,
+        }))
+        .await
+    }
+
+    /// Send the fee query to all the Elders of its destination section,
+    /// returning the fee from each of those which responded.
+    async fn get_elder_fees(&self, fee_query: DataQuery) -> Result<BTreeMap<NodeId, RequiredFee>> {
         let (_, elders) = self
             .session
             .get_all_elders_of_dst(fee_query.dst_name())
@@ -61,8 +83,10 @@ impl Client {
                 res
             })
             .filter_map(|(elder, resp)| match resp {
-                QueryResponse::GetFees(Ok(fee)) => Some((elder, fee)),
-                QueryResponse::GetFees(Err(error)) => {
+                QueryResponse::GetFees(Ok(fee)) | QueryResponse::GetStoreCost(Ok(fee)) => {
+                    Some((elder, fee))
+                }
+                QueryResponse::GetFees(Err(error)) | QueryResponse::GetStoreCost(Err(error)) => {
                     warn!("Fee query unexpectedly failed: {error}");
                     None
                 }
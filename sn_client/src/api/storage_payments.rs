@@ -0,0 +1,90 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{transfers::pay_for_storage, Client};
+use crate::Result;
+
+use sn_dbc::Dbc;
+use sn_interface::types::fees::{PaymentProof, MAX_PAID_ITEMS};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use xor_name::XorName;
+
+/// The payments made for storing data items, each of them paying for at most
+/// `MAX_PAID_ITEMS` of the data items, for its proof to be sent along with their stores.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoragePayments(pub(crate) Vec<PaymentProof>);
+
+impl StoragePayments {
+    /// The proof of the payment made for storing the data item with the given name, if any.
+    pub fn proof_for(&self, name: &XorName) -> Option<&PaymentProof> {
+        self.0.iter().find(|payment| payment.covers(name))
+    }
+
+    /// Returns true if no payment was made.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds the given payments to these.
+    pub fn extend(&mut self, other: StoragePayments) {
+        self.0.extend(other.0)
+    }
+}
+
+impl Client {
+    /// Add dbcs to those this client pays for storing data with.
+    pub async fn add_payment_dbcs(&self, dbcs: impl IntoIterator<Item = Dbc>) {
+        self.payment_dbcs.write().await.extend(dbcs);
+    }
+
+    /// Return the dbcs this client currently holds for paying for storing data,
+    /// which includes the change of any payment made so far.
+    pub async fn payment_dbcs(&self) -> Vec<Dbc> {
+        self.payment_dbcs.read().await.clone()
+    }
+
+    /// Pay for storing the data items with the given names, using the dbcs this client holds for it.
+    /// The data items are paid for in as many payments as needed for each to pay for at most
+    /// `MAX_PAID_ITEMS` of them, the spent dbcs being replaced with the change of each payment, if any.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn pay_for_storage(&self, content: BTreeSet<XorName>) -> Result<StoragePayments> {
+        // We hold the lock for all the payments, so that concurrent
+        // payments don't attempt to spend the same dbcs.
+        let mut payment_dbcs = self.payment_dbcs.write().await;
+
+        // the names are sorted, so that the data items stored in the same section
+        // are mostly paid for together
+        let names: Vec<_> = content.iter().copied().collect();
+        let mut payments = vec![];
+        for batch in names.chunks(MAX_PAID_ITEMS) {
+            let (payment, change_dbc) =
+                pay_for_storage(self, payment_dbcs.clone(), batch.iter().copied().collect())
+                    .await?;
+
+            let spent: BTreeSet<_> = payment
+                .tx
+                .inputs
+                .iter()
+                .map(|input| input.public_key)
+                .collect();
+            payment_dbcs.retain(|dbc| !spent.contains(&dbc.public_key()));
+            payment_dbcs.extend(change_dbc);
+            payments.push(payment);
+        }
+
+        debug!(
+            "Paid for storing {} data items, in {} payments",
+            content.len(),
+            payments.len()
+        );
+
+        Ok(StoragePayments(payments))
+    }
+}
@@ -26,6 +26,9 @@ pub enum Error {
     /// DbcReissueError
     #[error("DbcReissueError: {0}")]
     DbcReissueError(String),
+    /// Could not obtain the cost of storing data from a section
+    #[error("StoreCostError: {0}")]
+    StoreCostError(String),
     /// Verification of DBC validly signed by a known section failed
     #[error("DBC validity verification failed: {0}")]
     DbcVerificationFailed(String),
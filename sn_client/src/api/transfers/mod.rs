@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod errors;
+mod storage_payment;
 
 use crate::Client;
 use crate::Result;
@@ -27,6 +28,7 @@ use std::collections::{BTreeMap, BTreeSet, HashSet};
 use xor_name::XorName;
 
 pub use errors::Error;
+pub use storage_payment::pay_for_storage;
 
 type ReissueCiphers = BTreeMap<PublicKey, BTreeMap<XorName, (RequiredFee, OwnerOnce)>>;
 type ReissueInputs = (Vec<Dbc>, Vec<(Token, OwnerOnce)>, Token, ReissueCiphers);
@@ -94,7 +96,6 @@ pub async fn select_inputs(
         })?;
 
     let mut change_amount = total_output_amount;
-    let mut rng = rng::thread_rng();
     #[cfg(not(feature = "data-network"))]
     let mut all_fee_cipher_params = BTreeMap::new();
 
@@ -170,7 +171,7 @@ pub async fn select_inputs(
                 .iter()
                 .for_each(|((elder, required_fee), fee)| {
                     let owner = Owner::from(required_fee.content.elder_reward_key);
-                    let owner_once = OwnerOnce::from_owner_base(owner, &mut rng);
+                    let owner_once = OwnerOnce::from_owner_base(owner, &mut rng::thread_rng());
                     recipients.push((*fee, owner_once.clone()));
 
                     #[cfg(not(feature = "data-network"))]
@@ -0,0 +1,125 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{fee_ciphers, reissue_dbcs, select_inputs, Error};
+use crate::{Client, Result};
+
+use sn_dbc::{rng, Dbc, Owner, OwnerOnce, Token};
+use sn_interface::{elder_count, network_knowledge::supermajority, types::fees::PaymentProof};
+
+use std::collections::{BTreeMap, BTreeSet};
+use xor_name::XorName;
+
+/// Pay for storing the data items with the given names, using the provided dbcs.
+///
+/// The Elders of each section where any of the data items will be stored are asked for
+/// their store cost, and are all paid in a single spend of the necessary number of the dbcs,
+/// which also pays the fees for the spend itself.
+/// The proof of the payment is returned, to be sent along with the store cmds of the paid
+/// data items, together with the change dbc (if any).
+pub async fn pay_for_storage(
+    client: &Client,
+    dbcs: Vec<Dbc>,
+    content: BTreeSet<XorName>,
+) -> Result<(PaymentProof, Option<Dbc>)> {
+    // The store cost is returned encrypted to the id of a dbc we hold,
+    // so we use the first bearer dbc we have for getting it.
+    let quote_input = dbcs
+        .iter()
+        .find_map(|dbc| dbc.as_revealed_input_bearer().ok())
+        .ok_or_else(|| Error::NotEnoughBalance(Token::zero().to_string()))?;
+    let quote_key = quote_input.public_key();
+
+    // Each section is paid once for all the data items it will store.
+    let mut names_per_section = BTreeMap::<bls::PublicKey, Vec<XorName>>::new();
+    for name in &content {
+        let (section_key, _) = client.session.get_all_elders_of_dst(*name).await?;
+        names_per_section
+            .entry(section_key)
+            .or_default()
+            .push(*name);
+    }
+
+    let mut recipients = vec![];
+    let mut store_fee_params = BTreeMap::new();
+
+    for (section_key, names) in names_per_section {
+        let elder_fees = client.get_store_cost(quote_key, names[0]).await?;
+        let items = names.len() as u64;
+
+        let mut paid_elders = 0;
+        for (elder, required_fee) in elder_fees {
+            let fee = match required_fee.content.decrypt_amount(&quote_input.secret_key) {
+                Ok(fee) => fee,
+                Err(error) => {
+                    error!("Decrypting the store cost from {elder} failed! {error}");
+                    continue;
+                }
+            };
+            let amount = fee
+                .as_nano()
+                .checked_mul(items)
+                .map(Token::from_nano)
+                .ok_or_else(|| {
+                    Error::DbcReissueError(
+                        "Overflow occurred while calculating the store cost of an Elder."
+                            .to_string(),
+                    )
+                })?;
+
+            let owner = Owner::from(required_fee.content.elder_reward_key);
+            let owner_once = OwnerOnce::from_owner_base(owner, &mut rng::thread_rng());
+            recipients.push((amount, owner_once.clone()));
+            let _ = store_fee_params.insert(elder.name(), (required_fee, owner_once));
+            paid_elders += 1;
+        }
+
+        let required_responses = supermajority(elder_count());
+        if required_responses > paid_elders {
+            return Err(Error::StoreCostError(format!(
+                "Not enough valid store costs received from section {section_key:?}. \
+                Found: {paid_elders}, needed: {required_responses}"
+            )))?;
+        }
+    }
+
+    let (input_dbcs, outputs, change_amount, all_fee_cipher_params) =
+        select_inputs(client, dbcs, recipients).await?;
+
+    let (output_dbcs, change_dbc) = reissue_dbcs(
+        client,
+        input_dbcs,
+        outputs,
+        change_amount,
+        PaymentProof::reason_for(&content),
+        #[cfg(not(feature = "data-network"))]
+        all_fee_cipher_params,
+    )
+    .await?;
+
+    let revealed_outputs = output_dbcs
+        .iter()
+        .map(|(dbc, _, revealed_amount)| (dbc.public_key(), *revealed_amount))
+        .collect();
+    let fee_ciphers = fee_ciphers(&revealed_outputs, &store_fee_params)?;
+
+    // All outputs come from the same tx, spent with the same inputs.
+    let (tx, spent_proofs) = output_dbcs
+        .first()
+        .map(|(dbc, _, _)| (dbc.transaction.clone(), dbc.inputs_spent_proofs.clone()))
+        .ok_or_else(|| Error::DbcReissueError("No outputs paying for storage!".to_string()))?;
+
+    let payment = PaymentProof {
+        content,
+        tx,
+        spent_proofs,
+        fee_ciphers,
+    };
+
+    Ok((payment, change_dbc))
+}
@@ -0,0 +1,273 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::StoragePayments;
+use crate::Result;
+
+use bincode::{deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use xor_name::XorName;
+
+/// The status of a chunk in an [`UploadJournal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ChunkStatus {
+    /// The chunk is yet to be stored.
+    Pending,
+    /// The cmd storing the chunk was acknowledged by the network.
+    Acked,
+    /// The chunk was read back from the network after it was stored.
+    Verified,
+}
+
+/// A record, persisted on disk, of the progress of uploading a file, so that an
+/// interrupted upload can be resumed by sending only the chunks which were not stored yet,
+/// and paying only for those which were not paid for yet.
+///
+/// Since self-encryption is deterministic, resuming an upload chunks the file again,
+/// obtaining the very same chunks which are then checked against the journal.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadJournal {
+    /// Canonical path of the file being uploaded.
+    pub source: PathBuf,
+    /// Size of the file, used to detect whether it changed since the upload started.
+    pub source_size: u64,
+    /// Last modification time of the file, used to detect whether it changed since the upload started.
+    pub source_modified: Option<SystemTime>,
+    /// Address the file is stored at, known once all of its content was chunked.
+    pub head_address: Option<XorName>,
+    /// All the chunks of the file chunked so far, and their status.
+    pub chunks: BTreeMap<XorName, ChunkStatus>,
+    /// The payments made for storing the chunks, recorded as soon as each is made, so that
+    /// the chunks paid for are not paid for again if the upload is interrupted and resumed.
+    pub payments: StoragePayments,
+    // Where the journal is persisted.
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl UploadJournal {
+    /// Loads the journal persisted at the given path, if it exists and was recording the upload of the
+    /// given source file as it currently is. Otherwise an empty journal is returned for it.
+    ///
+    /// The source file is recognised whichever path it's given with, e.g. relative to another directory.
+    pub fn open(path: &Path, source: &Path) -> Result<Self> {
+        let source = source.canonicalize()?;
+        let metadata = fs::metadata(&source)?;
+        let source_size = metadata.len();
+        let source_modified = metadata.modified().ok();
+
+        match fs::read(path) {
+            Ok(bytes) => match deserialize::<Self>(&bytes) {
+                Ok(journal)
+                    if journal.source == source
+                        && journal.source_size == source_size
+                        && journal.source_modified == source_modified =>
+                {
+                    debug!(
+                        "Resuming upload of {} from journal at {}",
+                        source.display(),
+                        path.display()
+                    );
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        ..journal
+                    });
+                }
+                Ok(_) => {
+                    debug!(
+                        "Journal at {} was not recording the current upload of {}",
+                        path.display(),
+                        source.display()
+                    );
+                }
+                Err(error) => {
+                    warn!(
+                        "Ignoring invalid upload journal at {}: {error}",
+                        path.display()
+                    );
+                }
+            },
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        Ok(Self {
+            source,
+            source_size,
+            source_modified,
+            head_address: None,
+            chunks: BTreeMap::new(),
+            payments: StoragePayments::default(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Path the journal is persisted at.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Persists the journal to disk.
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first, so that an interruption
+        // while writing it doesn't leave a corrupted journal behind.
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serialize(self)?)?;
+        fs::rename(tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Removes the journal from disk, once the upload it was recording is complete.
+    pub fn remove(self) -> Result<()> {
+        match fs::remove_file(self.path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the chunk is already stored, i.e. it was acked by the network,
+    /// or also read back from it if `verify` is set.
+    pub(crate) fn is_stored(&self, name: &XorName, verify: bool) -> bool {
+        let required = if verify {
+            ChunkStatus::Verified
+        } else {
+            ChunkStatus::Acked
+        };
+        self.chunks
+            .get(name)
+            .map(|status| *status >= required)
+            .unwrap_or(false)
+    }
+
+    /// Records the chunk as yet to be stored, unless it was already recorded.
+    pub(crate) fn add_pending(&mut self, name: XorName) {
+        let _ = self.chunks.entry(name).or_insert(ChunkStatus::Pending);
+    }
+
+    /// Records the new status of the chunk.
+    pub(crate) fn set_status(&mut self, name: XorName, status: ChunkStatus) {
+        let _ = self.chunks.insert(name, status);
+    }
+
+    /// Returns whether a payment for storing the chunk is recorded.
+    pub(crate) fn is_paid(&self, name: &XorName) -> bool {
+        self.payments.proof_for(name).is_some()
+    }
+
+    /// Records the payments made for storing chunks.
+    pub(crate) fn add_payments(&mut self, payments: StoragePayments) {
+        self.payments.extend(payments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkStatus, UploadJournal};
+    use crate::api::StoragePayments;
+
+    use eyre::Result;
+    use sn_dbc::DbcTransaction;
+    use sn_interface::types::fees::PaymentProof;
+    use std::{collections::BTreeSet, fs};
+    use tempfile::tempdir;
+    use xor_name::XorName;
+
+    #[test]
+    fn journal_is_resumed_only_for_unchanged_source() -> Result<()> {
+        let dir = tempdir()?;
+        let source = dir.path().join("source");
+        let journal_path = dir.path().join("journals").join("source.journal");
+        fs::write(&source, b"some content")?;
+
+        let name = XorName::random(&mut rand::thread_rng());
+        let mut journal = UploadJournal::open(&journal_path, &source)?;
+        assert!(journal.chunks.is_empty());
+        journal.add_pending(name);
+        assert!(!journal.is_stored(&name, false));
+        journal.set_status(name, ChunkStatus::Acked);
+        journal.save()?;
+
+        let journal = UploadJournal::open(&journal_path, &source)?;
+        assert_eq!(journal.chunks.get(&name), Some(&ChunkStatus::Acked));
+        assert!(journal.is_stored(&name, false));
+        assert!(!journal.is_stored(&name, true));
+
+        // the journal doesn't apply to the source once it has changed
+        fs::write(&source, b"some other content")?;
+        let journal = UploadJournal::open(&journal_path, &source)?;
+        assert!(journal.chunks.is_empty());
+
+        journal.remove()?;
+        assert!(!journal_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_persists_the_payments_made() -> Result<()> {
+        let dir = tempdir()?;
+        let source = dir.path().join("source");
+        let journal_path = dir.path().join("source.journal");
+        fs::write(&source, b"some content")?;
+
+        let paid = XorName::random(&mut rand::thread_rng());
+        let unpaid = XorName::random(&mut rand::thread_rng());
+        let payment = PaymentProof {
+            content: BTreeSet::from([paid]),
+            tx: DbcTransaction {
+                inputs: vec![],
+                outputs: vec![],
+            },
+            spent_proofs: BTreeSet::new(),
+            fee_ciphers: Default::default(),
+        };
+
+        let mut journal = UploadJournal::open(&journal_path, &source)?;
+        assert!(!journal.is_paid(&paid));
+        journal.add_payments(StoragePayments(vec![payment.clone()]));
+        journal.save()?;
+
+        let journal = UploadJournal::open(&journal_path, &source)?;
+        assert!(journal.is_paid(&paid));
+        assert!(!journal.is_paid(&unpaid));
+        assert_eq!(journal.payments.proof_for(&paid), Some(&payment));
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_is_resumed_whichever_path_the_source_is_given_with() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("subdir"))?;
+        let source = dir.path().join("source");
+        let journal_path = dir.path().join("source.journal");
+        fs::write(&source, b"some content")?;
+
+        let name = XorName::random(&mut rand::thread_rng());
+        let mut journal = UploadJournal::open(&journal_path, &source)?;
+        journal.set_status(name, ChunkStatus::Acked);
+        journal.save()?;
+
+        let other_path = dir.path().join("subdir").join("..").join("source");
+        let journal = UploadJournal::open(&journal_path, &other_path)?;
+        assert_eq!(journal.source, source.canonicalize()?);
+        assert_eq!(journal.chunks.get(&name), Some(&ChunkStatus::Acked));
+
+        Ok(())
+    }
+}
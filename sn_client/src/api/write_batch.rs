@@ -0,0 +1,122 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Client, RegisterWriteAheadLog};
+use crate::Result;
+
+use serde::{Deserialize, Serialize};
+use sn_interface::types::{Chunk, Error as DtError};
+use std::{fs, path::Path};
+
+/// A batch of chunks to store and Register operations to apply, which were produced without
+/// being sent to the network, e.g. in a dry run, so that they can be published later on,
+/// possibly by another client, with [`Client::publish_batch`].
+///
+/// The Register operations are already signed by their author,
+/// thus the client publishing the batch only pays for storing its data.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteBatch {
+    /// Chunks to store.
+    pub chunks: Vec<Chunk>,
+    /// Register operations to apply, in order.
+    pub register_ops: RegisterWriteAheadLog,
+}
+
+impl WriteBatch {
+    /// Returns true if there is nothing to publish in the batch.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty() && self.register_ops.is_empty()
+    }
+
+    /// Loads the batch exported to the file at the given path.
+    pub fn load(path: &Path) -> Result<Self> {
+        // Batches are serialised as msgs are, rather than with bincode, as the Register
+        // operations skip serialising the policy version they were made as per when it's
+        // the initial one.
+        let batch = rmp_serde::from_slice(&fs::read(path)?)
+            .map_err(|err| DtError::Serialisation(err.to_string()))?;
+        Ok(batch)
+    }
+
+    /// Exports the batch to a file at the given path.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        // Write to a temporary file first, so that an interruption
+        // while writing it doesn't leave a corrupted batch behind.
+        let tmp_path = path.with_extension("tmp");
+        let bytes =
+            rmp_serde::to_vec(self).map_err(|err| DtError::Serialisation(err.to_string()))?;
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Publish a batch of chunks and Register operations to the network.
+    ///
+    /// The chunks are stored first, paying for all of them before storing any,
+    /// so that the Registers are never left pointing at data which is not stored.
+    /// As both storing chunks and applying Register operations is idempotent,
+    /// a batch can be published again if publishing it failed midway.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn publish_batch(&self, batch: WriteBatch) -> Result<()> {
+        debug!(
+            "Publishing batch of {} chunks and {} Register ops",
+            batch.chunks.len(),
+            batch.register_ops.len()
+        );
+        self.store_chunks(batch.chunks, true, None).await?;
+        self.publish_register_ops(batch.register_ops).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriteBatch;
+    use crate::api::{sign_register_create, sign_register_write};
+
+    use bytes::Bytes;
+    use eyre::Result;
+    use sn_interface::types::{
+        register::{Policy, Register, User},
+        Chunk, Keypair,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+    use tempfile::tempdir;
+
+    #[test]
+    fn batch_is_exported_and_loaded() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("batch");
+
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: BTreeMap::new(),
+        };
+        let name = xor_name::rand::random();
+        let mut register = Register::new(owner, name, 15_000, policy.clone());
+        let (_, edit) = sign_register_write(
+            &keypair,
+            &mut register,
+            b"some entry".to_vec(),
+            BTreeSet::new(),
+        )?;
+        let batch = WriteBatch {
+            chunks: vec![Chunk::new(Bytes::from_static(b"some data"))],
+            register_ops: vec![sign_register_create(&keypair, name, 15_000, policy)?, edit],
+        };
+        assert!(!batch.is_empty());
+
+        batch.save(&path)?;
+        assert_eq!(WriteBatch::load(&path)?, batch);
+
+        Ok(())
+    }
+}
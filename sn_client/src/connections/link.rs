@@ -8,7 +8,7 @@
 
 use qp2p::{Connection, Endpoint, RecvStream, StreamError, UsrMsgBytes};
 use sn_interface::{
-    messaging::MsgId,
+    messaging::{MsgId, ProtocolVersions, WireMsg},
     types::{log_markers::LogMarker, NodeId},
 };
 use std::{collections::BTreeMap, sync::Arc};
@@ -29,6 +29,8 @@ pub(crate) struct Link {
     node_id: NodeId,
     endpoint: Endpoint,
     connections: Arc<RwLock<BTreeMap<ConnId, Arc<Connection>>>>,
+    // The versions of the messaging protocol the node advertised in its responses.
+    node_versions: Arc<RwLock<Option<ProtocolVersions>>>,
 }
 
 impl Link {
@@ -37,9 +39,16 @@ impl Link {
             node_id,
             endpoint,
             connections: Arc::new(RwLock::new(BTreeMap::new())),
+            node_versions: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Records the versions of the messaging protocol the node advertised,
+    /// for the msgs sent to it from then on to be serialised with the latest one we support.
+    pub(crate) async fn record_versions(&self, versions: ProtocolVersions) {
+        *self.node_versions.write().await = Some(versions);
+    }
+
     pub(crate) async fn send_bi(
         &self,
         bytes: UsrMsgBytes,
@@ -47,6 +56,7 @@ impl Link {
     ) -> Result<RecvStream, LinkError> {
         let node_id = self.node_id;
         debug!("sending bidi msg out... {msg_id:?} to {node_id:?}");
+        let bytes = self.encode(bytes).await?;
         let conn = self.get_or_connect(msg_id).await?;
         debug!(
             "connection got {msg_id:?} to {node_id:?}, conn_id={}",
@@ -76,6 +86,20 @@ impl Link {
         Ok(recv_stream)
     }
 
+    // Serialises the msg with the latest version of the messaging protocol supported by both
+    // us and the node, or with the oldest we support until we've heard from the node.
+    async fn encode(&self, bytes: UsrMsgBytes) -> Result<UsrMsgBytes, LinkError> {
+        let ours = ProtocolVersions::SUPPORTED;
+        let version = self
+            .node_versions
+            .read()
+            .await
+            .and_then(|theirs| ours.negotiate(&theirs))
+            .unwrap_or(ours.min);
+
+        WireMsg::encode_for_version(bytes, version, ours).map_err(LinkError::Serialisation)
+    }
+
     // Get a connection or create a fresh one
     async fn get_or_connect(&self, msg_id: MsgId) -> Result<Arc<Connection>, LinkError> {
         debug!("Attempting to get conn read lock... {msg_id:?}");
@@ -154,4 +178,6 @@ pub enum LinkError {
     Connection(qp2p::ConnectionError),
     /// Failed to send a msg to a node
     Send(qp2p::SendError),
+    /// Failed to serialise a msg with the version of the messaging protocol the node supports
+    Serialisation(sn_interface::messaging::Error),
 }
@@ -11,7 +11,10 @@ mod link;
 pub(crate) use link::Link;
 pub use link::LinkError;
 
-use sn_interface::{messaging::MsgId, types::NodeId};
+use sn_interface::{
+    messaging::{MsgId, ProtocolVersions},
+    types::NodeId,
+};
 
 use qp2p::Endpoint;
 use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
@@ -80,6 +83,13 @@ impl NodeLinks {
         links.get(node_id).cloned()
     }
 
+    /// Records the versions of the messaging protocol the node advertised, if we've a link to it.
+    pub(super) async fn record_versions(&self, node_id: &NodeId, versions: ProtocolVersions) {
+        if let Some(link) = self.get(node_id).await {
+            link.record_versions(versions).await;
+        }
+    }
+
     /// Removes a link from NodeLinks.
     /// It does NOT disconnect it, as it could still be used to receveive messages on
     pub(super) async fn remove(&self, node_id: &NodeId) {
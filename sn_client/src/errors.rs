@@ -149,6 +149,9 @@ pub enum Error {
         /// Address name of the chunk
         address: XorName,
     },
+    /// No payment was made for storing the data item.
+    #[error("No payment was made for storing the data item {0}")]
+    MissingStorePayment(XorName),
     /// Transfer errors.
     #[error(transparent)]
     TransferError(#[from] crate::api::TransferError),
@@ -285,6 +288,18 @@ pub enum Error {
         /// Number of Chunks generated
         chunked: usize,
     },
+    /// Occurs if a segment of streamed data doesn't map to self-encrypted content.
+    #[error("The data at {0:?} is not a valid segment of streamed data.")]
+    InvalidSegment(XorName),
+    /// Occurs if the streamed data read to store it differs from the one read to pay for it.
+    #[error("The streamed data changed while being uploaded, from its segment {segment} on.")]
+    StreamedDataChanged {
+        /// Index of the first segment which changed
+        segment: usize,
+    },
+    /// Occurs if a blocking task, e.g. reading data to upload, panicked or was cancelled.
+    #[error("Blocking task failed: {0}")]
+    BlockingTaskFailed(#[from] tokio::task::JoinError),
     /// Occurs if a signed SAP cannot be obtained for a section key.
     #[error("A signed section authority provider was not found for section key {0:?}")]
     SignedSapNotFound(PublicKey),
@@ -305,6 +320,23 @@ pub enum Error {
     DataReplicasCheck(#[from] DataReplicasCheckError),
 }
 
+impl Error {
+    /// If the Elders rejected the msg because the client exceeded its rate limits,
+    /// the time they asked the client to wait before sending more msgs.
+    pub fn rate_limited_retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::CmdError {
+                source: ErrorMsg::ClientRateLimited { retry_after },
+                ..
+            }
+            | Self::ErrorMsg {
+                source: ErrorMsg::ClientRateLimited { retry_after },
+            } => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(feature = "check-replicas")]
 #[derive(Error, Debug)]
 #[non_exhaustive]
@@ -61,7 +61,9 @@ mod errors;
 
 // Export public API.
 pub use api::{
-    Client, QueriedDataReplicas, RegisterWriteAheadLog, DEFAULT_NETWORK_CONTACTS_FILE_NAME,
+    sign_register_create, sign_register_policy_edit, sign_register_write, ChunkStatus, Client,
+    FileProgress, QueriedDataReplicas, RegisterWriteAheadLog, StoragePayments, UploadJournal,
+    WriteBatch, DEFAULT_NETWORK_CONTACTS_FILE_NAME,
 };
 pub use connections::LinkError;
 pub use errors::{Error, Result};
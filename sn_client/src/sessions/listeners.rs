@@ -57,8 +57,12 @@ impl Session {
             }
 
             let bytes = recv_stream.read().await?;
+            let wire_msg = WireMsg::from(bytes)?;
+            self.node_links
+                .record_versions(&node_id, wire_msg.supported_versions())
+                .await;
 
-            match WireMsg::deserialize(bytes)? {
+            match (wire_msg.msg_id(), wire_msg.into_msg()?) {
                 (response_id, NetworkMsg::DataResponse(msg)) => return Ok((response_id, msg)),
                 (
                     _,
@@ -161,6 +165,17 @@ impl Session {
                     );
                     MsgResponse::CmdResponse(addr, Box::new(response))
                 }
+                DataResponse::RegisterEdited {
+                    edits,
+                    correlation_id,
+                } => {
+                    trace!(
+                        "RegisterEdited with id {msg_id:?} regarding correlation_id \
+                        {correlation_id:?} from {node_id:?} with {} edits",
+                        edits.len()
+                    );
+                    MsgResponse::RegisterEdited(addr, edits)
+                }
                 DataResponse::NetworkIssue(error) => MsgResponse::Failure(
                     addr,
                     Error::CmdError {
@@ -200,7 +215,7 @@ impl Session {
         self.update_network_knowledge(section_tree_update, src_node)
             .await;
 
-        let (msg_id, elders, query_index, payload, dst, auth) = self
+        let (msg_id, elders, query_index, is_register, payload, dst, auth) = self
             .new_target_elders(src_node, bounced_msg, correlation_id)
             .await?;
 
@@ -225,6 +240,7 @@ impl Session {
                     auth: auth.into_inner(),
                     is_spend: false,
                     query_index,
+                    is_register,
                 },
                 dst,
             );
@@ -297,18 +313,22 @@ impl Session {
             MsgId,
             Vec<NodeId>,
             Option<usize>,
+            bool,
             Bytes,
             Dst,
             AuthorityProof<ClientAuth>,
         ),
         Error,
     > {
-        let wire_msg = WireMsg::from(bounced_msg)?;
+        let mut wire_msg = WireMsg::from(bounced_msg)?;
+        // the payload is resent signed as it is, thus decompressed
+        wire_msg.decompress_payload()?;
         let msg_id = wire_msg.msg_id();
         let msg_kind = wire_msg.kind();
         let bounced_msg_dst = wire_msg.dst;
         let msg_type = wire_msg.into_msg()?;
         let query_index = *msg_kind.query_index();
+        let is_register = msg_kind.is_register();
         let (client_msg, auth) = match msg_type {
             NetworkMsg::Client { msg, auth } => (msg, auth),
             msg => {
@@ -352,6 +372,7 @@ impl Session {
                 msg_id,
                 target_elders,
                 query_index,
+                is_register,
                 wire_msg.payload,
                 dst,
                 auth,
@@ -11,7 +11,7 @@ use crate::{Error, Result};
 
 use sn_interface::{
     messaging::{
-        data::{DataQuery, QueryResponse, SpendQuery},
+        data::{DataQuery, QueryResponse, SignedRegisterEdit, SpendQuery},
         ClientAuth, Dst, MsgId, MsgKind, WireMsg,
     },
     network_knowledge::supermajority,
@@ -20,8 +20,8 @@ use sn_interface::{
 
 use bytes::Bytes;
 use rand::{rngs::OsRng, seq::SliceRandom};
-use std::collections::BTreeSet;
-use tokio::task::JoinSet;
+use std::{collections::BTreeSet, time::Duration};
+use tokio::{task::JoinSet, time::sleep};
 use tracing::{debug, error, trace, warn};
 use xor_name::XorName;
 
@@ -31,6 +31,10 @@ pub(crate) const NUM_OF_ELDERS_SUBSET_FOR_QUERIES: usize = 3;
 #[cfg(feature = "query-happy-path")]
 pub(crate) const NUM_OF_ELDERS_SUBSET_FOR_QUERIES: usize = 1;
 
+// Number of times a msg is sent again after the Elders rejected it
+// because we exceeded our rate limits
+const MAX_THROTTLED_RETRIES: u32 = 3;
+
 impl Session {
     #[instrument(skip(self), level = "debug", name = "session setup conns")]
     /// Make a best effort to pre connect to only relevant nodes for a set of dst addresses
@@ -68,6 +72,7 @@ impl Session {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[instrument(skip(self, auth, payload), level = "debug", name = "session send cmd")]
     pub(crate) async fn send_cmd(
         &self,
@@ -75,6 +80,41 @@ impl Session {
         auth: ClientAuth,
         payload: Bytes,
         is_spend: bool,
+        is_register: bool,
+        compressible: bool,
+        msg_id: MsgId,
+    ) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .send_cmd_to_elders(
+                    dst_address,
+                    auth.clone(),
+                    payload.clone(),
+                    is_spend,
+                    is_register,
+                    compressible,
+                    msg_id,
+                )
+                .await;
+            let Some(delay) = throttled_retry_delay(&result, attempt) else {
+                return result;
+            };
+            warn!("Cmd {msg_id:?} was throttled by the Elders, sending it again in {delay:?}");
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn send_cmd_to_elders(
+        &self,
+        dst_address: XorName,
+        auth: ClientAuth,
+        payload: Bytes,
+        is_spend: bool,
+        is_register: bool,
+        compressible: bool,
         msg_id: MsgId,
     ) -> Result<()> {
         let endpoint = self.endpoint.clone();
@@ -97,8 +137,10 @@ impl Session {
             auth,
             is_spend,
             query_index: None,
+            is_register,
         };
-        let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+        wire_msg.set_compressible(compressible);
 
         let log_line = |elders_len_s: String| {
             debug!(
@@ -186,13 +228,17 @@ impl Session {
 
             // We only require one ack, we wait it to get received.
             // Any AE message is handled by the tasks, hence no extra wait is required.
-            if self
+            match self
                 .we_have_sufficient_acks_for_cmd(msg_id, elders, expected_acks, send_cmd_tasks)
                 .await
-                .is_ok()
             {
-                trace!("Acks of Cmd {:?} received", msg_id);
-                return Ok(());
+                Ok(()) => {
+                    trace!("Acks of Cmd {:?} received", msg_id);
+                    return Ok(());
+                }
+                // we back off rather than sending it to more Elders straight away
+                Err(error) if error.rate_limited_retry_after().is_some() => return Err(error),
+                Err(_) => {}
             }
         }
 
@@ -234,6 +280,7 @@ impl Session {
         let mut received_acks = BTreeSet::default();
         let mut received_errors = BTreeSet::default();
         let mut failures = BTreeSet::default();
+        let mut throttled = None;
 
         while let Some(msg_resp) = send_cmd_tasks.join_next().await {
             debug!("Handling msg_resp sent to ack wait channel: {msg_resp:?}");
@@ -244,9 +291,17 @@ impl Session {
                     let _ = received_errors.insert(src);
                     continue;
                 }
+                Ok(MsgResponse::RegisterEdited(src, _)) => {
+                    debug!("Unexpected register edits received from {src:?} for {msg_id:?} when awaiting a CmdAck");
+                    let _ = received_errors.insert(src);
+                    continue;
+                }
                 Ok(MsgResponse::Failure(src, error)) => {
                     debug!("Failure occurred with msg {msg_id:?} from {src:?}: {error:?}");
                     let _ = failures.insert(src);
+                    if error.rate_limited_retry_after().is_some() {
+                        throttled = Some(error);
+                    }
                     continue;
                 }
                 Err(join_err) => {
@@ -306,6 +361,12 @@ impl Session {
             Missing Responses from: {missing_responses:?}",
             received_acks.len()
         );
+
+        // the caller shall back off if any Elder rejected the cmd because of our rate limits
+        if let Some(error) = throttled {
+            return Err(error);
+        }
+
         Err(Error::InsufficientAcksReceived {
             msg_id,
             expected: expected_acks,
@@ -345,8 +406,12 @@ impl Session {
             payload,
             MsgKind::Client {
                 auth,
-                is_spend: matches!(query, DataQuery::Spentbook(SpendQuery::GetFees(_))),
+                is_spend: matches!(
+                    query,
+                    DataQuery::Spentbook(SpendQuery::GetFees(_) | SpendQuery::GetStoreCost { .. })
+                ),
                 query_index: None,
+                is_register: matches!(query, DataQuery::Register(_)),
             },
             Dst {
                 name: query.dst_name(),
@@ -382,6 +447,34 @@ impl Session {
         auth: ClientAuth,
         payload: Bytes,
         dst_section_info: Option<(bls::PublicKey, Vec<NodeId>)>,
+    ) -> Result<QueryResponse> {
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .send_query_to_elders(
+                    query.clone(),
+                    query_node_index,
+                    auth.clone(),
+                    payload.clone(),
+                    dst_section_info.clone(),
+                )
+                .await;
+            let Some(delay) = throttled_retry_delay(&result, attempt) else {
+                return result;
+            };
+            warn!("Query {query:?} was throttled by the Elders, sending it again in {delay:?}");
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send_query_to_elders(
+        &self,
+        query: DataQuery,
+        query_node_index: usize,
+        auth: ClientAuth,
+        payload: Bytes,
+        dst_section_info: Option<(bls::PublicKey, Vec<NodeId>)>,
     ) -> Result<QueryResponse> {
         let endpoint = self.endpoint.clone();
 
@@ -414,8 +507,12 @@ impl Session {
         };
         let kind = MsgKind::Client {
             auth,
-            is_spend: matches!(query, DataQuery::Spentbook(SpendQuery::GetFees(_))),
+            is_spend: matches!(
+                query,
+                DataQuery::Spentbook(SpendQuery::GetFees(_) | SpendQuery::GetStoreCost { .. })
+            ),
             query_index: Some(query_node_index),
+            is_register: matches!(query, DataQuery::Register(_)),
         };
         let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
 
@@ -435,6 +532,78 @@ impl Session {
             .await
     }
 
+    /// Polls one of the Elders closest to the register for the edits of it we subscribed to,
+    /// awaiting them for up to `timeout`. No edits are returned if the Elder ended our
+    /// subscription or none were made within the timeout, for the caller to poll again.
+    #[instrument(skip(self, auth, payload), level = "debug")]
+    pub(crate) async fn poll_register_edits(
+        &self,
+        query: DataQuery,
+        elder_index: usize,
+        auth: ClientAuth,
+        payload: Bytes,
+        timeout: Duration,
+    ) -> Result<Vec<SignedRegisterEdit>> {
+        let dst = query.dst_name();
+        let (section_pk, mut elders) = self.get_all_elders_of_dst(dst).await?;
+        elders.sort_by(|lhs, rhs| dst.cmp_distance(&lhs.name(), &rhs.name()));
+        let elders_len = elders.len();
+        let Some(elder) = elders.into_iter().nth(elder_index) else {
+            return Err(Error::InsufficientElderConnections {
+                connections: elders_len,
+                required: elder_index + 1,
+            });
+        };
+
+        let msg_id = MsgId::new();
+        debug!("Polling {elder:?} for register edits with {msg_id:?}, {query:?}");
+
+        let kind = MsgKind::Client {
+            auth,
+            is_spend: false,
+            query_index: Some(elder_index),
+            is_register: true,
+        };
+        let dst = Dst {
+            name: dst,
+            section_key: section_pk,
+        };
+        let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+
+        // dropping the tasks once timed out stops awaiting the response
+        let mut tasks = self.send_msg(vec![elder], wire_msg).await?;
+        let response = match tokio::time::timeout(timeout, tasks.join_next()).await {
+            Ok(Some(Ok(response))) => response,
+            Ok(_) => {
+                return Err(Error::NoResponse {
+                    msg_id,
+                    nodes: vec![elder],
+                })
+            }
+            Err(_) => {
+                trace!("No register edits received from {elder:?} on {msg_id:?} in {timeout:?}");
+                return Ok(vec![]);
+            }
+        };
+
+        match response {
+            MsgResponse::RegisterEdited(_, edits) => Ok(edits),
+            MsgResponse::QueryResponse(_, response) => match *response {
+                QueryResponse::SubscribeToRegister(Ok(())) => Ok(vec![]),
+                QueryResponse::SubscribeToRegister(Err(source)) => Err(Error::ErrorMsg { source }),
+                response => Err(Error::UnexpectedQueryResponse { query, response }),
+            },
+            MsgResponse::Failure(_, error) => Err(error),
+            MsgResponse::CmdResponse(src, _) => {
+                debug!("Unexpected CmdAck received from {src:?} for {msg_id:?}");
+                Err(Error::NoResponse {
+                    msg_id,
+                    nodes: vec![elder],
+                })
+            }
+        }
+    }
+
     async fn check_query_responses(
         &self,
         msg_id: MsgId,
@@ -456,6 +625,11 @@ impl Session {
                     discarded_responses += 1;
                     continue;
                 }
+                Ok(MsgResponse::RegisterEdited(src, _)) => {
+                    debug!("Unexpected register edits received from {src:?} for {msg_id:?} when awaiting a QueryResponse");
+                    discarded_responses += 1;
+                    continue;
+                }
                 Ok(MsgResponse::Failure(src, error)) => {
                     debug!("Failure occurred with msg {msg_id:?} from {src:?}: {error:?}");
                     last_error_response = Some(error);
@@ -705,6 +879,13 @@ impl Session {
     }
 }
 
+// Time to wait before sending a msg again, after the Elders rejected it because we exceeded
+// our rate limits, doubling with each attempt. `None` if it's not to be sent again.
+fn throttled_retry_delay<T>(result: &Result<T>, attempt: u32) -> Option<Duration> {
+    let retry_after = result.as_ref().err()?.rate_limited_retry_after()?;
+    (attempt < MAX_THROTTLED_RETRIES).then(|| retry_after.saturating_mul(1 << attempt))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -751,4 +932,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn throttled_msgs_are_sent_again_after_increasing_delays() {
+        let retry_after = Duration::from_millis(100);
+        let throttled: crate::Result<()> = Err(Error::CmdError {
+            source: sn_interface::messaging::data::Error::ClientRateLimited { retry_after },
+            msg_id: MsgId::new(),
+        });
+
+        assert_eq!(throttled_retry_delay(&throttled, 0), Some(retry_after));
+        assert_eq!(throttled_retry_delay(&throttled, 2), Some(retry_after * 4));
+        assert_eq!(
+            throttled_retry_delay(&throttled, MAX_THROTTLED_RETRIES),
+            None
+        );
+
+        let failed: crate::Result<()> = Err(Error::NoResponse {
+            msg_id: MsgId::new(),
+            nodes: vec![],
+        });
+        assert_eq!(throttled_retry_delay(&failed, 0), None);
+        assert_eq!(throttled_retry_delay(&Ok(()), 0), None);
+    }
 }
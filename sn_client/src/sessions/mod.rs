@@ -12,7 +12,7 @@ mod messaging;
 use crate::{connections::NodeLinks, Error, Result};
 
 use sn_interface::{
-    messaging::data::{CmdResponse, QueryResponse},
+    messaging::data::{CmdResponse, QueryResponse, SignedRegisterEdit},
     network_knowledge::SectionTree,
 };
 
@@ -26,6 +26,7 @@ use tokio::sync::RwLock;
 pub(super) enum MsgResponse {
     CmdResponse(SocketAddr, Box<CmdResponse>),
     QueryResponse(SocketAddr, Box<QueryResponse>),
+    RegisterEdited(SocketAddr, Vec<SignedRegisterEdit>),
     Failure(SocketAddr, Error),
 }
 
@@ -32,7 +32,8 @@ pub async fn try_create_test_client() -> Client {
 }
 
 /// Create a test client optionally providing keypair and/or `bootstrap_config`
-/// If no keypair is provided, a check is run that a balance has been generated for the client
+/// If no keypair is provided, a check is run that a balance has been generated for the client.
+/// The genesis DBC of the testnet, if found, is given to the client to pay for storing data.
 pub async fn create_test_client_with(
     optional_keypair: Option<Keypair>,
     dbc_owner: Option<Owner>,
@@ -44,6 +45,7 @@ pub async fn create_test_client_with(
         .dbc_owner(dbc_owner)
         .query_timeout(timeout)
         .cmd_timeout(timeout)
+        .payment_dbcs(read_genesis_dbc_from_first_node().ok())
         .from_env() // any env var set will override the values we've set before
         .build()
         .await?;
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::TransportError;
+
 use sn_interface::messaging::MsgId;
 use thiserror::Error;
 
@@ -27,6 +29,8 @@ pub enum Error {
     InvalidMsgReceived(MsgId),
     #[error("Failed to send msg {0:?}")]
     FailedSend(MsgId),
+    #[error("Transport error: {0}")]
+    Transport(#[from] TransportError),
 }
 
 impl From<qp2p::SendError> for Error {
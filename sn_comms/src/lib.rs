@@ -47,27 +47,39 @@ extern crate tracing;
 mod error;
 mod listener;
 mod node_link;
+mod peer_versions;
+mod transport;
 
 pub use self::error::{Error, Result};
+pub use self::transport::{
+    Connection, IncomingMsg, QuicTransport, ResponseStream, SendStream, Transport, TransportError,
+    TransportResult,
+};
+#[cfg(any(test, feature = "test"))]
+pub use self::transport::{MemoryNetwork, MemoryTransport};
 
-use self::node_link::NodeLink;
+use self::{node_link::NodeLink, peer_versions::PeerVersions};
 
 use sn_interface::{
     messaging::{
         data::{DataResponse, Error as MsgError},
-        Dst, MsgId, MsgKind, WireMsg,
+        Dst, MsgId, MsgKind, ProtocolVersions, WireMsg,
     },
     types::{NodeId, Participant},
 };
 
 use futures::future::join_all;
-use qp2p::{Endpoint, SendStream, UsrMsgBytes};
+use qp2p::UsrMsgBytes;
 use std::{
     collections::{BTreeMap, BTreeSet},
     net::SocketAddr,
+    sync::Arc,
 };
 use tokio::{
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        oneshot,
+    },
     task,
 };
 
@@ -106,30 +118,55 @@ pub struct MsgReceived {
 /// in the section (otherwise ignoring failed send to out of section nodes or clients).
 #[derive(Clone, Debug)]
 pub struct Comm {
-    our_endpoint: Endpoint,
+    transport: Arc<dyn Transport>,
     public_addr: Option<SocketAddr>,
     cmd_sender: Sender<CommCmd>,
 }
 
 impl Comm {
-    /// Creates a new instance of Comm with an endpoint
+    /// Creates a new instance of Comm with a QUIC endpoint
     /// and starts listening to the incoming messages from other nodes.
     #[tracing::instrument(skip_all)]
     pub fn new(
         local_addr: SocketAddr,
-        mut public_addr: Option<SocketAddr>,
+        public_addr: Option<SocketAddr>,
     ) -> Result<(Self, Receiver<CommEvent>)> {
-        let (our_endpoint, incoming_conns) = Endpoint::builder()
-            .addr(local_addr)
-            .idle_timeout(70_000)
-            .server()?;
+        let (transport, incoming_msgs) = QuicTransport::new(local_addr)?;
+        Ok(Self::with_transport(
+            transport,
+            incoming_msgs,
+            public_addr,
+            ProtocolVersions::SUPPORTED,
+        ))
+    }
+
+    /// Creates a new instance of Comm bound to a network simulated within the process.
+    #[cfg(any(test, feature = "test"))]
+    pub fn new_in_memory(network: &MemoryNetwork) -> (Self, Receiver<CommEvent>) {
+        let (transport, incoming_msgs) = network.transport();
+        Self::with_transport(transport, incoming_msgs, None, ProtocolVersions::SUPPORTED)
+    }
 
+    /// Creates a new instance of Comm over the given transport,
+    /// and starts handling the msgs received on it.
+    ///
+    /// Msgs are exchanged with each node and client with the latest version of the messaging
+    /// protocol supported by both, the msgs serialised with the versions we don't support
+    /// being dropped.
+    #[tracing::instrument(skip_all)]
+    pub fn with_transport(
+        transport: impl Transport,
+        incoming_msgs: Receiver<IncomingMsg>,
+        mut public_addr: Option<SocketAddr>,
+        versions: ProtocolVersions,
+    ) -> (Self, Receiver<CommEvent>) {
         // If public port is `0`, we assume it is equal to our local endpoint port.
         if let Some(ref mut addr) = public_addr {
             if addr.port() == 0 {
-                addr.set_port(our_endpoint.local_addr().port());
+                addr.set_port(transport.local_addr().port());
             }
         }
+        let transport: Arc<dyn Transport> = Arc::new(transport);
 
         trace!("Creating comms..");
         // comm_events_receiver will be used by upper layer to receive all msgs coming in from the network
@@ -138,33 +175,43 @@ impl Comm {
         // (we may want some buffer here?)
         let (comm_events_sender, comm_events_receiver) = mpsc::channel(1);
         let (cmd_sender, cmd_receiver) = mpsc::channel(STANDARD_CHANNEL_SIZE);
-
-        // listen for msgs/connections to our endpoint
-        listener::listen_for_connections(comm_events_sender.clone(), incoming_conns);
-
-        process_cmds(our_endpoint.clone(), cmd_receiver, comm_events_sender);
-
-        Ok((
+        let peer_versions = PeerVersions::new(versions);
+
+        // listen for msgs received on our transport
+        listener::listen_for_msgs(
+            comm_events_sender.clone(),
+            incoming_msgs,
+            peer_versions.clone(),
+        );
+
+        process_cmds(
+            transport.clone(),
+            peer_versions,
+            cmd_receiver,
+            comm_events_sender,
+        );
+
+        (
             Self {
-                our_endpoint,
+                transport,
                 public_addr,
                 cmd_sender,
             },
             comm_events_receiver,
-        ))
+        )
     }
 
     /// The socket address of our endpoint.
     pub fn socket_addr(&self) -> SocketAddr {
         match self.public_addr {
             Some(addr) => addr,
-            None => self.our_endpoint.local_addr(),
+            None => self.transport.local_addr(),
         }
     }
 
     /// Closes the endpoint.
     pub fn close_endpoint(&self) {
-        self.our_endpoint.close()
+        self.transport.close()
     }
 
     /// Sets the available targets to be only those in the passed in set.
@@ -196,6 +243,8 @@ impl Comm {
     }
 
     /// Sends the payload on new bidi-stream to noe and sends the response on the dst stream.
+    /// The returned receiver gets the response the nodes agreed on, once it was sent on the dst
+    /// stream. It gets nothing if any of them failed or they responded differently.
     #[tracing::instrument(skip(self, node_bytes))]
     pub fn send_and_respond_on_stream(
         &self,
@@ -203,13 +252,36 @@ impl Comm {
         node_bytes: BTreeMap<NodeId, UsrMsgBytes>,
         expected_targets: usize,
         dst_stream: (Dst, SendStream),
-    ) {
+    ) -> oneshot::Receiver<UsrMsgBytes> {
+        let (response_sender, response) = oneshot::channel();
         self.send_cmd(CommCmd::SendAndRespondOnStream {
             msg_id,
             node_bytes,
             expected_targets,
             dst_stream,
-        })
+            response_sender,
+        });
+        response
+    }
+
+    /// Sends the payload on new bidi-streams to the nodes, without responding to anyone.
+    /// The returned receiver gets the response the nodes agreed on.
+    /// It gets nothing if any of them failed or they responded differently.
+    #[tracing::instrument(skip(self, node_bytes))]
+    pub fn send_and_await_response(
+        &self,
+        msg_id: MsgId,
+        node_bytes: BTreeMap<NodeId, UsrMsgBytes>,
+        expected_targets: usize,
+    ) -> oneshot::Receiver<UsrMsgBytes> {
+        let (response_sender, response) = oneshot::channel();
+        self.send_cmd(CommCmd::SendAndAwaitResponse {
+            msg_id,
+            node_bytes,
+            expected_targets,
+            response_sender,
+        });
+        response
     }
 
     fn send_cmd(&self, cmd: CommCmd) {
@@ -245,11 +317,20 @@ enum CommCmd {
         node_bytes: BTreeMap<NodeId, UsrMsgBytes>,
         expected_targets: usize,
         dst_stream: (Dst, SendStream),
+        response_sender: oneshot::Sender<UsrMsgBytes>,
+    },
+    SendAndAwaitResponse {
+        msg_id: MsgId,
+        #[debug(skip)]
+        node_bytes: BTreeMap<NodeId, UsrMsgBytes>,
+        expected_targets: usize,
+        response_sender: oneshot::Sender<UsrMsgBytes>,
     },
 }
 
 fn process_cmds(
-    our_endpoint: Endpoint,
+    transport: Arc<dyn Transport>,
+    peer_versions: PeerVersions,
     mut cmd_receiver: Receiver<CommCmd>,
     comm_events: Sender<CommEvent>,
 ) {
@@ -260,12 +341,14 @@ fn process_cmds(
             match cmd {
                 // This is the only place that mutates `links`.
                 CommCmd::SetTargets(targets) => {
-                    // Drops links that are not among the targets.
+                    // Drops links that are not among the targets, and the versions they support.
                     links.retain(|p, _| targets.contains(p));
+                    peer_versions.retain(|addr| targets.iter().any(|p| p.addr() == *addr));
                     // Adds new links for each new target.
                     targets.iter().for_each(|node_id| {
                         if links.get(node_id).is_none() {
-                            let link = NodeLink::new(*node_id, our_endpoint.clone());
+                            let link =
+                                NodeLink::new(*node_id, transport.clone(), peer_versions.clone());
                             let _ = links.insert(*node_id, link);
                         }
                     });
@@ -295,6 +378,7 @@ fn process_cmds(
                     node_bytes,
                     expected_targets,
                     dst_stream,
+                    response_sender,
                 } => {
                     let node_bytes = node_bytes
                         .into_iter()
@@ -309,9 +393,35 @@ fn process_cmds(
                         node_bytes,
                         expected_targets,
                         dst_stream,
+                        response_sender,
                         comm_events.clone(),
                     )
                 }
+                CommCmd::SendAndAwaitResponse {
+                    msg_id,
+                    node_bytes,
+                    expected_targets,
+                    response_sender,
+                } => {
+                    let node_bytes = node_bytes
+                        .into_iter()
+                        .map(|(node_id, bytes)| {
+                            let link = get_link(msg_id, node_id, &links, comm_events.clone());
+                            (node_id, (link, bytes))
+                        })
+                        .collect();
+
+                    let comm_events = comm_events.clone();
+                    let _handle = task::spawn(async move {
+                        let response =
+                            agreed_response(msg_id, node_bytes, expected_targets, comm_events)
+                                .await;
+                        if let Some(response_bytes) = response {
+                            // the caller may not be interested in the response anymore
+                            let _ = response_sender.send(response_bytes);
+                        }
+                    });
+                }
             }
         }
     });
@@ -399,77 +509,84 @@ fn send_and_return_response(
     });
 }
 
-#[tracing::instrument(skip_all)]
 fn send_and_respond_on_stream(
     msg_id: MsgId,
     node_bytes: BTreeMap<NodeId, (Option<NodeLink>, UsrMsgBytes)>,
     expected_targets: usize,
     dst_stream: (Dst, SendStream),
+    response_sender: oneshot::Sender<UsrMsgBytes>,
     comm_events: Sender<CommEvent>,
 ) {
     let _handle = task::spawn(async move {
         let (dst, stream) = dst_stream;
 
-        let tasks = node_bytes
-            .into_iter()
-            .map(|pb| (pb, comm_events.clone()))
-            .map(|((node_id, (link, bytes)), comm_events)| async move {
-                let link = match link {
-                    Some(link) => link,
-                    None => return (node_id, Err(Error::ConnectingToUnknownNode(msg_id))),
-                };
-
-                let node_response_bytes =
-                    match link.send_with_bi_return_response(bytes, msg_id).await {
-                        Ok(response_bytes) => response_bytes,
-                        Err(error) => {
-                            error!("Failed sending {msg_id:?} to {node_id:?}: {error:?}");
-                            send_error(node_id, Error::FailedSend(msg_id), comm_events);
-                            return (node_id, Err(Error::FailedSend(msg_id)));
-                        }
-                    };
-
-                debug!("Response from node {node_id:?} is in for {msg_id:?}");
-                (node_id, Ok(node_response_bytes))
-            });
+        let Some(response_bytes) =
+            agreed_response(msg_id, node_bytes, expected_targets, comm_events).await
+        else {
+            match error_response(dst) {
+                None => error!("Could not send the error response to client!"),
+                Some(bytes) => send_on_stream(msg_id, bytes, stream).await,
+            }
+            return;
+        };
 
-        let node_results: Vec<(NodeId, Result<UsrMsgBytes>)> = join_all(tasks).await;
+        send_on_stream(msg_id, response_bytes.clone(), stream).await;
+        // the caller may not be interested in the response
+        let _ = response_sender.send(response_bytes);
+    });
+}
 
-        let succeeded: Vec<_> = node_results
-            .into_iter()
-            .filter_map(|(node_id, res)| match res {
-                Ok(bytes) => Some((node_id, bytes)),
+/// Sends the msg to the nodes, returning their response if they all responded the same.
+async fn agreed_response(
+    msg_id: MsgId,
+    node_bytes: BTreeMap<NodeId, (Option<NodeLink>, UsrMsgBytes)>,
+    expected_targets: usize,
+    comm_events: Sender<CommEvent>,
+) -> Option<UsrMsgBytes> {
+    let tasks = node_bytes
+        .into_iter()
+        .map(|pb| (pb, comm_events.clone()))
+        .map(|((node_id, (link, bytes)), comm_events)| async move {
+            let link = match link {
+                Some(link) => link,
+                None => return (node_id, Err(Error::ConnectingToUnknownNode(msg_id))),
+            };
+
+            let node_response_bytes = match link.send_with_bi_return_response(bytes, msg_id).await {
+                Ok(response_bytes) => response_bytes,
                 Err(error) => {
                     error!("Failed sending {msg_id:?} to {node_id:?}: {error:?}");
-                    send_error(node_id, Error::FailedSend(msg_id), comm_events.clone());
-                    None
+                    send_error(node_id, Error::FailedSend(msg_id), comm_events);
+                    return (node_id, Err(Error::FailedSend(msg_id)));
                 }
-            })
-            .collect();
+            };
 
-        let some_failed = expected_targets > succeeded.len();
-        let all_ok_equal = || succeeded.windows(2).all(|w| are_equal(&w[0].1, &w[1].1));
+            debug!("Response from node {node_id:?} is in for {msg_id:?}");
+            (node_id, Ok(node_response_bytes))
+        });
 
-        let response_bytes = if some_failed || !all_ok_equal() {
-            match error_response(dst) {
-                None => {
-                    error!("Could not send the error response to client!");
-                    return;
-                }
-                Some(bytes) => bytes,
-            }
-        } else {
-            match succeeded.last() {
-                Some((_, bytes)) => bytes.clone(),
-                _ => {
-                    error!("Could not send the response to client!");
-                    return;
-                }
+    let node_results: Vec<(NodeId, Result<UsrMsgBytes>)> = join_all(tasks).await;
+
+    let succeeded: Vec<_> = node_results
+        .into_iter()
+        .filter_map(|(node_id, res)| match res {
+            Ok(bytes) => Some((node_id, bytes)),
+            Err(error) => {
+                error!("Failed sending {msg_id:?} to {node_id:?}: {error:?}");
+                send_error(node_id, Error::FailedSend(msg_id), comm_events.clone());
+                None
             }
-        };
+        })
+        .collect();
 
-        send_on_stream(msg_id, response_bytes, stream).await;
-    });
+    let some_failed = expected_targets > succeeded.len();
+    let all_ok_equal = || succeeded.windows(2).all(|w| are_equal(&w[0].1, &w[1].1));
+
+    if some_failed || !all_ok_equal() {
+        return None;
+    }
+
+    succeeded.last().map(|(_, bytes)| bytes.clone())
 }
 
 #[tracing::instrument(skip_all)]
@@ -501,8 +618,14 @@ fn error_response(dst: Dst) -> Option<UsrMsgBytes> {
 
 #[tracing::instrument(skip_all)]
 fn are_equal(a: &UsrMsgBytes, b: &UsrMsgBytes) -> bool {
-    let (_, _, a_payload) = a;
-    let (_, _, b_payload) = b;
+    // the payloads are compared decompressed, as the nodes may not all have compressed them
+    let (a_payload, b_payload) = match (WireMsg::from(a.clone()), WireMsg::from(b.clone())) {
+        (Ok(mut a), Ok(mut b)) => match (a.decompress_payload(), b.decompress_payload()) {
+            (Ok(()), Ok(())) => (a.payload, b.payload),
+            _ => return false,
+        },
+        _ => return false,
+    };
     if !are_bytes_equal(a_payload.to_vec(), b_payload.to_vec()) {
         return false;
     }
@@ -529,6 +652,8 @@ mod tests {
     use sn_interface::{
         messaging::{
             data::{ClientMsg, DataQuery},
+            serialisation::{MESSAGING_PROTO_VERSION, MIN_MESSAGING_PROTO_VERSION},
+            system::NodeMsg,
             ClientAuth, Dst, MsgId, MsgKind,
         },
         types::{ChunkAddress, Keypair, NodeId},
@@ -537,6 +662,7 @@ mod tests {
     use assert_matches::assert_matches;
     use eyre::Result;
     use futures::future;
+    use qp2p::Endpoint;
     use std::{net::Ipv4Addr, time::Duration};
     use tokio::{
         net::UdpSocket,
@@ -674,6 +800,180 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn in_memory_msgs_are_delivered_after_the_latency() -> Result<()> {
+        let network = MemoryNetwork::new(0);
+        network.set_latency(Duration::from_millis(100));
+        let (comm0, _, _rx0) = in_memory_node(&network);
+        let (_comm1, node1, mut rx1) = in_memory_node(&network);
+
+        comm0.set_comm_targets([node1].into());
+        let msg = new_test_msg(dst(node1))?;
+        comm0.send_out_bytes(node1, msg.msg_id(), msg.serialize()?);
+
+        assert_matches!(
+            time::timeout(Duration::from_millis(99), rx1.recv()).await,
+            Err(_)
+        );
+        assert_matches!(rx1.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_memory_partitions_cut_nodes_off_until_healed() -> Result<()> {
+        let network = MemoryNetwork::new(0);
+        let (comm0, _, mut rx0) = in_memory_node(&network);
+        let (_comm1, node1, mut rx1) = in_memory_node(&network);
+        comm0.set_comm_targets([node1].into());
+
+        network.partition([node1.addr()]);
+        let msg = new_test_msg(dst(node1))?;
+        comm0.send_out_bytes(node1, msg.msg_id(), msg.serialize()?);
+
+        assert_matches!(rx0.recv().await, Some(CommEvent::Error { node_id, error }) => {
+            assert_eq!(node_id, node1);
+            assert_matches!(error, Error::FailedSend(_));
+        });
+        assert_matches!(time::timeout(TIMEOUT, rx1.recv()).await, Err(_));
+
+        network.heal();
+        comm0.send_out_bytes(node1, msg.msg_id(), msg.serialize()?);
+
+        assert_matches!(rx1.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_memory_lost_msgs_are_not_delivered() -> Result<()> {
+        let network = MemoryNetwork::new(0);
+        network.set_loss_rate(1.0);
+        let (comm0, _, mut rx0) = in_memory_node(&network);
+        let (_comm1, node1, mut rx1) = in_memory_node(&network);
+        comm0.set_comm_targets([node1].into());
+
+        let msg = new_test_msg(dst(node1))?;
+        comm0.send_out_bytes(node1, msg.msg_id(), msg.serialize()?);
+        assert_matches!(time::timeout(TIMEOUT, rx1.recv()).await, Err(_));
+
+        // the response to a lost msg never arrives
+        comm0.send_and_return_response(node1, msg.msg_id(), msg.serialize()?);
+        assert_matches!(rx0.recv().await, Some(CommEvent::Error { node_id, error }) => {
+            assert_eq!(node_id, node1);
+            assert_matches!(error, Error::FailedSend(_));
+        });
+        assert_matches!(time::timeout(TIMEOUT, rx1.recv()).await, Err(_));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_memory_responses_are_returned_on_the_stream() -> Result<()> {
+        let network = MemoryNetwork::new(0);
+        let (comm0, node0, mut rx0) = in_memory_node(&network);
+        let (_comm1, node1, mut rx1) = in_memory_node(&network);
+        comm0.set_comm_targets([node1].into());
+
+        let msg = new_test_msg(dst(node1))?;
+        comm0.send_and_return_response(node1, msg.msg_id(), msg.serialize()?);
+
+        let response = new_test_msg(dst(node0))?;
+        assert_matches!(rx1.recv().await, Some(CommEvent::Msg(MsgReceived { sender, send_stream: Some(mut stream), .. })) => {
+            assert_eq!(sender.addr(), node0.addr());
+            stream.send_user_msg(response.serialize()?).await?;
+        });
+        assert_matches!(rx0.recv().await, Some(CommEvent::Msg(MsgReceived { sender, wire_msg, .. })) => {
+            assert_eq!(sender.addr(), node1.addr());
+            assert_eq!(wire_msg, response);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn mixed_version_nodes_keep_communicating() -> Result<()> {
+        let network = MemoryNetwork::new(0);
+        let (new0, node0, mut rx0) = in_memory_node(&network);
+        let (new1, node1, mut rx1) = in_memory_node(&network);
+        // a node predating the latest version
+        let (old, old_node, mut old_rx) =
+            in_memory_node_with_versions(&network, ProtocolVersions::LEGACY);
+        new0.set_comm_targets([node1, old_node].into());
+        new1.set_comm_targets([node0, old_node].into());
+        old.set_comm_targets([node0, node1].into());
+
+        // until they've heard from each other, nodes send with the oldest version they support
+        let msg = new_node_msg(node0, dst(node1))?;
+        new0.send_out_bytes(node1, msg.msg_id(), msg.serialize()?);
+        assert_matches!(rx1.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+            assert_eq!(wire_msg.version(), MIN_MESSAGING_PROTO_VERSION);
+            assert_eq!(wire_msg.supported_versions(), ProtocolVersions::SUPPORTED);
+        });
+
+        // then upgrade to the latest version they both support
+        let msg = new_node_msg(node1, dst(node0))?;
+        new1.send_out_bytes(node0, msg.msg_id(), msg.serialize()?);
+        assert_matches!(rx0.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+            assert_eq!(wire_msg.version(), MESSAGING_PROTO_VERSION);
+        });
+
+        // the old node keeps receiving the version it supports from the new nodes...
+        let msg = new_node_msg(old_node, dst(node0))?;
+        old.send_out_bytes(node0, msg.msg_id(), msg.serialize()?);
+        assert_matches!(rx0.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+            assert_eq!(wire_msg.version(), 1);
+            assert_eq!(wire_msg.supported_versions(), ProtocolVersions::LEGACY);
+        });
+        let msg = new_test_msg(dst(old_node))?;
+        new0.send_out_bytes(old_node, msg.msg_id(), msg.serialize()?);
+        assert_matches!(old_rx.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, msg);
+            assert_eq!(wire_msg.version(), 1);
+        });
+
+        // ...including the responses to its msgs
+        let request = new_test_msg(dst(node1))?;
+        old.send_and_return_response(node1, request.msg_id(), request.serialize()?);
+        let response = new_test_msg(dst(old_node))?;
+        assert_matches!(rx1.recv().await, Some(CommEvent::Msg(MsgReceived { send_stream: Some(mut stream), .. })) => {
+            stream.send_user_msg(response.serialize()?).await?;
+        });
+        assert_matches!(old_rx.recv().await, Some(CommEvent::Msg(MsgReceived { wire_msg, .. })) => {
+            assert_eq!(wire_msg, response);
+            assert_eq!(wire_msg.version(), 1);
+        });
+
+        // whereas the msgs serialised with the latest version would be dropped by it
+        let (transport, _incoming_msgs) = network.transport();
+        let conn = transport.connect_to(&old_node.addr()).await?;
+        conn.send(new_test_msg(dst(old_node))?.serialize()?).await?;
+        assert_matches!(time::timeout(TIMEOUT, old_rx.recv()).await, Err(_));
+
+        Ok(())
+    }
+
+    fn in_memory_node(network: &MemoryNetwork) -> (Comm, NodeId, Receiver<CommEvent>) {
+        in_memory_node_with_versions(network, ProtocolVersions::SUPPORTED)
+    }
+
+    fn in_memory_node_with_versions(
+        network: &MemoryNetwork,
+        versions: ProtocolVersions,
+    ) -> (Comm, NodeId, Receiver<CommEvent>) {
+        let (transport, incoming_msgs) = network.transport();
+        let (comm, rx) = Comm::with_transport(transport, incoming_msgs, None, versions);
+        let node_id = NodeId::new(xor_name::rand::random(), comm.socket_addr());
+        (comm, node_id, rx)
+    }
+
     fn dst(node_id: NodeId) -> Dst {
         Dst {
             name: node_id.name(),
@@ -700,11 +1000,22 @@ mod tests {
                 auth,
                 is_spend: false,
                 query_index: None,
+                is_register: false,
             },
             dst,
         ))
     }
 
+    fn new_node_msg(src: NodeId, dst: Dst) -> Result<WireMsg> {
+        let payload = WireMsg::serialize_msg_payload(&NodeMsg::HandoverAE(0))?;
+        let kind = MsgKind::Node {
+            name: src.name(),
+            is_join: false,
+            is_data: false,
+        };
+        Ok(WireMsg::new_msg(MsgId::new(), payload, kind, dst))
+    }
+
     async fn new_node_id() -> Result<(NodeId, Receiver<UsrMsgBytes>)> {
         let (endpoint, mut incoming_connections) =
             Endpoint::builder().addr(local_addr()).server()?;
@@ -5,104 +5,87 @@
 // under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
-use super::{CommEvent, MsgReceived};
+use super::{CommEvent, IncomingMsg, MsgReceived, PeerVersions, SendStream};
 
 use sn_interface::{
     messaging::{MsgKind, WireMsg},
     types::{log_markers::LogMarker, Participant},
 };
 
-use qp2p::{Connection, ConnectionIncoming, IncomingConnections};
-use tokio::{sync::mpsc::Sender, task};
+use tokio::{
+    sync::mpsc::{Receiver, Sender},
+    task,
+};
 
+// Hands over the msgs received, which the transports decoded on the task of the connection each
+// came over, negotiating the version to respond to them with.
 #[tracing::instrument(skip_all)]
-pub(crate) fn listen_for_connections(
-    comm_events_sender: Sender<CommEvent>,
-    mut incoming_connections: IncomingConnections,
+pub(crate) fn listen_for_msgs(
+    comm_events: Sender<CommEvent>,
+    mut incoming_msgs: Receiver<IncomingMsg>,
+    peer_versions: PeerVersions,
 ) {
     let _handle = task::spawn(async move {
-        while let Some((connection, incoming_msgs)) = incoming_connections.next().await {
-            trace!(
-                "{}: from {:?} with connection_id {}",
-                LogMarker::IncomingConnection,
-                connection.remote_address(),
-                connection.id()
-            );
+        while let Some(msg) = incoming_msgs.recv().await {
+            let IncomingMsg {
+                wire_msg,
+                len,
+                remote_addr,
+                conn_id,
+                mut send_stream,
+            } = msg;
 
-            let _handle = task::spawn(listen_for_msgs(
-                comm_events_sender.clone(),
-                connection,
-                incoming_msgs,
-            ));
-        }
-    });
-}
+            let stream_info = if let Some(stream) = &send_stream {
+                format!(" on {}", stream.id())
+            } else {
+                "".to_string()
+            };
 
-#[tracing::instrument(skip_all)]
-pub(crate) async fn listen_for_msgs(
-    comm_events: Sender<CommEvent>,
-    conn: Connection,
-    mut incoming_msgs: ConnectionIncoming,
-) {
-    let conn_id = conn.id();
-    let remote_address = conn.remote_address();
-
-    while let Some(result) = incoming_msgs.next_with_stream().await.transpose() {
-        match result {
-            Ok((msg_bytes, send_stream)) => {
-                let stream_info = if let Some(stream) = &send_stream {
-                    format!(" on {}", stream.id())
-                } else {
-                    "".to_string()
-                };
+            // We may decode versions we've been configured not to support, e.g. to run
+            // as an older node would, in which case we drop the msg as the older node would
+            let ours = peer_versions.ours();
+            if !ours.contains(wire_msg.version()) {
                 debug!(
-                    "New msg arrived over conn_id={conn_id} from {remote_address:?}{stream_info}"
+                    "Dropping msg received from {remote_addr:?}{stream_info}, serialised with unsupported version {}",
+                    wire_msg.version()
                 );
+                continue;
+            }
 
-                let (header, dst, payload) = &msg_bytes.0;
-                let original_bytes_len = header.len() + dst.len() + payload.len();
-
-                let wire_msg = match WireMsg::from(msg_bytes.0) {
-                    Ok(wire_msg) => wire_msg,
-                    Err(error) => {
-                        // TODO: should perhaps rather drop this connection.. as it is a spam vector
-                        debug!("Failed to deserialize message received from {remote_address:?}{stream_info}: {error:?}");
-                        continue;
-                    }
-                };
+            // Responses are serialised with the latest version supported by the sender,
+            // it's at worst the version it sent the msg with
+            let theirs = wire_msg.supported_versions();
+            if let Some(stream) = &mut send_stream {
+                let version = ours.negotiate(&theirs).unwrap_or(wire_msg.version());
+                stream.respond_with(version, ours);
+            }
 
-                let src_name = match wire_msg.kind() {
-                    MsgKind::Client { auth, .. } => auth.public_key.into(),
-                    MsgKind::Node { name, .. }
-                    | MsgKind::AntiEntropy(name)
-                    | MsgKind::DataResponse(name) => *name,
-                };
+            let src_name = match wire_msg.kind() {
+                MsgKind::Client { auth, .. } => auth.public_key.into(),
+                MsgKind::Node { name, .. }
+                | MsgKind::AntiEntropy(name)
+                | MsgKind::DataResponse(name) => {
+                    peer_versions.record(remote_addr, theirs);
+                    *name
+                }
+            };
 
-                let msg_id = wire_msg.msg_id();
-                let src = Participant::new(src_name, remote_address);
-                trace!(
-                    "{:?} from {src:?} length {original_bytes_len}",
-                    LogMarker::MsgReceived,
-                );
-                debug!(
-                        "Msg {msg_id:?} received, over conn_id={conn_id}, from: {src:?}{stream_info} was: {wire_msg:?}"
-                    );
+            let msg_id = wire_msg.msg_id();
+            let src = Participant::new(src_name, remote_addr);
+            trace!("{:?} from {src:?} length {len}", LogMarker::MsgReceived,);
+            debug!(
+                "Msg {msg_id:?} received, over conn_id={conn_id}, from: {src:?}{stream_info} was: {wire_msg:?}"
+            );
 
-                msg_received(wire_msg, src, send_stream, comm_events.clone()).await;
-            }
-            Err(error) => {
-                warn!("Error on connection {conn_id} with {remote_address}: {error:?}");
-            }
+            msg_received(wire_msg, src, send_stream, comm_events.clone()).await;
         }
-    }
-
-    trace!(%conn_id, %remote_address, "{}", LogMarker::ConnectionClosed);
+    });
 }
 
 pub(crate) async fn msg_received(
     wire_msg: WireMsg,
     sender: Participant,
-    send_stream: Option<qp2p::SendStream>,
+    send_stream: Option<SendStream>,
     comm_events: Sender<CommEvent>,
 ) {
     let msg_id = wire_msg.msg_id();
@@ -6,16 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Result;
+use super::{Connection, PeerVersions, Result, Transport, TransportError};
 
 use sn_interface::{
     messaging::MsgId,
     types::{log_markers::LogMarker, NodeId},
 };
 
-use qp2p::{Connection, Endpoint, UsrMsgBytes};
+use qp2p::UsrMsgBytes;
 
-use custom_debug::Debug;
 use dashmap::DashMap;
 use std::sync::Arc;
 use thiserror::Error;
@@ -40,18 +39,24 @@ const CONN_RETRY_WAIT: Duration = Duration::from_millis(100);
 #[derive(Clone)]
 pub(crate) struct NodeLink {
     node_id: NodeId,
-    endpoint: Endpoint,
+    transport: Arc<dyn Transport>,
     connections: NodeConnections,
+    versions: PeerVersions,
 }
 
-type NodeConnections = Arc<DashMap<ConnId, Arc<Connection>>>;
+type NodeConnections = Arc<DashMap<ConnId, Arc<dyn Connection>>>;
 
 impl NodeLink {
-    pub(crate) fn new(node_id: NodeId, endpoint: Endpoint) -> Self {
+    pub(crate) fn new(
+        node_id: NodeId,
+        transport: Arc<dyn Transport>,
+        versions: PeerVersions,
+    ) -> Self {
         Self {
             node_id,
-            endpoint,
+            transport,
             connections: NodeConnections::default(),
+            versions,
         }
     }
 
@@ -76,6 +81,7 @@ impl NodeLink {
             "Sending {msg_id:?} via a bi-stream to {node_id:?}, we have {} cached connections.",
             self.connections.len()
         );
+        let bytes = self.encode(bytes)?;
         let mut attempt = 0;
         loop {
             let conn = self
@@ -93,7 +99,7 @@ impl NodeLink {
             } else {
                 trace!("Sending {msg_id:?} via bi-di-stream over new connection to {node_id:?}, attempt #{attempt}.");
                 let conn =
-                    create_connection(node_id, &self.endpoint, self.connections.clone(), msg_id)
+                    create_connection(node_id, &*self.transport, self.connections.clone(), msg_id)
                         .await?;
                 (conn, true)
             };
@@ -102,61 +108,15 @@ impl NodeLink {
 
             let conn_id = conn.id();
             trace!("Connection {conn_id} got to {node_id:?} for {msg_id:?}");
-            let (mut send_stream, recv_stream) = match conn.open_bi().await {
-                Ok(bi_stream) => bi_stream,
-                Err(err) => {
-                    error!("{msg_id:?} Error opening bi-stream over {conn_id}: {err:?}");
-                    // remove that broken conn
-                    let _conn = self.connections.remove(&conn_id);
-                    match is_last_attempt {
-                        true => {
-                            error!("Last attempt reached for {msg_id:?}, erroring out...");
-                            break Err(NodeLinkError::Connection(err));
-                        }
-                        false => {
-                            // tiny wait for comms/dashmap to cope with removal
-                            sleep(CONN_RETRY_WAIT).await;
-                            continue;
-                        }
-                    }
-                }
-            };
-
-            let stream_id = send_stream.id();
-            trace!("bidi {stream_id} opened for {msg_id:?} to {node_id:?}");
-            send_stream.set_priority(10);
-            if let Err(err) = send_stream.send_user_msg(bytes.clone()).await {
-                error!("Error sending bytes for {msg_id:?} over {stream_id}: {err:?}");
-                // remove that broken conn
-                let _conn = self.connections.remove(&conn_id);
-                match is_last_attempt {
-                    true => break Err(NodeLinkError::Send(err)),
-                    false => {
-                        // tiny wait for comms/dashmap to cope with removal
-                        sleep(CONN_RETRY_WAIT).await;
-                        continue;
-                    }
-                }
-            }
-
-            trace!("{msg_id:?} sent on {stream_id} to {node_id:?}");
-
-            // unblock + move finish off thread as it's not strictly related to the sending of the msg.
-            let stream_id_clone = stream_id.clone();
-            let _handle = tokio::spawn(async move {
-                // Attempt to gracefully terminate the stream.
-                // If this errors it does _not_ mean our message has not been sent
-                let result = send_stream.finish().await;
-                trace!("{msg_id:?} finished {stream_id_clone} to {node_id:?}: {result:?}");
-            });
-
-            match recv_stream.read().await {
+            match conn.send_bi(bytes.clone()).await {
                 Ok(response) => break Ok(response),
                 Err(err) => {
-                    error!("Error receiving response to {msg_id:?} from {node_id:?} over {stream_id}: {err:?}");
+                    error!("Error sending {msg_id:?} and receiving the response via bi-stream over {conn_id} to {node_id:?}: {err:?}");
+                    // remove that broken conn
                     let _conn = self.connections.remove(&conn_id);
                     if is_last_attempt {
-                        break Err(NodeLinkError::Recv(err));
+                        error!("Last attempt reached for {msg_id:?}, erroring out...");
+                        break Err(NodeLinkError::Transport(err));
                     }
 
                     // tiny wait for comms/dashmap to cope with removal
@@ -175,6 +135,7 @@ impl NodeLink {
         let mut connection_retries = 0;
 
         let node_id = self.node_id;
+        let bytes = self.encode(bytes)?;
 
         loop {
             trace!("Sending to {node_id} over connection: {msg_id:?}");
@@ -244,8 +205,19 @@ impl NodeLink {
         }
     }
 
+    // Serialises the msg with the version of the messaging protocol negotiated with the node
+    fn encode(&self, bytes: UsrMsgBytes) -> Result<UsrMsgBytes, NodeLinkError> {
+        let version = self.versions.negotiate(&self.node_id.addr());
+        self.versions
+            .encode(bytes, version)
+            .map_err(|error| NodeLinkError::Transport(TransportError::Serialisation(error)))
+    }
+
     // Gets an existing connection or creates a new one
-    async fn get_or_connect(&mut self, msg_id: MsgId) -> Result<Arc<Connection>, NodeLinkError> {
+    async fn get_or_connect(
+        &mut self,
+        msg_id: MsgId,
+    ) -> Result<Arc<dyn Connection>, NodeLinkError> {
         let node_id = self.node_id;
         trace!("{msg_id:?} Grabbing a connection to {node_id:?} from cached set.");
 
@@ -259,14 +231,14 @@ impl NodeLink {
             Ok(conn)
         } else {
             trace!("{msg_id:?} No connection found to {node_id:?}, creating a new one.");
-            create_connection(node_id, &self.endpoint, self.connections.clone(), msg_id).await
+            create_connection(node_id, &*self.transport, self.connections.clone(), msg_id).await
         }
     }
 
     /// Send a message to the node using the given connection.
     #[instrument(skip_all)]
     async fn send_with_connection(
-        conn: Arc<Connection>,
+        conn: Arc<dyn Connection>,
         bytes: UsrMsgBytes,
         connections: NodeConnections,
     ) -> Result<(), NodeLinkError> {
@@ -274,7 +246,7 @@ impl NodeLink {
         let conns_count = connections.len();
         trace!("We have {conns_count} open connections to node {conn_id}.");
 
-        conn.send_with(bytes, 0 /* priority */).await.map_err(|error| {
+        conn.send(bytes).await.map_err(|error| {
             error!(
                 "Error sending out msg... We have {conns_count} open connections to node {conn_id}: {error:?}",
             );
@@ -286,34 +258,30 @@ impl NodeLink {
             debug!("Connection removed from session: {conn_id}");
             // dont close just let the conn timeout incase msgs are coming in...
             // it's removed from our node tracking, so won't be used again for sending.
-            NodeLinkError::Send(error)
+            NodeLinkError::Transport(error)
         })
     }
 }
 
 async fn create_connection(
     node_id: NodeId,
-    endpoint: &Endpoint,
+    transport: &dyn Transport,
     connections: NodeConnections,
     msg_id: MsgId,
-) -> Result<Arc<Connection>, NodeLinkError> {
+) -> Result<Arc<dyn Connection>, NodeLinkError> {
     debug!("{msg_id:?} create conn attempt to {node_id:?}");
-    let (conn, _) = endpoint
-        .connect_to(&node_id.addr())
-        .await
-        .map_err(NodeLinkError::Connection)?;
+    let conn = transport.connect_to(&node_id.addr()).await?;
 
     trace!(
         "{msg_id:?}: {} to {} (id: {})",
         LogMarker::ConnectionOpened,
-        conn.remote_address(),
+        conn.remote_addr(),
         conn.id()
     );
 
     let conn_id = conn.id();
     debug!("Inserting connection into node link: {conn_id}");
 
-    let conn = Arc::new(conn);
     let _ = connections.insert(conn_id.clone(), conn.clone());
     debug!("Connection INSERTED into node link: {conn_id}");
 
@@ -323,24 +291,14 @@ async fn create_connection(
 /// Errors that can be returned from `Comm::send_to_one`.
 #[derive(Debug, Error)]
 pub(super) enum NodeLinkError {
-    #[error("Failed to connect: {0:?}")]
-    Connection(qp2p::ConnectionError),
-    #[error("Failed to send a message: {0:?}")]
-    Send(qp2p::SendError),
-    #[error("Failed to receive a message: {0:?}")]
-    Recv(qp2p::RecvError),
+    #[error(transparent)]
+    Transport(#[from] TransportError),
     #[error("Max number of attempts ({0}) to send msg to the node has been reached")]
     MaxRetriesReached(usize),
 }
 
 impl NodeLinkError {
     fn is_local_close(&self) -> bool {
-        matches!(
-            self,
-            NodeLinkError::Connection(qp2p::ConnectionError::Closed(qp2p::Close::Local))
-                | NodeLinkError::Send(qp2p::SendError::ConnectionLost(
-                    qp2p::ConnectionError::Closed(qp2p::Close::Local)
-                ))
-        )
+        matches!(self, NodeLinkError::Transport(error) if error.is_local_close())
     }
 }
@@ -0,0 +1,63 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::messaging::{ProtocolVersions, Result as MsgResult, WireMsg};
+
+use dashmap::DashMap;
+use qp2p::UsrMsgBytes;
+use std::{net::SocketAddr, sync::Arc};
+
+/// The versions of the messaging protocol we and the nodes we send msgs to support.
+///
+/// Nodes advertise the versions they support in every msg they send, so we learn them
+/// as their msgs come in, and send ours to them with the latest version we both support.
+#[derive(Clone, Debug)]
+pub(crate) struct PeerVersions {
+    ours: ProtocolVersions,
+    peers: Arc<DashMap<SocketAddr, ProtocolVersions>>,
+}
+
+impl PeerVersions {
+    pub(crate) fn new(ours: ProtocolVersions) -> Self {
+        Self {
+            ours,
+            peers: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// The versions we support.
+    pub(crate) fn ours(&self) -> ProtocolVersions {
+        self.ours
+    }
+
+    /// Records the versions the node at the given address advertised.
+    pub(crate) fn record(&self, addr: SocketAddr, versions: ProtocolVersions) {
+        let _prev = self.peers.insert(addr, versions);
+    }
+
+    /// Forgets about the nodes not at any of the given addresses.
+    pub(crate) fn retain(&self, is_kept: impl Fn(&SocketAddr) -> bool) {
+        self.peers.retain(|addr, _| is_kept(addr));
+    }
+
+    /// The version to send msgs to the node at the given address with.
+    ///
+    /// Until we've heard from the node, or if we've no version in common, that's the oldest
+    /// version we support, which the node still decodes if it's within its deprecation window.
+    pub(crate) fn negotiate(&self, addr: &SocketAddr) -> u16 {
+        self.peers
+            .get(addr)
+            .and_then(|theirs| self.ours.negotiate(&theirs))
+            .unwrap_or(self.ours.min)
+    }
+
+    /// Serialises again the msg with the given version, advertising ours.
+    pub(crate) fn encode(&self, bytes: UsrMsgBytes, version: u16) -> MsgResult<UsrMsgBytes> {
+        WireMsg::encode_for_version(bytes, version, self.ours)
+    }
+}
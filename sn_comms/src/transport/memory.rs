@@ -0,0 +1,309 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    Connection, IncomingMsg, ResponseStream, SendStream, Transport, TransportError, TransportResult,
+};
+use crate::STANDARD_CHANNEL_SIZE;
+
+use async_trait::async_trait;
+use qp2p::UsrMsgBytes;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use tokio::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        oneshot,
+    },
+    time::sleep,
+};
+
+/// A network simulated within the process, the in-memory transports of its nodes are bound to.
+///
+/// Msgs are delivered after the latency set, unless lost, which is decided by a random
+/// generator seeded on creation, so that runs on a paused tokio clock are reproducible.
+/// Partitions cut off the nodes on each side from the others until healed.
+#[derive(Clone, Debug)]
+pub struct MemoryNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+#[derive(Debug)]
+struct NetworkState {
+    nodes: BTreeMap<SocketAddr, Sender<IncomingMsg>>,
+    // the nodes bound so far, which their addresses are derived from
+    nodes_bound: u32,
+    // the connections and streams opened so far, which their ids are derived from
+    ids_issued: u64,
+    latency: Duration,
+    loss_rate: f64,
+    partitions: Vec<BTreeSet<SocketAddr>>,
+    rng: StdRng,
+}
+
+// How a msg from one node to another is delivered
+struct Route {
+    inbox: Sender<IncomingMsg>,
+    latency: Duration,
+    is_lost: bool,
+}
+
+impl MemoryNetwork {
+    /// Creates a network without latency, loss nor partitions,
+    /// with the msgs lost later on decided by a generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        let state = NetworkState {
+            nodes: BTreeMap::new(),
+            nodes_bound: 0,
+            ids_issued: 0,
+            latency: Duration::ZERO,
+            loss_rate: 0.0,
+            partitions: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Binds a new transport to the network, at an address of its own,
+    /// handing over the msgs received on it.
+    pub fn transport(&self) -> (MemoryTransport, Receiver<IncomingMsg>) {
+        let mut state = self.lock();
+        let index = state.nodes_bound;
+        state.nodes_bound += 1;
+
+        let ip = Ipv4Addr::from(u32::from(Ipv4Addr::LOCALHOST) + index / u32::from(u16::MAX));
+        let port = 1 + (index % u32::from(u16::MAX)) as u16;
+        let addr = SocketAddr::from((ip, port));
+
+        let (inbox, incoming_msgs) = mpsc::channel(STANDARD_CHANNEL_SIZE);
+        let _prev = state.nodes.insert(addr, inbox);
+
+        let transport = MemoryTransport {
+            addr,
+            network: self.clone(),
+        };
+        (transport, incoming_msgs)
+    }
+
+    /// Sets the time it takes for any msg to be delivered.
+    pub fn set_latency(&self, latency: Duration) {
+        self.lock().latency = latency;
+    }
+
+    /// Sets the proportion of msgs which are lost, between 0 and 1.
+    /// The response to a msg sent on a bi-directional stream which is lost never arrives,
+    /// the stream being closed instead.
+    pub fn set_loss_rate(&self, loss_rate: f64) {
+        self.lock().loss_rate = loss_rate.clamp(0.0, 1.0);
+    }
+
+    /// Cuts off the nodes on the given side of a partition from all the other nodes.
+    pub fn partition(&self, side: impl IntoIterator<Item = SocketAddr>) {
+        self.lock().partitions.push(side.into_iter().collect());
+    }
+
+    /// Heals all the partitions.
+    pub fn heal(&self) {
+        self.lock().partitions.clear();
+    }
+
+    fn next_id(&self) -> u64 {
+        let mut state = self.lock();
+        state.ids_issued += 1;
+        state.ids_issued
+    }
+
+    // Whether both nodes are bound and on the same side of all the partitions
+    fn is_reachable(state: &NetworkState, from: &SocketAddr, to: &SocketAddr) -> bool {
+        state.nodes.contains_key(from)
+            && state.nodes.contains_key(to)
+            && state
+                .partitions
+                .iter()
+                .all(|side| side.contains(from) == side.contains(to))
+    }
+
+    fn route(&self, from: &SocketAddr, to: &SocketAddr) -> TransportResult<Route> {
+        let mut state = self.lock();
+        if !Self::is_reachable(&state, from, to) {
+            return Err(TransportError::Unreachable(*to));
+        }
+        let loss_rate = state.loss_rate;
+        let is_lost = loss_rate > 0.0 && state.rng.gen_bool(loss_rate);
+        let inbox = state
+            .nodes
+            .get(to)
+            .cloned()
+            .ok_or(TransportError::Unreachable(*to))?;
+
+        Ok(Route {
+            inbox,
+            latency: state.latency,
+            is_lost,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, NetworkState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Transport of a node bound to a `MemoryNetwork`.
+#[derive(Clone, Debug)]
+pub struct MemoryTransport {
+    addr: SocketAddr,
+    network: MemoryNetwork,
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    async fn connect_to(&self, addr: &SocketAddr) -> TransportResult<Arc<dyn Connection>> {
+        if !MemoryNetwork::is_reachable(&self.network.lock(), &self.addr, addr) {
+            return Err(TransportError::Unreachable(*addr));
+        }
+        Ok(Arc::new(MemoryConnection {
+            id: self.network.next_id().to_string(),
+            local_addr: self.addr,
+            remote_addr: *addr,
+            network: self.network.clone(),
+        }))
+    }
+
+    fn close(&self) {
+        let _inbox = self.network.lock().nodes.remove(&self.addr);
+    }
+}
+
+#[derive(Debug)]
+struct MemoryConnection {
+    id: String,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    network: MemoryNetwork,
+}
+
+#[async_trait]
+impl Connection for MemoryConnection {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+
+    async fn send(&self, bytes: UsrMsgBytes) -> TransportResult<()> {
+        let route = self.network.route(&self.local_addr, &self.remote_addr)?;
+        sleep(route.latency).await;
+        if route.is_lost {
+            trace!("Msg over connection {} was lost", self.id);
+            return Ok(());
+        }
+
+        // msgs are decoded on the sender's task, as on the task of each connection over QUIC
+        let Some(msg) = IncomingMsg::decode(bytes, self.local_addr, self.id.clone(), None) else {
+            return Ok(());
+        };
+        route
+            .inbox
+            .send(msg)
+            .await
+            .map_err(|_| TransportError::Unreachable(self.remote_addr))
+    }
+
+    async fn send_bi(&self, bytes: UsrMsgBytes) -> TransportResult<UsrMsgBytes> {
+        let route = self.network.route(&self.local_addr, &self.remote_addr)?;
+        let (response_sender, response) = oneshot::channel();
+        sleep(route.latency).await;
+
+        if route.is_lost {
+            // the sender finds the stream closed
+            trace!("Msg over connection {} was lost", self.id);
+            drop(response_sender);
+        } else {
+            let stream = MemoryStream {
+                id: format!("{}/{}", self.id, self.network.next_id()),
+                local_addr: self.remote_addr,
+                remote_addr: self.local_addr,
+                network: self.network.clone(),
+                response_sender: Some(response_sender),
+            };
+            // the sender finds the stream closed if the msg is dropped on receipt
+            if let Some(msg) = IncomingMsg::decode(
+                bytes,
+                self.local_addr,
+                self.id.clone(),
+                Some(SendStream::new(stream)),
+            ) {
+                route
+                    .inbox
+                    .send(msg)
+                    .await
+                    .map_err(|_| TransportError::Unreachable(self.remote_addr))?;
+            }
+        }
+
+        response
+            .await
+            .map_err(|_| TransportError::StreamClosed(self.remote_addr))
+    }
+}
+
+#[derive(custom_debug::Debug)]
+struct MemoryStream {
+    id: String,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    #[debug(skip)]
+    network: MemoryNetwork,
+    #[debug(skip)]
+    response_sender: Option<oneshot::Sender<UsrMsgBytes>>,
+}
+
+#[async_trait]
+impl ResponseStream for MemoryStream {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn set_priority(&self, _priority: i32) {}
+
+    async fn send_user_msg(&mut self, bytes: UsrMsgBytes) -> TransportResult<()> {
+        let route = self.network.route(&self.local_addr, &self.remote_addr)?;
+        let response_sender = self
+            .response_sender
+            .take()
+            .ok_or(TransportError::StreamClosed(self.local_addr))?;
+        sleep(route.latency).await;
+        if route.is_lost {
+            // the sender finds the stream closed
+            trace!("Response over stream {} was lost", self.id);
+            return Ok(());
+        }
+
+        response_sender
+            .send(bytes)
+            .map_err(|_| TransportError::StreamClosed(self.remote_addr))
+    }
+
+    async fn finish(&mut self) -> TransportResult<()> {
+        self.response_sender = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! The transports msgs between nodes are carried over.
+//!
+//! Nodes communicate over QUIC, while the in-memory transport simulates a whole network
+//! within a single process, with latency, msg loss and partitions injected as required.
+
+#[cfg(any(test, feature = "test"))]
+mod memory;
+mod quic;
+
+#[cfg(any(test, feature = "test"))]
+pub use self::memory::{MemoryNetwork, MemoryTransport};
+pub use self::quic::QuicTransport;
+
+use sn_interface::messaging::{ProtocolVersions, WireMsg, MAX_MSG_SIZE};
+
+use async_trait::async_trait;
+use qp2p::UsrMsgBytes;
+use std::{fmt::Debug, net::SocketAddr, sync::Arc};
+use thiserror::Error;
+
+/// The type returned by the transports.
+pub type TransportResult<T> = Result<T, TransportError>;
+
+/// A transport our node connects to other nodes with, and is connected to by them.
+///
+/// The msgs received on the transport are handed over on the channel it was created with.
+#[async_trait]
+pub trait Transport: Debug + Send + Sync + 'static {
+    /// The address other nodes connect to us on.
+    fn local_addr(&self) -> SocketAddr;
+
+    /// Opens a new connection to the node at the given address.
+    async fn connect_to(&self, addr: &SocketAddr) -> TransportResult<Arc<dyn Connection>>;
+
+    /// Closes all the connections, and stops accepting new ones.
+    fn close(&self);
+}
+
+/// A connection to a node, msgs are sent over.
+#[async_trait]
+pub trait Connection: Debug + Send + Sync {
+    /// The id of the connection, unique among those to the same node.
+    fn id(&self) -> String;
+
+    /// The address of the node we are connected to.
+    fn remote_addr(&self) -> SocketAddr;
+
+    /// Sends a msg to the node, not expecting any response to it.
+    async fn send(&self, bytes: UsrMsgBytes) -> TransportResult<()>;
+
+    /// Sends a msg to the node on a new bi-directional stream, and awaits the response to it.
+    async fn send_bi(&self, bytes: UsrMsgBytes) -> TransportResult<UsrMsgBytes>;
+}
+
+/// The sending half of the bi-directional stream a msg was received on.
+#[async_trait]
+pub trait ResponseStream: Debug + Send + Sync {
+    /// The id of the stream, for logging.
+    fn id(&self) -> String;
+
+    /// Sets the priority the response is sent with, relative to other streams.
+    fn set_priority(&self, priority: i32);
+
+    /// Sends the response to the msg.
+    async fn send_user_msg(&mut self, bytes: UsrMsgBytes) -> TransportResult<()>;
+
+    /// Gracefully terminates the stream, once the response was sent.
+    async fn finish(&mut self) -> TransportResult<()>;
+}
+
+/// Stream to respond on to a msg received, whichever the transport it was received over.
+#[derive(Debug)]
+pub struct SendStream {
+    stream: Box<dyn ResponseStream>,
+    // The version of the messaging protocol to respond with, as negotiated with the
+    // sender of the msg, and the versions we advertise in the response.
+    versions: Option<(u16, ProtocolVersions)>,
+}
+
+impl SendStream {
+    /// Wraps the sending half of a transport's stream.
+    pub fn new(stream: impl ResponseStream + 'static) -> Self {
+        Self {
+            stream: Box::new(stream),
+            versions: None,
+        }
+    }
+
+    /// Sets the version of the messaging protocol the response is serialised with,
+    /// advertising the given supported versions.
+    pub(crate) fn respond_with(&mut self, version: u16, supported_versions: ProtocolVersions) {
+        self.versions = Some((version, supported_versions));
+    }
+
+    /// The id of the stream, for logging.
+    pub fn id(&self) -> String {
+        self.stream.id()
+    }
+
+    /// Sets the priority the response is sent with, relative to other streams.
+    pub fn set_priority(&self, priority: i32) {
+        self.stream.set_priority(priority)
+    }
+
+    /// Sends the response to the msg.
+    pub async fn send_user_msg(&mut self, bytes: UsrMsgBytes) -> TransportResult<()> {
+        let bytes = match self.versions {
+            Some((version, supported_versions)) => {
+                WireMsg::encode_for_version(bytes, version, supported_versions)?
+            }
+            None => bytes,
+        };
+        self.stream.send_user_msg(bytes).await
+    }
+
+    /// Gracefully terminates the stream, once the response was sent.
+    pub async fn finish(&mut self) -> TransportResult<()> {
+        self.stream.finish().await
+    }
+}
+
+/// A msg received by a transport.
+///
+/// It's decoded on the task receiving the msgs of the connection it came over, so that
+/// a msg slow to decode doesn't hold up the msgs received over the other connections.
+#[derive(custom_debug::Debug)]
+pub struct IncomingMsg {
+    /// The msg, its payload still compressed if it was sent so.
+    #[debug(skip)]
+    pub wire_msg: WireMsg,
+    /// The number of bytes the msg was received as.
+    pub len: usize,
+    /// The address of the node, or client, which sent the msg.
+    pub remote_addr: SocketAddr,
+    /// The id of the connection the msg was received over.
+    pub conn_id: String,
+    /// The stream to respond on, if the msg came on a bi-directional stream.
+    pub send_stream: Option<SendStream>,
+}
+
+impl IncomingMsg {
+    /// Decodes the bytes of a msg received, which are dropped
+    /// if over the max msg size, or not a valid msg.
+    pub(crate) fn decode(
+        bytes: UsrMsgBytes,
+        remote_addr: SocketAddr,
+        conn_id: String,
+        send_stream: Option<SendStream>,
+    ) -> Option<Self> {
+        let stream_info = if let Some(stream) = &send_stream {
+            format!(" on {}", stream.id())
+        } else {
+            "".to_string()
+        };
+        debug!("New msg arrived over conn_id={conn_id} from {remote_addr:?}{stream_info}");
+
+        let (header, dst, payload) = &bytes;
+        let len = header.len() + dst.len() + payload.len();
+        if len > MAX_MSG_SIZE {
+            debug!("Dropping msg received from {remote_addr:?}{stream_info}, of {len} bytes, over the max msg size");
+            return None;
+        }
+
+        let wire_msg = match WireMsg::from(bytes) {
+            Ok(wire_msg) => wire_msg,
+            Err(error) => {
+                // TODO: should perhaps rather drop this connection.. as it is a spam vector
+                debug!("Failed to deserialize message received from {remote_addr:?}{stream_info}: {error:?}");
+                return None;
+            }
+        };
+
+        Some(Self {
+            wire_msg,
+            len,
+            remote_addr,
+            conn_id,
+            send_stream,
+        })
+    }
+}
+
+/// Errors occurring while sending msgs over a transport.
+#[derive(Debug, Error)]
+pub enum TransportError {
+    /// Failed to connect to the node.
+    #[error("Failed to connect: {0:?}")]
+    Connection(#[from] qp2p::ConnectionError),
+    /// Failed to send the msg to the node.
+    #[error("Failed to send a message: {0:?}")]
+    Send(#[from] qp2p::SendError),
+    /// Failed to receive the response from the node.
+    #[error("Failed to receive a message: {0:?}")]
+    Recv(#[from] qp2p::RecvError),
+    /// The node can't be reached, it's gone or on the other side of a partition.
+    #[error("Node at {0} is unreachable")]
+    Unreachable(SocketAddr),
+    /// The stream was closed before the response was received.
+    #[error("Stream closed by {0} before the response was sent")]
+    StreamClosed(SocketAddr),
+    /// The msg couldn't be serialised with the version of the messaging protocol negotiated.
+    #[error("Failed to serialise the msg for the node: {0}")]
+    Serialisation(#[from] sn_interface::messaging::Error),
+}
+
+impl TransportError {
+    /// Whether the connection was closed by us.
+    pub(crate) fn is_local_close(&self) -> bool {
+        matches!(
+            self,
+            Self::Connection(qp2p::ConnectionError::Closed(qp2p::Close::Local))
+                | Self::Send(qp2p::SendError::ConnectionLost(
+                    qp2p::ConnectionError::Closed(qp2p::Close::Local)
+                ))
+        )
+    }
+}
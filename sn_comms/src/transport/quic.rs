@@ -0,0 +1,172 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Connection, IncomingMsg, ResponseStream, SendStream, Transport, TransportResult};
+use crate::{Result, STANDARD_CHANNEL_SIZE};
+
+use sn_interface::types::log_markers::LogMarker;
+
+use async_trait::async_trait;
+use qp2p::{ConnectionIncoming, Endpoint, IncomingConnections, UsrMsgBytes};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender},
+    task,
+};
+
+/// Transport over QUIC, which nodes communicate over.
+#[derive(Clone, Debug)]
+pub struct QuicTransport {
+    endpoint: Endpoint,
+}
+
+impl QuicTransport {
+    /// Creates an endpoint bound to the given address, and starts listening
+    /// to the incoming connections, handing over the msgs received on them.
+    pub fn new(local_addr: SocketAddr) -> Result<(Self, Receiver<IncomingMsg>)> {
+        let (endpoint, incoming_conns) = Endpoint::builder()
+            .addr(local_addr)
+            .idle_timeout(70_000)
+            .server()?;
+
+        let (incoming_msgs_sender, incoming_msgs) = mpsc::channel(STANDARD_CHANNEL_SIZE);
+        listen_for_connections(incoming_msgs_sender, incoming_conns);
+
+        Ok((Self { endpoint }, incoming_msgs))
+    }
+}
+
+#[async_trait]
+impl Transport for QuicTransport {
+    fn local_addr(&self) -> SocketAddr {
+        self.endpoint.local_addr()
+    }
+
+    async fn connect_to(&self, addr: &SocketAddr) -> TransportResult<Arc<dyn Connection>> {
+        let (conn, _) = self.endpoint.connect_to(addr).await?;
+        Ok(Arc::new(QuicConnection(conn)))
+    }
+
+    fn close(&self) {
+        self.endpoint.close()
+    }
+}
+
+#[derive(Debug)]
+struct QuicConnection(qp2p::Connection);
+
+#[async_trait]
+impl Connection for QuicConnection {
+    fn id(&self) -> String {
+        self.0.id()
+    }
+
+    fn remote_addr(&self) -> SocketAddr {
+        self.0.remote_address()
+    }
+
+    async fn send(&self, bytes: UsrMsgBytes) -> TransportResult<()> {
+        self.0.send_with(bytes, 0 /* priority */).await?;
+        Ok(())
+    }
+
+    async fn send_bi(&self, bytes: UsrMsgBytes) -> TransportResult<UsrMsgBytes> {
+        let (mut send_stream, recv_stream) = self.0.open_bi().await?;
+
+        let stream_id = send_stream.id();
+        trace!("bidi {stream_id} opened to {:?}", self.remote_addr());
+        send_stream.set_priority(10);
+        send_stream.send_user_msg(bytes).await?;
+
+        // unblock + move finish off thread as it's not strictly related to the sending of the msg.
+        let _handle = task::spawn(async move {
+            // Attempt to gracefully terminate the stream.
+            // If this errors it does _not_ mean our message has not been sent
+            let result = send_stream.finish().await;
+            trace!("Finished {stream_id}: {result:?}");
+        });
+
+        Ok(recv_stream.read().await?)
+    }
+}
+
+#[async_trait]
+impl ResponseStream for qp2p::SendStream {
+    fn id(&self) -> String {
+        qp2p::SendStream::id(self).to_string()
+    }
+
+    fn set_priority(&self, priority: i32) {
+        qp2p::SendStream::set_priority(self, priority)
+    }
+
+    async fn send_user_msg(&mut self, bytes: UsrMsgBytes) -> TransportResult<()> {
+        Ok(qp2p::SendStream::send_user_msg(self, bytes).await?)
+    }
+
+    async fn finish(&mut self) -> TransportResult<()> {
+        Ok(qp2p::SendStream::finish(self).await?)
+    }
+}
+
+#[tracing::instrument(skip_all)]
+fn listen_for_connections(
+    incoming_msgs_sender: Sender<IncomingMsg>,
+    mut incoming_connections: IncomingConnections,
+) {
+    let _handle = task::spawn(async move {
+        while let Some((connection, incoming_msgs)) = incoming_connections.next().await {
+            trace!(
+                "{}: from {:?} with connection_id {}",
+                LogMarker::IncomingConnection,
+                connection.remote_address(),
+                connection.id()
+            );
+
+            let _handle = task::spawn(listen_for_msgs(
+                incoming_msgs_sender.clone(),
+                connection,
+                incoming_msgs,
+            ));
+        }
+    });
+}
+
+#[tracing::instrument(skip_all)]
+async fn listen_for_msgs(
+    incoming_msgs_sender: Sender<IncomingMsg>,
+    conn: qp2p::Connection,
+    mut incoming_msgs: ConnectionIncoming,
+) {
+    let conn_id = conn.id();
+    let remote_address = conn.remote_address();
+
+    while let Some(result) = incoming_msgs.next_with_stream().await.transpose() {
+        match result {
+            Ok((msg_bytes, send_stream)) => {
+                let Some(msg) = IncomingMsg::decode(
+                    msg_bytes.0,
+                    remote_address,
+                    conn_id.clone(),
+                    send_stream.map(SendStream::new),
+                ) else {
+                    continue;
+                };
+                if let Err(error) = incoming_msgs_sender.send(msg).await {
+                    error!("Error pushing msg received over conn_id={conn_id} onto the incoming msgs channel: {error:?}");
+                    break;
+                }
+            }
+            Err(error) => {
+                warn!("Error on connection {conn_id} with {remote_address}: {error:?}");
+            }
+        }
+    }
+
+    trace!(%conn_id, %remote_address, "{}", LogMarker::ConnectionClosed);
+}
@@ -46,13 +46,20 @@ pub enum IssueType {
     RequestOperation,
 }
 
+/// The scores of the nodes tracked, for each type of issue.
 #[derive(Debug)]
 pub struct ScoreResults {
+    /// Scores for `IssueType::Communication`.
     pub communication_scores: BTreeMap<XorName, f32>,
+    /// Scores for `IssueType::Dkg`.
     pub dkg_scores: BTreeMap<XorName, f32>,
+    /// Scores for `IssueType::ElderVoting`.
     pub elder_voting_scores: BTreeMap<XorName, f32>,
+    /// Scores for `IssueType::NetworkKnowledge`.
     pub knowledge_scores: BTreeMap<XorName, f32>,
+    /// Scores for `IssueType::RequestOperation`.
     pub op_scores: BTreeMap<XorName, f32>,
+    /// Scores for `IssueType::AeProbeMsg`.
     pub probe_scores: BTreeMap<XorName, f32>,
 }
 
@@ -47,7 +47,7 @@ extern crate tracing;
 
 mod detection;
 
-pub use detection::IssueType;
+pub use detection::{IssueType, ScoreResults};
 
 use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
@@ -192,7 +192,7 @@ impl FaultDetection {
     }
 
     /// List all current tracked nodes, elders and non alike
-    fn all_current_nodes(&self) -> BTreeSet<XorName> {
+    pub fn all_current_nodes(&self) -> BTreeSet<XorName> {
         let mut all_nodes = BTreeSet::new();
 
         for node in &self.non_elder_nodes {
@@ -234,14 +234,27 @@ impl FaultDetection {
             .collect::<Vec<XorName>>();
 
         for node in &nodes_being_removed {
-            let _ = self.communication_issues.remove(node);
-            let _ = self.network_knowledge_issues.remove(node);
-            let _ = self.dkg_issues.remove(node);
-            let _ = self.elder_voting_issues.remove(node);
-            let _ = self.probe_issues.remove(node);
-            let _ = self.unfulfilled_ops.remove(node);
+            self.remove_issues_of(node);
         }
     }
+
+    /// Removes a node from the tracker, along with the issues tracked for it,
+    /// e.g. when it leaves voluntarily and shall thus not be deemed faulty.
+    pub fn remove_node(&mut self, node: &XorName) {
+        info!("Removing node:{node} from FaultDetection tracker");
+        let _ = self.non_elder_nodes.remove(node);
+        let _ = self.elders.remove(node);
+        self.remove_issues_of(node);
+    }
+
+    fn remove_issues_of(&mut self, node: &XorName) {
+        let _ = self.communication_issues.remove(node);
+        let _ = self.network_knowledge_issues.remove(node);
+        let _ = self.dkg_issues.remove(node);
+        let _ = self.elder_voting_issues.remove(node);
+        let _ = self.probe_issues.remove(node);
+        let _ = self.unfulfilled_ops.remove(node);
+    }
 }
 
 /// Calculates the avg value in a data set
@@ -433,4 +446,26 @@ mod tests {
         assert_eq!(current_nodes.len(), 11);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn remove_node_should_remove_the_node_and_its_issues() -> Result<()> {
+        let nodes = (0..10)
+            .map(|_| random_xorname())
+            .collect::<BTreeSet<XorName>>();
+        let nodes_vec = nodes.iter().cloned().collect::<Vec<XorName>>();
+        let mut fault_detection = FaultDetection::new(nodes, BTreeSet::new());
+
+        fault_detection.track_issue(nodes_vec[0], IssueType::Communication);
+        fault_detection.track_issue(nodes_vec[0], IssueType::RequestOperation);
+        fault_detection.track_issue(nodes_vec[1], IssueType::Communication);
+
+        fault_detection.remove_node(&nodes_vec[0]);
+
+        let current_nodes = fault_detection.all_current_nodes();
+        assert_eq!(current_nodes.len(), 9);
+        assert!(!current_nodes.contains(&nodes_vec[0]));
+        assert_eq!(fault_detection.communication_issues.len(), 1);
+        assert_eq!(fault_detection.unfulfilled_ops.len(), 0);
+        Ok(())
+    }
 }
@@ -42,6 +42,17 @@ impl DataCmd {
         }
     }
 
+    /// Returns true if storing the data of this cmd on the network has to be paid for,
+    /// i.e. if it has to be sent as a [`ClientMsg::PaidCmd`].
+    ///
+    /// [`ClientMsg::PaidCmd`]: super::ClientMsg::PaidCmd
+    pub fn requires_payment(&self) -> bool {
+        matches!(
+            self,
+            DataCmd::StoreChunk(_) | DataCmd::Register(RegisterCmd::Create { .. })
+        )
+    }
+
     /// Creates a Response containing an error, with the Response variant corresponding to the
     /// Request.
     pub fn to_error_response(&self, error: Error) -> CmdResponse {
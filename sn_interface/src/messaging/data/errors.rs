@@ -8,10 +8,11 @@
 
 use crate::types::{
     register::{EntryHash, User},
-    DataAddress,
+    DataAddress, SpentbookAddress,
 };
 use serde::{Deserialize, Serialize};
-use std::result;
+use sn_dbc::Token;
+use std::{result, time::Duration};
 use thiserror::Error;
 use xor_name::Prefix;
 
@@ -57,6 +58,31 @@ pub enum Error {
     /// the section that signed one of the input spent proofs.
     #[error("Spent proof is signed by section key {0:?} that is unknown to the current section")]
     SpentProofUnknownSectionKey(bls::PublicKey),
+    /// A DBC spend request was rejected because the DBC was already logged as spent
+    /// in a different transaction.
+    #[error("DBC of spentbook {0:?} was already spent in a different transaction")]
+    DoubleSpendAttempt(SpentbookAddress),
     #[error("Trying to produce a CmdResponse error for a data type not resulting from a cmd")]
     NoCorrespondingCmdError,
+    /// The data was not paid for, or the payment did not include a fee for the receiving Elder.
+    #[error("Storing data requires a payment, but none was found for this node")]
+    MissingStorePayment,
+    /// The amount paid for storing the data is lower than the store cost.
+    #[error("Too low amount paid for storing data: {paid}. Min required: {required}.")]
+    StorePaymentTooLow {
+        /// Amount paid
+        paid: Token,
+        /// Amount required
+        required: Token,
+    },
+    /// The proof of payment for storing the data is invalid.
+    #[error("Invalid store payment: {0}")]
+    InvalidStorePayment(String),
+    /// The client has sent too many msgs to the Elder, which dropped this one.
+    /// The client shall not send more msgs to it before the given time has elapsed.
+    #[error("Too many msgs sent by the client, it shall retry after {retry_after:?}")]
+    ClientRateLimited {
+        /// Time to wait before sending more msgs to the Elder.
+        retry_after: Duration,
+    },
 }
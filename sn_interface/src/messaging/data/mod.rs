@@ -19,14 +19,15 @@ pub use self::{
     errors::{Error, Result},
     query::DataQuery,
     register::{
-        CreateRegister, EditRegister, RegisterCmd, RegisterQuery, SignedRegisterCreate,
-        SignedRegisterEdit,
+        CreateRegister, EditRegister, EditRegisterPolicy, RegisterCmd, RegisterQuery,
+        SignedRegisterCreate, SignedRegisterEdit, SignedRegisterPolicyEdit,
+        REGISTER_SUBSCRIPTION_TTL,
     },
     spentbook::{SpendQuery, SpentbookCmd},
 };
 
 use crate::types::{
-    fees::RequiredFee,
+    fees::{PaymentProof, RequiredFee},
     register::{Entry, EntryHash, Permissions, Policy, Register, User},
     Chunk,
 };
@@ -54,6 +55,16 @@ pub enum ClientMsg {
     /// the eventually consistent nature of the network, it may be necessary to continually retry
     /// operations that depend on the effects of mutations.
     Cmd(DataCmd),
+    /// A cmd storing new data on the network, along with the proof of it having been paid for.
+    ///
+    /// Such cmds are handled first by the Elders, each verifying the fee paid to them
+    /// before forwarding the data to the data holders. See [`DataCmd::requires_payment`].
+    PaidCmd {
+        /// The cmd storing the data.
+        cmd: DataCmd,
+        /// The proof of payment for storing the data.
+        payment: PaymentProof,
+    },
     /// A read-only operation.
     ///
     /// Senders should eventually receive either a corresponding [`QueryResponse`] or an error in
@@ -62,10 +73,33 @@ pub enum ClientMsg {
     Query(DataQuery),
 }
 
+impl ClientMsg {
+    /// Returns true if the msg carries a chunk, which being encrypted isn't worth compressing.
+    pub fn carries_chunks(&self) -> bool {
+        matches!(
+            self,
+            Self::Cmd(DataCmd::StoreChunk(_))
+                | Self::PaidCmd {
+                    cmd: DataCmd::StoreChunk(_),
+                    ..
+                }
+        )
+    }
+
+    /// Returns true if the msg is a register query or cmd.
+    pub fn is_register(&self) -> bool {
+        matches!(
+            self,
+            Self::Cmd(DataCmd::Register(_)) | Self::Query(DataQuery::Register(_))
+        )
+    }
+}
+
 impl Display for ClientMsg {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Cmd(cmd) => write!(f, "ClientMsg::Cmd({cmd:?})"),
+            Self::PaidCmd { cmd, .. } => write!(f, "ClientMsg::PaidCmd({cmd:?})"),
             Self::Query(query) => write!(f, "ClientMsg::Query({query:?})"),
         }
     }
@@ -95,6 +129,27 @@ pub enum DataResponse {
         /// [`Cmd`]: self::ClientMsg::Cmd
         correlation_id: MsgId,
     },
+    /// Notification of the edits of a [`Register`] the client subscribed to, seen by the
+    /// Elders when forwarding them to the [`Register`] holders.
+    RegisterEdited {
+        /// The edits, signed by their authors, in the order they were seen.
+        edits: Vec<SignedRegisterEdit>,
+        /// ID of the causing [`RegisterQuery::Subscribe`] message.
+        correlation_id: MsgId,
+    },
+}
+
+impl DataResponse {
+    /// Returns true if the msg carries a chunk, which being encrypted isn't worth compressing.
+    pub fn carries_chunks(&self) -> bool {
+        matches!(
+            self,
+            Self::QueryResponse {
+                response: QueryResponse::GetChunk(Ok(_)),
+                ..
+            }
+        )
+    }
 }
 
 impl Display for DataResponse {
@@ -109,6 +164,9 @@ impl Display for DataResponse {
             Self::NetworkIssue(error) => {
                 write!(f, "DataResponse::NetworkIssue({error:?})")
             }
+            Self::RegisterEdited { edits, .. } => {
+                write!(f, "DataResponse::RegisterEdited({} edits)", edits.len())
+            }
         }
     }
 }
@@ -139,6 +197,10 @@ pub enum QueryResponse {
     GetRegisterPolicy(Result<Policy>),
     /// Response to [`RegisterQuery::GetUserPermissions`].
     GetRegisterUserPermissions(Result<Permissions>),
+    /// Response to [`RegisterQuery::Subscribe`] when the subscription ended without edits
+    /// to notify of, or with the error it was rejected with. Edits are notified
+    /// with [`DataResponse::RegisterEdited`] instead.
+    SubscribeToRegister(Result<()>),
     //
     // ===== Spentbook Data =====
     //
@@ -147,6 +209,9 @@ pub enum QueryResponse {
     //
     /// Response to [`SpendQuery::GetFees`].
     GetFees(Result<RequiredFee>),
+    //
+    /// Response to [`SpendQuery::GetStoreCost`].
+    GetStoreCost(Result<RequiredFee>),
 }
 
 impl QueryResponse {
@@ -161,8 +226,10 @@ impl QueryResponse {
             ReadRegister(r) => r.is_err(),
             GetRegisterPolicy(r) => r.is_err(),
             GetRegisterUserPermissions(r) => r.is_err(),
+            SubscribeToRegister(r) => r.is_err(),
             GetSpentProofShares(r) => r.is_err(),
             GetFees(r) => r.is_err(),
+            GetStoreCost(r) => r.is_err(),
         }
     }
 
@@ -202,6 +269,8 @@ pub enum CmdResponse {
     CreateRegister(Result<()>),
     /// Response to RegisterCmd::Edit.
     EditRegister(Result<()>),
+    /// Response to RegisterCmd::EditPolicy.
+    EditRegisterPolicy(Result<()>),
     //
     // ===== Spentbook Data =====
     //
@@ -220,9 +289,17 @@ impl CmdResponse {
             ReplicatedData::RegisterWrite(RegisterCmd::Edit { .. }) => {
                 CmdResponse::EditRegister(Ok(()))
             }
-            ReplicatedData::SpentbookWrite(_) => CmdResponse::SpendKey(Ok(())),
+            ReplicatedData::RegisterWrite(RegisterCmd::EditPolicy { .. }) => {
+                CmdResponse::EditRegisterPolicy(Ok(()))
+            }
+            ReplicatedData::SpentbookWrite(_) | ReplicatedData::SpentProofShare(_) => {
+                CmdResponse::SpendKey(Ok(()))
+            }
             ReplicatedData::RegisterLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `RegisterLog` is not resulting from a cmd.
-            ReplicatedData::SpentbookLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `SpentbookLog` is not resulting from a cmd.
+            ReplicatedData::SpentbookLog(_) | ReplicatedData::Spentbook(_) => {
+                // this should be unreachable, since a whole spentbook is not resulting from a cmd.
+                return Err(Error::NoCorrespondingCmdError);
+            }
         };
         Ok(res)
     }
@@ -237,9 +314,17 @@ impl CmdResponse {
             ReplicatedData::RegisterWrite(RegisterCmd::Edit { .. }) => {
                 CmdResponse::EditRegister(Err(err))
             }
-            ReplicatedData::SpentbookWrite(_) => CmdResponse::SpendKey(Err(err)),
+            ReplicatedData::RegisterWrite(RegisterCmd::EditPolicy { .. }) => {
+                CmdResponse::EditRegisterPolicy(Err(err))
+            }
+            ReplicatedData::SpentbookWrite(_) | ReplicatedData::SpentProofShare(_) => {
+                CmdResponse::SpendKey(Err(err))
+            }
             ReplicatedData::RegisterLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `RegisterLog` is not resulting from a cmd.
-            ReplicatedData::SpentbookLog(_) => return Err(Error::NoCorrespondingCmdError), // this should be unreachable, since `SpentbookLog` is not resulting from a cmd.
+            ReplicatedData::SpentbookLog(_) | ReplicatedData::Spentbook(_) => {
+                // this should be unreachable, since a whole spentbook is not resulting from a cmd.
+                return Err(Error::NoCorrespondingCmdError);
+            }
         };
         Ok(res)
     }
@@ -251,6 +336,7 @@ impl CmdResponse {
             StoreChunk(result)
             | CreateRegister(result)
             | EditRegister(result)
+            | EditRegisterPolicy(result)
             | SpendKey(result) => result,
         }
     }
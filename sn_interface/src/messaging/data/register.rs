@@ -12,13 +12,18 @@ use crate::messaging::{ClientAuth, SectionSig};
 #[allow(unused_imports)] // needed by rustdocs links
 use crate::types::register::Register;
 use crate::types::{
-    register::{Entry, EntryHash, Policy, RegisterOp, User},
+    register::{Entry, EntryHash, Policy, PolicyOp, RegisterOp, User},
     RegisterAddress,
 };
 
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use xor_name::XorName;
 
+/// Time a [`RegisterQuery::Subscribe`] lasts for, the client having to subscribe
+/// again within it, not to miss any edits of the [`Register`].
+pub const REGISTER_SUBSCRIPTION_TTL: Duration = Duration::from_secs(60);
+
 /// [`Register`] read operations.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Clone, Serialize, Deserialize, Debug)]
 pub enum RegisterQuery {
@@ -69,6 +74,21 @@ pub enum RegisterQuery {
     ///
     /// [`GetRegisterOwner`]: QueryResponse::GetRegisterOwner
     GetOwner(RegisterAddress),
+    /// Subscribe to the edits of the [`Register`] at the given address.
+    ///
+    /// This is handled by the Elders of the section holding the [`Register`], which answer with
+    /// a [`RegisterEdited`] notification once there are edits the client hasn't been notified of.
+    /// A new subscription is first checked by the holders of the [`Register`], as a read of it,
+    /// the Elders responding with their error, if any, in a [`SubscribeToRegister`] response.
+    /// The subscription lasts for [`REGISTER_SUBSCRIPTION_TTL`] after each of these queries,
+    /// the Elders keeping the edits made meanwhile, so the client is to subscribe again within
+    /// that time to be notified of all edits. Should the subscription end without any edits,
+    /// e.g. when the Elder is no longer responsible for the [`Register`], a [`SubscribeToRegister`]
+    /// response is sent instead.
+    ///
+    /// [`RegisterEdited`]: super::DataResponse::RegisterEdited
+    /// [`SubscribeToRegister`]: QueryResponse::SubscribeToRegister
+    Subscribe(RegisterAddress),
 }
 
 /// A [`Register`] cmd that is stored in a log on Adults.
@@ -85,6 +105,8 @@ pub enum RegisterCmd {
     },
     /// Edit the [`Register`].
     Edit(SignedRegisterEdit),
+    /// Edit the [`Policy`] of the [`Register`].
+    EditPolicy(SignedRegisterPolicyEdit),
 }
 
 impl RegisterCmd {
@@ -94,6 +116,7 @@ impl RegisterCmd {
         match self {
             Self::Create { .. } => CmdResponse::CreateRegister(Err(error)),
             Self::Edit(_) => CmdResponse::EditRegister(Err(error)),
+            Self::EditPolicy(_) => CmdResponse::EditRegisterPolicy(Err(error)),
         }
     }
 }
@@ -130,7 +153,32 @@ pub struct EditRegister {
     /// The address of the [`Register`] to edit.
     pub address: RegisterAddress,
     /// The operation to perform.
+    ///
+    /// It has to be permitted as per the version of the [`Policy`] it was made as per,
+    /// and, when first applied, as per the current one too, thus it's rejected once the
+    /// permissions of its writer were revoked, unless it was made before that.
     pub edit: RegisterOp<Entry>,
+    /// The version of the [`Policy`] the edit was made as per, which every replica checks
+    /// it against, whatever edits of the policy they applied since.
+    ///
+    /// It's not serialised when it's the initial version, so that such an edit is serialised,
+    /// and signed, as by the clients predating the edits of the policy.
+    #[serde(default, skip_serializing_if = "is_initial_policy_version")]
+    pub policy_version: u64,
+}
+
+fn is_initial_policy_version(version: &u64) -> bool {
+    *version == 0
+}
+
+/// A cmd to edit the [`Policy`] of a [`Register`], e.g. to grant or revoke permissions to a user,
+/// or to transfer its ownership. Only the owner as per the policy being edited can sign it.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EditRegisterPolicy {
+    /// The address of the [`Register`] whose [`Policy`] to edit.
+    pub address: RegisterAddress,
+    /// The operation to perform.
+    pub op: PolicyOp,
 }
 
 /// A signed cmd to create a [`Register`].
@@ -155,6 +203,17 @@ pub struct SignedRegisterEdit {
     pub auth: ClientAuth,
 }
 
+/// A [`Policy`] edit operation signed by the requester.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedRegisterPolicyEdit {
+    /// The operation to perform.
+    pub op: EditRegisterPolicy,
+    /// A signature carrying authority to perform the operation.
+    ///
+    /// This will be verified against the register's owner.
+    pub auth: ClientAuth,
+}
+
 impl SignedRegisterCreate {
     /// Returns the dst address of the register.
     pub fn dst_address(&self) -> RegisterAddress {
@@ -169,6 +228,13 @@ impl SignedRegisterEdit {
     }
 }
 
+impl SignedRegisterPolicyEdit {
+    /// Returns the dst address of the register.
+    pub fn dst_address(&self) -> RegisterAddress {
+        self.op.address
+    }
+}
+
 impl RegisterQuery {
     /// Creates a Response containing an error, with the Response variant corresponding to the
     /// Request variant.
@@ -182,6 +248,7 @@ impl RegisterQuery {
             }
             Self::GetEntry { .. } => QueryResponse::GetRegisterEntry(Err(error)),
             Self::GetOwner(_) => QueryResponse::GetRegisterOwner(Err(error)),
+            Self::Subscribe(_) => QueryResponse::SubscribeToRegister(Err(error)),
         }
     }
 
@@ -193,7 +260,8 @@ impl RegisterQuery {
             | Self::GetPolicy(ref address)
             | Self::GetUserPermissions { ref address, .. }
             | Self::GetEntry { ref address, .. }
-            | Self::GetOwner(ref address) => *address,
+            | Self::GetOwner(ref address)
+            | Self::Subscribe(ref address) => *address,
         }
     }
 
@@ -205,7 +273,8 @@ impl RegisterQuery {
             | Self::GetPolicy(ref address)
             | Self::GetUserPermissions { ref address, .. }
             | Self::GetEntry { ref address, .. }
-            | Self::GetOwner(ref address) => *address.name(),
+            | Self::GetOwner(ref address)
+            | Self::Subscribe(ref address) => *address.name(),
         }
     }
 }
@@ -222,6 +291,7 @@ impl RegisterCmd {
         match self {
             Self::Create { cmd, .. } => cmd.dst_address(),
             Self::Edit(cmd) => cmd.dst_address(),
+            Self::EditPolicy(cmd) => cmd.dst_address(),
         }
     }
 }
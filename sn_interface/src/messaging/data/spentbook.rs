@@ -25,6 +25,15 @@ pub enum SpendQuery {
     /// Query for the individual reward keys and their respective
     /// fee amount for processing a `Spend` of a Dbc with the given id.
     GetFees(PublicKey),
+    /// Query for the individual reward keys and their respective fee amount
+    /// for storing a data item with the given name in the section it belongs to.
+    /// The amount is encrypted to the id of the Dbc that will be spent to pay for it.
+    GetStoreCost {
+        /// Id of the Dbc to be spent for the payment.
+        dbc_id: PublicKey,
+        /// Name of the data to be stored.
+        data_name: XorName,
+    },
     /// Query for the set of spent proofs if the provided public key has already been spent with a Tx.
     GetSpentProofShares(SpentbookAddress),
 }
@@ -75,6 +84,7 @@ impl SpendQuery {
     pub fn to_error_response(&self, error: Error) -> QueryResponse {
         match self {
             Self::GetFees(_) => QueryResponse::GetFees(Err(error)),
+            Self::GetStoreCost { .. } => QueryResponse::GetStoreCost(Err(error)),
             Self::GetSpentProofShares(_) => QueryResponse::GetSpentProofShares(Err(error)),
         }
     }
@@ -85,6 +95,7 @@ impl SpendQuery {
             Self::GetFees(dbc_id) => {
                 SpentbookAddress::new(XorName::from_content(&dbc_id.to_bytes()))
             }
+            Self::GetStoreCost { data_name, .. } => SpentbookAddress::new(*data_name),
             Self::GetSpentProofShares(address) => *address,
         }
     }
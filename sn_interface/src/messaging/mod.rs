@@ -50,7 +50,7 @@ pub use self::{
     msg_id::{MsgId, MESSAGE_ID_LEN},
     msg_kind::MsgKind,
     network_msg::NetworkMsg,
-    serialisation::WireMsg,
+    serialisation::{ProtocolVersions, WireMsg, MAX_MSG_SIZE},
 };
 
 use serde::{Deserialize, Serialize};
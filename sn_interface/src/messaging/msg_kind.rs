@@ -30,9 +30,20 @@ pub enum MsgKind {
         /// such as a spend or a fee query. (FIX: This pattern is quite confusing.)
         is_spend: bool,
         query_index: Option<usize>,
+        /// This is `true` if the msg is a register query or cmd, for the Elders forwarding it
+        /// to tell those they keep track of without decoding the payload of every other msg.
+        #[serde(default)]
+        is_register: bool,
     },
     /// A message from a Node along with its name
-    Node { name: XorName, is_join: bool },
+    Node {
+        name: XorName,
+        is_join: bool,
+        /// This is `true` if the msg carries data, e.g. for it to be stored or replicated,
+        /// so that it can be scheduled after the rest without decoding its payload.
+        #[serde(default)]
+        is_data: bool,
+    },
     /// A data cmd/query response sent from a Node (along with its name).
     DataResponse(XorName),
 }
@@ -49,6 +60,13 @@ impl MsgKind {
             _ => false,
         }
     }
+    /// is a register query or cmd
+    pub fn is_register(&self) -> bool {
+        match self {
+            Self::Client { is_register, .. } => *is_register,
+            _ => false,
+        }
+    }
     /// return query index
     pub fn query_index(&self) -> &Option<usize> {
         match self {
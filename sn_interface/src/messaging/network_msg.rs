@@ -34,6 +34,18 @@ pub enum NetworkMsg {
     DataResponse(DataResponse),
 }
 
+impl NetworkMsg {
+    /// Returns true if the msg only carries chunks, which being encrypted aren't worth compressing.
+    pub fn carries_chunks(&self) -> bool {
+        match self {
+            Self::AntiEntropy(_) => false,
+            Self::Client { msg, .. } => msg.carries_chunks(),
+            Self::Node(msg) => msg.carries_chunks(),
+            Self::DataResponse(msg) => msg.carries_chunks(),
+        }
+    }
+}
+
 impl Display for NetworkMsg {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
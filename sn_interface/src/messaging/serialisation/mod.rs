@@ -9,4 +9,10 @@
 mod wire_msg;
 mod wire_msg_header;
 
-pub use self::wire_msg::WireMsg;
+pub use self::{
+    wire_msg::{WireMsg, MAX_MSG_SIZE},
+    wire_msg_header::{
+        Compression, ProtocolVersions, COMPRESSION_PROTO_VERSION, MESSAGING_PROTO_VERSION,
+        MIN_MESSAGING_PROTO_VERSION,
+    },
+};
@@ -6,7 +6,9 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::wire_msg_header::WireMsgHeader;
+use super::wire_msg_header::{
+    Compression, ProtocolVersions, WireMsgHeader, COMPRESSION_PROTO_VERSION,
+};
 
 use crate::messaging::{
     data::{ClientMsg, DataResponse},
@@ -14,9 +16,23 @@ use crate::messaging::{
     AntiEntropyMsg, AuthorityProof, Dst, Error, MsgId, MsgKind, NetworkMsg, Result,
 };
 
+use brotli::enc::BrotliEncoderParams;
 use bytes::{BufMut, Bytes, BytesMut};
 use custom_debug::Debug;
 use serde::Serialize;
+use std::io::Read;
+
+/// Payloads smaller than this aren't worth compressing.
+const COMPRESSION_THRESHOLD: usize = 2 * 1024;
+
+/// Brotli quality the payloads are compressed with, trading their size off
+/// for the time it takes to compress them.
+const COMPRESSION_QUALITY: i32 = 3;
+
+/// Max size of the msgs the transports carry, their header, dst and payload bytes.
+/// Payloads aren't decompressed past it either, so that a compressed msg can't take
+/// more of our memory than any msg sent as it is.
+pub const MAX_MSG_SIZE: usize = 32 * 1024 * 1024;
 
 /// In order to send a message over the wire, it needs to be serialized
 /// along with a header (`WireMsgHeader`) which contains the information needed
@@ -29,7 +45,7 @@ pub struct WireMsg {
     #[debug(skip)]
     /// Serialized Message header
     pub serialized_header: Option<Bytes>,
-    /// Serialised message
+    /// Serialised message, compressed as per the header until decompressed
     #[debug(skip)]
     pub payload: Bytes,
     /// The target dst
@@ -37,6 +53,11 @@ pub struct WireMsg {
     #[debug(skip)]
     /// Serialized Message dst
     pub serialized_dst: Option<Bytes>,
+    #[debug(skip)]
+    /// Serialized Message payload, compressed as per the serialized header
+    pub serialized_payload: Option<Bytes>,
+    /// Whether the payload is worth compressing
+    compressible: bool,
 }
 
 impl PartialEq for WireMsg {
@@ -85,11 +106,23 @@ impl WireMsg {
             payload,
             serialized_dst: None,
             serialized_header: None,
+            serialized_payload: None,
+            compressible: true,
         }
     }
 
+    /// Sets whether the payload is worth compressing, which it isn't if it carries
+    /// encrypted data, such as chunks.
+    pub fn set_compressible(&mut self, compressible: bool) {
+        self.compressible = compressible;
+    }
+
     /// Attempts to create an instance of `WireMsg` by deserialising the bytes provided.
     /// To succeed, the bytes should contain at least a valid `WireMsgHeader`.
+    ///
+    /// The payload is kept as received, compressed or not, it's only decompressed once the
+    /// msg is deserialised, or `decompress_payload` is called, so that the msgs we drop,
+    /// e.g. from unknown or throttled senders, don't cost us decompressing them.
     pub fn from(bytes: (Bytes, Bytes, Bytes)) -> Result<Self> {
         let (header_bytes, dst_bytes, payload) = bytes;
         // Deserialize the header bytes first
@@ -104,22 +137,28 @@ impl WireMsg {
         Ok(Self {
             header,
             dst,
-            payload,
+            payload: payload.clone(),
             serialized_dst: Some(dst_bytes),
             serialized_header: Some(header_bytes),
+            serialized_payload: Some(payload),
+            compressible: true,
         })
     }
 
+    /// Decompresses the payload, if it was received compressed, as it's signed and handled
+    /// that way, while the bytes received are kept for the msg to be sent on as it is.
+    pub fn decompress_payload(&mut self) -> Result<()> {
+        if let Some(compression) = self.header.compression() {
+            self.payload = decompress(compression, &self.payload)?;
+            self.header.set_compression(None);
+        }
+        Ok(())
+    }
+
     /// Return the serialized `WireMsgHeader`, the Dst and the Payload bytes contained
     /// on the WireMsg
     pub fn serialize(&self) -> Result<(Bytes, Bytes, Bytes)> {
-        let header = if let Some(bytes) = &self.serialized_header {
-            bytes.clone()
-        } else {
-            // First we create a buffer with the capacity
-            // needed to serialize the wire msg
-            self.header.serialize()?
-        };
+        let (header, payload) = self.serialize_header_and_payload()?;
 
         let dst = if let Some(bytes) = &self.serialized_dst {
             bytes.clone()
@@ -128,20 +167,16 @@ impl WireMsg {
         };
 
         // We can now return the buffer containing the written bytes
-        Ok((header, dst, self.payload.clone()))
+        Ok((header, dst, payload))
     }
 
     /// Return the serialized `WireMsgHeader`, the Dst and the Payload bytes
     /// Caching the bytes to the WireMsg itself
     pub fn serialize_and_cache_bytes(&mut self) -> Result<(Bytes, Bytes, Bytes)> {
-        // if we've already serialized, grab those header bytes
-        let header = if let Some(hdr_bytes) = &self.serialized_header {
-            hdr_bytes.clone()
-        } else {
-            let hdr_bytes = self.header.serialize()?;
-            self.serialized_header = Some(hdr_bytes.clone());
-            hdr_bytes
-        };
+        // if we've already serialized, grab those header and payload bytes
+        let (header, payload) = self.serialize_header_and_payload()?;
+        self.serialized_header = Some(header.clone());
+        self.serialized_payload = Some(payload.clone());
 
         let dst = if let Some(dst_bytes) = &self.serialized_dst {
             dst_bytes.clone()
@@ -151,50 +186,71 @@ impl WireMsg {
             dst_bytes
         };
 
-        Ok((header, dst, self.payload.clone()))
+        Ok((header, dst, payload))
     }
 
     /// Return the serialized `WireMsg`, which contains the `WireMsgHeader` bytes,
     /// followed by the provided dst and payload bytes, i.e. the serialized Message.
     pub fn serialize_with_new_dst(&self, dst: &Dst) -> Result<(Bytes, Bytes, Bytes)> {
-        // if we've already serialized, grab those header bytes
-        let header = if let Some(bytes) = &self.serialized_header {
-            bytes.clone()
-        } else {
-            self.header.serialize()?
-        };
+        // if we've already serialized, grab those header and payload bytes
+        let (header, payload) = self.serialize_header_and_payload()?;
 
         let dst = Self::serialize_dst_payload(dst)?;
 
-        Ok((header, dst, self.payload.clone()))
+        Ok((header, dst, payload))
+    }
+
+    // Return the serialized `WireMsgHeader` and payload, reusing the bytes already serialized
+    // if any, the payload being compressed if worth it, and the header describing it so.
+    fn serialize_header_and_payload(&self) -> Result<(Bytes, Bytes)> {
+        if let (Some(header), Some(payload)) = (&self.serialized_header, &self.serialized_payload) {
+            return Ok((header.clone(), payload.clone()));
+        }
+
+        if self.compressible && self.payload.len() >= COMPRESSION_THRESHOLD {
+            let compressed = compress(&self.payload)?;
+            // payloads which turn out incompressible are sent as they are
+            if compressed.len() < self.payload.len() {
+                let mut header = self.header.clone();
+                header.set_compression(Some(Compression::Brotli));
+                return Ok((header.serialize()?, compressed));
+            }
+        }
+
+        Ok((self.header.serialize()?, self.payload.clone()))
     }
 
     /// Deserialize the payload from this `WireMsg` returning a `NetworkMsg` instance.
+    /// The payload is decompressed first if it wasn't already.
     pub fn into_msg(&self) -> Result<NetworkMsg> {
+        let payload = match self.header.compression() {
+            Some(compression) => decompress(compression, &self.payload)?,
+            None => self.payload.clone(),
+        };
         match self.header.msg_envelope.kind.clone() {
             MsgKind::AntiEntropy(_) => {
-                let msg: AntiEntropyMsg = rmp_serde::from_slice(&self.payload).map_err(|err| {
+                let msg: AntiEntropyMsg = rmp_serde::from_slice(&payload).map_err(|err| {
                     Error::FailedToParse(format!("Ae message payload as Msgpack: {err}"))
                 })?;
                 Ok(NetworkMsg::AntiEntropy(msg))
             }
             MsgKind::Client { auth, .. } => {
-                let msg: ClientMsg = rmp_serde::from_slice(&self.payload).map_err(|err| {
+                let msg: ClientMsg = rmp_serde::from_slice(&payload).map_err(|err| {
                     Error::FailedToParse(format!("Data message payload as Msgpack: {err}"))
                 })?;
 
-                let auth = AuthorityProof::verify(auth, &self.payload)?;
+                let auth = AuthorityProof::verify(auth, &payload)?;
 
                 Ok(NetworkMsg::Client { auth, msg })
             }
             MsgKind::DataResponse(_) => {
-                let msg: DataResponse = rmp_serde::from_slice(&self.payload).map_err(|err| {
+                let msg: DataResponse = rmp_serde::from_slice(&payload).map_err(|err| {
                     Error::FailedToParse(format!("Data message payload as Msgpack: {err}"))
                 })?;
                 Ok(NetworkMsg::DataResponse(msg))
             }
             MsgKind::Node { .. } => {
-                let msg: NodeMsg = rmp_serde::from_slice(&self.payload).map_err(|err| {
+                let msg: NodeMsg = rmp_serde::from_slice(&payload).map_err(|err| {
                     Error::FailedToParse(format!("Node signed message payload as Msgpack: {err}"))
                 })?;
                 Ok(NetworkMsg::Node(msg))
@@ -217,6 +273,52 @@ impl WireMsg {
         &self.dst
     }
 
+    /// Return the version of the messaging protocol this msg was serialised with
+    pub fn version(&self) -> u16 {
+        self.header.version()
+    }
+
+    /// Return the versions of the messaging protocol supported by the sender of this msg
+    pub fn supported_versions(&self) -> ProtocolVersions {
+        self.header.supported_versions()
+    }
+
+    /// Serializes again the msg bytes provided with the given version of the messaging
+    /// protocol, advertising the given supported versions, for the msg to be sent to a peer
+    /// which may not support the version it was serialised with.
+    /// Payloads are compressed once, when the msg is serialised, thus they're only
+    /// decompressed here for the versions predating the compression.
+    /// The bytes are returned as they are if already serialised that way.
+    pub fn encode_for_version(
+        bytes: (Bytes, Bytes, Bytes),
+        version: u16,
+        supported_versions: ProtocolVersions,
+    ) -> Result<(Bytes, Bytes, Bytes)> {
+        let (header_bytes, dst, payload) = bytes;
+        let (serialised_version, serialised_supported_versions, compression) =
+            WireMsgHeader::read_serialisation(&header_bytes)?;
+        let decompression = compression.filter(|_| version < COMPRESSION_PROTO_VERSION);
+
+        if serialised_version == version
+            && serialised_supported_versions == supported_versions
+            && decompression.is_none()
+        {
+            return Ok((header_bytes, dst, payload));
+        }
+
+        let mut header = WireMsgHeader::from(header_bytes)?;
+        let payload = match decompression {
+            Some(compression) => {
+                header.set_compression(None);
+                decompress(compression, &payload)?
+            }
+            None => payload,
+        };
+
+        header.set_versions(version, supported_versions);
+        Ok((header.serialize()?, dst, payload))
+    }
+
     /// Convenience function which creates a temporary `WireMsg` from the provided
     /// bytes, returning the deserialized message.
     pub fn deserialize(bytes: (Bytes, Bytes, Bytes)) -> Result<(MsgId, NetworkMsg)> {
@@ -225,19 +327,52 @@ impl WireMsg {
     }
 }
 
+fn compress(payload: &[u8]) -> Result<Bytes> {
+    let params = BrotliEncoderParams {
+        quality: COMPRESSION_QUALITY,
+        ..Default::default()
+    };
+    let mut compressed = vec![];
+    let _size = brotli::BrotliCompress(&mut &payload[..], &mut compressed, &params)
+        .map_err(|err| Error::Serialisation(format!("could not compress the payload: {err}")))?;
+    Ok(Bytes::from(compressed))
+}
+
+fn decompress(compression: Compression, payload: &[u8]) -> Result<Bytes> {
+    match compression {
+        Compression::Brotli => {
+            let mut decompressed = vec![];
+            let _size = brotli::Decompressor::new(payload, 4096)
+                .take(MAX_MSG_SIZE as u64 + 1)
+                .read_to_end(&mut decompressed)
+                .map_err(|err| {
+                    Error::FailedToParse(format!("could not decompress the payload: {err}"))
+                })?;
+            if decompressed.len() > MAX_MSG_SIZE {
+                return Err(Error::FailedToParse(format!(
+                    "decompressed payload exceeds {MAX_MSG_SIZE} bytes"
+                )));
+            }
+            Ok(Bytes::from(decompressed))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::messaging::serialisation::{MESSAGING_PROTO_VERSION, MIN_MESSAGING_PROTO_VERSION};
     use crate::{
         messaging::{
-            data::{ClientMsg, DataQuery},
-            system::NodeMsg,
+            data::{ClientMsg, DataCmd, DataQuery, DataResponse, QueryResponse, RegisterCmd},
+            system::{NodeDataCmd, NodeMsg},
             AuthorityProof, ClientAuth, MsgId,
         },
-        types::{ChunkAddress, Keypair},
+        types::{register::User, Chunk, ChunkAddress, Keypair, RegisterAddress, ReplicatedData},
     };
     use bls::SecretKey;
-    use eyre::Result;
+    use eyre::{bail, Result};
+    use xor_name::XorName;
 
     #[test]
     fn serialisation_node_msg() -> Result<()> {
@@ -254,6 +389,7 @@ mod tests {
         let kind = MsgKind::Node {
             name: Default::default(),
             is_join: true,
+            is_data: false,
         };
         let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
         let serialized = wire_msg.serialize()?;
@@ -295,6 +431,7 @@ mod tests {
             auth,
             is_spend: false,
             query_index: None,
+            is_register: false,
         };
 
         let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
@@ -318,4 +455,384 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn previous_versions_are_decoded() -> Result<()> {
+        let wire_msg = new_node_msg()?;
+        let bytes = wire_msg.serialize()?;
+        assert_eq!(wire_msg.version(), MESSAGING_PROTO_VERSION);
+
+        for version in MIN_MESSAGING_PROTO_VERSION..=MESSAGING_PROTO_VERSION {
+            let bytes =
+                WireMsg::encode_for_version(bytes.clone(), version, ProtocolVersions::SUPPORTED)?;
+            let deserialized = WireMsg::from(bytes)?;
+
+            assert_eq!(deserialized, wire_msg);
+            assert_eq!(deserialized.version(), version);
+            assert_eq!(
+                deserialized.supported_versions(),
+                ProtocolVersions::SUPPORTED
+            );
+            assert_eq!(deserialized.into_msg()?, wire_msg.into_msg()?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn supported_versions_are_not_read_by_older_decoders() -> Result<()> {
+        let wire_msg = new_node_msg()?;
+        let bytes = wire_msg.serialize()?;
+        let (header, dst, payload) =
+            WireMsg::encode_for_version(bytes, 1, ProtocolVersions::SUPPORTED)?;
+
+        // older decoders only read the header up to its declared size,
+        // as if the supported versions weren't serialised after it
+        let header_len = usize::from(u16::from_be_bytes([header[0], header[1]]));
+        assert!(header_len < header.len());
+        let legacy_header = header.slice(..header_len);
+
+        let deserialized = WireMsg::from((legacy_header, dst, payload))?;
+        assert_eq!(deserialized, wire_msg);
+        assert_eq!(deserialized.version(), 1);
+        assert_eq!(deserialized.supported_versions(), ProtocolVersions::LEGACY);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() -> Result<()> {
+        let wire_msg = new_node_msg()?;
+        let bytes = wire_msg.serialize()?;
+
+        for version in [MIN_MESSAGING_PROTO_VERSION - 1, MESSAGING_PROTO_VERSION + 1] {
+            let bytes =
+                WireMsg::encode_for_version(bytes.clone(), version, ProtocolVersions::SUPPORTED)?;
+            assert!(matches!(
+                WireMsg::from(bytes),
+                Err(Error::UnsupportedVersion(v)) if v == version
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn the_latest_common_version_is_negotiated() {
+        let supported = ProtocolVersions { min: 2, max: 4 };
+
+        let older = ProtocolVersions { min: 1, max: 3 };
+        assert_eq!(supported.negotiate(&older), Some(3));
+        assert_eq!(older.negotiate(&supported), Some(3));
+
+        let newer = ProtocolVersions { min: 3, max: 5 };
+        assert_eq!(supported.negotiate(&newer), Some(4));
+
+        let legacy = ProtocolVersions::LEGACY;
+        assert_eq!(supported.negotiate(&legacy), None);
+    }
+
+    #[test]
+    fn large_payloads_are_compressed_for_the_versions_allowing_it() -> Result<()> {
+        let payload = Bytes::from([b"register edit".as_slice(); 1_000].concat());
+        let wire_msg = new_node_msg_with_payload(payload.clone());
+
+        let compressed = wire_msg.serialize()?;
+        let (header, _, compressed_payload) = &compressed;
+        assert_eq!(
+            WireMsgHeader::from(header.clone())?.compression(),
+            Some(Compression::Brotli)
+        );
+        assert!(compressed_payload.len() < payload.len() / 10);
+        assert_eq!(
+            WireMsg::encode_for_version(
+                compressed.clone(),
+                COMPRESSION_PROTO_VERSION,
+                ProtocolVersions::SUPPORTED,
+            )?,
+            compressed
+        );
+
+        // the payload is only decompressed once the msg is handled...
+        let mut deserialized = WireMsg::from(compressed.clone())?;
+        assert_eq!(&deserialized.payload, compressed_payload);
+        deserialized.decompress_payload()?;
+        assert_eq!(deserialized, wire_msg);
+        assert_eq!(deserialized.payload, payload);
+        // ...the msg being sent on as it was received...
+        assert_eq!(deserialized.serialize()?, compressed);
+
+        // ...and before being sent with a version predating the compression
+        let (header, dst, decompressed_payload) = WireMsg::encode_for_version(
+            compressed,
+            COMPRESSION_PROTO_VERSION - 1,
+            ProtocolVersions::SUPPORTED,
+        )?;
+        assert_eq!(WireMsgHeader::from(header.clone())?.compression(), None);
+        assert_eq!(decompressed_payload, payload);
+        assert_eq!(
+            WireMsg::from((header, dst, decompressed_payload))?,
+            wire_msg
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn payloads_are_not_decompressed_past_the_max_msg_size() -> Result<()> {
+        let payload = Bytes::from(vec![0; MAX_MSG_SIZE + 1]);
+        let (header, dst, compressed_payload) = new_node_msg_with_payload(payload).serialize()?;
+        assert!(compressed_payload.len() < MAX_MSG_SIZE / 1000);
+
+        let mut deserialized = WireMsg::from((header, dst, compressed_payload))?;
+        assert!(deserialized.decompress_payload().is_err());
+        assert!(deserialized.into_msg().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compressed_payloads_are_rejected_for_the_versions_predating_the_compression() -> Result<()> {
+        let payload = Bytes::from([b"register edit".as_slice(); 1_000].concat());
+        let mut header = new_node_msg_with_payload(payload).header;
+        header.set_compression(Some(Compression::Brotli));
+        header.set_versions(COMPRESSION_PROTO_VERSION - 1, ProtocolVersions::SUPPORTED);
+
+        assert!(WireMsgHeader::from(header.serialize()?).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn payloads_are_compressed_once_for_all_the_recipients() -> Result<()> {
+        let payload = Bytes::from([b"register edit".as_slice(); 1_000].concat());
+        let mut wire_msg = new_node_msg_with_payload(payload);
+        let (_, _, compressed_payload) = wire_msg.serialize_and_cache_bytes()?;
+
+        for _ in 0..3 {
+            let dst = Dst {
+                name: xor_name::rand::random(),
+                section_key: SecretKey::random().public_key(),
+            };
+            let bytes = wire_msg.serialize_with_new_dst(&dst)?;
+            let (_, _, payload) = WireMsg::encode_for_version(
+                bytes,
+                MESSAGING_PROTO_VERSION,
+                ProtocolVersions::SUPPORTED,
+            )?;
+            // the very bytes compressed when first serialised are sent to every recipient
+            assert_eq!(payload.as_ptr(), compressed_payload.as_ptr());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn small_incompressible_and_chunk_payloads_are_not_compressed() -> Result<()> {
+        let small_payload = Bytes::from(vec![0; COMPRESSION_THRESHOLD - 1]);
+        let incompressible_payload = Bytes::from(
+            (0..2 * COMPRESSION_THRESHOLD)
+                .map(|_| rand::random::<u8>())
+                .collect::<Vec<_>>(),
+        );
+        let chunk_msg = NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(ReplicatedData::Chunk(
+            Chunk::new(Bytes::from(vec![0; 2 * COMPRESSION_THRESHOLD])),
+        )));
+        assert!(chunk_msg.carries_chunks());
+        let chunk_payload = WireMsg::serialize_msg_payload(&chunk_msg)?;
+
+        for (payload, compressible) in [
+            (small_payload, true),
+            (incompressible_payload, true),
+            (chunk_payload, false),
+        ] {
+            let mut wire_msg = new_node_msg_with_payload(payload.clone());
+            wire_msg.set_compressible(compressible);
+            let (header, dst, payload) = WireMsg::encode_for_version(
+                wire_msg.serialize()?,
+                MESSAGING_PROTO_VERSION,
+                ProtocolVersions::SUPPORTED,
+            )?;
+            assert_eq!(WireMsgHeader::from(header.clone())?.compression(), None);
+            assert_eq!(payload, wire_msg.payload);
+            assert_eq!(WireMsg::from((header, dst, payload))?, wire_msg);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn msgs_encoded_by_nodes_and_clients_of_the_first_version_are_decoded() -> Result<()> {
+        let address = RegisterAddress {
+            name: XorName([3; 32]),
+            tag: 15000,
+        };
+
+        let wire_msg = baseline_msg(
+            BASELINE_CLIENT_MSG_HEADER,
+            BASELINE_CLIENT_MSG_DST,
+            BASELINE_CLIENT_MSG_PAYLOAD,
+        )?;
+        assert_eq!(wire_msg.version(), 1);
+        assert_eq!(wire_msg.dst().name, XorName([5; 32]));
+        let edit = match wire_msg.into_msg()? {
+            NetworkMsg::Client {
+                msg: ClientMsg::Cmd(DataCmd::Register(RegisterCmd::Edit(edit))),
+                ..
+            } => edit,
+            other => bail!("Unexpected msg: {other:?}"),
+        };
+        assert_eq!(edit.dst_address(), address);
+        assert_eq!(edit.op.policy_version, 0);
+        // an edit made as per the initial policy is signed as by the first version
+        assert_eq!(bincode::serialize(&edit)?, hex::decode(BASELINE_EDIT)?);
+
+        let wire_msg = baseline_msg(
+            BASELINE_NODE_MSG_HEADER,
+            BASELINE_NODE_MSG_DST,
+            BASELINE_NODE_MSG_PAYLOAD,
+        )?;
+        assert_eq!(wire_msg.version(), 1);
+        assert_eq!(
+            wire_msg.into_msg()?,
+            NetworkMsg::Node(NodeMsg::NodeDataCmd(NodeDataCmd::ReplicateDataBatch(vec![
+                ReplicatedData::RegisterWrite(RegisterCmd::Edit(edit.clone()))
+            ])))
+        );
+
+        let wire_msg = baseline_msg(
+            BASELINE_RESPONSE_MSG_HEADER,
+            BASELINE_RESPONSE_MSG_DST,
+            BASELINE_RESPONSE_MSG_PAYLOAD,
+        )?;
+        assert_eq!(wire_msg.version(), 1);
+        let NetworkMsg::DataResponse(response) = wire_msg.into_msg()? else {
+            bail!("Unexpected msg kind: {:?}", wire_msg.kind());
+        };
+        // a register whose policy wasn't edited is serialised as by the first version
+        assert_eq!(WireMsg::serialize_msg_payload(&response)?, wire_msg.payload);
+        match response {
+            DataResponse::QueryResponse {
+                response: QueryResponse::GetRegister(Ok(register)),
+                ..
+            } => {
+                assert_eq!(register.address(), &address);
+                assert_eq!(register.owner(), User::Key(edit.auth.public_key));
+                assert_eq!(register.policy_version(), 0);
+                let entries: Vec<_> = register
+                    .read()
+                    .into_iter()
+                    .map(|(_, entry)| entry)
+                    .collect();
+                assert_eq!(entries, vec![b"baseline entry".to_vec()]);
+            }
+            other => bail!("Unexpected response: {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    fn new_node_msg_with_payload(payload: Bytes) -> WireMsg {
+        let dst = Dst {
+            name: xor_name::rand::random(),
+            section_key: SecretKey::random().public_key(),
+        };
+        let kind = MsgKind::Node {
+            name: xor_name::rand::random(),
+            is_join: false,
+            is_data: false,
+        };
+        WireMsg::new_msg(MsgId::new(), payload, kind, dst)
+    }
+
+    fn new_node_msg() -> Result<WireMsg> {
+        let payload = WireMsg::serialize_msg_payload(&NodeMsg::HandoverAE(100))?;
+        Ok(new_node_msg_with_payload(payload))
+    }
+
+    fn baseline_msg(header: &str, dst: &str, payload: &str) -> Result<WireMsg> {
+        let bytes = (
+            Bytes::from(hex::decode(header)?),
+            Bytes::from(hex::decode(dst)?),
+            Bytes::from(hex::decode(payload)?),
+        );
+        Ok(WireMsg::from(bytes)?)
+    }
+
+    // A register edit, msgs carrying it, and the response to a query of the register, encoded
+    // by nodes and clients of the first version of the protocol. The owner of the register has
+    // the ed25519 secret key [7; 32], while the msgs are sent to the section of bls secret key
+    // [1; 32].
+    const BASELINE_EDIT: &str = concat!(
+        "0303030303030303030303030303030303030303030303030303030303030303983a00000000000003030303",
+        "03030303030303030303030303030303030303030303030303030303983a0000000000000000000000000000",
+        "0e00000000000000626173656c696e6520656e74727901000000000000002000000000000000ea4a6c63e29c",
+        "520abef5507b132ec5f9954776aebebe7b92421eea691446d22c00000000002000000000000000ea4a6c63e2",
+        "9c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c000000004000000000000000a3d0142b82",
+        "39bfd3fafddd19e967602a862ac47695fdb8cea1e7425293c045c8a5ca93f775e4e7181047c2b358d87971f9",
+        "1394ef7ec8e2c5bbdf6d442f511c0e",
+    );
+    const BASELINE_CLIENT_MSG_HEADER: &str = concat!(
+        "00f8000182a66d73675f6964dc0020cc81034dccb8cc9937cccf4e1bccc6544acc9fccddcccbccd2ccb4ccd7",
+        "5fcca078cce71354cc83ccbcccaaccd20dccb13cccc0a46b696e6481a6436c69656e7483a46175746882aa70",
+        "75626c69635f6b657981a745643235353139c420ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92",
+        "421eea691446d22ca97369676e617475726581a745643235353139c440a77c2387d4dcd3cd59541a64a73e9f",
+        "62848f320971874f2eafeb0f45b7a62aac7bc4bb5a5b4c0c4a92e5afc12112429139cd85381d3bfc4a833d9e",
+        "f2004b460da869735f7370656e64c2ab71756572795f696e646578c0",
+    );
+    const BASELINE_CLIENT_MSG_DST: &str = concat!(
+        "92dc00200505050505050505050505050505050505050505050505050505050505050505dc0030ccaa1a1c26",
+        "055a32cc9817cca575cc9dcc877a27cc95ccf949cc9bcc97ccd6056eccddcce0cceecca3cc9512ccf24ecc8b",
+        "ccc874ccb4471f0501127accbb1ecca0ccd9ccf6cc8accc1",
+    );
+    const BASELINE_CLIENT_MSG_PAYLOAD: &str = concat!(
+        "81a3436d6481a8526567697374657281a445646974929292dc00200303030303030303030303030303030303",
+        "030303030303030303030303030303cd3a989492dc0020030303030303030303030303030303030303030303",
+        "0303030303030303030303cd3a9892909e626173656c696e6520656e74727981a34b657981a7456432353531",
+        "39c420ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22cc09281a74564323535",
+        "3139c420ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c81a7456432353531",
+        "39c440a3d0142b8239bfd3fafddd19e967602a862ac47695fdb8cea1e7425293c045c8a5ca93f775e4e71810",
+        "47c2b358d87971f91394ef7ec8e2c5bbdf6d442f511c0e",
+    );
+    const BASELINE_NODE_MSG_HEADER: &str = concat!(
+        "007c000182a66d73675f6964dc0020cc9f6644ccf5cc85064fccbc6e3467cce94ecc94ccaf3122cce2460b2b",
+        "3dccfa786ecc84ccffcccbcc9dccb9cc89ccc5a46b696e6481a44e6f646582a46e616d65dc00200909090909",
+        "090909090909090909090909090909090909090909090909090909a769735f6a6f696ec2",
+    );
+    const BASELINE_NODE_MSG_DST: &str = concat!(
+        "92dc00200505050505050505050505050505050505050505050505050505050505050505dc0030ccaa1a1c26",
+        "055a32cc9817cca575cc9dcc877a27cc95ccf949cc9bcc97ccd6056eccddcce0cceecca3cc9512ccf24ecc8b",
+        "ccc874ccb4471f0501127accbb1ecca0ccd9ccf6cc8accc1",
+    );
+    const BASELINE_NODE_MSG_PAYLOAD: &str = concat!(
+        "81ab4e6f646544617461436d6481b25265706c69636174654461746142617463689181ad5265676973746572",
+        "577269746581a445646974929292dc0020030303030303030303030303030303030303030303030303030303",
+        "0303030303cd3a989492dc002003030303030303030303030303030303030303030303030303030303030303",
+        "03cd3a9892909e626173656c696e6520656e74727981a34b657981a745643235353139c420ea4a6c63e29c52",
+        "0abef5507b132ec5f9954776aebebe7b92421eea691446d22cc09281a745643235353139c420ea4a6c63e29c",
+        "520abef5507b132ec5f9954776aebebe7b92421eea691446d22c81a745643235353139c440a3d0142b8239bf",
+        "d3fafddd19e967602a862ac47695fdb8cea1e7425293c045c8a5ca93f775e4e7181047c2b358d87971f91394",
+        "ef7ec8e2c5bbdf6d442f511c0e",
+    );
+    const BASELINE_RESPONSE_MSG_HEADER: &str = concat!(
+        "0076000182a66d73675f6964dc0020ccc1cce7cc8862cc8fcc97ccbbccb4cca0cca31541ccb61eccc5160fcc",
+        "d3256466ccf35644cce81f35cccdcca5ccd75f28a46b696e6481ac44617461526573706f6e7365dc00200909",
+        "090909090909090909090909090909090909090909090909090909090909",
+    );
+    const BASELINE_RESPONSE_MSG_DST: &str = concat!(
+        "92dc00200505050505050505050505050505050505050505050505050505050505050505dc0030ccaa1a1c26",
+        "055a32cc9817cca575cc9dcc877a27cc95ccf949cc9bcc97ccd6056eccddcce0cceecca3cc9512ccf24ecc8b",
+        "ccc874ccb4471f0501127accbb1ecca0ccd9ccf6cc8accc1",
+    );
+    const BASELINE_RESPONSE_MSG_PAYLOAD: &str = concat!(
+        "81ad5175657279526573706f6e73659281ab476574526567697374657281a24f6b9381a34b657981a7456432",
+        "35353139c420ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c9292dc002003",
+        "03030303030303030303030303030303030303030303030303030303030303cd3a989391dc0020ccd9cc9732",
+        "ccf16a4dcca8ccfc7dcce635cca4ccf1cc99ccca04ccd768cca0ccfc306a69ccceccde6942cca6cce561ccc1",
+        "cc9e81dc0020ccd9cc9732ccf16a4dcca8ccfc7dcce635cca4ccf1cc99ccca04ccd768cca0ccfc306a69ccce",
+        "ccde6942cca6cce561ccc1cc9e92909e626173656c696e6520656e747279809281a34b657981a74564323535",
+        "3139c420ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c80dc0020ccaccc9b",
+        "ccdbcce86bcccd5bcca17ccc981d2ecc92ccbfccecccd4cc9a69ccc421cc90774566cccf67cc84cc91cc9415",
+        "cca54f",
+    );
 }
@@ -18,20 +18,94 @@ use std::mem::size_of;
 
 use custom_debug::Debug as CustomDebug;
 use std::io::Write;
-// Current version of the messaging protocol.
-// At this point this implementation supports only this version.
-const MESSAGING_PROTO_VERSION: u16 = 1u16;
+
+/// Current version of the messaging protocol, which msgs are serialised with
+/// unless the recipient only supports older versions.
+///
+/// Version 2 serialises the msg envelope without the names of its fields,
+/// version 3 compresses the payload of large msgs, as flagged in the header.
+pub const MESSAGING_PROTO_VERSION: u16 = 3u16;
+
+/// Oldest version of the messaging protocol still supported. Msgs serialised with the
+/// versions deprecated but not yet dropped are still decoded, so that nodes and clients
+/// can be upgraded one at a time.
+///
+/// The payload of msgs is serialised the same for all the versions. Its types are thus only
+/// to gain enum variants, or trailing fields which are defaulted when missing and not
+/// serialised when unset, for the payloads of older nodes and clients to still be decoded.
+pub const MIN_MESSAGING_PROTO_VERSION: u16 = 1u16;
+
+/// First version of the messaging protocol the payload of msgs can be compressed with.
+pub const COMPRESSION_PROTO_VERSION: u16 = 3u16;
+
+/// Range of messaging protocol versions a node, or a client, supports.
+///
+/// It's advertised in the header of the msgs it sends, for its peers to negotiate
+/// the version they send their msgs to it with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolVersions {
+    /// Oldest version supported.
+    pub min: u16,
+    /// Latest version supported.
+    pub max: u16,
+}
+
+impl ProtocolVersions {
+    /// The versions supported by this implementation.
+    pub const SUPPORTED: Self = Self {
+        min: MIN_MESSAGING_PROTO_VERSION,
+        max: MESSAGING_PROTO_VERSION,
+    };
+
+    /// The versions supported by peers not advertising them, which predate the negotiation.
+    pub const LEGACY: Self = Self { min: 1, max: 1 };
+
+    const SIZE: usize = size_of::<Self>();
+
+    /// Whether the given version is within the range.
+    pub fn contains(&self, version: u16) -> bool {
+        self.min <= version && version <= self.max
+    }
+
+    /// The latest version supported by both ranges, if any.
+    pub fn negotiate(&self, other: &Self) -> Option<u16> {
+        let version = self.max.min(other.max);
+        (version >= self.min.max(other.min)).then_some(version)
+    }
+}
+
+/// The algorithm the payload of a msg was compressed with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Compression {
+    /// Brotli, as the chunks' content is compressed with.
+    Brotli,
+}
 
 // Header to be serialisied at the front of the wire message.
 // This header contains the information needed to deserialize the payload.
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct WireMsgHeader {
     // We serialise a header size field, but we don't know it up front until we deserialise it.
     // header_size: u16,
     version: u16,
+    // The versions supported by the sender, serialised after the end of the header
+    // as declared by its size, where the decoders predating them don't look for them.
+    supported_versions: Option<ProtocolVersions>,
+    // The compression applied to the payload, serialised after the supported versions.
+    compression: Option<Compression>,
     pub msg_envelope: MsgEnvelope,
 }
 
+// The version, advertised versions and compression are serialisation details,
+// the same msg can be serialised with any version.
+impl PartialEq for WireMsgHeader {
+    fn eq(&self, other: &Self) -> bool {
+        self.msg_envelope == other.msg_envelope
+    }
+}
+
+impl Eq for WireMsgHeader {}
+
 // The message envelope contains the ID of the message, the authority
 // signing this message (if any), as well as destination information
 // This is all part of the message header, and it gets deserialized
@@ -77,21 +151,91 @@ impl WireMsgHeader {
     pub fn new(msg_id: MsgId, kind: MsgKind) -> Self {
         Self {
             version: MESSAGING_PROTO_VERSION,
+            supported_versions: Some(ProtocolVersions::SUPPORTED),
+            compression: None,
             msg_envelope: MsgEnvelope { msg_id, kind },
         }
     }
 
+    // The version of the messaging protocol the header was serialised with.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    // The versions supported by the sender of the msg.
+    pub fn supported_versions(&self) -> ProtocolVersions {
+        self.supported_versions.unwrap_or(ProtocolVersions::LEGACY)
+    }
+
+    // Sets the version the header is serialised with, and the versions advertised in it.
+    pub fn set_versions(&mut self, version: u16, supported_versions: ProtocolVersions) {
+        self.version = version;
+        self.supported_versions = Some(supported_versions);
+    }
+
+    // The compression applied to the payload of the msg, if any.
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    // Sets the compression applied to the payload of the msg.
+    pub fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
+    }
+
     // Parses the provided bytes to deserialize a WireMsgHeader,
     // returning the created WireMsgHeader, as well as the remaining bytes which
     // correspond to the message payload. The caller shall then take care of
     // deserializing the payload using the information provided in the `WireMsgHeader`.
     pub fn from(bytes: Bytes) -> Result<Self> {
+        let meta = Self::read_meta(&bytes)?;
+
+        // ...then, we read the message envelope bytes, the Msgpack decoder
+        // reads them with or without the names of the fields, as per any version
+        let msg_envelope_bytes = &bytes[HeaderMeta::SIZE..meta.header_len()];
+        let msg_envelope: MsgEnvelope =
+            rmp_serde::from_slice(msg_envelope_bytes).map_err(|err| {
+                Error::FailedToParse(format!(
+                    "source authority couldn't be deserialized from the header: {err}",
+                ))
+            })?;
+
+        let (supported_versions, compression) =
+            Self::read_extension(meta.version, &bytes[meta.header_len()..])?;
+
+        let header = Self {
+            version: meta.version,
+            supported_versions,
+            compression,
+            msg_envelope,
+        };
+
+        Ok(header)
+    }
+
+    // Reads the version, the versions supported by the sender and the compression
+    // of the header serialised in the provided bytes, without deserialising its msg envelope.
+    pub fn read_serialisation(
+        bytes: &[u8],
+    ) -> Result<(u16, ProtocolVersions, Option<Compression>)> {
+        let meta = Self::read_meta(bytes)?;
+        let (supported_versions, compression) =
+            Self::read_extension(meta.version, &bytes[meta.header_len()..])?;
+        Ok((
+            meta.version,
+            supported_versions.unwrap_or(ProtocolVersions::LEGACY),
+            compression,
+        ))
+    }
+
+    // Reads the leading metadata, checking the header bytes and version are valid.
+    fn read_meta(bytes: &[u8]) -> Result<HeaderMeta> {
         let bytes_len = bytes.len();
 
         // Parse the leading metadata
         let meta: HeaderMeta = BINCODE_OPTIONS
             .allow_trailing_bytes()
-            .deserialize(&bytes)
+            .deserialize(bytes)
             .map_err(|err| Error::FailedToParse(format!("invalid message header: {err}")))?;
 
         // We check that we have at least the claimed number of header bytes.
@@ -102,31 +246,51 @@ impl WireMsgHeader {
         }
 
         // Make sure we support this version
-        if meta.version != MESSAGING_PROTO_VERSION {
+        if !ProtocolVersions::SUPPORTED.contains(meta.version) {
             return Err(Error::UnsupportedVersion(meta.version));
         }
 
-        // ...finally, we read the message envelope bytes
-        let msg_envelope_bytes = &bytes[HeaderMeta::SIZE..meta.header_len()];
-        let msg_envelope: MsgEnvelope =
-            rmp_serde::from_slice(msg_envelope_bytes).map_err(|err| {
-                Error::FailedToParse(format!(
-                    "source authority couldn't be deserialized from the header: {err}",
-                ))
-            })?;
+        Ok(meta)
+    }
 
-        let header = Self {
-            version: meta.version,
-            msg_envelope,
+    // Reads the versions supported by the sender past the end of the header, which older
+    // senders don't advertise, followed by the compression applied to the payload, if any,
+    // which the versions predating the compression can't carry.
+    fn read_extension(
+        version: u16,
+        mut extension: &[u8],
+    ) -> Result<(Option<ProtocolVersions>, Option<Compression>)> {
+        let mut supported_versions = None;
+        if extension.len() >= ProtocolVersions::SIZE {
+            supported_versions = BINCODE_OPTIONS.deserialize_from(&mut extension).ok();
+        }
+        let compression = if supported_versions.is_some() && !extension.is_empty() {
+            BINCODE_OPTIONS
+                .deserialize_from(&mut extension)
+                .map_err(|err| {
+                    Error::FailedToParse(format!("invalid compression in the header: {err}"))
+                })?
+        } else {
+            None
         };
+        if compression.is_some() && version < COMPRESSION_PROTO_VERSION {
+            return Err(Error::FailedToParse(format!(
+                "compressed payload in a header of version {version}"
+            )));
+        }
 
-        Ok(header)
+        Ok((supported_versions, compression))
     }
 
     /// Write header metadata and msg envelope info into a provided buffer
     pub fn serialize(&self) -> Result<Bytes> {
         // first serialise the msg envelope so we can figure out the total header size
-        let msg_envelope_vec = rmp_serde::to_vec_named(&self.msg_envelope).map_err(|err| {
+        let msg_envelope_vec = if self.version == 1 {
+            rmp_serde::to_vec_named(&self.msg_envelope)
+        } else {
+            rmp_serde::to_vec(&self.msg_envelope)
+        }
+        .map_err(|err| {
             Error::Serialisation(format!(
                 "could not serialize message envelope with Msgpack: {err}",
             ))
@@ -152,6 +316,27 @@ impl WireMsgHeader {
             .write(&msg_envelope_vec)
             .map_err(|_| Error::Serialisation("ups".to_string()))?;
 
+        // Write the versions we support past the end of the header
+        if let Some(supported_versions) = &self.supported_versions {
+            BINCODE_OPTIONS
+                .serialize_into(&mut buffer_writer, supported_versions)
+                .map_err(|err| {
+                    Error::Serialisation(format!(
+                        "supported versions couldn't be serialized after the header: {err}",
+                    ))
+                })?;
+
+            if let Some(compression) = &self.compression {
+                BINCODE_OPTIONS
+                    .serialize_into(&mut buffer_writer, &Some(compression))
+                    .map_err(|err| {
+                        Error::Serialisation(format!(
+                            "compression couldn't be serialized after the header: {err}",
+                        ))
+                    })?;
+            }
+        }
+
         Ok(buffer_writer.into_inner().freeze())
     }
 }
@@ -13,7 +13,10 @@ mod section_sig;
 
 use crate::messaging::AuthorityProof;
 use crate::network_knowledge::node_state::RelocationTrigger;
-use crate::network_knowledge::{NodeState, RelocationProof, SapCandidate};
+use crate::network_knowledge::{
+    NodeState, RelocationProof, SapCandidate, SectionMergeAgreement, SectionTreeUpdate,
+};
+use crate::types::ReplicatedData;
 use crate::SectionAuthorityProvider;
 
 pub use dkg::DkgSessionId;
@@ -28,7 +31,7 @@ use bls::PublicKey as BlsPublicKey;
 use ed25519::Signature;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{Display, Formatter},
 };
 use xor_name::XorName;
@@ -119,6 +122,28 @@ pub enum NodeMsg {
         /// BLS signature share of an Elder over the sap1's pubkey
         sig_share1: SectionSigShare,
     },
+    /// Sent by the Elders of a section which shrank below the minimum section size to the Elders
+    /// of its sibling section, proposing to merge both sections into their parent prefix.
+    /// The sibling aggregates the sig shares to obtain the section's agreement to the merge.
+    ProposeSectionMerge {
+        /// The SAP of the proposing section, along with the proof chain to trust it
+        section_tree_update: SectionTreeUpdate,
+        /// The current members of the proposing section
+        members: BTreeSet<NodeState>,
+        /// BLS signature share of an Elder over the merged prefix, the section key and the members
+        sig_share: SectionSigShare,
+    },
+    /// After Handover consensus on a merge, the elders of the section carrying it out inform the
+    /// new elder candidates that they are promoted
+    /// The candidates can aggregate the sig_share to obtain SectionSigned proof that they are promoted
+    SectionMergePromotion {
+        /// The promoted SAP of the merged section (signed by themselves)
+        sap: SectionSigned<SectionAuthorityProvider>,
+        /// BLS signature share of an Elder over the sap's pubkey
+        sig_share: SectionSigShare,
+        /// The agreement to the merge of the sibling section which didn't carry it out
+        merge_agreement: SectionSigned<SectionMergeAgreement>,
+    },
     /// Elders propose voting nodes off
     /// Once fully aggregated in the SectionStateVote aggregator, the proposal is accepted
     ProposeNodeOff {
@@ -137,6 +162,22 @@ impl NodeMsg {
         // we could also differentiate, say if it's a relocation
         matches!(self, NodeMsg::TryJoin(_))
     }
+
+    /// Returns true if the msg carries data, i.e. it's a data cmd sent among nodes.
+    pub fn is_data(&self) -> bool {
+        matches!(self, NodeMsg::NodeDataCmd(_))
+    }
+
+    /// Returns true if the msg only carries chunks, which being encrypted aren't worth compressing.
+    pub fn carries_chunks(&self) -> bool {
+        match self {
+            NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(ReplicatedData::Chunk(_))) => true,
+            NodeMsg::NodeDataCmd(NodeDataCmd::ReplicateDataBatch(batch)) => batch
+                .iter()
+                .all(|data| matches!(data, ReplicatedData::Chunk(_))),
+            _ => false,
+        }
+    }
 }
 
 impl Display for NodeMsg {
@@ -158,6 +199,8 @@ impl Display for NodeMsg {
             Self::HandoverAE { .. } => write!(f, "NodeMsg::HandoverAE"),
             Self::SectionHandoverPromotion { .. } => write!(f, "NodeMsg::SectionHandoverPromotion"),
             Self::SectionSplitPromotion { .. } => write!(f, "NodeMsg::SectionSplitPromotion"),
+            Self::ProposeSectionMerge { .. } => write!(f, "NodeMsg::ProposeSectionMerge"),
+            Self::SectionMergePromotion { .. } => write!(f, "NodeMsg::SectionMergePromotion"),
             Self::ProposeNodeOff { .. } => write!(f, "NodeMsg::ProposeSectionState"),
             Self::NodeEvent { .. } => write!(f, "NodeMsg::NodeEvent"),
             Self::NodeDataCmd { .. } => write!(f, "NodeMsg::NodeCmd"),
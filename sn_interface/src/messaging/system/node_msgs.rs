@@ -6,8 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::SectionSigShare;
 use crate::messaging::data::QueryResponse;
-use crate::types::{DataAddress, PublicKey, ReplicatedData};
+use crate::types::{
+    register::Register, DataAddress, PublicKey, ReplicatedData, ReplicatedRegisterLog,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +23,17 @@ pub enum NodeDataCmd {
     ReplicateDataBatch(Vec<ReplicatedData>),
     /// Tells a Node to fetch and replicate data from the sender.
     SendAnyMissingRelevantData(Vec<DataAddress>),
+    /// Asks the Elders to sign a snapshot of a Register, built from the log held by the sender,
+    /// so that the cmds it's built from no longer have to be stored and replicated.
+    SignRegisterSnapshot(ReplicatedRegisterLog),
+    /// Sent by an Elder in response to `SignRegisterSnapshot`. The sender aggregates the shares
+    /// of the Elders to obtain the snapshot signed by the section.
+    RegisterSnapshotShare {
+        /// The snapshot of the Register built by the Elder
+        snapshot: Register,
+        /// BLS signature share of the Elder over the snapshot
+        sig_share: SectionSigShare,
+    },
 }
 
 /// Event message sent among nodes
@@ -34,6 +48,13 @@ pub enum NodeEvent {
         /// Whether store failed due to full
         full: bool,
     },
+    /// Sent by a Node which is leaving the section voluntarily, once the data it handed off was
+    /// acknowledged by its new holders, or the hand-off timed out, so that the Elders vote it off
+    /// without deeming it faulty
+    Leaving {
+        /// Id of the Node which is leaving
+        node_id: PublicKey,
+    },
 }
 
 /// Responses to queries sent from Elders to Adults.
@@ -26,7 +26,7 @@ pub use self::{
     node_state::{MembershipState, NodeState, RelocationInfo, RelocationProof, RelocationState},
     section_authority_provider::{SapCandidate, SectionAuthUtils, SectionAuthorityProvider},
     section_keys::{SectionKeyShare, SectionKeysProvider},
-    section_tree::{SectionTree, SectionTreeUpdate},
+    section_tree::{SectionMergeAgreement, SectionTree, SectionTreeUpdate},
     sections_dag::SectionsDAG,
 };
 
@@ -91,6 +91,12 @@ pub fn recommended_section_size() -> usize {
     2 * elder_count()
 }
 
+/// Minimum section size.
+/// A section with fewer members than this merges with its sibling section into their parent prefix.
+pub fn min_section_size() -> usize {
+    elder_count()
+}
+
 /// `SuperMajority` of a given group (i.e. > 2/3)
 #[inline]
 pub const fn supermajority(group_size: usize) -> usize {
@@ -78,6 +78,7 @@ pub enum SapCandidate {
         SectionSigned<SectionAuthorityProvider>,
         SectionSigned<SectionAuthorityProvider>,
     ),
+    SectionMerge(SectionSigned<SectionAuthorityProvider>),
 }
 
 impl SapCandidate {
@@ -87,6 +88,7 @@ impl SapCandidate {
             SapCandidate::SectionSplit(sap1, sap2) => {
                 [sap1.elders_vec(), sap2.elders_vec()].concat().to_vec()
             }
+            SapCandidate::SectionMerge(sap) => sap.elders_vec(),
         }
     }
 
@@ -96,6 +98,7 @@ impl SapCandidate {
             SapCandidate::SectionSplit(sap1, sap2) => {
                 BTreeSet::from_iter([sap1.public_key_set(), sap2.public_key_set()])
             }
+            SapCandidate::SectionMerge(sap) => BTreeSet::from_iter([sap.public_key_set()]),
         }
     }
 }
@@ -21,14 +21,14 @@ use self::stats::NetworkStats;
 
 use crate::messaging::system::SectionSigned;
 use crate::network_knowledge::{
-    Error, Result, SectionAuthUtils, SectionAuthorityProvider, SectionsDAG,
+    Error, NodeState, Result, SectionAuthUtils, SectionAuthorityProvider, SectionsDAG,
 };
 
 use bls::PublicKey as BlsPublicKey;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     io::Write,
     iter::{self, Iterator},
     path::Path,
@@ -44,6 +44,15 @@ pub struct SectionTree {
     sections: BTreeMap<Prefix, SectionSigned<SectionAuthorityProvider>>,
     /// A DAG containing all section chains of the whole network that we are aware of
     sections_dag: SectionsDAG,
+    /// Keys of the sections which merged into their parent prefix, without signing the key of
+    /// the merged section. As a key only has one parent in the `sections_dag`, they are left
+    /// as leaves of it, while the SAPs of the sections are not kept anymore.
+    #[serde(default)]
+    merged_keys: BTreeSet<BlsPublicKey>,
+    /// Agreements to merge of the sections whose key didn't sign the key of the section they
+    /// merged into, by the prefix of the merged section, to be sent along with its SAP.
+    #[serde(default)]
+    merge_agreements: BTreeMap<Prefix, SectionSigned<SectionMergeAgreement>>,
 }
 
 impl SectionTree {
@@ -57,6 +66,8 @@ impl SectionTree {
         Ok(Self {
             sections: BTreeMap::from([(Prefix::default(), genesis_sap)]),
             sections_dag: SectionsDAG::new(genesis_pk),
+            merged_keys: BTreeSet::new(),
+            merge_agreements: BTreeMap::new(),
         })
     }
 
@@ -204,6 +215,7 @@ impl SectionTree {
         Ok(SectionTreeUpdate {
             signed_sap,
             proof_chain,
+            merge_agreement: self.merge_agreements.get(prefix).cloned(),
         })
     }
 
@@ -215,6 +227,7 @@ impl SectionTree {
     ) -> Result<bool> {
         let signed_sap = section_tree_update.signed_sap;
         let proof_chain = section_tree_update.proof_chain;
+        let merge_agreement = section_tree_update.merge_agreement;
 
         if self.sections_dag.has_key(&signed_sap.value.section_key()) {
             warn!(
@@ -283,10 +296,50 @@ impl SectionTree {
             )));
         }
 
+        // A SAP for the parent prefix of two known sibling sections is the outcome of their merge
+        // only if its key was signed off by one of them, otherwise it's an outdated SAP.
+        let siblings = [incoming_prefix.pushed(false), incoming_prefix.pushed(true)];
+        let signer = siblings.iter().find(|prefix| {
+            self.sections
+                .get(prefix)
+                .map_or(false, |sap| proof_chain.has_key(&sap.section_key()))
+        });
+        let is_merge = signer.is_some();
+        if let Some(signer) = signer {
+            if let Some(extension) = self.sections.keys().find(|prefix| {
+                prefix.is_extension_of(incoming_prefix) && !siblings.contains(prefix)
+            }) {
+                warn!("Dropping merge into {incoming_prefix:?}, as we know {extension:?} which split further");
+                return Ok(false);
+            }
+            // the other sibling didn't sign the merged key, so it must have agreed to the merge
+            if !self.verify_merge_agreement(&signer.sibling(), merge_agreement.as_ref()) {
+                warn!(
+                    "Dropping merge into {incoming_prefix:?}, as {:?} didn't agree to it",
+                    signer.sibling()
+                );
+                return Ok(false);
+            }
+            info!("Merging sections {siblings:?} into {incoming_prefix:?}");
+            for prefix in &siblings {
+                if let Some(sap) = self.sections.remove(prefix) {
+                    if !proof_chain.has_key(&sap.section_key()) {
+                        let _ = self.merged_keys.insert(sap.section_key());
+                    }
+                }
+                let _ = self.merge_agreements.remove(prefix);
+            }
+        }
+
         // We can now update our knowledge of the remote section's SAP.
         // Note: we don't expect the same SAP to be found in our records
         // for the prefix since we've already checked that above.
         if self.insert(signed_sap) {
+            if let Some(merge_agreement) = merge_agreement.filter(|_| is_merge) {
+                let _ = self
+                    .merge_agreements
+                    .insert(*incoming_prefix, merge_agreement);
+            }
             // update our sections_dag with the proof chain. Cannot be an error, since in cases
             // where we have outdated SAP (aware of prefix)/ not aware of the prefix, we have the
             // proof chain's genesis key in our sections_dag.
@@ -302,12 +355,48 @@ impl SectionTree {
         }
     }
 
+    // Checks the agreement to merge was signed by the section at the given prefix,
+    // with its current key or one it had before
+    fn verify_merge_agreement(
+        &self,
+        prefix: &Prefix,
+        merge_agreement: Option<&SectionSigned<SectionMergeAgreement>>,
+    ) -> bool {
+        let Some(merge_agreement) = merge_agreement else {
+            return false;
+        };
+        let Some(sap) = self.sections.get(prefix) else {
+            return false;
+        };
+        let agreement_key = merge_agreement.sig.public_key;
+        let is_key_of_section = self
+            .sections_dag
+            .partial_dag(self.sections_dag.genesis_key(), &sap.section_key())
+            .map_or(false, |chain| chain.has_key(&agreement_key));
+
+        is_key_of_section
+            && merge_agreement.section_key == agreement_key
+            && merge_agreement.merged_prefix == prefix.popped()
+            && merge_agreement.self_verify()
+    }
+
     /// For testing purpose, we may need to populate a `section_tree` without a proof chain.
     #[cfg(any(test, feature = "test-utils"))]
     pub fn insert_without_chain(&mut self, sap: SectionSigned<SectionAuthorityProvider>) -> bool {
         self.insert(sap)
     }
 
+    /// Keys of the latest SAPs we know of, i.e. the leaves of the `sections_dag`
+    /// other than the keys of the sections which merged.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn leaf_keys(&self) -> BTreeSet<BlsPublicKey> {
+        self.sections_dag
+            .leaf_keys()
+            .difference(&self.merged_keys)
+            .copied()
+            .collect()
+    }
+
     /// Serialise and write it to disk on the provided file path
     pub async fn write_to_disk(&self, path: &Path) -> Result<()> {
         trace!("Writing section tree to disk at {}", path.display());
@@ -389,6 +478,7 @@ impl SectionTree {
     fn prune(&mut self, mut prefix: Prefix) {
         loop {
             let _prev = self.sections.remove(&prefix);
+            let _prev = self.merge_agreements.remove(&prefix);
 
             if prefix.is_empty() {
                 break;
@@ -452,6 +542,11 @@ impl Eq for SectionTree {}
 pub struct SectionTreeUpdate {
     pub signed_sap: SectionSigned<SectionAuthorityProvider>,
     pub proof_chain: SectionsDAG,
+    /// If the section is the outcome of a merge, the agreement to it of the merged section
+    /// whose key is not in the proof chain. Not serialised when there is none, so that the
+    /// update can still be read by older nodes and clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_agreement: Option<SectionSigned<SectionMergeAgreement>>,
 }
 
 impl SectionTreeUpdate {
@@ -462,10 +557,37 @@ impl SectionTreeUpdate {
         Self {
             signed_sap,
             proof_chain,
+            merge_agreement: None,
+        }
+    }
+
+    /// The update with the SAP of a section which is the outcome of a merge,
+    /// along with the agreement to it of the merged section which didn't sign its key.
+    pub fn new_merge(
+        signed_sap: SectionSigned<SectionAuthorityProvider>,
+        proof_chain: SectionsDAG,
+        merge_agreement: SectionSigned<SectionMergeAgreement>,
+    ) -> Self {
+        Self {
+            signed_sap,
+            proof_chain,
+            merge_agreement: Some(merge_agreement),
         }
     }
 }
 
+/// The agreement of a section which shrank below the minimum section size
+/// to merge with its sibling section into their parent prefix.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SectionMergeAgreement {
+    /// The parent prefix both sections merge into
+    pub merged_prefix: Prefix,
+    /// The key of the section agreeing to the merge
+    pub section_key: BlsPublicKey,
+    /// The members of the section agreeing to the merge
+    pub members: BTreeSet<NodeState>,
+}
+
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils {
 
@@ -519,7 +641,11 @@ mod tests {
         test_utils::{assert_lists, prefix, TestKeys, TestSapBuilder, TestSectionTree},
     };
     use eyre::Result;
-    use proptest::{prelude::ProptestConfig, prop_assert_eq, proptest};
+    use proptest::{
+        collection::vec,
+        prelude::{any, prop_oneof, ProptestConfig, Strategy},
+        prop_assert_eq, proptest,
+    };
 
     #[test]
     fn insert_existing_prefix() -> Result<()> {
@@ -846,6 +972,176 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn merged_sap_replaces_the_merged_sections() -> Result<()> {
+        let (mut tree, genesis_sk) = TestSectionTree::random_tree()?;
+
+        let (sap0, sk0) = random_signed_sap(prefix("0"));
+        let tree_update =
+            TestSectionTree::get_section_tree_update(&sap0, tree.get_sections_dag(), &genesis_sk)?;
+        assert!(tree.update_the_section_tree(tree_update)?);
+        let (sap1, sk1) = random_signed_sap(prefix("1"));
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &genesis_sk.public_key())?;
+        let tree_update =
+            TestSectionTree::get_section_tree_update(&sap1, &proof_chain, &genesis_sk)?;
+        assert!(tree.update_the_section_tree(tree_update)?);
+
+        // sections '0' and '1' merge back, the merged key being signed by section '0'
+        // while section '1' signed its agreement to the merge
+        let (merged_sap, _) = random_signed_sap(Prefix::default());
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &sap0.section_key())?;
+        let mut tree_update =
+            TestSectionTree::get_section_tree_update(&merged_sap, &proof_chain, &sk0)?;
+        tree_update.merge_agreement = Some(merge_agreement(Prefix::default(), &sk1)?);
+        assert!(tree.update_the_section_tree(tree_update)?);
+
+        assert_eq!(tree.get(&Prefix::default()), Some(merged_sap.value.clone()));
+        assert_eq!(tree.get(&prefix("0")), None);
+        assert_eq!(tree.get(&prefix("1")), None);
+        assert!(tree.get_sections_dag().has_key(&merged_sap.section_key()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_only_replaces_the_merged_siblings() -> Result<()> {
+        let (mut tree, genesis_sk) = TestSectionTree::random_tree()?;
+
+        let mut section_sks = BTreeMap::new();
+        for p in ["1", "00", "01"] {
+            let (sap, sk) = random_signed_sap(prefix(p));
+            let proof_chain = tree
+                .get_sections_dag()
+                .partial_dag(tree.genesis_key(), &genesis_sk.public_key())?;
+            let tree_update =
+                TestSectionTree::get_section_tree_update(&sap, &proof_chain, &genesis_sk)?;
+            assert!(tree.update_the_section_tree(tree_update)?);
+            let _ = section_sks.insert(p, (sap, sk));
+        }
+
+        // sections '00' and '01' merge, the merged key being signed by section '01'
+        let (sap01, sk01) = &section_sks["01"];
+        let (merged_sap, _) = random_signed_sap(prefix("0"));
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &sap01.section_key())?;
+        let mut tree_update =
+            TestSectionTree::get_section_tree_update(&merged_sap, &proof_chain, sk01)?;
+        tree_update.merge_agreement = Some(merge_agreement(prefix("0"), &section_sks["00"].1)?);
+        assert!(tree.update_the_section_tree(tree_update)?);
+
+        assert_eq!(tree.get(&prefix("0")), Some(merged_sap.value));
+        assert_eq!(tree.get(&prefix("00")), None);
+        assert_eq!(tree.get(&prefix("01")), None);
+        assert_eq!(
+            tree.get(&prefix("1")),
+            Some(section_sks["1"].0.value.clone())
+        );
+        assert_lists(
+            tree.sections.values().map(|sap| sap.section_key()),
+            tree.leaf_keys(),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_of_sections_whose_sibling_split_further_results_in_no_update() -> Result<()> {
+        let (mut tree, genesis_sk) = TestSectionTree::random_tree()?;
+
+        let mut section_sks = BTreeMap::new();
+        for p in ["0", "10", "11"] {
+            let (sap, sk) = random_signed_sap(prefix(p));
+            let proof_chain = tree
+                .get_sections_dag()
+                .partial_dag(tree.genesis_key(), &genesis_sk.public_key())?;
+            let tree_update =
+                TestSectionTree::get_section_tree_update(&sap, &proof_chain, &genesis_sk)?;
+            assert!(tree.update_the_section_tree(tree_update)?);
+            let _ = section_sks.insert(p, (sap, sk));
+        }
+
+        // section '0' can't merge with '1', which we know has split
+        let (sap0, sk0) = &section_sks["0"];
+        let (merged_sap, _) = random_signed_sap(Prefix::default());
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &sap0.section_key())?;
+        let tree_update = TestSectionTree::get_section_tree_update(&merged_sap, &proof_chain, sk0)?;
+        assert!(!tree.update_the_section_tree(tree_update)?);
+
+        assert_eq!(tree.get(&Prefix::default()), None);
+        for p in ["0", "10", "11"] {
+            assert_eq!(tree.get(&prefix(p)), Some(section_sks[p].0.value.clone()));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_signed_by_only_one_sibling_results_in_no_update() -> Result<()> {
+        let (mut tree, genesis_sk) = TestSectionTree::random_tree()?;
+
+        let mut section_sks = BTreeMap::new();
+        for p in ["0", "1"] {
+            let (sap, sk) = random_signed_sap(prefix(p));
+            let proof_chain = tree
+                .get_sections_dag()
+                .partial_dag(tree.genesis_key(), &genesis_sk.public_key())?;
+            let tree_update =
+                TestSectionTree::get_section_tree_update(&sap, &proof_chain, &genesis_sk)?;
+            assert!(tree.update_the_section_tree(tree_update)?);
+            let _ = section_sks.insert(p, (sap, sk));
+        }
+
+        // section '0' signed the merged key, but section '1' didn't agree to the merge
+        let (sap0, sk0) = &section_sks["0"];
+        let (merged_sap, _) = random_signed_sap(Prefix::default());
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &sap0.section_key())?;
+        let mut tree_update =
+            TestSectionTree::get_section_tree_update(&merged_sap, &proof_chain, sk0)?;
+        assert!(!tree.update_the_section_tree(tree_update.clone())?);
+
+        // nor does an agreement signed by section '0' itself stand for it
+        tree_update.merge_agreement = Some(merge_agreement(Prefix::default(), sk0)?);
+        assert!(!tree.update_the_section_tree(tree_update)?);
+
+        assert_eq!(tree.get(&Prefix::default()), None);
+        for p in ["0", "1"] {
+            assert_eq!(tree.get(&prefix(p)), Some(section_sks[p].0.value.clone()));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_not_signed_by_a_merged_section_result_in_no_update() -> Result<()> {
+        let (mut tree, genesis_sk) = TestSectionTree::random_tree()?;
+
+        let (sap0, _) = random_signed_sap(prefix("0"));
+        let tree_update =
+            TestSectionTree::get_section_tree_update(&sap0, tree.get_sections_dag(), &genesis_sk)?;
+        assert!(tree.update_the_section_tree(tree_update)?);
+
+        // a SAP for the parent prefix signed by the genesis key doesn't cover section '0'
+        let (outdated_sap, _) = random_signed_sap(Prefix::default());
+        let proof_chain = tree
+            .get_sections_dag()
+            .partial_dag(tree.genesis_key(), &genesis_sk.public_key())?;
+        let tree_update =
+            TestSectionTree::get_section_tree_update(&outdated_sap, &proof_chain, &genesis_sk)?;
+        assert!(!tree.update_the_section_tree(tree_update)?);
+        assert_eq!(tree.get(&prefix("0")), Some(sap0.value));
+
+        Ok(())
+    }
+
     // Proptest which updates the `SectionTree` using randomized length/order of proof_chain. Error cases, no update cases
     // are ignored, i.e., each update results in a new SAP being added. At the end of each update verify that the
     // leaves of `SectionTree::sections_dag` are the keys of all the `SectionTree::sections` (SAPs). After all the
@@ -875,6 +1171,130 @@ mod tests {
         }
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig {
+            cases: 20, .. ProptestConfig::default()
+        })]
+        #[test]
+        #[allow(clippy::unwrap_used)]
+        fn proptest_section_tree_leaf_keys_stay_in_sync_through_merges(changes in vec(arb_section_change(), 1..30)) {
+            let (genesis_sap, genesis_sk, ..) = TestSapBuilder::new(Prefix::default()).build();
+            let genesis_sk = genesis_sk.secret_key();
+            let genesis_sap = TestKeys::get_section_signed(&genesis_sk, genesis_sap).unwrap();
+            let mut section_tree = SectionTree::new(genesis_sap).unwrap();
+            // the secret key of each section, by prefix
+            let mut sections = BTreeMap::from([(Prefix::default(), genesis_sk)]);
+
+            for change in changes {
+                for (sap, parent_sk, merge_agreement) in section_change_saps(&mut sections, change) {
+                    let proof_chain = section_tree
+                        .get_sections_dag()
+                        .partial_dag(section_tree.genesis_key(), &parent_sk.public_key())
+                        .unwrap();
+                    let mut tree_update =
+                        TestSectionTree::get_section_tree_update(&sap, &proof_chain, &parent_sk)
+                            .unwrap();
+                    tree_update.merge_agreement = merge_agreement;
+                    assert!(section_tree.update_the_section_tree(tree_update).unwrap());
+                }
+
+                prop_assert_eq!(
+                    section_tree.sections.keys().copied().collect::<BTreeSet<_>>(),
+                    sections.keys().copied().collect::<BTreeSet<_>>()
+                );
+                assert_lists(
+                    section_tree.sections.values().map(|sap| sap.section_key()),
+                    section_tree.leaf_keys(),
+                );
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum SectionChange {
+        // the elders of a section change
+        Churn(usize),
+        Split(usize),
+        // a section and its sibling merge, the merged key being signed by either one of them
+        Merge(usize, bool),
+    }
+
+    fn arb_section_change() -> impl Strategy<Value = SectionChange> {
+        prop_oneof![
+            any::<usize>().prop_map(SectionChange::Churn),
+            any::<usize>().prop_map(SectionChange::Split),
+            (any::<usize>(), any::<bool>())
+                .prop_map(|(index, signer)| SectionChange::Merge(index, signer)),
+        ]
+    }
+
+    // Applies the change to the sections, returning the resulting SAPs along with the secret key
+    // of the section which signed them off, and the agreement of the other section to a merge.
+    #[allow(clippy::type_complexity)]
+    fn section_change_saps(
+        sections: &mut BTreeMap<Prefix, bls::SecretKey>,
+        change: SectionChange,
+    ) -> Vec<(
+        SectionSigned<SectionAuthorityProvider>,
+        bls::SecretKey,
+        Option<SectionSigned<SectionMergeAgreement>>,
+    )> {
+        let prefixes: Vec<Prefix> = sections.keys().copied().collect();
+        match change {
+            SectionChange::Churn(index) => {
+                let prefix = prefixes[index % prefixes.len()];
+                let (sap, sk) = random_signed_sap(prefix);
+                let parent_sk = sections.insert(prefix, sk).expect("section is known");
+                vec![(sap, parent_sk, None)]
+            }
+            SectionChange::Split(index) => {
+                let prefix = prefixes[index % prefixes.len()];
+                let parent_sk = sections.remove(&prefix).expect("section is known");
+                [false, true]
+                    .into_iter()
+                    .map(|bit| {
+                        let (sap, sk) = random_signed_sap(prefix.pushed(bit));
+                        let _ = sections.insert(prefix.pushed(bit), sk);
+                        (sap, parent_sk.clone(), None)
+                    })
+                    .collect()
+            }
+            SectionChange::Merge(index, signer) => {
+                // only the sections whose sibling hasn't split further can merge
+                let mergeable: Vec<Prefix> = prefixes
+                    .iter()
+                    .filter(|prefix| !prefix.is_empty() && sections.contains_key(&prefix.sibling()))
+                    .map(|prefix| prefix.popped())
+                    .collect();
+                if mergeable.is_empty() {
+                    return vec![];
+                }
+                let merged_prefix = mergeable[index % mergeable.len()];
+                let parent_sk = sections.remove(&merged_prefix.pushed(signer));
+                let agreeing_sk = sections.remove(&merged_prefix.pushed(!signer));
+                let agreement =
+                    merge_agreement(merged_prefix, &agreeing_sk.expect("section is known"))
+                        .expect("Failed to sign merge agreement");
+                let (sap, sk) = random_signed_sap(merged_prefix);
+                let _ = sections.insert(merged_prefix, sk);
+                vec![(sap, parent_sk.expect("section is known"), Some(agreement))]
+            }
+        }
+    }
+
+    // The agreement of the section with the given key to merge into the given prefix
+    fn merge_agreement(
+        merged_prefix: Prefix,
+        sk: &bls::SecretKey,
+    ) -> Result<SectionSigned<SectionMergeAgreement>> {
+        let agreement = SectionMergeAgreement {
+            merged_prefix,
+            section_key: sk.public_key(),
+            members: BTreeSet::new(),
+        };
+        TestKeys::get_section_signed(sk, agreement)
+    }
+
     /// Test helper
     fn random_signed_sap(
         prefix: Prefix,
@@ -42,6 +42,15 @@ pub enum Error {
     /// Entry could not be found on the data
     #[error("Requested entry not found {0}")]
     NoSuchEntry(EntryHash),
+    /// The version of the register policy could not be found
+    #[error("Requested policy version not found: {0}")]
+    NoSuchPolicyVersion(u64),
+    /// A policy edit cannot result in version 0, which is the policy the register was created with
+    #[error("A policy edit cannot result in the policy the register was created with")]
+    PolicyEditToInitialVersion,
+    /// A different edit resulting in the same policy version was already applied
+    #[error("A different edit resulting in policy version {0} was already applied")]
+    PolicyVersionConflict(u64),
     /// User entry could not be found on the data
     #[error("Requested user not found {0:?}")]
     NoSuchUser(Box<User>),
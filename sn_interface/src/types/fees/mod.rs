@@ -40,14 +40,21 @@
 ///         An example could be that the fee is be paid to the section closest to the XOR of all input dbc ids. Any Elder processing
 ///         a spend, would then have to find that section, query it for their reward keys and verify that outputs to them exist. However,
 ///         they can still not verify the amounts by this. So that example is still not feasible. TBD.
+///
+/// Storage payments follow the same steps, but the Client asks the Elders of the sections where the data
+/// will be stored for the store cost instead, and pays them all in one spend whose reason commits to the
+/// names of the data being paid for, up to `MAX_PAID_ITEMS` of them per spend. The resulting `PaymentProof`
+/// is then attached to each store cmd, for those Elders to verify before forwarding the data to the data holders.
 mod errors;
 mod fee_ciphers;
+mod payment_proof;
 mod required_fee;
 mod required_fee_content;
 
 pub use self::{
     errors::{Error, Result},
     fee_ciphers::FeeCiphers,
+    payment_proof::{PaymentProof, MAX_PAID_ITEMS},
     required_fee::RequiredFee,
     required_fee_content::RequiredFeeContent,
 };
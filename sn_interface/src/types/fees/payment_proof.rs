@@ -0,0 +1,67 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::FeeCiphers;
+
+use crate::dbcs::DbcReason;
+
+use sn_dbc::{DbcTransaction, Hash, SpentProof};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use xor_name::XorName;
+
+/// Max number of data items a single payment pays for.
+///
+/// The proof of a payment is sent along with the store of each of the data items it pays for,
+/// and the Elders verify it, names included, for each of them, thus more data items are paid
+/// for in several payments, for the cost of storing them to only grow with their number.
+pub const MAX_PAID_ITEMS: usize = 100;
+
+/// Proof that storing a set of data items on the network has been paid for.
+///
+/// A Client asks the Elders of each section where the data will be stored for
+/// the storage cost, and pays them in a single spend, using the reason returned by
+/// [`PaymentProof::reason_for`] over the names of the data items being paid for,
+/// which are at most [`MAX_PAID_ITEMS`].
+/// The resulting transaction, along with the spent proofs of its inputs and the
+/// `FeeCiphers` for each paid Elder, is then attached to every store of those data items.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, custom_debug::Debug)]
+pub struct PaymentProof {
+    /// Names of all the data items paid for with this payment.
+    #[debug(skip)]
+    pub content: BTreeSet<XorName>,
+    /// The transaction with the outputs to the paid Elders.
+    #[debug(skip)]
+    pub tx: DbcTransaction,
+    /// Spent proofs for all the inputs of the transaction.
+    #[debug(skip)]
+    pub spent_proofs: BTreeSet<SpentProof>,
+    /// Ciphers allowing each paid Elder to find and verify its fee, keyed by Elder name.
+    #[debug(skip)]
+    pub fee_ciphers: BTreeMap<XorName, FeeCiphers>,
+}
+
+impl PaymentProof {
+    /// The reason the inputs of the payment transaction have to be spent with,
+    /// which ties the payment to the data items it pays for.
+    pub fn reason_for(content: &BTreeSet<XorName>) -> DbcReason {
+        let bytes: Vec<u8> = content.iter().flat_map(|name| name.0).collect();
+        DbcReason::from(Hash::hash(&bytes))
+    }
+
+    /// The reason the inputs of this payment's transaction are expected to be spent with.
+    pub fn reason(&self) -> DbcReason {
+        Self::reason_for(&self.content)
+    }
+
+    /// Whether the data item with the given name is paid for by this payment.
+    pub fn covers(&self, name: &XorName) -> bool {
+        self.content.contains(name)
+    }
+}
@@ -18,6 +18,9 @@ pub enum LogMarker {
     StillElderAfterSplit,
     SplitSuccess,
     SplitAttempt,
+    // Merge
+    MergeAttempt,
+    MergeSuccess,
     NewPrefix,
     AeSendUpdateToSiblings,
     AgreementOfMembership,
@@ -39,12 +42,14 @@ pub enum LogMarker {
     ClientMsgToBeForwarded,
     MsgReceived,
     ClientMsgToBeHandled,
+    ClientMsgThrottled,
     NodeMsgToBeHandled,
     // Membership
     MembershipVotesBeingHandled,
     MembershipSendingAeUpdateRequest,
     MembershipAeRequestReceived,
     GossippingMembershipVotes,
+    NodeLeavingVoluntarily,
     // Chunks
     StoringChunk,
     StoredNewChunk,
@@ -56,6 +61,10 @@ pub enum LogMarker {
     DataReorganisationUnderway,
     QueuingMissingReplicatedData,
     SendingMissingReplicatedData,
+    DataScrubRoundCompleted,
+    CorruptDataQuarantined,
+    NodeDrainStarted,
+    NodeDrainCompleted,
     // Register
     RegisterWrite,
     RegisterQueryReceivedAtElder,
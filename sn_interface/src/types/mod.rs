@@ -8,7 +8,7 @@
 
 //! SAFE network data types.
 
-/// Fees for a spend.
+/// Fees for a spend or for storing data.
 pub mod fees;
 /// public key types (ed25519)
 pub mod keys;
@@ -42,11 +42,14 @@ pub use keys::{
     signature::{Signature, SignatureShare},
 };
 
+use crate::messaging::system::SectionSigned;
+use register::Register;
 use serde::{Deserialize, Serialize};
+use sn_dbc::SpentProofShare;
 use xor_name::XorName;
 
-// TODO: temporary type tag for spentbook since its underlying data type is
-// still not implemented, it uses a Public Register for now.
+/// Type tag of the Registers spentbooks were stored as before they had their own data type.
+/// It's only used to migrate the spentbooks still stored that way.
 pub const SPENTBOOK_TYPE_TAG: u64 = 0;
 
 /// Register data exchange.
@@ -56,9 +59,24 @@ pub struct ReplicatedRegisterLog {
     pub address: RegisterAddress,
     ///
     pub op_log: Vec<RegisterCmd>,
+    /// Snapshots of the register signed by the section holding it, the cmds they were built
+    /// from being left out of the log. Not serialised when there are none, so that the log is
+    /// serialised as it was before snapshots were signed, and can still be read by older nodes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshots: Vec<SectionSigned<Register>>,
+}
+
+/// Spentbook data exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicatedSpentbook {
+    /// Address of the spentbook, derived from the id of the DBC it's for.
+    pub address: SpentbookAddress,
+    /// All the spent proof shares logged in the spentbook.
+    pub proof_shares: Vec<SpentProofShare>,
 }
 
 ///
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ReplicatedData {
     /// A chunk of data.
@@ -67,10 +85,16 @@ pub enum ReplicatedData {
     RegisterWrite(RegisterCmd),
     /// An entire op log of a register.
     RegisterLog(ReplicatedRegisterLog),
-    /// A single cmd for a spentbook.
+    /// A single cmd for a spentbook stored as a Register, as sent by nodes not upgraded yet
+    /// to store spentbooks natively. The spent proof share it writes is logged in the spentbook.
     SpentbookWrite(RegisterCmd),
-    /// An entire op log of a spentbook.
+    /// An entire op log of a spentbook stored as a Register, as sent by nodes not upgraded yet
+    /// to store spentbooks natively. The spent proof shares it holds are logged in the spentbook.
     SpentbookLog(ReplicatedRegisterLog),
+    /// A single spent proof share to log in a spentbook.
+    SpentProofShare(SpentProofShare),
+    /// All the spent proof shares of a spentbook.
+    Spentbook(ReplicatedSpentbook),
 }
 
 impl ReplicatedData {
@@ -81,6 +105,8 @@ impl ReplicatedData {
             Self::RegisterWrite(cmd) => *cmd.dst_address().name(),
             Self::SpentbookLog(log) => *log.address.name(),
             Self::SpentbookWrite(cmd) => *cmd.dst_address().name(),
+            Self::Spentbook(spentbook) => *spentbook.address.name(),
+            Self::SpentProofShare(share) => XorName::from_content(&share.public_key().to_bytes()),
         }
     }
 
@@ -89,12 +115,10 @@ impl ReplicatedData {
             Self::Chunk(chunk) => DataAddress::Bytes(*chunk.address()),
             Self::RegisterLog(log) => DataAddress::Register(log.address),
             Self::RegisterWrite(cmd) => DataAddress::Register(cmd.dst_address()),
-            Self::SpentbookLog(log) => {
-                DataAddress::Spentbook(SpentbookAddress::new(*log.address.name()))
-            }
-            Self::SpentbookWrite(cmd) => {
-                DataAddress::Spentbook(SpentbookAddress::new(*cmd.dst_address().name()))
+            Self::SpentbookLog(_) | Self::SpentbookWrite(_) | Self::SpentProofShare(_) => {
+                DataAddress::Spentbook(SpentbookAddress::new(self.name()))
             }
+            Self::Spentbook(spentbook) => DataAddress::Spentbook(spentbook.address),
         }
     }
 }
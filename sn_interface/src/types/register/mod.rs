@@ -11,7 +11,7 @@ mod policy;
 mod reg_crdt;
 
 pub use metadata::{Action, Entry};
-pub use policy::{Permissions, Policy, User};
+pub use policy::{Permissions, Policy, PolicyEdit, PolicyOp, User};
 pub use reg_crdt::EntryHash;
 
 pub(crate) use reg_crdt::{CrdtOperation, RegisterCrdt};
@@ -40,7 +40,13 @@ pub type RegisterOp<T> = CrdtOperation<T>;
 pub struct Register {
     authority: User,
     pub(super) crdt: RegisterCrdt, // Temporarily exposed to 'super' till spentbook fully implemented.
+    // The policy the register was created with.
     policy: Policy,
+    // The versions of the policy resulting from its edits, the last one being the current one.
+    // Not serialised when there are none, so that the register is serialised as it was before
+    // policies could be edited, and can still be read by older nodes and clients.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    policy_edits: Vec<Policy>,
 }
 
 impl Register {
@@ -51,6 +57,7 @@ impl Register {
             authority,
             crdt: RegisterCrdt::new(address),
             policy,
+            policy_edits: vec![],
         }
     }
 
@@ -83,7 +90,7 @@ impl Register {
 
     /// Return the owner of the data.
     pub fn owner(&self) -> User {
-        *self.policy.owner()
+        *self.policy().owner()
     }
 
     /// Return the PK which the messages are expected to be signed with by this replica.
@@ -101,6 +108,12 @@ impl Register {
         self.crdt.get(hash).ok_or(Error::NoSuchEntry(hash))
     }
 
+    /// Return the hashes of the entries the entry corresponding to the provided 'hash' succeeds,
+    /// which allows walking the history of the register back from its latest entries.
+    pub fn children(&self, hash: EntryHash) -> Result<BTreeSet<EntryHash>> {
+        self.crdt.children(hash).ok_or(Error::NoSuchEntry(hash))
+    }
+
     /// Read the last entry, or entries when there are branches, if the register is not empty.
     pub fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.crdt.read()
@@ -108,14 +121,62 @@ impl Register {
 
     /// Return user permissions, if applicable.
     pub fn permissions(&self, user: User) -> Result<Permissions> {
-        self.policy
+        self.policy()
             .permissions(user)
             .ok_or(Error::NoSuchUser(Box::new(user)))
     }
 
-    /// Return the policy.
+    /// Return the current policy.
     pub fn policy(&self) -> &Policy {
-        &self.policy
+        self.policy_edits.last().unwrap_or(&self.policy)
+    }
+
+    /// Return the version of the current policy, i.e. the number of edits made to the policy
+    /// the register was created with.
+    pub fn policy_version(&self) -> u64 {
+        self.policy_edits.len() as u64
+    }
+
+    /// Return the given version of the policy.
+    pub fn policy_at(&self, version: u64) -> Result<&Policy> {
+        if version == 0 {
+            return Ok(&self.policy);
+        }
+        usize::try_from(version - 1)
+            .ok()
+            .and_then(|index| self.policy_edits.get(index))
+            .ok_or(Error::NoSuchPolicyVersion(version))
+    }
+
+    /// Apply a policy operation made by the given requester, who must be the owner as per the
+    /// policy being edited. Operations must be applied in order of the versions they result in,
+    /// applying an operation which was already applied is a no-op.
+    pub fn apply_policy_op(&mut self, op: PolicyOp, requester: User) -> Result<()> {
+        let current = self.policy_version();
+        if op.version == 0 {
+            return Err(Error::PolicyEditToInitialVersion);
+        }
+        if op.version > current + 1 {
+            return Err(Error::NoSuchPolicyVersion(op.version - 1));
+        }
+
+        let previous = self.policy_at(op.version - 1)?;
+        if *previous.owner() != requester {
+            return Err(Error::AccessDenied(Box::new(requester)));
+        }
+
+        let policy = previous.edited(&op.edit);
+        if op.version <= current {
+            // it must be the very same edit we already applied
+            return if self.policy_at(op.version)? == &policy {
+                Ok(())
+            } else {
+                Err(Error::PolicyVersionConflict(op.version))
+            };
+        }
+
+        self.policy_edits.push(policy);
+        Ok(())
     }
 
     /// Write an entry to the Register, returning the generated unsigned
@@ -136,6 +197,27 @@ impl Register {
         self.crdt.apply_op(op)
     }
 
+    /// Merge another replica of the Register into this one, e.g. one previously
+    /// retrieved from the network, which may have entries and policy edits this one
+    /// doesn't have yet, or vice versa.
+    pub fn merge(&mut self, other: Register) -> Result<()> {
+        // the chain of policy edits of one replica must be a prefix of the other's
+        if let Some(version) = std::iter::once(&self.policy)
+            .chain(&self.policy_edits)
+            .zip(std::iter::once(&other.policy).chain(&other.policy_edits))
+            .position(|(policy, other_policy)| policy != other_policy)
+        {
+            return Err(Error::PolicyVersionConflict(version as u64));
+        }
+
+        self.crdt.merge(other.crdt)?;
+        if other.policy_edits.len() > self.policy_edits.len() {
+            self.policy_edits = other.policy_edits;
+        }
+
+        Ok(())
+    }
+
     // Private helper to check the given Entry's size is within define limit,
     // as well as check the Register hasn't already reached the maximum number of entries.
     fn check_entry_and_reg_sizes(&self, entry: &Entry) -> Result<()> {
@@ -163,14 +245,34 @@ impl Register {
     /// `Err::AccessDenied` if the action is not allowed.
     pub fn check_permissions(&self, action: Action, requester: Option<User>) -> Result<()> {
         let requester = requester.unwrap_or(self.authority);
-        self.policy.is_action_allowed(requester, action)
+        self.policy().is_action_allowed(requester, action)
+    }
+
+    /// Helper to check permissions for given `action` for the given requester's public key,
+    /// as per the given version of the policy.
+    ///
+    /// Returns:
+    /// `Ok(())` if the permissions are valid,
+    /// `Err::AccessDenied` if the action is not allowed,
+    /// `Err::NoSuchPolicyVersion` if the policy of the given version is unknown.
+    pub fn check_permissions_at(
+        &self,
+        version: u64,
+        action: Action,
+        requester: Option<User>,
+    ) -> Result<()> {
+        let requester = requester.unwrap_or(self.authority);
+        self.policy_at(version)?
+            .is_action_allowed(requester, action)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::{
-        register::{Entry, EntryHash, Permissions, Register, RegisterOp, User},
+        register::{
+            Action, Entry, EntryHash, Permissions, PolicyEdit, PolicyOp, Register, RegisterOp, User,
+        },
         utils, Error, Keypair, Result,
     };
     use crate::types::register::MAX_REG_NUM_ENTRIES;
@@ -452,6 +554,149 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn register_policy_edits_are_chained() -> eyre::Result<()> {
+        let (owner_keypair, mut replica) = create_reg_replicas(1).remove(0);
+        let owner = User::Key(owner_keypair.public_key());
+        let writer = User::Key(Keypair::new_ed25519().public_key());
+
+        let grant = PolicyOp {
+            version: 1,
+            edit: PolicyEdit::Grant {
+                user: writer,
+                permissions: Permissions::new(true),
+            },
+        };
+        // only the owner can edit the policy
+        assert_eq!(
+            replica.apply_policy_op(grant.clone(), writer),
+            Err(Error::AccessDenied(Box::new(writer)))
+        );
+        replica.apply_policy_op(grant.clone(), owner)?;
+        replica.check_permissions(Action::Write, Some(writer))?;
+        assert!(replica
+            .policy_at(0)?
+            .is_action_allowed(writer, Action::Write)
+            .is_err());
+
+        // the policy the register was created with can't be the result of an edit
+        let to_initial = PolicyOp {
+            version: 0,
+            edit: PolicyEdit::TransferOwnership(writer),
+        };
+        assert_eq!(
+            replica.apply_policy_op(to_initial, owner),
+            Err(Error::PolicyEditToInitialVersion)
+        );
+
+        // the ops must be applied in order, and re-applying one is a no-op
+        let transfer = PolicyOp {
+            version: 3,
+            edit: PolicyEdit::TransferOwnership(writer),
+        };
+        assert_eq!(
+            replica.apply_policy_op(transfer, writer),
+            Err(Error::NoSuchPolicyVersion(2))
+        );
+        replica.apply_policy_op(grant, owner)?;
+        assert_eq!(replica.policy_version(), 1);
+        let revoke = PolicyOp {
+            version: 1,
+            edit: PolicyEdit::Revoke(writer),
+        };
+        assert_eq!(
+            replica.apply_policy_op(revoke, owner),
+            Err(Error::PolicyVersionConflict(1))
+        );
+
+        // once the ownership is handed over, only the new owner can edit the policy
+        let transfer = PolicyOp {
+            version: 2,
+            edit: PolicyEdit::TransferOwnership(writer),
+        };
+        replica.apply_policy_op(transfer, owner)?;
+        assert_eq!(replica.owner(), writer);
+        let revoke = PolicyOp {
+            version: 3,
+            edit: PolicyEdit::Revoke(owner),
+        };
+        assert_eq!(
+            replica.apply_policy_op(revoke.clone(), owner),
+            Err(Error::AccessDenied(Box::new(owner)))
+        );
+        replica.apply_policy_op(revoke, writer)?;
+        assert!(replica
+            .check_permissions(Action::Write, Some(owner))
+            .is_err());
+        assert_eq!(replica.policy_version(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_replicas_are_merged() -> eyre::Result<()> {
+        let (owner_keypair, mut replica1) = create_reg_replicas(1).remove(0);
+        let mut replica2 = replica1.clone();
+        let owner = User::Key(owner_keypair.public_key());
+
+        let (hash1, _) = replica1.write(random_register_entry(), BTreeSet::new())?;
+        let (hash2, _) = replica2.write(random_register_entry(), BTreeSet::new())?;
+        let grant = PolicyOp {
+            version: 1,
+            edit: PolicyEdit::Grant {
+                user: User::Anyone,
+                permissions: Permissions::new(true),
+            },
+        };
+        replica2.apply_policy_op(grant, owner)?;
+
+        replica1.merge(replica2.clone())?;
+        assert_eq!(replica1.size(), 2);
+        assert!(replica1.get(hash1).is_ok());
+        assert!(replica1.get(hash2).is_ok());
+        assert_eq!(replica1.policy(), replica2.policy());
+
+        // replicas with conflicting policy edits can't be merged
+        let revoke = PolicyOp {
+            version: 2,
+            edit: PolicyEdit::Revoke(User::Anyone),
+        };
+        replica1.apply_policy_op(revoke, owner)?;
+        let transfer = PolicyOp {
+            version: 2,
+            edit: PolicyEdit::TransferOwnership(User::Anyone),
+        };
+        replica2.apply_policy_op(transfer, owner)?;
+        assert_eq!(
+            replica1.merge(replica2),
+            Err(Error::PolicyVersionConflict(2))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_history_is_walked_back_from_latest_entries() -> eyre::Result<()> {
+        let (_, mut replica) = create_reg_replicas(1).remove(0);
+
+        let (first, _) = replica.write(random_register_entry(), BTreeSet::new())?;
+        let (left, _) = replica.write(random_register_entry(), BTreeSet::from([first]))?;
+        let (right, _) = replica.write(random_register_entry(), BTreeSet::from([first]))?;
+        let (merge, _) = replica.write(random_register_entry(), BTreeSet::from([left, right]))?;
+
+        assert_eq!(replica.children(merge)?, BTreeSet::from([left, right]));
+        assert_eq!(replica.children(left)?, BTreeSet::from([first]));
+        assert_eq!(replica.children(right)?, BTreeSet::from([first]));
+        assert!(replica.children(first)?.is_empty());
+        assert_eq!(
+            replica.children(EntryHash([0; 32])),
+            Err(Error::NoSuchEntry(EntryHash([0; 32])))
+        );
+
+        Ok(())
+    }
+
     // Helpers for tests
 
     fn sign_register_op(mut op: RegisterOp<Entry>, keypair: &Keypair) -> Result<RegisterOp<Entry>> {
@@ -101,3 +101,48 @@ impl Policy {
         &self.owner
     }
 }
+
+/// An edit of a Register's [`Policy`], which only its current owner can make.
+#[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
+pub enum PolicyEdit {
+    /// Set the permissions of a user, replacing any it had.
+    Grant {
+        /// The user the permissions are set for.
+        user: User,
+        /// The permissions of the user.
+        permissions: Permissions,
+    },
+    /// Remove all the permissions of a user.
+    Revoke(User),
+    /// Hand over the ownership of the Register to another user.
+    TransferOwnership(User),
+}
+
+/// A [`PolicyEdit`] applicable to other Register replicas.
+///
+/// The edits of a Register's policy form a chain, each of them resulting in a new policy
+/// version, starting from version `0` which is the policy the Register was created with.
+#[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
+pub struct PolicyOp {
+    /// The version of the policy resulting from the edit.
+    pub version: u64,
+    /// The edit to apply to the previous version of the policy.
+    pub edit: PolicyEdit,
+}
+
+impl Policy {
+    /// Returns the policy resulting from applying the edit to this one.
+    pub fn edited(&self, edit: &PolicyEdit) -> Self {
+        let mut policy = self.clone();
+        match edit {
+            PolicyEdit::Grant { user, permissions } => {
+                let _ = policy.permissions.insert(*user, *permissions);
+            }
+            PolicyEdit::Revoke(user) => {
+                let _ = policy.permissions.remove(user);
+            }
+            PolicyEdit::TransferOwnership(user) => policy.owner = *user,
+        }
+        policy
+    }
+}
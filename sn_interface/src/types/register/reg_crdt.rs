@@ -15,7 +15,7 @@ use super::{
 };
 use crdts::{
     merkle_reg::{MerkleReg, Node},
-    CmRDT,
+    CmRDT, CvRDT,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -140,11 +140,30 @@ impl RegisterCrdt {
         Ok(())
     }
 
+    /// Merge another replica of the `RegisterCrdt` into this one.
+    pub(crate) fn merge(&mut self, other: RegisterCrdt) -> Result<()> {
+        if self.address != other.address {
+            return Err(Error::CrdtWrongAddress(other.address));
+        }
+
+        self.data.merge(other.data);
+
+        Ok(())
+    }
+
     /// Get the entry corresponding to the provided `hash` if it exists.
     pub(crate) fn get(&self, hash: EntryHash) -> Option<&Entry> {
         self.data.node(hash.0).map(|node| &node.value)
     }
 
+    /// Get the hashes of the entries succeeded by the entry corresponding to the provided `hash`,
+    /// i.e. the entries it was written as a child of, if it exists.
+    pub(crate) fn children(&self, hash: EntryHash) -> Option<BTreeSet<EntryHash>> {
+        self.data
+            .node(hash.0)
+            .map(|node| node.children.iter().copied().map(EntryHash).collect())
+    }
+
     /// Read current entries (multiple entries occur on concurrent writes).
     pub(crate) fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.data
@@ -8,14 +8,15 @@
 
 use sn_interface::{
     messaging::data::{CreateRegister, EditRegister, SignedRegisterCreate, SignedRegisterEdit},
+    network_knowledge::SectionsDAG,
     test_utils::TestKeys,
     types::{
         register::{Policy, Register, User},
-        Chunk, Keypair, PublicKey, RegisterCmd, ReplicatedData, SectionSig,
+        Chunk, Keypair, RegisterCmd, ReplicatedData, SectionSig,
     },
 };
 use sn_node::{
-    node::{cfg::config_handler::Config, DataStorage},
+    node::{cfg::config_handler::Config, DataStorage, StorageBackendKind},
     UsedSpace,
 };
 
@@ -35,6 +36,16 @@ use tokio::runtime::Runtime;
 // https://bheisler.github.io/criterion.rs/book/analysis.html#measurement
 const SAMPLE_SIZE: usize = 10;
 
+// each bench is run against all the storage backends
+const BACKENDS: [StorageBackendKind; 2] = [StorageBackendKind::Fs, StorageBackendKind::Kv];
+
+/// Pairs each of the storage backends with each of the sizes to bench.
+fn with_backends(sizes: &[usize]) -> impl Iterator<Item = (StorageBackendKind, &usize)> {
+    BACKENDS
+        .into_iter()
+        .flat_map(move |backend| sizes.iter().map(move |size| (backend, size)))
+}
+
 /// Generates a random vector using provided `length`.
 fn random_vector(length: usize) -> Vec<u8> {
     use rayon::prelude::*;
@@ -97,46 +108,41 @@ fn main() -> Result<()> {
 }
 
 fn bench_data_storage_writes(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-    let keypair = Keypair::new_ed25519();
-
     let mut group = c.benchmark_group("write-sampling");
 
     let runtime = Runtime::new().unwrap();
+    let section_chain = section_chain();
     pub const NONSENSE_CHUNK_SIZE: usize = 1024; // data size should not be important for keys() tests
 
     let size_ranges = [100, 1_000, 4_000];
 
-    for size in &size_ranges {
+    for (backend, size) in with_backends(&size_ranges) {
         let data_set: Vec<_> = (0..*size)
             .map(|_| create_random_register_replicated_data())
             .collect();
         group.bench_with_input(
-            BenchmarkId::new("register_writes", size),
+            BenchmarkId::new(format!("register_writes/{backend}"), size),
             &(size, &data_set),
             |b, (size, data_set)| {
-                let storage = get_new_data_store()
+                let storage = get_new_data_store(backend)
                     .context("Could not create a temp data store")
                     .unwrap();
                 b.to_async(&runtime).iter(|| async {
                     for i in 0..**size {
-                        let _ = storage
-                            .clone()
-                            .store(&data_set[i], pk, keypair.clone())
-                            .await;
+                        let _ = storage.clone().store(&data_set[i], &section_chain).await;
                     }
                 })
             },
         );
     }
 
-    for size in &size_ranges {
+    for (backend, size) in with_backends(&size_ranges) {
         let seed = random_vector(NONSENSE_CHUNK_SIZE);
         group.bench_with_input(
-            BenchmarkId::new("chunk writes", size),
+            BenchmarkId::new(format!("chunk writes/{backend}"), size),
             &(size, &seed),
             |b, (size, seed)| {
-                let storage = get_new_data_store()
+                let storage = get_new_data_store(backend)
                     .context("Could not create a temp data store")
                     .unwrap();
                 b.to_async(&runtime).iter(|| async {
@@ -145,7 +151,7 @@ fn bench_data_storage_writes(c: &mut Criterion) -> Result<()> {
                             ReplicatedData::Chunk(Chunk::new(grows_vec_to_bytes(seed)));
                         storage
                             .clone()
-                            .store(&random_data, pk, keypair.clone())
+                            .store(&random_data, &section_chain)
                             .await
                             .expect("failed to write chunk {i}");
                     }
@@ -158,14 +164,14 @@ fn bench_data_storage_writes(c: &mut Criterion) -> Result<()> {
 }
 
 fn bench_data_storage_register_edits(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
     let mut group = c.benchmark_group("register-edit-sampling");
 
     let runtime = Runtime::new().unwrap();
+    let section_chain = section_chain();
 
     let size_ranges = [1000];
 
-    for size in &size_ranges {
+    for (backend, size) in with_backends(&size_ranges) {
         let (keypair, op) = create_random_register_register_op();
         let address = op.address();
         // the actual register we'll be editing
@@ -181,10 +187,10 @@ fn bench_data_storage_register_edits(c: &mut Criterion) -> Result<()> {
             .collect();
 
         group.bench_with_input(
-            BenchmarkId::new("register_edits", size),
+            BenchmarkId::new(format!("register_edits/{backend}"), size),
             &(size, &data_set),
             |b, (size, data_set)| {
-                let storage = get_new_data_store()
+                let storage = get_new_data_store(backend)
                     .context("Could not create a temp data store")
                     .unwrap();
                 let signature =
@@ -203,14 +209,14 @@ fn bench_data_storage_register_edits(c: &mut Criterion) -> Result<()> {
 
                 let first_write = ReplicatedData::RegisterWrite(reg_cmd);
                 runtime
-                    .block_on(storage.store(&first_write, pk, keypair.clone()))
+                    .block_on(storage.store(&first_write, &section_chain))
                     .expect("Could not store initial register");
 
                 b.to_async(&runtime).iter(|| async {
                     for i in 0..**size {
                         storage
                             .clone()
-                            .store(&data_set[i], pk, keypair.clone())
+                            .store(&data_set[i], &section_chain)
                             .await
                             .expect("failed to write data storage edit");
                     }
@@ -223,19 +229,18 @@ fn bench_data_storage_register_edits(c: &mut Criterion) -> Result<()> {
 }
 
 fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
-    let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-    let keypair = Keypair::new_ed25519();
-
     let mut group = c.benchmark_group("read-sampling");
 
     let runtime = Runtime::new().unwrap();
+    let section_chain = section_chain();
     pub const NONSENSE_CHUNK_SIZE: usize = 1024; // data size should not be important for keys() tests
 
     let size_ranges = [100, 1_000, 4_000];
 
-    for size in &size_ranges {
-        group.bench_with_input(BenchmarkId::new("register_keys", size), size, |b, &size| {
-            let storage = get_new_data_store()
+    for (backend, size) in with_backends(&size_ranges) {
+        let id = BenchmarkId::new(format!("register_keys/{backend}"), size);
+        group.bench_with_input(id, size, |b, &size| {
+            let storage = get_new_data_store(backend)
                 .context("Could not create a temp data store")
                 .unwrap();
 
@@ -243,7 +248,7 @@ fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
                 let random_data = create_random_register_replicated_data();
 
                 if let Err(error) = runtime
-                    .block_on(storage.clone().store(&random_data, pk, keypair.clone()))
+                    .block_on(storage.clone().store(&random_data, &section_chain))
                     .context("could not store register")
                 {
                     panic!("Error storing register {random_data:?}: {error:?}");
@@ -256,9 +261,10 @@ fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
         });
     }
 
-    for size in &size_ranges {
-        group.bench_with_input(BenchmarkId::new("chunk keys", size), size, |b, &size| {
-            let storage = get_new_data_store()
+    for (backend, size) in with_backends(&size_ranges) {
+        let id = BenchmarkId::new(format!("chunk keys/{backend}"), size);
+        group.bench_with_input(id, size, |b, &size| {
+            let storage = get_new_data_store(backend)
                 .context("Could not create a temp data store")
                 .unwrap();
 
@@ -266,7 +272,7 @@ fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
                 let file = sn_interface::types::utils::random_bytes(NONSENSE_CHUNK_SIZE);
                 let random_data = ReplicatedData::Chunk(Chunk::new(file));
                 if let Err(error) = runtime
-                    .block_on(storage.store(&random_data, pk, keypair.clone()))
+                    .block_on(storage.store(&random_data, &section_chain))
                     .context("could not store chunk")
                 {
                     panic!("Error storing chunk {error:?}");
@@ -282,6 +288,11 @@ fn bench_data_storage_reads(c: &mut Criterion) -> Result<()> {
     Ok(())
 }
 
+// no spentbooks are written, thus the section keys don't matter
+fn section_chain() -> SectionsDAG {
+    SectionsDAG::new(bls::SecretKey::random().public_key())
+}
+
 fn section_sig() -> SectionSig {
     let sk = bls::SecretKey::random();
     TestKeys::get_section_sig_bytes(&sk, "hello".as_bytes())
@@ -321,6 +332,7 @@ pub fn create_random_register_replicated_data_edit(
     let edit_register_op = EditRegister {
         address: *register.address(),
         edit,
+        policy_version: register.policy_version(),
     };
     let signature =
         keypair.sign(&bincode::serialize(&edit_register_op).expect("could not serialize op"));
@@ -355,7 +367,7 @@ pub fn create_random_register_replicated_data() -> ReplicatedData {
     ReplicatedData::RegisterWrite(reg_cmd)
 }
 
-fn get_new_data_store() -> Result<DataStorage> {
+fn get_new_data_store(backend: StorageBackendKind) -> Result<DataStorage> {
     let random_filename: String = thread_rng()
         .sample_iter(&Alphanumeric)
         .take(7)
@@ -368,7 +380,8 @@ fn get_new_data_store() -> Result<DataStorage> {
     let config = Config::default();
 
     let used_space = UsedSpace::new(config.min_capacity(), config.max_capacity());
-    let store = DataStorage::new(&storage_dir, used_space);
+    let store = DataStorage::with_backend(&storage_dir, used_space, backend)
+        .map_err(|e| eyre::eyre!(e.to_string()))?;
 
     Ok(store)
 }
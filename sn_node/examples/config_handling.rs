@@ -87,6 +87,26 @@ async fn main() -> Result<()> {
         assert_eq!(file_config.local_addr, config.local_addr);
     }
 
+    assert_eq!(command_line_args.storage_backend, config.storage_backend);
+
+    if command_line_args.client_msgs_per_sec.is_some() {
+        assert_eq!(
+            command_line_args.client_msgs_per_sec,
+            config.client_msgs_per_sec
+        );
+    } else {
+        assert_eq!(file_config.client_msgs_per_sec, config.client_msgs_per_sec);
+    }
+
+    if command_line_args.client_msgs_burst.is_some() {
+        assert_eq!(
+            command_line_args.client_msgs_burst,
+            config.client_msgs_burst
+        );
+    } else {
+        assert_eq!(file_config.client_msgs_burst, config.client_msgs_burst);
+    }
+
     if command_line_args.first.is_some() {
         assert!(config.first.is_some());
     }
@@ -29,6 +29,8 @@
 )]
 
 mod log;
+#[cfg(feature = "metrics")]
+mod metrics;
 #[cfg(feature = "rpc-service")]
 mod rpc;
 
@@ -67,6 +69,9 @@ enum NodeCtrl {
     Restart(Duration),
     // Request to update the safenode app, and restart it, after the requested delay.
     Update(Duration),
+    // Request to hand off the node's data to other nodes, and then stop the execution of the
+    // safenode app, waiting up to the requested timeout for the data to be acknowledged.
+    Drain { timeout: Duration, cause: Error },
 }
 
 fn main() -> Result<()> {
@@ -189,6 +194,27 @@ fn create_runtime_and_node(config: &Config) -> Result<()> {
             // Monitor NodeEvents just in case we need to rejoin
             monitor_node_events(node_ref.clone(), ctrl_tx.clone(), log_dir.clone());
 
+            // Start up the metrics endpoint, only reachable locally
+            #[cfg(feature = "metrics")]
+            {
+                // ... using the port number two after the one used by sn_node.
+                let node_port = node_ref.read().await.context.socket_addr().port();
+                let started = match node_port.checked_add(2) {
+                    Some(port) => {
+                        let addr =
+                            std::net::SocketAddr::from((std::net::Ipv4Addr::LOCALHOST, port));
+                        metrics::start_metrics_server(addr, node_ref.clone())
+                            .map_err(|error| format!("could not bind to {addr}: {error}"))
+                    }
+                    None => Err(format!("no port available after {node_port}")),
+                };
+                if let Err(reason) = started {
+                    let message = format!("Failed to start the metrics server, {reason}");
+                    println!("{message}");
+                    error!("{message}");
+                }
+            }
+
             // Start up gRPC interface
             #[cfg(feature = "rpc-service")]
             {
@@ -197,7 +223,7 @@ fn create_runtime_and_node(config: &Config) -> Result<()> {
                 // ... and let's use subsequent port number to the one used by sn_node.
                 addr.set_port(addr.port() + 1);
 
-                rpc::start_rpc_service(addr, log_dir.clone(), node_ref, ctrl_tx);
+                rpc::start_rpc_service(addr, log_dir.clone(), node_ref.clone(), ctrl_tx);
             }
 
             // We'll block here until there is a reason reported to restart/stop,
@@ -218,6 +244,7 @@ fn create_runtime_and_node(config: &Config) -> Result<()> {
                     info!("{msg}");
                     println!("{msg} Node log path: {log_dir}");
                     sleep(delay);
+                    node_ref.read().await.prepare_shutdown();
                     return Err(cause);
                 }
                 NodeCtrl::Update(delay) => {
@@ -233,8 +260,22 @@ fn create_runtime_and_node(config: &Config) -> Result<()> {
                             continue;
                         }
                     }
+                }
+                NodeCtrl::Drain {timeout, cause} => {
+                    let msg = format!("Node is draining, handing off its data before stopping (timeout: {timeout:?})...");
+                    info!("{msg}");
+                    println!("{msg} Node log path: {log_dir}");
+                    let node = node_ref.read().await.clone();
+                    match node.drain(timeout).await {
+                        Ok(0) => info!("All the data held by the node has been handed off"),
+                        Ok(unacknowledged) => warn!("{unacknowledged} data items handed off by the node were not acknowledged"),
+                        Err(err) => error!("Failed to drain the node: {err:?}"),
+                    }
+                    node.prepare_shutdown();
+                    return Err(cause);
                 }}
             }
+            node_ref.read().await.prepare_shutdown();
             Ok(())
         })?;
 
@@ -0,0 +1,70 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_node::node::NodeRef;
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+// Content type of the Prometheus text format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Serves the node metrics over HTTP, at `/metrics`, to be scraped by Prometheus.
+///
+/// Fails if the metrics server can't bind to the address given.
+pub(super) fn start_metrics_server(
+    addr: SocketAddr,
+    node_ref: Arc<RwLock<NodeRef>>,
+) -> hyper::Result<()> {
+    let make_service = make_service_fn(move |_conn| {
+        let node_ref = node_ref.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                serve_metrics(request, node_ref.clone())
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+
+    info!("Metrics server listening on {addr}");
+    println!("Metrics server listening on {addr}");
+
+    let _handle = tokio::spawn(async move {
+        if let Err(error) = server.await {
+            error!("Metrics server failed: {error}");
+        }
+    });
+
+    Ok(())
+}
+
+async fn serve_metrics(
+    request: Request<Body>,
+    node_ref: Arc<RwLock<NodeRef>>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        let mut not_found = Response::new(Body::empty());
+        *not_found.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(not_found);
+    }
+
+    // we don't hold the lock while gathering, as the node context is updated through it
+    let node = node_ref.read().await.clone();
+    let mut response = Response::new(Body::from(node.metrics()));
+    let _ = response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(METRICS_CONTENT_TYPE),
+    );
+    Ok(response)
+}
@@ -1,9 +1,16 @@
 use super::NodeCtrl;
 
+use sn_interface::{network_knowledge::SectionAuthorityProvider, types::DataAddress};
 use sn_node::node::NodeRef;
 
 use color_eyre::eyre::{ErrReport, Result};
-use std::{env, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::{
     mpsc::{self, Sender},
     RwLock,
@@ -11,12 +18,16 @@ use tokio::sync::{
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{transport::Server, Code, Request, Response, Status};
 use tracing::{debug, info, trace};
+use xor_name::{XorName, XOR_NAME_LEN};
 
 use safenode::safe_node_server::{SafeNode, SafeNodeServer};
 use safenode::{
-    NodeEvent, NodeEventsRequest, NodeInfoRequest, NodeInfoResponse, RestartRequest,
-    RestartResponse, SectionMember, SectionMembersRequest, SectionMembersResponse, StopRequest,
-    StopResponse, UpdateRequest, UpdateResponse,
+    DkgSession, DkgSessionsRequest, DkgSessionsResponse, DrainRequest, DrainResponse,
+    FaultScoresRequest, FaultScoresResponse, NetworkKnowledgeRequest, NetworkKnowledgeResponse,
+    NodeEvent, NodeEventsRequest, NodeFaultScores, NodeInfoRequest, NodeInfoResponse,
+    RestartRequest, RestartResponse, ScrubReportRequest, ScrubReportResponse, SectionAuthority,
+    SectionKey, SectionMember, SectionMembersRequest, SectionMembersResponse, StopRequest,
+    StopResponse, StoredData, StoredDataRequest, StoredDataResponse, UpdateRequest, UpdateResponse,
 };
 
 // this would include code generated from .proto file
@@ -25,6 +36,10 @@ mod safenode {
     tonic::include_proto!("safenode");
 }
 
+// Max number of data items returned in a page of the stored data, which keeps
+// the responses well within the max size of a gRPC message (4 MiB by default)
+const MAX_STORED_DATA_PAGE_SIZE: usize = 10_000;
+
 // Defining a struct to hold information used by our gRPC service backend
 struct SafeNodeRpcService {
     addr: SocketAddr,
@@ -89,6 +104,185 @@ impl SafeNode for SafeNodeRpcService {
         Ok(resp)
     }
 
+    async fn scrub_report(
+        &self,
+        request: Request<ScrubReportRequest>,
+    ) -> Result<Response<ScrubReportResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+        let stats = self.node_ref.read().await.context.scrub_stats();
+        let resp = Response::new(ScrubReportResponse {
+            completed_passes: stats.completed_passes,
+            verified: stats.verified,
+            quarantined: stats.quarantined,
+        });
+
+        Ok(resp)
+    }
+
+    async fn stored_data(
+        &self,
+        request: Request<StoredDataRequest>,
+    ) -> Result<Response<StoredDataResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+        let request = request.get_ref();
+        let names = request
+            .names
+            .iter()
+            .map(|bytes| xorname_from_bytes(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let offset = usize::try_from(request.offset).unwrap_or(usize::MAX);
+        let limit = match request.limit as usize {
+            0 => MAX_STORED_DATA_PAGE_SIZE,
+            limit => limit.min(MAX_STORED_DATA_PAGE_SIZE),
+        };
+
+        // we don't hold the lock while reading the data, as the node context is updated through it
+        let context = self.node_ref.read().await.context.clone();
+        let (page, total) = context.stored_data(&names, offset, limit).await;
+        let stored_data = page
+            .into_iter()
+            .map(|(address, size)| StoredData {
+                name: address.name().0.to_vec(),
+                data_type: data_type(&address).to_string(),
+                size: size as u64,
+            })
+            .collect();
+
+        Ok(Response::new(StoredDataResponse {
+            stored_data,
+            total: total as u64,
+        }))
+    }
+
+    async fn network_knowledge(
+        &self,
+        request: Request<NetworkKnowledgeRequest>,
+    ) -> Result<Response<NetworkKnowledgeResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+        let context = &self.node_ref.read().await.context;
+        let network_knowledge = context.network_knowledge();
+        let section_tree = network_knowledge.section_tree();
+        let sections_dag = section_tree.get_sections_dag();
+
+        let mut dag_keys = vec![];
+        for key in sections_dag.keys() {
+            let parent_key = sections_dag.get_parent_key(&key).map_err(|err| {
+                Status::new(
+                    Code::Internal,
+                    format!("Failed to read the sections DAG: {err}"),
+                )
+            })?;
+            dag_keys.push(SectionKey {
+                key: key.to_bytes().to_vec(),
+                parent_key: parent_key.map(|parent_key| parent_key.to_bytes().to_vec()),
+            });
+        }
+
+        let resp = Response::new(NetworkKnowledgeResponse {
+            section_auth: Some(section_authority(&network_knowledge.section_auth())),
+            section_tree: section_tree.all().map(section_authority).collect(),
+            sections_dag: dag_keys,
+            membership_generation: context.membership_generation(),
+        });
+
+        Ok(resp)
+    }
+
+    async fn dkg_sessions(
+        &self,
+        request: Request<DkgSessionsRequest>,
+    ) -> Result<Response<DkgSessionsResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+        let dkg_sessions = self
+            .node_ref
+            .read()
+            .await
+            .context
+            .pending_dkg_sessions()
+            .into_iter()
+            .map(|session_id| DkgSession {
+                prefix: format!("{}", session_id.prefix),
+                elders: session_id
+                    .elders
+                    .iter()
+                    .map(|(name, addr)| SectionMember {
+                        node_name: name.0.to_vec(),
+                        is_elder: true,
+                        addr: format!("{addr}"),
+                    })
+                    .collect(),
+                section_chain_len: session_id.section_chain_len,
+                membership_generation: session_id.membership_gen,
+            })
+            .collect();
+
+        Ok(Response::new(DkgSessionsResponse { dkg_sessions }))
+    }
+
+    async fn fault_scores(
+        &self,
+        request: Request<FaultScoresRequest>,
+    ) -> Result<Response<FaultScoresResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+        let context = self.node_ref.read().await.context.clone();
+        let Some(scores) = context.fault_scores().await else {
+            return Err(Status::new(
+                Code::Internal,
+                "Failed to get the fault scores, the fault detection is not running".to_string(),
+            ));
+        };
+
+        let all_scores: [&BTreeMap<XorName, f32>; 6] = [
+            &scores.communication_scores,
+            &scores.dkg_scores,
+            &scores.elder_voting_scores,
+            &scores.knowledge_scores,
+            &scores.op_scores,
+            &scores.probe_scores,
+        ];
+        let nodes: BTreeSet<&XorName> =
+            all_scores.iter().flat_map(|scores| scores.keys()).collect();
+        let fault_scores = nodes
+            .into_iter()
+            .map(|node| {
+                let score = |node_scores: &BTreeMap<XorName, f32>| {
+                    node_scores.get(node).copied().unwrap_or_default()
+                };
+                NodeFaultScores {
+                    node_name: node.0.to_vec(),
+                    communication: score(&scores.communication_scores),
+                    dkg: score(&scores.dkg_scores),
+                    elder_voting: score(&scores.elder_voting_scores),
+                    knowledge: score(&scores.knowledge_scores),
+                    op: score(&scores.op_scores),
+                    probe: score(&scores.probe_scores),
+                }
+            })
+            .collect();
+
+        Ok(Response::new(FaultScoresResponse { fault_scores }))
+    }
+
     async fn node_events(
         &self,
         request: Request<NodeEventsRequest>,
@@ -146,6 +340,36 @@ impl SafeNode for SafeNodeRpcService {
         }
     }
 
+    async fn drain(
+        &self,
+        request: Request<DrainRequest>,
+    ) -> Result<Response<DrainResponse>, Status> {
+        trace!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+
+        let cause = if let Some(addr) = request.remote_addr() {
+            ErrReport::msg(format!(
+                "Node has been drained and stopped by an RPC request from {addr}."
+            ))
+        } else {
+            ErrReport::msg(
+                "Node has been drained and stopped by an RPC request from an unknown address.",
+            )
+        };
+
+        let timeout = Duration::from_millis(request.get_ref().timeout_millis);
+        match self.ctrl_tx.send(NodeCtrl::Drain { timeout, cause }).await {
+            Ok(()) => Ok(Response::new(DrainResponse {})),
+            Err(err) => Err(Status::new(
+                Code::Internal,
+                format!("Failed to drain the node: {err}"),
+            )),
+        }
+    }
+
     async fn restart(
         &self,
         request: Request<RestartRequest>,
@@ -187,6 +411,40 @@ impl SafeNode for SafeNodeRpcService {
     }
 }
 
+fn section_authority(sap: &SectionAuthorityProvider) -> SectionAuthority {
+    SectionAuthority {
+        prefix: format!("{}", sap.prefix()),
+        section_key: sap.section_key().to_bytes().to_vec(),
+        elders: sap
+            .elders()
+            .map(|node_id| SectionMember {
+                node_name: node_id.name().0.to_vec(),
+                is_elder: true,
+                addr: format!("{}", node_id.addr()),
+            })
+            .collect(),
+    }
+}
+
+fn data_type(address: &DataAddress) -> &'static str {
+    match address {
+        DataAddress::Bytes(_) => "chunk",
+        DataAddress::Register(_) => "register",
+        DataAddress::Spentbook(_) => "spentbook",
+        DataAddress::SafeKey(_) => "safekey",
+    }
+}
+
+fn xorname_from_bytes(bytes: &[u8]) -> Result<XorName, Status> {
+    let name: [u8; XOR_NAME_LEN] = bytes.try_into().map_err(|_| {
+        Status::new(
+            Code::InvalidArgument,
+            format!("Invalid data name of {} bytes", bytes.len()),
+        )
+    })?;
+    Ok(XorName(name))
+}
+
 pub(super) fn start_rpc_service(
     addr: SocketAddr,
     log_dir: String,
@@ -10,9 +10,10 @@ use crate::node::{
     cfg::keypair_storage::{
         get_reward_secret_key, store_network_keypair, store_new_reward_keypair,
     },
-    flow_ctrl::{fault_detection::FaultsCmd, CmdCtrl, FlowCtrl},
+    flow_ctrl::{cmds::Cmd, fault_detection::FaultsCmd, CmdCtrl, FlowCtrl},
     logging::log_system_details,
-    CmdChannel, Config, Error, MyNode, NodeContext, NodeEventsChannel, Result,
+    rate_limiter::{ClientRateLimiter, ClientRateLimits},
+    CmdChannel, Config, DataStorage, Error, MyNode, NodeContext, NodeEventsChannel, Result,
     STANDARD_CHANNEL_SIZE,
 };
 use crate::UsedSpace;
@@ -23,7 +24,11 @@ use sn_interface::{
     types::{keys::ed25519, log_markers::LogMarker},
 };
 
-use std::{path::Path, sync::Arc, time::Duration};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::{fs, sync::mpsc};
 use xor_name::Prefix;
 
@@ -36,9 +41,16 @@ use xor_name::Prefix;
 // set to GENESIS_DBC_AMOUNT (currently 4,525,524,120 * 10^9) individual units.
 const GENESIS_DBC_FILENAME: &str = "genesis_dbc";
 
+// Interval at which the progress of the drain of our node is checked
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Time given to our node to announce its leave once the data it held was handed off
+const LEAVE_ANNOUNCE_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// A reference to data structures to receive node's events,
 /// and submit commands to be processed by it.
 #[allow(missing_debug_implementations)]
+#[derive(Clone)]
 pub struct NodeRef {
     /// Sender which can be used to add a Cmd to the Node's CmdQueue
     pub cmd_channel: CmdChannel,
@@ -48,6 +60,59 @@ pub struct NodeRef {
     pub context: NodeContext,
 }
 
+impl NodeRef {
+    /// Drains our node before it goes offline: the data it holds is handed off to the nodes
+    /// which become responsible for it, and then its voluntary leave is announced to the Elders.
+    ///
+    /// Returns once all the data handed off has been acknowledged by its new holders, or
+    /// the timeout has expired, and the leave announced, with the number of data items
+    /// left unacknowledged.
+    pub async fn drain(&self, timeout: Duration) -> Result<usize> {
+        self.cmd_channel
+            .send(Cmd::Drain)
+            .await
+            .map_err(|_| Error::CmdCtrlChannelDropped)?;
+
+        let tracker = &self.context.drain_tracker;
+        let started = Instant::now();
+        let unacknowledged = loop {
+            match tracker.unacknowledged() {
+                Some(0) => {
+                    info!("{:?}", LogMarker::NodeDrainCompleted);
+                    break 0;
+                }
+                Some(unacknowledged) if started.elapsed() >= timeout => {
+                    warn!("{unacknowledged} data items handed off were not acknowledged in {timeout:?}");
+                    break unacknowledged;
+                }
+                None if started.elapsed() >= timeout => {
+                    warn!("Our node did not start draining in {timeout:?}");
+                    return Ok(self.context.data_storage.data_addrs().await.len());
+                }
+                _ => tokio::time::sleep(DRAIN_POLL_INTERVAL).await,
+            }
+        };
+
+        // the new holders can't acknowledge the data anymore once we are voted off
+        self.cmd_channel
+            .send(Cmd::AnnounceLeaving)
+            .await
+            .map_err(|_| Error::CmdCtrlChannelDropped)?;
+        let started = Instant::now();
+        while !tracker.is_leave_announced() && started.elapsed() < LEAVE_ANNOUNCE_TIMEOUT {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        Ok(unacknowledged)
+    }
+
+    /// Prepares our node to go offline cleanly, persisting the state which allows it to restart
+    /// faster, i.e. the space used by the data it holds, so it's not counted again.
+    pub fn prepare_shutdown(&self) {
+        self.context.data_storage.persist_used_space();
+    }
+}
+
 /// Start a new node.
 pub async fn new_node(config: &Config, join_retry_timeout: Duration) -> Result<NodeRef> {
     let root_dir_buf = config.root_dir()?;
@@ -64,10 +129,11 @@ pub async fn new_node(config: &Config, join_retry_timeout: Duration) -> Result<N
     };
 
     let used_space = UsedSpace::new(config.min_capacity(), config.max_capacity());
+    let data_storage = DataStorage::with_backend(root_dir, used_space, config.storage_backend())?;
 
     start_node(
         config,
-        used_space,
+        data_storage,
         root_dir,
         reward_secret_key,
         join_retry_timeout,
@@ -78,7 +144,7 @@ pub async fn new_node(config: &Config, join_retry_timeout: Duration) -> Result<N
 // Private helper to create a new node using the given config and bootstraps it to the network.
 async fn start_node(
     config: &Config,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     reward_secret_key: bls::SecretKey,
     join_retry_timeout: Duration,
@@ -89,10 +155,10 @@ async fn start_node(
     let events_channel = NodeEventsChannel::default();
     let (comm, incoming_msg_receiver) = Comm::new(config.local_addr(), config.first())?;
 
-    let node = if config.first().is_some() {
+    let mut node = if config.first().is_some() {
         start_genesis_node(
             comm,
-            used_space,
+            data_storage,
             root_storage_dir,
             reward_secret_key,
             fault_cmds_sender.clone(),
@@ -103,7 +169,7 @@ async fn start_node(
         let node = start_normal_node(
             config,
             comm,
-            used_space,
+            data_storage,
             root_storage_dir,
             reward_secret_key,
             fault_cmds_sender.clone(),
@@ -114,6 +180,17 @@ async fn start_node(
         node
     };
 
+    // Spentbooks stored as Registers by earlier versions are moved to the spentbook store
+    let _ = node
+        .data_storage
+        .migrate_register_spentbooks(&node.network_knowledge().section_chain())
+        .await;
+
+    node.client_rate_limiter = ClientRateLimiter::new(ClientRateLimits {
+        msgs_per_sec: config.client_msgs_per_sec(),
+        burst: config.client_msgs_burst(),
+    });
+
     let node_name = node.name();
     let context = node.context();
 
@@ -157,7 +234,7 @@ async fn start_node(
 
 async fn start_genesis_node(
     comm: Comm,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     reward_secret_key: bls::SecretKey,
     fault_cmds_sender: mpsc::Sender<FaultsCmd>,
@@ -180,7 +257,7 @@ async fn start_genesis_node(
         comm,
         keypair,
         reward_secret_key,
-        used_space.clone(),
+        data_storage,
         root_storage_dir.to_path_buf(),
         genesis_sk_set,
         fault_cmds_sender,
@@ -208,7 +285,7 @@ async fn start_genesis_node(
 async fn start_normal_node(
     config: &Config,
     comm: Comm,
-    used_space: UsedSpace,
+    data_storage: DataStorage,
     root_storage_dir: &Path,
     reward_secret_key: bls::SecretKey,
     fault_cmds_sender: mpsc::Sender<FaultsCmd>,
@@ -238,7 +315,7 @@ async fn start_normal_node(
         reward_secret_key,
         network_knowledge,
         None,
-        used_space.clone(),
+        data_storage,
         root_storage_dir.to_path_buf(),
         fault_cmds_sender,
         node_events_sender,
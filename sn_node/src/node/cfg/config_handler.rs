@@ -6,7 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{Error, Result};
+use crate::node::{
+    rate_limiter::{DEFAULT_CLIENT_MSGS_BURST, DEFAULT_CLIENT_MSGS_PER_SEC},
+    Error, Result, StorageBackendKind,
+};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -99,6 +102,20 @@ pub struct Config {
     /// non-local nodes).
     #[clap(long)]
     pub local_addr: Option<SocketAddr>,
+    /// Backend to store the node data with: "fs" to store each item in its own file, or "kv"
+    /// to store them in an embedded key-value store. Data stored with one of the backends is
+    /// not available to the other one.
+    #[clap(long, default_value = "fs")]
+    pub storage_backend: StorageBackendKind,
+    /// Average number of msgs per second accepted from each client when the node is an Elder,
+    /// where a client is identified by its public key as well as by its address. Msgs above the
+    /// limit are rejected, the client being told to back off. Defaults to 50.
+    #[clap(long)]
+    pub client_msgs_per_sec: Option<u32>,
+    /// Number of msgs accepted in a burst from each client when the node is an Elder,
+    /// above the average rate. Defaults to 200.
+    #[clap(long)]
+    pub client_msgs_burst: Option<u32>,
 }
 
 impl Config {
@@ -128,6 +145,14 @@ impl Config {
             ));
         }
 
+        if self.client_msgs_per_sec == Some(0) || self.client_msgs_burst == Some(0) {
+            return Err(Error::Configuration(
+                "The --client-msgs-per-sec and --client-msgs-burst arguments must be greater \
+                than zero, otherwise no client msg would ever be accepted."
+                    .to_string(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -175,6 +200,16 @@ impl Config {
         if config.local_addr.is_some() {
             self.local_addr = config.local_addr;
         }
+
+        self.storage_backend = config.storage_backend;
+
+        if config.client_msgs_per_sec.is_some() {
+            self.client_msgs_per_sec = config.client_msgs_per_sec;
+        }
+
+        if config.client_msgs_burst.is_some() {
+            self.client_msgs_burst = config.client_msgs_burst;
+        }
     }
 
     /// The address to be credited when this node farms `SafeCoin`.
@@ -202,6 +237,22 @@ impl Config {
         DEFAULT_MAX_CAPACITY
     }
 
+    /// Backend to store the node data with.
+    pub fn storage_backend(&self) -> StorageBackendKind {
+        self.storage_backend
+    }
+
+    /// Average number of msgs per second accepted from each client when the node is an Elder.
+    pub fn client_msgs_per_sec(&self) -> u32 {
+        self.client_msgs_per_sec
+            .unwrap_or(DEFAULT_CLIENT_MSGS_PER_SEC)
+    }
+
+    /// Number of msgs accepted in a burst from each client when the node is an Elder.
+    pub fn client_msgs_burst(&self) -> u32 {
+        self.client_msgs_burst.unwrap_or(DEFAULT_CLIENT_MSGS_BURST)
+    }
+
     /// Root directory for dbs and cached state. If not set, it defaults to
     /// `DEFAULT_ROOT_DIR_NAME` within the project's data directory (see `Config::root_dir` for the
     /// directories on each platform).
@@ -314,7 +365,7 @@ fn smoke() -> Result<()> {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 45;
+    let expected_size = 51;
 
     assert_eq!(bincode::serialize(&Config::default())?.len(), expected_size);
     Ok(())
@@ -36,6 +36,9 @@ impl MyNode {
             InvalidMsgReceived(msg_id) => {
                 trace!("Invalid msg {msg_id:?} received from {participant}");
             }
+            Transport(error) => {
+                trace!("Transport error with {participant}: {error}");
+            }
         }
         // Track comms issue if this is a node in our section.
         if self
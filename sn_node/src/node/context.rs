@@ -7,18 +7,21 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    dkg::DkgVoter, flow_ctrl::fault_detection::FaultsCmd, DataStorage, DkgSessionInfo, Membership,
+    dkg::DkgVoter, drain::DrainTracker, flow_ctrl::fault_detection::FaultsCmd,
+    rate_limiter::ClientRateLimiter, register_subscriptions::RegisterSubscriptions, DataStorage,
+    DkgSessionInfo, Membership, ScrubStats,
 };
 
 use ed25519_dalek::Keypair;
 use sn_comms::Comm;
-use sn_fault_detection::IssueType;
+use sn_fault_detection::{IssueType, ScoreResults};
 use sn_interface::{
+    messaging::system::DkgSessionId,
     network_knowledge::{MyNodeInfo, NetworkKnowledge, RelocationState, SectionKeysProvider},
-    types::keys::ed25519::Digest256,
+    types::{keys::ed25519::Digest256, DataAddress},
 };
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
-use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc::Sender, oneshot};
 use xor_name::XorName;
 
 /// Snapshot of node's state
@@ -45,6 +48,11 @@ pub struct NodeContext {
     #[debug(skip)]
     pub(crate) fault_cmds_sender: Sender<FaultsCmd>,
     pub(crate) relocation_state: RelocationState,
+    pub(crate) drain_tracker: DrainTracker,
+    #[debug(skip)]
+    pub(crate) client_rate_limiter: ClientRateLimiter,
+    #[debug(skip)]
+    pub(crate) register_subscriptions: RegisterSubscriptions,
 }
 
 impl NodeContext {
@@ -70,6 +78,75 @@ impl NodeContext {
         &self.network_knowledge
     }
 
+    /// Results of the re-verification of the data stored by our node.
+    pub fn scrub_stats(&self) -> ScrubStats {
+        self.data_storage.scrub_stats()
+    }
+
+    /// A page of the data stored by our node, in the order of their addresses, along with
+    /// its size in bytes, as recorded by the store, and the number of data items found across
+    /// all the pages. Only the data with the given names is looked up, unless no name is given.
+    pub async fn stored_data(
+        &self,
+        names: &[XorName],
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(DataAddress, usize)>, usize) {
+        let mut addresses: Vec<_> = self
+            .data_storage
+            .data_addrs()
+            .await
+            .into_iter()
+            .filter(|address| names.is_empty() || names.contains(address.name()))
+            .collect();
+        addresses.sort();
+
+        let mut stored_data = vec![];
+        for address in addresses.iter().skip(offset).take(limit) {
+            if let Some(size) = self.data_storage.data_size(address).await {
+                stored_data.push((*address, size));
+            }
+        }
+        (stored_data, addresses.len())
+    }
+
+    /// The generation of the section membership, if our node is an Elder.
+    pub fn membership_generation(&self) -> Option<u64> {
+        self.membership
+            .as_ref()
+            .map(|membership| membership.generation())
+    }
+
+    /// The DKG sessions our node is taking part in, which haven't completed yet.
+    pub fn pending_dkg_sessions(&self) -> Vec<DkgSessionId> {
+        self.dkg_sessions_info
+            .values()
+            .filter(|info| {
+                // sessions are kept around once completed, until the section changes
+                matches!(
+                    self.dkg_voter.reached_termination(&info.session_id),
+                    Ok(false)
+                )
+            })
+            .map(|info| info.session_id.clone())
+            .collect()
+    }
+
+    /// The current fault detection scores of the nodes tracked by our node,
+    /// or `None` if the fault detection is no longer running.
+    pub async fn fault_scores(&self) -> Option<ScoreResults> {
+        let (scores_sender, scores_receiver) = oneshot::channel();
+        if let Err(error) = self
+            .fault_cmds_sender
+            .send(FaultsCmd::GetScores(scores_sender))
+            .await
+        {
+            warn!("Could not send FaultsCmd through fault_cmds_sender: {error}");
+            return None;
+        }
+        scores_receiver.await.ok()
+    }
+
     /************ END OF Public API methods **************/
 
     /// Log an issue in dysfunction
@@ -0,0 +1,214 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Voluntary leave of our node, handing off the data it holds before it goes offline.
+
+use crate::node::{flow_ctrl::cmds::Cmd, MyNode, NodeContext};
+
+use sn_interface::{
+    data_copy_count,
+    messaging::system::{NodeEvent, NodeMsg},
+    types::{log_markers::LogMarker, DataAddress, NodeId, PublicKey},
+};
+
+use itertools::Itertools;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+use xor_name::XorName;
+
+// Max number of data items handed off to a holder in a single batch
+const DRAIN_BATCH_SIZE: usize = 25;
+
+// Data handed off to each holder, which it hasn't acknowledged yet
+type PendingHandOffs = BTreeMap<XorName, BTreeSet<DataAddress>>;
+
+/// The data handed off by our node while draining, which the new holders
+/// haven't acknowledged yet. `None` until the drain is started.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DrainTracker {
+    pending: Arc<Mutex<Option<PendingHandOffs>>>,
+    // whether the Elders were told about our leave, i.e. the hand-off is over
+    leave_announced: Arc<AtomicBool>,
+}
+
+impl DrainTracker {
+    /// Whether our node has started draining.
+    pub(crate) fn is_draining(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Number of data items handed off which are yet to be acknowledged by some holder,
+    /// or `None` if our node hasn't started draining.
+    pub(crate) fn unacknowledged(&self) -> Option<usize> {
+        self.lock()
+            .as_ref()
+            .map(|pending| pending.values().flatten().unique().count())
+    }
+
+    /// Whether our voluntary leave was announced to the Elders.
+    pub(crate) fn is_leave_announced(&self) -> bool {
+        self.leave_announced.load(Ordering::SeqCst)
+    }
+
+    /// Marks our voluntary leave as announced, returning whether it wasn't already.
+    fn mark_leave_announced(&self) -> bool {
+        !self.leave_announced.swap(true, Ordering::SeqCst)
+    }
+
+    /// Records the data handed off to each holder, starting the drain.
+    fn start(&self, handed_off: PendingHandOffs) {
+        *self.lock() = Some(handed_off);
+    }
+
+    /// Acknowledges the data handed off to the holder which it reports to hold.
+    pub(crate) fn acknowledge(&self, holder: &XorName, data_holder_has: &[DataAddress]) {
+        let mut pending = self.lock();
+        let Some(pending) = pending.as_mut() else {
+            return;
+        };
+        if let Some(data_addrs) = pending.get_mut(holder) {
+            data_addrs.retain(|addr| !data_holder_has.contains(addr));
+            if data_addrs.is_empty() {
+                let _ = pending.remove(holder);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<PendingHandOffs>> {
+        self.pending.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl MyNode {
+    /// Starts draining our node: the data we hold is handed off to the nodes closest to it,
+    /// other than ourselves.
+    ///
+    /// Our leave is only announced afterwards, see `announce_leaving`, as the new holders
+    /// acknowledge the data by messaging us, which they can't do once we are voted off.
+    pub(crate) async fn start_drain(context: &NodeContext) -> Vec<Cmd> {
+        if context.drain_tracker.is_draining() {
+            warn!("Our node is already draining");
+            return vec![];
+        }
+        info!("{:?}", LogMarker::NodeDrainStarted);
+
+        let members: Vec<NodeId> = context
+            .network_knowledge
+            .members()
+            .into_iter()
+            .filter(|node_id| node_id.name() != context.name)
+            .collect();
+
+        let mut handed_off: BTreeMap<NodeId, BTreeSet<DataAddress>> = BTreeMap::new();
+        for data in context.data_storage.data_addrs().await {
+            let holders = members
+                .iter()
+                .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                .take(data_copy_count());
+            for holder in holders {
+                let _ = handed_off.entry(*holder).or_default().insert(data);
+            }
+        }
+
+        context.drain_tracker.start(
+            handed_off
+                .iter()
+                .map(|(holder, data_addrs)| (holder.name(), data_addrs.clone()))
+                .collect(),
+        );
+
+        let mut cmds = vec![];
+        for (recipient, data_addrs) in handed_off {
+            debug!(
+                "Handing off {} data items to {recipient:?}",
+                data_addrs.len()
+            );
+            for data_batch in &data_addrs.into_iter().chunks(DRAIN_BATCH_SIZE) {
+                cmds.push(Cmd::EnqueueDataForReplication {
+                    recipient,
+                    data_batch: data_batch.collect(),
+                });
+            }
+        }
+
+        cmds
+    }
+
+    /// Tells the Elders about the voluntary leave of our node, once the data it handed off was
+    /// acknowledged, or the hand-off timed out, so that they vote it off.
+    pub(crate) fn announce_leaving(context: &NodeContext) -> Vec<Cmd> {
+        if !context.drain_tracker.is_draining() {
+            warn!("Not announcing our leave, as our node hasn't started draining");
+            return vec![];
+        }
+        if !context.drain_tracker.mark_leave_announced() {
+            return vec![];
+        }
+        info!("Announcing the voluntary leave of our node to the Elders");
+
+        vec![MyNode::send_to_elders(
+            context,
+            NodeMsg::NodeEvent(NodeEvent::Leaving {
+                node_id: PublicKey::from(context.keypair.public),
+            }),
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sn_interface::types::ChunkAddress;
+
+    #[test]
+    fn drain_tracker_counts_data_until_acknowledged_by_all_holders() {
+        let tracker = DrainTracker::default();
+        assert!(!tracker.is_draining());
+        assert_eq!(tracker.unacknowledged(), None);
+
+        let data_a = DataAddress::Bytes(ChunkAddress(xor_name::rand::random()));
+        let data_b = DataAddress::Bytes(ChunkAddress(xor_name::rand::random()));
+        let holder_1 = xor_name::rand::random();
+        let holder_2 = xor_name::rand::random();
+        tracker.start(BTreeMap::from([
+            (holder_1, BTreeSet::from([data_a, data_b])),
+            (holder_2, BTreeSet::from([data_b])),
+        ]));
+        assert!(tracker.is_draining());
+        assert_eq!(tracker.unacknowledged(), Some(2));
+
+        // a holder only acknowledges the data handed off to it
+        tracker.acknowledge(&holder_2, &[data_a, data_b]);
+        assert_eq!(tracker.unacknowledged(), Some(2));
+
+        tracker.acknowledge(&holder_1, &[data_b]);
+        assert_eq!(tracker.unacknowledged(), Some(1));
+
+        tracker.acknowledge(&holder_1, &[data_a]);
+        assert_eq!(tracker.unacknowledged(), Some(0));
+        assert!(tracker.is_draining());
+    }
+
+    #[test]
+    fn drain_tracker_announces_the_leave_only_once() {
+        let tracker = DrainTracker::default();
+        assert!(!tracker.is_leave_announced());
+
+        assert!(tracker.mark_leave_announced());
+        assert!(tracker.is_leave_announced());
+
+        assert!(!tracker.mark_leave_announced());
+        assert!(tracker.is_leave_announced());
+    }
+}
@@ -71,6 +71,12 @@ pub enum Error {
     InvalidFeeBlindedAmount,
     #[error("Too low amount for the transfer fee: {paid}. Min required: {required}.")]
     FeeTooLow { paid: Token, required: Token },
+    #[error("The payment for storing the data is missing.")]
+    MissingStorePayment,
+    #[error("Too low amount paid for storing data: {paid}. Min required: {required}.")]
+    StorePaymentTooLow { paid: Token, required: Token },
+    #[error("Invalid store payment: {0}")]
+    InvalidStorePayment(String),
     #[error("The secret key share is missing for public key {0:?}")]
     MissingSecretKeyShare(bls::PublicKey),
     #[error("Messaging protocol error: {0}")]
@@ -194,6 +200,14 @@ pub enum Error {
     /// Example: for section 10, the candidates are supposed to be 101, 100
     #[error("Invalid Section Prefix For Handover Split Candidate")]
     InvalidSectionPrefixForSplitCandidates,
+    /// Invalid Merge Candidates in Handover vote's SAP, or no merge with our sibling is pending
+    #[error("InvalidMergeCandidates")]
+    InvalidMergeCandidates,
+    /// Received an invalid section prefix when checking a merge handover candidate
+    /// The candidate's section prefix is supposed to be the parent of ours:
+    /// Example: for section 10, the candidate is supposed to be 1
+    #[error("Invalid Section Prefix For Handover Merge Candidate")]
+    InvalidSectionPrefixForMergeCandidate,
     /// Spentbook error
     #[error("Spentbook Error: {0}")]
     SpentbookError(String),
@@ -243,6 +257,11 @@ impl From<Error> for ErrorMsg {
             Error::SpentProofUnknownSectionKey(unknown_section_key) => {
                 ErrorMsg::SpentProofUnknownSectionKey(unknown_section_key)
             }
+            Error::MissingStorePayment => ErrorMsg::MissingStorePayment,
+            Error::StorePaymentTooLow { paid, required } => {
+                ErrorMsg::StorePaymentTooLow { paid, required }
+            }
+            Error::InvalidStorePayment(msg) => ErrorMsg::InvalidStorePayment(msg),
             Error::NetworkData(error) => error.into(),
             other => ErrorMsg::InvalidOperation(format!("Failed to perform operation: {other:?}")),
         }
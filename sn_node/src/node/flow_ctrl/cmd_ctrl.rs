@@ -11,12 +11,212 @@ use crate::node::{
     Error, MyNode, NodeEvent, STANDARD_CHANNEL_SIZE,
 };
 
-use sn_interface::types::{DataAddress, NodeId};
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use sn_interface::{
+    messaging::{system::NodeMsg, MsgKind, WireMsg},
+    types::{DataAddress, NodeId},
 };
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tokio::sync::mpsc::{channel, error::TryRecvError, Receiver, Sender};
+
+// Size of the queue of bulk cmds, smaller than the others so that data traffic
+// is pushed back on its senders well before it can pile up in memory
+const BULK_QUEUE_SIZE: usize = 10_000;
+// Number of times in a row a waiting cmd can be passed over in favour of cmds of other classes,
+// after which it is taken next, so that lower priority classes are never starved
+const STARVATION_LIMIT: usize = 16;
+
+/// Priority class of a cmd, which determines the queue it is scheduled on
+/// before being processed on the blocking cmd loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CmdPriority {
+    /// Cmds keeping the section alive: joins, relocations, membership, DKG, handover and AE.
+    Critical,
+    /// Cmds which are neither critical nor bulk.
+    Normal,
+    /// Data replication and client data traffic.
+    Bulk,
+}
+
+impl CmdPriority {
+    /// All the classes, from the highest priority to the lowest.
+    pub(crate) const ALL: [CmdPriority; 3] = [
+        CmdPriority::Critical,
+        CmdPriority::Normal,
+        CmdPriority::Bulk,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            CmdPriority::Critical => "critical",
+            CmdPriority::Normal => "normal",
+            CmdPriority::Bulk => "bulk",
+        }
+    }
+
+    /// The class of a msg received from the network, so that it's scheduled by it
+    /// from the moment it's received, ahead of the work it leads to.
+    /// It's told from the header alone, the payload being decoded only once the msg is handled.
+    pub(crate) fn of_msg(wire_msg: &WireMsg) -> Self {
+        match wire_msg.kind() {
+            MsgKind::AntiEntropy(_) | MsgKind::Node { is_join: true, .. } => CmdPriority::Critical,
+            MsgKind::Client { .. }
+            | MsgKind::DataResponse(_)
+            | MsgKind::Node { is_data: true, .. } => CmdPriority::Bulk,
+            MsgKind::Node { .. } => CmdPriority::Critical,
+        }
+    }
+
+    /// The class of a msg sent to us by another node.
+    pub(crate) fn of_node_msg(msg: &NodeMsg) -> Self {
+        match msg {
+            NodeMsg::NodeDataCmd(_) => CmdPriority::Bulk,
+            NodeMsg::NodeEvent(_) => CmdPriority::Normal,
+            _ => CmdPriority::Critical,
+        }
+    }
+
+    fn queue_size(&self) -> usize {
+        match self {
+            CmdPriority::Critical | CmdPriority::Normal => STANDARD_CHANNEL_SIZE,
+            CmdPriority::Bulk => BULK_QUEUE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueuedCmd {
+    cmd: Cmd,
+    enqueued_at: Instant,
+}
+
+/// Sender of cmds to the blocking cmd loop, or of the msgs received to be handled,
+/// each being put on the bounded queue of its priority class.
+#[derive(Clone, Debug)]
+pub struct CmdChannel {
+    queues: [Sender<QueuedCmd>; 3],
+}
+
+impl CmdChannel {
+    /// Enqueues the cmd, waiting for room if the queue of its class is full.
+    pub(crate) async fn send(&self, cmd: Cmd) -> Result<(), Error> {
+        let queue = &self.queues[cmd.priority() as usize];
+        queue
+            .send(QueuedCmd {
+                cmd,
+                enqueued_at: Instant::now(),
+            })
+            .await
+            .map_err(|_| Error::CmdChannelSendError)
+    }
+
+    /// Number of cmds waiting on the queue of each class.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn queue_depths(&self) -> impl Iterator<Item = (CmdPriority, usize)> + '_ {
+        CmdPriority::ALL
+            .into_iter()
+            .zip(&self.queues)
+            .map(|(priority, queue)| (priority, queue.max_capacity() - queue.capacity()))
+    }
+}
+
+/// The receiving end of the [`CmdChannel`], yielding the cmds by priority.
+///
+/// The cmd taken next is the one of the highest priority class, unless a cmd of another
+/// class has been passed over `STARVATION_LIMIT` times, in which case that one is taken.
+#[derive(Debug)]
+pub(crate) struct CmdQueues {
+    receivers: [Receiver<QueuedCmd>; 3],
+    // the next cmd of each class, taken off its queue to be compared with the others
+    heads: [Option<QueuedCmd>; 3],
+    // times the head of each class has been passed over
+    passed_over: [usize; 3],
+}
+
+impl CmdQueues {
+    pub(crate) fn new() -> (CmdChannel, Self) {
+        let (critical_tx, critical_rx) = channel(CmdPriority::Critical.queue_size());
+        let (normal_tx, normal_rx) = channel(CmdPriority::Normal.queue_size());
+        let (bulk_tx, bulk_rx) = channel(CmdPriority::Bulk.queue_size());
+        (
+            CmdChannel {
+                queues: [critical_tx, normal_tx, bulk_tx],
+            },
+            Self {
+                receivers: [critical_rx, normal_rx, bulk_rx],
+                heads: Default::default(),
+                passed_over: Default::default(),
+            },
+        )
+    }
+
+    /// Takes the next cmd, if any is waiting.
+    pub(crate) fn try_next(&mut self) -> Option<Cmd> {
+        for (receiver, head) in self.receivers.iter_mut().zip(&mut self.heads) {
+            if head.is_none() {
+                match receiver.try_recv() {
+                    Ok(queued) => *head = Some(queued),
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => {}
+                }
+            }
+        }
+        self.take_next()
+    }
+
+    /// Waits for the next cmd, returning `None` once all the senders have been dropped.
+    pub(crate) async fn next(&mut self) -> Option<Cmd> {
+        loop {
+            if let Some(cmd) = self.try_next() {
+                return Some(cmd);
+            }
+            // all heads are empty now, so we wait for a cmd of any class
+            let [critical, normal, bulk] = &mut self.receivers;
+            let (priority, queued) = tokio::select! {
+                Some(queued) = critical.recv() => (CmdPriority::Critical, queued),
+                Some(queued) = normal.recv() => (CmdPriority::Normal, queued),
+                Some(queued) = bulk.recv() => (CmdPriority::Bulk, queued),
+                else => return None,
+            };
+            self.heads[priority as usize] = Some(queued);
+        }
+    }
+
+    fn take_next(&mut self) -> Option<Cmd> {
+        let waiting: Vec<usize> = (0..self.heads.len())
+            .filter(|class| self.heads[*class].is_some())
+            .collect();
+        let next = waiting
+            .iter()
+            .find(|class| self.passed_over[**class] >= STARVATION_LIMIT)
+            .or_else(|| waiting.first())
+            .copied()?;
+
+        for class in 0..self.passed_over.len() {
+            if class == next || !waiting.contains(&class) {
+                self.passed_over[class] = 0;
+            } else {
+                self.passed_over[class] += 1;
+            }
+        }
+
+        let QueuedCmd { cmd, enqueued_at } = self.heads[next].take()?;
+        let priority = CmdPriority::ALL[next];
+        let delay = enqueued_at.elapsed();
+        trace!(
+            "Cmd of {} priority waited {delay:?} to be processed: {cmd:?}",
+            priority.label()
+        );
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_cmd_queueing_delay(priority, delay);
+
+        Some(cmd)
+    }
+}
 
 /// Takes care of spawning a new task for the processing of a cmd,
 /// collecting resulting cmds from it, and sending it back to the calling context,
@@ -46,14 +246,10 @@ impl CmdCtrl {
         &self,
         node: &mut MyNode,
         cmd: Cmd,
-        mut id: Vec<usize>,
         cmd_process_api: Sender<FlowCtrlCmd>,
     ) {
         let node_identifier = node.info().name();
-
-        if id.is_empty() {
-            id.push(self.id_counter.fetch_add(1, Ordering::SeqCst));
-        }
+        let id = vec![self.id_counter.fetch_add(1, Ordering::SeqCst)];
 
         trace!("Processing for {cmd:?}, id: {id:?}");
 
@@ -87,8 +283,13 @@ impl CmdCtrl {
                 });
             }
             Err(Error::RejoinRequired(reason)) => {
-                node.node_events_sender
-                    .broadcast(NodeEvent::RejoinRequired(reason));
+                if node.drain_tracker.is_draining() {
+                    // we are leaving on purpose, so we shall not rejoin
+                    info!("Not rejoining while our node is draining: {reason:?}");
+                } else {
+                    node.node_events_sender
+                        .broadcast(NodeEvent::RejoinRequired(reason));
+                }
             }
             Err(error) => warn!("Error when processing cmd: {:?}", error),
         }
@@ -100,3 +301,154 @@ impl CmdCtrl {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sn_interface::{
+        messaging::{
+            system::{NodeDataCmd, NodeMsg},
+            Dst, MsgId, NetworkMsg,
+        },
+        types::Participant,
+    };
+
+    use bls::SecretKey;
+    use eyre::Result;
+
+    async fn queues_with(cmds: Vec<Cmd>) -> Result<CmdQueues> {
+        let (channel, queues) = CmdQueues::new();
+        for cmd in cmds {
+            channel.send(cmd).await?;
+        }
+        Ok(queues)
+    }
+
+    fn priorities_of_next(queues: &mut CmdQueues, count: usize) -> Vec<CmdPriority> {
+        (0..count)
+            .filter_map(|_| queues.try_next())
+            .map(|cmd| cmd.priority())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn cmds_are_taken_by_priority() -> Result<()> {
+        let mut queues = queues_with(vec![
+            Cmd::Drain,
+            Cmd::SetJoinsAllowed(true),
+            Cmd::TryJoinNetwork,
+            Cmd::Drain,
+        ])
+        .await?;
+
+        assert_eq!(
+            priorities_of_next(&mut queues, 5),
+            vec![
+                CmdPriority::Critical,
+                CmdPriority::Normal,
+                CmdPriority::Bulk,
+                CmdPriority::Bulk
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lower_priority_cmds_are_not_starved() -> Result<()> {
+        let mut cmds = vec![Cmd::Drain];
+        cmds.extend((0..2 * STARVATION_LIMIT).map(|_| Cmd::TryJoinNetwork));
+        let mut queues = queues_with(cmds).await?;
+
+        let priorities = priorities_of_next(&mut queues, 2 * STARVATION_LIMIT + 1);
+        let bulk_position = priorities
+            .iter()
+            .position(|priority| *priority == CmdPriority::Bulk);
+        assert_eq!(bulk_position, Some(STARVATION_LIMIT));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn no_more_cmds_once_the_channel_is_dropped() -> Result<()> {
+        let mut queues = queues_with(vec![Cmd::SetJoinsAllowed(false)]).await?;
+
+        assert!(matches!(
+            queues.next().await,
+            Some(Cmd::SetJoinsAllowed(false))
+        ));
+        assert!(queues.next().await.is_none());
+        Ok(())
+    }
+
+    // The cmd to handle the msg as it's received from another node
+    fn received_node_msg(msg: NodeMsg) -> Result<Cmd> {
+        let sender = Participant::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+        let dst = Dst {
+            name: xor_name::rand::random(),
+            section_key: SecretKey::random().public_key(),
+        };
+        let kind = MsgKind::Node {
+            name: sender.name(),
+            is_join: msg.is_join(),
+            is_data: msg.is_data(),
+        };
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        Ok(Cmd::HandleMsg {
+            sender,
+            wire_msg: WireMsg::new_msg(MsgId::new(), payload, kind, dst),
+            send_stream: None,
+        })
+    }
+
+    #[test]
+    fn priority_of_a_received_msg_is_told_from_its_header() -> Result<()> {
+        let data_msg = received_node_msg(NodeMsg::NodeDataCmd(NodeDataCmd::ReplicateDataBatch(
+            vec![],
+        )))?;
+        assert_eq!(data_msg.priority(), CmdPriority::Bulk);
+        let membership_msg = received_node_msg(NodeMsg::MembershipVotes(vec![]))?;
+        assert_eq!(membership_msg.priority(), CmdPriority::Critical);
+
+        // the payload isn't even decoded for it
+        let Cmd::HandleMsg {
+            sender, wire_msg, ..
+        } = data_msg
+        else {
+            panic!("a received msg should be handled");
+        };
+        let undecodable = Cmd::HandleMsg {
+            sender,
+            wire_msg: WireMsg::new_msg(
+                MsgId::new(),
+                b"not a msg".to_vec().into(),
+                wire_msg.kind().clone(),
+                wire_msg.dst,
+            ),
+            send_stream: None,
+        };
+        assert_eq!(undecodable.priority(), CmdPriority::Bulk);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backlog_of_replication_msgs_does_not_delay_a_membership_msg() -> Result<()> {
+        let mut msgs = (0..100)
+            .map(|_| {
+                received_node_msg(NodeMsg::NodeDataCmd(NodeDataCmd::ReplicateDataBatch(
+                    vec![],
+                )))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        msgs.push(received_node_msg(NodeMsg::MembershipVotes(vec![]))?);
+        let mut queues = queues_with(msgs).await?;
+
+        let Some(Cmd::HandleMsg { wire_msg, .. }) = queues.next().await else {
+            panic!("a received msg should have been queued");
+        };
+        assert!(matches!(
+            wire_msg.into_msg()?,
+            NetworkMsg::Node(NodeMsg::MembershipVotes(_))
+        ));
+        Ok(())
+    }
+}
@@ -6,21 +6,22 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{messaging::Recipients, XorName};
+use crate::node::{flow_ctrl::cmd_ctrl::CmdPriority, messaging::Recipients, XorName};
 
-use qp2p::SendStream;
+use sn_comms::SendStream;
 use sn_consensus::Decision;
 use sn_fault_detection::IssueType;
 use sn_interface::{
     messaging::{
-        data::{ClientMsg, DataResponse},
+        data::{ClientMsg, DataResponse, SignedRegisterEdit},
         system::{NodeMsg, SectionSig, SectionSigned},
         AntiEntropyKind, AuthorityProof, ClientAuth, MsgId, NetworkMsg, WireMsg,
     },
     network_knowledge::{
-        NodeState, SectionAuthorityProvider, SectionKeyShare, SectionTreeUpdate, SectionsDAG,
+        NodeState, SectionAuthorityProvider, SectionKeyShare, SectionMergeAgreement,
+        SectionTreeUpdate, SectionsDAG,
     },
-    types::{ClientId, DataAddress, NodeId, Participant},
+    types::{ClientId, DataAddress, NodeId, Participant, RegisterAddress},
 };
 
 use custom_debug::Debug;
@@ -49,7 +50,7 @@ pub(crate) struct CmdJob {
 /// and prioritization, which is not something e.g. tokio tasks allow.
 /// In other words, it enables enhanced flow control.
 #[derive(Debug)]
-pub enum Cmd {
+pub(crate) enum Cmd {
     TryJoinNetwork,
     /// Validate `wire_msg` from `sender`.
     /// Holding the WireMsg that has been received from the network,
@@ -123,6 +124,12 @@ pub enum Cmd {
         sap2: SectionSigned<SectionAuthorityProvider>,
         sig2: SectionSig,
     },
+    /// Handle agree on the section merging ours and our sibling. This blocks node message processing until complete.
+    HandleSectionMergeAgreement {
+        sap: SectionSigned<SectionAuthorityProvider>,
+        sig: SectionSig,
+        merge_agreement: SectionSigned<SectionMergeAgreement>,
+    },
     /// Handle the outcome of a DKG session where we are one of the participants (that is, one of
     /// the proposed new elders).
     HandleDkgOutcome {
@@ -196,14 +203,29 @@ pub enum Cmd {
     },
     /// Performs serialisation and sends the msg to the nodes over a new bi-stream,
     /// awaiting for a response which is forwarded to the client.
+    /// A forwarded register edit is notified to its subscribers once the nodes applied it.
     SendAndForwardResponseToClient {
         wire_msg: WireMsg,
         targets: BTreeSet<NodeId>,
         client_stream: SendStream,
         client_id: ClientId,
+        register_edit: Option<SignedRegisterEdit>,
+    },
+    /// Performs serialisation and sends the subscription to the register holders over new
+    /// bi-streams, subscribing the client once they all responded it may read the register.
+    SendRegisterSubscriptionToHolders {
+        wire_msg: WireMsg,
+        targets: BTreeSet<NodeId>,
+        client_stream: SendStream,
+        client_id: ClientId,
+        address: RegisterAddress,
     },
     /// Proposes nodes as offline
     ProposeVoteNodesOffline(BTreeSet<XorName>),
+    /// Starts the voluntary leave of our node, handing off the data it holds
+    Drain,
+    /// Announces the voluntary leave of our node to the Elders, once its data was handed off
+    AnnounceLeaving,
 }
 
 impl Cmd {
@@ -254,6 +276,43 @@ impl Cmd {
         }
     }
 
+    /// The priority class the cmd is scheduled by, on the queues of the msgs received
+    /// and on the blocking cmd loop, so that data traffic doesn't hold back the cmds
+    /// keeping the section alive.
+    pub(crate) fn priority(&self) -> CmdPriority {
+        match self {
+            Cmd::HandleMsg { wire_msg, .. } => CmdPriority::of_msg(wire_msg),
+            Cmd::ProcessNodeMsg { msg, .. } => CmdPriority::of_node_msg(msg),
+            Cmd::TryJoinNetwork
+            | Cmd::ProcessAeMsg { .. }
+            | Cmd::UpdateCaller { .. }
+            | Cmd::UpdateCallerOnStream { .. }
+            | Cmd::HandleNodeOffAgreement { .. }
+            | Cmd::HandleMembershipDecision(_)
+            | Cmd::ProposeVoteNodesOffline(_)
+            | Cmd::HandleNewEldersAgreement { .. }
+            | Cmd::HandleNewSectionsAgreement { .. }
+            | Cmd::HandleSectionMergeAgreement { .. }
+            | Cmd::HandleDkgOutcome { .. } => CmdPriority::Critical,
+            Cmd::EnqueueDataForReplication { .. }
+            | Cmd::Drain
+            | Cmd::UpdateNetworkAndHandleValidClientMsg { .. } => CmdPriority::Bulk,
+            Cmd::HandleCommsError { .. }
+            | Cmd::TrackNodeIssue { .. }
+            | Cmd::AnnounceLeaving
+            | Cmd::SetJoinsAllowed(_)
+            | Cmd::SetJoinsAllowedUntilSplit(_) => CmdPriority::Normal,
+            // handled off-thread as soon as they're produced, so never queued
+            Cmd::ProcessClientMsg { .. }
+            | Cmd::SendMsg { .. }
+            | Cmd::SendMsgEnqueueAnyResponse { .. }
+            | Cmd::SendNodeMsgResponse { .. }
+            | Cmd::SendDataResponse { .. }
+            | Cmd::SendAndForwardResponseToClient { .. }
+            | Cmd::SendRegisterSubscriptionToHolders { .. } => CmdPriority::Normal,
+        }
+    }
+
     pub(crate) fn statemap_state(&self) -> sn_interface::statemap::State {
         use sn_interface::statemap::State;
         match self {
@@ -264,6 +323,7 @@ impl Cmd {
             | Cmd::SendNodeMsgResponse { .. }
             | Cmd::SendDataResponse { .. }
             | Cmd::SendAndForwardResponseToClient { .. }
+            | Cmd::SendRegisterSubscriptionToHolders { .. }
             | Cmd::HandleCommsError { .. } => State::Comms,
             Cmd::HandleMsg { .. } => State::HandleMsg,
             Cmd::ProcessNodeMsg { .. } => State::HandleMsg,
@@ -274,8 +334,11 @@ impl Cmd {
             Cmd::HandleNodeOffAgreement { .. } => State::Agreement,
             Cmd::HandleMembershipDecision(_) => State::Membership,
             Cmd::ProposeVoteNodesOffline(_) => State::Membership,
+            Cmd::Drain => State::Replication,
+            Cmd::AnnounceLeaving => State::Membership,
             Cmd::HandleNewEldersAgreement { .. } => State::Handover,
             Cmd::HandleNewSectionsAgreement { .. } => State::Handover,
+            Cmd::HandleSectionMergeAgreement { .. } => State::Handover,
             Cmd::HandleDkgOutcome { .. } => State::Dkg,
             Cmd::EnqueueDataForReplication { .. } => State::Replication,
             Cmd::SetJoinsAllowed { .. } => State::Data,
@@ -311,6 +374,7 @@ impl fmt::Display for Cmd {
             }
             Cmd::HandleNewEldersAgreement { .. } => write!(f, "HandleNewEldersAgreement"),
             Cmd::HandleNewSectionsAgreement { .. } => write!(f, "HandleNewSectionsAgreement"),
+            Cmd::HandleSectionMergeAgreement { .. } => write!(f, "HandleSectionMergeAgreement"),
             Cmd::HandleMembershipDecision(_) => write!(f, "HandleMembershipDecision"),
             Cmd::HandleDkgOutcome { .. } => write!(f, "HandleDkgOutcome"),
             Cmd::SendMsg { .. } => write!(f, "SendMsg"),
@@ -320,11 +384,16 @@ impl fmt::Display for Cmd {
             Cmd::SendAndForwardResponseToClient { .. } => {
                 write!(f, "SendAndForwardResponseToClient")
             }
+            Cmd::SendRegisterSubscriptionToHolders { .. } => {
+                write!(f, "SendRegisterSubscriptionToHolders")
+            }
             Cmd::EnqueueDataForReplication { .. } => write!(f, "EnqueueDataForReplication"),
             Cmd::TrackNodeIssue { name, issue } => {
                 write!(f, "TrackNodeIssue {name:?}, {issue:?}")
             }
             Cmd::ProposeVoteNodesOffline(_) => write!(f, "ProposeOffline"),
+            Cmd::Drain => write!(f, "Drain"),
+            Cmd::AnnounceLeaving => write!(f, "AnnounceLeaving"),
             Cmd::SetJoinsAllowed { .. } => write!(f, "SetJoinsAllowed"),
             Cmd::SetJoinsAllowedUntilSplit { .. } => write!(f, "SetJoinsAllowedUntilSplit"),
             Cmd::TryJoinNetwork => write!(f, "TryJoinNetwork"),
@@ -85,6 +85,14 @@ impl MyNode {
                 node.handle_new_sections_agreement(sap1, sig1, sap2, sig2)
                     .await?
             }
+            Cmd::HandleSectionMergeAgreement {
+                sap,
+                sig,
+                merge_agreement,
+            } => {
+                node.handle_section_merge_agreement(sap, sig, merge_agreement)
+                    .await?
+            }
             Cmd::HandleCommsError { participant, error } => {
                 trace!("Comms error {error}");
                 node.handle_comms_error(participant, error);
@@ -109,6 +117,8 @@ impl MyNode {
                 vec![]
             }
             Cmd::ProposeVoteNodesOffline(names) => node.cast_offline_proposals(&names)?,
+            Cmd::Drain => MyNode::start_drain(&context).await,
+            Cmd::AnnounceLeaving => MyNode::announce_leaving(&context),
             Cmd::SetJoinsAllowed(joins_allowed) => {
                 node.joins_allowed = joins_allowed;
                 vec![]
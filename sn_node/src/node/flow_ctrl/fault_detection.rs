@@ -8,9 +8,12 @@
 
 use crate::node::flow_ctrl::FlowCtrl;
 use crate::node::STANDARD_CHANNEL_SIZE;
-use sn_fault_detection::{FaultDetection, IssueType};
+use sn_fault_detection::{FaultDetection, IssueType, ScoreResults};
 use std::collections::BTreeSet;
-use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::{
+    mpsc::{self, Receiver, Sender},
+    oneshot,
+};
 use xor_name::XorName;
 
 pub(crate) struct FaultChannels {
@@ -21,10 +24,12 @@ pub(crate) struct FaultChannels {
 /// Set of cmds to interact with the `FaultDetection` module
 pub(crate) enum FaultsCmd {
     AddNode(XorName),
+    RemoveNode(XorName),
     UpdateNodes(BTreeSet<XorName>, BTreeSet<XorName>),
     TrackIssue(XorName, IssueType),
     UntrackIssue(XorName, IssueType),
     GetFaultyNodes,
+    GetScores(oneshot::Sender<ScoreResults>),
 }
 
 impl FlowCtrl {
@@ -39,6 +44,7 @@ impl FlowCtrl {
             while let Some(cmd) = fault_cmds_from_node.recv().await {
                 match cmd {
                     FaultsCmd::AddNode(node) => tracker.add_new_node(node),
+                    FaultsCmd::RemoveNode(node) => tracker.remove_node(&node),
                     FaultsCmd::UpdateNodes(adults, elders) => {
                         tracker.update_and_only_retain_members(adults, elders)
                     }
@@ -53,6 +59,10 @@ impl FlowCtrl {
                         };
                     }
                     FaultsCmd::GetFaultyNodes => {
+                        #[cfg(feature = "metrics")]
+                        crate::node::metrics::record_fault_scores(
+                            &tracker.calculate_scores(&tracker.all_current_nodes()),
+                        );
                         if let Err(error) =
                             fault_nodes_sender.send(tracker.get_faulty_nodes()).await
                         {
@@ -61,6 +71,12 @@ impl FlowCtrl {
                             );
                         }
                     }
+                    FaultsCmd::GetScores(scores_sender) => {
+                        let scores = tracker.calculate_scores(&tracker.all_current_nodes());
+                        if scores_sender.send(scores).is_err() {
+                            warn!("Could not send the fault scores, the requester is gone");
+                        }
+                    }
                 }
             }
         });
@@ -14,9 +14,10 @@ mod periodic_checks;
 
 #[cfg(test)]
 pub(crate) mod tests;
-pub(crate) use cmd_ctrl::CmdCtrl;
+pub(crate) use cmd_ctrl::{CmdChannel, CmdCtrl};
 
 use super::{DataStorage, Result};
+use cmd_ctrl::CmdQueues;
 use periodic_checks::PeriodicChecksTimestamps;
 
 use crate::node::{
@@ -26,7 +27,7 @@ use crate::node::{
         fault_detection::{FaultChannels, FaultsCmd},
     },
     messaging::Recipients,
-    CmdChannel, Error, MyNode, NodeContext, NodeEventsChannel, STANDARD_CHANNEL_SIZE,
+    Error, MyNode, NodeContext, NodeEventsChannel, STANDARD_CHANNEL_SIZE,
 };
 
 use sn_comms::{CommEvent, MsgReceived};
@@ -86,8 +87,10 @@ impl FlowCtrl {
         fault_cmds_channels: (Sender<FaultsCmd>, Receiver<FaultsCmd>),
     ) -> Result<CmdChannel> {
         let node_context = node.context();
-        let (blocking_cmd_sender_channel, mut blocking_cmds_receiver) =
-            mpsc::channel(STANDARD_CHANNEL_SIZE);
+        let (blocking_cmd_sender_channel, mut blocking_cmd_queues) = CmdQueues::new();
+        // msgs received are queued by priority before being handled,
+        // so that a backlog of data msgs doesn't hold back the ones keeping the section alive
+        let (incoming_msg_sender, incoming_msg_queues) = CmdQueues::new();
 
         // Our channel to process _all_ cmds. If it can, they are processed off thread with latest context,
         // otherwise they are sent to the blocking process channel
@@ -125,7 +128,7 @@ impl FlowCtrl {
         };
 
         // incoming events from comms
-        Self::handle_comm_events(incoming_msg_events, flow_ctrl_cmd_sender.clone());
+        Self::handle_comm_events(incoming_msg_events, incoming_msg_sender);
 
         // handle all incoming cmds, make decisions about pushing them off thread
         // or onto the blocking loop
@@ -133,6 +136,7 @@ impl FlowCtrl {
             node_context.clone(),
             flow_ctrl_cmd_sender.clone(),
             flow_ctrl_cmd_reciever,
+            incoming_msg_queues,
             blocking_cmd_sender_channel.clone(),
             node.node_events_sender.clone(),
         );
@@ -145,7 +149,7 @@ impl FlowCtrl {
                 join_retry_timeout,
                 flow_ctrl_cmd_sender.clone(), // sending of updates to context
                 context_updater_for_periodic.clone(), // sending of updates to context
-                &mut blocking_cmds_receiver,
+                &mut blocking_cmd_queues,
             )
             .await?;
 
@@ -158,7 +162,7 @@ impl FlowCtrl {
         let _handle = tokio::task::spawn(FlowCtrl::process_blocking_cmds(
             node,
             cmd_ctrl,
-            blocking_cmds_receiver,
+            blocking_cmd_queues,
             flow_ctrl_cmd_sender.clone(), // sending of updates to context
             context_updater_for_periodic.clone(), // sending of updates to context
         ));
@@ -182,7 +186,7 @@ impl FlowCtrl {
         join_retry_timeout: Duration,
         flow_ctrl_cmd_processing: Sender<FlowCtrlCmd>,
         context_udpated_for_periodics: Sender<FlowCtrlCmd>,
-        blocking_cmds_receiver: &mut Receiver<(Cmd, Vec<usize>)>,
+        blocking_cmd_queues: &mut CmdQueues,
     ) -> Result<MyNode> {
         let mut is_member = false;
         let preprocess_cmd_channel = self.preprocess_cmd_sender_channel.clone();
@@ -193,15 +197,10 @@ impl FlowCtrl {
 
         loop {
             // first do any pending processing
-            while let Ok((cmd, cmd_id)) = blocking_cmds_receiver.try_recv() {
+            while let Some(cmd) = blocking_cmd_queues.try_next() {
                 trace!("Taking cmd off stack: {cmd:?}");
                 cmd_ctrl
-                    .process_blocking_cmd_job(
-                        &mut node,
-                        cmd,
-                        cmd_id,
-                        preprocess_cmd_channel.clone(),
-                    )
+                    .process_blocking_cmd_job(&mut node, cmd, preprocess_cmd_channel.clone())
                     .await;
 
                 // update our context in flow ctrl cmd processor with each cmd
@@ -268,16 +267,16 @@ impl FlowCtrl {
     async fn process_blocking_cmds(
         mut node: MyNode,
         cmd_ctrl: CmdCtrl,
-        mut incoming_cmds_from_apis: Receiver<(Cmd, Vec<usize>)>,
+        mut incoming_cmds_from_apis: CmdQueues,
         cmd_processing: Sender<FlowCtrlCmd>,
         context_updater_for_periodics: Sender<FlowCtrlCmd>,
     ) -> Result<()> {
         // first do any pending processing
-        while let Some((cmd, cmd_id)) = incoming_cmds_from_apis.recv().await {
+        while let Some(cmd) = incoming_cmds_from_apis.next().await {
             trace!("Taking cmd off stack: {cmd:?}");
 
             cmd_ctrl
-                .process_blocking_cmd_job(&mut node, cmd, cmd_id, cmd_processing.clone())
+                .process_blocking_cmd_job(&mut node, cmd, cmd_processing.clone())
                 .await;
 
             let mut context = node.context();
@@ -371,6 +370,7 @@ impl FlowCtrl {
         context: NodeContext,
         flow_ctrl_cmd_sender: Sender<FlowCtrlCmd>,
         mut flow_ctrl_cmd_reciever: Receiver<FlowCtrlCmd>,
+        mut incoming_msg_queues: CmdQueues,
         blocking_cmd_channel: CmdChannel,
         node_events_sender: NodeEventsChannel,
     ) {
@@ -380,7 +380,14 @@ impl FlowCtrl {
         // TODO: make this handle cmds itself... and we either send to modifying loop
         // or here...
         let _handle = tokio::task::spawn(async move {
-            while let Some(cmd) = flow_ctrl_cmd_reciever.recv().await {
+            loop {
+                // context updates and the cmds we produced go first, then the msgs received by priority
+                let cmd = tokio::select! {
+                    biased;
+                    Some(cmd) = flow_ctrl_cmd_reciever.recv() => cmd,
+                    Some(cmd) = incoming_msg_queues.next() => FlowCtrlCmd::Handle(cmd),
+                    else => break,
+                };
                 let capacity = flow_ctrl_cmd_sender.capacity();
 
                 if capacity < 30 {
@@ -453,10 +460,11 @@ impl FlowCtrl {
         });
     }
 
-    /// Simple mapping of of CommEvents -> HandleMsg / HandleCommsError.
+    /// Simple mapping of of CommEvents -> HandleMsg / HandleCommsError,
+    /// queued by the priority of the msg received.
     fn handle_comm_events(
         mut incoming_msg_events: Receiver<CommEvent>,
-        flow_ctrl_cmd_sender: Sender<FlowCtrlCmd>,
+        incoming_msg_sender: CmdChannel,
     ) {
         let _handle = tokio::spawn(async move {
             while let Some(event) = incoming_msg_events.recv().await {
@@ -474,6 +482,9 @@ impl FlowCtrl {
                             trace_span!("handle_message", ?sender, msg_id = ?wire_msg.msg_id());
                         let _span_guard = span.enter();
 
+                        #[cfg(feature = "metrics")]
+                        crate::node::metrics::record_msg_received(wire_msg.kind());
+
                         Cmd::HandleMsg {
                             sender,
                             wire_msg,
@@ -482,7 +493,7 @@ impl FlowCtrl {
                     }
                 };
 
-                if let Err(e) = flow_ctrl_cmd_sender.send(FlowCtrlCmd::Handle(cmd)).await {
+                if let Err(e) = incoming_msg_sender.send(cmd).await {
                     warn!("MsgHandler event channel send failed: {e:?}");
                 }
             }
@@ -501,7 +512,7 @@ async fn handle_cmd(
 
     if let Some(blocking_cmd) = blocking_cmd {
         trace!("Sending cmd {blocking_cmd:?} onto blocking_cmd_channel channel");
-        if let Err(error) = blocking_cmd_channel.send((blocking_cmd, vec![])).await {
+        if let Err(error) = blocking_cmd_channel.send(blocking_cmd).await {
             error!("Error sending cmd onto blocking_cmd_channel channel {error:?}");
         }
         // early exit, no cmds produced here...
@@ -624,8 +635,9 @@ async fn process_cmd_non_blocking(
             targets,
             client_stream,
             client_id,
+            register_edit,
         } => {
-            MyNode::send_and_forward_response_to_client(
+            let response = MyNode::send_and_forward_response_to_client(
                 wire_msg,
                 context.comm.clone(),
                 context.network_knowledge.section_key(),
@@ -633,6 +645,38 @@ async fn process_cmd_non_blocking(
                 client_stream,
                 client_id,
             )?;
+            if let Some(edit) = register_edit {
+                new_cmds.extend(
+                    MyNode::notify_register_subscribers_once_applied(&context, &edit, response)
+                        .await,
+                );
+            }
+        }
+        Cmd::SendRegisterSubscriptionToHolders {
+            wire_msg,
+            targets,
+            client_stream,
+            client_id,
+            address,
+        } => {
+            let msg_id = wire_msg.msg_id();
+            let response = MyNode::send_and_await_response(
+                wire_msg,
+                context.comm.clone(),
+                context.network_knowledge.section_key(),
+                targets,
+            );
+            new_cmds.extend(
+                MyNode::handle_register_subscription_once_readable(
+                    &context,
+                    msg_id,
+                    address,
+                    client_id,
+                    client_stream,
+                    response,
+                )
+                .await,
+            );
         }
         Cmd::UpdateCaller {
             caller,
@@ -680,13 +724,13 @@ async fn process_cmd_non_blocking(
         } => {
             match msg {
                 NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(data)) => {
-                    // NB: We would only reach here if this is a Spentbook cmd.
+                    // NB: We would only reach here if this is a Spentbook cmd, or paid data forwarded by an Elder.
                     trace!(
                         "Attempting to store data locally (off-thread): {:?}",
                         data.address()
                     );
                     let Some(stream) = send_stream else {
-                        return Err(Error::NoClientResponseStream)
+                        return Err(Error::NoClientResponseStream);
                     };
                     // NB!! `sender` is actually a node here and should not be casted to ClientId! But since we are reusing the
                     // `store_data_and_respond` fn which is used when a forwarded client cmd comes in, we have to cast to ClientId here.. TO BE FIXED.
@@ -711,6 +755,10 @@ async fn process_cmd_non_blocking(
                         LogMarker::RequestForAnyMissingData,
                         msg_id
                     );
+                    // while draining, this is how the new holders acknowledge the data handed off
+                    context
+                        .drain_tracker
+                        .acknowledge(&node_id.name(), &known_data_addresses);
 
                     if let Some(cmd) =
                         MyNode::get_missing_data_for_node(&context, node_id, known_data_addresses)
@@ -719,6 +767,20 @@ async fn process_cmd_non_blocking(
                         new_cmds.push(cmd);
                     }
                 }
+                NodeMsg::NodeDataCmd(NodeDataCmd::SignRegisterSnapshot(log)) => {
+                    info!("SignRegisterSnapshot MsgId: {:?}", msg_id);
+                    if let Some(cmd) = MyNode::sign_register_snapshot(&context, log, node_id)? {
+                        new_cmds.push(cmd);
+                    }
+                }
+                NodeMsg::NodeDataCmd(NodeDataCmd::RegisterSnapshotShare {
+                    snapshot,
+                    sig_share,
+                }) => {
+                    info!("RegisterSnapshotShare MsgId: {:?}", msg_id);
+                    MyNode::aggregate_register_snapshot(&context, snapshot, sig_share, node_id)
+                        .await?;
+                }
                 NodeMsg::DkgAE(session_id) => {
                     trace!("Handling msg: DkgAE s{} from {}", session_id.sh(), node_id);
                     let cmd = MyNode::handle_dkg_anti_entropy_request(
@@ -29,6 +29,12 @@ const FAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 // Which should hopefully trigger fault if we're not getting responses back
 // const ADULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 const ELDER_PROBE_INTERVAL: Duration = Duration::from_secs(10);
+// Each round verifies a small batch of the data stored, so that scrubbing doesn't
+// compete with the handling of client requests for disk access.
+const DATA_SCRUB_INTERVAL: Duration = Duration::from_secs(10);
+const DATA_SCRUB_BATCH_SIZE: usize = 20;
+// How often the Elders are asked to sign snapshots of the Registers whose log grew too long
+const REGISTER_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub(super) struct PeriodicChecksTimestamps {
     last_probe: Instant,
@@ -40,6 +46,8 @@ pub(super) struct PeriodicChecksTimestamps {
     last_fault_check: Instant,
     request_to_relocate_check: Instant,
     join_as_relocated_check: Instant,
+    last_data_scrub: Instant,
+    last_register_snapshot: Instant,
 }
 
 impl PeriodicChecksTimestamps {
@@ -54,6 +62,8 @@ impl PeriodicChecksTimestamps {
             last_fault_check: Instant::now(),
             request_to_relocate_check: Instant::now(),
             join_as_relocated_check: Instant::now(),
+            last_data_scrub: Instant::now(),
+            last_register_snapshot: Instant::now(),
         }
     }
 
@@ -65,6 +75,8 @@ impl PeriodicChecksTimestamps {
             || self.last_fault_check.elapsed() > FAULT_CHECK_INTERVAL
             || self.request_to_relocate_check.elapsed() > REQUEST_TO_RELOCATE_TIMEOUT_SEC
             || self.join_as_relocated_check.elapsed() > JOIN_AS_RELOCATED_TIMEOUT_SEC
+            || self.last_data_scrub.elapsed() > DATA_SCRUB_INTERVAL
+            || self.last_register_snapshot.elapsed() > REGISTER_SNAPSHOT_INTERVAL
     }
 }
 
@@ -105,6 +117,16 @@ impl FlowCtrl {
             }
         }
 
+        if self.timestamps.last_data_scrub.elapsed() > DATA_SCRUB_INTERVAL {
+            self.timestamps.last_data_scrub = Instant::now();
+            Self::scrub_stored_data(context, self.preprocess_cmd_sender_channel.clone());
+        }
+
+        if self.timestamps.last_register_snapshot.elapsed() > REGISTER_SNAPSHOT_INTERVAL {
+            self.timestamps.last_register_snapshot = Instant::now();
+            Self::snapshot_registers(context, self.preprocess_cmd_sender_channel.clone());
+        }
+
         // check if we can request for relocation
         // The relocation_state will be changed into `JoinAsRelocated` once the request has been
         // approved by the section
@@ -340,6 +362,53 @@ impl FlowCtrl {
         }
     }
 
+    /// Verifies the next batch of the data stored, asking the other holders
+    /// for fresh copies of any data found corrupt, and thus quarantined.
+    fn scrub_stored_data(context: &NodeContext, sender_channel: Sender<FlowCtrlCmd>) {
+        let context = context.clone();
+        // move the scrubbing off thread to not block on disk access
+        let _handle = tokio::spawn(async move {
+            let quarantined = match context.data_storage.scrub_next(DATA_SCRUB_BATCH_SIZE).await {
+                Some(quarantined) => quarantined,
+                None => {
+                    trace!("Previous data scrub round still in progress");
+                    return;
+                }
+            };
+            debug!(
+                "{:?}: {} item/s quarantined, {:?}",
+                LogMarker::DataScrubRoundCompleted,
+                quarantined.len(),
+                context.data_storage.scrub_stats()
+            );
+
+            if quarantined.is_empty() {
+                return;
+            }
+            if let Some(cmd) =
+                MyNode::ask_holders_for_quarantined_data(&context, &quarantined).await
+            {
+                if let Err(error) = sender_channel.send(FlowCtrlCmd::Handle(cmd)).await {
+                    error!("Error asking for fresh copies of the data quarantined: {error:?}");
+                }
+            }
+        });
+    }
+
+    /// Asks the Elders to sign snapshots of the Registers whose log grew too long,
+    /// for the cmds they are built from to no longer be stored and replicated.
+    fn snapshot_registers(context: &NodeContext, sender_channel: Sender<FlowCtrlCmd>) {
+        let context = context.clone();
+        // move the reading of the logs off thread to not block on disk access
+        let _handle = tokio::spawn(async move {
+            for cmd in MyNode::ask_elders_to_sign_register_snapshots(&context).await {
+                if let Err(error) = sender_channel.send(FlowCtrlCmd::Handle(cmd)).await {
+                    error!("Error asking for snapshots of Registers to be signed: {error:?}");
+                }
+            }
+        });
+    }
+
     async fn vote_out_faulty_nodes(&mut self) -> Vec<Cmd> {
         info!("Voting out faulty nodes");
         let mut cmds = vec![];
@@ -6,14 +6,23 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::node::NodeContext;
+
 use sn_dbc::{
-    Dbc, DbcTransaction, Owner, OwnerOnce, PublicKey, SpentProof, SpentProofShare, Token,
-    TransactionBuilder,
+    get_blinded_amounts_from_transaction, Dbc, DbcTransaction, Hash, Owner, OwnerOnce, PublicKey,
+    SpentProof, SpentProofShare, Token, TransactionBuilder,
+};
+use sn_interface::{
+    network_knowledge::section_keys::build_spent_proof_share,
+    types::{
+        fees::{FeeCiphers, PaymentProof},
+        ReplicatedData,
+    },
 };
-use sn_interface::{messaging::data::RegisterCmd, types::ReplicatedData};
 
 use eyre::{eyre, Result};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use xor_name::XorName;
 
 /// Get the spent proof share that's packaged inside the data that's to be replicated to the adults
 /// in the section.
@@ -21,16 +30,9 @@ pub(crate) fn get_spent_proof_share_from_replicated_data(
     replicated_data: ReplicatedData,
 ) -> Result<SpentProofShare> {
     match replicated_data {
-        ReplicatedData::SpentbookWrite(reg_cmd) => match reg_cmd {
-            RegisterCmd::Edit(signed_edit) => {
-                let entry = signed_edit.op.edit.crdt_op.value;
-                let spent_proof_share: SpentProofShare = rmp_serde::from_slice(&entry)?;
-                Ok(spent_proof_share)
-            }
-            _ => Err(eyre!("A RegisterCmd::Edit variant was expected")),
-        },
+        ReplicatedData::SpentProofShare(spent_proof_share) => Ok(spent_proof_share),
         _ => Err(eyre!(
-            "A ReplicatedData::SpentbookWrite variant was expected"
+            "A ReplicatedData::SpentProofShare variant was expected"
         )),
     }
 }
@@ -96,3 +98,82 @@ pub(crate) fn reissue_invalid_dbc_with_no_inputs(
         .ok_or_else(|| eyre!("At least one output DBC should have been generated"))?;
     Ok(output_dbc)
 }
+
+/// Pay the given amount to the node of the given context, for storing the given content.
+///
+/// The input DBC is spent using the section keys of the node, so its spent proof is signed
+/// by a key known to the node (given a section with a threshold of 0).
+pub(crate) fn gen_store_payment(
+    input: &Dbc,
+    content: BTreeSet<XorName>,
+    amount: Token,
+    context: &NodeContext,
+) -> Result<PaymentProof> {
+    let input_amount = Token::from_nano(input.revealed_amount_bearer()?.value());
+    let change_amount = input_amount
+        .checked_sub(amount)
+        .ok_or_else(|| eyre!("The input amount minus the amount must evaluate to a valid value"))?;
+
+    let mut rng = rand::thread_rng();
+    let reward_pk = context.reward_secret_key.public_key();
+    let fee_owner = OwnerOnce::from_owner_base(Owner::from(reward_pk), &mut rng);
+    let dbc_builder = TransactionBuilder::default()
+        .add_input_dbc_bearer(input)?
+        .add_output_by_amount(amount, fee_owner.clone())
+        .add_output_by_amount(
+            change_amount,
+            OwnerOnce::from_owner_base(input.owner_base().clone(), &mut rng),
+        )
+        .build(&mut rng)?;
+
+    let reason = PaymentProof::reason_for(&content);
+    let sap = context.network_knowledge.section_auth();
+    let mut spent_proofs = BTreeSet::new();
+    let mut payment_tx = None;
+    for (public_key, tx) in dbc_builder.inputs() {
+        let blinded_amount = get_blinded_amounts_from_transaction(
+            &tx,
+            &input.inputs_spent_proofs,
+            &input.inputs_spent_transactions,
+        )?
+        .into_iter()
+        .find(|(k, _c)| k == &public_key)
+        .map(|(_k, c)| c)
+        .ok_or_else(|| eyre!("Found no blinded amount for Tx input with pubkey: {public_key:?}"))?;
+
+        let share = build_spent_proof_share(
+            &public_key,
+            &tx,
+            reason,
+            &sap,
+            &context.section_keys_provider,
+            blinded_amount,
+        )?;
+        let spent_proof = SpentProof::try_from_proof_shares(
+            public_key,
+            Hash::from(tx.hash()),
+            &HashSet::from([share]),
+        )?;
+        let _ = spent_proofs.insert(spent_proof);
+        payment_tx = Some(tx);
+    }
+    let tx = payment_tx.ok_or_else(|| eyre!("The payment should have had an input"))?;
+
+    let fee_owner_pk = fee_owner.as_owner().public_key();
+    let fee_output = dbc_builder
+        .revealed_outputs
+        .iter()
+        .find(|out| out.public_key == fee_owner_pk)
+        .ok_or_else(|| eyre!("Didn't find the fee output"))?;
+    let fee_ciphers = FeeCiphers::new(
+        fee_output.revealed_amount.encrypt(&fee_owner_pk),
+        reward_pk.encrypt(fee_owner.derivation_index),
+    );
+
+    Ok(PaymentProof {
+        content,
+        tx,
+        spent_proofs,
+        fee_ciphers: BTreeMap::from([(context.name, fee_ciphers)]),
+    })
+}
@@ -14,10 +14,13 @@ pub(crate) mod test_utils;
 use crate::node::{
     flow_ctrl::tests::{
         network_builder::TestNetworkBuilder,
-        test_utils::{gen_info_with_comm, gen_node_infos_with_comm},
+        test_utils::{
+            gen_info_with_comm, gen_info_with_comm_supporting, gen_node_infos_with_comm,
+            send_client_msg_to_node, single_section_network,
+        },
     },
     messaging::Recipients,
-    Cmd, Error, MyNode, NodeContext,
+    register_subscriptions, Cmd, Error, MyNode, NodeContext,
 };
 
 use sn_comms::{CommEvent, MsgReceived};
@@ -27,28 +30,41 @@ use sn_interface::{
     elder_count, init_logger,
     messaging::{
         data::{
-            ClientMsg, CmdResponse, DataCmd, DataResponse, Error as MessagingDataError,
-            SpentbookCmd,
+            ClientMsg, CmdResponse, DataCmd, DataQuery, DataResponse, Error as MessagingDataError,
+            QueryResponse, RegisterCmd, RegisterQuery, SpentbookCmd,
         },
         system::{JoinResponse, NodeDataCmd, NodeMsg},
-        AntiEntropyKind, AntiEntropyMsg, Dst, NetworkMsg, WireMsg,
+        AntiEntropyKind, AntiEntropyMsg, Dst, NetworkMsg, ProtocolVersions, WireMsg,
     },
     network_knowledge::{
         section_keys::SectionKeysProvider, Error as NetworkKnowledgeError, MyNodeInfo, NodeState,
-        RelocationInfo, RelocationProof, SectionTreeUpdate, SectionsDAG, MIN_ADULT_AGE,
+        RelocationInfo, RelocationProof, SectionAuthUtils, SectionAuthorityProvider,
+        SectionMergeAgreement, SectionTreeUpdate, SectionsDAG, MIN_ADULT_AGE,
     },
     test_utils::*,
-    types::{fees::FeeCiphers, keys::ed25519, Participant, PublicKey},
+    types::{
+        fees::{FeeCiphers, MAX_PAID_ITEMS},
+        keys::ed25519,
+        register::User,
+        Chunk, ClientId, NodeId, Participant, PublicKey, ReplicatedData,
+    },
 };
 
 use assert_matches::assert_matches;
+use bytes::Bytes;
 use eyre::{bail, eyre, Result};
+use qp2p::UsrMsgBytes;
 use rand::{rngs::StdRng, thread_rng, SeedableRng};
 use std::{
-    collections::{BTreeMap, BTreeSet, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
     iter,
+    time::Duration,
 };
 use test_utils::ProcessAndInspectCmds;
+use tokio::{
+    sync::{mpsc::Receiver, oneshot},
+    time::timeout,
+};
 use xor_name::{Prefix, XorName};
 
 #[tokio::test]
@@ -323,8 +339,11 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
     let _span = info_span!("ae_msg_from_the_future_is_handled").entered();
 
     let prefix = Prefix::default();
-    let (elders0, ..) = gen_node_infos_with_comm(&prefix, elder_count(), 0, Some(&[6]), None);
-    let new_elder = gen_info_with_comm(MIN_ADULT_AGE, Some(prefix));
+    let builder = TestNetworkBuilder::new(thread_rng());
+    let network = builder.memory_network();
+    let (elders0, ..) =
+        gen_node_infos_with_comm(network, &prefix, elder_count(), 0, Some(&[6]), None);
+    let new_elder = gen_info_with_comm(network, MIN_ADULT_AGE, Some(prefix));
     let elders1 = elders0
         .clone()
         .into_iter()
@@ -333,7 +352,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
         .collect::<Vec<_>>();
 
     // SAP0 is succeeded by SAP1 with a change in elder list
-    let env = TestNetworkBuilder::new(thread_rng())
+    let env = builder
         .sap_with_members(prefix, elders0.clone(), elders0)
         .sap_with_members(prefix, elders1.clone(), elders1)
         .build()?;
@@ -500,10 +519,14 @@ async fn handle_elders_update() -> Result<()> {
     let prefix = Prefix::default();
     // Start with section that has `elder_count()` elders with age 6, 1 non-elder with age 5 and one
     // to-be-elder with age 7
-    let (elders0, ..) = gen_node_infos_with_comm(&prefix, elder_count(), 1, Some(&[6]), None);
+    let builder = TestNetworkBuilder::new(StdRng::seed_from_u64(123));
+    let network = builder.memory_network();
+    let (elders0, ..) =
+        gen_node_infos_with_comm(network, &prefix, elder_count(), 1, Some(&[6]), None);
     let mut elders1 = elders0.clone();
     let promoted_node = {
-        let (promoted_node, promoted_comm, _) = gen_info_with_comm(MIN_ADULT_AGE + 2, None);
+        let (promoted_node, promoted_comm, _) =
+            gen_info_with_comm(network, MIN_ADULT_AGE + 2, None);
         (promoted_node, promoted_comm)
     };
     // members list remain the same for the two SAPs
@@ -515,7 +538,7 @@ async fn handle_elders_update() -> Result<()> {
     let demoted_node = elders1.remove(elders1.len() - 1);
     elders1.push(promoted_node.clone());
 
-    let env = TestNetworkBuilder::new(StdRng::seed_from_u64(123))
+    let env = builder
         .sap_with_members(prefix, elders0, members.clone())
         .sap_with_members(prefix, elders1, members)
         .build()?;
@@ -598,10 +621,19 @@ async fn handle_demote_during_split() -> Result<()> {
     let prefix0 = prefix("0");
     let prefix1 = prefix("1");
 
+    let builder = TestNetworkBuilder::new(thread_rng());
+    let network = builder.memory_network();
+
     // `nodes_a` + `info` are pre-split elders.
     // `nodes_a` + `node_c` are prefix-0 post-split elders.
-    let (mut nodes_a, ..) =
-        gen_node_infos_with_comm(&prefix0, elder_count(), 0, Some(&[MIN_ADULT_AGE]), None);
+    let (mut nodes_a, ..) = gen_node_infos_with_comm(
+        network,
+        &prefix0,
+        elder_count(),
+        0,
+        Some(&[MIN_ADULT_AGE]),
+        None,
+    );
 
     let info = nodes_a
         .pop()
@@ -609,11 +641,17 @@ async fn handle_demote_during_split() -> Result<()> {
     let node_name = info.0.name();
 
     // `nodes_b` are prefix-1 post-split elders.
-    let (nodes_b, ..) =
-        gen_node_infos_with_comm(&prefix1, elder_count(), 0, Some(&[MIN_ADULT_AGE]), None);
+    let (nodes_b, ..) = gen_node_infos_with_comm(
+        network,
+        &prefix1,
+        elder_count(),
+        0,
+        Some(&[MIN_ADULT_AGE]),
+        None,
+    );
     // `node_c` is a prefix-0 post-split elder.
     let node_c = {
-        let (node_c, comm, _) = gen_info_with_comm(MIN_ADULT_AGE, Some(prefix0));
+        let (node_c, comm, _) = gen_info_with_comm(network, MIN_ADULT_AGE, Some(prefix0));
         (node_c, comm)
     };
     // all members
@@ -623,7 +661,7 @@ async fn handle_demote_during_split() -> Result<()> {
         .cloned()
         .chain([info.clone(), node_c.clone()]);
 
-    let env = TestNetworkBuilder::new(thread_rng())
+    let env = builder
         // pre-split section
         .sap_with_members(
             Prefix::default(),
@@ -703,6 +741,180 @@ async fn handle_demote_during_split() -> Result<()> {
     Ok(())
 }
 
+/// Test that the elders of a section which shrank below the minimum size propose to merge
+/// with their sibling section.
+#[tokio::test]
+async fn shrunk_section_proposes_merge_to_sibling() -> Result<()> {
+    init_logger();
+    let _span = tracing::info_span!("shrunk_section_proposes_merge_to_sibling").entered();
+    let prefix0 = prefix("0");
+    let prefix1 = prefix("1");
+    let env = merge_test_network()?;
+
+    let sap1 = env.get_sap(prefix1, None)?;
+    let mut node = env.get_nodes(prefix0, 1, 0, None)?.remove(0);
+    learn_section(&mut node, &env, prefix1)?;
+
+    let cmds = node.propose_section_merge()?;
+    assert_eq!(cmds.len(), 1);
+    assert_matches!(&cmds[0], Cmd::SendMsg {
+        msg: NetworkMsg::Node(NodeMsg::ProposeSectionMerge { section_tree_update, members, .. }),
+        recipients: Recipients::Multiple(recipients),
+        ..
+    } => {
+        assert_eq!(section_tree_update.signed_sap.prefix(), prefix0);
+        assert_eq!(members.len(), SHRUNK_SECTION_SIZE);
+        assert_eq!(recipients, &sap1.elders_set());
+    });
+
+    // the sibling section isn't below the minimum size, so it doesn't propose a merge
+    let mut node = env.get_nodes(prefix1, 1, 0, None)?.remove(0);
+    learn_section(&mut node, &env, prefix0)?;
+    assert!(node.propose_section_merge()?.is_empty());
+
+    Ok(())
+}
+
+/// Test that the elders of the sibling section start the DKG for the merged section once
+/// they aggregated the agreement of the shrunk section to merge.
+#[tokio::test]
+async fn sibling_starts_merge_dkg_on_aggregated_merge_proposal() -> Result<()> {
+    init_logger();
+    let _span =
+        tracing::info_span!("sibling_starts_merge_dkg_on_aggregated_merge_proposal").entered();
+    let prefix0 = prefix("0");
+    let prefix1 = prefix("1");
+    let env = merge_test_network()?;
+
+    let sk_set0 = env.get_secret_key_set(prefix0, None)?;
+    let network_knowledge0 = env.get_network_knowledge(prefix0, None)?;
+    let section_tree_update = network_knowledge0
+        .section_tree()
+        .generate_section_tree_update(&prefix0)?;
+    let members0 = network_knowledge0.section_members();
+    let mut node = env.get_nodes(prefix1, 1, 0, None)?.remove(0);
+    let members1 = node.network_knowledge().section_members();
+
+    let payload = bincode::serialize(&SectionMergeAgreement {
+        merged_prefix: Prefix::default(),
+        section_key: sk_set0.public_keys().public_key(),
+        members: members0.clone(),
+    })?;
+    let mut cmds = vec![];
+    for (index, elder) in network_knowledge0.elders().into_iter().enumerate() {
+        let key_share = TestKeys::get_section_key_share(&sk_set0, index);
+        let sig_share = MyNode::sign_with_key_share(&payload, &key_share);
+        cmds.extend(node.handle_section_merge_proposal(
+            sn_interface::messaging::MsgId::new(),
+            section_tree_update.clone(),
+            members0.clone(),
+            sig_share,
+            elder,
+        )?);
+    }
+
+    let session_ids: Vec<_> = cmds
+        .iter()
+        .filter_map(|cmd| match cmd {
+            Cmd::SendMsg {
+                msg: NetworkMsg::Node(NodeMsg::DkgStart(session_id, _)),
+                ..
+            } => Some(session_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(session_ids.len(), 1);
+    let session_id = session_ids[0];
+    assert_eq!(session_id.prefix, Prefix::default());
+    assert_eq!(
+        session_id.bootstrap_members,
+        members0.union(&members1).cloned().collect()
+    );
+    assert_eq!(session_id.elders.len(), elder_count());
+    // the aggregated agreement of the shrunk section is kept, to go along with the merged SAP
+    let merge_agreement = node
+        .pending_section_merge
+        .expect("merge agreement should be pending");
+    assert!(merge_agreement.self_verify());
+    assert_eq!(
+        merge_agreement.sig.public_key,
+        sk_set0.public_keys().public_key()
+    );
+    assert_eq!(merge_agreement.value.members, members0);
+
+    Ok(())
+}
+
+/// Test that the agreement on the merged section switches a node to the merged prefix, and
+/// that it asks the merged section for the data it's now responsible for.
+#[tokio::test]
+async fn handle_section_merge_agreement() -> Result<()> {
+    init_logger();
+    let _span = tracing::info_span!("handle_section_merge_agreement").entered();
+    let prefix0 = prefix("0");
+    let prefix1 = prefix("1");
+    let env = merge_test_network()?;
+
+    let sap0 = env.get_sap(prefix0, None)?;
+    let sap1 = env.get_sap(prefix1, None)?;
+    let sk_set0 = env.get_secret_key_set(prefix0, None)?;
+    let sk_set1 = env.get_secret_key_set(prefix1, None)?;
+    let mut node = env.get_nodes(prefix0, 1, 0, None)?.remove(0);
+    learn_section(&mut node, &env, prefix1)?;
+
+    // the merged section's elders are the ones of the sibling, which carried the merge out
+    let merged_sk_set = bls::SecretKeySet::random(sap1.elder_count() - 1, &mut thread_rng());
+    let merged_sap = SectionAuthorityProvider::new(
+        sap1.elders().copied(),
+        Prefix::default(),
+        sap0.members().chain(sap1.members()).cloned(),
+        merged_sk_set.public_keys(),
+        0,
+    );
+    let merged_sap = TestKeys::get_section_signed(&merged_sk_set.secret_key(), merged_sap)?;
+    let bytes = bincode::serialize(&merged_sap.sig.public_key)?;
+    let sig = TestKeys::get_section_sig_bytes(&sk_set1.secret_key(), &bytes);
+    // our section agreed to the merge, as it didn't sign the merged key
+    let merge_agreement = TestKeys::get_section_signed(
+        &sk_set0.secret_key(),
+        SectionMergeAgreement {
+            merged_prefix: Prefix::default(),
+            section_key: sk_set0.public_keys().public_key(),
+            members: sap0.members().cloned().collect(),
+        },
+    )?;
+
+    let mut cmds = ProcessAndInspectCmds::new(Cmd::HandleSectionMergeAgreement {
+        sap: merged_sap.clone(),
+        sig,
+        merge_agreement,
+    });
+    let mut data_request_recipients = BTreeSet::new();
+    while let Some(cmd) = cmds.next(&mut node).await? {
+        if let Cmd::SendMsg {
+            msg: NetworkMsg::Node(NodeMsg::NodeDataCmd(NodeDataCmd::SendAnyMissingRelevantData(_))),
+            recipients,
+            ..
+        } = cmd
+        {
+            data_request_recipients.extend(recipients.clone().into_iter().map(|r| r.name()));
+        }
+    }
+
+    let network_knowledge = node.network_knowledge();
+    assert_eq!(network_knowledge.prefix(), Prefix::default());
+    assert_eq!(network_knowledge.section_key(), merged_sap.section_key());
+    let section_tree = network_knowledge.section_tree();
+    assert_eq!(section_tree.get(&prefix0), None);
+    assert_eq!(section_tree.get(&prefix1), None);
+    assert_eq!(section_tree.get(&Prefix::default()), Some(merged_sap.value));
+    assert!(sap1
+        .elders()
+        .all(|elder| data_request_recipients.contains(&elder.name())));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn spentbook_spend_client_message_should_replicate_to_adults_and_send_ack() -> Result<()> {
     init_logger();
@@ -733,6 +945,7 @@ async fn spentbook_spend_client_message_should_replicate_to_adults_and_send_ack(
 
     let comm_rx = env.take_comm_rx(node.info().public_key());
     let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
         ClientMsg::Cmd(DataCmd::Spentbook(SpentbookCmd::Spend {
             public_key,
             tx: tx.clone(),
@@ -823,6 +1036,7 @@ async fn spentbook_spend_transaction_with_no_inputs_should_return_spentbook_erro
 
     let comm_rx = env.take_comm_rx(node.info().public_key());
     let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
         ClientMsg::Cmd(DataCmd::Spentbook(SpentbookCmd::Spend {
             public_key: new_dbc2_sk.public_key(),
             tx: new_dbc2.transaction,
@@ -902,6 +1116,7 @@ async fn spentbook_spend_with_random_public_key_should_return_spentbook_error()
 
     let comm_rx = env.take_comm_rx(node.info().public_key());
     let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
         ClientMsg::Cmd(DataCmd::Spentbook(SpentbookCmd::Spend {
             public_key: random_public_key,
             tx: tx.clone(),
@@ -1008,6 +1223,7 @@ async fn spentbook_spend_with_updated_network_knowledge_should_update_the_node()
     let comm_rx = env.take_comm_rx(info.public_key());
 
     let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
         ClientMsg::Cmd(DataCmd::Spentbook(SpentbookCmd::Spend {
             public_key,
             tx,
@@ -1056,6 +1272,637 @@ async fn spentbook_spend_with_updated_network_knowledge_should_update_the_node()
     Ok(())
 }
 
+#[tokio::test]
+async fn store_chunk_without_payment_should_return_missing_payment_error() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = single_section_network(prefix)?;
+    let mut node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+
+    let chunk = Chunk::new(Bytes::from("unpaid chunk"));
+    let comm_rx = env.take_comm_rx(node.info().public_key());
+    let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
+        ClientMsg::Cmd(DataCmd::StoreChunk(chunk)),
+        &mut node,
+        comm_rx,
+    )
+    .await?;
+
+    while let Some(cmd) = cmds.next(&mut node).await? {
+        if let Cmd::SendDataResponse {
+            msg:
+                DataResponse::CmdResponse {
+                    response: CmdResponse::StoreChunk(Err(error)),
+                    ..
+                },
+            ..
+        } = cmd
+        {
+            assert_eq!(error, &MessagingDataError::MissingStorePayment);
+            return Ok(());
+        }
+    }
+
+    bail!("We expected an error to be returned");
+}
+
+#[tokio::test]
+async fn paid_store_chunk_should_be_forwarded_to_data_holders() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = single_section_network(prefix)?;
+    let mut node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+    let sk_set = env.get_secret_key_set(prefix, None)?;
+    let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+    let context = node.context();
+
+    let chunk = Chunk::new(Bytes::from("paid chunk"));
+    let payment = dbc_utils::gen_store_payment(
+        &genesis_dbc,
+        BTreeSet::from([*chunk.name()]),
+        context.current_fee(),
+        &context,
+    )?;
+
+    let comm_rx = env.take_comm_rx(node.info().public_key());
+    let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
+        ClientMsg::PaidCmd {
+            cmd: DataCmd::StoreChunk(chunk.clone()),
+            payment,
+        },
+        &mut node,
+        comm_rx,
+    )
+    .await?;
+
+    while let Some(cmd) = cmds.next(&mut node).await? {
+        if let Cmd::SendAndForwardResponseToClient {
+            wire_msg, targets, ..
+        } = cmd
+        {
+            assert_eq!(targets.len(), replication_count);
+            assert_matches!(
+                wire_msg.clone().into_msg()?,
+                NetworkMsg::Node(NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(
+                    ReplicatedData::Chunk(data)
+                ))) if data == chunk
+            );
+            return Ok(());
+        }
+    }
+
+    bail!("No cmd msg was generated to forward the paid data to node holders");
+}
+
+#[tokio::test]
+async fn paid_store_chunk_with_too_low_payment_should_return_error() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = single_section_network(prefix)?;
+    let mut node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+    let sk_set = env.get_secret_key_set(prefix, None)?;
+    let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+    let context = node.context();
+
+    // The payment covers two chunks of our section, but only the store cost of one is paid.
+    let chunk = Chunk::new(Bytes::from("underpaid chunk"));
+    let other_chunk = Chunk::new(Bytes::from("other underpaid chunk"));
+    let payment = dbc_utils::gen_store_payment(
+        &genesis_dbc,
+        BTreeSet::from([*chunk.name(), *other_chunk.name()]),
+        context.current_fee(),
+        &context,
+    )?;
+
+    let comm_rx = env.take_comm_rx(node.info().public_key());
+    let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
+        ClientMsg::PaidCmd {
+            cmd: DataCmd::StoreChunk(chunk),
+            payment,
+        },
+        &mut node,
+        comm_rx,
+    )
+    .await?;
+
+    while let Some(cmd) = cmds.next(&mut node).await? {
+        if let Cmd::SendDataResponse {
+            msg:
+                DataResponse::CmdResponse {
+                    response: CmdResponse::StoreChunk(Err(error)),
+                    ..
+                },
+            ..
+        } = cmd
+        {
+            assert_matches!(error, MessagingDataError::StorePaymentTooLow { .. });
+            return Ok(());
+        }
+    }
+
+    bail!("We expected an error to be returned");
+}
+
+#[tokio::test]
+async fn paid_store_chunk_with_payment_covering_too_many_items_should_return_error() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = single_section_network(prefix)?;
+    let mut node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+    let sk_set = env.get_secret_key_set(prefix, None)?;
+    let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+    let context = node.context();
+
+    // The store cost of all the chunks is paid, but they're more than paid for at once.
+    let chunk = Chunk::new(Bytes::from("chunk paid with too many others"));
+    let mut content: BTreeSet<_> = (0..MAX_PAID_ITEMS)
+        .map(|_| xor_name::rand::random())
+        .collect();
+    let _ = content.insert(*chunk.name());
+    let store_cost = Token::from_nano(context.current_fee().as_nano() * content.len() as u64);
+    let payment = dbc_utils::gen_store_payment(&genesis_dbc, content, store_cost, &context)?;
+
+    let comm_rx = env.take_comm_rx(node.info().public_key());
+    let mut cmds = ProcessAndInspectCmds::new_from_client_msg(
+        env.memory_network(),
+        ClientMsg::PaidCmd {
+            cmd: DataCmd::StoreChunk(chunk),
+            payment,
+        },
+        &mut node,
+        comm_rx,
+    )
+    .await?;
+
+    while let Some(cmd) = cmds.next(&mut node).await? {
+        if let Cmd::SendDataResponse {
+            msg:
+                DataResponse::CmdResponse {
+                    response: CmdResponse::StoreChunk(Err(error)),
+                    ..
+                },
+            ..
+        } = cmd
+        {
+            assert_matches!(error, MessagingDataError::InvalidStorePayment(_));
+            return Ok(());
+        }
+    }
+
+    bail!("We expected an error to be returned");
+}
+
+#[tokio::test]
+async fn paid_chunk_is_stored_by_the_data_holders_which_ack_it_to_the_client() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = TestNetworkBuilder::new(thread_rng())
+        .sap(
+            TestSapBuilder::new(prefix)
+                .elder_count(1)
+                .adult_count(6)
+                .sk_threshold_size(0),
+        )
+        .build()?;
+    let mut nodes = nodes_with_comm_rx(&mut env, prefix, 1, 6)?;
+
+    let chunk = Chunk::new(Bytes::from("chunk stored over the network"));
+    let (holders, response) = store_paid_chunk(&env, &mut nodes, &chunk).await?;
+    assert_eq!(holders.len(), replication_count);
+    assert_matches!(
+        response,
+        NetworkMsg::DataResponse(DataResponse::CmdResponse {
+            response: CmdResponse::StoreChunk(Ok(())),
+            ..
+        })
+    );
+
+    let address = ReplicatedData::Chunk(chunk).address();
+    for (node, _) in nodes
+        .iter()
+        .filter(|(node, _)| holders.contains(&node.info().id()))
+    {
+        assert!(node.data_storage.data_addrs().await.contains(&address));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn paid_chunk_store_fails_if_the_data_holders_are_partitioned_off() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    let mut env = TestNetworkBuilder::new(thread_rng())
+        .sap(
+            TestSapBuilder::new(prefix)
+                .elder_count(1)
+                .adult_count(6)
+                .sk_threshold_size(0),
+        )
+        .build()?;
+    let mut nodes = nodes_with_comm_rx(&mut env, prefix, 1, 6)?;
+
+    // the adults can't be reached by the elder anymore, which only handles the msgs sent to itself
+    let adults = nodes.split_off(1);
+    env.memory_network()
+        .partition(adults.iter().map(|(adult, _)| adult.info().addr));
+
+    let chunk = Chunk::new(Bytes::from("chunk stored across a partition"));
+    let (holders, response) = store_paid_chunk(&env, &mut nodes, &chunk).await?;
+    assert_eq!(holders.len(), replication_count);
+    assert_matches!(
+        response,
+        NetworkMsg::DataResponse(DataResponse::NetworkIssue(
+            MessagingDataError::InconsistentStorageNodeResponses
+        ))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn paid_chunk_is_stored_by_data_holders_of_mixed_protocol_versions() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let replication_count = 5;
+    std::env::set_var("SN_DATA_COPY_COUNT", replication_count.to_string());
+
+    // the section is being upgraded, some of its adults still only supporting the legacy version
+    let builder = TestNetworkBuilder::new(thread_rng());
+    let network = builder.memory_network();
+    let (elders, adults, mut comm_rxs) =
+        gen_node_infos_with_comm(network, &prefix, 1, 3, None, None);
+    let legacy_adults: Vec<_> = (0..3)
+        .map(|_| {
+            let (info, comm, comm_rx) = gen_info_with_comm_supporting(
+                network,
+                ProtocolVersions::LEGACY,
+                MIN_ADULT_AGE,
+                Some(prefix),
+            );
+            let _ = comm_rxs.insert(info.public_key(), Some(comm_rx));
+            (info, comm)
+        })
+        .collect();
+    let members = elders
+        .iter()
+        .chain(&adults)
+        .chain(&legacy_adults)
+        .cloned()
+        .collect::<Vec<_>>();
+    let env = builder.sap_with_members(prefix, elders, members).build()?;
+
+    let mut nodes = env
+        .get_nodes(prefix, 1, 6, None)?
+        .into_iter()
+        .map(|node| {
+            let comm_rx = comm_rxs
+                .remove(&node.info().public_key())
+                .flatten()
+                .ok_or_else(|| eyre!("The receiver of each node is expected"))?;
+            Ok((node, comm_rx))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let chunk = Chunk::new(Bytes::from("chunk stored across protocol versions"));
+    let (holders, response) = store_paid_chunk(&env, &mut nodes, &chunk).await?;
+    // at most two of the seven members are not holders
+    assert!(legacy_adults
+        .iter()
+        .any(|(info, _)| holders.contains(&info.id())));
+    assert_matches!(
+        response,
+        NetworkMsg::DataResponse(DataResponse::CmdResponse {
+            response: CmdResponse::StoreChunk(Ok(())),
+            ..
+        })
+    );
+
+    let address = ReplicatedData::Chunk(chunk).address();
+    for (node, _) in nodes
+        .iter()
+        .filter(|(node, _)| holders.contains(&node.info().id()))
+    {
+        assert!(node.data_storage.data_addrs().await.contains(&address));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn forwarded_register_edit_is_notified_once_its_holders_applied_it() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let mut env = TestNetworkBuilder::new(thread_rng())
+        .sap(TestSapBuilder::new(prefix).adult_count(3))
+        .build()?;
+    let node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+    let context = node.context();
+    let mut comm_rx = env.take_comm_rx(node.info().public_key());
+
+    let edit = register_subscriptions::tests::edit(xor_name::rand::random())?;
+    let address = edit.dst_address();
+    let dst = Dst {
+        name: *address.name(),
+        section_key: context.network_knowledge.section_key(),
+    };
+
+    // a client subscribes to the edits of the register, awaiting them
+    let subscribe = ClientMsg::Query(DataQuery::Register(RegisterQuery::Subscribe(address)));
+    let (client, wire_msg, stream, _) =
+        send_client_msg_to_node(env.memory_network(), &subscribe, dst, &node, &mut comm_rx).await?;
+    let cmds = MyNode::handle_msg(context.clone(), client, wire_msg, Some(stream)).await?;
+    let Ok(
+        [Cmd::SendRegisterSubscriptionToHolders {
+            wire_msg,
+            client_stream,
+            ..
+        }],
+    ) = <[Cmd; 1]>::try_from(cmds)
+    else {
+        bail!("The subscription wasn't sent to the register holders");
+    };
+
+    // it is subscribed once the holders responded it may read the register
+    let (response_sender, response) = oneshot::channel();
+    let _ = response_sender.send(subscribe_to_register_response(Ok(()), dst)?);
+    let cmds = MyNode::handle_register_subscription_once_readable(
+        &context,
+        wire_msg.msg_id(),
+        address,
+        ClientId::from(client),
+        client_stream,
+        response,
+    )
+    .await;
+    assert!(cmds.is_empty());
+    assert!(context
+        .register_subscriptions
+        .is_subscribed(&address, &ClientId::from(client)));
+
+    // another one edits it, the edit only being forwarded to its holders
+    let edit_cmd = ClientMsg::Cmd(DataCmd::Register(RegisterCmd::Edit(edit.clone())));
+    let (client, wire_msg, stream, _) =
+        send_client_msg_to_node(env.memory_network(), &edit_cmd, dst, &node, &mut comm_rx).await?;
+    let cmds = MyNode::handle_msg(context.clone(), client, wire_msg, Some(stream)).await?;
+    assert_matches!(
+        cmds.as_slice(),
+        [Cmd::SendAndForwardResponseToClient { register_edit: Some(forwarded), .. }]
+            if *forwarded == edit
+    );
+
+    // the edit is not notified if the holders didn't all respond, or failed to apply it
+    let (_, no_response) = oneshot::channel();
+    let cmds = MyNode::notify_register_subscribers_once_applied(&context, &edit, no_response).await;
+    assert!(cmds.is_empty());
+
+    let (response_sender, response) = oneshot::channel();
+    let error = MessagingDataError::AccessDenied(User::Anyone);
+    let _ = response_sender.send(edit_register_response(Err(error), dst)?);
+    let cmds = MyNode::notify_register_subscribers_once_applied(&context, &edit, response).await;
+    assert!(cmds.is_empty());
+
+    // once they applied it, the subscribed client is notified of it
+    let (response_sender, response) = oneshot::channel();
+    let _ = response_sender.send(edit_register_response(Ok(()), dst)?);
+    let cmds = MyNode::notify_register_subscribers_once_applied(&context, &edit, response).await;
+    assert_matches!(
+        cmds.as_slice(),
+        [Cmd::SendDataResponse { msg: DataResponse::RegisterEdited { edits, .. }, .. }]
+            if *edits == vec![edit]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn register_subscription_is_rejected_unless_its_holders_allow_reading_it() -> Result<()> {
+    init_logger();
+    let prefix = Prefix::default();
+    let mut env = TestNetworkBuilder::new(thread_rng())
+        .sap(TestSapBuilder::new(prefix).adult_count(3))
+        .build()?;
+    let node = env.get_nodes(prefix, 1, 0, None)?.remove(0);
+    let context = node.context();
+    let mut comm_rx = env.take_comm_rx(node.info().public_key());
+
+    let address = register_subscriptions::tests::edit(xor_name::rand::random())?.dst_address();
+    let dst = Dst {
+        name: *address.name(),
+        section_key: context.network_knowledge.section_key(),
+    };
+
+    let subscribe = ClientMsg::Query(DataQuery::Register(RegisterQuery::Subscribe(address)));
+    for holders_response in [
+        None,
+        Some(subscribe_to_register_response(
+            Err(MessagingDataError::AccessDenied(User::Anyone)),
+            dst,
+        )?),
+    ] {
+        let (client, wire_msg, stream, _) =
+            send_client_msg_to_node(env.memory_network(), &subscribe, dst, &node, &mut comm_rx)
+                .await?;
+        let client_id = ClientId::from(client);
+        let cmds = MyNode::handle_msg(context.clone(), client, wire_msg, Some(stream)).await?;
+        let Ok(
+            [Cmd::SendRegisterSubscriptionToHolders {
+                wire_msg,
+                client_stream,
+                ..
+            }],
+        ) = <[Cmd; 1]>::try_from(cmds)
+        else {
+            bail!("The subscription wasn't sent to the register holders");
+        };
+
+        // the holders didn't all respond, or responded the client may not read the register
+        let (response_sender, response) = oneshot::channel();
+        match holders_response {
+            Some(bytes) => {
+                let _ = response_sender.send(bytes);
+            }
+            None => drop(response_sender),
+        }
+        let cmds = MyNode::handle_register_subscription_once_readable(
+            &context,
+            wire_msg.msg_id(),
+            address,
+            client_id,
+            client_stream,
+            response,
+        )
+        .await;
+        assert_matches!(
+            cmds.as_slice(),
+            [Cmd::SendDataResponse {
+                msg: DataResponse::QueryResponse {
+                    response: QueryResponse::SubscribeToRegister(Err(_)),
+                    ..
+                },
+                ..
+            }]
+        );
+        assert!(!context
+            .register_subscriptions
+            .is_subscribed(&address, &client_id));
+    }
+
+    Ok(())
+}
+
+// Gets the elders, then the adults, of the section, along with the receivers of their comms
+fn nodes_with_comm_rx(
+    env: &mut network_builder::TestNetwork,
+    prefix: Prefix,
+    elder_count: usize,
+    adult_count: usize,
+) -> Result<Vec<(MyNode, Receiver<CommEvent>)>> {
+    let nodes = env.get_nodes(prefix, elder_count, adult_count, None)?;
+    Ok(nodes
+        .into_iter()
+        .map(|node| {
+            let comm_rx = env.take_comm_rx(node.info().public_key());
+            (node, comm_rx)
+        })
+        .collect())
+}
+
+// Has a client store the paid chunk through the first of the nodes, an elder. The msgs the chunk
+// is forwarded with are handled by the nodes they reach, within a timeout, the others being
+// skipped. Returns the data holders the chunk was forwarded to, and the response of the client.
+async fn store_paid_chunk(
+    env: &network_builder::TestNetwork,
+    nodes: &mut [(MyNode, Receiver<CommEvent>)],
+    chunk: &Chunk,
+) -> Result<(BTreeSet<NodeId>, NetworkMsg)> {
+    let (elder, elder_comm_rx) = nodes
+        .first_mut()
+        .ok_or_else(|| eyre!("An elder is expected"))?;
+    let context = elder.context();
+    let sk_set = env.get_secret_key_set(context.network_knowledge.prefix(), None)?;
+    let genesis_dbc = gen_genesis_dbc(&sk_set, &sk_set.secret_key())?;
+    let payment = dbc_utils::gen_store_payment(
+        &genesis_dbc,
+        BTreeSet::from([*chunk.name()]),
+        context.current_fee(),
+        &context,
+    )?;
+    let msg = ClientMsg::PaidCmd {
+        cmd: DataCmd::StoreChunk(chunk.clone()),
+        payment,
+    };
+    let dst = Dst {
+        name: context.name,
+        section_key: context.network_knowledge.section_key(),
+    };
+
+    let (client, wire_msg, stream, client_response) =
+        send_client_msg_to_node(env.memory_network(), &msg, dst, elder, elder_comm_rx).await?;
+    let cmds = MyNode::handle_msg(context, client, wire_msg, Some(stream)).await?;
+
+    let mut holders = BTreeSet::new();
+    let mut cmds = VecDeque::from(cmds);
+    while let Some(cmd) = cmds.pop_front() {
+        if let Cmd::SendAndForwardResponseToClient { targets, .. } = &cmd {
+            holders = targets.clone();
+        }
+        cmds.extend(MyNode::test_process_cmd(cmd, elder).await?);
+    }
+
+    for (node, comm_rx) in nodes.iter_mut() {
+        if !holders.contains(&node.info().id()) {
+            continue;
+        }
+        let Some(msg) = next_msg(comm_rx).await else {
+            continue;
+        };
+        let mut cmds = VecDeque::from(
+            MyNode::handle_msg(node.context(), msg.sender, msg.wire_msg, msg.send_stream).await?,
+        );
+        while let Some(cmd) = cmds.pop_front() {
+            cmds.extend(MyNode::test_process_cmd(cmd, node).await?);
+        }
+    }
+
+    let client_response = timeout(Duration::from_secs(5), client_response)
+        .await
+        .map_err(|_| eyre!("The client got no response"))?;
+    let response = WireMsg::from(client_response??)?.into_msg()?;
+    Ok((holders, response))
+}
+
+// Receives the next msg within a timeout, skipping the errors of the msgs sent
+async fn next_msg(comm_rx: &mut Receiver<CommEvent>) -> Option<MsgReceived> {
+    let next_msg = async {
+        loop {
+            match comm_rx.recv().await? {
+                CommEvent::Msg(msg) => break Some(msg),
+                CommEvent::Error { .. } => continue,
+            }
+        }
+    };
+    timeout(Duration::from_secs(5), next_msg).await.ok()?
+}
+
+// Number of members of the section which shrank below the minimum section size
+const SHRUNK_SECTION_SIZE: usize = 3;
+
+// A network where section '0' shrank below the minimum section size, while section '1' didn't
+fn merge_test_network() -> Result<network_builder::TestNetwork> {
+    let builder = TestNetworkBuilder::new(thread_rng());
+    let (elders0, ..) = gen_node_infos_with_comm(
+        builder.memory_network(),
+        &prefix("0"),
+        SHRUNK_SECTION_SIZE,
+        0,
+        Some(&[MIN_ADULT_AGE]),
+        None,
+    );
+    builder
+        .sap_with_members(prefix("0"), elders0.clone(), elders0)
+        .sap(TestSapBuilder::new(prefix("1")).adult_count(1))
+        .build()
+}
+
+// Updates the knowledge of the node with the SAP of the given section
+fn learn_section(
+    node: &mut MyNode,
+    env: &network_builder::TestNetwork,
+    prefix: Prefix,
+) -> Result<()> {
+    let update = env
+        .get_network_knowledge(prefix, None)?
+        .section_tree()
+        .generate_section_tree_update(&prefix)?;
+    let name = node.name();
+    let _updated = node
+        .network_knowledge
+        .update_sap_knowledge_if_valid(update, &name)?;
+    Ok(())
+}
+
 fn get_single_sig(proposal: &NodeState) -> Vec<u8> {
     bincode::serialize(proposal).expect("Failed to serialize")
 }
@@ -1070,6 +1917,42 @@ fn ae_msg(name: XorName, dst: Dst, msg: AntiEntropyMsg) -> Result<WireMsg> {
     ))
 }
 
+fn edit_register_response(
+    result: std::result::Result<(), MessagingDataError>,
+    dst: Dst,
+) -> Result<UsrMsgBytes> {
+    use sn_interface::messaging::{MsgId, MsgKind};
+    let response = DataResponse::CmdResponse {
+        response: CmdResponse::EditRegister(result),
+        correlation_id: MsgId::new(),
+    };
+    let wire_msg = WireMsg::new_msg(
+        MsgId::new(),
+        WireMsg::serialize_msg_payload(&response)?,
+        MsgKind::DataResponse(dst.name),
+        dst,
+    );
+    Ok(wire_msg.serialize()?)
+}
+
+fn subscribe_to_register_response(
+    result: std::result::Result<(), MessagingDataError>,
+    dst: Dst,
+) -> Result<UsrMsgBytes> {
+    use sn_interface::messaging::{MsgId, MsgKind};
+    let response = DataResponse::QueryResponse {
+        response: QueryResponse::SubscribeToRegister(result),
+        correlation_id: MsgId::new(),
+    };
+    let wire_msg = WireMsg::new_msg(
+        MsgId::new(),
+        WireMsg::serialize_msg_payload(&response)?,
+        MsgKind::DataResponse(dst.name),
+        dst,
+    );
+    Ok(wire_msg.serialize()?)
+}
+
 fn single_src_node(name: XorName, dst: Dst, msg: NodeMsg) -> Result<WireMsg> {
     use sn_interface::messaging::{MsgId, MsgKind};
     let msg_payload = WireMsg::serialize_msg_payload(&msg)?;
@@ -1080,6 +1963,7 @@ fn single_src_node(name: XorName, dst: Dst, msg: NodeMsg) -> Result<WireMsg> {
         MsgKind::Node {
             name,
             is_join: msg.is_join(),
+            is_data: msg.is_data(),
         },
         dst,
     );
@@ -8,11 +8,11 @@
 
 use super::test_utils::gen_node_infos_with_comm;
 use crate::{
-    node::{cfg::create_test_capacity_and_root_storage, MyNode, NodeEventsChannel},
+    node::{cfg::create_test_capacity_and_root_storage, DataStorage, MyNode, NodeEventsChannel},
     UsedSpace,
 };
 
-use sn_comms::{Comm, CommEvent};
+use sn_comms::{Comm, CommEvent, MemoryNetwork};
 use sn_interface::{
     messaging::system::SectionSigned,
     network_knowledge::{
@@ -45,6 +45,9 @@ enum TestMemberType {
 }
 
 /// Helper to build the `TestNetwork` struct
+///
+/// The comms of all the nodes are bound to a network simulated within the process, which the
+/// latency, loss and partitions of can be set through `TestNetwork::memory_network()`.
 pub(crate) struct TestNetworkBuilder<R: RngCore> {
     #[allow(clippy::type_complexity)]
     sections: Vec<(
@@ -53,21 +56,29 @@ pub(crate) struct TestNetworkBuilder<R: RngCore> {
         SecretKeySet,
     )>,
     receivers: TestCommRx,
+    memory_network: MemoryNetwork,
     rng: R,
     n_churns_each_section: usize,
 }
 
 impl<R: RngCore> TestNetworkBuilder<R> {
     /// Initializes the builder. Provide custom rng or just use `thread_rng()`
-    pub(crate) fn new(rng: R) -> TestNetworkBuilder<R> {
+    pub(crate) fn new(mut rng: R) -> TestNetworkBuilder<R> {
         TestNetworkBuilder {
             sections: Vec::new(),
-            rng,
             receivers: BTreeMap::new(),
+            memory_network: MemoryNetwork::new(rng.next_u64()),
+            rng,
             n_churns_each_section: 1,
         }
     }
 
+    /// The network the nodes are bound to, which the nodes provided through
+    /// `sap_with_members()` should be bound to as well.
+    pub(crate) fn memory_network(&self) -> &MemoryNetwork {
+        &self.memory_network
+    }
+
     /// The number of churn events that can happen within a single `Prefix`. This will create extra
     /// SAPs chained to each other for the same prefix. The n_churns_each_section will only be applied
     /// for the Prefixes for which the user has not provided the SAPs.
@@ -277,6 +288,7 @@ impl<R: RngCore> TestNetworkBuilder<R> {
             section_tree,
             nodes: node_infos,
             receivers: self.receivers,
+            memory_network: self.memory_network,
         })
     }
 
@@ -386,7 +398,8 @@ impl<R: RngCore> TestNetworkBuilder<R> {
         SecretKeySet,
         TestCommRx,
     ) {
-        let (sap, sk_set, elders, adults, comm_rx) = sap_builder.build_with_comm(&mut self.rng);
+        let (sap, sk_set, elders, adults, comm_rx) =
+            sap_builder.build_with_comm(&mut self.rng, &self.memory_network);
 
         let nodes = elders
             .into_iter()
@@ -420,9 +433,17 @@ pub(crate) struct TestNetwork {
     nodes: BTreeMap<Prefix, Vec<Vec<(MyNodeInfo, Comm, TestMemberType)>>>,
     // The mpsc receiver for each node. Will be moved out once retrieved
     receivers: TestCommRx,
+    // The network the comms of the nodes are bound to
+    memory_network: MemoryNetwork,
 }
 
 impl TestNetwork {
+    /// The network the nodes are bound to, on which the latency, loss and partitions of the
+    /// msgs between them can be set, and other nodes or clients can be bound to reach them.
+    pub(crate) fn memory_network(&self) -> &MemoryNetwork {
+        &self.memory_network
+    }
+
     /// Build elder/adult `MyNode` instances for a given `Prefix`. The elder_count and adult_count
     /// should be <= the actual count specified in the SAP.
     /// The created instance has knowledge about the Network only from the genesis section to its
@@ -660,7 +681,10 @@ impl TestNetwork {
             bls::SecretKey::random(),
             network_knowledge.clone(),
             sk_share.clone(),
-            UsedSpace::new(min_capacity, max_capacity),
+            DataStorage::new(
+                &root_storage_dir,
+                UsedSpace::new(min_capacity, max_capacity),
+            ),
             root_storage_dir,
             mpsc::channel(10).0,
             NodeEventsChannel::default(),
@@ -812,6 +836,7 @@ trait WithComms {
     fn build_with_comm(
         self,
         rng: impl RngCore,
+        memory_network: &MemoryNetwork,
     ) -> (
         SectionAuthorityProvider,
         SecretKeySet,
@@ -826,6 +851,7 @@ impl WithComms for TestSapBuilder {
     fn build_with_comm(
         self,
         rng: impl RngCore,
+        memory_network: &MemoryNetwork,
     ) -> (
         SectionAuthorityProvider,
         SecretKeySet,
@@ -836,6 +862,7 @@ impl WithComms for TestSapBuilder {
         // Todo: use custom rng to generate the random nodes. `gen_keypair` requires `rand-0.7`
         // version and `SecretKeySet` requires `rand-0.8`; wait for the other one to be bumped.
         let (elder_nodes, adult_nodes, comm_rx) = gen_node_infos_with_comm(
+            memory_network,
             &self.prefix,
             self.elder_count,
             self.adult_count,
@@ -9,35 +9,42 @@
 use crate::{
     node::{
         cfg::create_test_capacity_and_root_storage, flow_ctrl::process_cmd_non_blocking,
-        messaging::Recipients, Cmd, MyNode, NodeEventsChannel,
+        messaging::Recipients, Cmd, DataStorage, MyNode, NodeEventsChannel,
     },
     UsedSpace,
 };
-use sn_comms::{Comm, CommEvent, MsgReceived};
+use sn_comms::{Comm, CommEvent, MemoryNetwork, MsgReceived, SendStream, Transport};
 use sn_interface::{
     messaging::{
         data::ClientMsg, serialisation::WireMsg, AuthorityProof, ClientAuth, Dst, MsgId, MsgKind,
+        ProtocolVersions,
     },
     network_knowledge::{MyNodeInfo, NetworkKnowledge},
-    test_utils::expand_age_pattern,
-    types::{keys::ed25519::gen_keypair, Keypair},
+    test_utils::{expand_age_pattern, TestSapBuilder},
+    types::{keys::ed25519::gen_keypair, Keypair, Participant},
 };
 
 use bytes::Bytes;
 use eyre::{eyre, Context, Result};
-use qp2p::Endpoint;
+use qp2p::UsrMsgBytes;
+use rand::thread_rng;
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque},
-    net::{Ipv4Addr, SocketAddr},
     sync::Arc,
 };
-use tokio::sync::{
-    mpsc::{self, error::TryRecvError, Receiver},
-    RwLock,
+use tokio::{
+    sync::{
+        mpsc::{self, error::TryRecvError, Receiver},
+        RwLock,
+    },
+    task::JoinHandle,
 };
 use xor_name::{Prefix, XorName};
 
-use super::network_builder::TestCommRx;
+use super::network_builder::{TestCommRx, TestNetwork, TestNetworkBuilder};
+
+// The response a client gets to the msg it sent on a bi-stream
+pub(crate) type ClientResponse = JoinHandle<Result<UsrMsgBytes>>;
 
 // Process commands, allowing the user to inspect each and all of the intermediate
 // commands that are being returned by the Cmd node.
@@ -66,66 +73,32 @@ impl ProcessAndInspectCmds {
     // This constructor invokes `MyNode::handle_valid_client_msg` using the
     // provided ClientMsg, and it uses the outcome (commands) as the
     // starting set of cmds to process by the ProcessAndInspectCmds instance herein created.
+    // The client is bound to the given network, the node's one.
     // TODO: the client recv-stream created could be returned for the caller to use if necessary,
     // at this point it's useless since `Cmd::SendDataResponse` is not processed but only inspected.
     pub(crate) async fn new_from_client_msg(
+        network: &MemoryNetwork,
         msg: ClientMsg,
         node: &mut MyNode,
         mut comm_rx: Receiver<CommEvent>,
     ) -> crate::node::error::Result<ProcessAndInspectCmds> {
         let context = node.context();
-        let (msg_id, serialised_payload, msg_kind, _auth) =
-            get_client_msg_parts_for_handling(&msg)?;
-
-        let client_addr: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
-        let client_endpoint = Endpoint::builder()
-            .addr(client_addr)
-            .idle_timeout(70_000)
-            .client()
-            .expect("failed to create new client endpoint");
-
         let node_id = context.info.id();
-        let node_addr = node_id.addr();
-        let (client_conn, _) = client_endpoint
-            .connect_to(&node_addr)
-            .await
-            .unwrap_or_else(|err| panic!("failed to connect to node at {node_addr:?}: {err:?}"));
-        let (mut send_stream, _recv_stream) = client_conn
-            .open_bi()
-            .await
-            .expect("failed to open bi-stream from new client endpoint");
-
         let dst = Dst {
             name: node_id.name(),
             section_key: context.network_knowledge.section_key(),
         };
-        let wire_msg = WireMsg::new_msg(msg_id, serialised_payload, msg_kind, dst);
-        let user_msg = wire_msg.serialize()?;
-
-        // move send msg off thread so send / receive can both complete
-        let _handle = tokio::spawn(async move {
-            let _ = send_stream
-                .send_user_msg(user_msg)
-                .await
-                .context("Could not send user msg");
-        });
-
-        match comm_rx.recv().await {
-            Some(CommEvent::Msg(MsgReceived {
-                send_stream: Some(send_stream),
-                ..
-            })) => {
-                let cmds = MyNode::handle_msg(
-                    node.context(),
-                    sn_interface::types::Participant::from_node(node_id),
-                    wire_msg,
-                    Some(send_stream),
-                )
-                .await?;
-                Ok(Self::from(cmds))
-            }
-            _ => Err(crate::node::error::Error::NoClientResponseStream),
-        }
+        let (_, wire_msg, send_stream, _) =
+            send_client_msg_to_node(network, &msg, dst, node, &mut comm_rx).await?;
+
+        let cmds = MyNode::handle_msg(
+            node.context(),
+            Participant::from_node(node_id),
+            wire_msg,
+            Some(send_stream),
+        )
+        .await?;
+        Ok(Self::from(cmds))
     }
 
     pub(crate) async fn next(
@@ -147,6 +120,7 @@ impl ProcessAndInspectCmds {
                 Cmd::SendMsg { .. }
                     | Cmd::SendDataResponse { .. }
                     | Cmd::SendAndForwardResponseToClient { .. }
+                    | Cmd::SendRegisterSubscriptionToHolders { .. }
             ) {
                 let new_cmds = MyNode::test_process_cmd(cmd, node).await?;
                 self.pending_cmds.extend(new_cmds);
@@ -171,6 +145,46 @@ impl ProcessAndInspectCmds {
     }
 }
 
+/// Sends the client msg to the node from a client bound to the node's network, returning the
+/// client as seen by the node, along with the msg it received and the stream to respond to the
+/// client on, and the task awaiting the response the client gets.
+pub(crate) async fn send_client_msg_to_node(
+    network: &MemoryNetwork,
+    msg: &ClientMsg,
+    dst: Dst,
+    node: &MyNode,
+    comm_rx: &mut Receiver<CommEvent>,
+) -> crate::node::error::Result<(Participant, WireMsg, SendStream, ClientResponse)> {
+    let (msg_id, serialised_payload, msg_kind, _auth) = get_client_msg_parts_for_handling(msg)?;
+
+    let node_addr = node.info().addr;
+    let (client_transport, _) = network.transport();
+    let client_conn = client_transport
+        .connect_to(&node_addr)
+        .await
+        .unwrap_or_else(|err| panic!("failed to connect to node at {node_addr:?}: {err:?}"));
+
+    let wire_msg = WireMsg::new_msg(msg_id, serialised_payload, msg_kind, dst);
+    let user_msg = wire_msg.serialize()?;
+
+    // move send msg off thread so send / receive can both complete
+    let response = tokio::spawn(async move {
+        client_conn
+            .send_bi(user_msg)
+            .await
+            .context("Could not send user msg")
+    });
+
+    match comm_rx.recv().await {
+        Some(CommEvent::Msg(MsgReceived {
+            sender,
+            send_stream: Some(send_stream),
+            ..
+        })) => Ok((sender, wire_msg, send_stream, response)),
+        _ => Err(crate::node::error::Error::NoClientResponseStream),
+    }
+}
+
 pub(crate) fn get_client_msg_parts_for_handling(
     msg: &ClientMsg,
 ) -> crate::node::error::Result<(MsgId, Bytes, MsgKind, AuthorityProof<ClientAuth>)> {
@@ -185,6 +199,7 @@ pub(crate) fn get_client_msg_parts_for_handling(
         auth,
         is_spend: false,
         query_index: None,
+        is_register: msg.is_register(),
     };
 
     Ok((MsgId::new(), payload, kind, auth_proof))
@@ -388,7 +403,20 @@ pub(crate) async fn get_next_msg(comm_rx: &mut Receiver<CommEvent>) -> Option<Ms
     }
 }
 
-/// Create set of elder, adults nodes
+/// Build a network of a single section with the given prefix, with enough adults to hold
+/// the copies of the data stored, and a section key any single elder can sign with,
+/// e.g. to generate the store payments accepted by its elders.
+pub(crate) fn single_section_network(prefix: Prefix) -> Result<TestNetwork> {
+    TestNetworkBuilder::new(thread_rng())
+        .sap(
+            TestSapBuilder::new(prefix)
+                .adult_count(6)
+                .sk_threshold_size(0),
+        )
+        .build()
+}
+
+/// Create set of elder, adults nodes, bound to the given network
 ///
 /// Optionally provide `age_pattern` to create elders with specific ages.
 /// If None = elder's age is set to `MIN_ADULT_AGE`
@@ -397,6 +425,7 @@ pub(crate) async fn get_next_msg(comm_rx: &mut Receiver<CommEvent>) -> Option<Ms
 /// If age_pattern.len() > elder, then the extra elements after `count` are ignored.
 #[allow(clippy::type_complexity)]
 pub(crate) fn gen_node_infos_with_comm(
+    network: &MemoryNetwork,
     prefix: &Prefix,
     elders: usize,
     adults: usize,
@@ -408,14 +437,16 @@ pub(crate) fn gen_node_infos_with_comm(
     let mut comm_rx = BTreeMap::new();
     let elder_nodes = (0..elders)
         .map(|idx| {
-            let (node, comm, rx) = gen_info_with_comm(elder_age_pattern[idx], Some(*prefix));
+            let (node, comm, rx) =
+                gen_info_with_comm(network, elder_age_pattern[idx], Some(*prefix));
             let _ = comm_rx.insert(node.public_key(), Some(rx));
             (node, comm)
         })
         .collect();
     let adult_nodes = (0..adults)
         .map(|idx| {
-            let (node, comm, rx) = gen_info_with_comm(adult_age_pattern[idx], Some(*prefix));
+            let (node, comm, rx) =
+                gen_info_with_comm(network, adult_age_pattern[idx], Some(*prefix));
             let _ = comm_rx.insert(node.public_key(), Some(rx));
             (node, comm)
         })
@@ -423,13 +454,25 @@ pub(crate) fn gen_node_infos_with_comm(
     (elder_nodes, adult_nodes, comm_rx)
 }
 
-/// Generate `MyNodeInfo` and `Comm`
+/// Generate `MyNodeInfo` and `Comm`, the latter bound to the given network
 pub(crate) fn gen_info_with_comm(
+    network: &MemoryNetwork,
+    age: u8,
+    prefix: Option<Prefix>,
+) -> (MyNodeInfo, Comm, Receiver<CommEvent>) {
+    gen_info_with_comm_supporting(network, ProtocolVersions::SUPPORTED, age, prefix)
+}
+
+/// Generate `MyNodeInfo` and `Comm`, the latter bound to the given network, and only supporting
+/// the given versions of the messaging protocol, e.g. to simulate a node yet to be upgraded
+pub(crate) fn gen_info_with_comm_supporting(
+    network: &MemoryNetwork,
+    versions: ProtocolVersions,
     age: u8,
     prefix: Option<Prefix>,
 ) -> (MyNodeInfo, Comm, Receiver<CommEvent>) {
-    let socket_addr: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
-    let (comm, rx) = Comm::new(socket_addr, None).expect("failed  to create comm");
+    let (transport, incoming_msgs) = network.transport();
+    let (comm, rx) = Comm::with_transport(transport, incoming_msgs, None, versions);
     let info = MyNodeInfo::new(
         gen_keypair(&prefix.unwrap_or_default().range_inclusive(), age),
         comm.socket_addr(),
@@ -453,7 +496,10 @@ pub(crate) fn build_a_node_instance(
         bls::SecretKey::random(),
         network_knowledge.clone(),
         None,
-        UsedSpace::new(min_capacity, max_capacity),
+        DataStorage::new(
+            &root_storage_dir,
+            UsedSpace::new(min_capacity, max_capacity),
+        ),
         root_storage_dir,
         mpsc::channel(10).0,
         NodeEventsChannel::default(),
@@ -13,8 +13,8 @@ use sn_consensus::{
 use sn_interface::{
     messaging::system::DkgSessionId,
     network_knowledge::{
-        partition_by_prefix, recommended_section_size, MembershipState, NodeState,
-        SectionAuthorityProvider,
+        min_section_size, partition_by_prefix, recommended_section_size, MembershipState,
+        NodeState, SectionAuthorityProvider,
     },
 };
 use std::collections::{BTreeMap, BTreeSet};
@@ -93,6 +93,37 @@ pub(crate) fn try_split_dkg(
     Some((zero_id, one_id))
 }
 
+/// Checks if a section with the given members is too small and should merge with its sibling
+pub(crate) fn should_merge(prefix: &Prefix, members: &BTreeSet<NodeState>) -> bool {
+    !prefix.is_empty() && members.len() < min_section_size()
+}
+
+/// Returns the `DkgSessionId` for the section resulting from merging our section with our sibling's
+/// The elders of the merged section are picked among the members of both sections
+pub(crate) fn merge_dkg(
+    our_members: &BTreeSet<NodeState>,
+    sibling_members: &BTreeSet<NodeState>,
+    sap: &SectionAuthorityProvider,
+    section_chain_len: u64,
+    membership_gen: Generation,
+) -> Option<DkgSessionId> {
+    let prefix = sap.prefix();
+    if prefix.is_empty() {
+        return None;
+    }
+
+    let bootstrap_members: BTreeSet<_> = our_members.union(sibling_members).cloned().collect();
+    let elders = elder_candidates(bootstrap_members.iter().cloned(), sap);
+
+    Some(DkgSessionId {
+        prefix: prefix.popped(),
+        elders: BTreeMap::from_iter(elders.iter().map(|node| (node.name(), node.addr()))),
+        section_chain_len,
+        bootstrap_members,
+        membership_gen,
+    })
+}
+
 /// Returns the nodes that should be candidates to become the next elders, sorted by names.
 pub(crate) fn elder_candidates(
     candidates: impl IntoIterator<Item = NodeState>,
@@ -12,6 +12,7 @@ use crate::node::{
     Error, MyNode, NodeContext, Result,
 };
 
+use sn_comms::SendStream;
 use sn_fault_detection::IssueType;
 use sn_interface::{
     messaging::{AntiEntropyKind, AntiEntropyMsg, MsgId, MsgKind, NetworkMsg, WireMsg},
@@ -21,7 +22,6 @@ use sn_interface::{
 
 use bls::PublicKey as BlsPublicKey;
 use itertools::Itertools;
-use qp2p::SendStream;
 use std::collections::BTreeSet;
 use xor_name::XorName;
 
@@ -150,6 +150,9 @@ impl MyNode {
         kind: AntiEntropyKind,
         sender: Participant,
     ) -> Result<Vec<Cmd>> {
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_ae_round();
+
         let sap = section_tree_update.signed_sap.value.clone();
 
         let section_decisions = if let AntiEntropyKind::Update { section_decisions } = &kind {
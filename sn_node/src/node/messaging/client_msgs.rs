@@ -8,14 +8,18 @@
 
 use crate::node::{flow_ctrl::cmds::Cmd, Error, MyNode, NodeContext, Result};
 
+use sn_comms::SendStream;
 use sn_dbc::{
-    get_blinded_amounts_from_transaction, BlindedAmount, DbcTransaction, PublicKey, SpentProof,
-    SpentProofShare, Token,
+    get_blinded_amounts_from_transaction, BlindedAmount, DbcTransaction, Hash, PublicKey,
+    SpentProof, SpentProofShare, Token,
 };
 use sn_interface::{
     dbcs::DbcReason,
     messaging::{
-        data::{ClientMsg, DataCmd, DataQuery, DataResponse, SpendQuery, SpentbookCmd},
+        data::{
+            ClientMsg, DataCmd, DataQuery, DataResponse, Error as DataError, RegisterCmd,
+            SpendQuery, SpentbookCmd,
+        },
         system::NodeQueryResponse,
         AuthorityProof, ClientAuth, MsgId,
     },
@@ -23,15 +27,17 @@ use sn_interface::{
         section_keys::build_spent_proof_share, NetworkKnowledge, SectionTreeUpdate,
     },
     types::{
-        fees::{FeeCiphers, RequiredFee},
+        fees::{FeeCiphers, PaymentProof, RequiredFee, MAX_PAID_ITEMS},
         log_markers::LogMarker,
         register::User,
         ClientId, ReplicatedData,
     },
 };
 
-use qp2p::SendStream;
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
 use xor_name::XorName;
 
 impl MyNode {
@@ -46,6 +52,29 @@ impl MyNode {
         Cmd::send_data_response(msg, correlation_id, client_id, send_stream)
     }
 
+    /// Rejects a msg from a client which has exceeded its rate limits, telling it to back off.
+    pub(crate) fn reject_throttled_client_msg(
+        msg_id: MsgId,
+        retry_after: Duration,
+        client_id: ClientId,
+        send_stream: Option<SendStream>,
+    ) -> Vec<Cmd> {
+        warn!(
+            "{:?}: {msg_id:?} from {client_id:?}, which shall retry after {retry_after:?}",
+            LogMarker::ClientMsgThrottled
+        );
+        let Some(send_stream) = send_stream else {
+            return vec![];
+        };
+        let msg = DataResponse::NetworkIssue(DataError::ClientRateLimited { retry_after });
+        vec![MyNode::send_cmd_error_response_over_stream(
+            msg,
+            msg_id,
+            send_stream,
+            client_id,
+        )]
+    }
+
     /// Handle data query
     pub(crate) async fn handle_data_query_where_stored(
         msg_id: MsgId,
@@ -55,29 +84,37 @@ impl MyNode {
         send_stream: SendStream,
         context: NodeContext,
     ) -> Vec<Cmd> {
-        let response = if let DataQuery::Spentbook(SpendQuery::GetFees(dbc_id)) = query {
-            // We receive this directly from client, as an Elder, since `is_spend` is set to true (that is a very
-            // messy/confusing pattern, to be fixed).
-
-            // The client is asking for the fee to spend a specific dbc, and including the id of that dbc.
-            // The required fee content is encrypted to that dbc id, and so only the holder of the dbc secret
-            // key can unlock the contents.
-            let amount = context.current_fee().as_nano() as f64 * 1.1;
-            let required_fee = RequiredFee::new(
-                Token::from_nano(amount as u64),
-                dbc_id,
-                &context.reward_secret_key,
-            );
-            NodeQueryResponse::GetFees(Ok(required_fee))
-        } else {
-            context
-                .data_storage
-                .query(query, User::Key(auth.public_key))
-                .await
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let response = match query {
+            DataQuery::Spentbook(SpendQuery::GetFees(dbc_id)) => {
+                // We receive this directly from client, as an Elder, since `is_spend` is set to true (that is a very
+                // messy/confusing pattern, to be fixed).
+
+                // The client is asking for the fee to spend a specific dbc, and including the id of that dbc.
+                // The required fee content is encrypted to that dbc id, and so only the holder of the dbc secret
+                // key can unlock the contents.
+                NodeQueryResponse::GetFees(Ok(MyNode::required_fee(dbc_id, &context)))
+            }
+            DataQuery::Spentbook(SpendQuery::GetStoreCost { dbc_id, .. }) => {
+                // Same as above, but for storing a data item in our section. The Client sends this
+                // to the Elders of the section the data name belongs to, which are the ones to be paid.
+                NodeQueryResponse::GetStoreCost(Ok(MyNode::required_fee(dbc_id, &context)))
+            }
+            _ => {
+                context
+                    .data_storage
+                    .query(query, User::Key(auth.public_key))
+                    .await
+            }
         };
 
         trace!("{msg_id:?} data query response at node is: {response:?}");
 
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_client_query(started.elapsed());
+
         let msg = DataResponse::QueryResponse {
             response,
             correlation_id: msg_id,
@@ -86,6 +123,16 @@ impl MyNode {
         vec![Cmd::send_data_response(msg, msg_id, client_id, send_stream)]
     }
 
+    /// The fee we currently require, with some margin, encrypted to the given dbc id.
+    fn required_fee(dbc_id: &PublicKey, context: &NodeContext) -> RequiredFee {
+        let amount = context.current_fee().as_nano() as f64 * 1.1;
+        RequiredFee::new(
+            Token::from_nano(amount as u64),
+            dbc_id,
+            &context.reward_secret_key,
+        )
+    }
+
     /// Handle incoming client msgs.
     /// If this is a store request, and we are an Elder and one of
     /// the `data_copy_count()` nodes, then we will send a wiremsg
@@ -104,6 +151,9 @@ impl MyNode {
             ClientMsg::Cmd(cmd) => {
                 MyNode::handle_data_cmd(cmd, msg_id, client_id, auth, send_stream, context).await
             }
+            ClientMsg::PaidCmd { cmd, payment } => {
+                MyNode::handle_paid_data_cmd(cmd, payment, msg_id, client_id, send_stream, context)
+            }
             ClientMsg::Query(query) => Ok(MyNode::handle_data_query_where_stored(
                 msg_id,
                 &query,
@@ -125,6 +175,22 @@ impl MyNode {
         send_stream: SendStream,
         mut context: NodeContext,
     ) -> Result<Vec<Cmd>> {
+        if data_cmd.requires_payment() {
+            // Stores which need to be paid for only reach the data holders as `NodeDataCmd::StoreData`,
+            // after the Elders have verified the payment sent along in a `ClientMsg::PaidCmd`.
+            warn!(
+                "{msg_id:?} Dropping unpaid store of {:?}",
+                data_cmd.dst_name()
+            );
+            return MyNode::send_error(
+                msg_id,
+                data_cmd,
+                Error::MissingStorePayment,
+                send_stream,
+                client_id,
+            );
+        }
+
         // extract the data from the request
         let data_result: Result<ReplicatedData> = match data_cmd.clone() {
             DataCmd::StoreChunk(chunk) => Ok(ReplicatedData::Chunk(chunk)),
@@ -189,7 +255,6 @@ impl MyNode {
                 return MyNode::forward_spent_share(
                     msg_id,
                     spent_share,
-                    public_key,
                     client_id,
                     send_stream,
                     context,
@@ -205,6 +270,101 @@ impl MyNode {
         }
     }
 
+    /// Handle a store cmd paid for by the client.
+    /// As an Elder, we verify that we have been paid for it, and then forward the data to the data holders.
+    fn handle_paid_data_cmd(
+        data_cmd: DataCmd,
+        payment: PaymentProof,
+        msg_id: MsgId,
+        client_id: ClientId,
+        send_stream: SendStream,
+        context: NodeContext,
+    ) -> Result<Vec<Cmd>> {
+        let data = match data_cmd.clone() {
+            DataCmd::StoreChunk(chunk) => ReplicatedData::Chunk(chunk),
+            DataCmd::Register(cmd @ RegisterCmd::Create { .. }) => {
+                ReplicatedData::RegisterWrite(cmd)
+            }
+            _ => {
+                let error = Error::InvalidStorePayment(
+                    "Only stores of new data are to be paid for".to_string(),
+                );
+                return MyNode::send_error(msg_id, data_cmd, error, send_stream, client_id);
+            }
+        };
+
+        if let Err(error) = MyNode::verify_store_payment(&data.name(), &payment, &context) {
+            warn!("{msg_id:?} Dropping store of {:?}: {error:?}", data.name());
+            return MyNode::send_error(msg_id, data_cmd, error, send_stream, client_id);
+        }
+
+        MyNode::forward_paid_data(msg_id, data, client_id, send_stream, context)
+    }
+
+    /// Verify that the payment covers the data with the given name, that it has been spent,
+    /// and that it includes a sufficient fee to us for all the data it covers in our section.
+    fn verify_store_payment(
+        name: &XorName,
+        payment: &PaymentProof,
+        context: &NodeContext,
+    ) -> Result<()> {
+        // the payment is verified for each data item it covers, so we don't verify those
+        // covering more than a bounded number of them
+        if payment.content.len() > MAX_PAID_ITEMS {
+            return Err(Error::InvalidStorePayment(format!(
+                "The payment covers {} data items, while at most {MAX_PAID_ITEMS} are paid for at once",
+                payment.content.len()
+            )));
+        }
+        if !payment.covers(name) {
+            return Err(Error::InvalidStorePayment(format!(
+                "The payment does not cover data {name:?}"
+            )));
+        }
+
+        // verify the spent proofs
+        MyNode::verify_spent_proofs(&payment.spent_proofs, &context.network_knowledge)?;
+
+        // verify that all inputs have been spent in the payment tx, for the paid content
+        let tx_hash = Hash::from(payment.tx.hash());
+        let reason = payment.reason();
+        for input in &payment.tx.inputs {
+            let is_spent = payment.spent_proofs.iter().any(|proof| {
+                proof.public_key() == &input.public_key
+                    && proof.transaction_hash() == tx_hash
+                    && DbcReason::from(proof.reason()) == reason
+            });
+            if !is_spent {
+                return Err(Error::InvalidStorePayment(format!(
+                    "Input {:?} of the payment has not been spent for the paid content",
+                    input.public_key
+                )));
+            }
+        }
+
+        // we are to be paid for each of the data items that will be stored in our section
+        let prefix = context.network_knowledge.prefix();
+        let items_in_section = payment
+            .content
+            .iter()
+            .filter(|name| prefix.matches(name))
+            .count() as u64;
+        let store_cost = Token::from_nano(context.current_fee().as_nano() * items_in_section);
+
+        MyNode::verify_fee(
+            store_cost,
+            context.reward_secret_key.as_ref(),
+            &payment.tx,
+            context.name,
+            payment.fee_ciphers.clone(),
+        )
+        .map_err(|error| match error {
+            Error::MissingFee => Error::MissingStorePayment,
+            Error::FeeTooLow { paid, required } => Error::StorePaymentTooLow { paid, required },
+            other => other,
+        })
+    }
+
     fn send_error(
         msg_id: MsgId,
         cmd: DataCmd,
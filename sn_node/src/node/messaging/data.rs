@@ -7,31 +7,27 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{
-    flow_ctrl::fault_detection::FaultsCmd, messaging::Recipients, Cmd, Error, MyNode, NodeContext,
-    Result,
+    flow_ctrl::fault_detection::FaultsCmd, messaging::Recipients, Cmd, MyNode, NodeContext, Result,
 };
 use crate::storage::{Error as StorageError, StorageLevel};
 
+use sn_comms::SendStream;
 use sn_dbc::SpentProofShare;
 use sn_interface::{
     data_copy_count,
     messaging::{
-        data::{DataResponse, EditRegister, SignedRegisterEdit},
-        system::{NodeDataCmd, NodeEvent, NodeMsg},
-        ClientAuth, MsgId, MsgKind, WireMsg,
+        data::{DataResponse, SignedRegisterEdit},
+        system::{NodeDataCmd, NodeEvent, NodeMsg, SectionSigShare},
+        MsgId, MsgKind, WireMsg,
     },
     types::{
-        log_markers::LogMarker,
-        register::{Permissions, Policy, Register, User},
-        ClientId, DataError, Keypair, NodeId, Participant, PublicKey, RegisterCmd, ReplicatedData,
-        SPENTBOOK_TYPE_TAG,
+        log_markers::LogMarker, register::Register, ClientId, DataError, NodeId, Participant,
+        PublicKey, ReplicatedData, ReplicatedRegisterLog,
     },
 };
 
-use bytes::BufMut;
 use itertools::Itertools;
-use qp2p::SendStream;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 use tracing::info;
 use xor_name::XorName;
 
@@ -42,6 +38,7 @@ impl MyNode {
         wire_msg: WireMsg,
         client_id: ClientId,
         client_stream: SendStream,
+        register_edit: Option<SignedRegisterEdit>,
     ) -> Cmd {
         let msg_id = wire_msg.msg_id();
         // We accept that we might be sending a WireMsg to ourselves.
@@ -51,11 +48,7 @@ impl MyNode {
 
         let kind = wire_msg.kind();
         let query_index = match kind {
-            MsgKind::Client {
-                auth: _,
-                is_spend: _,
-                query_index,
-            } => *query_index,
+            MsgKind::Client { query_index, .. } => *query_index,
             _ => None,
         };
 
@@ -112,6 +105,7 @@ impl MyNode {
             targets,
             client_stream,
             client_id,
+            register_edit,
         }
     }
 
@@ -145,18 +139,15 @@ impl MyNode {
     pub(crate) fn forward_spent_share(
         msg_id: MsgId,
         share: SpentProofShare,
-        public_key: bls::PublicKey,
         client_id: ClientId,
         client_stream: SendStream,
         context: NodeContext,
     ) -> Result<Vec<Cmd>> {
         debug!("{msg_id:?} Forwarding SpentProofShare for Spentbook.");
 
-        let reg_cmd = gen_register_cmd(share, public_key, &context)?;
-        let name = reg_cmd.name();
-        let node_msg = NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(
-            ReplicatedData::SpentbookWrite(reg_cmd),
-        ));
+        let data = ReplicatedData::SpentProofShare(share);
+        let name = data.name();
+        let node_msg = NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(data));
         let section_key = context
             .network_knowledge
             .section_auth_by_name(&name)?
@@ -173,12 +164,64 @@ impl MyNode {
             targets,
             client_stream,
             client_id,
+            register_edit: None,
+        }])
+    }
+
+    /// Select targets to send out the data of a paid store cmd, once the payment
+    /// has been verified by us. The response is then forwarded back on to the client.
+    pub(crate) fn forward_paid_data(
+        msg_id: MsgId,
+        data: ReplicatedData,
+        client_id: ClientId,
+        client_stream: SendStream,
+        context: NodeContext,
+    ) -> Result<Vec<Cmd>> {
+        let name = data.name();
+        debug!("{msg_id:?} Forwarding paid data {name:?}.");
+
+        let node_msg = NodeMsg::NodeDataCmd(NodeDataCmd::StoreData(data));
+        let section_key = context
+            .network_knowledge
+            .section_auth_by_name(&name)?
+            .section_key();
+        let dst = sn_interface::messaging::Dst { name, section_key };
+        let (kind, payload) = MyNode::serialize_node_msg(context.name, &node_msg)?;
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+        wire_msg.set_compressible(!node_msg.carries_chunks());
+
+        let targets = MyNode::target_data_holders(&context, name, None);
+        if data_copy_count() > targets.len() {
+            error!(
+                "InsufficientNodeCount for storing data reliably for {msg_id:?}, {:?}",
+                targets.len()
+            );
+            let error = DataError::InsufficientNodeCount {
+                prefix: context.network_knowledge.prefix(),
+                expected: data_copy_count() as u8,
+                found: targets.len() as u8,
+            };
+            return Ok(vec![MyNode::send_cmd_error_response_over_stream(
+                DataResponse::NetworkIssue(error),
+                msg_id,
+                client_stream,
+                client_id,
+            )]);
+        }
+        debug!("{msg_id:?} Forwarding paid data to data holders: {targets:?}");
+
+        Ok(vec![Cmd::SendAndForwardResponseToClient {
+            wire_msg,
+            targets,
+            client_stream,
+            client_id,
+            register_edit: None,
         }])
     }
 
     /// Used to fetch the list of holders for given name of data.
     /// Sorts members by closeness to data address, returns data_copy_count of them
-    fn target_data_holders(
+    pub(super) fn target_data_holders(
         context: &NodeContext,
         target: XorName,
         query_index: Option<usize>,
@@ -219,19 +262,14 @@ impl MyNode {
     ) -> Result<Vec<Cmd>> {
         let mut cmds = vec![];
 
-        let section_pk = PublicKey::Bls(context.network_knowledge.section_key());
-        let node_keypair = Keypair::Ed25519(context.keypair.clone());
-
         let mut is_full = false;
         let data_batch_is_empty = data_batch.is_empty();
 
         let mut new_storage_level_passed = false;
+        let section_chain = context.network_knowledge.section_chain();
 
         for data in data_batch {
-            let store_result = context
-                .data_storage
-                .store(&data, section_pk, node_keypair.clone())
-                .await;
+            let store_result = context.data_storage.store(&data, &section_chain).await;
 
             // This may return a DatabaseFull error... but we should have reported StorageError::NotEnoughSpace
             // well before this
@@ -285,51 +323,90 @@ impl MyNode {
 
         Ok(cmds)
     }
-}
 
-/// Generate the RegisterCmd to write the SpentProofShare as an entry in the Spentbook
-/// (Register).
-fn gen_register_cmd(
-    spent_proof_share: SpentProofShare,
-    public_key: bls::PublicKey,
-    context: &NodeContext,
-) -> Result<RegisterCmd> {
-    let mut permissions = BTreeMap::new();
-    let _ = permissions.insert(User::Anyone, Permissions::new(true));
-
-    // use our own keypair for generating the register command
-    let own_keypair = Keypair::Ed25519(context.keypair.clone());
-    let owner = User::Key(own_keypair.public_key());
-    let policy = Policy { owner, permissions };
-
-    let mut register = Register::new(
-        owner,
-        XorName::from_content(&public_key.to_bytes()),
-        SPENTBOOK_TYPE_TAG,
-        policy,
-    );
-    let mut entry = vec![].writer();
-    rmp_serde::encode::write(&mut entry, &spent_proof_share).map_err(|err| {
-        Error::SpentbookError(format!(
-            "Failed to serialise SpentProofShare to insert it into the spentbook (Register): {err:?}",
-        ))
-    })?;
-
-    let (_, op) = register.write(entry.into_inner(), BTreeSet::default())?;
-    let op = EditRegister {
-        address: *register.address(),
-        edit: op,
-    };
-
-    let signature = own_keypair.sign(&bincode::serialize(&op)?);
-    let signed_edit = SignedRegisterEdit {
-        op,
-        auth: ClientAuth {
-            public_key: own_keypair.public_key(),
-            signature,
-        },
-    };
-
-    debug!("Successfully generated spent proof share for spend request");
-    Ok(RegisterCmd::Edit(signed_edit))
+    /// Asks the Elders of our section to sign a snapshot of each of the Registers we hold whose
+    /// log grew beyond the number of cmds a snapshot is to be built from.
+    pub(crate) async fn ask_elders_to_sign_register_snapshots(context: &NodeContext) -> Vec<Cmd> {
+        context
+            .data_storage
+            .register_logs_to_snapshot()
+            .await
+            .into_iter()
+            .map(|log| {
+                debug!(
+                    "Asking the Elders to sign a snapshot of Register {:?}, logging {} cmd/s",
+                    log.address,
+                    log.op_log.len()
+                );
+                let msg = NodeMsg::NodeDataCmd(NodeDataCmd::SignRegisterSnapshot(log));
+                MyNode::send_to_elders(context, msg)
+            })
+            .collect()
+    }
+
+    /// Builds the snapshot of a Register from the log sent by one of its holders in our section,
+    /// sending it back along with our share of the section signature over it.
+    pub(crate) fn sign_register_snapshot(
+        context: &NodeContext,
+        log: ReplicatedRegisterLog,
+        sender: NodeId,
+    ) -> Result<Option<Cmd>> {
+        if !context.is_elder {
+            warn!("Ignoring the request to sign a snapshot of a Register from {sender}, as we are not an Elder");
+            return Ok(None);
+        }
+        if !context
+            .network_knowledge
+            .prefix()
+            .matches(log.address.name())
+        {
+            warn!(
+                "Ignoring the request to sign a snapshot of Register {:?} from {sender}, which is not of our section",
+                log.address
+            );
+            return Ok(None);
+        }
+
+        let section_chain = context.network_knowledge.section_chain();
+        let (snapshot, signed_bytes) = context
+            .data_storage
+            .build_register_snapshot(&log, &section_chain)?;
+        let section_key = context.network_knowledge.section_key();
+        let key_share = context.section_keys_provider.key_share(&section_key)?;
+        let sig_share = MyNode::sign_with_key_share(signed_bytes, &key_share);
+
+        trace!(
+            "Signed the snapshot of Register {:?} for {sender}",
+            log.address
+        );
+        let msg = NodeMsg::NodeDataCmd(NodeDataCmd::RegisterSnapshotShare {
+            snapshot,
+            sig_share,
+        });
+        Ok(Some(Cmd::send_msg(
+            msg,
+            Recipients::Single(Participant::from_node(sender)),
+        )))
+    }
+
+    /// Aggregates the share of an Elder's signature over the snapshot of a Register we asked
+    /// for, storing the snapshot once signed by our section.
+    pub(crate) async fn aggregate_register_snapshot(
+        context: &NodeContext,
+        snapshot: Register,
+        sig_share: SectionSigShare,
+        sender: NodeId,
+    ) -> Result<()> {
+        if !context.network_knowledge.is_elder(&sender.name()) {
+            warn!("Ignoring the share of a Register snapshot signature from {sender}, which is not one of our Elders");
+            return Ok(());
+        }
+
+        let section_chain = context.network_knowledge.section_chain();
+        let _ = context
+            .data_storage
+            .add_register_snapshot_sig_share(snapshot, sig_share, &section_chain)
+            .await?;
+        Ok(())
+    }
 }
@@ -68,6 +68,7 @@ impl MyNode {
     /// DKG is triggered by the following events:
     /// - A change in the Elders
     /// - Section Split
+    /// - Section Merge
     pub(crate) fn send_dkg_start(&mut self, session_id: DkgSessionId) -> Result<Vec<Cmd>> {
         // Send DKG start to all candidates
         let recipients = Vec::from_iter(session_id.elder_ids());
@@ -169,7 +170,7 @@ impl MyNode {
     ) -> Result<Option<SectionSig>> {
         // check sig share
         let public_key = elder_sig.public_key_set.public_key();
-        if self.network_knowledge.section_key() != public_key {
+        if !self.is_dkg_authority(session_id, &public_key) {
             return Err(Error::InvalidKeyShareSectionKey);
         }
         let serialized_session_id = bincode::serialize(session_id)?;
@@ -186,6 +187,24 @@ impl MyNode {
             })
     }
 
+    /// DKG sessions are authorised by our section, except for the merge of our section with our
+    /// sibling which can be authorised by either section
+    fn is_dkg_authority(&self, session_id: &DkgSessionId, section_key: &BlsPublicKey) -> bool {
+        if &self.network_knowledge.section_key() == section_key {
+            return true;
+        }
+        let our_prefix = self.network_knowledge.prefix();
+        !our_prefix.is_empty()
+            && session_id.prefix == our_prefix.popped()
+            && self
+                .network_knowledge
+                .section_tree()
+                .get_signed(&our_prefix.sibling())
+                .map_or(false, |sibling_sap| {
+                    &sibling_sap.section_key() == section_key
+                })
+    }
+
     pub(crate) fn handle_dkg_start(
         &mut self,
         session_id: DkgSessionId,
@@ -234,9 +253,14 @@ impl MyNode {
             return Ok(vec![]);
         };
 
-        // ignore DkgStart from old chains
+        // ignore DkgStart from old chains, unless it's for a merge authorised by our sibling
+        // whose chain length isn't comparable to ours
         let current_chain_len = self.network_knowledge.section_chain_len();
-        if session_id.section_chain_len < current_chain_len {
+        let is_merge = self
+            .network_knowledge
+            .prefix()
+            .is_extension_of(&session_id.prefix);
+        if !is_merge && session_id.section_chain_len < current_chain_len {
             trace!("Skipping DkgStart for older chain: s{}", session_id.sh());
             return Ok(vec![]);
         }
@@ -250,6 +274,10 @@ impl MyNode {
         let _existing = self
             .dkg_sessions_info
             .insert(session_id.hash(), session_info);
+        #[cfg(feature = "metrics")]
+        if _existing.is_none() {
+            crate::node::metrics::record_dkg_session();
+        }
 
         // gen key
         let (ephemeral_pub_key, sig) =
@@ -305,7 +333,7 @@ impl MyNode {
         // check the signature
         let serialized_session_id = bincode::serialize(session_id)?;
         let section_sig = section_auth.clone().into_inner();
-        if self.network_knowledge.section_key() != section_sig.public_key {
+        if !self.is_dkg_authority(session_id, &section_sig.public_key) {
             warn!(
                 "Invalid section key in dkg auth proof in s{:?}: {sender:?}",
                 session_id.sh()
@@ -849,7 +877,9 @@ impl MyNode {
                 sap: sap.clone(),
                 sig_share: sig_share.clone(),
             };
-            let current_elders: Vec<_> = self.network_knowledge.section_auth().elders_vec();
+            let current_elders = self
+                .merge_authority_elders(&sap)
+                .unwrap_or_else(|| self.network_knowledge.section_auth().elders_vec());
             let (other_elders, myself) = self.split_nodes_and_self(current_elders);
             let nodes = Recipients::Multiple(other_elders);
             cmds.push(Cmd::send_msg(msg, nodes));
@@ -865,6 +895,37 @@ impl MyNode {
 
         Ok(cmds)
     }
+
+    // The elders of the section which authorised the DKG for the merged section, if the SAP is
+    // for our parent prefix. They carry out the handover, which could be our sibling's elders.
+    fn merge_authority_elders(&self, sap: &SectionAuthorityProvider) -> Option<Vec<NodeId>> {
+        if !self
+            .network_knowledge
+            .prefix()
+            .is_extension_of(&sap.prefix())
+        {
+            return None;
+        }
+        let elders = sap.names();
+        let authority_key = self
+            .dkg_sessions_info
+            .values()
+            .find(|info| {
+                info.session_id.prefix == sap.prefix()
+                    && info
+                        .session_id
+                        .elders
+                        .keys()
+                        .copied()
+                        .collect::<BTreeSet<_>>()
+                        == elders
+            })
+            .map(|info| info.authority.public_key)?;
+        self.network_knowledge
+            .section_tree()
+            .get_signed_by_key(&authority_key)
+            .map(|authority_sap| authority_sap.elders_vec())
+    }
 }
 
 #[cfg(test)]
@@ -9,7 +9,7 @@
 use crate::node::{
     flow_ctrl::cmds::Cmd,
     handover::{Error as HandoverError, Handover},
-    membership::{elder_candidates, try_split_dkg},
+    membership::{elder_candidates, merge_dkg, try_split_dkg},
     messaging::Recipients,
     Error, MyNode, NodeContext, NodeMsg, Result,
 };
@@ -82,7 +82,9 @@ impl MyNode {
         let is_extension_prefix = sap
             .prefix()
             .is_extension_of(&self.network_knowledge.prefix());
-        if !equal_prefix && !is_extension_prefix {
+        let is_parent_prefix = !self.network_knowledge.prefix().is_empty()
+            && sap.prefix() == self.network_knowledge.prefix().popped();
+        if !equal_prefix && !is_extension_prefix && !is_parent_prefix {
             // Other section. We shouldn't be receiving or updating a SAP for
             // a remote section here, that is done with a AE msg response.
             trace!(
@@ -102,6 +104,16 @@ impl MyNode {
             return self.propose_handover_consensus(SapCandidate::ElderHandover(signed_sap));
         }
 
+        // handle section merge (2 to 1), only if we agreed to carry the merge out
+        if is_parent_prefix {
+            if self.pending_section_merge.is_none() {
+                trace!("Ignoring merge handover request, no merge with our sibling is pending");
+                return Ok(vec![]);
+            }
+            debug!("Propose section merge handover to: {:?}", signed_sap.value);
+            return self.propose_handover_consensus(SapCandidate::SectionMerge(signed_sap));
+        }
+
         // add to pending split SAP candidates
         // those are stored in a mapping from Generation to BTreeSet so the order in the set is deterministic
         let section_candidates_for_gen = self
@@ -240,6 +252,34 @@ impl MyNode {
                     };
                 }
             }
+            SapCandidate::SectionMerge(sap) => {
+                let Some(merge_agreement) = self.pending_section_merge.clone() else {
+                    error!("Not promoting the merged section, our sibling's agreement to the merge is missing");
+                    return Ok(cmds);
+                };
+                let serialized_sap = bincode::serialize(&sap.sig.public_key)?;
+                let sig_share = self.sign_with_section_key_share(serialized_sap)?;
+                let msg = NodeMsg::SectionMergePromotion {
+                    sap: sap.clone(),
+                    sig_share: sig_share.clone(),
+                    merge_agreement: merge_agreement.clone(),
+                };
+                cmds.push(Cmd::send_msg(msg, nodes));
+
+                // handle our own if we are elder
+                if let Some(elder) = myself {
+                    match self.handle_section_merge_promotion(
+                        MsgId::new(),
+                        sap,
+                        sig_share,
+                        merge_agreement,
+                        elder,
+                    ) {
+                        Ok(c) => cmds.extend(c),
+                        Err(e) => error!("Failed to handle our own merge promotion: {e:?}"),
+                    };
+                }
+            }
         };
         Ok(cmds)
     }
@@ -377,6 +417,38 @@ impl MyNode {
         }
     }
 
+    fn check_section_merge_candidates(&self, sap: &SectionAuthorityProvider) -> Result<()> {
+        // in merge handover, the candidates are picked among the members of both sections
+        let Some(merge_agreement) = &self.pending_section_merge else {
+            return Err(Error::InvalidMergeCandidates);
+        };
+        let our_sap = self.network_knowledge.section_auth();
+        let our_members = self
+            .network_knowledge
+            .members_at_gen(sap.membership_gen())
+            .into_values()
+            .collect();
+        let dummy_chain_len = 0;
+
+        let received_candidates: BTreeSet<NodeId> = sap.elders().copied().collect();
+        let expected_candidates: BTreeSet<NodeId> = merge_dkg(
+            &our_members,
+            &merge_agreement.members,
+            &our_sap,
+            dummy_chain_len,
+            sap.membership_gen(),
+        )
+        .ok_or(Error::InvalidMergeCandidates)?
+        .elder_ids()
+        .collect();
+
+        if received_candidates != expected_candidates {
+            trace!("InvalidMergeCandidates: received SAP at gen {} with candidates {:#?}, expected candidates {:#?}", sap.membership_gen(), received_candidates, expected_candidates);
+            return Err(Error::InvalidMergeCandidates);
+        }
+        Ok(())
+    }
+
     fn check_sap_candidate_prefix(&self, sap_candidate: &SapCandidate) -> Result<()> {
         let section_prefix = self.network_knowledge.prefix();
         match sap_candidate {
@@ -404,6 +476,14 @@ impl MyNode {
                     Err(Error::InvalidSectionPrefixForSplitCandidates)
                 }
             }
+            SapCandidate::SectionMerge(sap) => {
+                // section merge, must be our parent prefix
+                if !section_prefix.is_empty() && sap.prefix() == section_prefix.popped() {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidSectionPrefixForMergeCandidate)
+                }
+            }
         }
     }
 
@@ -422,6 +502,10 @@ impl MyNode {
                 self.check_sap_sig(authed_sap2)?;
                 self.check_section_split_candidates(&authed_sap1.value, &authed_sap2.value)
             }
+            SapCandidate::SectionMerge(authed_sap) => {
+                self.check_sap_sig(authed_sap)?;
+                self.check_section_merge_candidates(&authed_sap.value)
+            }
         }
     }
 
@@ -43,7 +43,7 @@ mod tests {
         MIN_ADULT_AGE,
     };
 
-    use sn_comms::CommEvent;
+    use sn_comms::{CommEvent, MemoryNetwork};
     use sn_interface::{
         elder_count, init_logger,
         messaging::{system::JoinResponse, MsgId, NetworkMsg},
@@ -96,6 +96,7 @@ mod tests {
         let network_knowledge = env.get_network_knowledge(prefix, None)?;
 
         let (joining_node_name, mut joining_node_handle) = initialize_join(
+            env.memory_network(),
             prefix,
             &network_knowledge,
             &mut node_instances,
@@ -136,7 +137,7 @@ mod tests {
 
         let joining_node = {
             let (info, comm, _incoming_msg_receiver) =
-                gen_info_with_comm(MIN_ADULT_AGE, Some(prefix));
+                gen_info_with_comm(env.memory_network(), MIN_ADULT_AGE, Some(prefix));
             build_a_node_instance(&info, &comm, &network_knowledge)?
         };
 
@@ -203,7 +204,7 @@ mod tests {
 
         let joining_node = {
             let (info, comm, _incoming_msg_receiver) =
-                gen_info_with_comm(MIN_ADULT_AGE, Some(prefix));
+                gen_info_with_comm(env.memory_network(), MIN_ADULT_AGE, Some(prefix));
             build_a_node_instance(&info, &comm, &network_knowledge)?
         };
 
@@ -247,7 +248,7 @@ mod tests {
 
         let joining_node = {
             let (info, comm, _incoming_msg_receiver) =
-                gen_info_with_comm(MIN_ADULT_AGE, Some(wrong_prefix));
+                gen_info_with_comm(env.memory_network(), MIN_ADULT_AGE, Some(wrong_prefix));
             build_a_node_instance(&info, &comm, &network_knowledge)?
         };
 
@@ -286,8 +287,11 @@ mod tests {
         let network_knowledge = env.get_network_knowledge(section_prefix, None)?;
 
         let joining_node = {
-            let (info, comm, _incoming_msg_receiver) =
-                gen_info_with_comm(MIN_ADULT_AGE + 1, Some(section_prefix));
+            let (info, comm, _incoming_msg_receiver) = gen_info_with_comm(
+                env.memory_network(),
+                MIN_ADULT_AGE + 1,
+                Some(section_prefix),
+            );
             build_a_node_instance(&info, &comm, &network_knowledge)?
         };
 
@@ -327,7 +331,7 @@ mod tests {
 
         let joining_node = {
             let (info, comm, _incoming_msg_receiver) =
-                gen_info_with_comm(MIN_ADULT_AGE, Some(section_prefix));
+                gen_info_with_comm(env.memory_network(), MIN_ADULT_AGE, Some(section_prefix));
             build_a_node_instance(&info, &comm, &network_knowledge)?
         };
 
@@ -405,6 +409,7 @@ mod tests {
 
         // elder joins the network
         let (joining_node_name, mut joining_node_handle) = initialize_join(
+            env.memory_network(),
             prefix,
             &network_knowledge,
             &mut node_instances,
@@ -429,6 +434,7 @@ mod tests {
 
         // adult joins the network with the old network_knowledge, it will go through ae steps within the join process
         let (joining_node_name, mut joining_node_handle) = initialize_join(
+            env.memory_network(),
             prefix,
             &network_knowledge,
             &mut node_instances,
@@ -457,6 +463,7 @@ mod tests {
 
     // Create a new adult and send the TryJoinNetwork to the section
     async fn initialize_join(
+        network: &MemoryNetwork,
         prefix: Prefix,
         network_knowledge: &NetworkKnowledge,
         node_instances: &mut BTreeMap<XorName, Arc<RwLock<TestNode>>>,
@@ -464,7 +471,8 @@ mod tests {
         msg_tracker: Arc<RwLock<TestMsgTracker>>,
     ) -> Result<(XorName, Option<(XorName, JoinHandle<Result<Vec<Cmd>>>)>)> {
         // create the new adult
-        let (info, comm, incoming_msg_receiver) = gen_info_with_comm(MIN_ADULT_AGE, Some(prefix));
+        let (info, comm, incoming_msg_receiver) =
+            gen_info_with_comm(network, MIN_ADULT_AGE, Some(prefix));
         let node = build_a_node_instance(&info, &comm, network_knowledge)?;
         let name = node.info().name();
         let node = Arc::new(RwLock::new(TestNode::new(node, msg_tracker.clone())));
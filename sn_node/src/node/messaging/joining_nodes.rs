@@ -10,7 +10,7 @@ use crate::node::{
     flow_ctrl::cmds::Cmd, messaging::Recipients, Error, MyNode, NodeContext, Result,
 };
 
-use qp2p::SendStream;
+use sn_comms::SendStream;
 use sn_interface::{
     messaging::{
         system::{JoinResponse, NodeMsg},
@@ -221,6 +221,11 @@ impl MyNode {
 
         cmds.extend(self.trigger_dkg()?);
 
+        match self.propose_section_merge() {
+            Ok(merge_cmds) => cmds.extend(merge_cmds),
+            Err(err) => error!("Failed to propose a merge with our sibling section: {err:?}"),
+        }
+
         cmds.extend(self.send_ae_update_to_our_section()?);
 
         self.fault_detection_retain_only(
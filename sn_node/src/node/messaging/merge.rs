@@ -0,0 +1,190 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    flow_ctrl::cmds::Cmd,
+    membership::{merge_dkg, should_merge},
+    messaging::Recipients,
+    MyNode, Result,
+};
+
+use sn_interface::{
+    messaging::{
+        system::{NodeMsg, SectionSigned},
+        MsgId, SectionSigShare,
+    },
+    network_knowledge::{NodeState, SectionAuthUtils, SectionMergeAgreement, SectionTreeUpdate},
+    types::{log_markers::LogMarker, NodeId},
+};
+
+use std::collections::BTreeSet;
+
+impl MyNode {
+    /// If our section shrank below the minimum section size, proposes to the elders of our
+    /// sibling section to merge both sections into our parent prefix.
+    /// The proposal is signed with our section key share, so the sibling can aggregate
+    /// the agreement of our section to the merge.
+    pub(crate) fn propose_section_merge(&self) -> Result<Vec<Cmd>> {
+        let prefix = self.network_knowledge.prefix();
+        let members = self.network_knowledge.section_members();
+        if self.is_not_elder() || !should_merge(&prefix, &members) {
+            return Ok(vec![]);
+        }
+
+        let section_tree = self.network_knowledge.section_tree();
+        let Some(sibling_sap) = section_tree.get_signed(&prefix.sibling()) else {
+            // our sibling has split further or we don't know it yet, it can't merge with us
+            debug!("Not proposing a merge of {prefix:?}, sibling section is unknown");
+            return Ok(vec![]);
+        };
+
+        info!(
+            "{}: {prefix:?} has {} members, proposing to merge with {:?}",
+            LogMarker::MergeAttempt,
+            members.len(),
+            sibling_sap.prefix()
+        );
+
+        let merge_agreement = SectionMergeAgreement {
+            merged_prefix: prefix.popped(),
+            section_key: self.network_knowledge.section_key(),
+            members: members.clone(),
+        };
+        let payload = bincode::serialize(&merge_agreement)?;
+        let sig_share = self.sign_with_section_key_share(payload)?;
+        let section_tree_update = section_tree.generate_section_tree_update(&prefix)?;
+
+        let msg = NodeMsg::ProposeSectionMerge {
+            section_tree_update,
+            members,
+            sig_share,
+        };
+        let recipients = Recipients::Multiple(sibling_sap.elders().cloned().collect());
+
+        Ok(vec![Cmd::send_msg(msg, recipients)])
+    }
+
+    /// Handles a merge proposal from an elder of our sibling section.
+    /// Once the agreement of our sibling is aggregated, starts the DKG
+    /// for the elders of the merged section.
+    pub(crate) fn handle_section_merge_proposal(
+        &mut self,
+        msg_id: MsgId,
+        section_tree_update: SectionTreeUpdate,
+        members: BTreeSet<NodeState>,
+        sig_share: SectionSigShare,
+        sender: NodeId,
+    ) -> Result<Vec<Cmd>> {
+        trace!("Handling section merge proposal {msg_id:?} from {sender:?}");
+        let our_prefix = self.network_knowledge.prefix();
+        let their_sap = section_tree_update.signed_sap.clone();
+
+        if self.is_not_elder() {
+            trace!("Ignoring merge proposal {msg_id:?}, we are not an elder");
+            return Ok(vec![]);
+        }
+        if our_prefix.is_empty() || their_sap.prefix() != our_prefix.sibling() {
+            trace!("Ignoring merge proposal {msg_id:?} from a section which isn't our sibling");
+            return Ok(vec![]);
+        }
+
+        // make sure we know about the latest SAP of our sibling
+        if let Err(err) = self
+            .network_knowledge
+            .update_sap_knowledge_if_valid(section_tree_update, &self.name())
+        {
+            warn!("Ignoring merge proposal {msg_id:?} with untrusted SAP: {err:?}");
+            return Ok(vec![]);
+        }
+        let Some(sibling_sap) = self
+            .network_knowledge
+            .section_tree()
+            .get_signed(&our_prefix.sibling())
+            .cloned()
+        else {
+            warn!("Ignoring merge proposal {msg_id:?}, sibling section is unknown");
+            return Ok(vec![]);
+        };
+
+        let sibling_key = sibling_sap.section_key();
+        if sig_share.public_key_set.public_key() != sibling_key
+            || !sibling_sap.contains_elder(&sender.name())
+        {
+            warn!("Ignoring merge proposal {msg_id:?} not signed by our sibling's current elders");
+            return Ok(vec![]);
+        }
+        if members
+            .iter()
+            .any(|node| !sibling_sap.prefix().matches(&node.name()))
+        {
+            warn!("Ignoring merge proposal {msg_id:?} with members out of our sibling's prefix");
+            return Ok(vec![]);
+        }
+
+        // If both sections shrank, both propose to merge. Only the section at the
+        // 0 side of the parent prefix carries the merge out, to avoid concurrent DKGs.
+        let our_members = self.network_knowledge.section_members();
+        let we_are_one_side = our_prefix == our_prefix.popped().pushed(true);
+        if should_merge(&our_prefix, &our_members) && we_are_one_side {
+            trace!("Ignoring merge proposal {msg_id:?}, our sibling carries the merge out");
+            return Ok(vec![]);
+        }
+
+        let merge_agreement = SectionMergeAgreement {
+            merged_prefix: our_prefix.popped(),
+            section_key: sibling_key,
+            members,
+        };
+        let payload = bincode::serialize(&merge_agreement)?;
+        let merge_agreement = match self
+            .section_merge_aggregator
+            .try_aggregate(&payload, sig_share)
+        {
+            Ok(Some(sig)) => {
+                trace!("Merge proposal {msg_id:?} successfully aggregated");
+                SectionSigned::new(merge_agreement, sig)
+            }
+            Ok(None) => {
+                trace!("Merge proposal {msg_id:?} acknowledged, waiting for more...");
+                return Ok(vec![]);
+            }
+            Err(err) => {
+                error!("Failed to aggregate merge proposal {msg_id:?} from {sender}: {err:?}");
+                return Ok(vec![]);
+            }
+        };
+
+        let Some(membership) = &self.membership else {
+            warn!(
+                "Cannot carry out the merge with {:?}, no membership instance",
+                sibling_sap.prefix()
+            );
+            return Ok(vec![]);
+        };
+        let Some(session_id) = merge_dkg(
+            &our_members,
+            &merge_agreement.members,
+            &self.network_knowledge.section_auth(),
+            self.network_knowledge.section_chain_len(),
+            membership.generation(),
+        ) else {
+            return Ok(vec![]);
+        };
+
+        info!(
+            "{}: merging {our_prefix:?} and {:?} into {:?}",
+            LogMarker::MergeAttempt,
+            sibling_sap.prefix(),
+            session_id.prefix
+        );
+        // the agreement of our sibling goes along with the merged SAP, for it to be trusted
+        self.pending_section_merge = Some(merge_agreement);
+
+        self.send_dkg_start(session_id)
+    }
+}
@@ -14,6 +14,7 @@ mod handover;
 mod join_section;
 mod joining_nodes;
 mod membership;
+mod merge;
 pub(crate) mod node_msgs;
 mod promotion;
 mod relocation;
@@ -21,19 +22,24 @@ mod section_state;
 mod serialize;
 mod signature;
 mod streams;
+mod subscriptions;
 mod update_section;
 
 use crate::node::{flow_ctrl::cmds::Cmd, Error, MyNode, NodeContext, Result};
+
+use sn_comms::SendStream;
 use sn_interface::{
-    messaging::{AntiEntropyMsg, MsgKind, NetworkMsg, WireMsg},
+    messaging::{
+        data::{ClientMsg, DataCmd, DataQuery, RegisterCmd, RegisterQuery},
+        AntiEntropyMsg, AuthorityProof, MsgKind, NetworkMsg, WireMsg,
+    },
     types::{log_markers::LogMarker, ClientId, NodeId, Participant},
 };
 
-use qp2p::SendStream;
 use std::collections::BTreeSet;
 
 #[derive(Debug, Clone)]
-pub enum Recipients {
+pub(crate) enum Recipients {
     Single(Participant),
     Multiple(BTreeSet<NodeId>),
 }
@@ -66,12 +72,12 @@ impl MyNode {
     pub(crate) async fn handle_msg(
         context: NodeContext,
         sender: Participant,
-        wire_msg: WireMsg,
+        mut wire_msg: WireMsg,
         send_stream: Option<SendStream>,
     ) -> Result<Vec<Cmd>> {
         let is_elder = context.is_elder;
         let msg_id = wire_msg.msg_id();
-        let msg_kind = wire_msg.kind();
+        let msg_kind = wire_msg.kind().clone();
 
         trace!("Handling msg {msg_id:?}. from {sender:?} Checking for AE first...");
 
@@ -87,6 +93,40 @@ impl MyNode {
 
         debug!("{msg_id:?} was sent from a member: {sent_from_a_member:?}");
 
+        // msgs sent by clients to us as an Elder are admitted before we do any work for them,
+        // decompressing them included, by their source address, and then, once their signature
+        // is verified, by their key
+        if let MsgKind::Client { auth, .. } = &msg_kind {
+            if is_elder && !sent_from_a_member {
+                let client_id = ClientId::from(sender);
+                let limiter = &context.client_rate_limiter;
+                if let Err(retry_after) = limiter.try_admit_addr(client_id.addr()) {
+                    return Ok(MyNode::reject_throttled_client_msg(
+                        msg_id,
+                        retry_after,
+                        client_id,
+                        send_stream,
+                    ));
+                }
+                if let Err(error) = wire_msg.decompress_payload() {
+                    warn!("Dropping {msg_id:?} from {client_id}, as its payload can't be decompressed: {error:?}");
+                    return Ok(vec![]);
+                }
+                if let Err(error) = AuthorityProof::verify(auth.clone(), &wire_msg.payload) {
+                    warn!("Dropping {msg_id:?} from {client_id}, as its signature is invalid: {error:?}");
+                    return Ok(vec![]);
+                }
+                if let Err(retry_after) = limiter.try_admit_key(auth.public_key) {
+                    return Ok(MyNode::reject_throttled_client_msg(
+                        msg_id,
+                        retry_after,
+                        client_id,
+                        send_stream,
+                    ));
+                }
+            }
+        }
+
         let is_for_us =
             sent_from_a_member || wire_msg.dst().name == context.name || msg_kind.is_client_spend();
         debug!(
@@ -133,14 +173,49 @@ impl MyNode {
                     return Err(Error::NoClientResponseStream);
                 };
 
+                let client_id = ClientId::from(sender);
+                // register subscriptions are kept by us, once the holders checked the client
+                // may read the register, while the edits we forward are notified to the clients
+                // subscribed to them, once their holders applied them,
+                // thus only the register msgs, told from the header, are decoded to look into
+                let mut register_edit = None;
+                let register_msg = if msg_kind.is_register() {
+                    wire_msg.clone().into_msg().ok()
+                } else {
+                    None
+                };
+                if let Some(NetworkMsg::Client { msg, .. }) = register_msg {
+                    match msg {
+                        ClientMsg::Query(DataQuery::Register(RegisterQuery::Subscribe(
+                            address,
+                        ))) => {
+                            if context
+                                .register_subscriptions
+                                .is_subscribed(&address, &client_id)
+                            {
+                                return Ok(MyNode::handle_register_subscription(
+                                    &context, msg_id, address, client_id, stream,
+                                ));
+                            }
+                            return Ok(vec![MyNode::forward_register_subscription_to_holders(
+                                &context, wire_msg, address, client_id, stream,
+                            )]);
+                        }
+                        ClientMsg::Cmd(DataCmd::Register(RegisterCmd::Edit(edit))) => {
+                            register_edit = Some(edit);
+                        }
+                        _ => {}
+                    }
+                }
+
                 trace!("{:?}: {msg_id:?} ", LogMarker::ClientMsgToBeForwarded);
-                let cmd = MyNode::forward_data_and_respond_to_client(
+                return Ok(vec![MyNode::forward_data_and_respond_to_client(
                     context,
                     wire_msg,
-                    ClientId::from(sender),
+                    client_id,
                     stream,
-                );
-                return Ok(vec![cmd]);
+                    register_edit,
+                )]);
             }
         }
 
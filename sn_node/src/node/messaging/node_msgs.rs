@@ -12,9 +12,9 @@ use crate::{
     },
     storage::{Error as StorageError, StorageLevel},
 };
-use sn_comms::Comm;
+use sn_comms::{Comm, SendStream};
 
-use qp2p::{SendStream, UsrMsgBytes};
+use qp2p::UsrMsgBytes;
 use sn_fault_detection::IssueType;
 use sn_interface::{
     messaging::{
@@ -23,7 +23,7 @@ use sn_interface::{
         Dst, MsgId, NetworkMsg, WireMsg,
     },
     network_knowledge::{MembershipState, NetworkKnowledge},
-    types::{log_markers::LogMarker, ClientId, Keypair, NodeId, PublicKey, ReplicatedData},
+    types::{log_markers::LogMarker, ClientId, NodeId, PublicKey, ReplicatedData},
     SectionAuthorityProvider,
 };
 use std::collections::BTreeSet;
@@ -79,19 +79,14 @@ impl MyNode {
         correlation_id: MsgId,
     ) -> Result<Vec<Cmd>> {
         let mut cmds = vec![];
-        let section_pk = PublicKey::Bls(context.network_knowledge.section_key());
-        let node_keypair = Keypair::Ed25519(context.keypair.clone());
         let data_addr = data.address();
 
         trace!("About to store data from {correlation_id:?}: {data_addr:?}");
 
         // This may return a DatabaseFull error... but we should have
         // reported StorageError::NotEnoughSpace well before this
-        let response = match context
-            .data_storage
-            .store(&data, section_pk, node_keypair.clone())
-            .await
-        {
+        let section_chain = context.network_knowledge.section_chain();
+        let response = match context.data_storage.store(&data, &section_chain).await {
             Ok(storage_level) => {
                 info!("{correlation_id:?} Data has been stored: {data_addr:?}");
                 if matches!(storage_level, StorageLevel::Updated(_level)) {
@@ -322,6 +317,32 @@ impl MyNode {
 
                 Ok(cmds)
             }
+            NodeMsg::NodeEvent(NodeEvent::Leaving {
+                node_id: leaving_node,
+            }) => {
+                info!(
+                    "{:?} {node_id} with {msg_id:?}",
+                    LogMarker::NodeLeavingVoluntarily
+                );
+
+                if !context.is_elder {
+                    error!("Received unexpected message while Adult");
+                    return Ok(vec![]);
+                }
+                if XorName::from(leaving_node) != node_id.name() {
+                    warn!("Ignoring the leave of {leaving_node:?} announced by another node, {node_id}");
+                    return Ok(vec![]);
+                }
+                if !context.network_knowledge.is_section_member(&node_id.name()) {
+                    warn!("Ignoring the leave of {node_id}, which is not a member of our section");
+                    return Ok(vec![]);
+                }
+
+                // the node going offline is not to be deemed faulty, as it's leaving on purpose
+                node.stop_tracking_node(node_id.name());
+                let nodes = BTreeSet::from([node_id.name()]);
+                Ok(vec![Cmd::ProposeVoteNodesOffline(nodes)])
+            }
             NodeMsg::RequestHandover { sap, sig_share } => {
                 info!("RequestHandover with msg_id {msg_id:?}");
                 node.handle_handover_request(msg_id, sap, sig_share, node_id)
@@ -341,6 +362,34 @@ impl MyNode {
                     msg_id, sap0, sig_share0, sap1, sig_share1, node_id,
                 )
             }
+            NodeMsg::ProposeSectionMerge {
+                section_tree_update,
+                members,
+                sig_share,
+            } => {
+                info!("ProposeSectionMerge with msg_id {msg_id:?}");
+                node.handle_section_merge_proposal(
+                    msg_id,
+                    section_tree_update,
+                    members,
+                    sig_share,
+                    node_id,
+                )
+            }
+            NodeMsg::SectionMergePromotion {
+                sap,
+                sig_share,
+                merge_agreement,
+            } => {
+                info!("SectionMergePromotion with msg_id {msg_id:?}");
+                node.handle_section_merge_promotion(
+                    msg_id,
+                    sap,
+                    sig_share,
+                    merge_agreement,
+                    node_id,
+                )
+            }
             msg => {
                 error!("This node msg should have been handled in the non-blocking flow ctrl cmd thread(s): {msg:?}");
 
@@ -368,6 +417,7 @@ pub(crate) fn into_msg_bytes(
     };
 
     let mut initial_wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+    initial_wire_msg.set_compressible(!msg.carries_chunks());
     let _bytes = initial_wire_msg.serialize_and_cache_bytes()?;
 
     let mut msgs = vec![];
@@ -385,5 +435,8 @@ pub(crate) fn into_msg_bytes(
         }
     }
 
+    #[cfg(feature = "metrics")]
+    crate::node::metrics::record_msgs_sent(initial_wire_msg.kind(), msgs.len());
+
     Ok(msgs)
 }
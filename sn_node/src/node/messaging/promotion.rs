@@ -7,14 +7,14 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::flow_ctrl::cmds::Cmd;
-use crate::node::{MyNode, Result};
+use crate::node::{MyNode, NodeContext, Result};
 
 use sn_interface::{
     messaging::{
         system::{SectionSig, SectionSigShare, SectionSigned},
         MsgId,
     },
-    network_knowledge::{SectionAuthorityProvider, SectionTreeUpdate},
+    network_knowledge::{SectionAuthorityProvider, SectionMergeAgreement, SectionTreeUpdate},
     types::{log_markers::LogMarker, NodeId},
 };
 
@@ -139,6 +139,72 @@ impl MyNode {
         }
     }
 
+    pub(crate) fn handle_section_merge_promotion(
+        &mut self,
+        msg_id: MsgId,
+        sap: SectionSigned<SectionAuthorityProvider>,
+        sig_share: SectionSigShare,
+        merge_agreement: SectionSigned<SectionMergeAgreement>,
+        sender: NodeId,
+    ) -> Result<Vec<Cmd>> {
+        trace!(
+            "Handling section merge promotion message {msg_id:?} by {sender:?} with sap: {sap:?}"
+        );
+        let our_prefix = self.network_knowledge.prefix();
+        let sig_share_pk = sig_share.public_key_set.public_key();
+
+        // Proposal from sections other than ours or our sibling shall be ignored.
+        if our_prefix.is_empty()
+            || sap.prefix() != our_prefix.popped()
+            || !sap.prefix().matches(&sender.name())
+        {
+            trace!("Ignore merge promotion message {msg_id:?} from other section sent by {sender:?} when our prefix is {our_prefix:?}");
+            return Ok(vec![]);
+        }
+        // The merge is carried out by either our section or our sibling, so the sig share
+        // shall be from the current key of one of them.
+        let sibling_key = self
+            .network_knowledge
+            .section_tree()
+            .get(&our_prefix.sibling())
+            .map(|sibling_sap| sibling_sap.section_key());
+        if sig_share_pk != self.network_knowledge.section_key() && Some(sig_share_pk) != sibling_key
+        {
+            warn!("Ignore merge promotion message {msg_id:?} with untrusted sig share");
+            return Ok(vec![]);
+        }
+
+        // try aggregate
+        let serialize_err = |e| {
+            error!("Failed to serialize pubkey while handling merge promotion message {msg_id:?}");
+            e
+        };
+        let serialised_pk = bincode::serialize(&sap.sig.public_key).map_err(serialize_err)?;
+        match self
+            .elder_promotion_aggregator
+            .try_aggregate(&serialised_pk, sig_share)
+        {
+            Ok(Some(sig)) => {
+                trace!("Merge promotion message {msg_id:?} successfully aggregated");
+                Ok(vec![Cmd::HandleSectionMergeAgreement {
+                    sap,
+                    sig,
+                    merge_agreement,
+                }])
+            }
+            Ok(None) => {
+                trace!("Merge promotion message {msg_id:?} acknowledged, waiting for more...");
+                Ok(vec![])
+            }
+            Err(err) => {
+                error!(
+                    "Failed to aggregate merge promotion message {msg_id:?} from {sender}: {err:?}"
+                );
+                Ok(vec![])
+            }
+        }
+    }
+
     #[instrument(skip(self), level = "trace")]
     pub(crate) async fn handle_new_sections_agreement(
         &mut self,
@@ -233,13 +299,53 @@ impl MyNode {
             section_sig.signature,
         )?;
         let update = SectionTreeUpdate::new(signed_sap, section_chain);
+        self.update_our_sap(update, &old_context).await
+    }
+
+    /// Switches to the SAP of the section our section merged into.
+    /// The merged section key is signed by the key of the section which carried the merge out,
+    /// which is either ours or our sibling's, so it's chained from that key.
+    #[instrument(skip(self), level = "trace")]
+    pub(crate) async fn handle_section_merge_agreement(
+        &mut self,
+        signed_sap: SectionSigned<SectionAuthorityProvider>,
+        section_sig: SectionSig,
+        merge_agreement: SectionSigned<SectionMergeAgreement>,
+    ) -> Result<Vec<Cmd>> {
+        trace!("{}", LogMarker::HandlingNewEldersAgreement);
+        let old_context = self.context();
+        let prefix = signed_sap.prefix();
+        trace!("{}: for {:?}", LogMarker::NewSignedSap, prefix);
+
+        info!("Merged SAP agreed for:{}", *signed_sap);
+
+        let sections_dag = self.network_knowledge.section_tree().get_sections_dag();
+        let mut proof_chain =
+            sections_dag.partial_dag(sections_dag.genesis_key(), &section_sig.public_key)?;
+        proof_chain.verify_and_insert(
+            &section_sig.public_key,
+            signed_sap.section_key(),
+            section_sig.signature,
+        )?;
+        // the agreement of the section which didn't sign the merged key goes along with it
+        let update = SectionTreeUpdate::new_merge(signed_sap, proof_chain, merge_agreement);
+        self.update_our_sap(update, &old_context).await
+    }
+
+    // Updates our network knowledge with a new SAP of our section, if valid
+    async fn update_our_sap(
+        &mut self,
+        update: SectionTreeUpdate,
+        old_context: &NodeContext,
+    ) -> Result<Vec<Cmd>> {
+        let prefix = update.signed_sap.prefix();
         let name = old_context.name;
         let updated = self
             .network_knowledge
             .update_sap_knowledge_if_valid(update, &name)?;
 
         if updated {
-            let cmds = self.update_on_sap_change(&old_context).await;
+            let cmds = self.update_on_sap_change(old_context).await;
             info!("Updated our network knowledge for {:?}", prefix);
             info!("Writing updated knowledge to disk");
             MyNode::write_section_tree(
@@ -243,7 +243,7 @@ mod tests {
             },
         },
     };
-    use sn_comms::{Comm, CommEvent};
+    use sn_comms::{Comm, CommEvent, MemoryNetwork};
     use sn_consensus::Decision;
     use sn_interface::{
         elder_count, init_logger,
@@ -273,13 +273,14 @@ mod tests {
     /// to complete because it needs to generate a signature with the number of trailing zeroes equal
     /// to (or greater that) `age`.
     fn create_relocation_trigger(
+        network: &MemoryNetwork,
         sk_set: &bls::SecretKeySet,
         gen: u64,
         age: u8,
         prefix: Prefix,
     ) -> Result<(Decision<NodeState>, MyNodeInfo, Comm, Receiver<CommEvent>)> {
         loop {
-            let (info, comm, comm_rx) = gen_info_with_comm(MIN_ADULT_AGE, Some(prefix));
+            let (info, comm, comm_rx) = gen_info_with_comm(network, MIN_ADULT_AGE, Some(prefix));
             if let Some((_trigger, decision)) =
                 try_create_relocation_trigger(info.id(), sk_set, gen, age)?
             {
@@ -308,8 +309,13 @@ mod tests {
         // update our node with the new network_knowledge
         node.network_knowledge = section.clone();
 
-        let (membership_decision, ..) =
-            create_relocation_trigger(&sk_set, 2, relocated_node.age(), prefix)?;
+        let (membership_decision, ..) = create_relocation_trigger(
+            env.memory_network(),
+            &sk_set,
+            2,
+            relocated_node.age(),
+            prefix,
+        )?;
 
         let mut cmds =
             ProcessAndInspectCmds::new(Cmd::HandleMembershipDecision(membership_decision));
@@ -383,8 +389,13 @@ mod tests {
         let sk_set = env.get_secret_key_set(Prefix::default(), None)?;
 
         // Find a node, that when joined will trigger the relocation of our relocation_node
-        let (_, trig_info, trig_comm, trig_comm_rx) =
-            create_relocation_trigger(&sk_set, 1, relocation_node_age, Prefix::default())?;
+        let (_, trig_info, trig_comm, trig_comm_rx) = create_relocation_trigger(
+            env.memory_network(),
+            &sk_set,
+            1,
+            relocation_node_age,
+            Prefix::default(),
+        )?;
         let trig_node = build_a_node_instance(&trig_info, &trig_comm, &network_knowledge)?;
         let trig_node = Arc::new(RwLock::new(TestNode::new(trig_node, msg_tracker.clone())));
         let _ = node_instances.insert((Prefix::default(), trig_info.name()), trig_node);
@@ -515,8 +526,13 @@ mod tests {
         let sk_set = env.get_secret_key_set(prefix0, None)?;
 
         // Find a node, that when joined will trigger the relocation of our relocation_node
-        let (_, trig_info, trig_comm, trig_comm_rx) =
-            create_relocation_trigger(&sk_set, 1, relocation_node_age, prefix0)?;
+        let (_, trig_info, trig_comm, trig_comm_rx) = create_relocation_trigger(
+            env.memory_network(),
+            &sk_set,
+            1,
+            relocation_node_age,
+            prefix0,
+        )?;
         let trig_node = build_a_node_instance(&trig_info, &trig_comm, &network_knowledge_0)?;
         let trig_node = Arc::new(RwLock::new(TestNode::new(trig_node, msg_tracker.clone())));
         let _ = node_instances.insert((prefix0, trig_info.name()), trig_node);
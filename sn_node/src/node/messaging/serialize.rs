@@ -20,6 +20,7 @@ impl MyNode {
         let kind = MsgKind::Node {
             name: our_name,
             is_join: msg.is_join(),
+            is_data: msg.is_data(),
         };
         Ok((kind, payload))
     }
@@ -36,6 +37,7 @@ impl MyNode {
                 MsgKind::Node {
                     name: our_name,
                     is_join: msg.is_join(),
+                    is_data: msg.is_data(),
                 },
             ),
             NetworkMsg::DataResponse(msg) => (
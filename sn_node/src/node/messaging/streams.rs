@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{Cmd, Error, MyNode, NodeContext, Result};
-use sn_comms::Comm;
+use sn_comms::{Comm, SendStream};
 
 use bls::PublicKey;
 use sn_interface::{
@@ -18,9 +18,10 @@ use sn_interface::{
 };
 
 use bytes::Bytes;
-use qp2p::SendStream;
+use qp2p::UsrMsgBytes;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::collections::{BTreeMap, BTreeSet};
+use tokio::sync::oneshot;
 use xor_name::XorName;
 
 // Message handling over streams
@@ -40,6 +41,7 @@ impl MyNode {
             msg_id,
             kind,
             payload,
+            !msg.carries_chunks(),
             Participant::from_node(node_id),
             correlation_id,
             context.network_knowledge.section_key(),
@@ -61,6 +63,7 @@ impl MyNode {
             msg_id,
             MsgKind::AntiEntropy(context.name),
             WireMsg::serialize_msg_payload(&msg)?,
+            true,
             participant,
             correlation_id,
             context.network_knowledge.section_key(),
@@ -83,6 +86,7 @@ impl MyNode {
             msg_id,
             MsgKind::DataResponse(our_name),
             WireMsg::serialize_msg_payload(&msg)?,
+            !msg.carries_chunks(),
             Participant::from_client(client_id),
             correlation_id,
             our_section_key,
@@ -110,8 +114,12 @@ impl MyNode {
             section_key: context.network_knowledge.section_key(),
         };
         let mut wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+        wire_msg.set_compressible(!msg.carries_chunks());
         let _bytes = wire_msg.serialize_and_cache_bytes()?;
 
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_msgs_sent(wire_msg.kind(), recipients.len());
+
         for target in recipients {
             dst.name = target.name();
             let bytes_to_node = wire_msg.serialize_with_new_dst(&dst)?;
@@ -131,7 +139,7 @@ impl MyNode {
         targets: BTreeSet<NodeId>,
         client_stream: SendStream,
         client_id: ClientId,
-    ) -> Result<()> {
+    ) -> Result<oneshot::Receiver<UsrMsgBytes>> {
         let msg_id = wire_msg.msg_id();
         let targets_len = targets.len();
 
@@ -139,22 +147,10 @@ impl MyNode {
             "Sending out {msg_id:?}, coming from {client_id}, to {targets_len} holder node/s {targets:?}",
         );
 
-        let node_bytes: BTreeMap<_, _> = targets
-            .into_par_iter()
-            .filter_map(|target| {
-                let dst = Dst {
-                    name: target.name(),
-                    section_key: our_section_key,
-                };
-                match wire_msg.serialize_with_new_dst(&dst) {
-                    Ok(bytes_to_node) => Some((target, bytes_to_node)),
-                    Err(error) => {
-                        error!("Sending out {msg_id:?} to {target} failed due to {error}.");
-                        None
-                    }
-                }
-            })
-            .collect();
+        let node_bytes = serialize_to_targets(&wire_msg, our_section_key, targets);
+
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_msgs_sent(wire_msg.kind(), node_bytes.len());
 
         let dst_stream = (
             Dst {
@@ -164,17 +160,61 @@ impl MyNode {
             client_stream,
         );
 
-        comm.send_and_respond_on_stream(msg_id, node_bytes, targets_len, dst_stream);
+        Ok(comm.send_and_respond_on_stream(msg_id, node_bytes, targets_len, dst_stream))
+    }
 
-        Ok(())
+    /// Send out msg and await the response the nodes agreed on, without forwarding it
+    pub(crate) fn send_and_await_response(
+        wire_msg: WireMsg,
+        comm: Comm,
+        our_section_key: PublicKey,
+        targets: BTreeSet<NodeId>,
+    ) -> oneshot::Receiver<UsrMsgBytes> {
+        let msg_id = wire_msg.msg_id();
+        let targets_len = targets.len();
+        debug!("Sending out {msg_id:?} to {targets_len} holder node/s {targets:?}");
+
+        let node_bytes = serialize_to_targets(&wire_msg, our_section_key, targets);
+
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_msgs_sent(wire_msg.kind(), node_bytes.len());
+
+        comm.send_and_await_response(msg_id, node_bytes, targets_len)
     }
 }
 
+// Serializes the msg for each of the targets, as its dst
+fn serialize_to_targets(
+    wire_msg: &WireMsg,
+    our_section_key: PublicKey,
+    targets: BTreeSet<NodeId>,
+) -> BTreeMap<NodeId, UsrMsgBytes> {
+    let msg_id = wire_msg.msg_id();
+    targets
+        .into_par_iter()
+        .filter_map(|target| {
+            let dst = Dst {
+                name: target.name(),
+                section_key: our_section_key,
+            };
+            match wire_msg.serialize_with_new_dst(&dst) {
+                Ok(bytes_to_node) => Some((target, bytes_to_node)),
+                Err(error) => {
+                    error!("Sending out {msg_id:?} to {target} failed due to {error}.");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
 // Send a msg on a given stream
+#[allow(clippy::too_many_arguments)]
 async fn send_msg_on_stream(
     msg_id: MsgId,
     kind: MsgKind,
     payload: Bytes,
+    compressible: bool,
     recipient: Participant,
     correlation_id: MsgId,
     section_key: PublicKey,
@@ -184,9 +224,13 @@ async fn send_msg_on_stream(
         name: recipient.name(),
         section_key,
     };
-    let wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+    let mut wire_msg = WireMsg::new_msg(msg_id, payload, kind, dst);
+    wire_msg.set_compressible(compressible);
     let bytes = wire_msg.serialize().map_err(|_| Error::InvalidMessage)?;
 
+    #[cfg(feature = "metrics")]
+    crate::node::metrics::record_msgs_sent(wire_msg.kind(), 1);
+
     let stream_id = send_stream.id();
     info!(
         "Sending response {msg_id:?} of msg {correlation_id:?}, to {recipient:?} over {stream_id}"
@@ -0,0 +1,217 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    register_subscriptions::{PollOutcome, RegisterPoll},
+    Cmd, MyNode, NodeContext,
+};
+
+use sn_comms::SendStream;
+use sn_interface::{
+    data_copy_count,
+    messaging::{
+        data::{
+            CmdResponse, DataResponse, Error as DataError, QueryResponse, Result as DataResult,
+            SignedRegisterEdit,
+        },
+        MsgId, NetworkMsg, WireMsg,
+    },
+    types::{ClientId, RegisterAddress},
+};
+
+use qp2p::UsrMsgBytes;
+use tokio::sync::oneshot;
+
+impl MyNode {
+    /// Forwards a client subscribing to a register it isn't subscribed to yet, to the holders
+    /// of the register, for them to check the client may read it before we subscribe it.
+    pub(crate) fn forward_register_subscription_to_holders(
+        context: &NodeContext,
+        wire_msg: WireMsg,
+        address: RegisterAddress,
+        client_id: ClientId,
+        send_stream: SendStream,
+    ) -> Cmd {
+        let msg_id = wire_msg.msg_id();
+        let targets = MyNode::target_data_holders(context, *address.name(), None);
+        if data_copy_count() > targets.len() {
+            error!(
+                "InsufficientNodeCount for checking subscription {msg_id:?} to register {address:?}, {:?}",
+                targets.len()
+            );
+            let poll = RegisterPoll {
+                client_id,
+                correlation_id: msg_id,
+                stream: send_stream,
+            };
+            let error = DataError::InsufficientNodeCount {
+                prefix: context.network_knowledge.prefix(),
+                expected: data_copy_count() as u8,
+                found: targets.len() as u8,
+            };
+            return respond_to_poll(poll, Err(error));
+        }
+
+        Cmd::SendRegisterSubscriptionToHolders {
+            wire_msg,
+            targets,
+            client_stream: send_stream,
+            client_id,
+            address,
+        }
+    }
+
+    /// Handles a client subscribing to a register, once its holders all responded
+    /// that the client may read it, i.e. they ran the same checks as for reading it.
+    pub(crate) async fn handle_register_subscription_once_readable(
+        context: &NodeContext,
+        msg_id: MsgId,
+        address: RegisterAddress,
+        client_id: ClientId,
+        send_stream: SendStream,
+        holders_response: oneshot::Receiver<UsrMsgBytes>,
+    ) -> Vec<Cmd> {
+        let result = match holders_response.await {
+            Ok(response_bytes) => {
+                match WireMsg::from(response_bytes).and_then(|wire_msg| wire_msg.into_msg()) {
+                    Ok(NetworkMsg::DataResponse(DataResponse::QueryResponse {
+                        response: QueryResponse::SubscribeToRegister(result),
+                        ..
+                    })) => result,
+                    other => {
+                        debug!("Holders of register {address:?} responded to {msg_id:?} with {other:?}");
+                        Err(DataError::InconsistentStorageNodeResponses)
+                    }
+                }
+            }
+            Err(_) => Err(DataError::InconsistentStorageNodeResponses),
+        };
+
+        if let Err(error) = result {
+            debug!("Rejecting subscription {msg_id:?} of {client_id} to register {address:?}: {error:?}");
+            let poll = RegisterPoll {
+                client_id,
+                correlation_id: msg_id,
+                stream: send_stream,
+            };
+            return vec![respond_to_poll(poll, Err(error))];
+        }
+
+        MyNode::handle_register_subscription(context, msg_id, address, client_id, send_stream)
+    }
+
+    /// Handles a client subscribing to the edits of a register, which polls for the edits
+    /// it hasn't been notified of yet. If there are none, the poll awaits them.
+    pub(crate) fn handle_register_subscription(
+        context: &NodeContext,
+        msg_id: MsgId,
+        address: RegisterAddress,
+        client_id: ClientId,
+        send_stream: SendStream,
+    ) -> Vec<Cmd> {
+        let subscriptions = &context.register_subscriptions;
+        let mut cmds = MyNode::end_register_polls(subscriptions.remove_expired());
+
+        let poll = RegisterPoll {
+            client_id,
+            correlation_id: msg_id,
+            stream: send_stream,
+        };
+        match subscriptions.poll(address, poll) {
+            Ok(PollOutcome::Notify { poll, edits }) => {
+                debug!("Notifying {client_id} of the edits of register {address:?} on {msg_id:?}");
+                cmds.push(notify_edits(poll, edits));
+            }
+            Ok(PollOutcome::Wait { replaced }) => {
+                debug!("{client_id} awaits the edits of register {address:?} on {msg_id:?}");
+                cmds.extend(MyNode::end_register_polls(replaced));
+            }
+            Err(poll) => {
+                warn!(
+                    "Rejecting subscription {msg_id:?} of {client_id} to register {address:?}, \
+                    too many subscriptions are kept already"
+                );
+                let error = DataError::InvalidOperation("Too many register subscriptions".into());
+                cmds.push(respond_to_poll(poll, Err(error)));
+            }
+        }
+
+        cmds
+    }
+
+    /// Notifies the clients subscribed to the register of an edit we forward to its holders.
+    pub(crate) fn notify_register_subscribers(
+        context: &NodeContext,
+        edit: &SignedRegisterEdit,
+    ) -> Vec<Cmd> {
+        let subscriptions = &context.register_subscriptions;
+        let mut cmds = MyNode::end_register_polls(subscriptions.remove_expired());
+
+        cmds.extend(subscriptions.notify(edit).into_iter().map(|poll| {
+            trace!(
+                "Notifying {} of an edit of register {:?}",
+                poll.client_id,
+                edit.dst_address()
+            );
+            notify_edits(poll, vec![edit.clone()])
+        }));
+
+        cmds
+    }
+
+    /// Notifies the clients subscribed to the register of an edit we forwarded, once its
+    /// holders all responded that they applied it, i.e. its signature and policy were checked.
+    pub(crate) async fn notify_register_subscribers_once_applied(
+        context: &NodeContext,
+        edit: &SignedRegisterEdit,
+        holders_response: oneshot::Receiver<UsrMsgBytes>,
+    ) -> Vec<Cmd> {
+        let address = edit.dst_address();
+        let Ok(response_bytes) = holders_response.await else {
+            debug!(
+                "Not notifying the edit of register {address:?}, its holders didn't all apply it"
+            );
+            return vec![];
+        };
+
+        match WireMsg::from(response_bytes).and_then(|wire_msg| wire_msg.into_msg()) {
+            Ok(NetworkMsg::DataResponse(DataResponse::CmdResponse {
+                response: CmdResponse::EditRegister(Ok(())),
+                ..
+            })) => MyNode::notify_register_subscribers(context, edit),
+            other => {
+                debug!("Not notifying the edit of register {address:?}, its holders responded with {other:?}");
+                vec![]
+            }
+        }
+    }
+
+    /// Ends the polls of the subscriptions we no longer keep, for the clients to subscribe again.
+    pub(crate) fn end_register_polls(polls: impl IntoIterator<Item = RegisterPoll>) -> Vec<Cmd> {
+        polls
+            .into_iter()
+            .map(|poll| respond_to_poll(poll, Ok(())))
+            .collect()
+    }
+}
+
+fn notify_edits(poll: RegisterPoll, edits: Vec<SignedRegisterEdit>) -> Cmd {
+    let msg = DataResponse::RegisterEdited {
+        edits,
+        correlation_id: poll.correlation_id,
+    };
+    Cmd::send_data_response(msg, poll.correlation_id, poll.client_id, poll.stream)
+}
+
+fn respond_to_poll(poll: RegisterPoll, result: DataResult<()>) -> Cmd {
+    let msg = DataResponse::QueryResponse {
+        response: QueryResponse::SubscribeToRegister(result),
+        correlation_id: poll.correlation_id,
+    };
+    Cmd::send_data_response(msg, poll.correlation_id, poll.client_id, poll.stream)
+}
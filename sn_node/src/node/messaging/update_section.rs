@@ -114,4 +114,35 @@ impl MyNode {
         let msg = NodeMsg::NodeDataCmd(NodeDataCmd::SendAnyMissingRelevantData(data_i_have));
         Cmd::send_msg(msg, Recipients::Multiple(members))
     }
+
+    /// Sends the list of data we hold to the other holders of the data we quarantined,
+    /// so that they send us back fresh copies of it, since it's now missing from our list.
+    #[instrument(skip(context))]
+    pub(crate) async fn ask_holders_for_quarantined_data(
+        context: &NodeContext,
+        quarantined: &[DataAddress],
+    ) -> Option<Cmd> {
+        let members = context.network_knowledge.members();
+        let holders: BTreeSet<_> = quarantined
+            .iter()
+            .flat_map(|data| {
+                members
+                    .iter()
+                    .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                    .take(data_copy_count())
+            })
+            .filter(|node_id| node_id.name() != context.name)
+            .cloned()
+            .collect();
+
+        if holders.is_empty() {
+            warn!("We have no nodes to ask for fresh copies of the data quarantined!");
+            return None;
+        }
+        debug!("Asking {holders:?} for fresh copies of the data quarantined");
+
+        let data_i_have = context.data_storage.data_addrs().await;
+        let msg = NodeMsg::NodeDataCmd(NodeDataCmd::SendAnyMissingRelevantData(data_i_have));
+        Some(Cmd::send_msg(msg, Recipients::Multiple(holders)))
+    }
 }
@@ -0,0 +1,192 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Metrics of the node, to be scraped in the Prometheus text format.
+//!
+//! Events are recorded as they happen, while the state of the node, e.g. the data stored,
+//! is only sampled when the metrics are gathered.
+
+use super::{flow_ctrl::cmd_ctrl::CmdPriority, NodeRef};
+
+use sn_fault_detection::ScoreResults;
+use sn_interface::messaging::MsgKind;
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_gauge, register_gauge_vec, register_histogram, register_histogram_vec,
+    register_int_counter, register_int_counter_vec, register_int_gauge_vec, Encoder, Gauge,
+    GaugeVec, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+use std::{collections::BTreeMap, time::Duration};
+use xor_name::XorName;
+
+lazy_static! {
+    static ref CMD_QUEUE_DEPTH: IntGaugeVec = register_int_gauge_vec!(
+        "safenode_cmd_queue_depth",
+        "Number of cmds waiting to be processed by the node, by priority class",
+        &["priority"]
+    )
+    .expect("metric can be registered");
+    static ref CMD_QUEUEING_DELAY: HistogramVec = register_histogram_vec!(
+        "safenode_cmd_queueing_delay_seconds",
+        "Time cmds waited on their queue before being processed, by priority class",
+        &["priority"]
+    )
+    .expect("metric can be registered");
+    static ref MSGS_RECEIVED: IntCounterVec = register_int_counter_vec!(
+        "safenode_msgs_received_total",
+        "Number of msgs received, by kind",
+        &["kind"]
+    )
+    .expect("metric can be registered");
+    static ref MSGS_SENT: IntCounterVec = register_int_counter_vec!(
+        "safenode_msgs_sent_total",
+        "Number of msgs sent, by kind",
+        &["kind"]
+    )
+    .expect("metric can be registered");
+    static ref AE_ROUNDS: IntCounter = register_int_counter!(
+        "safenode_anti_entropy_rounds_total",
+        "Number of anti-entropy msgs handled"
+    )
+    .expect("metric can be registered");
+    static ref DKG_SESSIONS: IntCounter = register_int_counter!(
+        "safenode_dkg_sessions_total",
+        "Number of DKG sessions our node took part in"
+    )
+    .expect("metric can be registered");
+    static ref FAULT_SCORES: GaugeVec = register_gauge_vec!(
+        "safenode_fault_detection_score",
+        "Latest fault detection score of each node tracked, by issue type",
+        &["node", "issue"]
+    )
+    .expect("metric can be registered");
+    static ref USED_SPACE_RATIO: Gauge = register_gauge!(
+        "safenode_used_space_ratio",
+        "Ratio of the storage space used to the max capacity"
+    )
+    .expect("metric can be registered");
+    static ref DATA_STORED: IntGaugeVec = register_int_gauge_vec!(
+        "safenode_data_stored",
+        "Number of data items stored, by type",
+        &["type"]
+    )
+    .expect("metric can be registered");
+    static ref CLIENT_QUERY_DURATION: Histogram = register_histogram!(
+        "safenode_client_query_duration_seconds",
+        "Time taken to respond to client queries for the data we hold"
+    )
+    .expect("metric can be registered");
+}
+
+pub(crate) fn record_cmd_queueing_delay(priority: CmdPriority, delay: Duration) {
+    CMD_QUEUEING_DELAY
+        .with_label_values(&[priority.label()])
+        .observe(delay.as_secs_f64());
+}
+
+pub(crate) fn record_msg_received(kind: &MsgKind) {
+    MSGS_RECEIVED.with_label_values(&[kind_label(kind)]).inc();
+}
+
+pub(crate) fn record_msgs_sent(kind: &MsgKind, count: usize) {
+    MSGS_SENT
+        .with_label_values(&[kind_label(kind)])
+        .inc_by(count as u64);
+}
+
+pub(crate) fn record_ae_round() {
+    AE_ROUNDS.inc();
+}
+
+pub(crate) fn record_dkg_session() {
+    DKG_SESSIONS.inc();
+}
+
+pub(crate) fn record_client_query(duration: Duration) {
+    CLIENT_QUERY_DURATION.observe(duration.as_secs_f64());
+}
+
+/// Replaces the fault detection scores, so that nodes no longer tracked are dropped.
+pub(crate) fn record_fault_scores(scores: &ScoreResults) {
+    FAULT_SCORES.reset();
+    let scores_by_issue: [(&str, &BTreeMap<XorName, f32>); 6] = [
+        ("communication", &scores.communication_scores),
+        ("dkg", &scores.dkg_scores),
+        ("elder_voting", &scores.elder_voting_scores),
+        ("knowledge", &scores.knowledge_scores),
+        ("op", &scores.op_scores),
+        ("probe", &scores.probe_scores),
+    ];
+    for (issue, node_scores) in scores_by_issue {
+        for (node, score) in node_scores {
+            FAULT_SCORES
+                .with_label_values(&[&hex::encode(node), issue])
+                .set(f64::from(*score));
+        }
+    }
+}
+
+fn kind_label(kind: &MsgKind) -> &'static str {
+    match kind {
+        MsgKind::AntiEntropy(_) => "anti_entropy",
+        MsgKind::Client { .. } => "client",
+        MsgKind::Node { .. } => "node",
+        MsgKind::DataResponse(_) => "data_response",
+    }
+}
+
+impl NodeRef {
+    /// Gathers the metrics of our node, encoded in the Prometheus text format.
+    pub fn metrics(&self) -> String {
+        for (priority, depth) in self.cmd_channel.queue_depths() {
+            CMD_QUEUE_DEPTH
+                .with_label_values(&[priority.label()])
+                .set(depth as i64);
+        }
+
+        let data_storage = &self.context.data_storage;
+        USED_SPACE_RATIO.set(data_storage.used_space_ratio());
+        let (chunks, registers, spentbooks) = data_storage.data_counts();
+        DATA_STORED.with_label_values(&["chunk"]).set(chunks as i64);
+        DATA_STORED
+            .with_label_values(&["register"])
+            .set(registers as i64);
+        DATA_STORED
+            .with_label_values(&["spentbook"])
+            .set(spentbooks as i64);
+
+        let mut buffer = vec![];
+        if let Err(error) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+            error!("Failed to encode the node metrics: {error}");
+        }
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_events_are_gathered() {
+        let kind = MsgKind::AntiEntropy(xor_name::rand::random());
+        let sent_before = MSGS_SENT.with_label_values(&["anti_entropy"]).get();
+        record_msgs_sent(&kind, 3);
+        record_msg_received(&kind);
+
+        assert_eq!(
+            MSGS_SENT.with_label_values(&["anti_entropy"]).get(),
+            sent_before + 3
+        );
+        let families = prometheus::gather();
+        assert!(families
+            .iter()
+            .any(|family| family.get_name() == "safenode_msgs_received_total"));
+    }
+}
@@ -15,12 +15,17 @@ mod api;
 mod connectivity;
 mod context;
 mod dkg;
+mod drain;
 mod error;
 mod flow_ctrl;
 mod handover;
 mod logging;
 mod membership;
 mod messaging;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod rate_limiter;
+mod register_subscriptions;
 mod relocation;
 
 pub use self::{
@@ -30,18 +35,20 @@ pub use self::{
     error::{Error, Result},
     flow_ctrl::RejoinReason,
 };
-pub use crate::storage::DataStorage;
+pub use crate::storage::{DataStorage, ScrubStats, StorageBackendKind};
 pub use sn_interface::network_knowledge::MIN_ADULT_AGE;
 
 use self::{
     api::NodeEventsChannel,
     dkg::DkgVoter,
-    flow_ctrl::{cmds::Cmd, fault_detection::FaultsCmd},
+    drain::DrainTracker,
+    flow_ctrl::{cmds::Cmd, fault_detection::FaultsCmd, CmdChannel},
     handover::Handover,
     membership::{elder_candidates, try_split_dkg, Membership},
     messaging::Recipients,
+    rate_limiter::ClientRateLimiter,
+    register_subscriptions::RegisterSubscriptions,
 };
-use crate::storage::UsedSpace;
 
 use sn_comms::Comm;
 use sn_consensus::Generation;
@@ -57,8 +64,8 @@ use sn_interface::{
     },
     network_knowledge::{
         supermajority, MyNodeInfo, NetworkKnowledge, NodeState, RelocationState,
-        SectionAuthorityProvider, SectionKeyShare, SectionKeysProvider, SectionTree, SectionsDAG,
-        GENESIS_DBC_SK,
+        SectionAuthorityProvider, SectionKeyShare, SectionKeysProvider, SectionMergeAgreement,
+        SectionTree, SectionsDAG, GENESIS_DBC_SK,
     },
     types::{keys::ed25519::Digest256, log_markers::LogMarker, DataAddress, NodeId},
 };
@@ -77,8 +84,6 @@ use tokio::sync::mpsc::Sender;
 /// Standard channel size, to allow for large swings in throughput
 pub static STANDARD_CHANNEL_SIZE: usize = 100_000;
 
-pub(crate) type CmdChannel = Sender<(Cmd, Vec<usize>)>;
-
 // File name where to cache this node's section tree (stored at this node's set root storage dir)
 const SECTION_TREE_FILE_NAME: &str = "section_tree";
 const GOSSIP_SECTION_COUNT: usize = 3;
@@ -106,6 +111,9 @@ pub(crate) struct MyNode {
     pub(crate) elder_promotion_aggregator: SignatureAggregator,
     pub(crate) pending_split_sections:
         BTreeMap<Generation, BTreeSet<SectionSigned<SectionAuthorityProvider>>>,
+    pub(crate) section_merge_aggregator: SignatureAggregator,
+    // The agreement of our sibling section to merge with us, along with its members
+    pub(crate) pending_section_merge: Option<SectionSigned<SectionMergeAgreement>>,
     pub(crate) relocation_state: RelocationState,
     // ======================== Elder only ========================
     pub(crate) membership: Option<Membership>,
@@ -121,6 +129,11 @@ pub(crate) struct MyNode {
     pub(crate) data_replication_sender: Option<Sender<(Vec<DataAddress>, NodeId)>>,
     /// Node events channel
     pub(crate) node_events_sender: NodeEventsChannel,
+    /// Data handed off while draining our node
+    pub(crate) drain_tracker: DrainTracker,
+    pub(crate) client_rate_limiter: ClientRateLimiter,
+    /// Subscriptions of clients to the edits of the registers held by our section
+    pub(crate) register_subscriptions: RegisterSubscriptions,
 }
 
 impl MyNode {
@@ -145,6 +158,9 @@ impl MyNode {
             data_storage: self.data_storage.clone(),
             fault_cmds_sender: self.fault_cmds_sender.clone(),
             relocation_state: self.relocation_state.clone(),
+            drain_tracker: self.drain_tracker.clone(),
+            client_rate_limiter: self.client_rate_limiter.clone(),
+            register_subscriptions: self.register_subscriptions.clone(),
         }
     }
 
@@ -155,7 +171,7 @@ impl MyNode {
         reward_secret_key: bls::SecretKey,
         network_knowledge: NetworkKnowledge,
         section_key_share: Option<SectionKeyShare>,
-        used_space: UsedSpace,
+        data_storage: DataStorage,
         root_storage_dir: PathBuf,
         fault_cmds_sender: Sender<FaultsCmd>,
         node_events_sender: NodeEventsChannel,
@@ -181,8 +197,6 @@ impl MyNode {
 
         let section_keys_provider = SectionKeysProvider::new(section_key_share.clone());
 
-        let data_storage = DataStorage::new(&root_storage_dir, used_space);
-
         // create handover
         let handover = if let Some(key) = section_key_share {
             let secret_key = (key.index as u8, key.secret_key_share);
@@ -208,6 +222,8 @@ impl MyNode {
             section_keys_provider,
             dkg_sessions_info: HashMap::default(),
             pending_split_sections: Default::default(),
+            section_merge_aggregator: SignatureAggregator::default(),
+            pending_section_merge: None,
             relocation_state: RelocationState::NoRelocation,
             dkg_start_aggregator: SignatureAggregator::default(),
             dkg_voter: DkgVoter::default(),
@@ -222,6 +238,9 @@ impl MyNode {
             section_proposal_aggregator: SignatureAggregator::default(),
             data_replication_sender: None,
             node_events_sender,
+            drain_tracker: DrainTracker::default(),
+            client_rate_limiter: ClientRateLimiter::default(),
+            register_subscriptions: RegisterSubscriptions::default(),
         };
 
         Ok(node)
@@ -232,7 +251,7 @@ impl MyNode {
         comm: Comm,
         keypair: Keypair,
         reward_secret_key: bls::SecretKey,
-        used_space: UsedSpace,
+        data_storage: DataStorage,
         root_storage_dir: PathBuf,
         genesis_sk_set: bls::SecretKeySet,
         fault_cmds_sender: Sender<FaultsCmd>,
@@ -251,7 +270,7 @@ impl MyNode {
             reward_secret_key,
             network_knowledge,
             Some(section_key_share),
-            used_space,
+            data_storage,
             root_storage_dir,
             fault_cmds_sender,
             node_events_sender,
@@ -607,8 +626,9 @@ impl MyNode {
         self.update_dkg_on_section_change(&new_section_key);
 
         if new_section_key != old_section_key {
-            // clean up pending split sections since they no longer apply to the new section
+            // clean up pending split and merge sections since they no longer apply to the new section
             self.pending_split_sections = Default::default();
+            self.pending_section_merge = None;
         }
 
         if new.is_elder {
@@ -640,6 +660,15 @@ impl MyNode {
             self.membership = None;
         }
 
+        // clients subscribed to registers we are no longer responsible for
+        // are to subscribe again to the Elders which are
+        let dropped_polls = if new.is_elder {
+            self.register_subscriptions.remove_outside(&new_prefix)
+        } else {
+            self.register_subscriptions.remove_all()
+        };
+        cmds.extend(MyNode::end_register_polls(dropped_polls));
+
         if new.is_elder || old.is_elder {
             cmds.extend(self.send_ae_update_to_our_section()?);
         }
@@ -657,7 +686,8 @@ impl MyNode {
             old_elders.intersection(&current_elders).copied().collect();
 
         let new_elders = !added_elders.is_empty();
-        let section_split = new_prefix != old_prefix;
+        let section_split = new_prefix.is_extension_of(&old_prefix);
+        let section_merged = old_prefix.is_extension_of(&new_prefix);
         let elders_changed = !added_elders.is_empty() || !removed_elders.is_empty();
 
         if !old.is_elder && new.is_elder {
@@ -702,7 +732,38 @@ impl MyNode {
             );
         };
 
-        if !section_split && elders_changed {
+        if section_merged {
+            info!("{}: {:?}", LogMarker::MergeSuccess, new_prefix);
+
+            self.fault_detection_retain_only(
+                self.network_knowledge
+                    .adults()
+                    .iter()
+                    .map(|node_id| node_id.name())
+                    .collect(),
+                self.network_knowledge
+                    .elders()
+                    .iter()
+                    .map(|node_id| node_id.name())
+                    .collect(),
+            )
+            .await;
+
+            // we're now responsible for the data of our former sibling as well
+            cmds.push(MyNode::ask_for_any_new_data_from_whole_section(&self.context()).await);
+
+            info!(
+                "Section has been merged, new_prefix: {:?}, section_key {:?}, remaining elders\
+                    in our section {:?}, new elders {:?} removed elders {:?}",
+                new_prefix,
+                new.network_knowledge.section_key(),
+                remaining_elders,
+                new_elders,
+                removed_elders
+            );
+        }
+
+        if !section_split && !section_merged && elders_changed {
             info!(
                 "Elders has been changed. prefix: {:?}, section_key {:?},  remaining elders\
                     in our section {:?}, new elders {:?} removed elders {:?}",
@@ -762,6 +823,19 @@ impl MyNode {
         });
     }
 
+    /// Sends `FaultsCmd::RemoveNode` cmd
+    /// Spawns a process to send this incase the channel may be full, we don't hold up
+    /// processing around this
+    pub(crate) fn stop_tracking_node(&self, name: XorName) {
+        debug!("Removing {name} from fault detection");
+        let fault_sender = self.fault_cmds_sender.clone();
+        let _handle = tokio::spawn(async move {
+            if let Err(error) = fault_sender.send(FaultsCmd::RemoveNode(name)).await {
+                warn!("Could not send FaultsCmd through fault_cmds_tx: {error}");
+            }
+        });
+    }
+
     pub(crate) fn log_network_stats(&self) {
         info!(
             "{}",
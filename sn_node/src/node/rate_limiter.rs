@@ -0,0 +1,360 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Admission control of the msgs sent by clients to our node as an Elder.
+//!
+//! Each client is limited by a token bucket keyed by its source IP (its /64 prefix for IPv6),
+//! and by another one keyed by its public key, so that neither changing keys, ports nor
+//! addresses within its IPv6 prefix lets it exceed the limits.
+//! The bucket of the key is only charged once the msg signature is verified, so that a key
+//! can't be throttled by msgs its holder didn't send.
+
+use sn_interface::types::PublicKey;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+// Average number of msgs per second accepted from each client, unless configured otherwise
+pub(crate) const DEFAULT_CLIENT_MSGS_PER_SEC: u32 = 50;
+// Number of msgs accepted from each client in a burst, unless configured otherwise
+pub(crate) const DEFAULT_CLIENT_MSGS_BURST: u32 = 200;
+
+// Number of buckets kept per key type, beyond which the least recently used ones are dropped
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Limits on the msgs accepted from each client.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ClientRateLimits {
+    /// Average number of msgs accepted per second.
+    pub(crate) msgs_per_sec: u32,
+    /// Number of msgs accepted in a burst.
+    pub(crate) burst: u32,
+}
+
+impl Default for ClientRateLimits {
+    fn default() -> Self {
+        Self {
+            msgs_per_sec: DEFAULT_CLIENT_MSGS_PER_SEC,
+            burst: DEFAULT_CLIENT_MSGS_BURST,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(limits: &ClientRateLimits, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limits.burst),
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, limits: &ClientRateLimits, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * f64::from(limits.msgs_per_sec))
+            .min(f64::from(limits.burst));
+        self.last_refill = now;
+    }
+
+    fn is_full(&self, limits: &ClientRateLimits) -> bool {
+        self.tokens >= f64::from(limits.burst)
+    }
+
+    // Time until a token is available, zero if there's one already
+    fn wait_for_token(&self, limits: &ClientRateLimits) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        let missing = 1.0 - self.tokens;
+        Duration::from_secs_f64(missing / f64::from(limits.msgs_per_sec.max(1)))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    by_key: LruBuckets<PublicKey>,
+    by_ip: LruBuckets<IpAddr>,
+}
+
+// Buckets of the clients, along with the order they were last used in
+#[derive(Debug)]
+struct LruBuckets<K> {
+    buckets: HashMap<K, (TokenBucket, u64)>,
+    by_last_use: BTreeMap<u64, K>,
+    next_use: u64,
+}
+
+impl<K> Default for LruBuckets<K> {
+    fn default() -> Self {
+        Self {
+            buckets: HashMap::new(),
+            by_last_use: BTreeMap::new(),
+            next_use: 0,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> LruBuckets<K> {
+    // Returns the refilled bucket of the client, a full one if it's not tracked yet,
+    // marking it as the most recently used one
+    fn bucket_of(
+        &mut self,
+        client: K,
+        limits: &ClientRateLimits,
+        now: Instant,
+    ) -> &mut TokenBucket {
+        let last_use = self.next_use;
+        self.next_use += 1;
+        let _ = self.by_last_use.insert(last_use, client.clone());
+
+        let (bucket, bucket_last_use) = self
+            .buckets
+            .entry(client)
+            .or_insert_with(|| (TokenBucket::full(limits, now), last_use));
+        if *bucket_last_use != last_use {
+            let _ = self.by_last_use.remove(bucket_last_use);
+            *bucket_last_use = last_use;
+        }
+        bucket.refill(limits, now);
+        bucket
+    }
+
+    // Drops the least recently used buckets while there are too many of them, or while they
+    // are full again, as full buckets are the same as untracked ones
+    fn evict(&mut self, limits: &ClientRateLimits, now: Instant) {
+        while let Some(entry) = self.by_last_use.first_entry() {
+            let too_many = self.buckets.len() > MAX_TRACKED_BUCKETS;
+            if let Some((bucket, _)) = self.buckets.get_mut(entry.get()) {
+                bucket.refill(limits, now);
+                if !too_many && !bucket.is_full(limits) {
+                    break;
+                }
+            }
+            let client = entry.remove();
+            let _ = self.buckets.remove(&client);
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.buckets.len()
+    }
+}
+
+/// Token buckets of the clients sending msgs to our node.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ClientRateLimiter {
+    limits: ClientRateLimits,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl ClientRateLimiter {
+    pub(crate) fn new(limits: ClientRateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Takes a token from the bucket of the address the client sends from, if it has one.
+    /// Otherwise the msg is not to be handled, and the time the client
+    /// has to wait before being admitted again is returned.
+    pub(crate) fn try_admit_addr(&self, addr: SocketAddr) -> Result<(), Duration> {
+        self.try_admit_addr_at(addr, Instant::now())
+    }
+
+    /// Takes a token from the bucket of the public key the client signed its msg with,
+    /// if it has one. To be called only once the msg signature is verified.
+    /// Otherwise the msg is not to be handled, and the time the client
+    /// has to wait before being admitted again is returned.
+    pub(crate) fn try_admit_key(&self, public_key: PublicKey) -> Result<(), Duration> {
+        self.try_admit_key_at(public_key, Instant::now())
+    }
+
+    fn try_admit_addr_at(&self, addr: SocketAddr, now: Instant) -> Result<(), Duration> {
+        try_take_token(&mut self.lock().by_ip, ip_of(addr), &self.limits, now)
+    }
+
+    fn try_admit_key_at(&self, public_key: PublicKey, now: Instant) -> Result<(), Duration> {
+        try_take_token(&mut self.lock().by_key, public_key, &self.limits, now)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Buckets> {
+        self.buckets.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+// The IP the client is limited by, the /64 prefix it sends from for IPv6,
+// as a single host is usually assigned a whole /64
+fn ip_of(addr: SocketAddr) -> IpAddr {
+    match addr.ip() {
+        IpAddr::V4(ip) => IpAddr::V4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => IpAddr::V4(ip),
+            None => {
+                let prefix = u128::from(ip) & !u128::from(u64::MAX);
+                IpAddr::V6(Ipv6Addr::from(prefix))
+            }
+        },
+    }
+}
+
+// Takes a token from the bucket of the client, or returns the time until one is available
+fn try_take_token<K: Clone + Eq + Hash>(
+    buckets: &mut LruBuckets<K>,
+    client: K,
+    limits: &ClientRateLimits,
+    now: Instant,
+) -> Result<(), Duration> {
+    let bucket = buckets.bucket_of(client, limits, now);
+    let wait = bucket.wait_for_token(limits);
+    let result = if wait > Duration::ZERO {
+        Err(wait)
+    } else {
+        bucket.tokens -= 1.0;
+        Ok(())
+    };
+
+    buckets.evict(limits, now);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sn_interface::types::Keypair;
+
+    fn limiter(msgs_per_sec: u32, burst: u32) -> ClientRateLimiter {
+        ClientRateLimiter::new(ClientRateLimits {
+            msgs_per_sec,
+            burst,
+        })
+    }
+
+    fn client(host: u8) -> (PublicKey, SocketAddr) {
+        let addr = SocketAddr::from(([127, 0, 0, host], 12000));
+        (Keypair::new_ed25519().public_key(), addr)
+    }
+
+    // Admits the msg of the client as it's done for the msgs with a valid signature
+    fn try_admit_at(
+        limiter: &ClientRateLimiter,
+        (key, addr): (PublicKey, SocketAddr),
+        now: Instant,
+    ) -> Result<(), Duration> {
+        limiter.try_admit_addr_at(addr, now)?;
+        limiter.try_admit_key_at(key, now)
+    }
+
+    #[test]
+    fn burst_is_admitted_then_client_is_throttled_until_refill() {
+        let limiter = limiter(10, 3);
+        let client = client(1);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(try_admit_at(&limiter, client, now), Ok(()));
+        }
+        let Err(retry_after) = try_admit_at(&limiter, client, now) else {
+            panic!("client should have been throttled");
+        };
+        assert_eq!(retry_after, Duration::from_millis(100));
+
+        assert_eq!(try_admit_at(&limiter, client, now + retry_after), Ok(()));
+    }
+
+    #[test]
+    fn clients_are_throttled_by_key_and_by_addr() {
+        let limiter = limiter(1, 2);
+        let (key, addr) = client(1);
+        let (other_key, other_addr) = client(2);
+        let now = Instant::now();
+
+        assert_eq!(try_admit_at(&limiter, (key, addr), now), Ok(()));
+        assert_eq!(try_admit_at(&limiter, (key, addr), now), Ok(()));
+
+        // the same key from another address, or another key from the same address
+        assert!(try_admit_at(&limiter, (key, other_addr), now).is_err());
+        assert!(try_admit_at(&limiter, (other_key, addr), now).is_err());
+
+        assert_eq!(try_admit_at(&limiter, (other_key, other_addr), now), Ok(()));
+    }
+
+    #[test]
+    fn key_bucket_is_not_charged_for_msgs_only_admitted_by_addr() {
+        let limiter = limiter(1, 2);
+        let (key, addr) = client(1);
+        let now = Instant::now();
+
+        // msgs claiming the key, but whose signature is invalid, only charge their address
+        for host in 2..6 {
+            let other_addr = SocketAddr::from(([127, 0, 0, host], 12000));
+            assert_eq!(limiter.try_admit_addr_at(other_addr, now), Ok(()));
+        }
+
+        assert_eq!(try_admit_at(&limiter, (key, addr), now), Ok(()));
+        assert_eq!(try_admit_at(&limiter, (key, addr), now), Ok(()));
+    }
+
+    #[test]
+    fn rotating_ports_or_ipv6_addrs_within_a_prefix_shares_a_bucket() {
+        let limiter = limiter(1, 2);
+        let now = Instant::now();
+
+        for port in 12000..12002 {
+            let addr = SocketAddr::from(([127, 0, 0, 1], port));
+            assert_eq!(limiter.try_admit_addr_at(addr, now), Ok(()));
+        }
+        let addr = SocketAddr::from(([127, 0, 0, 1], 12002));
+        assert!(limiter.try_admit_addr_at(addr, now).is_err());
+
+        for host in 1..3 {
+            let addr = SocketAddr::from(([0x2001, 0xdb8, 0, 1, 0, 0, 0, host], 12000));
+            assert_eq!(limiter.try_admit_addr_at(addr, now), Ok(()));
+        }
+        let addr = SocketAddr::from(([0x2001, 0xdb8, 0, 1, 0xffff, 0, 0, 3], 12000));
+        assert!(limiter.try_admit_addr_at(addr, now).is_err());
+
+        // another /64 prefix has a bucket of its own
+        let addr = SocketAddr::from(([0x2001, 0xdb8, 0, 2, 0, 0, 0, 1], 12000));
+        assert_eq!(limiter.try_admit_addr_at(addr, now), Ok(()));
+
+        assert_eq!(limiter.lock().by_ip.len(), 3);
+    }
+
+    #[test]
+    fn rotating_addrs_does_not_grow_the_buckets_beyond_their_max() {
+        let limiter = limiter(1, 2);
+        let now = Instant::now();
+
+        for n in 0..MAX_TRACKED_BUCKETS as u32 + 100 {
+            let addr = SocketAddr::from((n.to_be_bytes(), 12000));
+            assert_eq!(limiter.try_admit_addr_at(addr, now), Ok(()));
+            let addr = SocketAddr::from(([0x2001, 0xdb8, 0, n as u16, 0, 0, 0, 1], 12000));
+            assert_eq!(limiter.try_admit_addr_at(addr, now), Ok(()));
+        }
+        assert_eq!(limiter.lock().by_ip.len(), MAX_TRACKED_BUCKETS);
+
+        // buckets full again are dropped as soon as another client is admitted
+        let later = now + Duration::from_secs(2);
+        let addr = SocketAddr::from(([10, 0, 0, 1], 12000));
+        assert_eq!(limiter.try_admit_addr_at(addr, later), Ok(()));
+        assert_eq!(limiter.lock().by_ip.len(), 1);
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Subscriptions of clients to the edits of the registers held by our section.
+//!
+//! A subscribed client polls our node as an Elder for the edits we forward to the register
+//! holders. The stream of a poll is kept until there are edits to answer it with, while the
+//! edits seen in between polls are kept until the next one, or until the subscription expires.
+//! Subscriptions are dropped once we are no longer responsible for the register, so that
+//! clients subscribe again to the Elders which are.
+
+use sn_comms::SendStream;
+use sn_interface::{
+    messaging::{
+        data::{SignedRegisterEdit, REGISTER_SUBSCRIPTION_TTL},
+        MsgId,
+    },
+    types::{ClientId, RegisterAddress},
+};
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+use xor_name::Prefix;
+
+// Number of subscriptions kept, beyond which new ones are rejected
+pub(crate) const MAX_REGISTER_SUBSCRIPTIONS: usize = 10_000;
+// Number of subscriptions kept for a client, beyond which its new ones are rejected
+pub(crate) const MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT: usize = 100;
+// Number of edits kept for a client in between its polls, beyond which the oldest are dropped
+const MAX_PENDING_EDITS: usize = 100;
+
+/// A poll of a client for the edits of a register, awaiting them on its stream.
+#[derive(Debug)]
+pub(crate) struct RegisterPoll<S = SendStream> {
+    pub(crate) client_id: ClientId,
+    /// Id of the subscription msg, which the response is correlated to.
+    pub(crate) correlation_id: MsgId,
+    pub(crate) stream: S,
+}
+
+/// Outcome of a client polling for the edits of a register.
+#[derive(Debug)]
+pub(crate) enum PollOutcome<S = SendStream> {
+    /// There are edits the client hasn't been notified of, to answer the poll with.
+    Notify {
+        poll: RegisterPoll<S>,
+        edits: Vec<SignedRegisterEdit>,
+    },
+    /// The poll is kept until there are edits, replacing the previous poll of the client,
+    /// which is to be answered to end it.
+    Wait { replaced: Option<RegisterPoll<S>> },
+}
+
+#[derive(Debug)]
+struct Subscription<S> {
+    client_id: ClientId,
+    poll: Option<RegisterPoll<S>>,
+    pending_edits: VecDeque<SignedRegisterEdit>,
+    expires_at: Instant,
+}
+
+type Subscriptions<S> = BTreeMap<RegisterAddress, Vec<Subscription<S>>>;
+
+/// The subscriptions of clients to the edits of registers, kept by our node as an Elder.
+#[derive(Debug)]
+pub(crate) struct RegisterSubscriptions<S = SendStream> {
+    subscriptions: Arc<Mutex<Subscriptions<S>>>,
+}
+
+// Derived impls would require `S: Clone + Default`, while only the `Arc` is needed
+impl<S> Clone for RegisterSubscriptions<S> {
+    fn clone(&self) -> Self {
+        Self {
+            subscriptions: self.subscriptions.clone(),
+        }
+    }
+}
+
+impl<S> Default for RegisterSubscriptions<S> {
+    fn default() -> Self {
+        Self {
+            subscriptions: Arc::default(),
+        }
+    }
+}
+
+impl<S> RegisterSubscriptions<S> {
+    /// Whether the client is subscribed to the edits of the register.
+    pub(crate) fn is_subscribed(&self, address: &RegisterAddress, client_id: &ClientId) -> bool {
+        self.lock().get(address).map_or(false, |subscribers| {
+            subscribers.iter().any(|sub| sub.client_id == *client_id)
+        })
+    }
+
+    /// Subscribes the client to the edits of the register for another `REGISTER_SUBSCRIPTION_TTL`,
+    /// polling for the edits it hasn't been notified of yet.
+    /// The poll is returned back as an error if we keep too many subscriptions already,
+    /// in total or for the client.
+    pub(crate) fn poll(
+        &self,
+        address: RegisterAddress,
+        poll: RegisterPoll<S>,
+    ) -> Result<PollOutcome<S>, RegisterPoll<S>> {
+        self.poll_at(address, poll, Instant::now())
+    }
+
+    fn poll_at(
+        &self,
+        address: RegisterAddress,
+        poll: RegisterPoll<S>,
+        now: Instant,
+    ) -> Result<PollOutcome<S>, RegisterPoll<S>> {
+        let mut subscriptions = self.lock();
+        let is_subscribed = subscriptions.get(&address).map_or(false, |subs| {
+            subs.iter().any(|sub| sub.client_id == poll.client_id)
+        });
+        if !is_subscribed {
+            let (total, of_client) = subscriptions
+                .values()
+                .flatten()
+                .fold((0, 0), |(t, c), sub| {
+                    (t + 1, c + usize::from(sub.client_id == poll.client_id))
+                });
+            if total >= MAX_REGISTER_SUBSCRIPTIONS
+                || of_client >= MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT
+            {
+                return Err(poll);
+            }
+        }
+
+        let subscribers = subscriptions.entry(address).or_default();
+        let index = match subscribers
+            .iter()
+            .position(|sub| sub.client_id == poll.client_id)
+        {
+            Some(index) => index,
+            None => {
+                subscribers.push(Subscription {
+                    client_id: poll.client_id,
+                    poll: None,
+                    pending_edits: VecDeque::new(),
+                    expires_at: now,
+                });
+                subscribers.len() - 1
+            }
+        };
+
+        let sub = &mut subscribers[index];
+        sub.expires_at = now + REGISTER_SUBSCRIPTION_TTL;
+        if sub.pending_edits.is_empty() {
+            let replaced = sub.poll.replace(poll);
+            Ok(PollOutcome::Wait { replaced })
+        } else {
+            // a poll is only kept while there are no edits to answer it with
+            let edits = std::mem::take(&mut sub.pending_edits).into();
+            Ok(PollOutcome::Notify { poll, edits })
+        }
+    }
+
+    /// Notifies the clients subscribed to the register of the edit, returning the polls to be
+    /// answered with it. The edit is kept for the subscribed clients which aren't polling.
+    pub(crate) fn notify(&self, edit: &SignedRegisterEdit) -> Vec<RegisterPoll<S>> {
+        self.notify_at(edit, Instant::now())
+    }
+
+    fn notify_at(&self, edit: &SignedRegisterEdit, now: Instant) -> Vec<RegisterPoll<S>> {
+        let mut subscriptions = self.lock();
+        let Some(subscribers) = subscriptions.get_mut(&edit.dst_address()) else {
+            return vec![];
+        };
+
+        let mut polls = vec![];
+        for sub in subscribers.iter_mut().filter(|sub| sub.expires_at > now) {
+            if let Some(poll) = sub.poll.take() {
+                polls.push(poll);
+            } else {
+                if sub.pending_edits.len() >= MAX_PENDING_EDITS {
+                    warn!(
+                        "Dropping the oldest edit of register {:?} kept for {}, \
+                        as it isn't polling for them",
+                        edit.dst_address(),
+                        sub.client_id
+                    );
+                    let _ = sub.pending_edits.pop_front();
+                }
+                sub.pending_edits.push_back(edit.clone());
+            }
+        }
+        polls
+    }
+
+    /// Removes the subscriptions which expired, returning any poll of theirs to be answered.
+    pub(crate) fn remove_expired(&self) -> Vec<RegisterPoll<S>> {
+        self.remove_expired_at(Instant::now())
+    }
+
+    fn remove_expired_at(&self, now: Instant) -> Vec<RegisterPoll<S>> {
+        self.remove_where(|_, sub| sub.expires_at <= now)
+    }
+
+    /// Removes the subscriptions to registers out of our section's prefix,
+    /// returning their polls to be answered.
+    pub(crate) fn remove_outside(&self, prefix: &Prefix) -> Vec<RegisterPoll<S>> {
+        self.remove_where(|address, _| !prefix.matches(address.name()))
+    }
+
+    /// Removes all the subscriptions, returning their polls to be answered.
+    pub(crate) fn remove_all(&self) -> Vec<RegisterPoll<S>> {
+        self.remove_where(|_, _| true)
+    }
+
+    fn remove_where(
+        &self,
+        predicate: impl Fn(&RegisterAddress, &Subscription<S>) -> bool,
+    ) -> Vec<RegisterPoll<S>> {
+        let mut removed_polls = vec![];
+        self.lock().retain(|address, subscribers| {
+            let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(subscribers)
+                .into_iter()
+                .partition(|sub| predicate(address, sub));
+            removed_polls.extend(removed.into_iter().filter_map(|sub| sub.poll));
+            *subscribers = kept;
+            !subscribers.is_empty()
+        });
+        removed_polls
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Subscriptions<S>> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use sn_interface::{
+        messaging::{data::EditRegister, ClientAuth},
+        types::{
+            register::{Policy, Register, User},
+            Keypair,
+        },
+    };
+
+    use eyre::{eyre, Result};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        net::SocketAddr,
+    };
+    use xor_name::XorName;
+
+    fn poll(client_id: ClientId) -> RegisterPoll<()> {
+        RegisterPoll {
+            client_id,
+            correlation_id: MsgId::new(),
+            stream: (),
+        }
+    }
+
+    fn client(port: u16) -> ClientId {
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        ClientId::new(xor_name::rand::random(), addr)
+    }
+
+    pub(crate) fn edit(name: XorName) -> Result<SignedRegisterEdit> {
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: BTreeMap::new(),
+        };
+        let mut register = Register::new(owner, name, 1, policy);
+        let (_, edit) = register.write(b"edit".to_vec(), BTreeSet::default())?;
+        let op = EditRegister {
+            address: *register.address(),
+            edit,
+            policy_version: register.policy_version(),
+        };
+        let signature = keypair.sign(&bincode::serialize(&op)?);
+        let auth = ClientAuth {
+            public_key: keypair.public_key(),
+            signature,
+        };
+        Ok(SignedRegisterEdit { op, auth })
+    }
+
+    #[test]
+    fn edits_answer_the_poll_or_are_kept_until_the_next_one() -> Result<()> {
+        let subscriptions = RegisterSubscriptions::default();
+        let edit = edit(xor_name::rand::random())?;
+        let address = edit.dst_address();
+        let alice = client(12000);
+        let now = Instant::now();
+
+        let outcome = subscriptions.poll_at(address, poll(alice), now);
+        assert!(matches!(outcome, Ok(PollOutcome::Wait { replaced: None })));
+
+        // the poll awaiting the edits is answered with the first one
+        let polls = subscriptions.notify_at(&edit, now);
+        assert_eq!(polls.len(), 1);
+        assert_eq!(polls[0].client_id, alice);
+
+        // while the next ones are kept until alice polls again
+        assert!(subscriptions.notify_at(&edit, now).is_empty());
+        assert!(subscriptions.notify_at(&edit, now).is_empty());
+        match subscriptions.poll_at(address, poll(alice), now) {
+            Ok(PollOutcome::Notify { poll, edits }) => {
+                assert_eq!(poll.client_id, alice);
+                assert_eq!(edits.len(), 2);
+            }
+            other => return Err(eyre!("Unexpected poll outcome: {other:?}")),
+        }
+
+        let outcome = subscriptions.poll_at(address, poll(alice), now);
+        assert!(matches!(outcome, Ok(PollOutcome::Wait { replaced: None })));
+        let outcome = subscriptions.poll_at(address, poll(alice), now);
+        assert!(matches!(
+            outcome,
+            Ok(PollOutcome::Wait { replaced: Some(_) })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn subscriptions_expire_unless_polled_again() -> Result<()> {
+        let subscriptions = RegisterSubscriptions::default();
+        let edit = edit(xor_name::rand::random())?;
+        let address = edit.dst_address();
+        let (alice, bob) = (client(12000), client(12001));
+        let now = Instant::now();
+
+        let _ = subscriptions.poll_at(address, poll(alice), now);
+        let _ = subscriptions.poll_at(address, poll(bob), now);
+        assert_eq!(subscriptions.notify_at(&edit, now).len(), 2);
+
+        // only alice polls again in time
+        let later = now + REGISTER_SUBSCRIPTION_TTL / 2;
+        let _ = subscriptions.poll_at(address, poll(alice), later);
+
+        let expired_at = now + REGISTER_SUBSCRIPTION_TTL;
+        let polls = subscriptions.notify_at(&edit, expired_at);
+        assert_eq!(polls.len(), 1);
+        assert_eq!(polls[0].client_id, alice);
+
+        assert!(subscriptions.remove_expired_at(expired_at).is_empty());
+        let outcome = subscriptions.poll_at(address, poll(bob), expired_at);
+        assert!(matches!(outcome, Ok(PollOutcome::Wait { replaced: None })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn new_subscriptions_beyond_the_max_per_client_are_rejected() {
+        let subscriptions = RegisterSubscriptions::default();
+        let (alice, bob) = (client(12000), client(12001));
+        let addresses: Vec<_> = (0..=MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT)
+            .map(|_| RegisterAddress {
+                name: xor_name::rand::random(),
+                tag: 1,
+            })
+            .collect();
+
+        for address in &addresses[..MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT] {
+            assert!(subscriptions.poll(*address, poll(alice)).is_ok());
+        }
+        let rejected = subscriptions.poll(
+            addresses[MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT],
+            poll(alice),
+        );
+        assert!(rejected.is_err());
+
+        // alice still polls the registers she's subscribed to, while other clients subscribe
+        assert!(subscriptions.poll(addresses[0], poll(alice)).is_ok());
+        assert!(subscriptions
+            .poll(addresses[MAX_REGISTER_SUBSCRIPTIONS_PER_CLIENT], poll(bob))
+            .is_ok());
+    }
+
+    #[test]
+    fn new_subscriptions_beyond_the_max_are_rejected() {
+        let subscriptions = RegisterSubscriptions::default();
+        let address = RegisterAddress {
+            name: xor_name::rand::random(),
+            tag: 1,
+        };
+
+        // clients are told apart by their random names
+        for _ in 0..MAX_REGISTER_SUBSCRIPTIONS {
+            assert!(subscriptions.poll(address, poll(client(12000))).is_ok());
+        }
+        let carol = client(12000);
+        assert!(subscriptions.poll(address, poll(carol)).is_err());
+        assert!(!subscriptions.is_subscribed(&address, &carol));
+    }
+
+    #[test]
+    fn subscriptions_out_of_our_prefix_are_removed() -> Result<()> {
+        let subscriptions = RegisterSubscriptions::default();
+        let prefix = Prefix::default().pushed(false);
+        let ours = edit(prefix.substituted_in(xor_name::rand::random()))?;
+        let theirs = edit(prefix.sibling().substituted_in(xor_name::rand::random()))?;
+        let alice = client(12000);
+
+        let _ = subscriptions.poll(ours.dst_address(), poll(alice));
+        let _ = subscriptions.poll(theirs.dst_address(), poll(alice));
+
+        let removed = subscriptions.remove_outside(&prefix);
+        assert_eq!(removed.len(), 1);
+        assert!(subscriptions.notify(&theirs).is_empty());
+        assert_eq!(subscriptions.notify(&ours).len(), 1);
+
+        assert!(subscriptions.remove_all().is_empty());
+        assert!(subscriptions.notify(&ours).is_empty());
+
+        Ok(())
+    }
+}
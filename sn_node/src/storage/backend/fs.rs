@@ -0,0 +1,390 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Namespace, StorageBackend, StoredCounts, StoredSize, MAX_QUARANTINED};
+use crate::storage::{Error, Result};
+
+use async_trait::async_trait;
+use hex::FromHex;
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::{
+    fs::{create_dir_all, metadata, read, read_dir, remove_dir_all, remove_file, rename, File},
+    io::AsyncWriteExt,
+};
+use walkdir::WalkDir;
+use xor_name::XorName;
+
+const BIT_TREE_DEPTH: usize = 20;
+
+/// Stores each item in its own file, named after the hex encoded name of the item,
+/// within a tree of folders made of the first `BIT_TREE_DEPTH` bits of the name.
+///
+/// A log is stored as a folder in place of the file, holding a file per entry named after its id.
+#[derive(Clone, Debug)]
+pub(in crate::storage) struct FsBackend {
+    root: PathBuf,
+    // the items and logs found are counted when the backend is created
+    counts: Arc<StoredCounts>,
+}
+
+impl FsBackend {
+    pub(in crate::storage) fn new(root: &Path) -> Self {
+        let backend = Self {
+            root: root.to_path_buf(),
+            counts: Arc::default(),
+        };
+        for ns in Namespace::ALL {
+            backend.counts.set(ns, backend.stored_names(ns).len());
+        }
+        backend
+    }
+
+    fn item_path(&self, ns: Namespace, name: &XorName) -> PathBuf {
+        prefix_tree_path(&self.root.join(ns.dir_name()), *name).join(hex::encode(name))
+    }
+
+    fn stored_names(&self, ns: Namespace) -> Vec<XorName> {
+        let files = list_files_in(&self.root.join(ns.dir_name()));
+        // the name of a log is that of the folder holding its entries
+        let paths: BTreeSet<_> = if ns.is_log() {
+            files
+                .iter()
+                .filter_map(|filepath| filepath.parent().map(Path::to_path_buf))
+                .collect()
+        } else {
+            files.into_iter().collect()
+        };
+
+        paths
+            .iter()
+            .filter_map(|path| match filepath_to_name(path) {
+                Ok(name) => Some(name),
+                Err(err) => {
+                    warn!("Ignoring unexpected file found in the store: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for FsBackend {
+    async fn contains(&self, ns: Namespace, name: &XorName) -> Result<bool> {
+        Ok(self.item_path(ns, name).exists())
+    }
+
+    async fn put(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<bool> {
+        let filepath = self.item_path(ns, name);
+        if filepath.exists() {
+            return Ok(false);
+        }
+
+        write_file(&filepath, value).await?;
+        self.counts.increment(ns);
+        Ok(true)
+    }
+
+    async fn replace(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<Option<usize>> {
+        let filepath = self.item_path(ns, name);
+        let replaced = file_size(&filepath).await?;
+
+        // the new item is written aside first, so that it's never found incomplete
+        let tmp_filepath = filepath.with_extension("tmp");
+        write_file(&tmp_filepath, value).await?;
+        rename(tmp_filepath, filepath).await?;
+        if replaced.is_none() {
+            self.counts.increment(ns);
+        }
+
+        Ok(replaced)
+    }
+
+    async fn get(&self, ns: Namespace, name: &XorName) -> Result<Option<Vec<u8>>> {
+        match read(self.item_path(ns, name)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn remove(&self, ns: Namespace, name: &XorName) -> Result<Option<usize>> {
+        let removed = remove_file_if_exists(&self.item_path(ns, name)).await?;
+        if removed.is_some() {
+            self.counts.decrement(ns);
+        }
+        Ok(removed)
+    }
+
+    async fn contains_entry(&self, ns: Namespace, name: &XorName, entry_id: &str) -> Result<bool> {
+        Ok(self.item_path(ns, name).join(entry_id).exists())
+    }
+
+    async fn append(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+        value: &[u8],
+    ) -> Result<bool> {
+        let log_path = self.item_path(ns, name);
+        let filepath = log_path.join(entry_id);
+        if filepath.exists() {
+            return Ok(false);
+        }
+
+        let new_log = !log_path.exists();
+        write_file(&filepath, value).await?;
+        if new_log {
+            self.counts.increment(ns);
+        }
+        Ok(true)
+    }
+
+    async fn remove_entry(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+    ) -> Result<Option<usize>> {
+        remove_file_if_exists(&self.item_path(ns, name).join(entry_id)).await
+    }
+
+    async fn read_log(&self, ns: Namespace, name: &XorName) -> Result<Vec<Vec<u8>>> {
+        let mut entries = vec![];
+        for filepath in list_files_in(&self.item_path(ns, name)) {
+            entries.push(read(filepath).await?);
+        }
+        Ok(entries)
+    }
+
+    async fn remove_log(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        let dir = self.item_path(ns, name);
+        let files = list_files_in(&dir);
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let mut bytes = 0;
+        for file in &files {
+            bytes += metadata(file).await?.len() as usize;
+        }
+        remove_dir_all(dir).await?;
+        self.counts.decrement(ns);
+
+        Ok(Some(StoredSize {
+            entries: files.len(),
+            bytes,
+        }))
+    }
+
+    async fn quarantine(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        let path = self.item_path(ns, name);
+        let files = if path.is_dir() {
+            list_files_in(&path)
+        } else if path.exists() {
+            vec![path.clone()]
+        } else {
+            return Ok(None);
+        };
+
+        let mut bytes = 0;
+        for file in &files {
+            bytes += metadata(file).await?.len() as usize;
+        }
+
+        // anything quarantined before under the same name is replaced
+        let quarantine_dir = self.root.join(ns.quarantine_dir_name());
+        create_dir_all(&quarantine_dir).await?;
+        let quarantine_path = quarantine_dir.join(hex::encode(name));
+        if quarantine_path.is_dir() {
+            remove_dir_all(&quarantine_path).await?;
+        } else if quarantine_path.exists() {
+            remove_file(&quarantine_path).await?;
+        }
+        rename(path, &quarantine_path).await?;
+        self.counts.decrement(ns);
+        evict_quarantined(&quarantine_dir, &quarantine_path).await?;
+
+        Ok(Some(StoredSize {
+            entries: files.len(),
+            bytes,
+        }))
+    }
+
+    async fn quarantined(&self, ns: Namespace) -> Result<Vec<XorName>> {
+        let quarantine_dir = self.root.join(ns.quarantine_dir_name());
+        Ok(quarantined_paths(&quarantine_dir)
+            .await?
+            .iter()
+            .filter_map(|(_, path)| filepath_to_name(path).ok())
+            .collect())
+    }
+
+    async fn size(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        let path = self.item_path(ns, name);
+        if !path.is_dir() {
+            return Ok(file_size(&path)
+                .await?
+                .map(|bytes| StoredSize { entries: 1, bytes }));
+        }
+
+        let files = list_files_in(&path);
+        if files.is_empty() {
+            return Ok(None);
+        }
+        let mut bytes = 0;
+        for file in &files {
+            bytes += metadata(file).await?.len() as usize;
+        }
+
+        Ok(Some(StoredSize {
+            entries: files.len(),
+            bytes,
+        }))
+    }
+
+    async fn names(&self, ns: Namespace) -> Result<Vec<XorName>> {
+        Ok(self.stored_names(ns))
+    }
+
+    fn count(&self, ns: Namespace) -> usize {
+        self.counts.get(ns)
+    }
+
+    fn stored_size(&self, ns: Namespace) -> StoredSize {
+        let files = list_files_in(&self.root.join(ns.dir_name()));
+        let bytes = files
+            .iter()
+            .filter_map(|filepath| std::fs::metadata(filepath).ok())
+            .map(|metadata| metadata.len() as usize)
+            .sum();
+
+        StoredSize {
+            entries: files.len(),
+            bytes,
+        }
+    }
+}
+
+async fn write_file(filepath: &Path, value: &[u8]) -> Result<()> {
+    if let Some(dirs) = filepath.parent() {
+        create_dir_all(dirs).await?;
+    }
+
+    let mut file = File::create(filepath).await?;
+    file.write_all(value).await?;
+    // Let's sync up OS data to disk to reduce the chances of
+    // concurrent reading failing by reading an empty/incomplete file
+    file.sync_data().await?;
+
+    Ok(())
+}
+
+async fn file_size(filepath: &Path) -> Result<Option<usize>> {
+    match metadata(filepath).await {
+        Ok(meta) => Ok(Some(meta.len() as usize)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn remove_file_if_exists(filepath: &Path) -> Result<Option<usize>> {
+    let size = file_size(filepath).await?;
+    if size.is_some() {
+        remove_file(filepath).await?;
+    }
+    Ok(size)
+}
+
+// Removes the oldest items, or logs, quarantined in the folder, other than the one just
+// quarantined, until there are no more than `MAX_QUARANTINED` left.
+async fn evict_quarantined(quarantine_dir: &Path, just_quarantined: &Path) -> Result<()> {
+    let mut paths = quarantined_paths(quarantine_dir).await?;
+    if paths.len() <= MAX_QUARANTINED {
+        return Ok(());
+    }
+
+    paths.sort();
+    let excess = paths.len() - MAX_QUARANTINED;
+    for (_, path) in paths
+        .iter()
+        .filter(|(_, path)| path != just_quarantined)
+        .take(excess)
+    {
+        if path.is_dir() {
+            remove_dir_all(path).await?;
+        } else {
+            remove_file(path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Last modification time and path of the items, or logs, quarantined in the folder
+async fn quarantined_paths(quarantine_dir: &Path) -> Result<Vec<(Option<SystemTime>, PathBuf)>> {
+    let mut entries = match read_dir(quarantine_dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut paths = vec![];
+    while let Some(entry) = entries.next_entry().await? {
+        let modified = entry.metadata().await?.modified().ok();
+        paths.push((modified, entry.path()));
+    }
+    Ok(paths)
+}
+
+fn filepath_to_name(path: &Path) -> Result<XorName> {
+    let filename = path
+        .file_name()
+        .ok_or_else(|| Error::NoFilename(path.to_path_buf()))?
+        .to_str()
+        .ok_or_else(|| Error::InvalidFilename(path.to_path_buf()))?;
+
+    Ok(XorName(<[u8; 32]>::from_hex(filename)?))
+}
+
+// Helper that returns the prefix tree path of depth BIT_TREE_DEPTH for a given xorname
+// Example:
+// - with a xorname with starting bits `010001110110....`
+// - and a BIT_TREE_DEPTH of `6`
+// returns the path `ROOT_PATH/0/1/0/0/0/1`
+fn prefix_tree_path(root: &Path, xorname: XorName) -> PathBuf {
+    let bin = format!("{xorname:b}");
+    let prefix_dir_path: PathBuf = bin.chars().take(BIT_TREE_DEPTH).map(String::from).collect();
+    root.join(prefix_dir_path)
+}
+
+fn list_files_in(path: &Path) -> Vec<PathBuf> {
+    if !path.exists() {
+        return vec![];
+    }
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| match e {
+            Ok(direntry) => Some(direntry),
+            Err(err) => {
+                warn!("Store: failed to process filesystem entry: {}", err);
+                None
+            }
+        })
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.path().to_path_buf())
+        .collect()
+}
@@ -0,0 +1,378 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Namespace, StorageBackend, StoredCounts, StoredSize, MAX_QUARANTINED};
+use crate::storage::{Error, Result};
+
+use async_trait::async_trait;
+use sled::{
+    transaction::{
+        ConflictableTransactionError, ConflictableTransactionResult, TransactionError,
+        TransactionalTree,
+    },
+    Db, IVec, Transactional, Tree,
+};
+use std::{convert::Infallible, path::Path, sync::Arc};
+use xor_name::XorName;
+
+const KV_STORE_DIR_NAME: &str = "kv";
+
+/// Stores the items in an embedded key-value store, with a tree per `Namespace`.
+///
+/// Items are keyed by their name, and log entries by the name of the log followed by the
+/// id of the entry, so that all the entries of a log are found by prefix. The names of the
+/// items and logs are also kept in a tree of their own, so that listing them doesn't
+/// require reading all the data stored. Each name is indexed along with a generation, bumped
+/// by every write to the item or log, since removing a log requires to scan its entries, which
+/// can't be done from within a transaction. The names of the items and logs quarantined are
+/// likewise kept in a tree of their own, along with an id increasing as they are quarantined.
+#[derive(Clone, Debug)]
+pub(in crate::storage) struct KvBackend {
+    db: Db,
+    // the names indexed are counted when the store is opened
+    counts: Arc<StoredCounts>,
+}
+
+impl KvBackend {
+    /// Opens the key-value store at the given root location, creating it if it doesn't exist.
+    pub(in crate::storage) fn open(root: &Path) -> Result<Self> {
+        let db = sled::open(root.join(KV_STORE_DIR_NAME))?;
+        let backend = Self {
+            db,
+            counts: Arc::default(),
+        };
+        for ns in Namespace::ALL {
+            let (_, names) = backend.trees(ns)?;
+            backend.counts.set(ns, names.len());
+        }
+        Ok(backend)
+    }
+
+    fn trees(&self, ns: Namespace) -> Result<(Tree, Tree)> {
+        let data = self.db.open_tree(ns.dir_name())?;
+        let names = self.db.open_tree(format!("{}_names", ns.dir_name()))?;
+        Ok((data, names))
+    }
+
+    // Inserts the value unless the key is already in use, indexing the name along with it
+    async fn insert_new(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<bool> {
+        let (data, names) = self.trees(ns)?;
+        let (inserted, new_name) = (&data, &names)
+            .transaction(|(data, names)| {
+                if data.get(key)?.is_some() {
+                    return Ok::<_, ConflictableTransactionError<Infallible>>((false, false));
+                }
+                let _ = data.insert(key, value)?;
+                let new_name = bump_generation(names, name)?;
+                Ok((true, new_name))
+            })
+            .map_err(transaction_error)?;
+
+        if new_name {
+            self.counts.increment(ns);
+        }
+        if inserted {
+            let _ = self.db.flush_async().await?;
+        }
+        Ok(inserted)
+    }
+
+    fn quarantine_trees(&self, ns: Namespace) -> Result<(Tree, Tree)> {
+        let data = self.db.open_tree(ns.quarantine_dir_name())?;
+        let names = self
+            .db
+            .open_tree(format!("{}_names", ns.quarantine_dir_name()))?;
+        Ok((data, names))
+    }
+
+    // Removes the item or log entries keyed by the name, moving them into the quarantine trees
+    // if given. Their keys are scanned before the transaction removing them, which is thus
+    // retried if the name's generation changed meanwhile, i.e. any was written or removed.
+    async fn take(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        quarantine: Option<&(Tree, Tree)>,
+    ) -> Result<Option<StoredSize>> {
+        let (data, names) = self.trees(ns)?;
+        let mut trees = vec![&data, &names];
+        let mut quarantine_id = [0; 8];
+        if let Some((quarantine, quarantined)) = quarantine {
+            trees.extend([quarantine, quarantined]);
+            quarantine_id = self.db.generate_id()?.to_be_bytes();
+        }
+
+        let entries = loop {
+            let generation = names.get(name)?;
+            // items are keyed by their name, which is thus also the prefix of the key of log entries
+            let mut entries = vec![];
+            for entry in data.scan_prefix(name) {
+                entries.push(entry?);
+            }
+            if entries.is_empty() {
+                return Ok(None);
+            }
+
+            let taken = trees.as_slice().transaction(|trees| {
+                let (data, names) = (&trees[0], &trees[1]);
+                if names.get(name)? != generation {
+                    return Ok(false);
+                }
+                for (key, value) in &entries {
+                    let _ = data.remove(key)?;
+                    if let Some(quarantine) = trees.get(2) {
+                        let _ = quarantine.insert(key, value)?;
+                    }
+                }
+                let _ = names.remove(&name.0)?;
+                if let Some(quarantined) = trees.get(3) {
+                    let _ = quarantined.insert(&name.0, &quarantine_id)?;
+                }
+                Ok::<_, ConflictableTransactionError<Infallible>>(true)
+            });
+            if taken.map_err(transaction_error)? {
+                break entries;
+            }
+        };
+        self.counts.decrement(ns);
+        let _ = self.db.flush_async().await?;
+
+        Ok(Some(StoredSize {
+            entries: entries.len(),
+            bytes: entries.iter().map(|(_, value)| value.len()).sum(),
+        }))
+    }
+
+    // Removes the items, or logs, quarantined the earliest,
+    // until there are no more than `MAX_QUARANTINED` left
+    async fn evict_quarantined(&self, (quarantine, quarantined): &(Tree, Tree)) -> Result<()> {
+        if quarantined.len() <= MAX_QUARANTINED {
+            return Ok(());
+        }
+
+        let mut by_id = vec![];
+        for entry in quarantined.iter() {
+            let (name, id) = entry?;
+            by_id.push((id, name));
+        }
+        by_id.sort();
+        let excess = by_id.len() - MAX_QUARANTINED;
+        for (_, name) in by_id.iter().take(excess) {
+            for key in quarantine.scan_prefix(name).keys() {
+                let _ = quarantine.remove(key?)?;
+            }
+            let _ = quarantined.remove(name)?;
+        }
+        let _ = self.db.flush_async().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for KvBackend {
+    async fn contains(&self, ns: Namespace, name: &XorName) -> Result<bool> {
+        let (data, _) = self.trees(ns)?;
+        Ok(data.contains_key(name)?)
+    }
+
+    async fn put(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<bool> {
+        self.insert_new(ns, name, &name.0, value).await
+    }
+
+    async fn replace(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<Option<usize>> {
+        let (data, names) = self.trees(ns)?;
+        let (replaced, new_name) = (&data, &names)
+            .transaction(|(data, names)| {
+                let replaced = data.insert(&name.0, value)?;
+                let new_name = bump_generation(names, name)?;
+                Ok::<_, ConflictableTransactionError<Infallible>>((replaced, new_name))
+            })
+            .map_err(transaction_error)?;
+        if new_name {
+            self.counts.increment(ns);
+        }
+
+        let _ = self.db.flush_async().await?;
+        Ok(replaced.map(|value| value.len()))
+    }
+
+    async fn get(&self, ns: Namespace, name: &XorName) -> Result<Option<Vec<u8>>> {
+        let (data, _) = self.trees(ns)?;
+        Ok(data.get(name)?.map(|value| value.to_vec()))
+    }
+
+    async fn remove(&self, ns: Namespace, name: &XorName) -> Result<Option<usize>> {
+        let (data, names) = self.trees(ns)?;
+        let (removed, unindexed) = (&data, &names)
+            .transaction(|(data, names)| {
+                let removed = data.remove(&name.0)?;
+                let unindexed = names.remove(&name.0)?.is_some();
+                Ok::<_, ConflictableTransactionError<Infallible>>((removed, unindexed))
+            })
+            .map_err(transaction_error)?;
+        if unindexed {
+            self.counts.decrement(ns);
+        }
+
+        let _ = self.db.flush_async().await?;
+        Ok(removed.map(|value| value.len()))
+    }
+
+    async fn contains_entry(&self, ns: Namespace, name: &XorName, entry_id: &str) -> Result<bool> {
+        let (data, _) = self.trees(ns)?;
+        Ok(data.contains_key(entry_key(name, entry_id))?)
+    }
+
+    async fn append(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+        value: &[u8],
+    ) -> Result<bool> {
+        self.insert_new(ns, name, &entry_key(name, entry_id), value)
+            .await
+    }
+
+    async fn remove_entry(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+    ) -> Result<Option<usize>> {
+        // the name of the log is kept indexed, even if it's left with no entries
+        let (data, names) = self.trees(ns)?;
+        let key = entry_key(name, entry_id);
+        let removed = (&data, &names)
+            .transaction(|(data, names)| {
+                let removed = data.remove(key.as_slice())?;
+                if removed.is_some() {
+                    let _ = bump_generation(names, name)?;
+                }
+                Ok::<_, ConflictableTransactionError<Infallible>>(removed)
+            })
+            .map_err(transaction_error)?;
+        if removed.is_some() {
+            let _ = self.db.flush_async().await?;
+        }
+        Ok(removed.map(|value| value.len()))
+    }
+
+    async fn read_log(&self, ns: Namespace, name: &XorName) -> Result<Vec<Vec<u8>>> {
+        let (data, _) = self.trees(ns)?;
+        data.scan_prefix(name)
+            .values()
+            .map(|value| Ok(value?.to_vec()))
+            .collect()
+    }
+
+    async fn remove_log(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        self.take(ns, name, None).await
+    }
+
+    async fn quarantine(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        let trees = self.quarantine_trees(ns)?;
+        let taken = self.take(ns, name, Some(&trees)).await?;
+        if taken.is_some() {
+            self.evict_quarantined(&trees).await?;
+        }
+        Ok(taken)
+    }
+
+    async fn quarantined(&self, ns: Namespace) -> Result<Vec<XorName>> {
+        let (_, quarantined) = self.quarantine_trees(ns)?;
+        let mut all_names = vec![];
+        for key in quarantined.iter().keys() {
+            if let Ok(name) = <[u8; 32]>::try_from(key?.as_ref()) {
+                all_names.push(XorName(name));
+            }
+        }
+        Ok(all_names)
+    }
+
+    async fn size(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>> {
+        let (data, _) = self.trees(ns)?;
+        // items are keyed by their name, which is thus also the prefix of the key of log entries
+        let mut size = StoredSize::default();
+        for value in data.scan_prefix(name).values() {
+            size.entries += 1;
+            size.bytes += value?.len();
+        }
+
+        Ok((size.entries > 0).then_some(size))
+    }
+
+    async fn names(&self, ns: Namespace) -> Result<Vec<XorName>> {
+        let (_, names) = self.trees(ns)?;
+        let mut all_names = vec![];
+        for key in names.iter().keys() {
+            match <[u8; 32]>::try_from(key?.as_ref()) {
+                Ok(name) => all_names.push(XorName(name)),
+                Err(_) => warn!("Ignoring invalid name found in the {ns:?} names index"),
+            }
+        }
+        Ok(all_names)
+    }
+
+    fn count(&self, ns: Namespace) -> usize {
+        self.counts.get(ns)
+    }
+
+    fn stored_size(&self, ns: Namespace) -> StoredSize {
+        let data = match self.trees(ns) {
+            Ok((data, _)) => data,
+            Err(err) => {
+                warn!("Could not open the {ns:?} key-value store: {err}");
+                return StoredSize::default();
+            }
+        };
+
+        data.iter().values().filter_map(|value| value.ok()).fold(
+            StoredSize::default(),
+            |size, value| StoredSize {
+                entries: size.entries + 1,
+                bytes: size.bytes + value.len(),
+            },
+        )
+    }
+}
+
+fn entry_key(name: &XorName, entry_id: &str) -> Vec<u8> {
+    let mut key = name.0.to_vec();
+    key.extend_from_slice(entry_id.as_bytes());
+    key
+}
+
+// Bumps the generation the name is indexed with, indexing it if it's not yet,
+// in which case true is returned
+fn bump_generation<E>(
+    names: &TransactionalTree,
+    name: &XorName,
+) -> ConflictableTransactionResult<bool, E> {
+    let indexed = names.get(name.0)?;
+    let new_name = indexed.is_none();
+    let generation = indexed
+        .and_then(|generation: IVec| <[u8; 8]>::try_from(generation.as_ref()).ok())
+        .map_or(0, u64::from_be_bytes);
+    let _ = names.insert(&name.0, &(generation + 1).to_be_bytes())?;
+    Ok(new_name)
+}
+
+fn transaction_error(error: TransactionError<Infallible>) -> Error {
+    match error {
+        TransactionError::Storage(err) => Error::Kv(err),
+        TransactionError::Abort(never) => match never {},
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Backends the data stores persist their items with.
+//!
+//! Chunks and snapshots of Registers are stored as single items, while Registers and spentbooks
+//! are stored as logs, i.e. a set of entries which are only ever appended, each with an id unique
+//! within the log. The entries compacted out of the log of a Register are moved to its archive.
+
+mod fs;
+mod kv;
+
+pub(super) use self::fs::FsBackend;
+pub(super) use kv::KvBackend;
+
+use super::Result;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use xor_name::XorName;
+
+/// The kind of backend the node data is stored with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageBackendKind {
+    /// A file per item, within a tree of folders named after the prefix of the item's name.
+    #[default]
+    Fs,
+    /// An embedded key-value store.
+    Kv,
+}
+
+impl FromStr for StorageBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fs" => Ok(Self::Fs),
+            "kv" => Ok(Self::Kv),
+            other => Err(format!(
+                "Unknown storage backend '{other}', expected 'fs' or 'kv'"
+            )),
+        }
+    }
+}
+
+impl Display for StorageBackendKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Fs => write!(formatter, "fs"),
+            Self::Kv => write!(formatter, "kv"),
+        }
+    }
+}
+
+/// Max number of items, or logs, kept quarantined within each `Namespace`. As they are not
+/// counted in the space used, the oldest ones are evicted to keep them within this number.
+pub(super) const MAX_QUARANTINED: usize = 100;
+
+/// The kinds of data stored, each kept apart from the others by the backends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Namespace {
+    Chunks,
+    Registers,
+    RegisterSnapshots,
+    Spentbooks,
+}
+
+impl Namespace {
+    pub(super) const ALL: [Self; 4] = [
+        Self::Chunks,
+        Self::Registers,
+        Self::RegisterSnapshots,
+        Self::Spentbooks,
+    ];
+
+    // Name of the folder, or tree, the data is stored in
+    pub(super) fn dir_name(&self) -> &'static str {
+        match self {
+            Self::Chunks => "chunks",
+            Self::Registers => "register",
+            Self::RegisterSnapshots => "register_snapshot",
+            Self::Spentbooks => "spentbook",
+        }
+    }
+
+    // Name of the folder, or tree, the data quarantined is kept in
+    pub(super) fn quarantine_dir_name(&self) -> String {
+        format!("quarantine_{}", self.dir_name())
+    }
+
+    // Whether the data is stored as logs rather than single items
+    pub(super) fn is_log(&self) -> bool {
+        !matches!(self, Self::Chunks | Self::RegisterSnapshots)
+    }
+}
+
+/// Number of items, or log entries, and their total size in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct StoredSize {
+    pub(super) entries: usize,
+    pub(super) bytes: usize,
+}
+
+/// Number of items, or logs, stored within each `Namespace`, as recorded by a backend when
+/// they are written and removed, so that they can be counted without listing them.
+#[derive(Debug, Default)]
+pub(super) struct StoredCounts([AtomicUsize; Namespace::ALL.len()]);
+
+impl StoredCounts {
+    pub(super) fn get(&self, ns: Namespace) -> usize {
+        self.0[ns as usize].load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set(&self, ns: Namespace, count: usize) {
+        self.0[ns as usize].store(count, Ordering::Relaxed);
+    }
+
+    pub(super) fn increment(&self, ns: Namespace) {
+        let _ = self.0[ns as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn decrement(&self, ns: Namespace) {
+        let _ = self.0[ns as usize].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            Some(count.saturating_sub(1))
+        });
+    }
+}
+
+/// Persistence of the data stored by the node, by name, within each `Namespace`.
+///
+/// Writes must be persisted before returning, so that concurrent reads don't find
+/// incomplete items. Writing an item, or a log entry, which is already stored is a no-op.
+#[async_trait]
+pub(super) trait StorageBackend: Debug + Send + Sync {
+    /// Returns true if an item is stored under the given name.
+    async fn contains(&self, ns: Namespace, name: &XorName) -> Result<bool>;
+
+    /// Stores an item, unless one is already stored under the same name.
+    /// Returns true if the item was stored.
+    async fn put(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<bool>;
+
+    /// Stores an item, replacing the one stored under the same name, if any.
+    /// Returns the size of the item replaced.
+    async fn replace(&self, ns: Namespace, name: &XorName, value: &[u8]) -> Result<Option<usize>>;
+
+    /// Returns the item stored under the given name, if any.
+    async fn get(&self, ns: Namespace, name: &XorName) -> Result<Option<Vec<u8>>>;
+
+    /// Removes the item stored under the given name, returning its size if it was found.
+    async fn remove(&self, ns: Namespace, name: &XorName) -> Result<Option<usize>>;
+
+    /// Returns true if the log with the given name holds an entry with the given id.
+    async fn contains_entry(&self, ns: Namespace, name: &XorName, entry_id: &str) -> Result<bool>;
+
+    /// Appends an entry to the log with the given name, unless it already holds an
+    /// entry with the same id. Returns true if the entry was appended.
+    async fn append(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+        value: &[u8],
+    ) -> Result<bool>;
+
+    /// Removes the entry with the given id from the log with the given name,
+    /// returning its size if it was found.
+    async fn remove_entry(
+        &self,
+        ns: Namespace,
+        name: &XorName,
+        entry_id: &str,
+    ) -> Result<Option<usize>>;
+
+    /// Returns all the entries of the log with the given name, empty if there is no such log.
+    async fn read_log(&self, ns: Namespace, name: &XorName) -> Result<Vec<Vec<u8>>>;
+
+    /// Removes the log with the given name, returning the size of its entries if it was found.
+    async fn remove_log(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>>;
+
+    /// Moves the item, or log, stored under the given name out of the namespace, keeping it
+    /// aside for inspection, along with up to `MAX_QUARANTINED` others. Returns its size if it was found.
+    async fn quarantine(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>>;
+
+    /// Names of the items, or logs, kept quarantined.
+    async fn quarantined(&self, ns: Namespace) -> Result<Vec<XorName>>;
+
+    /// Size of the item, or log entries, stored under the given name, if any,
+    /// as recorded by the store, i.e. without reading the data.
+    async fn size(&self, ns: Namespace, name: &XorName) -> Result<Option<StoredSize>>;
+
+    /// Names of all the items, or logs, stored.
+    async fn names(&self, ns: Namespace) -> Result<Vec<XorName>>;
+
+    /// Number of items, or logs, stored, as recorded by the store, i.e. without listing them.
+    fn count(&self, ns: Namespace) -> usize;
+
+    /// Size of all the items, or log entries, stored.
+    fn stored_size(&self, ns: Namespace) -> StoredSize;
+}
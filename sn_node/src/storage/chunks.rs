@@ -6,7 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{list_files_in, prefix_tree_path, used_space::StorageLevel, Error, Result, UsedSpace};
+use super::{
+    backend::{Namespace, StorageBackend},
+    used_space::StorageLevel,
+    Error, Result, UsedSpace,
+};
 
 use sn_interface::{
     messaging::system::NodeQueryResponse,
@@ -14,81 +18,75 @@ use sn_interface::{
 };
 
 use bytes::Bytes;
-use hex::FromHex;
 use std::{
     fmt::{self, Display, Formatter},
-    io::{self, ErrorKind},
-    path::{Path, PathBuf},
-};
-use tokio::{
-    fs::{create_dir_all, metadata, read, remove_file, File},
-    io::AsyncWriteExt,
+    sync::Arc,
 };
 use tracing::info;
-use xor_name::XorName;
-
-const CHUNKS_STORE_DIR_NAME: &str = "chunks";
 
 /// Operations on data chunks.
 #[derive(Clone, Debug)]
 pub(super) struct ChunkStorage {
-    file_store_path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
     used_space: UsedSpace,
 }
 
 impl ChunkStorage {
-    /// Creates a new `ChunkStorage` at the specified root location
+    /// Creates a new `ChunkStorage` persisted with the given backend
     ///
-    /// If the location specified already contains a `ChunkStorage`, it is simply used
+    /// If the backend already contains chunks, they are simply used
     ///
-    /// Used space of the dir is tracked
-    pub(super) fn new(path: &Path, used_space: UsedSpace) -> Self {
+    /// Used space of the chunks is tracked
+    pub(super) fn new(backend: Arc<dyn StorageBackend>, used_space: UsedSpace) -> Self {
         Self {
-            file_store_path: path.join(CHUNKS_STORE_DIR_NAME),
+            backend,
             used_space,
         }
     }
 
-    pub(super) fn addrs(&self) -> Vec<ChunkAddress> {
-        list_files_in(&self.file_store_path)
-            .iter()
-            .filter_map(|filepath| Self::chunk_filepath_to_address(filepath).ok())
-            .collect()
+    pub(super) async fn addrs(&self) -> Vec<ChunkAddress> {
+        match self.backend.names(Namespace::Chunks).await {
+            Ok(names) => names.into_iter().map(ChunkAddress).collect(),
+            Err(err) => {
+                warn!("Could not list the chunks stored: {err}");
+                vec![]
+            }
+        }
     }
 
-    fn chunk_filepath_to_address(path: &Path) -> Result<ChunkAddress> {
-        let filename = path
-            .file_name()
-            .ok_or_else(|| Error::NoFilename(path.to_path_buf()))?
-            .to_str()
-            .ok_or_else(|| Error::InvalidFilename(path.to_path_buf()))?;
+    /// Number of chunks stored, as recorded by the backend.
+    #[cfg(any(test, feature = "metrics"))]
+    pub(super) fn count(&self) -> usize {
+        self.backend.count(Namespace::Chunks)
+    }
 
-        let xorname = XorName(<[u8; 32]>::from_hex(filename)?);
-        Ok(ChunkAddress(xorname))
+    /// Size of all the chunks stored, as accounted for in the used space.
+    pub(super) fn stored_data_size(&self) -> usize {
+        self.backend.stored_size(Namespace::Chunks).bytes
     }
 
-    fn chunk_addr_to_filepath(&self, addr: &ChunkAddress) -> Result<PathBuf> {
-        let xorname = *addr.name();
-        let path = prefix_tree_path(&self.file_store_path, xorname);
-        let filename = hex::encode(xorname);
-        Ok(path.join(filename))
+    /// Size of the chunk stored, as accounted for in the used space, if we hold it.
+    pub(super) async fn data_size(&self, address: &ChunkAddress) -> Result<Option<usize>> {
+        let size = self.backend.size(Namespace::Chunks, address.name()).await?;
+        Ok(size.map(|size| size.bytes))
     }
 
     pub(super) async fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         debug!("Removing chunk, {:?}", address);
-        let filepath = self.chunk_addr_to_filepath(address)?;
-        let meta = metadata(filepath.clone()).await?;
-        remove_file(filepath).await?;
-        self.used_space.decrease(meta.len() as usize);
+        let size = self
+            .backend
+            .remove(Namespace::Chunks, address.name())
+            .await?
+            .ok_or(Error::ChunkNotFound(*address.name()))?;
+        self.used_space.decrease(size);
         Ok(())
     }
 
     pub(super) async fn get_chunk(&self, address: &ChunkAddress) -> Result<Chunk> {
         trace!("Getting chunk {:?}", address);
 
-        let file_path = self.chunk_addr_to_filepath(address)?;
-        match read(file_path).await {
-            Ok(bytes) => {
+        match self.backend.get(Namespace::Chunks, address.name()).await? {
+            Some(bytes) => {
                 let chunk = Chunk::new(Bytes::from(bytes));
                 if chunk.address() != address {
                     // This can happen if the content read is empty, or incomplete,
@@ -99,10 +97,31 @@ impl ChunkStorage {
                     Ok(chunk)
                 }
             }
-            Err(io_error @ io::Error { .. }) if io_error.kind() == ErrorKind::NotFound => {
-                Err(Error::ChunkNotFound(*address.name()))
+            None => Err(Error::ChunkNotFound(*address.name())),
+        }
+    }
+
+    /// Returns false if the chunk stored doesn't match its address. A chunk not found is deemed valid.
+    pub(super) async fn verify(&self, address: &ChunkAddress) -> Result<bool> {
+        match self.backend.get(Namespace::Chunks, address.name()).await? {
+            Some(bytes) => Ok(Chunk::new(Bytes::from(bytes)).address() == address),
+            None => Ok(true),
+        }
+    }
+
+    /// Moves the chunk out of the store, returning false if not found.
+    pub(super) async fn quarantine(&self, address: &ChunkAddress) -> Result<bool> {
+        debug!("Quarantining chunk, {:?}", address);
+        match self
+            .backend
+            .quarantine(Namespace::Chunks, address.name())
+            .await?
+        {
+            Some(quarantined) => {
+                self.used_space.decrease(quarantined.bytes);
+                Ok(true)
             }
-            Err(other) => Err(other.into()),
+            None => Ok(false),
         }
     }
 
@@ -115,13 +134,16 @@ impl ChunkStorage {
         NodeQueryResponse::GetChunk(self.get_chunk(address).await.map_err(|error| error.into()))
     }
 
-    /// Store a chunk in the local disk store unless it is already there
+    /// Store a chunk in the local store unless it is already there
     #[instrument(skip_all)]
     pub(super) async fn store(&self, chunk: &Chunk) -> Result<StorageLevel> {
         let addr = chunk.address();
-        let filepath = self.chunk_addr_to_filepath(addr)?;
 
-        if filepath.exists() {
+        if self
+            .backend
+            .contains(Namespace::Chunks, addr.name())
+            .await?
+        {
             info!(
                 "{}: Chunk data already exists, not storing: {:?}",
                 self, addr
@@ -137,19 +159,17 @@ impl ChunkStorage {
             return Err(Error::NotEnoughSpace);
         }
 
-        // Store the data on disk
+        // Store the data
         trace!("{:?} {addr:?}", LogMarker::StoringChunk);
-        if let Some(dirs) = filepath.parent() {
-            create_dir_all(dirs).await?;
+        if !self
+            .backend
+            .put(Namespace::Chunks, addr.name(), chunk.value())
+            .await?
+        {
+            // it was stored concurrently
+            return Ok(StorageLevel::NoChange);
         }
 
-        let mut file = File::create(filepath).await?;
-
-        file.write_all(chunk.value()).await?;
-        // Let's sync up OS data to disk to reduce the chances of
-        // concurrent reading failing by reading an empty/incomplete file
-        file.sync_data().await?;
-
         let storage_level = self.used_space.increase(chunk.value().len());
         trace!("{:?} {addr:?}", LogMarker::StoredNewChunk);
 
@@ -166,6 +186,7 @@ impl Display for ChunkStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::backend::{FsBackend, KvBackend};
     use sn_interface::types::utils::random_bytes;
 
     use eyre::{eyre, Result};
@@ -175,7 +196,7 @@ mod tests {
 
     fn init_file_store() -> ChunkStorage {
         let root = tempdir().expect("Failed to create temporary directory for chunk disk store");
-        ChunkStorage::new(root.path(), UsedSpace::default())
+        ChunkStorage::new(Arc::new(FsBackend::new(root.path())), UsedSpace::default())
     }
 
     #[tokio::test]
@@ -223,12 +244,10 @@ mod tests {
         let address = chunk.address();
 
         // create chunk file but with empty content
-        let filepath = storage.chunk_addr_to_filepath(address)?;
-        if let Some(dirs) = filepath.parent() {
-            create_dir_all(dirs).await?;
-        }
-        let mut file = File::create(&filepath).await?;
-        file.write_all(b"").await?;
+        let _ = storage
+            .backend
+            .put(Namespace::Chunks, address.name(), b"")
+            .await?;
 
         // trying to read the chunk shall return ChunkNotFound error since
         // its content shouldn't match chunk address
@@ -245,6 +264,32 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_chunks_stored_in_kv_backend() -> Result<()> {
+        let root = tempdir()?;
+        let storage = ChunkStorage::new(
+            Arc::new(KvBackend::open(root.path())?),
+            UsedSpace::default(),
+        );
+
+        let chunk = Chunk::new(random_bytes(100));
+        let _ = storage.store(&chunk).await?;
+        // storing it again is a no-op
+        let _ = storage.store(&chunk).await?;
+        assert_eq!(storage.get_chunk(chunk.address()).await?, chunk);
+        assert_eq!(storage.addrs().await, vec![*chunk.address()]);
+        assert_eq!(storage.stored_data_size(), 100);
+
+        storage.remove_chunk(chunk.address()).await?;
+        assert!(storage.addrs().await.is_empty());
+        assert!(matches!(
+            storage.get_chunk(chunk.address()).await,
+            Err(Error::ChunkNotFound(_))
+        ));
+
+        Ok(())
+    }
+
     async fn write_and_read_chunks(chunks: &[Chunk], storage: ChunkStorage) {
         // write all chunks
         let mut tasks = Vec::new();
@@ -8,7 +8,7 @@
 
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
-    types::{ChunkAddress, DataAddress, PublicKey, RegisterAddress},
+    types::{ChunkAddress, DataAddress, PublicKey, RegisterAddress, SpentbookAddress},
 };
 
 use std::{io, path::PathBuf};
@@ -31,6 +31,30 @@ pub enum Error {
     /// Chunk not found.
     #[error("Chunk not found: {0:?}")]
     ChunkNotFound(XorName),
+    /// Spentbook not found in local storage.
+    #[error("Spentbook not found in local storage: {0:?}")]
+    SpentbookNotFound(SpentbookAddress),
+    /// The DBC was already logged as spent in a different transaction.
+    #[error("Double spend attempt on spentbook: {0:?}")]
+    DoubleSpendAttempt(SpentbookAddress),
+    /// Spent proof share not signed by the section key share it claims to be.
+    #[error("Invalid signature of spent proof share for spentbook: {0:?}")]
+    InvalidSpentProofShare(SpentbookAddress),
+    /// Spent proof share signed with a key set whose key is not in our section chain.
+    #[error("Spent proof share signed with unknown section key for spentbook: {0:?}")]
+    SpentProofShareUnknownSectionKey(SpentbookAddress),
+    /// A spent proof share stored could not be read back.
+    #[error("Corrupted spent proof share found in spentbook: {0:?}")]
+    CorruptedSpentbook(SpentbookAddress),
+    /// Snapshot of a Register not signed by the section key it claims to be.
+    #[error("Invalid signature of snapshot of Register: {0:?}")]
+    InvalidRegisterSnapshot(RegisterAddress),
+    /// Snapshot of a Register signed with a key which is not in our section chain.
+    #[error("Snapshot of Register signed with unknown section key: {0:?}")]
+    RegisterSnapshotUnknownSectionKey(RegisterAddress),
+    /// A spentbook stored as a Register could not be migrated to the spentbook store.
+    #[error("Spentbook stored as Register {0:?} could not be migrated: {1}")]
+    SpentbookRegisterMigration(RegisterAddress, String),
     /// Storage not supported for type of data address
     #[error("Storage not supported for type of data address: {0:?}")]
     UnsupportedDataType(DataAddress),
@@ -40,6 +64,9 @@ pub enum Error {
     /// I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
+    /// Key-value store error.
+    #[error("Key-value store error: {0}")]
+    Kv(#[from] sled::Error),
     /// Bincode error.
     #[error("Bincode error:: {0}")]
     Bincode(#[from] bincode::Error),
@@ -79,6 +106,10 @@ impl From<Error> for ErrorMsg {
             Error::ChunkNotFound(xorname) => {
                 ErrorMsg::DataNotFound(DataAddress::Bytes(ChunkAddress(xorname)))
             }
+            Error::SpentbookNotFound(address) => {
+                ErrorMsg::DataNotFound(DataAddress::Spentbook(address))
+            }
+            Error::DoubleSpendAttempt(address) => ErrorMsg::DoubleSpendAttempt(address),
             Error::NetworkData(error) => error.into(),
             other => ErrorMsg::InvalidOperation(format!("Failed to perform operation: {other:?}")),
         }
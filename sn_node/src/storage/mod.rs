@@ -6,37 +6,45 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod backend;
 mod chunks;
 mod errors;
 mod register_store;
 mod registers;
+mod scrub;
+mod spentbooks;
 mod used_space;
 
+pub use backend::StorageBackendKind;
+pub use scrub::ScrubStats;
 pub use used_space::UsedSpace;
 
 pub(crate) use errors::{Error, Result};
 pub(crate) use used_space::StorageLevel;
 
+use backend::{FsBackend, KvBackend, StorageBackend};
 use chunks::ChunkStorage;
 use registers::RegisterStorage;
+use spentbooks::{spentbook_address, SpentbookStorage};
 
 use sn_dbc::SpentProofShare;
 use sn_interface::{
     messaging::{
-        data::{DataQuery, Error as MessagingError, RegisterQuery, SpendQuery},
-        system::NodeQueryResponse,
+        data::{DataQuery, RegisterCmd, SpendQuery},
+        system::{NodeQueryResponse, SectionSigShare},
     },
+    network_knowledge::SectionsDAG,
     types::{
-        register::User, DataAddress, Keypair, PublicKey, RegisterAddress, ReplicatedData,
-        SPENTBOOK_TYPE_TAG,
+        register::{Register, User},
+        DataAddress, RegisterAddress, ReplicatedData, ReplicatedRegisterLog, ReplicatedSpentbook,
+        SpentbookAddress, SPENTBOOK_TYPE_TAG,
     },
 };
 
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
-use xor_name::XorName;
-
-const BIT_TREE_DEPTH: usize = 20;
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
 /// Operations on data stored to disk.
 /// As data the storage struct may be cloned throughoout the node
@@ -46,19 +54,66 @@ const BIT_TREE_DEPTH: usize = 20;
 pub struct DataStorage {
     chunks: ChunkStorage,
     registers: RegisterStorage,
+    spentbooks: SpentbookStorage,
     used_space: UsedSpace,
+    // addresses of the data yet to be verified in the current scrubbing pass
+    scrub_queue: Arc<tokio::sync::Mutex<Vec<DataAddress>>>,
+    scrub_stats: Arc<Mutex<ScrubStats>>,
 }
 
 impl DataStorage {
-    /// Set up a new `DataStorage` instance
+    /// Set up a new `DataStorage` instance, storing the data in files at the given path
+    ///
+    /// The space used by the data already stored at the given path is restored
+    /// into the `UsedSpace` tracker, which then keeps it persisted at that path.
     pub fn new(path: &Path, used_space: UsedSpace) -> Self {
+        Self::with_storage_backend(path, used_space, Arc::new(FsBackend::new(path)))
+    }
+
+    /// Set up a new `DataStorage` instance, storing the data at the given path
+    /// with the given kind of backend
+    pub fn with_backend(
+        path: &Path,
+        used_space: UsedSpace,
+        backend: StorageBackendKind,
+    ) -> Result<Self> {
+        info!("Using the {backend} storage backend at {}", path.display());
+        let backend: Arc<dyn StorageBackend> = match backend {
+            StorageBackendKind::Fs => Arc::new(FsBackend::new(path)),
+            StorageBackendKind::Kv => Arc::new(KvBackend::open(path)?),
+        };
+        Ok(Self::with_storage_backend(path, used_space, backend))
+    }
+
+    fn with_storage_backend(
+        path: &Path,
+        used_space: UsedSpace,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Self {
+        let chunks = ChunkStorage::new(backend.clone(), used_space.clone());
+        let registers = RegisterStorage::new(backend.clone(), used_space.clone());
+        let spentbooks = SpentbookStorage::new(backend, used_space.clone());
+
+        used_space.restore(path, || {
+            chunks.stored_data_size() + registers.stored_data_size() + spentbooks.stored_data_size()
+        });
+
         Self {
-            chunks: ChunkStorage::new(path, used_space.clone()),
-            registers: RegisterStorage::new(path, used_space.clone()),
+            chunks,
+            registers,
+            spentbooks,
             used_space,
+            scrub_queue: Arc::default(),
+            scrub_stats: Arc::default(),
         }
     }
 
+    /// Persists the space used by the data stored, for it to be restored without counting it
+    /// when the node restarts. To be called when the node shuts down cleanly.
+    pub(crate) fn persist_used_space(&self) {
+        self.used_space.persist();
+    }
+
     /// Returns the ratio of used storage space to min capacity.
     pub(crate) fn used_space_ratio(&self) -> f64 {
         self.used_space.ratio()
@@ -88,6 +143,7 @@ impl DataStorage {
             let _handle = tokio::task::spawn(async move {
                 let chunk_addr_to_remove = chunks
                     .addrs()
+                    .await
                     .into_iter()
                     .filter(|addr| !prefix.matches(addr.name()));
                 for addr in chunk_addr_to_remove {
@@ -110,33 +166,187 @@ impl DataStorage {
                     }
                 }
             });
+
+            let spentbooks = self.spentbooks.clone();
+            let _handle = tokio::task::spawn(async move {
+                let spentbook_addr_to_remove = spentbooks
+                    .addrs()
+                    .await
+                    .into_iter()
+                    .filter(|addr| !prefix.matches(addr.name()));
+                for addr in spentbook_addr_to_remove {
+                    if let Err(err) = spentbooks.remove_spentbook(&addr).await {
+                        warn!("Could not remove spentbook {addr:?} due to {err}.");
+                    }
+                }
+            });
         }
     }
 
+    /// Moves the spentbooks stored as Registers, as done before spentbooks had their own
+    /// data type, to the spentbook store. Returns the number of spentbooks migrated.
+    ///
+    /// A Register is only removed once all its entries are logged in the spentbook store,
+    /// those which can't be migrated are left untouched. The entries are verified as any
+    /// spent proof share, thus against the given chain of our section keys.
+    pub(crate) async fn migrate_register_spentbooks(&self, section_chain: &SectionsDAG) -> usize {
+        let mut migrated = 0;
+        for addr in self.registers.addrs().await {
+            if addr.tag != SPENTBOOK_TYPE_TAG {
+                continue;
+            }
+
+            let result = match self.registers.get_register_replica(&addr).await {
+                Ok(log) => self.migrate_register_spentbook(&log, section_chain).await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(_) => match self.registers.remove_register(&addr).await {
+                    Ok(()) => migrated += 1,
+                    Err(err) => {
+                        warn!("Could not remove migrated spentbook Register {addr:?}: {err}")
+                    }
+                },
+                Err(err) => warn!("Could not migrate spentbook Register {addr:?}: {err}"),
+            }
+        }
+
+        if migrated > 0 {
+            info!("Migrated {migrated} spentbooks stored as Registers to the spentbook store");
+        }
+        migrated
+    }
+
+    // Logs the spent proof shares found in the entries of a spentbook stored as a Register
+    async fn migrate_register_spentbook(
+        &self,
+        log: &ReplicatedRegisterLog,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        let address = SpentbookAddress::new(*log.address.name());
+        let proof_shares = log
+            .op_log
+            .iter()
+            .filter_map(|cmd| match cmd {
+                RegisterCmd::Edit(edit) => Some(&edit.op.edit.crdt_op.value),
+                _ => None,
+            })
+            .map(|entry| register_entry_share(&log.address, entry))
+            .collect::<Result<Vec<_>>>()?;
+
+        if proof_shares.is_empty() {
+            return Ok(StorageLevel::NoChange);
+        }
+
+        let storage_level = self
+            .spentbooks
+            .update(
+                &ReplicatedSpentbook {
+                    address,
+                    proof_shares: proof_shares.clone(),
+                },
+                section_chain,
+            )
+            .await?;
+
+        // Shares which are invalid, or a double spend, are discarded when logged
+        let logged = self.spentbooks.get_spentbook(&address).await?.proof_shares;
+        if let Some(share) = proof_shares.iter().find(|share| !logged.contains(share)) {
+            return Err(Error::SpentbookRegisterMigration(
+                log.address,
+                format!("entry could not be logged in the spentbook: {share:?}"),
+            ));
+        }
+
+        Ok(storage_level)
+    }
+
+    /// The logs of the Registers which grew beyond the number of cmds a snapshot of them is to
+    /// be built from, since last taken, for our section to sign a snapshot of each of them.
+    pub(crate) async fn register_logs_to_snapshot(&self) -> Vec<ReplicatedRegisterLog> {
+        self.registers
+            .logs_to_snapshot()
+            .await
+            .into_iter()
+            // spentbooks stored as Registers are migrated instead
+            .filter(|log| log.address.tag != SPENTBOOK_TYPE_TAG)
+            .collect()
+    }
+
+    /// Builds the snapshot of a Register from its log, verifying its snapshots against the given
+    /// section chain, and each of its cmds. Returns it along with the bytes it's signed over.
+    pub(crate) fn build_register_snapshot(
+        &self,
+        log: &ReplicatedRegisterLog,
+        section_chain: &SectionsDAG,
+    ) -> Result<(Register, Vec<u8>)> {
+        let snapshot = self.registers.build_snapshot(log, section_chain)?;
+        let signed_bytes = register_store::serialise_snapshot(&snapshot)?;
+        Ok((snapshot, signed_bytes))
+    }
+
+    /// Aggregates the share of an Elder's signature over the snapshot of a Register, storing
+    /// the snapshot once signed by a key of the given section chain.
+    pub(crate) async fn add_register_snapshot_sig_share(
+        &self,
+        snapshot: Register,
+        sig_share: SectionSigShare,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        self.registers
+            .add_snapshot_sig_share(snapshot, sig_share, section_chain)
+            .await
+    }
+
     /// Store data in the local store
-    #[instrument(skip(self))]
+    ///
+    /// Spent proof shares are only logged if signed by a key set of the given section chain,
+    /// as are the snapshots of Registers.
+    #[instrument(skip(self, section_chain))]
     pub async fn store(
         &self,
         data: &ReplicatedData,
-        section_pk: PublicKey,
-        node_keypair: Keypair,
+        section_chain: &SectionsDAG,
     ) -> Result<StorageLevel> {
         debug!("Replicating {data:?}");
         match data {
             ReplicatedData::Chunk(chunk) => self.chunks.store(chunk).await,
+            // spentbooks are still replicated as Registers by nodes not upgraded yet
+            ReplicatedData::RegisterLog(data) | ReplicatedData::SpentbookLog(data)
+                if data.address.tag == SPENTBOOK_TYPE_TAG =>
+            {
+                info!(
+                    "Migrating spentbook replicated as Register: {:?}",
+                    data.address
+                );
+                self.migrate_register_spentbook(data, section_chain).await
+            }
             ReplicatedData::RegisterLog(data) => {
                 info!("Updating register: {:?}", data.address);
-                self.registers.update(data).await
+                self.registers.update(data, section_chain).await
             }
             ReplicatedData::RegisterWrite(cmd) => self.registers.write(cmd).await,
-            ReplicatedData::SpentbookWrite(cmd) => {
-                // FIMXE: this is temporary logic to have spentbooks as Registers.
-                // Spentbooks shall always exist, and the section nodes shall create them by default.
-                self.registers
-                    .write_spentbook_register(cmd, section_pk, node_keypair)
-                    .await
+            ReplicatedData::SpentbookWrite(RegisterCmd::Edit(edit))
+                if edit.op.address.tag == SPENTBOOK_TYPE_TAG =>
+            {
+                let share = register_entry_share(&edit.op.address, &edit.op.edit.crdt_op.value)?;
+                self.spentbooks.write(&share, section_chain).await
+            }
+            ReplicatedData::SpentbookWrite(cmd) => Err(Error::SpentbookRegisterMigration(
+                cmd.dst_address(),
+                "not a write of a spent proof share".to_string(),
+            )),
+            ReplicatedData::SpentbookLog(data) => Err(Error::SpentbookRegisterMigration(
+                data.address,
+                "not a spentbook Register".to_string(),
+            )),
+            ReplicatedData::SpentProofShare(share) => {
+                self.spentbooks.write(share, section_chain).await
+            }
+            ReplicatedData::Spentbook(data) => {
+                info!("Updating spentbook: {:?}", data.address);
+                self.spentbooks.update(data, section_chain).await
             }
-            ReplicatedData::SpentbookLog(data) => self.registers.update(data).await,
         }
     }
 
@@ -146,45 +356,7 @@ impl DataStorage {
             DataQuery::GetChunk(addr) => self.chunks.get(addr).await,
             DataQuery::Register(read) => self.registers.read(read, requester).await,
             DataQuery::Spentbook(SpendQuery::GetSpentProofShares(addr)) => {
-                // TODO: this is temporary till spentbook native data type is implemented,
-                // we read from the Register where we store the spentbook data
-                let reg_addr = RegisterAddress::new(*addr.name(), SPENTBOOK_TYPE_TAG);
-
-                match self
-                    .registers
-                    .read(&RegisterQuery::Get(reg_addr), requester)
-                    .await
-                {
-                    NodeQueryResponse::GetRegister(Err(MessagingError::DataNotFound(_))) => {
-                        NodeQueryResponse::GetSpentProofShares(Ok(Vec::new()))
-                    }
-                    NodeQueryResponse::GetRegister(result) => {
-                        let proof_shares_result = result.map(|reg| {
-                            let mut proof_shares = Vec::new();
-                            let entries = reg.read();
-                            for (_, entry) in entries {
-                                // Deserialise spent proof share from the entry
-                                let spent_proof_share: SpentProofShare = match rmp_serde::from_slice(&entry) {
-                                    Ok(proof) => proof,
-                                    Err(err) => {
-                                        warn!("Ignoring entry found in Spentbook since it cannot be deserialised as a valid SpentProofShare: {:?}", err);
-                                        continue;
-                                    }
-                                };
-
-                                proof_shares.push(spent_proof_share);
-                            }
-                            proof_shares
-                        });
-
-                        NodeQueryResponse::GetSpentProofShares(proof_shares_result)
-                    }
-                    other => {
-                        // TODO: this is temporary till spentbook native data type is implemented,
-                        // for now we just return the response even that it's a Register query response.
-                        other
-                    }
-                }
+                self.spentbooks.get(addr).await
             }
             // this should be unreachable
             DataQuery::Spentbook(SpendQuery::GetFees(dbc_id)) => {
@@ -192,6 +364,14 @@ impl DataStorage {
                     DataAddress::Spentbook(SpendQuery::GetFees(*dbc_id).dst_address()),
                 )))
             }
+            // this should be unreachable
+            DataQuery::Spentbook(query @ SpendQuery::GetStoreCost { .. }) => {
+                NodeQueryResponse::GetStoreCost(Err(
+                    sn_interface::messaging::data::Error::DataNotFound(DataAddress::Spentbook(
+                        query.dst_address(),
+                    )),
+                ))
+            }
         }
     }
 
@@ -211,26 +391,50 @@ impl DataStorage {
                 .get_register_replica(addr)
                 .await
                 .map(ReplicatedData::RegisterLog),
-            DataAddress::Spentbook(addr) => {
-                let reg_addr = RegisterAddress::new(*addr.name(), SPENTBOOK_TYPE_TAG);
-                self.registers
-                    .get_register_replica(&reg_addr)
-                    .await
-                    .map(ReplicatedData::SpentbookLog)
-            }
+            DataAddress::Spentbook(addr) => self
+                .spentbooks
+                .get_spentbook(addr)
+                .await
+                .map(ReplicatedData::Spentbook),
             other => Err(Error::UnsupportedDataType(*other)),
         }
     }
 
+    /// Number of chunks, Registers and spentbooks stored, respectively, as recorded by the
+    /// store, i.e. without listing them.
+    #[cfg(any(test, feature = "metrics"))]
+    pub(crate) fn data_counts(&self) -> (usize, usize, usize) {
+        (
+            self.chunks.count(),
+            self.registers.count(),
+            self.spentbooks.count(),
+        )
+    }
+
+    /// Size in bytes of the data stored at the given address, as accounted for in the
+    /// used space, or `None` if we don't hold it. The data itself is not read.
+    pub(crate) async fn data_size(&self, address: &DataAddress) -> Option<usize> {
+        let size = match address {
+            DataAddress::Bytes(addr) => self.chunks.data_size(addr).await,
+            DataAddress::Register(addr) => self.registers.data_size(addr).await,
+            DataAddress::Spentbook(addr) => self.spentbooks.data_size(addr).await,
+            other => Err(Error::UnsupportedDataType(*other)),
+        };
+        match size {
+            Ok(size) => size,
+            Err(error) => {
+                warn!("Failed to get the size of the data at {address:?}: {error}");
+                None
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) async fn remove(&mut self, address: &DataAddress) -> Result<()> {
         match address {
             DataAddress::Bytes(addr) => self.chunks.remove_chunk(addr).await,
             DataAddress::Register(addr) => self.registers.remove_register(addr).await,
-            DataAddress::Spentbook(addr) => {
-                let reg_addr = RegisterAddress::new(*addr.name(), SPENTBOOK_TYPE_TAG);
-                self.registers.remove_register(&reg_addr).await
-            }
+            DataAddress::Spentbook(addr) => self.spentbooks.remove_spentbook(addr).await,
             other => Err(Error::UnsupportedDataType(*other)),
         }
     }
@@ -240,6 +444,7 @@ impl DataStorage {
         // TODO: Parallelize this below loops
         self.chunks
             .addrs()
+            .await
             .into_iter()
             .map(DataAddress::Bytes)
             .chain(
@@ -249,65 +454,72 @@ impl DataStorage {
                     .into_iter()
                     .map(DataAddress::Register),
             )
+            .chain(
+                self.spentbooks
+                    .addrs()
+                    .await
+                    .into_iter()
+                    .map(DataAddress::Spentbook),
+            )
             .collect()
     }
 }
 
-// Helper that returns the prefix tree path of depth BIT_TREE_DEPTH for a given xorname
-// Example:
-// - with a xorname with starting bits `010001110110....`
-// - and a BIT_TREE_DEPTH of `6`
-// returns the path `ROOT_PATH/0/1/0/0/0/1`
-fn prefix_tree_path(root: &Path, xorname: XorName) -> PathBuf {
-    let bin = format!("{xorname:b}");
-    let prefix_dir_path: PathBuf = bin.chars().take(BIT_TREE_DEPTH).map(String::from).collect();
-    root.join(prefix_dir_path)
-}
-
-fn list_files_in(path: &Path) -> Vec<PathBuf> {
-    if !path.exists() {
-        return vec![];
+// Spent proof share written as an entry of a spentbook stored as a Register
+fn register_entry_share(address: &RegisterAddress, entry: &[u8]) -> Result<SpentProofShare> {
+    let share: SpentProofShare = rmp_serde::from_slice(entry)
+        .map_err(|err| Error::SpentbookRegisterMigration(*address, err.to_string()))?;
+    if spentbook_address(&share).name() != address.name() {
+        return Err(Error::SpentbookRegisterMigration(
+            *address,
+            format!("entry found for another spentbook: {share:?}"),
+        ));
     }
-
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| match e {
-            Ok(direntry) => Some(direntry),
-            Err(err) => {
-                warn!("Store: failed to process filesystem entry: {}", err);
-                None
-            }
-        })
-        .filter(|entry| entry.file_type().is_file())
-        .map(|entry| entry.path().to_path_buf())
-        .collect()
+    Ok(share)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{DataStorage, Error, UsedSpace};
+    use super::{
+        registers::create_reg_w_policy,
+        spentbooks::{spentbook_address, tests::spent_proof_shares},
+        DataStorage, Error, StorageBackendKind, UsedSpace,
+    };
+    use sn_dbc::{Hash, SpentProofShare};
     use sn_interface::{
         init_logger,
         messaging::{
-            data::{CreateRegister, DataQuery, SignedRegisterCreate},
+            data::{
+                CreateRegister, DataQuery, EditRegister, SignedRegisterCreate, SignedRegisterEdit,
+                SpendQuery,
+            },
             system::NodeQueryResponse,
+            ClientAuth,
         },
+        network_knowledge::SectionsDAG,
         test_utils::TestKeys,
         types::{
-            register::{Policy, User},
+            register::{Permissions, Policy, Register, User},
             utils::random_bytes,
-            Chunk, ChunkAddress, DataAddress, Keypair, PublicKey, RegisterCmd, ReplicatedData,
-            SectionSig,
+            Chunk, ChunkAddress, DataAddress, Keypair, RegisterCmd, ReplicatedData,
+            ReplicatedRegisterLog, ReplicatedSpentbook, SectionSig, SpentbookAddress,
+            SPENTBOOK_TYPE_TAG,
         },
     };
 
-    use eyre::Result;
+    use eyre::{bail, Result};
     use proptest::{
         collection::SizeRange,
         prelude::{any, prop_oneof, proptest},
         strategy::Strategy,
     };
-    use std::{cmp::max, collections::BTreeMap, thread, time::Duration};
+    use std::{
+        cmp::max,
+        collections::{BTreeMap, BTreeSet},
+        mem::size_of,
+        thread,
+        time::Duration,
+    };
     use tempfile::tempdir;
     use tokio::runtime::Runtime;
     use xor_name::XorName;
@@ -332,11 +544,8 @@ mod tests {
         let chunk = Chunk::new(bytes);
         let replicated_data = ReplicatedData::Chunk(chunk.clone());
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_data, pk, keypair).await?;
+        let _ = storage.store(&replicated_data, &section_chain()).await?;
 
         // Test local fetch
         let fetched_data = storage
@@ -367,6 +576,38 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn data_size_is_that_accounted_for_in_the_used_space() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let used_space = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::new(tmp_dir.path(), used_space.clone());
+
+        let chunk = ReplicatedData::Chunk(Chunk::new(random_bytes(1024)));
+        assert_eq!(storage.data_size(&chunk.address()).await, None);
+        let keypair = Keypair::new_ed25519();
+        let policy = Policy {
+            owner: User::Key(keypair.public_key()),
+            permissions: BTreeMap::new(),
+        };
+        let cmd = create_reg_w_policy(xor_name::rand::random(), 15000, policy, &keypair)?;
+        let register = ReplicatedData::RegisterWrite(cmd);
+        assert_eq!(storage.data_size(&register.address()).await, None);
+
+        let _ = storage.store(&chunk, &section_chain()).await?;
+        let _ = storage.store(&register, &section_chain()).await?;
+
+        let chunk_size = storage.data_size(&chunk.address()).await;
+        assert_eq!(chunk_size, Some(1024));
+        let register_size = storage.data_size(&register.address()).await;
+        assert_eq!(register_size, Some(size_of::<RegisterCmd>()));
+
+        let counted = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let _ = counted.increase(1024 + size_of::<RegisterCmd>());
+        assert_eq!(used_space.ratio(), counted.ratio());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn data_storage_chunk_keys_returned() -> Result<(), Error> {
         init_logger();
@@ -384,11 +625,8 @@ mod tests {
         let chunk = Chunk::new(bytes);
         let replicated_chunk = ReplicatedData::Chunk(chunk.clone());
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_chunk, pk, keypair).await?;
+        let _ = storage.store(&replicated_chunk, &section_chain()).await?;
 
         let keys = storage.data_addrs().await;
 
@@ -401,6 +639,11 @@ mod tests {
         Ok(())
     }
 
+    // no spentbooks are stored, thus the section keys don't matter
+    fn section_chain() -> SectionsDAG {
+        SectionsDAG::new(bls::SecretKey::random().public_key())
+    }
+
     fn section_sig() -> SectionSig {
         let sk = bls::SecretKey::random();
         TestKeys::get_section_sig_bytes(&sk, "hello".as_bytes())
@@ -439,7 +682,7 @@ mod tests {
         let cmd = RegisterCmd::Create {
             cmd: SignedRegisterCreate {
                 op,
-                auth: sn_interface::messaging::ClientAuth {
+                auth: ClientAuth {
                     public_key: keypair.public_key(),
                     signature,
                 },
@@ -449,11 +692,10 @@ mod tests {
 
         let replicated_register = ReplicatedData::RegisterWrite(cmd);
 
-        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let keypair = Keypair::new_ed25519();
-
         // Store the chunk
-        let _ = storage.store(&replicated_register, pk, keypair).await?;
+        let _ = storage
+            .store(&replicated_register, &section_chain())
+            .await?;
 
         let keys = storage.data_addrs().await;
 
@@ -466,6 +708,315 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn used_space_is_restored_at_startup() -> Result<()> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::new(path, used_space.clone());
+
+        let chunk = Chunk::new(random_bytes(100 * 1024));
+        let _ = storage
+            .store(&ReplicatedData::Chunk(chunk), &section_chain())
+            .await?;
+        let keypair = Keypair::new_ed25519();
+        let policy = Policy {
+            owner: User::Key(keypair.public_key()),
+            permissions: BTreeMap::new(),
+        };
+        let cmd = create_reg_w_policy(xor_name::rand::random(), 15000, policy, &keypair)?;
+        let _ = storage
+            .store(&ReplicatedData::RegisterWrite(cmd), &section_chain())
+            .await?;
+        assert!(used_space.ratio() > 0.0);
+        let summary_path = path.join("used_space");
+        assert!(!summary_path.exists());
+
+        // the used space is loaded from the summary persisted on a clean shutdown...
+        storage.persist_used_space();
+        assert!(summary_path.exists());
+        let restored = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::new(path, restored.clone());
+        assert_eq!(restored.ratio(), used_space.ratio());
+        // ...which is not trusted anymore once the node is running
+        assert!(!summary_path.exists());
+
+        // ...or else counted from the data stored, as after an unclean shutdown
+        drop(storage);
+        let counted = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::new(path, counted.clone());
+        assert_eq!(counted.ratio(), used_space.ratio());
+
+        // the summary persisted is removed as soon as the used space changes
+        storage.persist_used_space();
+        let chunk = Chunk::new(random_bytes(100 * 1024));
+        let _ = storage
+            .store(&ReplicatedData::Chunk(chunk), &section_chain())
+            .await?;
+        assert!(!summary_path.exists());
+        let restored = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let _storage = DataStorage::new(path, restored.clone());
+        assert_eq!(restored.ratio(), counted.ratio());
+        assert!(restored.ratio() > used_space.ratio());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_stored_with_kv_backend_is_found_after_restart() -> Result<()> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::with_backend(path, used_space.clone(), StorageBackendKind::Kv)?;
+
+        let chunk = Chunk::new(random_bytes(100 * 1024));
+        let _ = storage
+            .store(&ReplicatedData::Chunk(chunk.clone()), &section_chain())
+            .await?;
+        let keypair = Keypair::new_ed25519();
+        let policy = Policy {
+            owner: User::Key(keypair.public_key()),
+            permissions: BTreeMap::new(),
+        };
+        let cmd = create_reg_w_policy(xor_name::rand::random(), 15000, policy, &keypair)?;
+        let _ = storage
+            .store(
+                &ReplicatedData::RegisterWrite(cmd.clone()),
+                &section_chain(),
+            )
+            .await?;
+        drop(storage);
+
+        // the used space is counted from the data stored in the key-value store
+        let counted = UsedSpace::new(1024 * 1024, 2 * 1024 * 1024);
+        let storage = DataStorage::with_backend(path, counted.clone(), StorageBackendKind::Kv)?;
+        assert_eq!(counted.ratio(), used_space.ratio());
+
+        let addrs: BTreeSet<_> = storage.data_addrs().await.into_iter().collect();
+        let expected = BTreeSet::from([
+            DataAddress::Bytes(*chunk.address()),
+            DataAddress::Register(cmd.dst_address()),
+        ]);
+        assert_eq!(addrs, expected);
+        assert_eq!(
+            storage
+                .get_from_local_store(&DataAddress::Bytes(*chunk.address()))
+                .await?,
+            ReplicatedData::Chunk(chunk)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_counts_are_recorded_as_data_is_stored_and_removed() -> Result<()> {
+        init_logger();
+        for backend in [StorageBackendKind::Fs, StorageBackendKind::Kv] {
+            let tmp_dir = tempdir()?;
+            let path = tmp_dir.path();
+            let mut storage = DataStorage::with_backend(path, UsedSpace::default(), backend)?;
+            assert_eq!(storage.data_counts(), (0, 0, 0));
+
+            let chunk = Chunk::new(random_bytes(100));
+            for _ in 0..2 {
+                let _ = storage
+                    .store(&ReplicatedData::Chunk(chunk.clone()), &section_chain())
+                    .await?;
+            }
+            let keypair = Keypair::new_ed25519();
+            let policy = Policy {
+                owner: User::Key(keypair.public_key()),
+                permissions: BTreeMap::new(),
+            };
+            let cmd = create_reg_w_policy(xor_name::rand::random(), 15000, policy, &keypair)?;
+            let _ = storage
+                .store(&ReplicatedData::RegisterWrite(cmd), &section_chain())
+                .await?;
+            assert_eq!(storage.data_counts(), (1, 1, 0));
+
+            storage
+                .remove(&DataAddress::Bytes(*chunk.address()))
+                .await?;
+            assert_eq!(storage.data_counts(), (0, 1, 0));
+
+            // the counts are restored from the data stored
+            drop(storage);
+            let storage = DataStorage::with_backend(path, UsedSpace::default(), backend)?;
+            assert_eq!(storage.data_counts(), (0, 1, 0));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spentbooks_stored_as_registers_are_migrated() -> Result<()> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let storage = DataStorage::new(tmp_dir.path(), UsedSpace::default());
+
+        // a spentbook stored as a Register, as done before spentbooks had their own data type
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let section_chain = SectionsDAG::new(sk_set.public_keys().public_key());
+        let dbc_id = bls::SecretKey::random().public_key();
+        let shares = spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 2);
+        let address = spentbook_address(&shares[0]);
+        let log = register_spentbook_log(*address.name(), &shares)?;
+        let _ = storage.registers.update(&log, &section_chain).await?;
+        assert_eq!(
+            storage
+                .query(&spent_proof_shares_query(address), User::Anyone)
+                .await,
+            NodeQueryResponse::GetSpentProofShares(Ok(vec![]))
+        );
+
+        assert_eq!(storage.migrate_register_spentbooks(&section_chain).await, 1);
+        assert!(storage.registers.addrs().await.is_empty());
+        match storage
+            .query(&spent_proof_shares_query(address), User::Anyone)
+            .await
+        {
+            NodeQueryResponse::GetSpentProofShares(Ok(migrated)) => {
+                assert_eq!(migrated.len(), shares.len());
+                assert!(shares.iter().all(|share| migrated.contains(share)));
+            }
+            other => bail!("Unexpected response: {other:?}"),
+        }
+        assert_eq!(
+            storage.data_addrs().await,
+            vec![DataAddress::Spentbook(address)]
+        );
+
+        // spentbooks replicated as Registers, by nodes not upgraded yet, are migrated as well
+        let legacy_variants: [fn(ReplicatedRegisterLog) -> Vec<ReplicatedData>; 3] = [
+            |log| vec![ReplicatedData::RegisterLog(log)],
+            |log| vec![ReplicatedData::SpentbookLog(log)],
+            |log| {
+                log.op_log
+                    .into_iter()
+                    .filter(|cmd| matches!(cmd, RegisterCmd::Edit(_)))
+                    .map(ReplicatedData::SpentbookWrite)
+                    .collect()
+            },
+        ];
+        for to_replicated_data in legacy_variants {
+            let other_shares = spent_proof_shares(
+                bls::SecretKey::random().public_key(),
+                Hash::hash(b"other tx"),
+                &sk_set,
+                1,
+            );
+            let other_address = spentbook_address(&other_shares[0]);
+            let log = register_spentbook_log(*other_address.name(), &other_shares)?;
+            for data in to_replicated_data(log) {
+                let _ = storage.store(&data, &section_chain).await?;
+            }
+            assert!(storage.registers.addrs().await.is_empty());
+            assert_eq!(
+                storage
+                    .get_from_local_store(&DataAddress::Spentbook(other_address))
+                    .await?,
+                ReplicatedData::Spentbook(ReplicatedSpentbook {
+                    address: other_address,
+                    proof_shares: other_shares,
+                })
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spent_proof_shares_of_unknown_sections_are_rejected() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let storage = DataStorage::new(tmp_dir.path(), UsedSpace::default());
+
+        // validly signed, but by a key set which isn't that of our section
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let dbc_id = bls::SecretKey::random().public_key();
+        let shares = spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 2);
+        let address = spentbook_address(&shares[0]);
+
+        match storage
+            .store(
+                &ReplicatedData::SpentProofShare(shares[0].clone()),
+                &section_chain(),
+            )
+            .await
+        {
+            Err(Error::SpentProofShareUnknownSectionKey(addr)) => assert_eq!(addr, address),
+            other => bail!("Share of an unknown section was not rejected: {other:?}"),
+        }
+
+        // replicated ones are discarded
+        let _ = storage
+            .store(
+                &ReplicatedData::Spentbook(ReplicatedSpentbook {
+                    address,
+                    proof_shares: shares,
+                }),
+                &section_chain(),
+            )
+            .await?;
+        assert_eq!(
+            storage
+                .query(&spent_proof_shares_query(address), User::Anyone)
+                .await,
+            NodeQueryResponse::GetSpentProofShares(Ok(vec![]))
+        );
+
+        Ok(())
+    }
+
+    fn spent_proof_shares_query(address: SpentbookAddress) -> DataQuery {
+        DataQuery::Spentbook(SpendQuery::GetSpentProofShares(address))
+    }
+
+    // Log of a Register holding the shares as entries, the way spentbooks used to be stored
+    fn register_spentbook_log(
+        name: XorName,
+        shares: &[SpentProofShare],
+    ) -> Result<ReplicatedRegisterLog> {
+        let keypair = Keypair::new_ed25519();
+        let owner = User::Key(keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: BTreeMap::from([(User::Anyone, Permissions::new(true))]),
+        };
+        let mut op_log = vec![create_reg_w_policy(
+            name,
+            SPENTBOOK_TYPE_TAG,
+            policy.clone(),
+            &keypair,
+        )?];
+
+        let mut register = Register::new(owner, name, SPENTBOOK_TYPE_TAG, policy);
+        for share in shares {
+            let entry = rmp_serde::to_vec(share)?;
+            let (_, edit) = register.write(entry, BTreeSet::new())?;
+            let op = EditRegister {
+                address: *register.address(),
+                edit,
+                policy_version: register.policy_version(),
+            };
+            let signature = keypair.sign(&bincode::serialize(&op)?);
+            op_log.push(RegisterCmd::Edit(SignedRegisterEdit {
+                op,
+                auth: ClientAuth {
+                    public_key: keypair.public_key(),
+                    signature,
+                },
+            }));
+        }
+
+        Ok(ReplicatedRegisterLog {
+            address: *register.address(),
+            op_log,
+            snapshots: vec![],
+        })
+    }
+
     // Model-based testing where random sets of Operations are performed on the Storage module and
     // a hashmap. The behaviour of both the models should be identical.
     proptest! {
@@ -511,8 +1062,6 @@ mod tests {
         let used_space = UsedSpace::default();
         let runtime = Runtime::new()?;
         let mut storage = DataStorage::new(path, used_space);
-        let owner_pk = PublicKey::Bls(bls::SecretKey::random().public_key());
-        let owner_keypair = Keypair::new_ed25519();
         for op in ops {
             match op {
                 Op::Store(flag, chunk_size) => {
@@ -536,7 +1085,7 @@ mod tests {
                         }
                     };
                     runtime.block_on(async {
-                        match storage.store(&data, owner_pk, owner_keypair.clone()).await {
+                        match storage.store(&data, &section_chain()).await {
                             Ok(_) => {
                                 // do nothing
                                 Ok(())
@@ -6,28 +6,31 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{list_files_in, prefix_tree_path, Error, Result, StorageLevel};
+use super::{
+    backend::{Namespace, StorageBackend},
+    Error, Result, StorageLevel,
+};
 
 use crate::UsedSpace;
 
 use sn_interface::{
-    messaging::data::SignedRegisterCreate,
+    messaging::{
+        data::{EditRegister, SignedRegisterCreate, SignedRegisterEdit, SignedRegisterPolicyEdit},
+        system::SectionSigned,
+        ClientAuth, SectionSig, VerifyAuthority,
+    },
     types::{
-        register::{EntryHash, Register},
+        register::{Entry, EntryHash, Register, RegisterOp},
         utils::{deserialise, serialise},
-        RegisterAddress, RegisterCmd,
+        Error as DataError, RegisterAddress, RegisterCmd,
     },
 };
 
 use bincode::serialize;
-use std::{
-    collections::{btree_map::Entry, BTreeMap},
-    mem::size_of,
-    path::{Path, PathBuf},
-};
+use serde::Deserialize;
+use std::{mem::size_of, sync::Arc};
 use tiny_keccak::{Hasher, Sha3};
-use tokio::fs::{create_dir_all, metadata, read, remove_file, File};
-use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 use xor_name::XorName;
 
 // Deterministic Id for a register Cmd, takes into account the underlying cmd, and all sigs
@@ -38,99 +41,251 @@ pub(super) type RegisterLog = Vec<RegisterCmd>;
 #[derive(Clone, Debug)]
 pub(super) struct StoredRegister {
     pub(super) state: Option<Register>,
+    // the snapshots signed by our section, the cmds they were built from not being logged
+    pub(super) snapshots: Vec<SectionSigned<Register>>,
+    // the cmds logged which no snapshot was built from
     pub(super) op_log: RegisterLog,
-    pub(super) op_log_path: PathBuf,
+    pub(super) reg_id: XorName,
 }
 
-/// A disk store for Registers
+impl StoredRegister {
+    // Whether the cmd was applied to any of the snapshots, hence it no longer has to be logged.
+    pub(super) fn in_snapshots(&self, cmd: &RegisterCmd) -> bool {
+        self.snapshots
+            .iter()
+            .any(|snapshot| snapshot_includes(snapshot, cmd))
+    }
+}
+
+/// A store for Registers, keeping a log of RegisterCmds for each of them.
+///
+/// Snapshots of a Register, signed by our section, are stored along with its log. The cmds
+/// a snapshot was built from are removed from the log, thus only the cmds logged since have
+/// to be applied when loading the Register, and replicated along with the snapshots.
 #[derive(Clone, Debug)]
 pub(super) struct RegisterStore {
-    file_store_path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
     used_space: UsedSpace,
+    // the snapshots are read and replaced as a whole, their updates are not to be interleaved
+    snapshot_lock: Arc<Mutex<()>>,
 }
 
 impl RegisterStore {
-    /// Creates a new `RegisterStore` at the specified root location
+    /// Creates a new `RegisterStore` persisted with the given backend
     ///
-    /// If the location specified already contains a `RegisterStore`, it is simply used
+    /// If the backend already contains Registers, they are simply used
     ///
-    /// Used space of the dir is tracked
-    pub(super) fn new(file_store_path: PathBuf, used_space: UsedSpace) -> Self {
+    /// Used space of the Registers is tracked
+    pub(super) fn new(backend: Arc<dyn StorageBackend>, used_space: UsedSpace) -> Self {
         Self {
-            file_store_path,
+            backend,
             used_space,
+            snapshot_lock: Arc::default(),
         }
     }
 
-    pub(super) fn address_to_filepath(&self, addr: &RegisterAddress) -> Result<PathBuf> {
-        // this is a unique identifier of the Register,
-        // since it encodes both the xorname and tag.
-        let reg_id = XorName::from_content(&serialize(addr)?);
-        let path = prefix_tree_path(&self.file_store_path, reg_id);
-
-        // we need to append a folder for the file specifically so bit depth is an issue when low.
-        // we use hex to get full id, not just first bytes
-        Ok(path.join(hex::encode(reg_id)))
+    // This is a unique identifier of the Register, the name its log is stored under,
+    // since it encodes both the xorname and tag.
+    pub(super) fn reg_id(addr: &RegisterAddress) -> Result<XorName> {
+        Ok(XorName::from_content(&serialize(addr)?))
     }
 
     pub(super) async fn list_all_reg_addrs(&self) -> Vec<RegisterAddress> {
         trace!("Listening all register addrs");
-        let iter = list_files_in(&self.file_store_path)
-            .into_iter()
-            .filter_map(|e| e.parent().map(|parent| (parent.to_path_buf(), e.clone())));
-
-        let mut addrs = BTreeMap::<PathBuf, RegisterAddress>::new();
-        for (parent, op_file) in iter {
-            if let Entry::Vacant(vacant) = addrs.entry(parent) {
-                if let Ok(Ok(cmd)) = read(op_file)
-                    .await
-                    .map(|serialized_data| deserialise::<RegisterCmd>(&serialized_data))
+        let reg_ids = match self.backend.names(Namespace::Registers).await {
+            Ok(reg_ids) => reg_ids,
+            Err(err) => {
+                warn!("Could not list the Registers stored: {err}");
+                return vec![];
+            }
+        };
+
+        let mut addrs = vec![];
+        for reg_id in reg_ids {
+            // the address is found in any of the cmds of the log
+            if let Ok(log) = self.backend.read_log(Namespace::Registers, &reg_id).await {
+                if let Some(cmd) = log
+                    .iter()
+                    .find_map(|serialized_data| deserialise_cmd(serialized_data).ok())
                 {
-                    let _existing = vacant.insert(cmd.dst_address());
+                    addrs.push(cmd.dst_address());
                 }
             }
         }
 
         trace!("Listing all register addrs done.");
-        addrs.into_values().collect()
+        addrs
+    }
+
+    /// Number of Registers stored, as recorded by the backend. The cmd creating a Register
+    /// is never removed from its log, thus the log is always found.
+    #[cfg(any(test, feature = "metrics"))]
+    pub(super) fn count(&self) -> usize {
+        self.backend.count(Namespace::Registers)
+    }
+
+    /// Estimated size of all the Register cmds logged, along with the size of the snapshots,
+    /// as accounted for in the used space.
+    pub(super) fn stored_data_size(&self) -> usize {
+        self.backend.stored_size(Namespace::Registers).entries * size_of::<RegisterCmd>()
+            + self.backend.stored_size(Namespace::RegisterSnapshots).bytes
+    }
+
+    /// Estimated size of the cmds logged for the Register, along with the size of its
+    /// snapshots, as accounted for in the used space, if we hold it.
+    pub(super) async fn data_size(&self, addr: &RegisterAddress) -> Result<Option<usize>> {
+        let reg_id = Self::reg_id(addr)?;
+        let mut sizes = vec![];
+        if let Some(size) = self.backend.size(Namespace::Registers, &reg_id).await? {
+            sizes.push(size.entries * size_of::<RegisterCmd>());
+        }
+        if let Some(size) = self
+            .backend
+            .size(Namespace::RegisterSnapshots, &reg_id)
+            .await?
+        {
+            sizes.push(size.bytes);
+        }
+
+        Ok((!sizes.is_empty()).then(|| sizes.iter().sum()))
     }
 
     pub(super) async fn delete_data(&self, addr: &RegisterAddress) -> Result<()> {
-        let filepath = self.address_to_filepath(addr)?;
-        let meta = metadata(filepath.clone()).await?;
-        remove_file(filepath).await?;
-        self.used_space.decrease(meta.len() as usize);
+        let reg_id = Self::reg_id(addr)?;
+        let removed_log = self
+            .backend
+            .remove_log(Namespace::Registers, &reg_id)
+            .await?;
+        let removed_snapshots = self
+            .backend
+            .remove(Namespace::RegisterSnapshots, &reg_id)
+            .await?;
+        if removed_log.is_none() && removed_snapshots.is_none() {
+            return Err(Error::RegisterNotFound(*addr));
+        }
+
+        // the space used by each cmd is estimated as when it was written
+        self.used_space.decrease(
+            removed_log.map_or(0, |log| log.entries) * size_of::<RegisterCmd>()
+                + removed_snapshots.unwrap_or(0),
+        );
         Ok(())
     }
 
-    /// Opens the log of RegisterCmds for a given register address.
-    /// Creates a new log if no data is found
+    /// Checks that all the cmds in the log of the given Register can be read back, are meant
+    /// for it, and are signed by the client that made them, and that its snapshots can be read
+    /// back and are of it. A log not found is deemed valid.
+    pub(super) async fn verify_log(&self, addr: &RegisterAddress) -> Result<bool> {
+        let reg_id = Self::reg_id(addr)?;
+        let entries = self.backend.read_log(Namespace::Registers, &reg_id).await?;
+        for serialized_data in entries {
+            let cmd = match deserialise_cmd(&serialized_data) {
+                Ok(cmd) => cmd,
+                Err(err) => {
+                    warn!("Undecodable Register cmd found in log {reg_id:?} of {addr:?}: {err:?}");
+                    return Ok(false);
+                }
+            };
+
+            if &cmd.dst_address() != addr {
+                warn!(
+                    "Register cmd for {:?} found in log {reg_id:?} of {addr:?}",
+                    cmd.dst_address()
+                );
+                return Ok(false);
+            }
+
+            let (auth, signed_bytes) = match &cmd {
+                RegisterCmd::Create { cmd, .. } => (&cmd.auth, serialize(&cmd.op)?),
+                RegisterCmd::Edit(edit) => (&edit.auth, serialize(&edit.op)?),
+                RegisterCmd::EditPolicy(edit) => (&edit.auth, serialize(&edit.op)?),
+            };
+            if auth.clone().verify_authority(signed_bytes).is_err() {
+                warn!("Register cmd with invalid signature found in log {reg_id:?} of {addr:?}");
+                return Ok(false);
+            }
+        }
+
+        if let Some(serialized_data) = self
+            .backend
+            .get(Namespace::RegisterSnapshots, &reg_id)
+            .await?
+        {
+            match deserialise_snapshots(&serialized_data) {
+                Ok(snapshots) if snapshots.iter().all(|s| s.value.address() == addr) => {}
+                other => {
+                    warn!("Invalid snapshots found for Register {addr:?}: {other:?}");
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Moves the log of the given Register, and its snapshots, aside, out of the store.
+    /// Returns false if there was no such Register.
+    pub(super) async fn quarantine(&self, addr: &RegisterAddress) -> Result<bool> {
+        let reg_id = Self::reg_id(addr)?;
+        let log = self
+            .backend
+            .quarantine(Namespace::Registers, &reg_id)
+            .await?;
+        let snapshots = self
+            .backend
+            .quarantine(Namespace::RegisterSnapshots, &reg_id)
+            .await?;
+        if log.is_none() && snapshots.is_none() {
+            return Ok(false);
+        }
+
+        self.used_space.decrease(
+            log.map_or(0, |log| log.entries) * size_of::<RegisterCmd>()
+                + snapshots.map_or(0, |snapshots| snapshots.bytes),
+        );
+        Ok(true)
+    }
+
+    /// Opens the log of RegisterCmds for a given register address, along with its snapshots,
+    /// the Register state being built from them. Creates a new log if no data is found
     pub(super) async fn open_reg_log_from_disk(
         &self,
         addr: &RegisterAddress,
     ) -> Result<StoredRegister> {
-        let path = self.address_to_filepath(addr)?;
+        let reg_id = Self::reg_id(addr)?;
+        // The log is read before the snapshots, so that the cmds removed from it as a
+        // snapshot is stored concurrently are found in either of them.
+        let entries = self.backend.read_log(Namespace::Registers, &reg_id).await?;
+        let snapshots = self.read_snapshots(&reg_id).await?;
+
+        let mut state: Option<Register> = None;
+        for snapshot in &snapshots {
+            match state.as_mut() {
+                None => state = Some(snapshot.value.clone()),
+                Some(register) => {
+                    if let Err(err) = register.merge(snapshot.value.clone()) {
+                        warn!("Ignoring conflicting snapshot of Register {addr:?}: {err:?}");
+                    }
+                }
+            }
+        }
+
         let mut stored_reg = StoredRegister {
-            state: None,
+            state,
+            snapshots,
             op_log: RegisterLog::new(),
-            op_log_path: path.clone(),
+            reg_id,
         };
 
-        if !path.exists() {
-            trace!(
-                "Register log path for {addr:?} does not exist yet: {}",
-                path.display()
-            );
+        if entries.is_empty() {
+            trace!("Register log for {addr:?} does not exist yet: {reg_id:?}");
             return Ok(stored_reg);
         }
 
-        trace!("Register log path for {addr:?} exists: {}", path.display());
-        for filepath in list_files_in(&path) {
-            match read(&filepath)
-                .await
-                .map(|serialized_data| deserialise::<RegisterCmd>(&serialized_data))
-            {
-                Ok(Ok(reg_cmd)) => {
+        trace!("Register log for {addr:?} exists: {reg_id:?}");
+        for serialized_data in entries {
+            match deserialise_cmd(&serialized_data) {
+                Ok(reg_cmd) => {
                     stored_reg.op_log.push(reg_cmd.clone());
 
                     if let RegisterCmd::Create { cmd, .. } = reg_cmd {
@@ -146,8 +301,7 @@ impl RegisterStore {
                 }
                 other => {
                     warn!(
-                        "Ignoring corrupted Register cmd from storage, for {addr:?}, found at {}: {other:?}",
-                        filepath.display()
+                        "Ignoring corrupted Register cmd from storage, for {addr:?}, found in log {reg_id:?}: {other:?}"
                     )
                 }
             }
@@ -156,28 +310,96 @@ impl RegisterStore {
         Ok(stored_reg)
     }
 
-    /// Persists a RegisterLog to disk
+    /// Stores a snapshot of a Register signed by our section, unless any of the snapshots
+    /// stored already includes all of it. The snapshots it includes all of are replaced by it,
+    /// and the cmds it was built from are removed from the log. The cmd creating the Register
+    /// is kept in the log, so that the Register is still listed along with its address.
+    pub(super) async fn store_snapshot(
+        &self,
+        snapshot: SectionSigned<Register>,
+    ) -> Result<StorageLevel> {
+        let addr = *snapshot.value.address();
+        let reg_id = Self::reg_id(&addr)?;
+        let _guard = self.snapshot_lock.lock().await;
+
+        let mut snapshots = self.read_snapshots(&reg_id).await?;
+        if snapshots
+            .iter()
+            .any(|stored| includes(&stored.value, &snapshot.value))
+        {
+            trace!("Snapshot of Register {addr:?} already stored at {reg_id:?}");
+            return Ok(StorageLevel::NoChange);
+        }
+        snapshots.retain(|stored| !includes(&snapshot.value, &stored.value));
+        snapshots.push(snapshot.clone());
+
+        let serialized_data = serialise_snapshots(&snapshots)?;
+        if !self.used_space.can_add(serialized_data.len()) {
+            return Err(Error::NotEnoughSpace);
+        }
+        let replaced = self
+            .backend
+            .replace(Namespace::RegisterSnapshots, &reg_id, &serialized_data)
+            .await?;
+        self.used_space.decrease(replaced.unwrap_or(0));
+        let storage_level = self.used_space.increase(serialized_data.len());
+
+        // The cmds are only removed once the snapshot is stored, so that they are never lost.
+        let mut removed = 0;
+        for serialized_data in self.backend.read_log(Namespace::Registers, &reg_id).await? {
+            let Ok(cmd) = deserialise_cmd(&serialized_data) else {
+                continue;
+            };
+            if !snapshot_includes(&snapshot, &cmd) {
+                continue;
+            }
+            let reg_cmd_id = register_operation_id(&cmd)?;
+            if self
+                .backend
+                .remove_entry(Namespace::Registers, &reg_id, &reg_cmd_id)
+                .await?
+                .is_some()
+            {
+                self.used_space.decrease(size_of::<RegisterCmd>());
+                removed += 1;
+            }
+        }
+
+        trace!("Stored snapshot of Register {addr:?}, removing {removed} cmd/s from its log at {reg_id:?}");
+        Ok(storage_level)
+    }
+
+    // Reads the snapshots stored for a Register, none if it has no snapshot
+    async fn read_snapshots(&self, reg_id: &XorName) -> Result<Vec<SectionSigned<Register>>> {
+        match self
+            .backend
+            .get(Namespace::RegisterSnapshots, reg_id)
+            .await?
+        {
+            Some(serialized_data) => deserialise_snapshots(&serialized_data),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Persists a RegisterLog
     pub(super) async fn write_log_to_disk(
         &self,
         log: &RegisterLog,
-        path: &Path,
+        reg_id: &XorName,
     ) -> Result<StorageLevel> {
         trace!(
-            "Writing to register log with {} cmd/s at {}",
-            log.len(),
-            path.display()
+            "Writing to register log with {} cmd/s at {reg_id:?}",
+            log.len()
         );
         if log.is_empty() {
             return Ok(StorageLevel::NoChange);
         }
 
-        create_dir_all(path).await?;
-
         let mut last_err = None;
         let mut storage_level = StorageLevel::NoChange;
 
         for cmd in log {
-            match self.write_register_cmd(cmd, path).await {
+            match self.write_register_cmd(cmd, reg_id).await {
                 Ok(level) => {
                     if matches!(level, StorageLevel::Updated(_))
                         && matches!(storage_level, StorageLevel::NoChange)
@@ -196,19 +418,18 @@ impl RegisterStore {
             Err(err)
         } else {
             trace!(
-                "Log of {} cmd/s written successfully at {}",
-                log.len(),
-                path.display()
+                "Log of {} cmd/s written successfully at {reg_id:?}",
+                log.len()
             );
             Ok(storage_level)
         }
     }
 
-    /// Persists a RegisterCmd to disk
+    /// Persists a RegisterCmd
     pub(super) async fn write_register_cmd(
         &self,
         cmd: &RegisterCmd,
-        path: &Path,
+        reg_id: &XorName,
     ) -> Result<StorageLevel> {
         // rough estimate of the RegisterCmd
         let required_space = size_of::<RegisterCmd>();
@@ -217,48 +438,55 @@ impl RegisterStore {
         }
 
         let reg_cmd_id = register_operation_id(cmd)?;
-        let path = path.join(reg_cmd_id.clone());
         let addr = cmd.dst_address();
 
-        trace!(
-            "Writing cmd register log for {addr:?} at {}",
-            path.display()
-        );
+        trace!("Writing cmd register log for {addr:?} at {reg_id:?}, id {reg_cmd_id}");
 
-        let entry_hash = if let RegisterCmd::Edit(edit_cmd) = cmd {
-            let entry_hash = EntryHash(edit_cmd.op.edit.crdt_op.hash());
-            trace!(
-                "Writing RegisterEdit cmd log for {addr:?}, entry hash: {entry_hash}, at {}",
-                path.display()
-            );
-            Some(entry_hash)
-        } else {
-            trace!(
-                "Writing RegisterCreate cmd log for {addr:?} at {}",
-                path.display()
-            );
-            None
+        let entry_hash = match cmd {
+            RegisterCmd::Edit(edit_cmd) => {
+                let entry_hash = EntryHash(edit_cmd.op.edit.crdt_op.hash());
+                trace!(
+                    "Writing RegisterEdit cmd log for {addr:?}, entry hash: {entry_hash}, at {reg_id:?}"
+                );
+                Some(entry_hash)
+            }
+            RegisterCmd::EditPolicy(edit_cmd) => {
+                trace!(
+                    "Writing RegisterEditPolicy cmd log for {addr:?}, policy version: {}, at {reg_id:?}",
+                    edit_cmd.op.op.version
+                );
+                None
+            }
+            RegisterCmd::Create { .. } => {
+                trace!("Writing RegisterCreate cmd log for {addr:?} at {reg_id:?}");
+                None
+            }
         };
 
         // it's deterministic, so they are exactly the same op so we can leave
-        if path.exists() {
+        if self
+            .backend
+            .contains_entry(Namespace::Registers, reg_id, &reg_cmd_id)
+            .await?
+        {
             trace!("RegisterCmd exists on disk for {addr:?}, entry hash: {entry_hash:?}, so was not written: {cmd:?}");
             return Ok(StorageLevel::NoChange);
         }
 
-        let mut file = File::create(&path).await?;
-
-        let serialized_data = serialise(cmd)?;
-        file.write_all(&serialized_data).await?;
-        // Let's sync up OS data to disk to reduce the chances of
-        // concurrent reading failing by reading an empty/incomplete file
-        file.sync_data().await?;
+        let serialized_data = serialise_cmd(cmd)?;
+        if !self
+            .backend
+            .append(Namespace::Registers, reg_id, &reg_cmd_id, &serialized_data)
+            .await?
+        {
+            // it was written concurrently
+            return Ok(StorageLevel::NoChange);
+        }
 
         let storage_level = self.used_space.increase(required_space);
 
         trace!(
-            "RegisterCmd writing successful for {addr:?}, id {reg_cmd_id}, at {}, entry hash: {entry_hash:?}",
-            path.display()
+            "RegisterCmd writing successful for {addr:?}, id {reg_cmd_id}, at {reg_id:?}, entry hash: {entry_hash:?}"
         );
 
         Ok(storage_level)
@@ -278,3 +506,93 @@ fn register_operation_id(cmd: &RegisterCmd) -> Result<RegisterCmdId> {
     let id = hex::encode(output);
     Ok(id)
 }
+
+/// The bytes a snapshot of a Register is signed over by our section, serialised as msgs are,
+/// so that the nodes it's replicated to can serialise it back to the very same bytes.
+pub(super) fn serialise_snapshot(snapshot: &Register) -> Result<Vec<u8>> {
+    rmp_serde::to_vec(snapshot)
+        .map_err(|err| Error::NetworkData(DataError::Serialisation(err.to_string())))
+}
+
+fn serialise_snapshots(snapshots: &[SectionSigned<Register>]) -> Result<Vec<u8>> {
+    rmp_serde::to_vec(snapshots)
+        .map_err(|err| Error::NetworkData(DataError::Serialisation(err.to_string())))
+}
+
+fn deserialise_snapshots(bytes: &[u8]) -> Result<Vec<SectionSigned<Register>>> {
+    rmp_serde::from_slice(bytes)
+        .map_err(|err| Error::NetworkData(DataError::Serialisation(err.to_string())))
+}
+
+// Whether the cmd was applied to the snapshot, the cmd creating the Register being always logged.
+fn snapshot_includes(snapshot: &SectionSigned<Register>, cmd: &RegisterCmd) -> bool {
+    match cmd {
+        RegisterCmd::Create { .. } => false,
+        RegisterCmd::Edit(edit) => snapshot
+            .value
+            .get(EntryHash(edit.op.edit.crdt_op.hash()))
+            .is_ok(),
+        RegisterCmd::EditPolicy(edit) => edit.op.op.version <= snapshot.value.policy_version(),
+    }
+}
+
+// Whether the Register includes all the entries and policy edits of the other one.
+fn includes(register: &Register, other: &Register) -> bool {
+    let mut merged = register.clone();
+    merged.merge(other.clone()).is_ok() && &merged == register
+}
+
+// Cmds are logged as msgs are serialised, rather than with bincode, as an edit skips serialising
+// the version of the policy it was made as per when it's the initial one. The cmds logged with
+// bincode before that are still read, as per the layout they were logged with.
+fn serialise_cmd(cmd: &RegisterCmd) -> Result<Vec<u8>> {
+    rmp_serde::to_vec(cmd)
+        .map_err(|err| Error::NetworkData(DataError::Serialisation(err.to_string())))
+}
+
+fn deserialise_cmd(bytes: &[u8]) -> Result<RegisterCmd> {
+    match rmp_serde::from_slice(bytes) {
+        Ok(cmd) => Ok(cmd),
+        Err(err) => deserialise::<LegacyRegisterCmd>(bytes)
+            .map(RegisterCmd::from)
+            .map_err(|_| Error::NetworkData(DataError::Serialisation(err.to_string()))),
+    }
+}
+
+// The layout of the cmds logged with bincode, predating the policy version of the edits.
+#[allow(clippy::large_enum_variant)]
+#[derive(Deserialize)]
+enum LegacyRegisterCmd {
+    Create {
+        cmd: SignedRegisterCreate,
+        section_sig: SectionSig,
+    },
+    Edit {
+        op: LegacyEditRegister,
+        auth: ClientAuth,
+    },
+    EditPolicy(SignedRegisterPolicyEdit),
+}
+
+#[derive(Deserialize)]
+struct LegacyEditRegister {
+    address: RegisterAddress,
+    edit: RegisterOp<Entry>,
+}
+
+impl From<LegacyRegisterCmd> for RegisterCmd {
+    fn from(cmd: LegacyRegisterCmd) -> Self {
+        match cmd {
+            LegacyRegisterCmd::Create { cmd, section_sig } => Self::Create { cmd, section_sig },
+            LegacyRegisterCmd::Edit { op, auth } => Self::Edit(SignedRegisterEdit {
+                op: EditRegister {
+                    address: op.address,
+                    edit: op.edit,
+                    policy_version: 0,
+                },
+                auth,
+            }),
+            LegacyRegisterCmd::EditPolicy(edit) => Self::EditPolicy(edit),
+        }
+    }
+}
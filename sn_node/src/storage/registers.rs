@@ -7,49 +7,69 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    register_store::{RegisterStore, StoredRegister},
+    backend::StorageBackend,
+    register_store::{serialise_snapshot, RegisterLog, RegisterStore, StoredRegister},
     used_space::StorageLevel,
     Error, Result,
 };
 
+#[cfg(test)]
+use sn_interface::{
+    messaging::{data::CreateRegister, ClientAuth, SectionSig},
+    types::{register::Policy, Keypair},
+};
 use sn_interface::{
     messaging::{
         data::{
-            CreateRegister, EditRegister, RegisterCmd, RegisterQuery, SignedRegisterCreate,
-            SignedRegisterEdit,
+            EditRegister, RegisterCmd, RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+            SignedRegisterPolicyEdit,
         },
-        system::NodeQueryResponse,
-        ClientAuth, SectionSig, VerifyAuthority,
+        signature_aggregator::SignatureAggregator,
+        system::{NodeQueryResponse, SectionSigShare, SectionSigned},
+        VerifyAuthority,
     },
+    network_knowledge::SectionsDAG,
     types::{
-        register::{Action, EntryHash, Permissions, Policy, Register, User},
-        Keypair, PublicKey, RegisterAddress, ReplicatedRegisterLog, SPENTBOOK_TYPE_TAG,
+        register::{Action, EntryHash, Register, User},
+        RegisterAddress, ReplicatedRegisterLog,
     },
 };
 
 use crate::UsedSpace;
 use bincode::serialize;
 use std::{
-    collections::BTreeMap,
+    collections::BTreeSet,
     fmt::{self, Display, Formatter},
-    path::Path,
+    mem,
+    sync::Arc,
 };
+use tokio::sync::Mutex;
 use tracing::info;
+#[cfg(test)]
 use xor_name::XorName;
 
-const REGISTER_STORE_DIR_NAME: &str = "register";
+// Number of cmds logged for a Register beyond which a snapshot of it is to be signed by our section.
+const REGISTER_SNAPSHOT_THRESHOLD: usize = 100;
 
 /// Operations over the Register data type and its storage.
 #[derive(Debug, Clone)]
 pub(super) struct RegisterStorage {
     file_store: RegisterStore,
+    // the Registers whose log grew beyond the threshold since their logs to snapshot were taken
+    to_snapshot: Arc<Mutex<BTreeSet<RegisterAddress>>>,
+    // the shares of the Elders' signatures over the snapshots built from those logs
+    snapshot_sig_shares: Arc<Mutex<SignatureAggregator>>,
 }
 
 impl RegisterStorage {
     /// Create new `RegisterStorage`
-    pub(super) fn new(path: &Path, used_space: UsedSpace) -> Self {
-        let file_store = RegisterStore::new(path.join(REGISTER_STORE_DIR_NAME), used_space);
-        Self { file_store }
+    pub(super) fn new(backend: Arc<dyn StorageBackend>, used_space: UsedSpace) -> Self {
+        let file_store = RegisterStore::new(backend, used_space);
+        Self {
+            file_store,
+            to_snapshot: Arc::default(),
+            snapshot_sig_shares: Arc::default(),
+        }
     }
 
     pub(super) async fn remove_register(&self, address: &RegisterAddress) -> Result<()> {
@@ -57,10 +77,34 @@ impl RegisterStorage {
         self.file_store.delete_data(address).await
     }
 
+    pub(super) fn stored_data_size(&self) -> usize {
+        self.file_store.stored_data_size()
+    }
+
+    #[cfg(any(test, feature = "metrics"))]
+    pub(super) fn count(&self) -> usize {
+        self.file_store.count()
+    }
+
+    pub(super) async fn data_size(&self, address: &RegisterAddress) -> Result<Option<usize>> {
+        self.file_store.data_size(address).await
+    }
+
     pub(super) async fn addrs(&self) -> Vec<RegisterAddress> {
         self.file_store.list_all_reg_addrs().await
     }
 
+    /// Returns false if the log stored for the Register is corrupt.
+    pub(super) async fn verify(&self, address: &RegisterAddress) -> Result<bool> {
+        self.file_store.verify_log(address).await
+    }
+
+    /// Moves the log stored for the Register out of the store, returning false if not found.
+    pub(super) async fn quarantine(&self, address: &RegisterAddress) -> Result<bool> {
+        debug!("Quarantining register, {:?}", address);
+        self.file_store.quarantine(address).await
+    }
+
     /// Used for replication of data to new Adults.
     pub(super) async fn get_register_replica(
         &self,
@@ -68,20 +112,43 @@ impl RegisterStorage {
     ) -> Result<ReplicatedRegisterLog> {
         let stored_reg = self.try_load_stored_register(address).await?;
         // Build the replicated register log assuming ops stored are all valid and correctly
-        // signed since we performed such validations before storing them.
+        // signed since we performed such validations before storing them. The snapshots
+        // are signed by our section, the nodes they are replicated to verify that instead.
         Ok(ReplicatedRegisterLog {
             address: *address,
             op_log: stored_reg.op_log,
+            snapshots: stored_reg.snapshots,
         })
     }
 
     /// Update our Register's replica on receiving data from other nodes.
-    pub(super) async fn update(&self, data: &ReplicatedRegisterLog) -> Result<StorageLevel> {
+    ///
+    /// The snapshots replicated are only stored if signed by a key of the given section chain.
+    pub(super) async fn update(
+        &self,
+        data: &ReplicatedRegisterLog,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
         trace!("Updating Register store: {:?}", data.address);
+        let mut storage_level = StorageLevel::NoChange;
+        for snapshot in &data.snapshots {
+            if let Err(err) = verify_snapshot(snapshot, &data.address, section_chain) {
+                warn!("Discarding ReplicatedRegisterLog snapshot: {err:?}");
+                continue;
+            }
+            let level = self.file_store.store_snapshot(snapshot.clone()).await?;
+            if matches!(level, StorageLevel::Updated(_)) {
+                storage_level = level;
+            }
+        }
+
         let mut stored_reg = self.try_load_stored_register(&data.address).await?;
 
         let mut log_to_write = Vec::new();
-        for replicated_cmd in &data.op_log {
+        for replicated_cmd in in_apply_order(&data.op_log) {
+            if stored_reg.in_snapshots(replicated_cmd) {
+                continue;
+            }
             if let Err(err) =
                 self.try_to_apply_cmd_against_register_state(replicated_cmd, &mut stored_reg)
             {
@@ -95,9 +162,16 @@ impl RegisterStorage {
         }
 
         // Write the new cmds all to disk
-        self.file_store
-            .write_log_to_disk(&log_to_write, &stored_reg.op_log_path)
-            .await
+        let level = self
+            .file_store
+            .write_log_to_disk(&log_to_write, &stored_reg.reg_id)
+            .await?;
+        if matches!(level, StorageLevel::Updated(_)) {
+            storage_level = level;
+        }
+
+        self.check_log_size(&data.address, &stored_reg).await;
+        Ok(storage_level)
     }
 
     /// --- Writing ---
@@ -107,13 +181,115 @@ impl RegisterStorage {
         // Let's first try to load and reconstruct the replica of targetted Register
         // we have in local storage, to then try to apply the new command onto it.
         let mut stored_reg = self.try_load_stored_register(&cmd.dst_address()).await?;
-
+        if stored_reg.in_snapshots(cmd) {
+            return Ok(StorageLevel::NoChange);
+        }
+        // A new edit has to be permitted as per our current policy too, so that a writer
+        // whose permissions were revoked cannot pass an edit off as made before that.
+        if let (Some(register), RegisterCmd::Edit(edit)) = (&stored_reg.state, cmd) {
+            register.check_permissions(Action::Write, Some(User::Key(edit.auth.public_key)))?;
+        }
         self.try_to_apply_cmd_against_register_state(cmd, &mut stored_reg)?;
 
         // Everything went fine, let's write the single cmd to disk
-        self.file_store
-            .write_log_to_disk(&vec![cmd.clone()], &stored_reg.op_log_path)
+        let storage_level = self
+            .file_store
+            .write_log_to_disk(&vec![cmd.clone()], &stored_reg.reg_id)
+            .await?;
+
+        self.check_log_size(&cmd.dst_address(), &stored_reg).await;
+        Ok(storage_level)
+    }
+
+    // Records the Register for a snapshot of it to be signed, if its log grew beyond the threshold.
+    async fn check_log_size(&self, address: &RegisterAddress, stored_reg: &StoredRegister) {
+        if stored_reg.op_log.len() > REGISTER_SNAPSHOT_THRESHOLD {
+            let _ = self.to_snapshot.lock().await.insert(*address);
+        }
+    }
+
+    /// --- Snapshots ---
+
+    /// Takes the logs of the Registers which grew beyond the threshold since last taken,
+    /// for our section to sign a snapshot of each of them.
+    pub(super) async fn logs_to_snapshot(&self) -> Vec<ReplicatedRegisterLog> {
+        let addrs = mem::take(&mut *self.to_snapshot.lock().await);
+        let mut logs = vec![];
+        for address in addrs {
+            match self.get_register_replica(&address).await {
+                Ok(log) if log.op_log.len() > REGISTER_SNAPSHOT_THRESHOLD => logs.push(log),
+                // a snapshot was stored since
+                Ok(_) => {}
+                Err(err) => warn!("Could not read the log of Register {address:?}: {err:?}"),
+            }
+        }
+        logs
+    }
+
+    /// Builds the snapshot of a Register from its log, as an Elder signing it does.
+    /// The snapshots of the log are verified against the given section chain, and each
+    /// of its cmds as when replicated, those which are invalid being discarded.
+    pub(super) fn build_snapshot(
+        &self,
+        log: &ReplicatedRegisterLog,
+        section_chain: &SectionsDAG,
+    ) -> Result<Register> {
+        let mut stored_reg = StoredRegister {
+            state: None,
+            snapshots: vec![],
+            op_log: RegisterLog::new(),
+            reg_id: RegisterStore::reg_id(&log.address)?,
+        };
+        for snapshot in &log.snapshots {
+            verify_snapshot(snapshot, &log.address, section_chain)?;
+            match stored_reg.state.as_mut() {
+                Some(register) => register
+                    .merge(snapshot.value.clone())
+                    .map_err(Error::NetworkData)?,
+                None => stored_reg.state = Some(snapshot.value.clone()),
+            }
+        }
+        for cmd in in_apply_order(&log.op_log) {
+            if let Err(err) = self.try_to_apply_cmd_against_register_state(cmd, &mut stored_reg) {
+                warn!("Discarding Register cmd {cmd:?} from the log to snapshot: {err:?}");
+            }
+        }
+
+        stored_reg.state.ok_or(Error::RegisterNotFound(log.address))
+    }
+
+    /// Aggregates the share of an Elder's signature over a snapshot of a Register, storing the
+    /// snapshot once signed by our section, as verified against the given section chain.
+    pub(super) async fn add_snapshot_sig_share(
+        &self,
+        snapshot: Register,
+        sig_share: SectionSigShare,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        let address = *snapshot.address();
+        let serialized_data = serialise_snapshot(&snapshot)?;
+        let sig = match self
+            .snapshot_sig_shares
+            .lock()
             .await
+            .try_aggregate(&serialized_data, sig_share)
+        {
+            Ok(Some(sig)) => sig,
+            Ok(None) => return Ok(StorageLevel::NoChange),
+            Err(err) => {
+                warn!("Invalid signature share over the snapshot of Register {address:?}: {err}");
+                return Err(Error::InvalidRegisterSnapshot(address));
+            }
+        };
+
+        let snapshot = SectionSigned {
+            value: snapshot,
+            sig,
+        };
+        verify_snapshot(&snapshot, &address, section_chain)?;
+        let storage_level = self.file_store.store_snapshot(snapshot).await?;
+        info!("Stored the snapshot of Register {address:?} signed by our section");
+        Ok(storage_level)
     }
 
     /// --- Reading ---
@@ -130,6 +306,7 @@ impl RegisterStorage {
             GetUserPermissions { address, user } => {
                 self.get_user_permissions(*address, *user, requester).await
             }
+            Subscribe(address) => self.check_subscription(*address, requester).await,
         }
     }
 
@@ -152,6 +329,24 @@ impl RegisterStorage {
         }
     }
 
+    /// Check the requester may subscribe to the `Register`, i.e. read it.
+    /// The subscription itself is kept by the Elders.
+    async fn check_subscription(
+        &self,
+        address: RegisterAddress,
+        requester: User,
+    ) -> NodeQueryResponse {
+        let result = match self.get_register(&address, Action::Read, requester).await {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Error checking subscription to register {address:?}: {error:?}");
+                Err(error.into())
+            }
+        };
+
+        NodeQueryResponse::SubscribeToRegister(result)
+    }
+
     /// Get entire Register.
     async fn get(&self, address: RegisterAddress, requester: User) -> NodeQueryResponse {
         let result = match self.get_register(&address, Action::Read, requester).await {
@@ -236,32 +431,6 @@ impl RegisterStorage {
     // =========================== Helpers ====================================
     // ========================================================================
 
-    // Temporary helper function which makes sure there exists a Register for the spentbook,
-    // this shouldn't be required once we have a Spentbook data type.
-    pub(super) async fn write_spentbook_register(
-        &self,
-        cmd: &RegisterCmd,
-        section_pk: PublicKey,
-        node_keypair: Keypair,
-    ) -> Result<StorageLevel> {
-        let address = cmd.dst_address();
-        debug!("Creating new spentbook register: {:?}", address);
-
-        let mut permissions = BTreeMap::new();
-        let _ = permissions.insert(User::Anyone, Permissions::new(true));
-        let owner = User::Key(section_pk);
-        let policy = Policy { owner, permissions };
-
-        let create_cmd =
-            create_reg_w_policy(*address.name(), SPENTBOOK_TYPE_TAG, policy, &node_keypair)?;
-
-        self.update(&ReplicatedRegisterLog {
-            address,
-            op_log: [create_cmd, cmd.clone()].to_vec(),
-        })
-        .await
-    }
-
     // Private helper which does all verification and tries to apply given cmd to given Register
     // state. It accumulates the cmd, if valid, into the log so further calls can be made with
     // the same state and log, as used by the `update` function.
@@ -278,7 +447,9 @@ impl RegisterStorage {
         // verified untill we have the `Register create` cmd.
         match (stored_reg.state.as_mut(), cmd) {
             (Some(_), RegisterCmd::Create { .. }) => return Ok(()), // no op, since already created
-            (Some(ref mut register), RegisterCmd::Edit(_)) => self.apply(cmd, register)?,
+            (Some(ref mut register), RegisterCmd::Edit(_) | RegisterCmd::EditPolicy(_)) => {
+                self.apply(cmd, register)?
+            }
             (None, RegisterCmd::Create { cmd, .. }) => {
                 // the target Register is not in our store or we don't have the 'Register create',
                 // let's verify the create cmd we received is valid and try to apply stored cmds we may have.
@@ -291,14 +462,20 @@ impl RegisterStorage {
 
                 trace!("Creating new register: {:?}", cmd.dst_address());
                 // let's do a final check, let's try to apply all cmds to it,
-                // those which are new cmds were not validated yet, so let's do it now.
+                // those which are new cmds were not validated yet, so let's do it now,
+                // discarding the invalid ones rather than the Register.
                 let mut register =
                     Register::new(*op.policy.owner(), op.name, op.tag, op.policy.clone());
 
-                for cmd in &stored_reg.op_log {
-                    self.apply(cmd, &mut register)?;
+                let mut valid_cmds = RegisterLog::new();
+                for cmd in in_apply_order(&stored_reg.op_log) {
+                    match self.apply(cmd, &mut register) {
+                        Ok(()) => valid_cmds.push(cmd.clone()),
+                        Err(err) => warn!("Discarding Register cmd {cmd:?}: {err:?}"),
+                    }
                 }
 
+                stored_reg.op_log = valid_cmds;
                 stored_reg.state = Some(register);
             }
             (None, _edit_cmd) => { /* we cannot validate it right now, but we'll store it */ }
@@ -328,7 +505,14 @@ impl RegisterStorage {
                     .or(Err(Error::InvalidSignature(Box::new(public_key))))?;
 
                 info!("Editing Register: {:?}", addr);
-                register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
+                // The edit is checked against the version of the policy it was made as per,
+                // whatever edits of the policy we applied since, so that all replicas agree on
+                // it, whichever order they received the edits in.
+                register.check_permissions_at(
+                    op.policy_version,
+                    Action::Write,
+                    Some(User::Key(public_key)),
+                )?;
                 let result = register
                     .apply_op(op.edit.clone())
                     .map_err(Error::NetworkData);
@@ -344,22 +528,70 @@ impl RegisterStorage {
                     }
                 }
             }
+            RegisterCmd::EditPolicy(SignedRegisterPolicyEdit { op, auth }) => {
+                let public_key = auth.public_key;
+                let _ = auth
+                    .clone()
+                    .verify_authority(serialize(op)?)
+                    .or(Err(Error::InvalidSignature(Box::new(public_key))))?;
+
+                info!("Editing Register policy: {:?}", addr);
+                let result = register
+                    .apply_policy_op(op.op.clone(), User::Key(public_key))
+                    .map_err(Error::NetworkData);
+
+                match result {
+                    Ok(()) => {
+                        trace!("Editing Register policy success: {:?}", addr);
+                        Ok(())
+                    }
+                    Err(err) => {
+                        trace!("Editing Register policy failed {:?}: {:?}", addr, err);
+                        Err(err)
+                    }
+                }
+            }
         }
     }
 
-    // Gets stored register log from disk, trying to reconstruct the Register
-    // Note this doesn't perform any cmd sig/perms validation, it's only used when the log
-    // is read from disk which has already been validated before storing it.
+    // Gets stored register log from disk, trying to reconstruct the Register from its
+    // snapshots and the cmds logged since.
+    // Note this doesn't perform any cmd sig validation, it's only used when the log
+    // is read from disk which has already been validated before storing it. The edits
+    // logged before the Register was created, or a snapshot of it stored, weren't validated
+    // against it though, thus their permissions are, and the policy edits are applied to it.
     async fn try_load_stored_register(&self, addr: &RegisterAddress) -> Result<StoredRegister> {
         let mut stored_reg = self.file_store.open_reg_log_from_disk(addr).await?;
         // if we have the Register creation cmd, apply all ops to reconstruct the Register
         if let Some(register) = &mut stored_reg.state {
-            for cmd in &stored_reg.op_log {
-                if let RegisterCmd::Edit(SignedRegisterEdit { op, .. }) = cmd {
-                    let EditRegister { edit, .. } = op;
-                    register
-                        .apply_op(edit.clone())
-                        .map_err(Error::NetworkData)?;
+            for cmd in in_apply_order(&stored_reg.op_log) {
+                match cmd {
+                    RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
+                        let EditRegister {
+                            edit,
+                            policy_version,
+                            ..
+                        } = op;
+                        if let Err(err) = register.check_permissions_at(
+                            *policy_version,
+                            Action::Write,
+                            Some(User::Key(auth.public_key)),
+                        ) {
+                            warn!("Ignoring Register cmd from storage, for {addr:?}: {err:?}");
+                            continue;
+                        }
+                        register
+                            .apply_op(edit.clone())
+                            .map_err(Error::NetworkData)?;
+                    }
+                    RegisterCmd::EditPolicy(SignedRegisterPolicyEdit { op, auth }) => {
+                        if let Err(err) =
+                            register.apply_policy_op(op.op.clone(), User::Key(auth.public_key))
+                        {
+                            warn!("Ignoring Register cmd from storage, for {addr:?}: {err:?}");
+                        }
+                    }
+                    RegisterCmd::Create { .. } => {}
                 }
             }
         }
@@ -374,9 +606,45 @@ impl Display for RegisterStorage {
     }
 }
 
-// Helper functions temporarily used for spentbook logic, but also used for tests.
-// This shouldn't be required outside of tests once we have a Spentbook data type.
-fn create_reg_w_policy(
+// Checks the snapshot is of the Register at the given address, and signed by a key of the
+// given section chain.
+fn verify_snapshot(
+    snapshot: &SectionSigned<Register>,
+    address: &RegisterAddress,
+    section_chain: &SectionsDAG,
+) -> Result<()> {
+    if snapshot.value.address() != address {
+        return Err(Error::RegisterAddrMismatch {
+            cmd_dst_addr: *snapshot.value.address(),
+            reg_addr: *address,
+        });
+    }
+    if !section_chain.has_key(&snapshot.sig.public_key) {
+        return Err(Error::RegisterSnapshotUnknownSectionKey(*address));
+    }
+    if !snapshot.sig.verify(&serialise_snapshot(&snapshot.value)?) {
+        return Err(Error::InvalidRegisterSnapshot(*address));
+    }
+    Ok(())
+}
+
+// Returns the cmds in the causal order they have to be applied to a Register: the edits of its
+// policy in the order of the versions they result in, each edit of its data following the edit
+// resulting in the version of the policy it was made as per, which it's checked against.
+fn in_apply_order(log: &[RegisterCmd]) -> impl Iterator<Item = &RegisterCmd> {
+    let mut cmds: Vec<_> = log.iter().collect();
+    // the sort is stable, the edits made as per the same version are kept in the order logged
+    cmds.sort_by_key(|cmd| match cmd {
+        RegisterCmd::Create { .. } => (0, false),
+        RegisterCmd::EditPolicy(edit) => (edit.op.op.version, false),
+        RegisterCmd::Edit(edit) => (edit.op.policy_version, true),
+    });
+    cmds.into_iter()
+}
+
+// Helper functions used for tests.
+#[cfg(test)]
+pub(super) fn create_reg_w_policy(
     name: XorName,
     tag: u64,
     policy: Policy,
@@ -396,6 +664,7 @@ fn create_reg_w_policy(
     })
 }
 
+#[cfg(test)]
 fn section_sig() -> SectionSig {
     let sk = bls::SecretKey::random();
     let public_key = sk.public_key();
@@ -411,23 +680,31 @@ fn section_sig() -> SectionSig {
 mod test {
     use crate::storage::StorageLevel;
 
-    use super::{create_reg_w_policy, RegisterStorage, UsedSpace};
+    use super::{
+        create_reg_w_policy, serialise_snapshot, RegisterStorage, RegisterStore, UsedSpace,
+        REGISTER_SNAPSHOT_THRESHOLD,
+    };
+    use crate::storage::backend::{FsBackend, KvBackend, Namespace, StorageBackend};
     use sn_interface::{
         messaging::{
-            data::{EditRegister, RegisterCmd, RegisterQuery, SignedRegisterEdit},
-            system::NodeQueryResponse,
+            data::{
+                EditRegister, EditRegisterPolicy, RegisterCmd, RegisterQuery, SignedRegisterEdit,
+                SignedRegisterPolicyEdit,
+            },
+            system::{NodeQueryResponse, SectionSig, SectionSigShare},
             ClientAuth,
         },
+        network_knowledge::SectionsDAG,
         types::{
-            register::{EntryHash, Policy, Register, User},
-            Keypair,
+            register::{EntryHash, Permissions, Policy, PolicyEdit, PolicyOp, Register, User},
+            Keypair, RegisterAddress, ReplicatedRegisterLog,
         },
     };
 
     use bincode::serialize;
     use eyre::{bail, Result};
     use rand::{distributions::Alphanumeric, Rng};
-    use std::collections::BTreeSet;
+    use std::{collections::BTreeSet, sync::Arc};
     use tempfile::tempdir;
     use xor_name::XorName;
 
@@ -437,21 +714,21 @@ mod test {
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = RegisterStore::reg_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
 
         let stored_reg = store.try_load_stored_register(&addr).await?;
         // it should *not* contain the create cmd
         assert!(stored_reg.state.is_none());
         assert!(stored_reg.op_log.is_empty());
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         let _ = store.write(&cmd_create).await?;
         let stored_reg = store.try_load_stored_register(&addr).await?;
         // it should contain the create cmd
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
         assert_eq!(stored_reg.op_log, vec![cmd_create.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(0));
 
         // let's now edit the register
@@ -469,7 +746,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -481,7 +758,7 @@ mod test {
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = RegisterStore::reg_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
 
         // let's first store an edit cmd for the register
@@ -492,7 +769,7 @@ mod test {
         // it should contain the edit cmd only
         assert_eq!(stored_reg.state, None);
         assert_eq!(stored_reg.op_log, vec![cmd_edit.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // and now store the create cmd for the register
         let _ = store.write(&cmd_create).await?;
@@ -508,7 +785,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -520,7 +797,7 @@ mod test {
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = RegisterStore::reg_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
         let mut stored_reg = store.try_load_stored_register(&addr).await?;
 
@@ -529,7 +806,7 @@ mod test {
         // it should contain the create cmd
         assert_eq!(stored_reg.state.as_ref(), Some(&register));
         assert_eq!(stored_reg.op_log, vec![cmd_create.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(0));
 
         // apply the create cmd again should change nothing
@@ -554,7 +831,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(1));
 
         // applying the edit cmd again shouldn't fail or alter the register content,
@@ -569,7 +846,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.map(|reg| reg.size()), Some(1));
 
         Ok(())
@@ -581,7 +858,7 @@ mod test {
 
         let (cmd_create, _, keypair, name, policy) = create_register()?;
         let addr = cmd_create.dst_address();
-        let log_path = store.file_store.address_to_filepath(&addr)?;
+        let reg_id = RegisterStore::reg_id(&addr)?;
         let mut register = Register::new(*policy.owner(), name, 0, policy);
         let mut stored_reg = store.try_load_stored_register(&addr).await?;
 
@@ -591,7 +868,7 @@ mod test {
         // it should contain the edit cmd
         assert_eq!(stored_reg.state, None);
         assert_eq!(stored_reg.op_log, vec![cmd_edit.clone()]);
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // applying the edit cmd again shouldn't fail,
         // although the log will contain the edit cmd duplicated
@@ -602,7 +879,7 @@ mod test {
             stored_reg.op_log.iter().all(|op| op == &cmd_edit),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
 
         // let's apply the create cmd now
         store.try_to_apply_cmd_against_register_state(&cmd_create, &mut stored_reg)?;
@@ -616,7 +893,7 @@ mod test {
                 .all(|op| [&cmd_create, &cmd_edit].contains(&op)),
             "Op log doesn't match"
         );
-        assert_eq!(stored_reg.op_log_path, log_path);
+        assert_eq!(stored_reg.reg_id, reg_id);
         assert_eq!(stored_reg.state.as_ref().map(|reg| reg.size()), Some(1));
 
         // apply the create cmd again should change nothing
@@ -698,7 +975,9 @@ mod test {
         let new_store = new_store()?;
         for addr in all_addrs {
             let replica = store.get_register_replica(&addr).await?;
-            let _ = new_store.update(&replica).await?;
+            let _ = new_store
+                .update(&replica, &section_chain(&sk_set()))
+                .await?;
         }
 
         // assert the same tests hold as for the first store
@@ -757,6 +1036,207 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_subscription_is_checked_as_a_read() -> Result<()> {
+        let store = new_store()?;
+
+        let (cmd_create, authority, _, _, _) = create_register()?;
+        let address = cmd_create.dst_address();
+
+        // the register isn't held yet
+        let res = store
+            .read(&RegisterQuery::Subscribe(address), authority)
+            .await;
+        assert!(matches!(
+            res,
+            NodeQueryResponse::SubscribeToRegister(Err(_))
+        ));
+
+        let _ = store.write(&cmd_create).await?;
+        let res = store
+            .read(&RegisterQuery::Subscribe(address), authority)
+            .await;
+        assert_eq!(res, NodeQueryResponse::SubscribeToRegister(Ok(())));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_policy_edits() -> Result<()> {
+        let store = new_store()?;
+
+        let (cmd_create, owner, owner_keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+        let mut register = Register::new(owner, name, 0, policy);
+        let _ = store.write(&cmd_create).await?;
+
+        let (writer, writer_keypair) = random_user();
+        let mut writer_replica = register.clone();
+        let denied_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+        assert!(store.write(&denied_edit).await.is_err());
+
+        // once granted permissions, the user can write to the register
+        let grant = PolicyEdit::Grant {
+            user: writer,
+            permissions: Permissions::new(true),
+        };
+        let cmd_grant = edit_register_policy(&mut register, grant, &owner_keypair)?;
+        let _ = store.write(&cmd_grant).await?;
+        let mut writer_replica = register.clone();
+        let cmd_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+        let _ = store.write(&cmd_edit).await?;
+        // made while the user is still permitted to write, but only sent once revoked
+        let stale_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+
+        // only the owner can edit the policy
+        let mut writer_replica = register.clone();
+        let revoke = PolicyOp {
+            version: 2,
+            edit: PolicyEdit::Revoke(writer),
+        };
+        writer_replica.apply_policy_op(revoke.clone(), owner)?;
+        let op = EditRegisterPolicy {
+            address: addr,
+            op: revoke,
+        };
+        let signature = writer_keypair.sign(&serialize(&op)?);
+        let forged_revoke = RegisterCmd::EditPolicy(SignedRegisterPolicyEdit {
+            op,
+            auth: ClientAuth {
+                public_key: writer_keypair.public_key(),
+                signature,
+            },
+        });
+        assert!(store.write(&forged_revoke).await.is_err());
+
+        // once the permissions are revoked, the user's new edits are rejected,
+        // even if made as per a version of the policy still permitting them
+        let cmd_revoke =
+            edit_register_policy(&mut register, PolicyEdit::Revoke(writer), &owner_keypair)?;
+        let _ = store.write(&cmd_revoke).await?;
+        assert!(store.write(&stale_edit).await.is_err());
+        // as are the edits made as per a version of the policy we don't know of
+        let mut writer_replica = register.clone();
+        writer_replica.apply_policy_op(
+            PolicyOp {
+                version: 3,
+                edit: PolicyEdit::Grant {
+                    user: writer,
+                    permissions: Permissions::new(true),
+                },
+            },
+            owner,
+        )?;
+        let future_edit = edit_register(&mut writer_replica, &writer_keypair)?;
+        assert!(store.write(&future_edit).await.is_err());
+
+        let stored = load_register(&store, &addr).await?;
+        assert_eq!(stored.policy_version(), 2);
+        assert_eq!(stored.policy(), register.policy());
+        assert_eq!(stored.size(), 1);
+
+        // A replica which received the stale edit before the permissions were revoked keeps it,
+        // each edit being checked against the version of the policy it was made as per,
+        // whatever the order the replica received the edits in, or if it received them
+        // before the register was created, the edits denied by the policy being discarded.
+        let replica_store = new_store()?;
+        let _ = replica_store
+            .update(
+                &ReplicatedRegisterLog {
+                    address: addr,
+                    op_log: vec![
+                        stale_edit,
+                        cmd_revoke,
+                        denied_edit,
+                        future_edit,
+                        cmd_edit,
+                        cmd_grant,
+                    ],
+                    snapshots: vec![],
+                },
+                &section_chain(&sk_set()),
+            )
+            .await?;
+        let _ = replica_store.write(&cmd_create).await?;
+        let replica_reg = load_register(&replica_store, &addr).await?;
+        assert_eq!(replica_reg.policy(), stored.policy());
+        assert_eq!(replica_reg.size(), 2);
+
+        // the replicas converge once replicated to one another
+        let _ = store
+            .update(
+                &replica_store.get_register_replica(&addr).await?,
+                &section_chain(&sk_set()),
+            )
+            .await?;
+        let _ = replica_store
+            .update(
+                &store.get_register_replica(&addr).await?,
+                &section_chain(&sk_set()),
+            )
+            .await?;
+        let stored = load_register(&store, &addr).await?;
+        let replica_reg = load_register(&replica_store, &addr).await?;
+        assert_eq!(stored.size(), 2);
+        assert_eq!(stored.read(), replica_reg.read());
+        assert_eq!(stored.policy(), replica_reg.policy());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_edit_predating_policy_edits() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let backend = Arc::new(FsBackend::new(tmp_dir.path()));
+        let store = RegisterStorage::new(backend.clone(), UsedSpace::default());
+
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7; 32])?;
+        let keypair = Keypair::from(secret);
+        let owner = User::Key(keypair.public_key());
+        let policy = Policy {
+            owner,
+            permissions: Default::default(),
+        };
+        let cmd_create = create_reg_w_policy(XorName([3; 32]), 15000, policy, &keypair)?;
+        let addr = cmd_create.dst_address();
+        let _ = store.write(&cmd_create).await?;
+
+        // the edit as logged by a node predating the policy edits, with bincode
+        let logged_edit = [&1_u32.to_le_bytes()[..], &hex::decode(BASELINE_EDIT)?].concat();
+        let reg_id = RegisterStore::reg_id(&addr)?;
+        let _ = backend
+            .append(Namespace::Registers, &reg_id, "baseline edit", &logged_edit)
+            .await?;
+        // it's still read, and signed as it was
+        assert!(store.verify(&addr).await?);
+        let replica = store.get_register_replica(&addr).await?;
+        let edits: Vec<_> = replica
+            .op_log
+            .iter()
+            .filter_map(|cmd| match cmd {
+                RegisterCmd::Edit(edit) => Some(edit),
+                _ => None,
+            })
+            .collect();
+        match &edits[..] {
+            [edit] => {
+                assert_eq!(edit.op.policy_version, 0);
+                assert_eq!(serialize(edit)?, hex::decode(BASELINE_EDIT)?);
+            }
+            other => bail!("Unexpected edits {other:?}"),
+        }
+
+        match store.read(&RegisterQuery::Read(addr), owner).await {
+            NodeQueryResponse::ReadRegister(Ok(entries)) => {
+                let entries: Vec<_> = entries.into_iter().map(|(_, entry)| entry).collect();
+                assert_eq!(entries, vec![b"baseline entry".to_vec()]);
+            }
+            other => bail!("Unexpected response {other:?}"),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_non_existing_permissions() -> Result<()> {
         // setup store
@@ -789,11 +1269,199 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_stored_in_kv_backend() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let backend = Arc::new(KvBackend::open(tmp_dir.path())?);
+        let store = RegisterStorage::new(backend, UsedSpace::default());
+
+        let (cmd_create, authority, keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+        let mut register = Register::new(*policy.owner(), name, 0, policy);
+
+        let _ = store.write(&cmd_create).await?;
+        let cmd_edit = edit_register(&mut register, &keypair)?;
+        let _ = store.write(&cmd_edit).await?;
+        // writing the same cmd again is a no-op
+        assert!(matches!(
+            store.write(&cmd_create).await?,
+            StorageLevel::NoChange
+        ));
+
+        assert_eq!(store.addrs().await, vec![addr]);
+        let replica = store.get_register_replica(&addr).await?;
+        assert_eq!(replica.op_log.len(), 2);
+        match store.read(&RegisterQuery::Get(addr), authority).await {
+            NodeQueryResponse::GetRegister(Ok(reg)) => {
+                assert_eq!(reg.read(), register.read(), "Should be equal!")
+            }
+            e => bail!("Could not read register! {e:?}"),
+        }
+
+        store.remove_register(&addr).await?;
+        assert!(store.addrs().await.is_empty());
+        assert_eq!(store.stored_data_size(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_snapshots() -> Result<()> {
+        let fs_dir = tempdir()?;
+        check_register_snapshots(Arc::new(FsBackend::new(fs_dir.path()))).await?;
+        let kv_dir = tempdir()?;
+        check_register_snapshots(Arc::new(KvBackend::open(kv_dir.path())?)).await
+    }
+
+    async fn check_register_snapshots(backend: Arc<dyn StorageBackend>) -> Result<()> {
+        let store = RegisterStorage::new(backend, UsedSpace::default());
+        let sk_set = sk_set();
+        let section_chain = section_chain(&sk_set);
+
+        let (cmd_create, authority, keypair, name, policy) = create_register()?;
+        let addr = cmd_create.dst_address();
+        let mut register = Register::new(*policy.owner(), name, 0, policy);
+
+        let _ = store.write(&cmd_create).await?;
+        for _ in 0..REGISTER_SNAPSHOT_THRESHOLD {
+            let cmd_edit = edit_register(&mut register, &keypair)?;
+            let _ = store.write(&cmd_edit).await?;
+        }
+        let logged_size = store.stored_data_size();
+
+        // the log grew beyond the threshold, thus it's taken for a snapshot of it to be signed
+        let logs = store.logs_to_snapshot().await;
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].op_log.len(), REGISTER_SNAPSHOT_THRESHOLD + 1);
+        assert!(store.logs_to_snapshot().await.is_empty());
+
+        // the Elders build the snapshot from the log, which is stored once signed by enough of them
+        let snapshot = store.build_snapshot(&logs[0], &section_chain)?;
+        assert_eq!(snapshot, register);
+        let signed_bytes = serialise_snapshot(&snapshot)?;
+        for index in 0..=sk_set.threshold() {
+            let sig_share = SectionSigShare::new(
+                sk_set.public_keys(),
+                index,
+                &sk_set.secret_key_share(index),
+                &signed_bytes,
+            );
+            let _ = store
+                .add_snapshot_sig_share(snapshot.clone(), sig_share, &section_chain)
+                .await?;
+        }
+
+        // the edits the snapshot was built from are no longer stored, only the create cmd is left
+        let stored_reg = store.try_load_stored_register(&addr).await?;
+        assert_eq!(stored_reg.op_log, vec![cmd_create.clone()]);
+        assert_eq!(stored_reg.snapshots.len(), 1);
+        assert_eq!(stored_reg.state.as_ref(), Some(&register));
+        assert!(store.stored_data_size() < logged_size);
+
+        // nor are they logged again
+        let snapshotted_edit = logs[0]
+            .op_log
+            .iter()
+            .find(|cmd| matches!(cmd, RegisterCmd::Edit(_)))
+            .ok_or_else(|| eyre::eyre!("No edit logged"))?;
+        assert!(matches!(
+            store.write(snapshotted_edit).await?,
+            StorageLevel::NoChange
+        ));
+
+        // further edits are applied onto the snapshot
+        let cmd_edit = edit_register(&mut register, &keypair)?;
+        let _ = store.write(&cmd_edit).await?;
+        match store.read(&RegisterQuery::Get(addr), authority).await {
+            NodeQueryResponse::GetRegister(Ok(reg)) => assert_eq!(reg, register),
+            e => bail!("Could not read register! {e:?}"),
+        }
+
+        // the snapshot is replicated along with the cmds logged since
+        let replica = store.get_register_replica(&addr).await?;
+        assert_eq!(replica.snapshots.len(), 1);
+        assert_eq!(replica.op_log.len(), 2);
+        assert!(replica.op_log.contains(&cmd_create));
+        assert!(replica.op_log.contains(&cmd_edit));
+
+        // the snapshots replicated are verified, thus those not signed by our section are
+        // discarded, as are the forged cmds
+        let mut forged_register = register.clone();
+        let mut forged_edit = edit_register(&mut forged_register, &Keypair::new_ed25519())?;
+        if let RegisterCmd::Edit(edit) = &mut forged_edit {
+            edit.auth.public_key = keypair.public_key();
+        }
+        let mut forged_snapshot = replica.snapshots[0].clone();
+        forged_snapshot.value = forged_register.clone();
+        let other_sk = bls::SecretKey::random();
+        let mut unknown_key_snapshot = forged_snapshot.clone();
+        unknown_key_snapshot.sig = SectionSig {
+            public_key: other_sk.public_key(),
+            signature: other_sk.sign(serialise_snapshot(&forged_register)?),
+        };
+        let mut tampered_replica = replica.clone();
+        tampered_replica
+            .snapshots
+            .extend([forged_snapshot, unknown_key_snapshot]);
+        tampered_replica.op_log.push(forged_edit);
+
+        let other_store = new_store()?;
+        let _ = other_store
+            .update(&tampered_replica, &section_chain)
+            .await?;
+        match other_store.read(&RegisterQuery::Get(addr), authority).await {
+            NodeQueryResponse::GetRegister(Ok(reg)) => assert_eq!(reg, register),
+            e => bail!("Could not read replicated register! {e:?}"),
+        }
+        // the cmds may be logged in another order
+        let other_replica = other_store.get_register_replica(&addr).await?;
+        assert_eq!(other_replica.snapshots, replica.snapshots);
+        assert_eq!(other_replica.op_log.len(), replica.op_log.len());
+        assert!(replica
+            .op_log
+            .iter()
+            .all(|cmd| other_replica.op_log.contains(cmd)));
+
+        store.remove_register(&addr).await?;
+        assert!(store.addrs().await.is_empty());
+        assert_eq!(store.stored_data_size(), 0);
+
+        Ok(())
+    }
+
+    // A register edit signed and serialised by a node or client predating register policy edits,
+    // the owner of the register it edits having the ed25519 secret key [7; 32].
+    const BASELINE_EDIT: &str = concat!(
+        "0303030303030303030303030303030303030303030303030303030303030303983a00000000000003030303",
+        "03030303030303030303030303030303030303030303030303030303983a0000000000000000000000000000",
+        "0e00000000000000626173656c696e6520656e74727901000000000000002000000000000000ea4a6c63e29c",
+        "520abef5507b132ec5f9954776aebebe7b92421eea691446d22c00000000002000000000000000ea4a6c63e2",
+        "9c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c000000004000000000000000a3d0142b82",
+        "39bfd3fafddd19e967602a862ac47695fdb8cea1e7425293c045c8a5ca93f775e4e7181047c2b358d87971f9",
+        "1394ef7ec8e2c5bbdf6d442f511c0e",
+    );
+
+    async fn load_register(store: &RegisterStorage, addr: &RegisterAddress) -> Result<Register> {
+        store
+            .try_load_stored_register(addr)
+            .await?
+            .state
+            .ok_or_else(|| eyre::eyre!("Register not found"))
+    }
+
+    fn sk_set() -> bls::SecretKeySet {
+        bls::SecretKeySet::random(1, &mut rand::thread_rng())
+    }
+
+    fn section_chain(sk_set: &bls::SecretKeySet) -> SectionsDAG {
+        SectionsDAG::new(sk_set.public_keys().public_key())
+    }
+
     fn new_store() -> Result<RegisterStorage> {
         let tmp_dir = tempdir()?;
         let path = tmp_dir.path();
         let used_space = UsedSpace::default();
-        let store = RegisterStorage::new(path, used_space);
+        let store = RegisterStorage::new(Arc::new(FsBackend::new(path)), used_space);
         Ok(store)
     }
 
@@ -824,6 +1492,7 @@ mod test {
         let op = EditRegister {
             address: *register.address(),
             edit,
+            policy_version: register.policy_version(),
         };
         let signature = keypair.sign(&serialize(&op)?);
 
@@ -835,4 +1504,29 @@ mod test {
             },
         }))
     }
+
+    fn edit_register_policy(
+        register: &mut Register,
+        edit: PolicyEdit,
+        keypair: &Keypair,
+    ) -> Result<RegisterCmd> {
+        let op = PolicyOp {
+            version: register.policy_version() + 1,
+            edit,
+        };
+        register.apply_policy_op(op.clone(), User::Key(keypair.public_key()))?;
+        let op = EditRegisterPolicy {
+            address: *register.address(),
+            op,
+        };
+        let signature = keypair.sign(&serialize(&op)?);
+
+        Ok(RegisterCmd::EditPolicy(SignedRegisterPolicyEdit {
+            op,
+            auth: ClientAuth {
+                public_key: keypair.public_key(),
+                signature,
+            },
+        }))
+    }
 }
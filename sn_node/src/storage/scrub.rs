@@ -0,0 +1,292 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Re-verification of the data stored, so that corrupt items are found and replaced
+//! before a client happens to request them.
+
+use super::{DataStorage, Result};
+
+use sn_interface::types::{log_markers::LogMarker, DataAddress};
+
+/// Results of the scrubbing of the data stored, since the node started.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScrubStats {
+    /// Number of times all the data stored has been verified.
+    pub completed_passes: u64,
+    /// Number of chunks and Registers verified.
+    pub verified: u64,
+    /// Number of chunks and Registers found to be corrupt, and thus quarantined.
+    pub quarantined: u64,
+}
+
+impl DataStorage {
+    /// Verifies the next `batch_size` chunks and Registers of the current scrubbing pass,
+    /// starting a new pass over all the data stored when the previous one is completed.
+    ///
+    /// Corrupt items are quarantined, and their addresses returned so that fresh copies can be
+    /// requested from the other holders. Returns `None` if a batch is already being scrubbed.
+    pub(crate) async fn scrub_next(&self, batch_size: usize) -> Option<Vec<DataAddress>> {
+        let mut queue = self.scrub_queue.try_lock().ok()?;
+        if queue.is_empty() {
+            queue.extend(
+                self.chunks
+                    .addrs()
+                    .await
+                    .into_iter()
+                    .map(DataAddress::Bytes),
+            );
+            queue.extend(
+                self.registers
+                    .addrs()
+                    .await
+                    .into_iter()
+                    .map(DataAddress::Register),
+            );
+        }
+
+        let batch_len = batch_size.min(queue.len());
+        let batch: Vec<_> = queue.drain(..batch_len).collect();
+        let mut verified = 0;
+        let mut quarantined = vec![];
+        for address in batch {
+            match self.verify(&address).await {
+                Ok(true) => verified += 1,
+                Ok(false) => {
+                    verified += 1;
+                    match self.quarantine(&address).await {
+                        Ok(true) => {
+                            info!("{:?} {address:?}", LogMarker::CorruptDataQuarantined);
+                            quarantined.push(address);
+                        }
+                        Ok(false) => {} // it was removed meanwhile
+                        Err(err) => error!("Failed to quarantine corrupt {address:?}: {err:?}"),
+                    }
+                }
+                Err(err) => warn!("Could not verify stored {address:?}: {err:?}"),
+            }
+        }
+
+        let mut stats = self
+            .scrub_stats
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        stats.verified += verified;
+        stats.quarantined += quarantined.len() as u64;
+        if queue.is_empty() {
+            stats.completed_passes += 1;
+        }
+
+        Some(quarantined)
+    }
+
+    /// Results of the scrubbing of the data stored so far.
+    pub fn scrub_stats(&self) -> ScrubStats {
+        *self
+            .scrub_stats
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    async fn verify(&self, address: &DataAddress) -> Result<bool> {
+        match address {
+            DataAddress::Bytes(addr) => self.chunks.verify(addr).await,
+            DataAddress::Register(addr) => self.registers.verify(addr).await,
+            // spentbooks are only ever checked when spent proof shares are added to them
+            _ => Ok(true),
+        }
+    }
+
+    async fn quarantine(&self, address: &DataAddress) -> Result<bool> {
+        match address {
+            DataAddress::Bytes(addr) => self.chunks.quarantine(addr).await,
+            DataAddress::Register(addr) => self.registers.quarantine(addr).await,
+            _ => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{
+        backend::{FsBackend, KvBackend, Namespace, StorageBackend, MAX_QUARANTINED},
+        register_store::RegisterStore,
+        registers::create_reg_w_policy,
+        UsedSpace,
+    };
+
+    use sn_interface::{
+        init_logger,
+        network_knowledge::SectionsDAG,
+        types::{
+            register::{Policy, User},
+            utils::{random_bytes, serialise},
+            Chunk, Keypair, RegisterCmd, ReplicatedData,
+        },
+    };
+
+    use eyre::Result;
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        path::Path,
+        sync::Arc,
+    };
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn corrupt_data_is_quarantined() -> Result<()> {
+        init_logger();
+        let fs_dir = tempdir()?;
+        check_corrupt_data_is_quarantined(fs_dir.path(), Arc::new(FsBackend::new(fs_dir.path())))
+            .await?;
+        let kv_dir = tempdir()?;
+        check_corrupt_data_is_quarantined(kv_dir.path(), Arc::new(KvBackend::open(kv_dir.path())?))
+            .await
+    }
+
+    async fn check_corrupt_data_is_quarantined(
+        path: &Path,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<()> {
+        let storage =
+            DataStorage::with_storage_backend(path, UsedSpace::default(), backend.clone());
+        // no spentbooks are stored, thus the section keys don't matter
+        let section_chain = SectionsDAG::new(bls::SecretKey::random().public_key());
+
+        let chunk = Chunk::new(random_bytes(100));
+        let _ = storage
+            .store(&ReplicatedData::Chunk(chunk.clone()), &section_chain)
+            .await?;
+        let register_cmd = new_register_cmd()?;
+        let _ = storage
+            .store(
+                &ReplicatedData::RegisterWrite(register_cmd.clone()),
+                &section_chain,
+            )
+            .await?;
+
+        // a chunk whose content doesn't match its address...
+        let corrupt_chunk = Chunk::new(random_bytes(100));
+        let _ = backend
+            .put(Namespace::Chunks, corrupt_chunk.name(), &random_bytes(100))
+            .await?;
+        // ...and a Register whose log holds a cmd with an invalid signature
+        let mut corrupt_register_cmd = new_register_cmd()?;
+        if let RegisterCmd::Create { cmd, .. } = &mut corrupt_register_cmd {
+            cmd.auth.signature = Keypair::new_ed25519().sign(b"something else");
+        }
+        let corrupt_reg_address = corrupt_register_cmd.dst_address();
+        let _ = backend
+            .append(
+                Namespace::Registers,
+                &RegisterStore::reg_id(&corrupt_reg_address)?,
+                "corrupt",
+                &serialise(&corrupt_register_cmd)?,
+            )
+            .await?;
+
+        // the first round doesn't get through all the data stored
+        let mut quarantined: BTreeSet<_> = storage
+            .scrub_next(3)
+            .await
+            .expect("no scrub round in progress")
+            .into_iter()
+            .collect();
+        assert_eq!(storage.scrub_stats().completed_passes, 0);
+        quarantined.extend(
+            storage
+                .scrub_next(3)
+                .await
+                .expect("no scrub round in progress"),
+        );
+
+        let expected = BTreeSet::from([
+            DataAddress::Bytes(*corrupt_chunk.address()),
+            DataAddress::Register(corrupt_reg_address),
+        ]);
+        assert_eq!(quarantined, expected);
+        assert_eq!(
+            storage.scrub_stats(),
+            ScrubStats {
+                completed_passes: 1,
+                verified: 4,
+                quarantined: 2,
+            }
+        );
+
+        let addrs: BTreeSet<_> = storage.data_addrs().await.into_iter().collect();
+        let intact = BTreeSet::from([
+            DataAddress::Bytes(*chunk.address()),
+            DataAddress::Register(register_cmd.dst_address()),
+        ]);
+        assert_eq!(addrs, intact);
+
+        // the next pass only finds the intact data
+        let quarantined = storage
+            .scrub_next(3)
+            .await
+            .expect("no scrub round in progress");
+        assert!(quarantined.is_empty());
+        assert_eq!(
+            storage.scrub_stats(),
+            ScrubStats {
+                completed_passes: 2,
+                verified: 6,
+                quarantined: 2,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quarantine_is_kept_within_its_max() -> Result<()> {
+        init_logger();
+        let fs_dir = tempdir()?;
+        check_quarantine_is_kept_within_its_max(Arc::new(FsBackend::new(fs_dir.path()))).await?;
+        let kv_dir = tempdir()?;
+        check_quarantine_is_kept_within_its_max(Arc::new(KvBackend::open(kv_dir.path())?)).await
+    }
+
+    async fn check_quarantine_is_kept_within_its_max(
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<()> {
+        let mut last = None;
+        for _ in 0..MAX_QUARANTINED + 1 {
+            let name = xor_name::rand::random();
+            let _ = backend
+                .put(Namespace::Chunks, &name, &random_bytes(10))
+                .await?;
+            assert!(backend
+                .quarantine(Namespace::Chunks, &name)
+                .await?
+                .is_some());
+            last = Some(name);
+        }
+
+        let quarantined = backend.quarantined(Namespace::Chunks).await?;
+        assert_eq!(quarantined.len(), MAX_QUARANTINED);
+        assert!(quarantined.iter().any(|name| Some(*name) == last));
+
+        Ok(())
+    }
+
+    fn new_register_cmd() -> Result<RegisterCmd> {
+        let keypair = Keypair::new_ed25519();
+        let policy = Policy {
+            owner: User::Key(keypair.public_key()),
+            permissions: BTreeMap::new(),
+        };
+        Ok(create_reg_w_policy(
+            xor_name::rand::random(),
+            15000,
+            policy,
+            &keypair,
+        )?)
+    }
+}
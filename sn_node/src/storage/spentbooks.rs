@@ -0,0 +1,485 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    backend::{Namespace, StorageBackend},
+    used_space::StorageLevel,
+    Error, Result, UsedSpace,
+};
+
+use sn_dbc::SpentProofShare;
+use sn_interface::{
+    messaging::system::NodeQueryResponse,
+    network_knowledge::SectionsDAG,
+    types::{
+        utils::{deserialise, serialise},
+        ReplicatedSpentbook, SpentbookAddress,
+    },
+};
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use xor_name::XorName;
+
+/// Operations over spentbooks and their storage.
+///
+/// The spentbook of a DBC is kept as a log named after its address, i.e. the hash of the
+/// DBC id, holding an entry per spent proof share logged in it. Shares are only ever appended,
+/// each as an entry identified by the hash of its content, so logging a share twice is a no-op.
+#[derive(Clone, Debug)]
+pub(super) struct SpentbookStorage {
+    backend: Arc<dyn StorageBackend>,
+    used_space: UsedSpace,
+    // Writes are serialised per spentbook, so that checking for a double spend and
+    // logging the share is done atomically for each, while writes to different ones
+    // don't wait for each other. See `SpentbookStorage::write_lock`.
+    write_locks: Arc<Mutex<BTreeMap<SpentbookAddress, Arc<Mutex<()>>>>>,
+}
+
+impl SpentbookStorage {
+    /// Creates a new `SpentbookStorage` persisted with the given backend
+    ///
+    /// If the backend already contains spentbooks, they are simply used
+    ///
+    /// Used space of the spentbooks is tracked
+    pub(super) fn new(backend: Arc<dyn StorageBackend>, used_space: UsedSpace) -> Self {
+        Self {
+            backend,
+            used_space,
+            write_locks: Arc::default(),
+        }
+    }
+
+    pub(super) async fn addrs(&self) -> Vec<SpentbookAddress> {
+        match self.backend.names(Namespace::Spentbooks).await {
+            Ok(names) => names.into_iter().map(SpentbookAddress::new).collect(),
+            Err(err) => {
+                warn!("Could not list the spentbooks stored: {err}");
+                vec![]
+            }
+        }
+    }
+
+    /// Number of spentbooks stored, as recorded by the backend.
+    #[cfg(any(test, feature = "metrics"))]
+    pub(super) fn count(&self) -> usize {
+        self.backend.count(Namespace::Spentbooks)
+    }
+
+    /// Size of all the spent proof shares stored, as accounted for in the used space.
+    pub(super) fn stored_data_size(&self) -> usize {
+        self.backend.stored_size(Namespace::Spentbooks).bytes
+    }
+
+    /// Size of the spent proof shares stored for the spentbook, as accounted for in the
+    /// used space, if we hold it.
+    pub(super) async fn data_size(&self, address: &SpentbookAddress) -> Result<Option<usize>> {
+        let size = self
+            .backend
+            .size(Namespace::Spentbooks, address.name())
+            .await?;
+        Ok(size.map(|size| size.bytes))
+    }
+
+    pub(super) async fn remove_spentbook(&self, address: &SpentbookAddress) -> Result<()> {
+        debug!("Removing spentbook, {:?}", address);
+        let _lock = self.write_lock(address).await;
+        let removed = self
+            .backend
+            .remove_log(Namespace::Spentbooks, address.name())
+            .await?
+            .ok_or(Error::SpentbookNotFound(*address))?;
+        self.used_space.decrease(removed.bytes);
+        Ok(())
+    }
+
+    /// Used for replication of data to new Adults.
+    pub(super) async fn get_spentbook(
+        &self,
+        address: &SpentbookAddress,
+    ) -> Result<ReplicatedSpentbook> {
+        let proof_shares = self.read_proof_shares(address).await?;
+        if proof_shares.is_empty() {
+            return Err(Error::SpentbookNotFound(*address));
+        }
+
+        Ok(ReplicatedSpentbook {
+            address: *address,
+            proof_shares,
+        })
+    }
+
+    // Read the spent proof shares of a spentbook and return NodeQueryResponse.
+    // A spentbook with no shares logged is equivalent to the DBC not being spent.
+    pub(super) async fn get(&self, address: &SpentbookAddress) -> NodeQueryResponse {
+        trace!("Getting spent proof shares from spentbook {address:?}");
+        NodeQueryResponse::GetSpentProofShares(
+            self.read_proof_shares(address)
+                .await
+                .map_err(|error| error.into()),
+        )
+    }
+
+    /// Log a spent proof share in the spentbook of the DBC it was spent for.
+    ///
+    /// The share is rejected if it's not signed by a key set of our section chain, or if the DBC
+    /// was already logged as spent in a different transaction.
+    pub(super) async fn write(
+        &self,
+        share: &SpentProofShare,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        let address = spentbook_address(share);
+        let _lock = self.write_lock(&address).await;
+        self.try_to_log_share(&address, share, section_chain).await
+    }
+
+    /// Update our spentbook replica on receiving data from other nodes.
+    ///
+    /// Invalid or conflicting shares are discarded, keeping the spend already logged, if any.
+    pub(super) async fn update(
+        &self,
+        data: &ReplicatedSpentbook,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        trace!("Updating spentbook store: {:?}", data.address);
+        let _lock = self.write_lock(&data.address).await;
+
+        let mut storage_level = StorageLevel::NoChange;
+        for share in &data.proof_shares {
+            if spentbook_address(share) != data.address {
+                warn!(
+                    "Discarding spent proof share not belonging to spentbook {:?}: {share:?}",
+                    data.address
+                );
+                continue;
+            }
+
+            match self
+                .try_to_log_share(&data.address, share, section_chain)
+                .await
+            {
+                Ok(level @ StorageLevel::Updated(_)) => storage_level = level,
+                Ok(StorageLevel::NoChange) => {}
+                Err(err @ Error::NotEnoughSpace) => return Err(err),
+                Err(err) => warn!(
+                    "Discarding replicated spent proof share for {:?}: {err:?}",
+                    data.address
+                ),
+            }
+        }
+
+        Ok(storage_level)
+    }
+
+    // Locks the spentbook for writing to it, once no other write to it is in progress.
+    // The locks which are no longer held nor awaited are dropped meanwhile, for them
+    // not to pile up, one for each spentbook ever written to.
+    async fn write_lock(&self, address: &SpentbookAddress) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.write_locks.lock().await;
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(*address).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+
+    // Verifies the share and that it doesn't conflict with those already logged before
+    // appending it to the spentbook. Must be called while holding its write lock.
+    async fn try_to_log_share(
+        &self,
+        address: &SpentbookAddress,
+        share: &SpentProofShare,
+        section_chain: &SectionsDAG,
+    ) -> Result<StorageLevel> {
+        // A valid signature share only proves the share was signed with the key set it carries,
+        // which must then be that of our section, current or past, for the spend to be genuine.
+        let section_key = share.spentbook_pks().public_key();
+        if !section_chain.has_key(&section_key) {
+            warn!("Spent proof share for spentbook {address:?} signed with unknown section key {section_key:?}");
+            return Err(Error::SpentProofShareUnknownSectionKey(*address));
+        }
+
+        let (index, sig_share) = share.spentbook_sig_share().threshold_crypto();
+        if !share
+            .spentbook_pks()
+            .public_key_share(index)
+            .verify(sig_share, share.content.hash().as_ref())
+        {
+            return Err(Error::InvalidSpentProofShare(*address));
+        }
+
+        let existing = self.read_proof_shares(address).await?;
+        if existing
+            .iter()
+            .any(|logged| logged.transaction_hash() != share.transaction_hash())
+        {
+            warn!("Double spend attempt detected for spentbook {address:?}: {share:?}");
+            return Err(Error::DoubleSpendAttempt(*address));
+        }
+
+        let bytes = serialise(share)?;
+        let entry_id = hex::encode(XorName::from_content(&bytes));
+        if self
+            .backend
+            .contains_entry(Namespace::Spentbooks, address.name(), &entry_id)
+            .await?
+        {
+            trace!("Spent proof share already logged in spentbook {address:?}");
+            return Ok(StorageLevel::NoChange);
+        }
+
+        if !self.used_space.can_add(bytes.len()) {
+            return Err(Error::NotEnoughSpace);
+        }
+
+        // writes are serialised by the lock held, thus the entry can't have been appended since
+        let _ = self
+            .backend
+            .append(Namespace::Spentbooks, address.name(), &entry_id, &bytes)
+            .await?;
+
+        let storage_level = self.used_space.increase(bytes.len());
+        trace!("Spent proof share logged in spentbook {address:?}");
+
+        Ok(storage_level)
+    }
+
+    async fn read_proof_shares(&self, address: &SpentbookAddress) -> Result<Vec<SpentProofShare>> {
+        let mut proof_shares = vec![];
+        for bytes in self
+            .backend
+            .read_log(Namespace::Spentbooks, address.name())
+            .await?
+        {
+            // Shares are verified before being written, thus a share
+            // which can't be read back means the store is corrupted.
+            let share = deserialise::<SpentProofShare>(&bytes).map_err(|err| {
+                error!("Corrupted spent proof share found in spentbook {address:?}: {err:?}");
+                Error::CorruptedSpentbook(*address)
+            })?;
+            proof_shares.push(share);
+        }
+
+        Ok(proof_shares)
+    }
+}
+
+impl Display for SpentbookStorage {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "SpentbookStorage")
+    }
+}
+
+// Address of the spentbook of the DBC the share was spent for.
+pub(super) fn spentbook_address(share: &SpentProofShare) -> SpentbookAddress {
+    SpentbookAddress::new(XorName::from_content(&share.public_key().to_bytes()))
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::{spentbook_address, SpentbookStorage};
+    use crate::storage::{
+        backend::{FsBackend, KvBackend, StorageBackend},
+        Error, StorageLevel, UsedSpace,
+    };
+
+    use sn_dbc::{
+        Hash, IndexedSignatureShare, PedersenGens, RevealedAmount, SpentProofContent,
+        SpentProofShare,
+    };
+    use sn_interface::{
+        messaging::system::NodeQueryResponse, network_knowledge::SectionsDAG,
+        types::ReplicatedSpentbook,
+    };
+
+    use eyre::{bail, Result};
+    use std::{path::Path, sync::Arc, time::Duration};
+    use tempfile::tempdir;
+    use tokio::time::timeout;
+
+    // Builds the share of each of the given Elders of the spend of a DBC in the given transaction
+    pub(crate) fn spent_proof_shares(
+        dbc_id: bls::PublicKey,
+        tx_hash: Hash,
+        sk_set: &bls::SecretKeySet,
+        elders: usize,
+    ) -> Vec<SpentProofShare> {
+        let revealed_amount = RevealedAmount::from_amount(100, rand::thread_rng());
+        let content = SpentProofContent {
+            public_key: dbc_id,
+            transaction_hash: tx_hash,
+            reason: Hash::default(),
+            blinded_amount: revealed_amount.blinded_amount(&PedersenGens::default()),
+        };
+        (0..elders)
+            .map(|index| SpentProofShare {
+                content: content.clone(),
+                spentbook_pks: sk_set.public_keys(),
+                spentbook_sig_share: IndexedSignatureShare::new(
+                    index as u64,
+                    sk_set.secret_key_share(index).sign(content.hash().as_ref()),
+                ),
+            })
+            .collect()
+    }
+
+    fn section_chain(sk_set: &bls::SecretKeySet) -> SectionsDAG {
+        SectionsDAG::new(sk_set.public_keys().public_key())
+    }
+
+    fn new_storage(root: &Path) -> SpentbookStorage {
+        SpentbookStorage::new(Arc::new(FsBackend::new(root)), UsedSpace::default())
+    }
+
+    #[tokio::test]
+    async fn spent_proof_shares_are_appended_to_spentbook() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let backends: [Arc<dyn StorageBackend>; 2] = [
+            Arc::new(FsBackend::new(tmp_dir.path())),
+            Arc::new(KvBackend::open(tmp_dir.path())?),
+        ];
+        for backend in backends {
+            let storage = SpentbookStorage::new(backend, UsedSpace::default());
+            check_spent_proof_shares_are_appended(&storage).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_spent_proof_shares_are_appended(storage: &SpentbookStorage) -> Result<()> {
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let dbc_id = bls::SecretKey::random().public_key();
+        let shares = spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 3);
+        let address = spentbook_address(&shares[0]);
+
+        assert_eq!(
+            storage.get(&address).await,
+            NodeQueryResponse::GetSpentProofShares(Ok(vec![]))
+        );
+
+        for share in &shares {
+            let _ = storage.write(share, &section_chain(&sk_set)).await?;
+        }
+        // logging the same share again is a no-op
+        assert!(matches!(
+            storage.write(&shares[0], &section_chain(&sk_set)).await?,
+            StorageLevel::NoChange
+        ));
+
+        let spentbook = storage.get_spentbook(&address).await?;
+        assert_eq!(spentbook.proof_shares.len(), 3);
+        for share in &shares {
+            assert!(spentbook.proof_shares.contains(share));
+        }
+        assert_eq!(storage.addrs().await, vec![address]);
+
+        storage.remove_spentbook(&address).await?;
+        assert!(storage.addrs().await.is_empty());
+        assert!(matches!(
+            storage.get_spentbook(&address).await,
+            Err(Error::SpentbookNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn double_spend_attempts_are_rejected() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let storage = new_storage(tmp_dir.path());
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let dbc_id = bls::SecretKey::random().public_key();
+        let spent = spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 1);
+        let double_spent = spent_proof_shares(dbc_id, Hash::hash(b"other tx"), &sk_set, 2);
+        let address = spentbook_address(&spent[0]);
+
+        let section_chain = section_chain(&sk_set);
+        let _ = storage.write(&spent[0], &section_chain).await?;
+        match storage.write(&double_spent[0], &section_chain).await {
+            Err(Error::DoubleSpendAttempt(addr)) => assert_eq!(addr, address),
+            other => bail!("Double spend attempt was not rejected: {other:?}"),
+        }
+
+        // conflicting shares received from other nodes are discarded as well
+        let _ = storage
+            .update(
+                &ReplicatedSpentbook {
+                    address,
+                    proof_shares: double_spent,
+                },
+                &section_chain,
+            )
+            .await?;
+        assert_eq!(storage.get_spentbook(&address).await?.proof_shares, spent);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spent_proof_shares_with_invalid_signature_are_rejected() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let storage = new_storage(tmp_dir.path());
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let dbc_id = bls::SecretKey::random().public_key();
+        let mut shares = spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 2);
+        // the signature share of another Elder, claimed to be that of the first one
+        let (_, other_sig_share) = shares[1].spentbook_sig_share.threshold_crypto();
+        shares[0].spentbook_sig_share = IndexedSignatureShare::new(0, other_sig_share.clone());
+
+        match storage.write(&shares[0], &section_chain(&sk_set)).await {
+            Err(Error::InvalidSpentProofShare(_)) => Ok(()),
+            other => bail!("Share with invalid signature was not rejected: {other:?}"),
+        }
+    }
+    #[tokio::test]
+    async fn spentbooks_are_written_to_independently() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let storage = new_storage(tmp_dir.path());
+        let sk_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        let section_chain = section_chain(&sk_set);
+        let shares = |dbc_id| spent_proof_shares(dbc_id, Hash::hash(b"tx"), &sk_set, 1);
+        let share = shares(bls::SecretKey::random().public_key()).remove(0);
+        let other_share = shares(bls::SecretKey::random().public_key()).remove(0);
+
+        // a write to another spentbook isn't held up by the one in progress
+        let lock = storage.write_lock(&spentbook_address(&share)).await;
+        let write = storage.write(&other_share, &section_chain);
+        let _ = timeout(Duration::from_secs(1), write).await??;
+        assert_eq!(
+            storage
+                .get_spentbook(&spentbook_address(&other_share))
+                .await?
+                .proof_shares,
+            vec![other_share]
+        );
+
+        // while one to the same spentbook waits for it
+        let write = storage.write(&share, &section_chain);
+        assert!(timeout(Duration::from_millis(100), write).await.is_err());
+        drop(lock);
+        let _ = storage.write(&share, &section_chain).await?;
+        assert_eq!(
+            storage
+                .get_spentbook(&spentbook_address(&share))
+                .await?
+                .proof_shares,
+            vec![share.clone()]
+        );
+
+        // and the locks are dropped once released
+        let _lock = storage.write_lock(&spentbook_address(&share)).await;
+        assert_eq!(storage.write_locks.lock().await.len(), 1);
+
+        Ok(())
+    }
+}
@@ -7,12 +7,21 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use sn_interface::network_knowledge::recommended_section_size;
-use std::sync::{
-    atomic::{AtomicU8, AtomicUsize, Ordering},
-    Arc,
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 use tracing::info;
 
+// Name of the file, at the root of the storage dir, the used space is persisted to
+// when the node shuts down cleanly. It's removed while the node is running.
+const USED_SPACE_SUMMARY_FILENAME: &str = "used_space";
+
 /// The StorageLevel is an n-level scale of used space,
 /// where each level represents a x% increase of usage.
 ///
@@ -50,6 +59,11 @@ pub struct UsedSpace {
     last_seen_level: Arc<AtomicU8>,
     /// Counts the number of bytes stored to disk.
     used_space: Arc<AtomicUsize>,
+    /// File the used space is persisted to on a clean shutdown, so that it doesn't have to be
+    /// counted again from the stored data when the node restarts. Not set until restored.
+    summary_path: Arc<Mutex<Option<PathBuf>>>,
+    /// Whether the summary persisted is up to date, i.e. nothing changed since it was written.
+    persisted: Arc<AtomicBool>,
 }
 
 impl UsedSpace {
@@ -60,6 +74,61 @@ impl UsedSpace {
             max_capacity,
             used_space: Arc::new(AtomicUsize::new(0)),
             last_seen_level: Arc::new(AtomicU8::new(0)),
+            summary_path: Arc::new(Mutex::new(None)),
+            persisted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Restores the used space of the data stored at the given root dir, as persisted to its
+    /// summary, or else as counted by the given function.
+    ///
+    /// The summary is only written when the node shuts down cleanly, see [`Self::persist`], and
+    /// removed once read, so its presence marks a clean shutdown. After an unclean one, the
+    /// used space is thus counted again from the data stored. The summary also has to be removed
+    /// for the used space to be counted again, e.g. after stored data was removed manually.
+    pub(crate) fn restore(&self, root: &Path, count: impl FnOnce() -> usize) {
+        let summary_path = root.join(USED_SPACE_SUMMARY_FILENAME);
+        let used_space = match read_summary(&summary_path) {
+            Some(used_space) => {
+                // the summary won't be accurate anymore as soon as data is stored or removed
+                if let Err(error) = fs::remove_file(&summary_path) {
+                    warn!("Failed to remove used space summary at {summary_path:?}: {error}");
+                }
+                used_space
+            }
+            None => {
+                info!("Counting the space used by the data stored at {root:?}");
+                count()
+            }
+        };
+        info!("Used space restored: {used_space}");
+
+        self.used_space.store(used_space, Ordering::Relaxed);
+        // the levels passed before the restart are not reported again
+        let used_space_ratio = used_space as f64 / self.min_capacity as f64;
+        self.last_seen_level
+            .store(to_storage_level(used_space_ratio), Ordering::SeqCst);
+
+        *lock(&self.summary_path) = Some(summary_path);
+    }
+
+    /// Persists the current used space to the summary, if it was restored already, for it to be
+    /// restored when the node restarts. To be called when the node shuts down cleanly.
+    ///
+    /// Should the used space change afterwards, the summary is removed again.
+    pub(crate) fn persist(&self) {
+        // The value is read while holding the lock, so that the summary can't be
+        // removed due to a concurrent change before it's flagged as persisted.
+        let summary_path = lock(&self.summary_path);
+        if let Some(path) = summary_path.as_ref() {
+            let used_space = self.used_space.load(Ordering::Relaxed) as u64;
+            match write_summary(path, used_space) {
+                Ok(()) => {
+                    self.persisted.store(true, Ordering::SeqCst);
+                    info!("Used space persisted: {used_space}");
+                }
+                Err(error) => warn!("Failed to persist used space to {path:?}: {error}"),
+            }
         }
     }
 
@@ -70,6 +139,7 @@ impl UsedSpace {
     /// and then rises again, we don't report the same passing a second time.
     pub(crate) fn increase(&self, size: usize) -> StorageLevel {
         let _ = self.used_space.fetch_add(size, Ordering::Relaxed);
+        self.invalidate_summary();
         let used_space = self.used_space.load(Ordering::Relaxed);
         let used_space_ratio = used_space as f64 / self.min_capacity as f64;
         let current_level = to_storage_level(used_space_ratio);
@@ -103,7 +173,13 @@ impl UsedSpace {
     }
 
     pub(crate) fn decrease(&self, size: usize) {
-        let _ = self.used_space.fetch_sub(size, Ordering::Relaxed);
+        // the used space restored may be lower than the size of the data stored
+        let _ = self
+            .used_space
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used_space| {
+                Some(used_space.saturating_sub(size))
+            });
+        self.invalidate_summary();
     }
 
     /// This prevents the node to fill up actual disk space
@@ -124,6 +200,51 @@ impl UsedSpace {
         let min_capacity = self.min_capacity;
         used as f64 / min_capacity as f64
     }
+
+    // Removes the summary if it was persisted, as it no longer matches the used space.
+    // This only touches the disk on the first change following a call to `persist`.
+    fn invalidate_summary(&self) {
+        if !self.persisted.load(Ordering::SeqCst) {
+            return;
+        }
+        let summary_path = lock(&self.summary_path);
+        if self.persisted.swap(false, Ordering::SeqCst) {
+            if let Some(path) = summary_path.as_ref() {
+                if let Err(error) = fs::remove_file(path) {
+                    warn!("Failed to remove outdated used space summary at {path:?}: {error}");
+                }
+            }
+        }
+    }
+}
+
+fn read_summary(path: &Path) -> Option<usize> {
+    let bytes = fs::read(path).ok()?;
+    let used_space = u64::from_le_bytes(bytes.try_into().ok()?);
+    Some(used_space as usize)
+}
+
+// Writes to a temporary file first, synced before it replaces the summary, so that
+// an interruption while writing it doesn't leave a corrupted summary behind.
+fn write_summary(path: &Path, used_space: u64) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&used_space.to_le_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    // the rename itself is only durable once the dir is synced
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+// The lock is only held while persisting or removing the summary,
+// thus a panic while doing so doesn't leave it inconsistent.
+fn lock(summary_path: &Mutex<Option<PathBuf>>) -> std::sync::MutexGuard<'_, Option<PathBuf>> {
+    summary_path
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 /// We expect it to return a value between 0-(100 / recommended_section_size),
@@ -142,6 +263,8 @@ impl Default for UsedSpace {
             max_capacity: DEFAULT_MAX_CAPACITY,
             used_space: Arc::new(AtomicUsize::new(0)),
             last_seen_level: Arc::new(AtomicU8::new(0)),
+            summary_path: Arc::new(Mutex::new(None)),
+            persisted: Arc::new(AtomicBool::new(false)),
         }
     }
 }